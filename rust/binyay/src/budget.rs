@@ -0,0 +1,140 @@
+//! `yay --timeout SECS` and `yay --max-memory SIZE`: limits on how long a
+//! conversion may run and how much memory it may take.
+//!
+//! Both are enforced around the library rather than inside it, so a
+//! conversion that goes over either one, in any format and at any stage,
+//! stops the process with a message and exit code 3 instead of hanging or
+//! running the machine out of memory. Memory is counted by the global
+//! allocator, which tracks the bytes the program has allocated and not yet
+//! freed; the time by a thread that wakes when it runs out.
+//!
+//! Until a limit is set the allocator counts nothing, so a run without
+//! `--max-memory` pays only for checking that there is no limit. Output is
+//! written under [`writing`], and a timeout waits for a write in progress
+//! to finish, so it never leaves an output file half written.
+//!
+//! With `--stream`, `--max-memory` is instead the budget the streaming
+//! encoder plans within (see `stream`), and is not a hard limit.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::process;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::thread;
+use std::time::Duration;
+
+/// The exit code when a conversion goes over a limit.
+pub const EXIT_LIMIT: i32 = 3;
+
+/// The system allocator, counting the bytes in use and stopping the
+/// process when they would pass the limit.
+pub struct Counting;
+
+/// The most bytes that may be in use, or 0 for no limit.
+static LIMIT: AtomicUsize = AtomicUsize::new(0);
+/// The bytes allocated and not yet freed.
+static USED: AtomicUsize = AtomicUsize::new(0);
+/// Whether the limit has been passed, so that reporting it allocates
+/// without reporting it again.
+static EXCEEDED: AtomicBool = AtomicBool::new(false);
+/// Held while output is written, and by a timeout before it stops the
+/// process.
+static WRITING: Mutex<()> = Mutex::new(());
+
+/// Stop the process once more than `bytes` are in use.
+pub fn limit_memory(bytes: usize) {
+    LIMIT.store(bytes, Ordering::Relaxed);
+}
+
+/// Stop the process once it has run for `timeout`, after any output being
+/// written is complete.
+pub fn limit_time(timeout: Duration) {
+    thread::spawn(move || {
+        thread::sleep(timeout);
+        // Never released: the process ends while this is held.
+        let _writing = writing();
+        eprintln!(
+            "Error: Gave up after the --timeout of {} seconds",
+            timeout.as_secs_f64()
+        );
+        process::exit(EXIT_LIMIT);
+    });
+}
+
+/// Hold this while writing output, so that `--timeout` cannot stop the
+/// process partway through.
+pub fn writing() -> MutexGuard<'static, ()> {
+    WRITING.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl Counting {
+    /// Count `size` more bytes in use, or stop the process if that would
+    /// pass the limit.
+    fn reserve(&self, size: usize) {
+        let limit = LIMIT.load(Ordering::Relaxed);
+        if limit == 0 {
+            return;
+        }
+        let used = USED.fetch_add(size, Ordering::Relaxed) + size;
+        if used > limit && !EXCEEDED.swap(true, Ordering::Relaxed) {
+            // Lift the limit, so that writing the message can allocate.
+            LIMIT.store(0, Ordering::Relaxed);
+            eprintln!(
+                "Error: Gave up at the --max-memory of {} bytes (needed {} more)",
+                limit, size
+            );
+            process::exit(EXIT_LIMIT);
+        }
+    }
+
+    /// Count `size` fewer bytes in use. Bytes allocated before the limit
+    /// was set were never counted, so the count stops at zero.
+    fn release(&self, size: usize) {
+        if LIMIT.load(Ordering::Relaxed) == 0 {
+            return;
+        }
+        let _ = USED.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |used| {
+            Some(used.saturating_sub(size))
+        });
+    }
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        self.reserve(layout.size());
+        let ptr = System.alloc(layout);
+        if ptr.is_null() {
+            self.release(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        self.reserve(layout.size());
+        let ptr = System.alloc_zeroed(layout);
+        if ptr.is_null() {
+            self.release(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        self.release(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        if new_size > layout.size() {
+            self.reserve(new_size - layout.size());
+        }
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if new_ptr.is_null() {
+            if new_size > layout.size() {
+                self.release(new_size - layout.size());
+            }
+        } else if new_size < layout.size() {
+            self.release(layout.size() - new_size);
+        }
+        new_ptr
+    }
+}
@@ -0,0 +1,258 @@
+//! `yay batch [--fail-fast] MANIFEST`
+//!
+//! Runs every conversion a manifest lists in one process, in place of a
+//! build script that runs `yay` once for each file:
+//!
+//! ```yay
+//! - from: "config/app.yaml"
+//!   out: "build/app.yay"
+//! - from: "config/app.yaml"
+//!   to_fmt: "json"
+//!   out: "build/app.json.gz"
+//! - {from: "data/points.cbor", from_fmt: "cbor", out: "build/points.go"}
+//! ```
+//!
+//! `from` and `out` are paths relative to the manifest. `from_fmt` is
+//! inferred from the input's extension when not given, and `to_fmt` from
+//! the output's; an output ending in `.gz` or `.zst` is compressed. Each
+//! input is read once however many outputs it has, and conversions run in
+//! parallel as directory mode's files do, with the same summary at the
+//! end. An output whose contents would not change is not rewritten, so
+//! its modification time stays for tools that go by it.
+
+use libyay::compress::Compression;
+use libyay::{Format, Value};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use super::{convert, infer_format, input_format, read_value};
+use crate::{config, pipeline};
+
+/// One conversion from the manifest.
+#[derive(Debug, PartialEq)]
+struct Entry {
+    from: String,
+    from_fmt: &'static str,
+    to_fmt: Format,
+    out: String,
+}
+
+pub fn run(args: &[String]) -> i32 {
+    let mut fail_fast = false;
+    let mut manifest: Option<&str> = None;
+    for arg in args {
+        match arg.as_str() {
+            "--fail-fast" => fail_fast = true,
+            arg if arg.starts_with('-') => {
+                eprintln!("Error: Unknown option: {}", arg);
+                return 1;
+            }
+            arg if manifest.is_some() => {
+                eprintln!("Error: Unexpected argument: {}", arg);
+                return 1;
+            }
+            arg => manifest = Some(arg),
+        }
+    }
+    let Some(manifest) = manifest else {
+        eprintln!("Error: yay batch requires a manifest file");
+        return 1;
+    };
+
+    let entries = match read_value(manifest, None)
+        .and_then(|value| entries(&value, Path::new(manifest).parent()))
+    {
+        Ok(entries) => entries,
+        Err(e) => {
+            eprintln!("{}: {}", manifest, e);
+            return 1;
+        }
+    };
+
+    // Each input is read once, by the first conversion to need it.
+    type Read = Arc<OnceLock<Result<Value, String>>>;
+    let inputs: Mutex<HashMap<(&str, &str), Read>> = Mutex::new(HashMap::new());
+    let by_out: HashMap<&str, &Entry> = entries.iter().map(|e| (e.out.as_str(), e)).collect();
+    let outs: Vec<String> = entries.iter().map(|e| e.out.clone()).collect();
+
+    let summary = pipeline::run(&outs, pipeline::default_jobs(), fail_fast, |out| {
+        let entry = by_out[out];
+        let input = {
+            let mut inputs = inputs.lock().unwrap_or_else(|e| e.into_inner());
+            inputs
+                .entry((entry.from.as_str(), entry.from_fmt))
+                .or_default()
+                .clone()
+        };
+        let value = match input.get_or_init(|| read_value(&entry.from, Some(entry.from_fmt))) {
+            Ok(value) => value,
+            Err(e) => {
+                eprintln!("{}", e);
+                return pipeline::Outcome::Failed;
+            }
+        };
+        match convert(value, entry.to_fmt).and_then(|output| write(out, output)) {
+            Ok(true) => pipeline::Outcome::Reformatted,
+            Ok(false) => pipeline::Outcome::Unchanged,
+            Err(e) => {
+                eprintln!("{}: {}", out, e);
+                pipeline::Outcome::Failed
+            }
+        }
+    });
+
+    eprintln!("{}", summary);
+    if summary.success() {
+        0
+    } else {
+        1
+    }
+}
+
+/// The conversions a manifest lists, with paths resolved against `base`.
+fn entries(manifest: &Value, base: Option<&Path>) -> Result<Vec<Entry>, String> {
+    let Value::Array(items) = manifest else {
+        return Err("the manifest must be an array of conversions".to_string());
+    };
+    let resolve = |path: &str| match base {
+        Some(base) => base.join(path).to_string_lossy().to_string(),
+        None => path.to_string(),
+    };
+    let mut entries = Vec::new();
+    let mut outs = HashSet::new();
+    for (i, item) in items.iter().enumerate() {
+        let at = |message: String| format!("conversion {}: {}", i + 1, message);
+        let Value::Object(fields) = item else {
+            return Err(at("expected an object".to_string()));
+        };
+        let mut from = None;
+        let mut from_fmt = None;
+        let mut to_fmt = None;
+        let mut out = None;
+        for (key, value) in fields {
+            let Value::String(text) = value else {
+                return Err(at(format!("{} must be a string", key)));
+            };
+            match key.as_str() {
+                "from" => from = Some(resolve(text)),
+                "out" => out = Some(resolve(text)),
+                "from_fmt" => {
+                    from_fmt = Some(
+                        input_format(text)
+                            .ok_or_else(|| at(format!("unknown input format: {}", text)))?,
+                    )
+                }
+                "to_fmt" => {
+                    to_fmt = Some(
+                        crate::format_named(text)
+                            .ok_or_else(|| at(format!("unknown output format: {}", text)))?,
+                    )
+                }
+                _ => return Err(at(format!("unknown field: {}", key))),
+            }
+        }
+        let from = from.ok_or_else(|| at("missing from".to_string()))?;
+        let out = out.ok_or_else(|| at("missing out".to_string()))?;
+        let to_fmt = match to_fmt {
+            Some(format) => format,
+            None => output_format(&out)
+                .ok_or_else(|| at(format!("no output format for {}; give to_fmt", out)))?,
+        };
+        if !outs.insert(out.clone()) {
+            return Err(at(format!("{} is already an output", out)));
+        }
+        entries.push(Entry {
+            from_fmt: from_fmt.unwrap_or_else(|| infer_format(&from)),
+            from,
+            to_fmt,
+            out,
+        });
+    }
+    Ok(entries)
+}
+
+/// The output format for a file named `out`, by its extension after any
+/// `.gz` or `.zst`.
+fn output_format(out: &str) -> Option<Format> {
+    let extension = super::strip_compression(Path::new(out))
+        .extension()?
+        .to_str()?
+        .to_string();
+    config::get()
+        .output_format(&extension)
+        .or_else(|| crate::format_named(&extension))
+}
+
+/// Write `output` to `out`, compressed as its name says, creating its
+/// directory. Returns whether the file changed.
+fn write(out: &str, output: Vec<u8>) -> Result<bool, String> {
+    let path = PathBuf::from(out);
+    let output = match Compression::from_path(&path) {
+        Compression::None => output,
+        compression => compression.compress(&output),
+    };
+    if fs::read(&path).is_ok_and(|before| before == output) {
+        return Ok(false);
+    }
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).map_err(|e| format!("Error creating {}: {}", dir.display(), e))?;
+    }
+    fs::write(&path, output).map_err(|e| format!("Error writing {}: {}", out, e))?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libyay::parse;
+
+    #[test]
+    fn test_entries() {
+        let manifest = parse(concat!(
+            "- from: \"a.yaml\"\n",
+            "  out: \"build/a.yay\"\n",
+            "- {from: \"a.yaml\", to_fmt: \"json\", out: \"build/a.json.gz\"}\n",
+            "- {from: \"b.cfg\", from_fmt: \"toml\", out: \"b.go\"}\n",
+        ))
+        .unwrap();
+        let entries = entries(&manifest, Some(Path::new("conf"))).unwrap();
+        let entry = |from: &str, from_fmt, to_fmt, out: &str| Entry {
+            from: from.to_string(),
+            from_fmt,
+            to_fmt,
+            out: out.to_string(),
+        };
+        assert_eq!(
+            entries,
+            vec![
+                entry("conf/a.yaml", "yaml", Format::Yay, "conf/build/a.yay"),
+                entry("conf/a.yaml", "yaml", Format::Json, "conf/build/a.json.gz"),
+                entry("conf/b.cfg", "toml", Format::Go, "conf/b.go"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_entry_errors() {
+        let error = |source: &str| entries(&parse(source).unwrap(), None).unwrap_err();
+        assert_eq!(
+            error("a: 1"),
+            "the manifest must be an array of conversions"
+        );
+        assert_eq!(error("- {out: \"a.yay\"}"), "conversion 1: missing from");
+        assert_eq!(
+            error("- {from: \"a.yay\", out: \"a.out\"}"),
+            "conversion 1: no output format for a.out; give to_fmt"
+        );
+        assert_eq!(
+            error("- {from: \"a.yay\", out: \"b.json\"}\n- {from: \"c.yay\", out: \"b.json\"}"),
+            "conversion 2: b.json is already an output"
+        );
+        assert_eq!(
+            error("- {from: \"a.yay\", out: \"b.json\", mode: \"fast\"}"),
+            "conversion 1: unknown field: mode"
+        );
+    }
+}
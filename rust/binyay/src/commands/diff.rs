@@ -0,0 +1,60 @@
+//! `yay diff [-f FORMAT] OLD [-f FORMAT] NEW`
+//!
+//! Compares two documents value by value and prints each added, removed,
+//! or changed path, one per line, with the values in inline YAY: a review
+//! of what changed between generated snapshots without the noise of a
+//! textual diff. Exits 1 if the documents differ, like `diff`.
+//!
+//! Each input's format is inferred from its extension, or given by a `-f`
+//! before it, as `yay merge` takes them.
+
+use libyay::diff;
+
+use super::{format_arg, read_value};
+
+pub fn run(args: &[String]) -> i32 {
+    let mut inputs: Vec<(&str, Option<&str>)> = Vec::new();
+    let mut from_format = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-f" | "--from" => match format_arg(args.next()) {
+                Ok(format) => from_format = Some(format),
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    return 2;
+                }
+            },
+            arg if arg.starts_with('-') => {
+                eprintln!("Error: Unknown option: {}", arg);
+                return 2;
+            }
+            arg => inputs.push((arg, from_format.take())),
+        }
+    }
+    if from_format.is_some() {
+        eprintln!("Error: -f must come before the input it gives the format of");
+        return 2;
+    }
+    let [old, new] = inputs[..] else {
+        eprintln!("Error: yay diff requires OLD and NEW files");
+        return 2;
+    };
+
+    let mut values = Vec::new();
+    for (path, format) in [old, new] {
+        match read_value(path, format) {
+            Ok(value) => values.push(value),
+            Err(e) => {
+                eprintln!("{}", e);
+                return 2;
+            }
+        }
+    }
+
+    let changes = diff(&values[0], &values[1]);
+    for change in &changes {
+        println!("{}", change);
+    }
+    i32::from(!changes.is_empty())
+}
@@ -0,0 +1,338 @@
+//! `yay doctor`
+//!
+//! Runs a built-in suite of representative documents through every parse,
+//! encode, and transcode path in this build and prints what each path did
+//! with each document, for telling apart a broken installation from a
+//! missing feature. A cell is one of:
+//!
+//! - `ok`: the document came back unchanged, or for formats this tool
+//!   only writes, encoded without error.
+//! - `lossy`: the format has no exact form for a type in the document, like
+//!   a date and time in JSON, and the loss was the expected one.
+//! - `-`: the format cannot hold the document, and says so.
+//! - `FAIL`: anything else. `yay doctor` then exits 1.
+
+use libyay::compress::{decompress, Compression};
+use libyay::{
+    encode, encode_to, parse, parse_bytes, parse_reader, parse_yson, DateTime, Format, Map,
+    ParseOptions, PushParser, Value,
+};
+use num_bigint::BigInt;
+use std::panic::{self, AssertUnwindSafe};
+
+use crate::transcode;
+
+/// What a path did with one document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Outcome {
+    Ok,
+    Lossy,
+    Unsupported,
+    Fail,
+}
+
+impl Outcome {
+    fn label(self) -> &'static str {
+        match self {
+            Outcome::Ok => "ok",
+            Outcome::Lossy => "lossy",
+            Outcome::Unsupported => "-",
+            Outcome::Fail => "FAIL",
+        }
+    }
+}
+
+/// A document of the suite, and the paths that are expected to lose
+/// something of it.
+struct Sample {
+    name: &'static str,
+    value: Value,
+    lossy: &'static [&'static str],
+}
+
+/// A parse, encode, or transcode path, named as `-f` and `-t` name formats.
+type Check = fn(&Value) -> Result<Option<Value>, String>;
+
+/// The paths, each of which returns the document read back, or `None` for
+/// formats that are only written.
+const PATHS: &[(&str, Check)] = &[
+    ("yay", |v| {
+        parse(&encode(v, Format::Yay))
+            .map(Some)
+            .map_err(|e| e.to_string())
+    }),
+    ("yay bytes", |v| {
+        parse_bytes(encode(v, Format::Yay).as_bytes(), &ParseOptions::default())
+            .map(Some)
+            .map_err(|e| e.to_string())
+    }),
+    ("yay reader", |v| {
+        parse_reader(encode(v, Format::Yay).as_bytes())
+            .map(Some)
+            .map_err(|e| e.to_string())
+    }),
+    ("yay push", |v| {
+        let text = encode(v, Format::Yay);
+        let mut parser = PushParser::new(&ParseOptions::default());
+        for chunk in text.as_bytes().chunks(7) {
+            parser.feed(chunk).map_err(|e| e.to_string())?;
+        }
+        parser.finish().map(Some).map_err(|e| e.to_string())
+    }),
+    ("yay stream", |v| streamed(v, Format::Yay)),
+    ("json", |v| parse_yson(&encode(v, Format::Json)).map(Some)),
+    ("json stream", |v| streamed(v, Format::Json)),
+    ("yson", |v| parse_yson(&encode(v, Format::Yson)).map(Some)),
+    ("yaml", |v| {
+        let text = transcode::yaml::encode(v, Default::default())?;
+        Ok(Some(transcode::yaml::decode(&text)?))
+    }),
+    ("toml", |v| {
+        let text = transcode::toml::encode(v, Default::default())?;
+        Ok(Some(transcode::toml::decode(&text)?))
+    }),
+    ("cbor", |v| {
+        Ok(Some(transcode::cbor::decode(&transcode::cbor::encode(v)?)?))
+    }),
+    ("diag", |v| {
+        transcode::cbor::diagnostic(&transcode::cbor::encode(v)?).map(|_| None)
+    }),
+    ("gzip", |v| compressed(v, Compression::Gzip)),
+    ("zstd", |v| compressed(v, Compression::Zstd)),
+    ("ndyay", |v| written(v, Format::Ndyay)),
+    ("js", |v| written(v, Format::JavaScript)),
+    ("go", |v| written(v, Format::Go)),
+    ("python", |v| written(v, Format::Python)),
+    ("rust", |v| written(v, Format::Rust)),
+    ("c", |v| written(v, Format::C)),
+    ("java", |v| written(v, Format::Java)),
+    ("scheme", |v| written(v, Format::Scheme)),
+];
+
+/// The optional features of this build, as Cargo names them.
+const FEATURES: &[(&str, bool)] = &[
+    ("net", cfg!(feature = "net")),
+    ("s3", cfg!(feature = "s3")),
+    ("gcs", cfg!(feature = "gcs")),
+];
+
+pub fn run(args: &[String]) -> i32 {
+    if let Some(arg) = args.first() {
+        eprintln!("Error: Unknown argument: {}", arg);
+        return 1;
+    }
+
+    let features: Vec<String> = FEATURES
+        .iter()
+        .map(|(name, on)| format!("{}{}", if *on { "+" } else { "-" }, name))
+        .collect();
+    println!("yay {} ({})", env!("CARGO_PKG_VERSION"), features.join(" "));
+    println!();
+
+    let samples = samples();
+    let matrix = matrix(&samples);
+    let width = PATHS.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    let mut header = " ".repeat(width);
+    for sample in &samples {
+        header.push_str(&format!("  {}", sample.name));
+    }
+    println!("{}", header);
+    let mut failed = false;
+    for ((path, _), row) in PATHS.iter().zip(&matrix) {
+        let mut line = format!("{:width$}", path);
+        for (sample, outcome) in samples.iter().zip(row) {
+            line.push_str(&format!("  {:w$}", outcome.label(), w = sample.name.len()));
+            failed |= *outcome == Outcome::Fail;
+        }
+        println!("{}", line.trim_end());
+    }
+    if failed {
+        println!();
+        println!("Some paths failed; please report this with the output above.");
+        return 1;
+    }
+    0
+}
+
+/// The outcome of every path with every sample, a row per path.
+fn matrix(samples: &[Sample]) -> Vec<Vec<Outcome>> {
+    PATHS
+        .iter()
+        .map(|(path, check)| {
+            samples
+                .iter()
+                .map(|sample| outcome(path, *check, sample))
+                .collect()
+        })
+        .collect()
+}
+
+fn outcome(path: &str, check: Check, sample: &Sample) -> Outcome {
+    if let Some(format) = format_of(path) {
+        if !sample.value.compatibility(format).is_empty() {
+            return Outcome::Unsupported;
+        }
+    }
+    // An encoder that panics is a failure of that path, not of the suite.
+    let result = panic::catch_unwind(AssertUnwindSafe(|| check(&sample.value)));
+    let lossy = sample.lossy.contains(&path);
+    match result {
+        Ok(Ok(None)) => Outcome::Ok,
+        Ok(Ok(Some(value))) if value == sample.value => {
+            if lossy {
+                Outcome::Fail
+            } else {
+                Outcome::Ok
+            }
+        }
+        Ok(Ok(Some(_))) if lossy => Outcome::Lossy,
+        _ => Outcome::Fail,
+    }
+}
+
+/// The format whose limits a path is subject to, for those with limits.
+fn format_of(path: &str) -> Option<Format> {
+    match path {
+        "json" | "json stream" => Some(Format::Json),
+        "yaml" => Some(Format::Yaml),
+        "toml" => Some(Format::Toml),
+        "cbor" | "diag" => Some(Format::Cbor),
+        _ => None,
+    }
+}
+
+/// Write a value with `encode_to`, which must give the text `encode` does,
+/// and read it back.
+fn streamed(value: &Value, format: Format) -> Result<Option<Value>, String> {
+    let mut out = Vec::new();
+    encode_to(&mut out, value, format).map_err(|e| e.to_string())?;
+    let text = String::from_utf8(out).map_err(|e| e.to_string())?;
+    if text != encode(value, format) {
+        return Err("encode_to and encode differ".to_string());
+    }
+    match format {
+        Format::Json => parse_yson(&text).map(Some),
+        _ => parse(&text).map(Some).map_err(|e| e.to_string()),
+    }
+}
+
+/// Compress a value's YAY and read it back through `decompress`.
+fn compressed(value: &Value, compression: Compression) -> Result<Option<Value>, String> {
+    let bytes = compression.compress(encode(value, Format::Yay).as_bytes());
+    let text = decompress(&bytes).map_err(|e| e.to_string())?;
+    parse_bytes(&text, &ParseOptions::default())
+        .map(Some)
+        .map_err(|e| e.to_string())
+}
+
+/// Encode a value in a format this tool only writes.
+fn written(value: &Value, format: Format) -> Result<Option<Value>, String> {
+    encode(value, format);
+    Ok(None)
+}
+
+fn object(members: Vec<(&str, Value)>) -> Value {
+    let mut map = Map::new();
+    for (key, value) in members {
+        map.insert(key.to_string(), value);
+    }
+    Value::Object(map)
+}
+
+/// The documents of the suite, each exercising a few types.
+fn samples() -> Vec<Sample> {
+    let big: BigInt = "123456789012345678901234567890".parse().unwrap();
+    let when = DateTime::from_unix(1_714_564_800, 0).unwrap();
+    vec![
+        Sample {
+            name: "scalars",
+            value: object(vec![
+                ("nothing", Value::Null),
+                ("yes", Value::Bool(true)),
+                ("no", Value::Bool(false)),
+                ("ratio", Value::Float(0.25)),
+                ("name", Value::from("yay")),
+            ]),
+            lossy: &[],
+        },
+        Sample {
+            name: "strings",
+            value: object(vec![
+                ("empty", Value::from("")),
+                ("escapes", Value::from("tab\t\"quoted\"\nline")),
+                ("unicode", Value::from("émile ✓ 日本")),
+            ]),
+            lossy: &[],
+        },
+        Sample {
+            name: "integers",
+            value: object(vec![
+                ("small", Value::from(42)),
+                ("negative", Value::from(-7)),
+                ("big", Value::Integer(big)),
+            ]),
+            lossy: &[],
+        },
+        Sample {
+            name: "bytes",
+            value: object(vec![
+                ("short", Value::Bytes(vec![0xca, 0xfe])),
+                ("long", Value::Bytes((0..=255).collect())),
+            ]),
+            lossy: &[],
+        },
+        Sample {
+            name: "nesting",
+            value: object(vec![
+                (
+                    "servers",
+                    Value::Array(vec![
+                        object(vec![
+                            ("host", Value::from("a")),
+                            ("weight", Value::Float(1.5)),
+                        ]),
+                        object(vec![
+                            ("host", Value::from("b")),
+                            ("tags", Value::Array(vec![])),
+                        ]),
+                    ]),
+                ),
+                ("empty", object(vec![])),
+            ]),
+            lossy: &[],
+        },
+        Sample {
+            name: "dates",
+            value: object(vec![("released", Value::DateTime(when))]),
+            // Only TOML and CBOR have dates; the rest write them as strings.
+            lossy: &[
+                "yay",
+                "yay bytes",
+                "yay reader",
+                "yay push",
+                "yay stream",
+                "json",
+                "json stream",
+                "yson",
+                "yaml",
+                "gzip",
+                "zstd",
+            ],
+        },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_doctor() {
+        let samples = samples();
+        for ((path, _), row) in PATHS.iter().zip(matrix(&samples)) {
+            for (sample, outcome) in samples.iter().zip(row) {
+                assert_ne!(outcome, Outcome::Fail, "{} with {}", path, sample.name);
+            }
+        }
+    }
+}
@@ -0,0 +1,193 @@
+//! `yay fmt [--add-checksums] [--range START:END] [-w] [FILE...]`
+//!
+//! Reformats each document to canonical YAY and prints it, or with
+//! `-w`/`--write` rewrites the file in place. Reads stdin when no file is
+//! given. Compressed files are read transparently and rewritten in their
+//! own compression. `--add-checksums` also writes a `# sha256:` comment on each block
+//! byte array, for `yay --check --verify-checksums` to validate later.
+//!
+//! `--range START:END` formats only the items on those lines (1-based,
+//! inclusive) of a single document, for an editor's "format selection". It
+//! prints the replacement text, and the lines it replaces, grown to whole
+//! items, as `START:END` on stderr.
+
+use libyay::compress::{decompress, Compression};
+use libyay::{add_checksums, decode_bytes, format_yay, format_yay_range, ParseOptions};
+use std::fs;
+use std::io::{self, Read};
+use std::ops::Range;
+use std::path::Path;
+
+use super::read_file;
+
+pub fn run(args: &[String]) -> i32 {
+    let mut checksums = false;
+    let mut write_back = false;
+    let mut range: Option<Range<usize>> = None;
+    let mut paths: Vec<&str> = Vec::new();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--add-checksums" => checksums = true,
+            "-w" | "--write" => write_back = true,
+            "--range" => match args.next().map(|arg| parse_range(arg)) {
+                Some(Some(lines)) => range = Some(lines),
+                Some(None) => {
+                    eprintln!("Error: Invalid --range, expected START:END");
+                    return 1;
+                }
+                None => {
+                    eprintln!("Error: --range requires an argument");
+                    return 1;
+                }
+            },
+            arg if arg.starts_with('-') => {
+                eprintln!("Error: Unknown option: {}", arg);
+                return 1;
+            }
+            arg => paths.push(arg),
+        }
+    }
+
+    if let Some(lines) = range {
+        return match paths.as_slice() {
+            [] if write_back => {
+                eprintln!("Error: --write requires an input file");
+                1
+            }
+            [] | [_] => format_range(paths.first().copied(), lines, checksums, write_back),
+            _ => {
+                eprintln!("Error: --range requires a single input");
+                1
+            }
+        };
+    }
+
+    if paths.is_empty() {
+        if write_back {
+            eprintln!("Error: --write requires an input file");
+            return 1;
+        }
+        let mut bytes = Vec::new();
+        if let Err(e) = io::stdin().read_to_end(&mut bytes) {
+            eprintln!("Error reading stdin: {}", e);
+            return 1;
+        }
+        return match decompress(&bytes)
+            .map_err(|e| e.to_string())
+            .and_then(|bytes| format(&bytes, checksums))
+        {
+            Ok(output) => {
+                print!("{}", output);
+                0
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                1
+            }
+        };
+    }
+
+    let mut exit_code = 0;
+    for path in paths {
+        let result = read_file(path)
+            .and_then(|bytes| format(&bytes, checksums).map_err(|e| format!("{}: {}", path, e)));
+        match result {
+            Ok(output) if write_back => {
+                let compression = Compression::from_path(Path::new(path));
+                if let Err(e) = fs::write(path, compression.compress(output.as_bytes())) {
+                    eprintln!("Error writing {}: {}", path, e);
+                    exit_code = 1;
+                }
+            }
+            Ok(output) => print!("{}", output),
+            Err(e) => {
+                eprintln!("{}", e);
+                exit_code = 1;
+            }
+        }
+    }
+    exit_code
+}
+
+/// Parse `START:END` or `LINE`, 1-based and inclusive, as 0-based lines.
+fn parse_range(s: &str) -> Option<Range<usize>> {
+    let (start, end) = s.split_once(':').unwrap_or((s, s));
+    let (start, end): (usize, usize) = (start.parse().ok()?, end.parse().ok()?);
+    (start >= 1 && start <= end).then(|| start - 1..end)
+}
+
+/// Format the items on `lines` of the document at `path`, or stdin.
+fn format_range(path: Option<&str>, lines: Range<usize>, checksums: bool, write_back: bool) -> i32 {
+    let bytes = match path {
+        Some(path) => read_file(path),
+        None => {
+            let mut bytes = Vec::new();
+            io::stdin()
+                .read_to_end(&mut bytes)
+                .map_err(|e| format!("Error reading stdin: {}", e))
+                .and_then(|_| {
+                    decompress(&bytes)
+                        .map(|b| b.into_owned())
+                        .map_err(|e| e.to_string())
+                })
+        }
+    };
+    let text = match bytes.and_then(|bytes| {
+        decode_bytes(&bytes, &ParseOptions::default())
+            .map_err(|e| format!("{}: {}", path.unwrap_or("stdin"), e))
+    }) {
+        Ok(text) => text,
+        Err(e) => {
+            eprintln!("{}", e);
+            return 1;
+        }
+    };
+    let (lines, replacement) = match format_yay_range(&text, lines) {
+        Ok((lines, replacement)) if checksums => (lines, add_checksums(&replacement)),
+        Ok(formatted) => formatted,
+        Err(e) => {
+            eprintln!("{}: {}", path.unwrap_or("stdin"), e);
+            return 1;
+        }
+    };
+
+    match path {
+        Some(path) if write_back => {
+            let mut output = String::new();
+            for (i, line) in text.lines().enumerate() {
+                if i == lines.start {
+                    output.push_str(&replacement);
+                }
+                if !lines.contains(&i) {
+                    output.push_str(line);
+                    output.push('\n');
+                }
+            }
+            if lines.start >= text.lines().count() {
+                output.push_str(&replacement);
+            }
+            let compression = Compression::from_path(Path::new(path));
+            if let Err(e) = fs::write(path, compression.compress(output.as_bytes())) {
+                eprintln!("Error writing {}: {}", path, e);
+                return 1;
+            }
+        }
+        _ => {
+            print!("{}", replacement);
+            eprintln!("{}:{}", lines.start + 1, lines.end);
+        }
+    }
+    0
+}
+
+fn format(bytes: &[u8], checksums: bool) -> Result<String, String> {
+    let text = decode_bytes(bytes, &ParseOptions::default()).map_err(|e| e.to_string())?;
+    let formatted = format_yay(&text)?;
+    Ok(if checksums {
+        add_checksums(&formatted)
+    } else {
+        formatted
+    })
+}
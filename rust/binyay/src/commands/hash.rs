@@ -0,0 +1,46 @@
+//! `yay hash FILE|DIR...`
+//!
+//! Prints the canonical SHA-256 hash of each document, in the form
+//! `sha256sum` uses: documents that parse to the same value hash the same,
+//! however they are formatted and whatever order their keys are in, so the
+//! hash can name a document as a content-addressed artifact. See
+//! `Value::canonical_hash`.
+
+use super::{collect_yay_files, read_value};
+
+pub fn run(args: &[String]) -> i32 {
+    if let Some(arg) = args.iter().find(|arg| arg.starts_with('-')) {
+        eprintln!("Error: Unknown option: {}", arg);
+        return 1;
+    }
+    if args.is_empty() {
+        eprintln!("Error: yay hash requires at least one file or directory");
+        return 1;
+    }
+    let files = match collect_yay_files(args) {
+        Ok(files) => files,
+        Err(e) => {
+            eprintln!("{}", e);
+            return 1;
+        }
+    };
+
+    let mut exit_code = 0;
+    for file in &files {
+        match read_value(file, None) {
+            Ok(value) => {
+                let digest: String = value
+                    .canonical_hash()
+                    .iter()
+                    .map(|b| format!("{:02x}", b))
+                    .collect();
+                println!("{}  {}", digest, file);
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                exit_code = 1;
+            }
+        }
+    }
+    exit_code
+}
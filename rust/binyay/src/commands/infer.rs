@@ -0,0 +1,64 @@
+//! `yay infer [--examples N] FILE...`
+//!
+//! Prints a YAY report of the shape of the documents: for each path
+//! pattern, like `servers[].port`, the types found there and how often,
+//! how many were null, the least and greatest numbers, the shortest and
+//! longest strings and arrays, and a few example values. Several files are
+//! reported together, as samples of the same kind of document.
+
+use libyay::infer::Inference;
+use libyay::{encode, Format};
+
+use super::read_value;
+
+const DEFAULT_EXAMPLES: usize = 3;
+
+pub fn run(args: &[String]) -> i32 {
+    let mut examples = DEFAULT_EXAMPLES;
+    let mut paths: Vec<&str> = Vec::new();
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--examples" => {
+                i += 1;
+                match args.get(i).and_then(|s| s.parse().ok()) {
+                    Some(n) => examples = n,
+                    None => {
+                        eprintln!("Error: --examples requires a number");
+                        return 1;
+                    }
+                }
+            }
+            arg if arg.starts_with('-') => {
+                eprintln!("Error: Unknown option: {}", arg);
+                return 1;
+            }
+            arg => paths.push(arg),
+        }
+        i += 1;
+    }
+
+    if paths.is_empty() {
+        eprintln!("Error: yay infer requires at least one file");
+        return 1;
+    }
+
+    let mut inference = Inference::new(examples);
+    for path in &paths {
+        match read_value(path, None) {
+            Ok(value) => inference.add(&value),
+            Err(e) => {
+                eprintln!("{}", e);
+                return 1;
+            }
+        }
+    }
+
+    let mut output = encode(&inference.to_value(), Format::Yay);
+    if !output.ends_with('\n') {
+        output.push('\n');
+    }
+    print!("{}", output);
+    0
+}
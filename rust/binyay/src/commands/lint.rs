@@ -0,0 +1,106 @@
+//! `yay lint [--config FILE] FILE|DIR...`
+//!
+//! Runs the libyay lint rules over each document and prints diagnostics as
+//! `path:line: severity [rule] message`. Exits 1 if any diagnostic has error
+//! severity.
+
+use libyay::lint::{LintConfig, Linter, Severity, SizeBudget};
+use std::fs;
+
+use super::{collect_yay_files, read_file};
+use crate::transcode;
+
+pub fn run(args: &[String]) -> i32 {
+    let mut config_path: Option<&str> = None;
+    let mut paths: Vec<String> = Vec::new();
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--config" => {
+                i += 1;
+                if i >= args.len() {
+                    eprintln!("Error: --config requires an argument");
+                    return 1;
+                }
+                config_path = Some(&args[i]);
+            }
+            arg if arg.starts_with('-') => {
+                eprintln!("Error: Unknown option: {}", arg);
+                return 1;
+            }
+            arg => paths.push(arg.to_string()),
+        }
+        i += 1;
+    }
+
+    if paths.is_empty() {
+        eprintln!("Error: yay lint requires at least one file or directory");
+        return 1;
+    }
+
+    let config = match config_path {
+        Some(path) => match fs::read_to_string(path) {
+            Ok(source) => match LintConfig::from_yay(&source) {
+                Ok(config) => config,
+                Err(e) => {
+                    eprintln!("{}: {}", path, e);
+                    return 1;
+                }
+            },
+            Err(e) => {
+                eprintln!("Error reading {}: {}", path, e);
+                return 1;
+            }
+        },
+        None => LintConfig::default(),
+    };
+    let mut linter = Linter::new(config);
+    // The size budget rule can measure CBOR, which only the CLI can encode.
+    linter.add_rule(Box::new(SizeBudget::new().with_sizer(
+        "cbor",
+        Box::new(|v| transcode::cbor::encode(v).ok().map(|b| b.len())),
+    )));
+    if let Err(e) = linter.validate_config() {
+        eprintln!("{}: {}", config_path.unwrap_or("lint config"), e);
+        return 1;
+    }
+
+    let files = match collect_yay_files(&paths) {
+        Ok(files) => files,
+        Err(e) => {
+            eprintln!("{}", e);
+            return 1;
+        }
+    };
+
+    let mut had_errors = false;
+    for file in &files {
+        let source = match read_file(file).and_then(|bytes| {
+            String::from_utf8(bytes).map_err(|e| format!("Error reading {}: {}", file, e))
+        }) {
+            Ok(s) => s,
+            Err(e) => {
+                eprintln!("{}", e);
+                had_errors = true;
+                continue;
+            }
+        };
+        for diagnostic in linter.lint(&source, Some(file)) {
+            if diagnostic.severity == Severity::Error {
+                had_errors = true;
+            }
+            if diagnostic.line.is_some() {
+                println!("{}:{}", file, diagnostic);
+            } else {
+                println!("{}: {}", file, diagnostic);
+            }
+        }
+    }
+
+    if had_errors {
+        1
+    } else {
+        0
+    }
+}
@@ -0,0 +1,92 @@
+//! `yay merge [--concat-arrays] [--null-deletes] [-t FORMAT] BASE OVERRIDE...`
+//!
+//! Deep-merges each override document over the base, in order, and prints
+//! the result as YAY, or in the format `-t` names: layered configuration,
+//! like defaults followed by an environment's settings. Objects merge
+//! member by member; arrays and other values are replaced.
+//! `--concat-arrays` appends arrays instead, and `--null-deletes` removes
+//! a member whose override is null.
+//!
+//! Each input's format is inferred from its extension, or given by a `-f`
+//! before it, for inputs without one, like `<(...)` in a shell:
+//! `yay merge -f yaml <(helm get values web) -f toml local.cfg`.
+
+use libyay::{ArrayMerge, Format, MergeStrategy};
+use std::io::{self, Write};
+
+use super::{convert, format_arg, read_value};
+
+pub fn run(args: &[String]) -> i32 {
+    let mut strategy = MergeStrategy::default();
+    let mut to_format = Format::Yay;
+    let mut inputs: Vec<(&str, Option<&str>)> = Vec::new();
+    let mut from_format = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--concat-arrays" => strategy.arrays = ArrayMerge::Concat,
+            "--null-deletes" => strategy.null_deletes = true,
+            "-f" | "--from" => match format_arg(args.next()) {
+                Ok(format) => from_format = Some(format),
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    return 1;
+                }
+            },
+            "-t" | "--to" => {
+                let Some(name) = args.next() else {
+                    eprintln!("Error: -t requires a format argument");
+                    return 1;
+                };
+                let Some(format) = crate::format_named(name) else {
+                    eprintln!("Error: Unknown format: {}", name);
+                    return 1;
+                };
+                to_format = format;
+            }
+            arg if arg.starts_with('-') => {
+                eprintln!("Error: Unknown option: {}", arg);
+                return 1;
+            }
+            arg => inputs.push((arg, from_format.take())),
+        }
+    }
+
+    if from_format.is_some() {
+        eprintln!("Error: -f must come before the input it gives the format of");
+        return 1;
+    }
+    if inputs.len() < 2 {
+        eprintln!("Error: yay merge requires a base file and at least one override");
+        return 1;
+    }
+
+    let mut merged = None;
+    for (path, format) in &inputs {
+        let value = match read_value(path, *format) {
+            Ok(value) => value,
+            Err(e) => {
+                eprintln!("{}", e);
+                return 1;
+            }
+        };
+        match &mut merged {
+            None => merged = Some(value),
+            Some(merged) => merged.merge(&value, strategy),
+        }
+    }
+
+    let output = match merged.map_or_else(|| Ok(Vec::new()), |v| convert(&v, to_format)) {
+        Ok(output) => output,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return 1;
+        }
+    };
+    if let Err(e) = io::stdout().lock().write_all(&output) {
+        eprintln!("Error writing to stdout: {}", e);
+        return 1;
+    }
+    0
+}
@@ -0,0 +1,142 @@
+//! `yay migrate [--to VERSION] [--migrations FILE] [-w [--force]] FILE|DIR...`
+//!
+//! Upgrades each document from the version in its `version:` member to
+//! `--to` (by default the latest version), applying the steps listed in a
+//! migrations file, `migrations.yay` beside the document unless
+//! `--migrations` names another. Prints the upgraded document, or with
+//! `-w`/`--write` rewrites it in place, and reports each file's versions on
+//! stderr. See `libyay::migrate` for the migrations format.
+//!
+//! The upgraded document is written in canonical form from its value, which
+//! keeps no comments, so a document with comments is not rewritten unless
+//! `--force` is given.
+
+use libyay::migrate::Migrations;
+use libyay::{encode, Format};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use super::{collect_yay_files, read_yay, write_back};
+
+pub fn run(args: &[String]) -> i32 {
+    let mut to: Option<u64> = None;
+    let mut migrations_path: Option<&str> = None;
+    let mut write = false;
+    let mut force = false;
+    let mut paths: Vec<String> = Vec::new();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--to" => match args.next().map(|arg| arg.parse()) {
+                Some(Ok(version)) => to = Some(version),
+                Some(Err(_)) => {
+                    eprintln!("Error: Invalid --to, expected a version number");
+                    return 1;
+                }
+                None => {
+                    eprintln!("Error: --to requires an argument");
+                    return 1;
+                }
+            },
+            "--migrations" => match args.next() {
+                Some(path) => migrations_path = Some(path),
+                None => {
+                    eprintln!("Error: --migrations requires an argument");
+                    return 1;
+                }
+            },
+            "-w" | "--write" => write = true,
+            "--force" => force = true,
+            arg if arg.starts_with('-') => {
+                eprintln!("Error: Unknown option: {}", arg);
+                return 1;
+            }
+            arg => paths.push(arg.to_string()),
+        }
+    }
+    if paths.is_empty() {
+        eprintln!("Error: yay migrate requires at least one file or directory");
+        return 1;
+    }
+
+    let files = match collect_yay_files(&paths) {
+        Ok(files) => files,
+        Err(e) => {
+            eprintln!("{}", e);
+            return 1;
+        }
+    };
+
+    // Migrations files by path, each read once.
+    let mut loaded: HashMap<String, Migrations> = HashMap::new();
+    let mut exit_code = 0;
+    for file in &files {
+        let source = match migrations_path {
+            Some(path) => path.to_string(),
+            None => Path::new(file)
+                .with_file_name("migrations.yay")
+                .to_string_lossy()
+                .to_string(),
+        };
+        if Path::new(file) == Path::new(&source) {
+            continue;
+        }
+        if !loaded.contains_key(&source) {
+            match fs::read_to_string(&source)
+                .map_err(|e| format!("Error reading {}: {}", source, e))
+                .and_then(|text| {
+                    Migrations::from_yay(&text).map_err(|e| format!("{}: {}", source, e))
+                }) {
+                Ok(migrations) => {
+                    loaded.insert(source.clone(), migrations);
+                }
+                Err(e) => {
+                    eprintln!("{}", e);
+                    exit_code = 1;
+                    continue;
+                }
+            }
+        }
+        let migrations = &loaded[&source];
+
+        let (mut document, had_comments) = match read_yay(file) {
+            Ok(read) => read,
+            Err(e) => {
+                eprintln!("{}", e);
+                exit_code = 1;
+                continue;
+            }
+        };
+        let to = to.unwrap_or_else(|| migrations.latest());
+        let from = match migrations.apply(&mut document, to) {
+            Ok(from) => from,
+            Err(e) => {
+                eprintln!("{}: {}", file, e);
+                exit_code = 1;
+                continue;
+            }
+        };
+
+        let mut output = encode(&document, Format::Yay);
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if !write {
+            print!("{}", output);
+        } else if from != to {
+            if let Err(e) = write_back(file, &output, had_comments, force) {
+                eprintln!("{}", e);
+                exit_code = 1;
+                continue;
+            }
+        }
+        if from == to {
+            eprintln!("{}: already at version {}", file, to);
+        } else {
+            eprintln!("{}: version {} to {}", file, from, to);
+        }
+    }
+    exit_code
+}
@@ -0,0 +1,254 @@
+//! Subcommands: `yay <COMMAND> ...`.
+//!
+//! A subcommand is selected when the first argument names one; everything
+//! else falls through to the classic flag-driven interface in `main`.
+
+use libyay::compress::{decompress, Compression};
+use libyay::{
+    decode_bytes, encode, format_yay, parse, parse_jsonc, parse_with_options, parse_with_report,
+    parse_yson, Format, KeyOrder, ParseOptions, Value,
+};
+use std::borrow::Cow;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config;
+use crate::transcode;
+
+pub mod batch;
+pub mod diff;
+pub mod doctor;
+pub mod fmt;
+pub mod hash;
+pub mod infer;
+pub mod lint;
+pub mod merge;
+pub mod migrate;
+pub mod outline;
+pub mod patch;
+pub mod refs;
+pub mod rename_key;
+pub mod stamp;
+pub mod stats;
+
+/// Run the named subcommand with its arguments, returning the exit code,
+/// or `None` if `name` is not a subcommand.
+pub fn dispatch(name: &str, args: &[String]) -> Option<i32> {
+    match name {
+        "batch" => Some(batch::run(args)),
+        "diff" => Some(diff::run(args)),
+        "doctor" => Some(doctor::run(args)),
+        "fmt" => Some(fmt::run(args)),
+        "hash" => Some(hash::run(args)),
+        "infer" => Some(infer::run(args)),
+        "lint" => Some(lint::run(args)),
+        "merge" => Some(merge::run(args)),
+        "migrate" => Some(migrate::run(args)),
+        "outline" => Some(outline::run(args)),
+        "patch" => Some(patch::run(args)),
+        "refs" => Some(refs::run(args)),
+        "rename-key" => Some(rename_key::run(args)),
+        "stamp" => Some(stamp::run(args)),
+        "stats" => Some(stats::run(args)),
+        _ => None,
+    }
+}
+
+/// `path` without a `.gz` or `.zst` extension.
+pub fn strip_compression(path: &Path) -> PathBuf {
+    match Compression::from_path(path) {
+        Compression::None => path.to_path_buf(),
+        _ => path.with_extension(""),
+    }
+}
+
+/// Whether `path` names a YAY document: `.yay`, `.yay.gz`, or `.yay.zst`,
+/// or an extension the config file gives YAY.
+pub fn is_yay_path(path: &Path) -> bool {
+    strip_compression(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e == "yay" || config::get().format_of(e) == Some("meh"))
+}
+
+/// Read a file, decompressing it if it is gzip or zstd.
+pub fn read_file(path: &str) -> Result<Vec<u8>, String> {
+    let bytes = fs::read(path).map_err(|e| format!("Error reading {}: {}", path, e))?;
+    match decompress(&bytes) {
+        Ok(Cow::Borrowed(_)) => Ok(bytes),
+        Ok(Cow::Owned(data)) => Ok(data),
+        Err(e) => Err(format!("{}: {}", path, e)),
+    }
+}
+
+/// Expand file and directory arguments into the list of `.yay` files to
+/// process, matching directory mode in `main`.
+pub fn collect_yay_files(paths: &[String]) -> Result<Vec<String>, String> {
+    let mut files = Vec::new();
+    for path in paths {
+        let path_ref = Path::new(path);
+        if path_ref.is_dir() {
+            let entries = fs::read_dir(path_ref)
+                .map_err(|e| format!("Error reading directory {}: {}", path, e))?;
+            let mut found: Vec<String> = entries
+                .flatten()
+                .map(|e| e.path())
+                .filter(|p| is_yay_path(p))
+                .map(|p| p.to_string_lossy().to_string())
+                .collect();
+            found.sort();
+            files.extend(found);
+        } else {
+            files.push(path.clone());
+        }
+    }
+    Ok(files)
+}
+
+/// Infer an input format name from a file extension, defaulting to `meh`.
+/// A `.gz` or `.zst` extension is looked through. The config file's
+/// extensions come before the usual ones.
+pub fn infer_format(path: &str) -> &'static str {
+    let stripped = strip_compression(Path::new(path));
+    let extension = stripped.extension().and_then(|e| e.to_str());
+    if let Some(format) = extension.and_then(|e| config::get().format_of(e)) {
+        return format;
+    }
+    match extension {
+        Some("json") => "json",
+        Some("jsonc") => "jsonc",
+        Some("yson") => "yson",
+        Some("ndyay") => "ndyay",
+        Some("yaml") | Some("yml") => "yaml",
+        Some("toml") => "toml",
+        Some("cbor") => "cbor",
+        _ => "meh",
+    }
+}
+
+/// The input format named `name`, as `-f` takes it.
+pub fn input_format(name: &str) -> Option<&'static str> {
+    [
+        "meh", "yay", "json", "jsonc", "yson", "ndyay", "yaml", "toml", "cbor",
+    ]
+    .into_iter()
+    .find(|format| *format == name)
+}
+
+/// The format named by the argument after a `-f`, for commands that read
+/// several documents: there each `-f` gives the format of the one input
+/// after it, so `-f yaml a.cfg -f toml b.cfg` reads each as it is.
+pub fn format_arg(name: Option<&String>) -> Result<&'static str, String> {
+    let name = name.ok_or("-f requires a format argument")?;
+    input_format(name).ok_or_else(|| format!("Unknown format: {}", name))
+}
+
+/// Options for parsing the canonical YAY that `format_yay` writes from MEH
+/// input, which keeps a `%yay` version directive.
+pub fn canonical_options() -> ParseOptions {
+    let mut options = ParseOptions::default();
+    options.version_directive = true;
+    options
+}
+
+/// Read and decode a document. The format is inferred from the extension
+/// when not given; `meh` input is reformatted to canonical YAY and parsed.
+pub fn read_value(path: &str, format: Option<&str>) -> Result<Value, String> {
+    let format = format.unwrap_or_else(|| infer_format(path));
+    let bytes = read_file(path)?;
+    if format == "cbor" {
+        return transcode::cbor::decode(&bytes).map_err(|e| e.in_file(path));
+    }
+    let text =
+        decode_bytes(&bytes, &ParseOptions::default()).map_err(|e| format!("{}: {}", path, e))?;
+    let result = match format {
+        "yay" => parse(&text).map_err(|e| e.to_string()),
+        "json" | "yson" => parse_yson(&text),
+        "jsonc" => parse_jsonc(&text),
+        "yaml" | "yml" => return transcode::yaml::decode(&text).map_err(|e| e.in_file(path)),
+        "toml" => return transcode::toml::decode(&text).map_err(|e| e.in_file(path)),
+        _ => format_yay(&text).and_then(|canonical| {
+            parse_with_options(&canonical, &canonical_options()).map_err(|e| e.to_string())
+        }),
+    };
+    result.map_err(|e| format!("{}: {}", path, e))
+}
+
+/// Read a YAY or MEH document as `read_value` does, and whether it had
+/// comments, which the value does not keep.
+pub fn read_yay(path: &str) -> Result<(Value, bool), String> {
+    let bytes = read_file(path)?;
+    let text =
+        decode_bytes(&bytes, &ParseOptions::default()).map_err(|e| format!("{}: {}", path, e))?;
+    format_yay(&text)
+        .and_then(|canonical| {
+            parse_with_report(&canonical, &canonical_options()).map_err(|e| e.to_string())
+        })
+        .map(|(value, report)| (value, report.had_comments))
+        .map_err(|e| format!("{}: {}", path, e))
+}
+
+/// Refuse YAY output made from the value of a document that `had_comments`,
+/// which the value did not keep, unless `force` is set. `hint` says how to
+/// keep them. Every way of writing YAY through a `Value` checks this.
+pub fn keep_comments(
+    input_file: Option<&str>,
+    had_comments: bool,
+    force: bool,
+    hint: &str,
+) -> Result<(), String> {
+    if !had_comments || force {
+        return Ok(());
+    }
+    let prefix = input_file.map(|p| format!("{}: ", p)).unwrap_or_default();
+    Err(format!(
+        "{}Error: This would drop the comments in the input; {}, or pass --force",
+        prefix, hint
+    ))
+}
+
+/// Rewrite `path` in place with `output`, a document encoded from a
+/// `Value`, compressed as the path's extension says. If the document
+/// `had_comments`, it is left as it was unless `force` is set; see
+/// [`keep_comments`].
+pub fn write_back(path: &str, output: &str, had_comments: bool, force: bool) -> Result<(), String> {
+    keep_comments(
+        Some(path),
+        had_comments,
+        force,
+        "print the result without -w to check it",
+    )?;
+    let compression = Compression::from_path(Path::new(path));
+    fs::write(path, compression.compress(output.as_bytes()))
+        .map_err(|e| format!("Error writing {}: {}", path, e))
+}
+
+/// Encode `value` in `format`.
+pub fn convert(value: &Value, format: Format) -> Result<Vec<u8>, String> {
+    let name = match format {
+        Format::Json => Some("JSON"),
+        Format::Toml => Some("TOML"),
+        Format::Cbor | Format::CborDiag => Some("CBOR"),
+        _ => None,
+    };
+    if let Some(name) = name {
+        if let Some(first) = value.compatibility(format).first() {
+            return Err(format!("Cannot convert to {}: {}", name, first));
+        }
+    }
+    let keys = KeyOrder::default();
+    let mut output = match format {
+        Format::Yaml => transcode::yaml::encode(value, keys)?.into_bytes(),
+        Format::Toml => transcode::toml::encode(value, keys)?.into_bytes(),
+        Format::Cbor => return transcode::cbor::encode_ordered(value, keys),
+        Format::CborDiag => {
+            transcode::cbor::diagnostic(&transcode::cbor::encode_ordered(value, keys)?)?
+                .into_bytes()
+        }
+        _ => encode(value, format).into_bytes(),
+    };
+    if !output.ends_with(b"\n") {
+        output.push(b'\n');
+    }
+    Ok(output)
+}
@@ -0,0 +1,55 @@
+//! `yay outline FILE`
+//!
+//! Prints the keys and array items of a document as an indented tree, each
+//! with its kind and its lines (1-based), like an editor's outline view.
+//! Arrays show their item count.
+
+use libyay::outline::{symbols, Symbol};
+use libyay::{decode_bytes, ParseOptions};
+
+use super::read_file;
+
+pub fn run(args: &[String]) -> i32 {
+    let mut paths: Vec<&str> = Vec::new();
+    for arg in args {
+        match arg.as_str() {
+            arg if arg.starts_with('-') => {
+                eprintln!("Error: Unknown option: {}", arg);
+                return 1;
+            }
+            arg => paths.push(arg),
+        }
+    }
+    let [path] = paths.as_slice() else {
+        eprintln!("Error: yay outline requires exactly one file");
+        return 1;
+    };
+
+    let text = match read_file(path).and_then(|bytes| {
+        decode_bytes(&bytes, &ParseOptions::default()).map_err(|e| format!("{}: {}", path, e))
+    }) {
+        Ok(text) => text,
+        Err(e) => {
+            eprintln!("{}", e);
+            return 1;
+        }
+    };
+    print_symbols(&symbols(&text), 0);
+    0
+}
+
+fn print_symbols(symbols: &[Symbol], depth: usize) {
+    for symbol in symbols {
+        let kind = match symbol.items {
+            Some(items) => format!("{}[{}]", symbol.kind, items),
+            None => symbol.kind.to_string(),
+        };
+        let lines = if symbol.lines.len() > 1 {
+            format!("{}-{}", symbol.lines.start + 1, symbol.lines.end)
+        } else {
+            (symbol.lines.start + 1).to_string()
+        };
+        println!("{}{}: {}  {}", "  ".repeat(depth), symbol.name, kind, lines);
+        print_symbols(&symbol.children, depth + 1);
+    }
+}
@@ -0,0 +1,108 @@
+//! `yay patch [--merge] [-w [--force]] PATCH FILE|DIR...`
+//!
+//! Applies a JSON Patch (RFC 6902), written in YAY or JSON, to each
+//! document: adding, removing, replacing, moving, copying, and testing
+//! values at JSON Pointers. Prints the patched document, or with
+//! `-w`/`--write` rewrites it in place. A document any operation fails on,
+//! a failed `test` included, is left as it was. See `libyay::patch`.
+//!
+//! The patched document is written in canonical form from its value, which
+//! keeps no comments, so a document with comments is not rewritten unless
+//! `--force` is given.
+//!
+//! With `--merge`, the patch is a JSON Merge Patch (RFC 7386) instead: a
+//! document shaped like the ones it patches, whose nulls remove members.
+//! See `Value::merge_patch`.
+
+use libyay::patch::Patch;
+use libyay::{encode, Format};
+
+use super::{collect_yay_files, read_value, read_yay, write_back};
+
+pub fn run(args: &[String]) -> i32 {
+    let mut write = false;
+    let mut force = false;
+    let mut merge = false;
+    let mut paths: Vec<String> = Vec::new();
+
+    for arg in args {
+        match arg.as_str() {
+            "-w" | "--write" => write = true,
+            "--force" => force = true,
+            "--merge" => merge = true,
+            arg if arg.starts_with('-') => {
+                eprintln!("Error: Unknown option: {}", arg);
+                return 1;
+            }
+            arg => paths.push(arg.to_string()),
+        }
+    }
+    if paths.len() < 2 {
+        eprintln!("Error: yay patch requires a patch and at least one file or directory");
+        return 1;
+    }
+    let patch_path = paths.remove(0);
+
+    let patch_value = match read_value(&patch_path, None) {
+        Ok(value) => value,
+        Err(e) => {
+            eprintln!("{}", e);
+            return 1;
+        }
+    };
+    // A merge patch is applied as it is; a JSON Patch is read first.
+    let patch = if merge {
+        None
+    } else {
+        match Patch::from_value(&patch_value) {
+            Ok(patch) => Some(patch),
+            Err(e) => {
+                eprintln!("{}: {}", patch_path, e);
+                return 1;
+            }
+        }
+    };
+    let files = match collect_yay_files(&paths) {
+        Ok(files) => files,
+        Err(e) => {
+            eprintln!("{}", e);
+            return 1;
+        }
+    };
+
+    let mut exit_code = 0;
+    for file in &files {
+        let (mut document, had_comments) = match read_yay(file) {
+            Ok(read) => read,
+            Err(e) => {
+                eprintln!("{}", e);
+                exit_code = 1;
+                continue;
+            }
+        };
+        match &patch {
+            Some(patch) => {
+                if let Err(e) = patch.apply(&mut document) {
+                    eprintln!("{}: {}", file, e);
+                    exit_code = 1;
+                    continue;
+                }
+            }
+            None => document.merge_patch(&patch_value),
+        }
+
+        let mut output = encode(&document, Format::Yay);
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        if !write {
+            print!("{}", output);
+            continue;
+        }
+        if let Err(e) = write_back(file, &output, had_comments, force) {
+            eprintln!("{}", e);
+            exit_code = 1;
+        }
+    }
+    exit_code
+}
@@ -0,0 +1,117 @@
+//! `yay refs --rules FILE FILE|DIR...`
+//!
+//! Checks the references declared in a rules file across all the given
+//! documents together, printing each value that refers to nothing as
+//! `path:line: value.path: VALUE is not a TARGET`. Exits 1 if any
+//! reference is broken. See `libyay::references` for the rules format.
+
+use libyay::references::{Document, ReferenceRules};
+use libyay::{encode, Format, Value};
+use std::fs;
+use std::path::Path;
+
+use super::{collect_yay_files, is_yay_path, read_file, read_value};
+
+pub fn run(args: &[String]) -> i32 {
+    let mut rules_path: Option<&str> = None;
+    let mut paths: Vec<String> = Vec::new();
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--rules" => {
+                i += 1;
+                if i >= args.len() {
+                    eprintln!("Error: --rules requires an argument");
+                    return 1;
+                }
+                rules_path = Some(&args[i]);
+            }
+            arg if arg.starts_with('-') => {
+                eprintln!("Error: Unknown option: {}", arg);
+                return 1;
+            }
+            arg => paths.push(arg.to_string()),
+        }
+        i += 1;
+    }
+
+    let Some(rules_path) = rules_path else {
+        eprintln!("Error: yay refs requires --rules FILE");
+        return 1;
+    };
+    if paths.is_empty() {
+        eprintln!("Error: yay refs requires at least one file or directory");
+        return 1;
+    }
+
+    let rules = match fs::read_to_string(rules_path)
+        .map_err(|e| format!("Error reading {}: {}", rules_path, e))
+        .and_then(|source| {
+            ReferenceRules::from_yay(&source).map_err(|e| format!("{}: {}", rules_path, e))
+        }) {
+        Ok(rules) => rules,
+        Err(e) => {
+            eprintln!("{}", e);
+            return 1;
+        }
+    };
+
+    let files = match collect_yay_files(&paths) {
+        Ok(files) => files,
+        Err(e) => {
+            eprintln!("{}", e);
+            return 1;
+        }
+    };
+
+    let mut loaded: Vec<(Value, Option<String>)> = Vec::new();
+    for file in &files {
+        let value = match read_value(file, None) {
+            Ok(value) => value,
+            Err(e) => {
+                eprintln!("{}", e);
+                return 1;
+            }
+        };
+        let source = if is_yay_path(Path::new(file)) {
+            read_file(file)
+                .ok()
+                .and_then(|bytes| String::from_utf8(bytes).ok())
+        } else {
+            None
+        };
+        loaded.push((value, source));
+    }
+    let documents: Vec<Document> = files
+        .iter()
+        .zip(&loaded)
+        .map(|(file, (value, source))| Document {
+            name: file,
+            value,
+            source: source.as_deref(),
+        })
+        .collect();
+
+    let broken = rules.check(&documents);
+    for reference in &broken {
+        let file = &files[reference.document];
+        let location = match reference.line {
+            Some(line) => format!("{}:{}", file, line + 1),
+            None => file.clone(),
+        };
+        println!(
+            "{}: {}: {} is not {}",
+            location,
+            reference.path,
+            encode(&reference.value, Format::Yay),
+            reference.expected
+        );
+    }
+
+    if broken.is_empty() {
+        0
+    } else {
+        1
+    }
+}
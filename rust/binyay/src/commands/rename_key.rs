@@ -0,0 +1,101 @@
+//! `yay rename-key OLD.PATH NEW [-w] FILE|DIR...`
+//!
+//! Renames the key at a dotted path, like `servers.host`, to a new name in
+//! every document it appears in, and prints each file that changes with the
+//! number of keys renamed. Array items are passed through, so
+//! `servers.host` renames `host` in each item of `servers`. Only the keys
+//! are rewritten: comments and formatting stay as they were.
+//!
+//! Without `-w`/`--write` nothing is written, so a run shows what would
+//! change. A file that cannot be read, or whose object already has a key
+//! with the new name, is reported and left alone, and the exit code is 1.
+
+use libyay::compress::Compression;
+use libyay::refactor::rename_key;
+use std::fs;
+use std::path::Path;
+
+use super::{collect_yay_files, read_file};
+
+pub fn run(args: &[String]) -> i32 {
+    let mut write_back = false;
+    let mut operands: Vec<&str> = Vec::new();
+    for arg in args {
+        match arg.as_str() {
+            "-w" | "--write" => write_back = true,
+            arg if arg.starts_with('-') => {
+                eprintln!("Error: Unknown option: {}", arg);
+                return 1;
+            }
+            arg => operands.push(arg),
+        }
+    }
+    let [old, new, paths @ ..] = operands.as_slice() else {
+        eprintln!("Error: yay rename-key requires OLD.PATH NEW and at least one file or directory");
+        return 1;
+    };
+    if paths.is_empty() {
+        eprintln!("Error: yay rename-key requires at least one file or directory");
+        return 1;
+    }
+    let path: Vec<&str> = old.split('.').collect();
+    if path.iter().any(|key| key.is_empty()) || new.is_empty() {
+        eprintln!("Error: Invalid key path: {}", old);
+        return 1;
+    }
+
+    let paths: Vec<String> = paths.iter().map(|p| p.to_string()).collect();
+    let files = match collect_yay_files(&paths) {
+        Ok(files) => files,
+        Err(e) => {
+            eprintln!("{}", e);
+            return 1;
+        }
+    };
+
+    let mut exit_code = 0;
+    let mut changed = 0;
+    for file in &files {
+        let result = read_file(file)
+            .and_then(|bytes| {
+                String::from_utf8(bytes).map_err(|e| format!("Error reading {}: {}", file, e))
+            })
+            .and_then(|source| {
+                rename_key(&source, &path, new).map_err(|e| format!("{}: {}", file, e))
+            });
+        let (text, count) = match result {
+            Ok(renamed) => renamed,
+            Err(e) => {
+                eprintln!("{}", e);
+                exit_code = 1;
+                continue;
+            }
+        };
+        if count == 0 {
+            continue;
+        }
+        if write_back {
+            let compression = Compression::from_path(Path::new(file));
+            if let Err(e) = fs::write(file, compression.compress(text.as_bytes())) {
+                eprintln!("Error writing {}: {}", file, e);
+                exit_code = 1;
+                continue;
+            }
+        }
+        changed += 1;
+        println!("{}: {} renamed", file, count);
+    }
+
+    let summary = format!(
+        "{} of {} {}",
+        changed,
+        files.len(),
+        if files.len() == 1 { "file" } else { "files" }
+    );
+    if write_back {
+        println!("{} changed", summary);
+    } else {
+        println!("{} would change; use -w to write them", summary);
+    }
+    exit_code
+}
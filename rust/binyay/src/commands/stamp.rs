@@ -0,0 +1,176 @@
+//! `yay stamp (--set-path PATH SOURCE)... [-w] FILE|DIR...`
+//!
+//! Sets build metadata at paths in each document, as a release pipeline
+//! records when and from what a file was generated. Each `--set-path`
+//! is followed by where its value comes from:
+//!
+//! - `--now`: the current time in UTC, like `2024-05-01T12:00:00Z`, or the
+//!   time in `SOURCE_DATE_EPOCH` when that is set, for reproducible builds.
+//! - `--git`: the commit `git rev-parse HEAD` names.
+//! - `--value TEXT`: the string `TEXT`.
+//!
+//! Documents are edited through their syntax tree, so comments and the
+//! formatting of everything else stay as they were; missing objects on
+//! the way to a path are added. Prints each stamped document, or with
+//! `-w`/`--write` rewrites it in place.
+
+use libyay::compress::Compression;
+use libyay::{cst, DateTime, Value};
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::{collect_yay_files, read_file};
+
+/// Where a stamped value comes from.
+enum Source<'a> {
+    Now,
+    Git,
+    Text(&'a str),
+}
+
+pub fn run(args: &[String]) -> i32 {
+    let mut write_back = false;
+    let mut stamps: Vec<(&str, Source)> = Vec::new();
+    let mut paths: Vec<String> = Vec::new();
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "-w" | "--write" => write_back = true,
+            "--set-path" => {
+                let Some(path) = args.get(i + 1) else {
+                    eprintln!("Error: --set-path requires a path");
+                    return 1;
+                };
+                i += 2;
+                let source = match args.get(i).map(String::as_str) {
+                    Some("--now") => Source::Now,
+                    Some("--git") => Source::Git,
+                    Some("--value") => {
+                        i += 1;
+                        let Some(text) = args.get(i) else {
+                            eprintln!("Error: --value requires a value");
+                            return 1;
+                        };
+                        Source::Text(text)
+                    }
+                    _ => {
+                        eprintln!(
+                            "Error: --set-path {} must be followed by --now, --git, or --value",
+                            path
+                        );
+                        return 1;
+                    }
+                };
+                stamps.push((path, source));
+            }
+            arg if arg.starts_with('-') => {
+                eprintln!("Error: Unknown option: {}", arg);
+                return 1;
+            }
+            arg => paths.push(arg.to_string()),
+        }
+        i += 1;
+    }
+    if stamps.is_empty() || paths.is_empty() {
+        eprintln!("Error: yay stamp requires --set-path and at least one file or directory");
+        return 1;
+    }
+
+    // Every document gets the same values, read once.
+    let mut values = Vec::with_capacity(stamps.len());
+    for (path, source) in &stamps {
+        let value = match source {
+            Source::Now => now(),
+            Source::Git => git_commit(),
+            Source::Text(text) => Ok(Value::String(text.to_string())),
+        };
+        match value {
+            Ok(value) => values.push((*path, value)),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                return 1;
+            }
+        }
+    }
+
+    let files = match collect_yay_files(&paths) {
+        Ok(files) => files,
+        Err(e) => {
+            eprintln!("{}", e);
+            return 1;
+        }
+    };
+
+    let mut exit_code = 0;
+    for file in &files {
+        let result = read_file(file)
+            .and_then(|bytes| {
+                String::from_utf8(bytes).map_err(|e| format!("Error reading {}: {}", file, e))
+            })
+            .and_then(|source| stamp(&source, &values).map_err(|e| format!("{}: {}", file, e)));
+        let output = match result {
+            Ok(output) => output,
+            Err(e) => {
+                eprintln!("{}", e);
+                exit_code = 1;
+                continue;
+            }
+        };
+        if !write_back {
+            print!("{}", output);
+            continue;
+        }
+        let compression = Compression::from_path(Path::new(file));
+        if let Err(e) = fs::write(file, compression.compress(output.as_bytes())) {
+            eprintln!("Error writing {}: {}", file, e);
+            exit_code = 1;
+        }
+    }
+    exit_code
+}
+
+/// Set each path of a document to its value.
+fn stamp(source: &str, values: &[(&str, Value)]) -> Result<String, String> {
+    let mut document = cst::parse(source)?;
+    for (path, value) in values {
+        document.set(path, value)?;
+    }
+    Ok(cst::to_string(&document))
+}
+
+/// The current time, or `SOURCE_DATE_EPOCH`, to the second.
+fn now() -> Result<Value, String> {
+    let seconds = match env::var("SOURCE_DATE_EPOCH") {
+        Ok(epoch) => epoch
+            .trim()
+            .parse::<i64>()
+            .map_err(|_| format!("Invalid SOURCE_DATE_EPOCH: {}", epoch))?,
+        Err(_) => SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|e| format!("The clock is before 1970: {}", e))?
+            .as_secs() as i64,
+    };
+    DateTime::from_unix(seconds, 0)
+        .map(Value::DateTime)
+        .ok_or_else(|| format!("Time out of range: {} seconds", seconds))
+}
+
+/// The commit checked out in the current directory.
+fn git_commit() -> Result<Value, String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .output()
+        .map_err(|e| format!("could not run git: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "git rev-parse HEAD failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    let commit = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Ok(Value::String(commit))
+}
@@ -0,0 +1,116 @@
+//! `yay stats FILE` and `yay stats --diff OLD NEW`
+//!
+//! Prints document statistics, or per-path size and node count deltas
+//! between two versions of a document, largest size change first.
+//! `--depth N` limits how deep per-path entries go (default 3).
+
+use libyay::stats::{diff, stats, PathStats, Stats};
+
+use super::read_value;
+
+const DEFAULT_DEPTH: usize = 3;
+
+pub fn run(args: &[String]) -> i32 {
+    let mut depth = DEFAULT_DEPTH;
+    let mut diff_mode = false;
+    let mut paths: Vec<&str> = Vec::new();
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--diff" => diff_mode = true,
+            "--depth" => {
+                i += 1;
+                match args.get(i).and_then(|s| s.parse().ok()) {
+                    Some(n) => depth = n,
+                    None => {
+                        eprintln!("Error: --depth requires a number");
+                        return 1;
+                    }
+                }
+            }
+            arg if arg.starts_with('-') => {
+                eprintln!("Error: Unknown option: {}", arg);
+                return 1;
+            }
+            arg => paths.push(arg),
+        }
+        i += 1;
+    }
+
+    let expected = if diff_mode { 2 } else { 1 };
+    if paths.len() != expected {
+        if diff_mode {
+            eprintln!("Error: yay stats --diff requires OLD and NEW files");
+        } else {
+            eprintln!("Error: yay stats requires exactly one file");
+        }
+        return 1;
+    }
+
+    let mut all = Vec::new();
+    for path in &paths {
+        match read_value(path, None) {
+            Ok(value) => all.push(stats(&value, depth)),
+            Err(e) => {
+                eprintln!("{}", e);
+                return 1;
+            }
+        }
+    }
+
+    if diff_mode {
+        print_diff(&all[0], &all[1]);
+    } else {
+        print_stats(&all[0]);
+    }
+    0
+}
+
+fn display_path(path: &str) -> &str {
+    if path.is_empty() {
+        "(root)"
+    } else {
+        path
+    }
+}
+
+fn print_stats(s: &Stats) {
+    println!("nodes: {}", s.nodes);
+    println!("max depth: {}", s.max_depth);
+    println!(
+        "size: {} bytes",
+        s.paths.get("").map(|p| p.size).unwrap_or(0)
+    );
+    for (name, count) in &s.by_type {
+        println!("{}: {}", name, count);
+    }
+}
+
+fn print_diff(old: &Stats, new: &Stats) {
+    let deltas = diff(old, new);
+    if deltas.is_empty() {
+        println!("no changes");
+        return;
+    }
+    let width = deltas
+        .iter()
+        .map(|d| display_path(&d.path).len())
+        .max()
+        .unwrap_or(0);
+    for d in &deltas {
+        let size = |p: Option<PathStats>| p.map(|p| p.size.to_string()).unwrap_or("-".into());
+        let count = |p: Option<PathStats>| p.map(|p| p.count.to_string()).unwrap_or("-".into());
+        println!(
+            "{:width$}  size {} -> {} ({:+})  nodes {} -> {} ({:+})",
+            display_path(&d.path),
+            size(d.old),
+            size(d.new),
+            d.size_delta(),
+            count(d.old),
+            count(d.new),
+            d.count_delta(),
+            width = width
+        );
+    }
+}
@@ -0,0 +1,190 @@
+//! The CLI config file: a YAY document named by the `YAY_CONFIG`
+//! environment variable, for settings a project wants on every run.
+//!
+//! ```yay
+//! extensions:
+//!   js: "mjs"
+//!   yaml: ["yml", "yaml"]
+//! ```
+//!
+//! `extensions` gives a format's file extensions, by the format names `-f`
+//! and `-t` take. The first is the one `--write` gives output in that
+//! format, and every one is read as that format where the CLI infers a
+//! format from an extension. Formats not listed keep their usual
+//! extensions.
+
+use std::fs;
+use std::sync::OnceLock;
+
+use libyay::{parse, Format, Value};
+
+#[derive(Debug, Default)]
+pub struct Config {
+    /// The extension written for each format given one.
+    write: Vec<(Format, String)>,
+    /// The input format, as [`infer_format`](crate::commands::infer_format)
+    /// names it, for each extension given one.
+    read: Vec<(String, &'static str)>,
+}
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// Read the file `YAY_CONFIG` names, if it is set.
+pub fn load() -> Result<(), String> {
+    let Some(path) = std::env::var_os("YAY_CONFIG") else {
+        return Ok(());
+    };
+    let path = path.to_string_lossy();
+    let source =
+        fs::read_to_string(&*path).map_err(|e| format!("Error reading {}: {}", path, e))?;
+    let config = Config::from_yay(&source).map_err(|e| format!("{}: {}", path, e))?;
+    CONFIG.get_or_init(|| config);
+    Ok(())
+}
+
+/// The config file's settings, or the defaults without one.
+pub fn get() -> &'static Config {
+    CONFIG.get_or_init(Config::default)
+}
+
+/// The input format name of a format `-f` takes, if the CLI reads it.
+fn input_name(format: &str) -> Option<&'static str> {
+    Some(match format {
+        "yay" | "meh" => "meh",
+        "json" => "json",
+        "jsonc" => "jsonc",
+        "yson" => "yson",
+        "ndyay" => "ndyay",
+        "yaml" | "yml" => "yaml",
+        "toml" => "toml",
+        "cbor" => "cbor",
+        _ => return None,
+    })
+}
+
+impl Config {
+    pub fn from_yay(source: &str) -> Result<Config, String> {
+        let value = parse(source).map_err(|e| e.to_string())?;
+        let Value::Object(root) = value else {
+            return Err("the config must be an object".to_string());
+        };
+        let mut config = Config::default();
+        for (key, value) in &root {
+            match key.as_str() {
+                "extensions" => config.add_extensions(value)?,
+                _ => return Err(format!("unknown setting: {}", key)),
+            }
+        }
+        Ok(config)
+    }
+
+    fn add_extensions(&mut self, value: &Value) -> Result<(), String> {
+        let Value::Object(formats) = value else {
+            return Err("extensions must be an object of formats".to_string());
+        };
+        for (name, extensions) in formats {
+            let extensions = match extensions {
+                Value::String(extension) => vec![extension],
+                Value::Array(items) => items
+                    .iter()
+                    .map(|item| match item {
+                        Value::String(extension) => Ok(extension),
+                        _ => Err(format!("extensions of {} must be strings", name)),
+                    })
+                    .collect::<Result<_, _>>()?,
+                _ => return Err(format!("extensions of {} must be a string or array", name)),
+            };
+            if extensions.is_empty() {
+                return Err(format!("no extensions given for {}", name));
+            }
+            let format = crate::format_named(name);
+            let input = input_name(name);
+            if format.is_none() && input.is_none() {
+                return Err(format!("unknown format: {}", name));
+            }
+            for extension in &extensions {
+                let extension = extension.trim_start_matches('.');
+                if extension.is_empty() || extension.contains('/') {
+                    return Err(format!("invalid extension for {}: {:?}", name, extension));
+                }
+                if let Some(input) = input {
+                    if let Some((_, other)) = self.read.iter().find(|(e, _)| e == extension) {
+                        if *other != input {
+                            return Err(format!(
+                                "extension {} given for both {} and {}",
+                                extension, other, input
+                            ));
+                        }
+                    }
+                    self.read.push((extension.to_string(), input));
+                }
+            }
+            if let Some(format) = format {
+                let first = extensions[0].trim_start_matches('.').to_string();
+                self.write.retain(|(f, _)| *f != format);
+                self.write.push((format, first));
+            }
+        }
+        Ok(())
+    }
+
+    /// The extension `--write` gives output in `format`.
+    pub fn extension(&self, format: Format) -> &str {
+        self.write
+            .iter()
+            .find(|(f, _)| *f == format)
+            .map_or(format.extension(), |(_, extension)| extension)
+    }
+
+    /// The output format written to files ending in `extension`, if the
+    /// config file gives one.
+    pub fn output_format(&self, extension: &str) -> Option<Format> {
+        self.write
+            .iter()
+            .find(|(_, e)| e == extension)
+            .map(|(format, _)| *format)
+    }
+
+    /// The input format configured for files ending in `extension`.
+    pub fn format_of(&self, extension: &str) -> Option<&'static str> {
+        self.read
+            .iter()
+            .find(|(e, _)| e == extension)
+            .map(|(_, format)| *format)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extensions() {
+        let config = Config::from_yay(
+            "extensions:\n  js: \"mjs\"\n  yaml: [\"yml\", \"yaml\"]\n  yay: \".conf\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.extension(Format::JavaScript), "mjs");
+        assert_eq!(config.extension(Format::Yaml), "yml");
+        assert_eq!(config.extension(Format::Yay), "conf");
+        assert_eq!(config.extension(Format::Json), "json");
+        assert_eq!(config.output_format("mjs"), Some(Format::JavaScript));
+        assert_eq!(config.format_of("yml"), Some("yaml"));
+        assert_eq!(config.format_of("conf"), Some("meh"));
+        // Output-only formats have no reader.
+        assert_eq!(config.format_of("mjs"), None);
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |source: &str| Config::from_yay(source).unwrap_err();
+        assert_eq!(error("[]"), "the config must be an object");
+        assert_eq!(error("colors: true"), "unknown setting: colors");
+        assert_eq!(error("extensions:\n  xml: \"xml\""), "unknown format: xml");
+        assert_eq!(error("extensions:\n  js: []"), "no extensions given for js");
+        assert_eq!(
+            error("extensions:\n  json: \"cfg\"\n  toml: \"cfg\""),
+            "extension cfg given for both json and toml"
+        );
+    }
+}
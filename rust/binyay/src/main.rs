@@ -1,29 +1,112 @@
 //! YAY command-line tool for parsing, formatting, and transcoding YAY documents.
 //!
-//! Usage: yay [OPTIONS] [FILE|DIR]
+//! Usage: yay [OPTIONS] [FILE|DIR|URL]
+//!        yay <COMMAND> [ARGS]
+//!
+//! Commands:
+//!   batch [--fail-fast] MANIFEST       Run the conversions a manifest lists
+//!   diff [-f FORMAT] OLD [-f FORMAT] NEW
+//!                                      Print the paths whose values differ
+//!   doctor                             Check every format and path in this build
+//!   fmt [--add-checksums] [--range START:END] [-w] [FILE...]
+//!                                      Reformat documents to canonical YAY
+//!   hash FILE|DIR...                   Print the canonical hash of each document
+//!   infer [--examples N] FILE...       Report the types and values at each path
+//!   lint [--config FILE] FILE|DIR...   Check documents against lint rules
+//!   merge [--concat-arrays] [--null-deletes] [-t FORMAT] BASE OVERRIDE...
+//!                                      Deep-merge documents over a base
+//!   migrate [--to VERSION] [--migrations FILE] [-w [--force]] FILE|DIR...
+//!                                      Upgrade documents to a newer version
+//!   outline FILE                       Print the keys and items of a document
+//!   patch [--merge] [-w [--force]] PATCH FILE|DIR...
+//!                                      Apply a JSON Patch to documents
+//!   refs --rules FILE FILE|DIR...      Check references between documents
+//!   rename-key OLD.PATH NEW [-w] FILE|DIR...
+//!                                      Rename a key in every document
+//!   stamp (--set-path PATH --now|--git|--value TEXT)... [-w] FILE|DIR...
+//!                                      Set build metadata in documents
+//!   stats [--diff] [--depth N] FILE...  Print document statistics or deltas
 //!
 //! Options:
-//!       -f, --from <FORMAT>    Input format (meh, yay, json, yson, yaml, toml, cbor)
+//!       -f, --from <FORMAT>    Input format (meh, yay, json, jsonc, yson, ndyay, yaml, toml, cbor)
 //!                              [default: meh, or yay when --check]
-//!   -t, --to <FORMAT>      Output format (yay, json, yson, js, go, python, rust, c, java, scheme, yaml, toml, cbor, diag)
+//!   -t, --to <FORMAT>      Output format (yay, json, yson, ndyay, js, go, python, rust, c, java, scheme, yaml, toml, cbor, diag)
 //!   -w, --write            Write output to file with inferred name
+//!                          (extensions from the YAY_CONFIG file, if set)
 //!   -o, --output <FILE>    Write output to specified file
+//!   --template <FILE>      Write text output into FILE at {{value}}, with
+//!                          {{symbol}} and {{source_path}} for the input
+//!   --compress <FORMAT>    Compress output (none, gzip, zstd)
 //!   --check                Check if file is valid (exit 0 if valid, 1 if invalid)
 //!                          Defaults to strict YAY input; use --from meh for lenient
+//!                          Checks the .yay members of a tar or zip archive
+//!                          Without --from, checks a directory's .yay, .yaml,
+//!                          and .json files, each in its own format
+//!   --verify-checksums     With --check, verify `# sha256:` comments on block bytes
+//!   --fix                  With --check, fix uppercase hex digits and exponents,
+//!                          extra spaces after a colon, and trailing spaces in place
+//!   --fail-fast            With directory input, stop after the first failing file
+//!   --header <NAME: VALUE> With URL input, send a request header (repeatable)
+//!   --fetch-timeout <SECS> With remote input, give up after SECS seconds [default: 30]
+//!   --timeout <SECS>       Give up on the conversion after SECS seconds (exit code 3)
+//!   --head <N>             Keep the first N items of longer arrays
+//!   --tail <N>             Keep the last N items of longer arrays
+//!   --sample <N>           Keep N evenly spaced items of longer arrays
+//!   --eval                 Replace each '$eval' object with its expression's value
+//!   --decimals             Read YAY floats as exact decimals, kept to CBOR and JSON
+//!   --decimal-strings      Write exact decimals as strings, for JSON readers
+//!   --json-lossy           With -t json, write byte arrays, big integers, nan, and
+//!                          the infinities as strings or null instead of failing
+//!   --force                Convert even when comments in YAY input would be lost
+//!   --duplicate-keys <POLICY> A key repeated in one YAY object: error, first,
+//!                          last (default), or warn
+//!   --key-order <ORDER>    Order of keys in output: bytes (default), collated, or document
+//!   --no-sort-keys         Write keys in the order of the input (--key-order document)
+//!   --invalid-utf8 <POLICY> Handling of invalid UTF-8 in CBOR text (error, lossy, bytes)
+//!   --resume               With -f cbor -o FILE, checkpoint a CBOR array conversion
+//!                          and continue an interrupted one
+//!   --stream               With -f cbor, convert without holding the whole document
+//!   --max-memory <SIZE>    Give up on a conversion that needs more memory (exit code 3);
+//!                          with --stream, the memory budget [default: 64MiB]
 //!   -h, --help             Print help
 //!   -V, --version          Print version
 
+use libyay::archive::{self, ArchiveKind};
+use libyay::compress::{decompress, Compression};
+use libyay::eval::evaluate;
+use libyay::subset::Subset;
 use libyay::{
-    encode, format_yay, parse, parse_shon_bracket, parse_shon_file_bytes, parse_shon_file_string,
-    parse_shon_hex, parse_with_filename, parse_yson, Format, Value,
+    decode_bytes, encode_decimals_as_strings, encode_ordered, fix_yay, format_yay, jsonc_to_yay,
+    parse_jsonc, parse_shon_bracket, parse_shon_file_bytes, parse_shon_file_string, parse_shon_hex,
+    parse_with_report, parse_yson_with_duplicates, reveal_invisible, verify_checksums, Documents,
+    DuplicateKeys, EncodingDetection, Format, JsonCompatOptions, KeyOrder, ParseError,
+    ParseOptions, Value,
 };
+use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::fs;
-use std::io::{self, Read, Write};
-use std::path::Path;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
 use std::process;
+use std::sync::Mutex;
+use std::time::Duration;
 
+mod budget;
+mod commands;
+mod config;
+mod net;
+mod pipeline;
+mod resume;
+mod source;
+mod stream;
+mod template;
 mod transcode;
 
+use transcode::Warning;
+
+#[global_allocator]
+static ALLOCATOR: budget::Counting = budget::Counting;
+
 /// Check whether a string is a recognized format name for -f or -t.
 fn is_format_name(s: &str) -> bool {
     matches!(
@@ -31,7 +114,9 @@ fn is_format_name(s: &str) -> bool {
         "meh"
             | "yay"
             | "json"
+            | "jsonc"
             | "yson"
+            | "ndyay"
             | "js"
             | "javascript"
             | "go"
@@ -54,13 +139,36 @@ fn is_format_name(s: &str) -> bool {
 fn main() {
     let args: Vec<String> = std::env::args().collect();
 
+    if let Err(e) = config::load() {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+
+    if let Some(command) = args.get(1) {
+        if let Some(exit_code) = commands::dispatch(command, &args[2..]) {
+            process::exit(exit_code);
+        }
+    }
+
     let mut from_format: Option<&str> = None;
     let mut to_format: Option<&str> = None;
     let mut write_back = false;
     let mut output_file: Option<&str> = None;
     let mut check_only = false;
+    let mut verify_checksums = false;
+    let mut fix = false;
+    let mut fail_fast = false;
+    let mut resumable = false;
+    let mut streaming = false;
+    let mut max_memory: Option<usize> = None;
+    let mut timeout: Option<Duration> = None;
     let mut input_path: Option<&str> = None;
     let mut shon_value: Option<Value> = None;
+    let mut cbor_options = transcode::cbor::DecodeOptions::default();
+    let mut conversion = Conversion::default();
+    let mut compression = Compression::None;
+    let mut headers: Vec<String> = Vec::new();
+    let mut fetch_timeout = Duration::from_secs(30);
 
     let mut i = 1;
     while i < args.len() {
@@ -111,6 +219,179 @@ fn main() {
             "--check" => {
                 check_only = true;
             }
+            "--verify-checksums" => {
+                verify_checksums = true;
+            }
+            "--fix" => {
+                fix = true;
+            }
+            "--fail-fast" => {
+                fail_fast = true;
+            }
+            "--resume" => {
+                resumable = true;
+            }
+            "--stream" => {
+                streaming = true;
+            }
+            "--max-memory" => {
+                i += 1;
+                if i >= args.len() {
+                    eprintln!("Error: --max-memory requires a size argument");
+                    process::exit(1);
+                }
+                max_memory = match Value::String(args[i].clone()).as_byte_size() {
+                    Some(size) if size > 0 => Some(size.try_into().unwrap_or(usize::MAX)),
+                    _ => {
+                        eprintln!("Error: Invalid --max-memory: {}", args[i]);
+                        process::exit(1);
+                    }
+                };
+            }
+            "--compress" => {
+                i += 1;
+                if i >= args.len() {
+                    eprintln!("Error: --compress requires an argument");
+                    process::exit(1);
+                }
+                compression = match Compression::from_name(&args[i]) {
+                    Some(compression) => compression,
+                    None => {
+                        eprintln!("Error: Unknown compression: {}", args[i]);
+                        process::exit(1);
+                    }
+                };
+            }
+            "--header" => {
+                i += 1;
+                if i >= args.len() {
+                    eprintln!("Error: --header requires an argument");
+                    process::exit(1);
+                }
+                headers.push(args[i].clone());
+            }
+            "--timeout" => {
+                i += 1;
+                if i >= args.len() {
+                    eprintln!("Error: --timeout requires an argument");
+                    process::exit(1);
+                }
+                timeout = match args[i].parse::<f64>() {
+                    Ok(secs) if secs > 0.0 && secs.is_finite() => {
+                        Some(Duration::from_secs_f64(secs))
+                    }
+                    _ => {
+                        eprintln!("Error: Invalid --timeout: {}", args[i]);
+                        process::exit(1);
+                    }
+                };
+            }
+            "--fetch-timeout" => {
+                i += 1;
+                if i >= args.len() {
+                    eprintln!("Error: --fetch-timeout requires an argument");
+                    process::exit(1);
+                }
+                fetch_timeout = match args[i].parse::<f64>() {
+                    Ok(secs) if secs > 0.0 && secs.is_finite() => Duration::from_secs_f64(secs),
+                    _ => {
+                        eprintln!("Error: Invalid --fetch-timeout: {}", args[i]);
+                        process::exit(1);
+                    }
+                };
+            }
+            "--head" | "--tail" | "--sample" => {
+                let option = args[i].as_str();
+                i += 1;
+                if i >= args.len() {
+                    eprintln!("Error: {} requires a count", option);
+                    process::exit(1);
+                }
+                let Ok(n) = args[i].parse::<usize>() else {
+                    eprintln!("Error: Invalid {}: {}", option, args[i]);
+                    process::exit(1);
+                };
+                if conversion.subset.is_some() {
+                    eprintln!("Error: Use only one of --head, --tail, and --sample");
+                    process::exit(1);
+                }
+                conversion.subset = Some(match option {
+                    "--head" => Subset::Head(n),
+                    "--tail" => Subset::Tail(n),
+                    _ => Subset::Sample(n),
+                });
+            }
+            "--eval" => conversion.eval = true,
+            "--decimals" => conversion.decimals = true,
+            "--force" => conversion.force = true,
+            "--template" => {
+                i += 1;
+                if i >= args.len() {
+                    eprintln!("Error: --template requires a file argument");
+                    process::exit(1);
+                }
+                match template::Template::load(&args[i]) {
+                    Ok(template) => conversion.template = Some(Box::leak(Box::new(template))),
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        process::exit(1);
+                    }
+                }
+            }
+            "--duplicate-keys" => {
+                i += 1;
+                if i >= args.len() {
+                    eprintln!("Error: --duplicate-keys requires a policy argument");
+                    process::exit(1);
+                }
+                match DuplicateKeys::from_name(&args[i]) {
+                    Some(policy) => conversion.duplicate_keys = policy,
+                    None => {
+                        eprintln!(
+                            "Error: Unknown --duplicate-keys policy: {} (expected error, first, last, or warn)",
+                            args[i]
+                        );
+                        process::exit(1);
+                    }
+                }
+            }
+            "--decimal-strings" => conversion.decimal_strings = true,
+            "--json-lossy" => conversion.json_lossy = true,
+            "--no-sort-keys" => conversion.key_order = KeyOrder::Document,
+            "--key-order" => {
+                i += 1;
+                if i >= args.len() {
+                    eprintln!("Error: --key-order requires an order argument");
+                    process::exit(1);
+                }
+                match KeyOrder::from_name(&args[i]) {
+                    Some(order) => conversion.key_order = order,
+                    None => {
+                        eprintln!(
+                            "Error: Unknown --key-order: {} (expected bytes, collated, or document)",
+                            args[i]
+                        );
+                        process::exit(1);
+                    }
+                }
+            }
+            "--invalid-utf8" => {
+                i += 1;
+                if i >= args.len() {
+                    eprintln!("Error: --invalid-utf8 requires a policy argument");
+                    process::exit(1);
+                }
+                match transcode::cbor::Utf8Policy::from_name(&args[i]) {
+                    Some(policy) => cbor_options.invalid_utf8 = policy,
+                    None => {
+                        eprintln!(
+                            "Error: Unknown --invalid-utf8 policy: {} (expected error, lossy, or bytes)",
+                            args[i]
+                        );
+                        process::exit(1);
+                    }
+                }
+            }
             "-" => {
                 // Explicit stdin
                 // input_path stays None, which means stdin
@@ -226,6 +507,40 @@ fn main() {
         process::exit(1);
     }
 
+    // Remote input is read up front, so the format its source implies can
+    // stand in for --from.
+    let source_options = source::SourceOptions {
+        headers,
+        timeout: fetch_timeout,
+        max_output: max_memory.unwrap_or(libyay::compress::DEFAULT_MAX_OUTPUT),
+    };
+    let mut fetched: Option<Vec<u8>> = None;
+    if let Some(location) = input_path.filter(|path| source::is_remote(path)) {
+        if write_back {
+            eprintln!("Error: --write cannot be used with remote input");
+            process::exit(1);
+        }
+        match source::read(Some(location), &source_options) {
+            Ok(input) => {
+                if from_format.is_none() {
+                    from_format = input.format;
+                }
+                fetched = Some(input.bytes);
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                process::exit(1);
+            }
+        }
+    } else if !source_options.headers.is_empty() {
+        eprintln!("Error: --header requires URL input");
+        process::exit(1);
+    }
+
+    // Checking a directory without --from checks each file in the format
+    // its extension names.
+    let mixed_formats = check_only && from_format.is_none();
+
     // Default input format: "yay" (strict) when --check, "meh" (lenient) otherwise.
     // Can always be overridden with --from.
     let from_format = from_format.unwrap_or(if check_only { "yay" } else { "meh" });
@@ -235,12 +550,112 @@ fn main() {
         eprintln!("Error: --write and --output are mutually exclusive");
         process::exit(1);
     }
+    if verify_checksums && !check_only {
+        eprintln!("Error: --verify-checksums requires --check");
+        process::exit(1);
+    }
+    if fix && !check_only {
+        eprintln!("Error: --fix requires --check");
+        process::exit(1);
+    }
+    if fix && from_format != "yay" {
+        eprintln!("Error: --fix requires strict YAY input");
+        process::exit(1);
+    }
+    if fix && input_path.is_none() {
+        eprintln!("Error: --fix requires an input file or directory");
+        process::exit(1);
+    }
+    if fail_fast && !input_path.is_some_and(|path| Path::new(path).is_dir()) {
+        eprintln!("Error: --fail-fast requires directory input");
+        process::exit(1);
+    }
 
     // Determine output format
     // Default output is YAY (canonical form)
     let output_format_str = to_format.unwrap_or("yay");
     let output_format = parse_format(output_format_str);
 
+    if let Some(timeout) = timeout {
+        budget::limit_time(timeout);
+    }
+
+    if conversion.subset.is_some() && (resumable || streaming) {
+        eprintln!("Error: --head, --tail, and --sample cannot be used with --resume or --stream");
+        process::exit(1);
+    }
+    if conversion.eval && (resumable || streaming) {
+        eprintln!("Error: --eval cannot be used with --resume or --stream");
+        process::exit(1);
+    }
+    if conversion.template.is_some()
+        && (check_only || resumable || streaming || output_format == Format::Cbor)
+    {
+        eprintln!(
+            "Error: --template requires text output and cannot be used with --check, --resume, or --stream"
+        );
+        process::exit(1);
+    }
+
+    // Resumable conversion streams the input itself.
+    if resumable {
+        let (Some(input), Some(output)) = (input_path, output_file) else {
+            eprintln!("Error: --resume requires an input file and --output");
+            process::exit(1);
+        };
+        if from_format != "cbor" || output_format != Format::Yay || check_only {
+            eprintln!("Error: --resume only supports converting CBOR to YAY (-f cbor -t yay)");
+            process::exit(1);
+        }
+        if compression != Compression::None || source::is_remote(input) {
+            eprintln!("Error: --resume requires a local, uncompressed input and output");
+            process::exit(1);
+        }
+        match resume::convert(input, output, cbor_options) {
+            Ok(_) => process::exit(0),
+            Err(e) => {
+                eprintln!("{}", e);
+                process::exit(1);
+            }
+        }
+    }
+
+    // Streaming conversion reads the input as it goes.
+    if streaming {
+        if from_format != "cbor"
+            || !matches!(output_format, Format::Yay | Format::Json | Format::Yson)
+            || check_only
+        {
+            eprintln!("Error: --stream only supports converting CBOR to YAY, JSON, or YSON");
+            process::exit(1);
+        }
+        if write_back || compression != Compression::None {
+            eprintln!("Error: --stream cannot be used with --write or --compress");
+            process::exit(1);
+        }
+        if input_path.is_some_and(|path| source::is_remote(path) || Path::new(path).is_dir()) {
+            eprintln!("Error: --stream requires a local file or stdin");
+            process::exit(1);
+        }
+        let max_memory = max_memory.unwrap_or(stream::DEFAULT_MAX_MEMORY);
+        match stream::convert(
+            input_path,
+            output_file,
+            output_format,
+            cbor_options,
+            max_memory,
+        ) {
+            Ok(()) => process::exit(0),
+            Err(e) => {
+                eprintln!("{}", e);
+                process::exit(1);
+            }
+        }
+    }
+    if let Some(bytes) = max_memory {
+        budget::limit_memory(bytes);
+    }
+
     // SHON mode: we already have a Value, skip file reading and parsing
     if let Some(value) = shon_value {
         if check_only {
@@ -255,6 +670,8 @@ fn main() {
             output_file,
             write_back,
             None,
+            compression,
+            &conversion,
         );
         process::exit(exit_code);
     }
@@ -270,11 +687,17 @@ fn main() {
             }
             process_directory(
                 path,
-                from_format,
+                (!mixed_formats).then_some(from_format),
                 output_format_str,
                 output_format,
                 write_back,
                 check_only,
+                verify_checksums,
+                fix,
+                &cbor_options,
+                conversion,
+                compression,
+                fail_fast,
             );
             return;
         }
@@ -282,38 +705,62 @@ fn main() {
 
     // Single file mode: always read raw bytes first, then derive string as needed.
     // This avoids the double-read problem for CBOR and supports stdin uniformly.
-    let raw_bytes: Vec<u8> = match input_path {
-        Some(path) => match fs::read(path) {
-            Ok(bytes) => bytes,
+    // gzip and zstd input is decompressed first, whatever the format.
+    let raw_bytes: Vec<u8> = match fetched {
+        Some(bytes) => bytes,
+        None => match source::read(input_path, &source_options) {
+            Ok(input) => input.bytes,
             Err(e) => {
-                eprintln!("Error reading {}: {}", path, e);
+                eprintln!("{}", e);
                 process::exit(1);
             }
         },
-        None => {
-            let mut buffer = Vec::new();
-            if let Err(e) = io::stdin().read_to_end(&mut buffer) {
-                eprintln!("Error reading stdin: {}", e);
-                process::exit(1);
-            }
-            buffer
-        }
     };
 
+    // A tar or zip bundle is checked member by member, like a directory.
+    if from_format != "cbor" && ArchiveKind::detect(&raw_bytes).is_some() {
+        if !check_only {
+            eprintln!("Error: archive input is only supported with --check");
+            process::exit(1);
+        }
+        if fix {
+            eprintln!("Error: --fix cannot rewrite archive members");
+            process::exit(1);
+        }
+        let exit_code = check_archive(
+            &raw_bytes,
+            input_path.unwrap_or("<stdin>"),
+            from_format,
+            verify_checksums,
+            &cbor_options,
+        );
+        process::exit(exit_code);
+    }
+
     let is_binary_input = from_format == "cbor";
     let input: String = if is_binary_input {
         // For CBOR, the string representation is unused by the parser,
         // but process_input still takes &str, so provide an empty string.
         String::new()
     } else {
-        match String::from_utf8(raw_bytes.clone()) {
+        match decode_bytes(&raw_bytes, &text_options(from_format)) {
             Ok(s) => s,
             Err(e) => {
-                eprintln!("Error: input is not valid UTF-8: {}", e);
+                eprintln!("Error: {}", e);
                 process::exit(1);
             }
         }
     };
+    let input = match input_path {
+        Some(path) if fix => match fix_file(path, input) {
+            Ok((input, _)) => input,
+            Err(e) => {
+                eprintln!("{}", e);
+                process::exit(1);
+            }
+        },
+        _ => input,
+    };
 
     let input_bytes: Option<&[u8]> = if is_binary_input {
         Some(&raw_bytes)
@@ -331,15 +778,78 @@ fn main() {
         output_file,
         write_back,
         check_only,
+        verify_checksums,
+        &cbor_options,
+        conversion,
+        compression,
     );
     process::exit(exit_code);
 }
 
+/// Options that change a document between reading and writing it.
+#[derive(Debug, Clone, Copy, Default)]
+struct Conversion {
+    /// `--decimals`: read YAY floats written in decimal as exact decimals.
+    decimals: bool,
+    /// `--decimal-strings`: write decimals as strings of their digits.
+    decimal_strings: bool,
+    /// `--json-lossy`: map what JSON cannot represent to what it can.
+    json_lossy: bool,
+    /// `--head`, `--tail`, or `--sample`: keep a few items of long arrays.
+    subset: Option<Subset>,
+    /// `--eval`: replace `$eval` objects with the values they compute.
+    eval: bool,
+    /// `--force`: convert even when the input's comments would be lost.
+    force: bool,
+    /// `--duplicate-keys`: what to do with a key repeated in one object.
+    duplicate_keys: DuplicateKeys,
+    /// `--key-order`: the order of object keys in encoded YAY output.
+    key_order: KeyOrder,
+    /// `--template`: a file to write text output into. It is read once and
+    /// kept for the whole run.
+    template: Option<&'static template::Template>,
+}
+
+impl Conversion {
+    /// Text output for `input_file`, written into the template if given.
+    fn render<'a>(&self, output: &'a str, input_file: Option<&str>) -> Cow<'a, str> {
+        match self.template {
+            Some(template) => Cow::Owned(template.render(output, input_file)),
+            None => Cow::Borrowed(output),
+        }
+    }
+}
+
+/// Options for decoding text input. Strict YAY keeps the spec's rule that
+/// a document is UTF-8 without a byte order mark; other formats accept a
+/// BOM and UTF-16/32.
+fn text_options(from_format: &str) -> ParseOptions {
+    let mut options = ParseOptions::default();
+    options.encoding = if from_format == "yay" {
+        EncodingDetection::Utf8Only
+    } else {
+        EncodingDetection::Detect
+    };
+    options
+}
+
 fn parse_format(s: &str) -> Format {
-    match s {
+    match format_named(s) {
+        Some(format) => format,
+        None => {
+            eprintln!("Error: Unknown format: {}", s);
+            process::exit(1);
+        }
+    }
+}
+
+/// The output format named `s`, as `-t` takes it.
+fn format_named(s: &str) -> Option<Format> {
+    Some(match s {
         "yay" | "meh" => Format::Yay,
         "json" => Format::Json,
         "yson" => Format::Yson,
+        "ndyay" => Format::Ndyay,
         "js" | "javascript" => Format::JavaScript,
         "go" => Format::Go,
         "python" | "py" => Format::Python,
@@ -351,39 +861,39 @@ fn parse_format(s: &str) -> Format {
         "toml" => Format::Toml,
         "cbor" => Format::Cbor,
         "diag" => Format::CborDiag,
-        _ => {
-            eprintln!("Error: Unknown format: {}", s);
-            process::exit(1);
-        }
-    }
+        _ => return None,
+    })
 }
 
-fn format_extension(format: Format) -> &'static str {
-    match format {
-        Format::Yay => "yay",
-        Format::Json => "json",
-        Format::Yson => "yson",
-        Format::JavaScript => "js",
-        Format::Go => "go",
-        Format::Python => "py",
-        Format::Rust => "rs",
-        Format::C => "c",
-        Format::Java => "java",
-        Format::Scheme => "scm",
-        Format::Yaml => "yaml",
-        Format::Toml => "toml",
-        Format::Cbor => "cbor",
-        Format::CborDiag => "diag",
+/// The format to check a file of a directory in when no `--from` is given:
+/// strict YAY, YAML, or JSON, by extension. Other files are skipped.
+fn check_format(path: &Path) -> Option<&'static str> {
+    if commands::is_yay_path(path) {
+        return Some("yay");
+    }
+    match commands::infer_format(&path.to_string_lossy()) {
+        "yaml" => Some("yaml"),
+        "json" => Some("json"),
+        _ => None,
     }
 }
 
+/// Process the files of a directory in `from_format`, or with `None`,
+/// check each file in its [`check_format`].
+#[allow(clippy::too_many_arguments)]
 fn process_directory(
     dir_path: &str,
-    from_format: &str,
+    from_format: Option<&str>,
     output_format_str: &str,
     output_format: Format,
     write_back: bool,
     check_only: bool,
+    verify_checksums: bool,
+    fix: bool,
+    cbor_options: &transcode::cbor::DecodeOptions,
+    conversion: Conversion,
+    compression: Compression,
+    fail_fast: bool,
 ) {
     let entries = match fs::read_dir(dir_path) {
         Ok(e) => e,
@@ -393,40 +903,172 @@ fn process_directory(
         }
     };
 
-    let mut had_errors = false;
+    let mut paths: Vec<String> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| match from_format {
+            Some(_) => commands::is_yay_path(path),
+            None => check_format(path).is_some(),
+        })
+        .map(|path| path.to_string_lossy().to_string())
+        .collect();
+    paths.sort();
 
-    for entry in entries.flatten() {
-        let path = entry.path();
-        if path.extension().map(|e| e == "yay").unwrap_or(false) {
-            let path_str = path.to_string_lossy();
-            let input = match fs::read_to_string(&path) {
+    // Files checked and failed, by format, when checking mixed formats.
+    let by_format: Mutex<BTreeMap<&str, (usize, usize)>> = Mutex::new(BTreeMap::new());
+
+    let summary = pipeline::run(&paths, pipeline::default_jobs(), fail_fast, |path_str| {
+        let from_format = match from_format {
+            Some(format) => format,
+            None => check_format(Path::new(path_str)).unwrap_or("yay"),
+        };
+        let input = match commands::read_file(path_str) {
+            Ok(bytes) => match decode_bytes(&bytes, &text_options(from_format)) {
                 Ok(content) => content,
                 Err(e) => {
-                    eprintln!("Error reading {}: {}", path_str, e);
-                    had_errors = true;
-                    continue;
+                    eprintln!("{}: {}", path_str, e);
+                    return pipeline::Outcome::Failed;
                 }
-            };
+            },
+            Err(e) => {
+                eprintln!("{}", e);
+                return pipeline::Outcome::Failed;
+            }
+        };
+        let (input, fixed) = if fix && from_format == "yay" {
+            match fix_file(path_str, input) {
+                Ok(fixed) => fixed,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    return pipeline::Outcome::Failed;
+                }
+            }
+        } else {
+            (input, false)
+        };
 
-            let exit_code = process_input(
-                &input,
-                None,
-                Some(&path_str),
-                from_format,
-                output_format_str,
-                output_format,
-                None,
-                write_back,
-                check_only,
-            );
+        // --write counts as a reformat only when it changes what is on disk.
+        let target = write_back.then(|| write_back_path(path_str, output_format, compression));
+        let before = target.as_ref().map(|target| fs::read(target).ok());
+
+        let exit_code = process_input(
+            &input,
+            None,
+            Some(path_str),
+            from_format,
+            output_format_str,
+            output_format,
+            None,
+            write_back,
+            check_only,
+            verify_checksums,
+            cbor_options,
+            conversion,
+            compression,
+        );
+
+        if let Ok(mut by_format) = by_format.lock() {
+            let (checked, failed) = by_format.entry(from_format).or_default();
+            *checked += 1;
+            *failed += usize::from(exit_code != 0);
+        }
+
+        if exit_code != 0 {
+            pipeline::Outcome::Failed
+        } else if fixed || target.is_some_and(|target| before != Some(fs::read(target).ok())) {
+            pipeline::Outcome::Reformatted
+        } else {
+            pipeline::Outcome::Unchanged
+        }
+    });
 
-            if exit_code != 0 {
+    eprintln!("{}", summary);
+    if from_format.is_none() {
+        let by_format = by_format.into_inner().unwrap_or_default();
+        let counts: Vec<String> = by_format
+            .iter()
+            .map(|(format, (checked, failed))| {
+                format!("{} {} ({} failed)", checked, format, failed)
+            })
+            .collect();
+        if !counts.is_empty() {
+            eprintln!("by format: {}", counts.join(", "));
+        }
+    }
+    process::exit(if summary.success() { 0 } else { 1 });
+}
+
+/// Check every `.yay` member of a tar or zip archive without extracting it.
+/// Members are reported as `ARCHIVE:MEMBER`.
+fn check_archive(
+    bytes: &[u8],
+    archive_path: &str,
+    from_format: &str,
+    verify_checksums: bool,
+    cbor_options: &transcode::cbor::DecodeOptions,
+) -> i32 {
+    let members = match archive::members(bytes) {
+        Ok(members) => members,
+        Err(e) => {
+            eprintln!("{}: {}", archive_path, e);
+            return 1;
+        }
+    };
+
+    let mut had_errors = false;
+
+    for member in members {
+        let member = match member {
+            Ok(member) => member,
+            Err(e) => {
+                eprintln!("{}: {}", archive_path, e);
                 had_errors = true;
+                continue;
             }
+        };
+        if !commands::is_yay_path(Path::new(&member.name)) {
+            continue;
+        }
+        let label = format!("{}:{}", archive_path, member.name);
+        let input = match decompress(&member.data)
+            .map_err(|e| e.to_string())
+            .and_then(|data| {
+                decode_bytes(&data, &text_options(from_format)).map_err(|e| e.to_string())
+            }) {
+            Ok(content) => content,
+            Err(e) => {
+                eprintln!("{}: {}", label, e);
+                had_errors = true;
+                continue;
+            }
+        };
+
+        let exit_code = process_input(
+            &input,
+            None,
+            Some(&label),
+            from_format,
+            "yay",
+            Format::Yay,
+            None,
+            false,
+            true,
+            verify_checksums,
+            cbor_options,
+            Conversion::default(),
+            Compression::None,
+        );
+
+        if exit_code != 0 {
+            had_errors = true;
         }
     }
 
-    process::exit(if had_errors { 1 } else { 0 });
+    if had_errors {
+        1
+    } else {
+        0
+    }
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -440,6 +1082,10 @@ fn process_input(
     output_file: Option<&str>,
     write_back: bool,
     check_only: bool,
+    verify_checksums: bool,
+    cbor_options: &transcode::cbor::DecodeOptions,
+    conversion: Conversion,
+    compression: Compression,
 ) -> i32 {
     let filename = input_file.map(|p| {
         Path::new(p)
@@ -448,25 +1094,79 @@ fn process_input(
             .unwrap_or_else(|| p.to_string())
     });
 
-    // For strict YAY mode (--from yay), validate with strict parser first
-    if from_format == "yay" {
-        match parse_with_filename(input, filename.as_deref()) {
-            Ok(_) => {
-                // Strict parse succeeded, continue to MEH processing
+    // For strict YAY mode (--from yay), validate with strict parser first,
+    // and look for repeated keys unless the last one simply wins.
+    let mut repeated_keys = false;
+    if from_format == "yay"
+        || (from_format == "meh" && conversion.duplicate_keys != DuplicateKeys::LastWins)
+    {
+        let mut options = ParseOptions::default();
+        options.filename = filename.clone();
+        options.version_directive = from_format == "meh";
+        options.duplicate_keys = match conversion.duplicate_keys {
+            DuplicateKeys::Error => DuplicateKeys::Error,
+            _ => DuplicateKeys::Warn,
+        };
+        // MEH input that is not already YAY is checked in its canonical
+        // form, whose lines may not match the input's.
+        let mut report = parse_with_report(input, &options);
+        let mut canonical = false;
+        if from_format == "meh" && !matches!(report, Ok(_) | Err(ParseError::DuplicateKey(..))) {
+            if let Ok(formatted) = format_yay(input) {
+                report = parse_with_report(&formatted, &options);
+                canonical = true;
             }
-            Err(e) => {
+        }
+        match report {
+            Ok((_, report)) => {
+                repeated_keys = !report.duplicate_keys.is_empty();
+                if conversion.duplicate_keys == DuplicateKeys::Warn {
+                    let warnings: Vec<Warning> = report
+                        .duplicate_keys
+                        .iter()
+                        .map(|d| Warning {
+                            line: (!canonical).then_some(d.line + 1),
+                            message: format!(
+                                "duplicate key \"{}\" (first at line {}); last value wins",
+                                d.key,
+                                d.first_line + 1
+                            ),
+                        })
+                        .collect();
+                    print_warnings(&warnings, input_file);
+                }
+            }
+            Err(e)
+                if from_format == "yay"
+                    || canonical
+                    || matches!(e, ParseError::DuplicateKey(..)) =>
+            {
                 if let Some(path) = input_file {
                     eprintln!("{}: {}", path, e);
                 } else {
                     eprintln!("Parse error: {}", e);
                 }
+                if !canonical {
+                    print_invisible(input, &e);
+                    if let Some(suggestion) = e.suggestion(input) {
+                        eprintln!("help: {}", suggestion);
+                    }
+                }
                 return 1;
             }
+            Err(_) => {}
         }
     }
 
     // For --check mode, just validate
     if check_only {
+        if verify_checksums
+            && (from_format == "yay" || from_format == "meh")
+            && !report_checksums(input, input_file)
+        {
+            return 1;
+        }
+
         // For strict YAY, we already validated above
         if from_format == "yay" {
             if let Some(path) = input_file {
@@ -497,7 +1197,29 @@ fn process_input(
 
         // For JSON/YSON, validate with YSON parser
         if from_format == "json" || from_format == "yson" {
-            match parse_yson(input) {
+            match parse_yson_with_duplicates(input) {
+                Ok((_, duplicates)) => {
+                    let warnings: Vec<Warning> = duplicates.iter().map(Warning::from).collect();
+                    print_warnings(&warnings, input_file);
+                    if let Some(path) = input_file {
+                        println!("{}: ok", path);
+                    }
+                    return 0;
+                }
+                Err(e) => {
+                    if let Some(path) = input_file {
+                        eprintln!("{}: {}", path, e);
+                    } else {
+                        eprintln!("{}", e);
+                    }
+                    return 1;
+                }
+            }
+        }
+
+        // For JSONC, validate by converting
+        if from_format == "jsonc" {
+            match jsonc_to_yay(input) {
                 Ok(_) => {
                     if let Some(path) = input_file {
                         println!("{}: ok", path);
@@ -517,8 +1239,9 @@ fn process_input(
 
         // For YAML/TOML/CBOR, validate by parsing
         if from_format == "yaml" || from_format == "yml" {
-            match transcode::yaml::decode(input) {
-                Ok(_) => {
+            match transcode::yaml::decode_with_warnings(input) {
+                Ok((_, warnings)) => {
+                    print_warnings(&warnings, input_file);
                     if let Some(path) = input_file {
                         println!("{}: ok", path);
                     }
@@ -526,7 +1249,7 @@ fn process_input(
                 }
                 Err(e) => {
                     if let Some(path) = input_file {
-                        eprintln!("{}: {}", path, e);
+                        eprintln!("{}", e.in_file(path));
                     } else {
                         eprintln!("{}", e);
                     }
@@ -536,8 +1259,9 @@ fn process_input(
         }
 
         if from_format == "toml" {
-            match transcode::toml::decode(input) {
-                Ok(_) => {
+            match transcode::toml::decode_with_warnings(input) {
+                Ok((_, warnings)) => {
+                    print_warnings(&warnings, input_file);
                     if let Some(path) = input_file {
                         println!("{}: ok", path);
                     }
@@ -545,7 +1269,7 @@ fn process_input(
                 }
                 Err(e) => {
                     if let Some(path) = input_file {
-                        eprintln!("{}: {}", path, e);
+                        eprintln!("{}", e.in_file(path));
                     } else {
                         eprintln!("{}", e);
                     }
@@ -556,7 +1280,7 @@ fn process_input(
 
         if from_format == "cbor" {
             let bytes = input_bytes.unwrap_or(input.as_bytes());
-            match transcode::cbor::decode(bytes) {
+            match transcode::cbor::decode_with_options(bytes, cbor_options) {
                 Ok(_) => {
                     if let Some(path) = input_file {
                         println!("{}: ok", path);
@@ -565,7 +1289,7 @@ fn process_input(
                 }
                 Err(e) => {
                     if let Some(path) = input_file {
-                        eprintln!("{}: {}", path, e);
+                        eprintln!("{}", e.in_file(path));
                     } else {
                         eprintln!("{}", e);
                     }
@@ -575,8 +1299,14 @@ fn process_input(
         }
     }
 
-    // Special case: YAY/MEH to YAY uses MEH formatter to preserve comments/key order
-    if (from_format == "yay" || from_format == "meh") && output_format_str == "yay" {
+    // Special case: YAY/MEH to YAY uses MEH formatter to preserve comments/key order,
+    // unless keeping the first of a repeated key means rewriting the value
+    if (from_format == "yay" || from_format == "meh")
+        && output_format_str == "yay"
+        && conversion.subset.is_none()
+        && !conversion.eval
+        && !(repeated_keys && conversion.duplicate_keys == DuplicateKeys::FirstWins)
+    {
         let output = match format_yay(input) {
             Ok(s) => s,
             Err(e) => {
@@ -589,14 +1319,60 @@ fn process_input(
             }
         };
 
-        write_text_output(&output, output_file, write_back, input_file, output_format);
+        write_text_output(
+            &conversion.render(&output, input_file),
+            output_file,
+            write_back,
+            input_file,
+            output_format,
+            compression,
+        );
+        return 0;
+    }
+
+    // Special case: JSONC to YAY goes through the MEH CST to keep comments
+    if from_format == "jsonc"
+        && output_format_str == "yay"
+        && conversion.subset.is_none()
+        && !conversion.eval
+    {
+        let output = match jsonc_to_yay(input) {
+            Ok(s) => s,
+            Err(e) => {
+                if let Some(path) = input_file {
+                    eprintln!("{}: {}", path, e);
+                } else {
+                    eprintln!("Parse error: {}", e);
+                }
+                return 1;
+            }
+        };
+
+        write_text_output(
+            &conversion.render(&output, input_file),
+            output_file,
+            write_back,
+            input_file,
+            output_format,
+            compression,
+        );
         return 0;
     }
 
     // Parse input for other conversions
-    let value: Value = match from_format {
-        "yay" => match parse(input) {
-            Ok(v) => v,
+    let mut parse_options = ParseOptions::default();
+    parse_options.decimals = conversion.decimals;
+    parse_options.duplicate_keys = conversion.duplicate_keys;
+    // MEH keeps a `%yay` directive in the canonical YAY it is read through.
+    parse_options.version_directive = from_format == "meh";
+    // Comments in YAY input do not survive the trip through a Value.
+    let mut had_comments = false;
+    let mut value: Value = match from_format {
+        "yay" => match parse_with_report(input, &parse_options) {
+            Ok((v, report)) => {
+                had_comments = report.had_comments;
+                v
+            }
             Err(e) => {
                 if let Some(path) = input_file {
                     eprintln!("{}: {}", path, e);
@@ -606,34 +1382,52 @@ fn process_input(
                 return 1;
             }
         },
-        "meh" => {
-            // For MEH input, first format to canonical YAY, then parse
-            let canonical = match format_yay(input) {
-                Ok(s) => s,
-                Err(e) => {
-                    if let Some(path) = input_file {
-                        eprintln!("{}: {}", path, e);
-                    } else {
-                        eprintln!("Format error: {}", e);
+        // Formatting rewrites floats in their shortest form, so with
+        // --decimals, input that is already valid YAY is parsed as written.
+        "meh" => match conversion
+            .decimals
+            .then(|| parse_with_report(input, &parse_options))
+        {
+            Some(Ok((v, report))) => {
+                had_comments = report.had_comments;
+                v
+            }
+            _ => {
+                // For MEH input, first format to canonical YAY, then parse
+                let canonical = match format_yay(input) {
+                    Ok(s) => s,
+                    Err(e) => {
+                        if let Some(path) = input_file {
+                            eprintln!("{}: {}", path, e);
+                        } else {
+                            eprintln!("Format error: {}", e);
+                        }
+                        return 1;
                     }
-                    return 1;
-                }
-            };
-            match parse(&canonical) {
-                Ok(v) => v,
-                Err(e) => {
-                    // This shouldn't happen if format_yay succeeded
-                    if let Some(path) = input_file {
-                        eprintln!("{}: {}", path, e);
-                    } else {
-                        eprintln!("Parse error: {}", e);
+                };
+                match parse_with_report(&canonical, &parse_options) {
+                    Ok((v, report)) => {
+                        had_comments = report.had_comments;
+                        v
+                    }
+                    Err(e) => {
+                        // This shouldn't happen if format_yay succeeded
+                        if let Some(path) = input_file {
+                            eprintln!("{}: {}", path, e);
+                        } else {
+                            eprintln!("Parse error: {}", e);
+                        }
+                        return 1;
                     }
-                    return 1;
                 }
             }
-        }
-        "json" | "yson" => match parse_yson(input) {
-            Ok(v) => v,
+        },
+        "json" | "yson" => match parse_yson_with_duplicates(input) {
+            Ok((v, duplicates)) => {
+                let warnings: Vec<Warning> = duplicates.iter().map(Warning::from).collect();
+                print_warnings(&warnings, input_file);
+                v
+            }
             Err(e) => {
                 if let Some(path) = input_file {
                     eprintln!("{}: {}", path, e);
@@ -643,7 +1437,7 @@ fn process_input(
                 return 1;
             }
         },
-        "yaml" | "yml" => match transcode::yaml::decode(input) {
+        "jsonc" => match parse_jsonc(input) {
             Ok(v) => v,
             Err(e) => {
                 if let Some(path) = input_file {
@@ -654,24 +1448,57 @@ fn process_input(
                 return 1;
             }
         },
-        "toml" => match transcode::toml::decode(input) {
-            Ok(v) => v,
+        "yaml" | "yml" => match transcode::yaml::decode_with_warnings(input) {
+            Ok((v, warnings)) => {
+                print_warnings(&warnings, input_file);
+                v
+            }
             Err(e) => {
                 if let Some(path) = input_file {
-                    eprintln!("{}: {}", path, e);
+                    eprintln!("{}", e.in_file(path));
                 } else {
                     eprintln!("Parse error: {}", e);
                 }
                 return 1;
             }
         },
+        "toml" => match transcode::toml::decode_with_warnings(input) {
+            Ok((v, warnings)) => {
+                print_warnings(&warnings, input_file);
+                v
+            }
+            Err(e) => {
+                if let Some(path) = input_file {
+                    eprintln!("{}", e.in_file(path));
+                } else {
+                    eprintln!("Parse error: {}", e);
+                }
+                return 1;
+            }
+        },
+        // Each record of newline-delimited YAY becomes an item of an array.
+        "ndyay" => {
+            let mut options = parse_options.clone();
+            options.filename = input_file.map(str::to_string);
+            match Documents::new(input.as_bytes(), &options).collect() {
+                Ok(items) => Value::Array(items),
+                Err(e) => {
+                    if let Some(path) = input_file {
+                        eprintln!("{}: {}", path, e);
+                    } else {
+                        eprintln!("Parse error: {}", e);
+                    }
+                    return 1;
+                }
+            }
+        }
         "cbor" => {
             let bytes = input_bytes.unwrap_or(input.as_bytes());
-            match transcode::cbor::decode(bytes) {
+            match transcode::cbor::decode_with_options(bytes, cbor_options) {
                 Ok(v) => v,
                 Err(e) => {
                     if let Some(path) = input_file {
-                        eprintln!("{}: {}", path, e);
+                        eprintln!("{}", e.in_file(path));
                     } else {
                         eprintln!("Parse error: {}", e);
                     }
@@ -693,41 +1520,95 @@ fn process_input(
         return 0;
     }
 
-    // Check for JSON incompatibility
-    if output_format == Format::Json {
-        if let Some(reason) = value.json_incompatibility() {
-            eprintln!(
-                "Error: Cannot convert to JSON because the document contains {}.",
-                reason
-            );
-            eprintln!("Hint: Try using YSON format instead (-t yson), which supports these types.");
+    if conversion.eval {
+        if let Err(e) = evaluate(&mut value) {
+            match input_file {
+                Some(path) => eprintln!("{}: {}", path, e),
+                None => eprintln!("Error: {}", e),
+            }
             return 1;
         }
     }
 
+    // The arrays cut short are recorded as comments at the top of the
+    // output, or reported on stderr for formats without comments.
+    let mut truncations = String::new();
+    if let Some(subset) = conversion.subset {
+        let prefix = input_file.map(|p| format!("{}: ", p)).unwrap_or_default();
+        for cut in subset.apply(&mut value) {
+            let path = if cut.path.is_empty() {
+                "(root)"
+            } else {
+                &cut.path
+            };
+            let note = format!("{}: kept {} of {} items", path, subset.limit(), cut.length);
+            match line_comment(output_format) {
+                Some(marker) => truncations.push_str(&format!("{} {}\n", marker, note)),
+                None => eprintln!("{}{}", prefix, note),
+            }
+        }
+    }
+
+    if conversion.json_lossy && output_format == Format::Json {
+        value = value.to_json_lossy(&JsonCompatOptions::default());
+    } else if check_compatibility(&value, output_format) {
+        return 1;
+    }
+
+    if !comments_may_be_lost(
+        output_format_str,
+        output_format,
+        input_file,
+        had_comments,
+        conversion.force,
+    ) {
+        return 1;
+    }
+
     // Handle output formats that need special treatment
     match output_format {
-        Format::Yaml => match transcode::yaml::encode(&value) {
+        Format::Yaml => match transcode::yaml::encode(&value, conversion.key_order) {
             Ok(output) => {
-                write_text_output(&output, output_file, write_back, input_file, output_format);
+                write_text_output(
+                    &(truncations + &conversion.render(&output, input_file)),
+                    output_file,
+                    write_back,
+                    input_file,
+                    output_format,
+                    compression,
+                );
             }
             Err(e) => {
                 eprintln!("Error: Cannot convert to YAML: {}", e);
                 return 1;
             }
         },
-        Format::Toml => match transcode::toml::encode(&value) {
+        Format::Toml => match transcode::toml::encode(&value, conversion.key_order) {
             Ok(output) => {
-                write_text_output(&output, output_file, write_back, input_file, output_format);
+                write_text_output(
+                    &(truncations + &conversion.render(&output, input_file)),
+                    output_file,
+                    write_back,
+                    input_file,
+                    output_format,
+                    compression,
+                );
             }
             Err(e) => {
                 eprintln!("Error: Cannot convert to TOML: {}", e);
                 return 1;
             }
         },
-        Format::Cbor => match transcode::cbor::encode(&value) {
+        Format::Cbor => match transcode::cbor::encode_ordered(&value, conversion.key_order) {
             Ok(bytes) => {
-                write_binary_output(&bytes, output_file, write_back, input_file, output_format);
+                write_binary_output(
+                    &bytes,
+                    output_file,
+                    write_back,
+                    input_file,
+                    output_format,
+                    compression,
+                );
             }
             Err(e) => {
                 eprintln!("Error: Cannot convert to CBOR: {}", e);
@@ -737,15 +1618,16 @@ fn process_input(
         Format::CborDiag => {
             // Encode to CBOR bytes first, then render as diagnostic notation.
             // This ensures the diagnostic output reflects the actual wire encoding.
-            match transcode::cbor::encode(&value) {
+            match transcode::cbor::encode_ordered(&value, conversion.key_order) {
                 Ok(bytes) => match transcode::cbor::diagnostic(&bytes) {
                     Ok(output) => {
                         write_text_output(
-                            &output,
+                            &(truncations + &conversion.render(&output, input_file)),
                             output_file,
                             write_back,
                             input_file,
                             output_format,
+                            compression,
                         );
                     }
                     Err(e) => {
@@ -761,16 +1643,170 @@ fn process_input(
         }
         _ => {
             // Use libyay's encode for all other formats
-            let output = encode(&value, output_format);
-            write_text_output(&output, output_file, write_back, input_file, output_format);
+            let output = if conversion.decimal_strings {
+                encode_decimals_as_strings(&value, output_format)
+            } else {
+                encode_ordered(&value, output_format, conversion.key_order)
+            };
+            write_text_output(
+                &(truncations + &conversion.render(&output, input_file)),
+                output_file,
+                write_back,
+                input_file,
+                output_format,
+                compression,
+            );
         }
     }
 
     0
 }
 
+/// The marker that starts a line comment in `format`, if it has one.
+fn line_comment(format: Format) -> Option<&'static str> {
+    match format {
+        Format::Yay | Format::Yaml | Format::Toml | Format::Python => Some("#"),
+        Format::JavaScript | Format::Go | Format::Rust | Format::C | Format::Java => Some("//"),
+        Format::Scheme => Some(";;"),
+        _ => None,
+    }
+}
+
+/// Report every value that the output format cannot represent, returning
+/// `true` if there were any. YAML conversion is lossy rather than failing,
+/// so it is not checked.
+fn check_compatibility(value: &Value, output_format: Format) -> bool {
+    let name = match output_format {
+        Format::Json => "JSON",
+        Format::Toml => "TOML",
+        Format::Cbor | Format::CborDiag => "CBOR",
+        _ => return false,
+    };
+    let found = value.compatibility(output_format);
+    if found.is_empty() {
+        return false;
+    }
+    eprintln!("Error: Cannot convert to {}:", name);
+    for incompatibility in &found {
+        eprintln!("  {}", incompatibility);
+    }
+    if output_format == Format::Json {
+        eprintln!("Hint: Try using YSON format instead (-t yson), which supports these types.");
+    }
+    true
+}
+
+/// Report every block byte array whose bytes do not match its
+/// `# sha256:` comment, returning `true` if all checksums matched.
+fn report_checksums(input: &str, input_file: Option<&str>) -> bool {
+    let prefix = input_file.map(|p| format!("{}:", p)).unwrap_or_default();
+    let mismatches = verify_checksums(input);
+    for m in &mismatches {
+        eprintln!(
+            "{}{}: error: sha256 checksum mismatch (comment says {}, bytes hash to {})",
+            prefix, m.line, m.expected, m.actual
+        );
+    }
+    mismatches.is_empty()
+}
+
+/// Print each warning on stderr with its file and line prefix.
+fn print_warnings(warnings: &[Warning], input_file: Option<&str>) {
+    let prefix = input_file.map(|p| format!("{}:", p)).unwrap_or_default();
+    for w in warnings {
+        match w.line {
+            Some(line) => eprintln!("{}{}: warning: {}", prefix, line, w.message),
+            None if prefix.is_empty() => eprintln!("warning: {}", w.message),
+            None => eprintln!("{} warning: {}", prefix, w.message),
+        }
+    }
+}
+
+/// Show the line a YAY parse error is on with its tabs, no-break spaces,
+/// and other invisible characters marked, when it has any, and point to the
+/// column of the error.
+fn print_invisible(input: &str, error: &ParseError) {
+    let (line, col) = match error.location() {
+        Some((line, col)) => (line, Some(col)),
+        // Without a filename the message has no location, but a forbidden
+        // character is on the first line with one.
+        None if matches!(
+            error,
+            ParseError::TabNotAllowed(_)
+                | ParseError::ForbiddenCodePoint(..)
+                | ParseError::IllegalSurrogate(_)
+        ) =>
+        {
+            match input
+                .split('\n')
+                .position(|l| reveal_invisible(l).is_some())
+            {
+                Some(index) => (index + 1, None),
+                None => return,
+            }
+        }
+        None => return,
+    };
+    let Some(revealed) = input.split('\n').nth(line - 1).and_then(reveal_invisible) else {
+        return;
+    };
+    let col = col.unwrap_or_else(|| {
+        let found = &revealed.found;
+        revealed
+            .text
+            .chars()
+            .position(|c| found.iter().any(|f| f.mark == c))
+            .map_or(1, |i| i + 1)
+    });
+    let gutter = " ".repeat(line.to_string().len());
+    eprintln!("{} | {}", line, revealed.text);
+    eprintln!("{} | {}^", gutter, " ".repeat(col - 1));
+    for invisible in &revealed.found {
+        eprintln!("{} = {}", gutter, invisible.describe());
+    }
+}
+
+/// Report that converting the input to `output_format_str` drops its
+/// comments, if it `had_comments` and `--force` was not given. YAY output
+/// would be expected to keep them, so it is refused, as every write of YAY
+/// through a `Value` is; other formats have no place for them, so they get
+/// a warning. Returns whether to go on.
+fn comments_may_be_lost(
+    output_format_str: &str,
+    output_format: Format,
+    input_file: Option<&str>,
+    had_comments: bool,
+    force: bool,
+) -> bool {
+    if output_format == Format::Yay {
+        let kept = commands::keep_comments(
+            input_file,
+            had_comments,
+            force,
+            "use -t yay without --head, --tail, or --sample to keep them",
+        );
+        if let Err(e) = &kept {
+            eprintln!("{}", e);
+        }
+        return kept.is_ok();
+    }
+    if !had_comments || force {
+        return true;
+    }
+    let warning = Warning {
+        line: None,
+        message: format!(
+            "comments in the input are dropped converting to {}",
+            output_format_str
+        ),
+    };
+    print_warnings(&[warning], input_file);
+    true
+}
+
 /// Output a Value that was already parsed (e.g. from SHON).
 /// This skips the parse phase and goes straight to encoding/output.
+#[allow(clippy::too_many_arguments)]
 fn output_value(
     value: &Value,
     output_format_str: &str,
@@ -778,59 +1814,94 @@ fn output_value(
     output_file: Option<&str>,
     write_back: bool,
     input_file: Option<&str>,
+    compression: Compression,
+    conversion: &Conversion,
 ) -> i32 {
     // For SHON → YAY, encode via the standard encoder
     if output_format_str == "yay" {
-        let output = encode(value, Format::Yay);
-        write_text_output(&output, output_file, write_back, input_file, output_format);
+        let output = encode_ordered(value, Format::Yay, conversion.key_order);
+        write_text_output(
+            &conversion.render(&output, input_file),
+            output_file,
+            write_back,
+            input_file,
+            output_format,
+            compression,
+        );
         return 0;
     }
 
-    // Check for JSON incompatibility
-    if output_format == Format::Json {
-        if let Some(reason) = value.json_incompatibility() {
-            eprintln!(
-                "Error: Cannot convert to JSON because the document contains {}.",
-                reason
-            );
-            eprintln!("Hint: Try using YSON format instead (-t yson), which supports these types.");
-            return 1;
-        }
-    }
+    let lossy;
+    let value = if conversion.json_lossy && output_format == Format::Json {
+        lossy = value.to_json_lossy(&JsonCompatOptions::default());
+        &lossy
+    } else if check_compatibility(value, output_format) {
+        return 1;
+    } else {
+        value
+    };
 
     // Handle output formats that need special treatment
     match output_format {
-        Format::Yaml => match transcode::yaml::encode(value) {
+        Format::Yaml => match transcode::yaml::encode(value, conversion.key_order) {
             Ok(output) => {
-                write_text_output(&output, output_file, write_back, input_file, output_format);
+                write_text_output(
+                    &conversion.render(&output, input_file),
+                    output_file,
+                    write_back,
+                    input_file,
+                    output_format,
+                    compression,
+                );
             }
             Err(e) => {
                 eprintln!("Error: Cannot convert to YAML: {}", e);
                 return 1;
             }
         },
-        Format::Toml => match transcode::toml::encode(value) {
+        Format::Toml => match transcode::toml::encode(value, conversion.key_order) {
             Ok(output) => {
-                write_text_output(&output, output_file, write_back, input_file, output_format);
+                write_text_output(
+                    &conversion.render(&output, input_file),
+                    output_file,
+                    write_back,
+                    input_file,
+                    output_format,
+                    compression,
+                );
             }
             Err(e) => {
                 eprintln!("Error: Cannot convert to TOML: {}", e);
                 return 1;
             }
         },
-        Format::Cbor => match transcode::cbor::encode(value) {
+        Format::Cbor => match transcode::cbor::encode_ordered(value, conversion.key_order) {
             Ok(bytes) => {
-                write_binary_output(&bytes, output_file, write_back, input_file, output_format);
+                write_binary_output(
+                    &bytes,
+                    output_file,
+                    write_back,
+                    input_file,
+                    output_format,
+                    compression,
+                );
             }
             Err(e) => {
                 eprintln!("Error: Cannot convert to CBOR: {}", e);
                 return 1;
             }
         },
-        Format::CborDiag => match transcode::cbor::encode(value) {
+        Format::CborDiag => match transcode::cbor::encode_ordered(value, conversion.key_order) {
             Ok(bytes) => match transcode::cbor::diagnostic(&bytes) {
                 Ok(output) => {
-                    write_text_output(&output, output_file, write_back, input_file, output_format);
+                    write_text_output(
+                        &conversion.render(&output, input_file),
+                        output_file,
+                        write_back,
+                        input_file,
+                        output_format,
+                        compression,
+                    );
                 }
                 Err(e) => {
                     eprintln!("Error: Cannot render CBOR diagnostic notation: {}", e);
@@ -843,8 +1914,15 @@ fn output_value(
             }
         },
         _ => {
-            let output = encode(value, output_format);
-            write_text_output(&output, output_file, write_back, input_file, output_format);
+            let output = encode_ordered(value, output_format, conversion.key_order);
+            write_text_output(
+                &conversion.render(&output, input_file),
+                output_file,
+                write_back,
+                input_file,
+                output_format,
+                compression,
+            );
         }
     }
 
@@ -857,30 +1935,28 @@ fn write_text_output(
     write_back: bool,
     input_file: Option<&str>,
     format: Format,
+    compression: Compression,
 ) {
-    if let Some(path) = output_file {
-        if let Err(e) = fs::write(path, output) {
-            eprintln!("Error writing {}: {}", path, e);
-            process::exit(1);
-        }
-    } else if write_back {
-        if let Some(input_path) = input_file {
-            let ext = format_extension(format);
-            let output_path = Path::new(input_path).with_extension(ext);
-            if let Err(e) = fs::write(&output_path, output) {
-                eprintln!("Error writing {}: {}", output_path.display(), e);
-                process::exit(1);
-            }
-        } else {
-            eprintln!("Error: --write requires an input file");
-            process::exit(1);
-        }
+    if output_file.is_none() && !write_back && !output.ends_with('\n') {
+        // Ensure output to stdout ends with newline
+        let output = format!("{}\n", output);
+        write_binary_output(
+            output.as_bytes(),
+            None,
+            false,
+            input_file,
+            format,
+            compression,
+        );
     } else {
-        print!("{}", output);
-        // Ensure output ends with newline
-        if !output.ends_with('\n') {
-            println!();
-        }
+        write_binary_output(
+            output.as_bytes(),
+            output_file,
+            write_back,
+            input_file,
+            format,
+            compression,
+        );
     }
 }
 
@@ -890,7 +1966,16 @@ fn write_binary_output(
     write_back: bool,
     input_file: Option<&str>,
     format: Format,
+    compression: Compression,
 ) {
+    let compressed;
+    let output = if compression == Compression::None {
+        output
+    } else {
+        compressed = compression.compress(output);
+        &compressed
+    };
+    let _writing = budget::writing();
     if let Some(path) = output_file {
         if let Err(e) = fs::write(path, output) {
             eprintln!("Error writing {}: {}", path, e);
@@ -898,8 +1983,7 @@ fn write_binary_output(
         }
     } else if write_back {
         if let Some(input_path) = input_file {
-            let ext = format_extension(format);
-            let output_path = Path::new(input_path).with_extension(ext);
+            let output_path = write_back_path(input_path, format, compression);
             if let Err(e) = fs::write(&output_path, output) {
                 eprintln!("Error writing {}: {}", output_path.display(), e);
                 process::exit(1);
@@ -919,20 +2003,129 @@ fn write_binary_output(
     }
 }
 
+/// With `--fix`, repair the mechanical errors in the strict YAY file at
+/// `path`, rewrite it if anything changed, and print each fix. Returns the
+/// fixed text and whether it changed.
+fn fix_file(path: &str, input: String) -> Result<(String, bool), String> {
+    let (fixed, fixes) = fix_yay(&input);
+    if fixes.is_empty() {
+        return Ok((input, false));
+    }
+    let compression = Compression::from_path(Path::new(path));
+    let output = compression.compress(fixed.as_bytes());
+    let _writing = budget::writing();
+    fs::write(path, output).map_err(|e| format!("Error writing {}: {}", path, e))?;
+    for fix in &fixes {
+        println!("{}:{}: {}", path, fix.line, fix.message);
+    }
+    Ok((fixed, true))
+}
+
+/// The file `--write` writes for `input_path`: the input's name with the
+/// output format's extension (from the config file, if it gives one) in
+/// place of its own, after any `.gz` or `.zst`, and the output
+/// compression's extension added.
+fn write_back_path(input_path: &str, format: Format, compression: Compression) -> PathBuf {
+    let mut path = commands::strip_compression(Path::new(input_path))
+        .with_extension(config::get().extension(format))
+        .into_os_string();
+    if let Some(ext) = compression.extension() {
+        path.push(".");
+        path.push(ext);
+    }
+    PathBuf::from(path)
+}
+
 fn print_help() {
     println!(
         "yay - YAY command-line tool
 
 USAGE:
-    yay [OPTIONS] [FILE|DIR]
+    yay [OPTIONS] [FILE|DIR|URL]
+    yay <COMMAND> [ARGS]
+
+COMMANDS:
+    batch [--fail-fast] MANIFEST
+                           Run every conversion a manifest lists (from, out,
+                           and optional from_fmt and to_fmt for each) in
+                           parallel, rewriting only outputs that change
+    diff [-f FORMAT] OLD [-f FORMAT] NEW
+                           Print each path added (+), removed (-), or changed (~)
+                           between two documents, with its values; exits 1 if
+                           they differ. A -f gives the format of the input
+                           after it, in place of its extension
+    doctor                 Run a built-in suite of documents through every
+                           parse, encode, and transcode path in this build,
+                           and print which paths work with which documents;
+                           exits 1 if any fails
+    fmt [--add-checksums] [--range START:END] [-w] [FILE...]
+                           Reformat documents to canonical YAY, printing them
+                           or rewriting them in place with -w. --add-checksums
+                           writes a '# sha256:' comment on each block byte array;
+                           --range formats only the items on those lines
+    hash FILE|DIR...
+                           Print the SHA-256 hash of each document's value,
+                           the same however it is formatted or its keys are
+                           ordered, in the form sha256sum prints
+    infer [--examples N] FILE...
+                           Report, for each path like servers[].port, the
+                           types found, null count, numeric range, lengths,
+                           and up to N example values (default 3), as YAY
+    lint [--config FILE] FILE|DIR...
+                           Check documents against lint rules
+    merge [--concat-arrays] [--null-deletes] [-t FORMAT] BASE OVERRIDE...
+                           Deep-merge each override over the base, in order,
+                           printing YAY or the -t format; arrays are replaced
+                           unless --concat-arrays, and a null sets null unless
+                           --null-deletes removes it. A -f gives the format
+                           of the input after it: -f yaml a.cfg -f toml b.cfg
+    migrate [--to VERSION] [--migrations FILE] [-w [--force]] FILE|DIR...
+                           Upgrade documents from their 'version:' to VERSION
+                           (default: the latest) with the steps in a
+                           migrations file (default: migrations.yay beside
+                           each document), printing them or rewriting with -w;
+                           one with comments is only rewritten with --force
+    outline FILE           Print the keys and array items of a document as a
+                           tree, with their kinds and line ranges
+    patch [--merge] [-w [--force]] PATCH FILE|DIR...
+                           Apply a JSON Patch (RFC 6902), in YAY or JSON, to
+                           each document, printing it or rewriting it with -w;
+                           a document any operation fails on is left as it was,
+                           and one with comments is only rewritten with
+                           --force; --merge applies a JSON Merge Patch
+                           (RFC 7386), whose nulls remove members
+    refs --rules FILE FILE|DIR...
+                           Check that each value at a 'from' pattern in the
+                           rules file matches a value or key at its 'to'
+                           pattern in any of the documents, listing broken
+                           references with their lines
+    rename-key OLD.PATH NEW [-w] FILE|DIR...
+                           Rename the key at a dotted path in every document,
+                           keeping comments and formatting; lists the files
+                           that change, and rewrites them with -w
+    stamp (--set-path PATH --now|--git|--value TEXT)... [-w] FILE|DIR...
+                           Set each path to the time (or SOURCE_DATE_EPOCH),
+                           the git commit, or a string, keeping comments and
+                           formatting, printing documents or rewriting with -w
+    stats [--depth N] FILE
+    stats --diff [--depth N] OLD NEW
+                           Print document statistics, or per-path size and
+                           node count changes between two versions
 
 ARGS:
     [FILE|DIR]    Input file or directory (reads from stdin if not provided)
                   When a directory is given, processes all .yay files in it
+                  gzip and zstd input (.yay.gz, .yay.zst) is decompressed
+                  With --check, a tar or zip archive (.tar.gz, .zip) has
+                  each of its .yay members checked without extraction
+    [URL]         An http:// or https:// URL, when built with the net feature;
+                  the Content-Type chooses the input format unless -f is given
+                  s3:// and gs:// objects are read with the s3 and gcs features
 
 OPTIONS:
     -f, --from <FORMAT>    Input format [default: meh, or yay when --check]
-                           Supported: meh, yay, json, yson, yaml, toml, cbor
+                           Supported: meh, yay, json, jsonc, yson, ndyay, yaml,
+                                      toml, cbor
                            
                            'meh' (default) accepts loose formatting and reformats
                            to canonical YAY. 'yay' enforces strict YAY syntax
@@ -942,15 +2135,108 @@ OPTIONS:
                            (strict). Use --from meh to check lenient syntax.
     
     -t, --to <FORMAT>      Output format
-                           Supported: yay, json, yson, js, go, python, rust, c,
-                                      java, scheme, yaml, toml, cbor, diag
+                           Supported: yay, json, yson, ndyay, js, go, python,
+                                      rust, c, java, scheme, yaml, toml, cbor,
+                                      diag
     
-    -w, --write            Write output to file with inferred extension
+    -w, --write            Write output to file with inferred extension; the
+                           YAY_CONFIG file, if set, can give each format its
+                           extensions
     
     -o, --output <FILE>    Write output to specified file (not valid with directory input)
     
+    --template <FILE>      Write text output into the template in FILE, in place
+                           of {{{{value}}}}; {{{{symbol}}}} is an identifier made from
+                           the input's file name and {{{{source_path}}}} its path
+    
+    --compress <FORMAT>    Compress output: none (default), gzip, or zstd
+                           With --write, adds .gz or .zst to the file name
+    
     --check                Check if input is valid (exit 0 if valid, 1 if invalid)
                            Defaults to strict YAY input; use --from meh for lenient
+                           Without --from, a directory's .yay, .yaml, and .json
+                           files are each checked in their own format
+    
+    --verify-checksums     With --check, also verify '# sha256:' comments on
+                           block byte arrays (see 'yay fmt --add-checksums')
+    
+    --fix                  With --check, first fix uppercase hex digits and
+                           exponents, extra spaces after a colon, and trailing
+                           spaces, rewriting the file and printing each fix
+    
+    --fail-fast            With directory input, stop taking new files after
+                           the first one that fails
+    
+    --header <NAME: VALUE>
+                           With URL input, send a request header; may be repeated
+    
+    --fetch-timeout <SECS> With remote input, give up after SECS seconds (default: 30)
+    
+    --timeout <SECS>       Give up on the conversion after SECS seconds, with exit
+                           code 3, for batch jobs over untrusted input
+    
+    --head <N>             Keep only the first N items of every longer array,
+                           noting each array cut and its length in a comment
+                           at the top of the output, or on stderr for formats
+                           without comments
+    
+    --tail <N>             Keep only the last N items of every longer array
+    
+    --sample <N>           Keep N evenly spaced items, first and last included,
+                           of every longer array
+    
+    --eval                 Replace each object whose only member is '$eval' with
+                           the value of its expression, computed from numbers
+                           and strings elsewhere in the document
+    
+    --decimals             Read YAY floats written in decimal, like 12.30, as exact
+                           decimals, which CBOR keeps as decimal fractions (tag 4)
+                           and JSON writes with the same digits
+    
+    --decimal-strings      Write exact decimals as strings of their digits, for
+                           JSON readers that would round them to floats
+    
+    --json-lossy           With -t json, write byte arrays as base64 strings,
+                           integers beyond 2^53 as strings, and nan and the
+                           infinities as null, instead of failing
+    
+    --force                Convert even when comments in YAY input would be lost
+                           (YAY output that would lose them is refused otherwise)
+    
+    --duplicate-keys <POLICY>
+                           What to do with a key repeated in one YAY object:
+                           error (report both places), first (keep the first
+                           value), last (default, keep the last value), or warn
+                           (keep the last value and report it)
+    
+    --key-order <ORDER>
+                           The order of object keys in output converted from
+                           another format: bytes (default, by UTF-8 bytes),
+                           collated (as in a dictionary: case and accents
+                           sort with their letters), or document (as the
+                           input wrote them)
+    
+    --no-sort-keys
+                           Write object keys in the order the input wrote
+                           them, the same as --key-order document
+    
+    --invalid-utf8 <POLICY>
+                           Handling of CBOR text strings that are not valid
+                           UTF-8: error (default), lossy (replace with U+FFFD),
+                           or bytes (import as a byte array)
+    
+    --resume               With -f cbor and -o FILE, convert a top-level CBOR
+                           array item by item, checkpointing progress in
+                           FILE.resume; run again to continue after an interruption
+    
+    --stream               With -f cbor and -t yay, json, or yson, convert without
+                           holding the whole document in memory; large objects
+                           are sorted through temporary files
+    
+    --max-memory <SIZE>    Give up on a conversion that needs more than SIZE of
+                           memory, like 256MiB, with exit code 3; with --stream,
+                           about how much of the document to hold at once
+                           instead (default: 64MiB)
     
     -h, --help             Print help
     
@@ -969,6 +2255,9 @@ EXAMPLES:
     # Validate all YAY files in a directory strictly
     yay --check ./configs/
     
+    # Validate the YAY files in a fixture bundle
+    yay --check fixtures.tar.gz
+    
     # Convert YAY to JSON (lenient input)
     yay -t json config.yay
     
@@ -1008,6 +2297,9 @@ EXAMPLES:
     # Convert YAY to YSON (JSON with YAY extensions)
     yay -t yson config.yay -o config.yson
     
+    # Read a log of one YAY document per line as an array
+    yay -f ndyay -t json events.ndyay
+    
     # SHON: construct data from command-line arguments
     yay [ --name hello --count 42 ]
     yay -t json [ --x 1.0 --y 2.0 ]
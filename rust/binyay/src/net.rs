@@ -0,0 +1,293 @@
+//! Remote input: `yay https://example.com/config.yay -t json`.
+//!
+//! Fetching is compiled in with the `net` feature, which fetches with
+//! `ureq`. The response's `Content-Type` picks the input format when
+//! `--from` is not given. `--header` values go only to the origin that was
+//! asked for, not to another one a redirect points at.
+
+use std::time::Duration;
+
+/// The body and content type of a successful response.
+pub struct Response {
+    pub body: Vec<u8>,
+    pub content_type: Option<String>,
+}
+
+/// Whether an input argument names a URL rather than a file.
+pub fn is_url(input: &str) -> bool {
+    input.starts_with("http://") || input.starts_with("https://")
+}
+
+/// The input format a `Content-Type` implies, or `None` for YAY and
+/// unrecognized types, which keep the usual default.
+pub fn format_for_content_type(content_type: &str) -> Option<&'static str> {
+    let mime = content_type
+        .split(';')
+        .next()
+        .unwrap_or("")
+        .trim()
+        .to_ascii_lowercase();
+    match mime.as_str() {
+        "application/json" | "text/json" => Some("json"),
+        "application/yaml" | "application/x-yaml" | "text/yaml" | "text/x-yaml" => Some("yaml"),
+        "application/toml" | "text/toml" => Some("toml"),
+        "application/cbor" => Some("cbor"),
+        _ if mime.ends_with("+json") => Some("json"),
+        _ if mime.ends_with("+yaml") => Some("yaml"),
+        _ => None,
+    }
+}
+
+/// Fetch `url`, sending each of `headers` (`Name: value`) and giving up
+/// after `timeout`.
+#[cfg(feature = "net")]
+pub fn fetch(url: &str, headers: &[String], timeout: Duration) -> Result<Response, String> {
+    http::fetch(url, headers, timeout)
+}
+
+#[cfg(not(feature = "net"))]
+pub fn fetch(url: &str, _headers: &[String], _timeout: Duration) -> Result<Response, String> {
+    Err(format!(
+        "{}: URL input requires yay built with the `net` feature",
+        url
+    ))
+}
+
+#[cfg(feature = "net")]
+mod http {
+    use super::Response;
+    use std::time::{Duration, Instant};
+    use ureq::Agent;
+
+    const MAX_REDIRECTS: usize = 5;
+
+    pub fn fetch(url: &str, headers: &[String], timeout: Duration) -> Result<Response, String> {
+        let mut pairs = Vec::new();
+        for header in headers {
+            match header.split_once(':') {
+                Some((name, value)) => pairs.push((name.trim(), value.trim())),
+                None => {
+                    return Err(format!(
+                        "Invalid header (expected `Name: value`): {}",
+                        header
+                    ))
+                }
+            }
+        }
+        // Redirects are followed here rather than by ureq, so that headers
+        // meant for one origin, like `Authorization`, are not sent to
+        // another.
+        let agent: Agent = Agent::config_builder()
+            .http_status_as_error(false)
+            .max_redirects(0)
+            .build()
+            .into();
+        let deadline = Instant::now() + timeout;
+        let requested = origin(url).to_string();
+        let mut url = url.to_string();
+        for _ in 0..=MAX_REDIRECTS {
+            let remaining = deadline
+                .checked_duration_since(Instant::now())
+                .filter(|d| !d.is_zero())
+                .ok_or_else(|| format!("{}: timed out", url))?;
+            let mut request = agent.get(&url);
+            if origin(&url).eq_ignore_ascii_case(&requested) {
+                for (name, value) in &pairs {
+                    request = request.header(*name, *value);
+                }
+            }
+            let mut response = request
+                .config()
+                .timeout_global(Some(remaining))
+                .build()
+                .call()
+                .map_err(|e| failure(&url, e))?;
+            let status = response.status();
+            let header = |name: &str| {
+                response
+                    .headers()
+                    .get(name)
+                    .and_then(|v| v.to_str().ok())
+                    .map(str::to_string)
+            };
+            match status.as_u16() {
+                200..=299 => {
+                    let content_type = header("content-type");
+                    let body = response
+                        .body_mut()
+                        .with_config()
+                        .limit(u64::MAX)
+                        .read_to_vec()
+                        .map_err(|e| failure(&url, e))?;
+                    return Ok(Response { body, content_type });
+                }
+                301 | 302 | 303 | 307 | 308 => {
+                    let location = header("location")
+                        .ok_or_else(|| format!("{}: redirect without a Location", url))?;
+                    url = resolve(&url, &location);
+                }
+                _ => {
+                    return Err(format!(
+                        "{}: HTTP {} {}",
+                        url,
+                        status.as_u16(),
+                        status.canonical_reason().unwrap_or("")
+                    ))
+                }
+            }
+        }
+        Err(format!("{}: too many redirects", url))
+    }
+
+    fn failure(url: &str, error: ureq::Error) -> String {
+        match error {
+            ureq::Error::Timeout(_) => format!("{}: timed out", url),
+            e => format!("{}: {}", url, e),
+        }
+    }
+
+    /// The scheme, host, and port of a URL, as it is written.
+    fn origin(url: &str) -> &str {
+        let scheme_end = url.find("://").map(|i| i + 3).unwrap_or(0);
+        let end = url[scheme_end..]
+            .find(['/', '?', '#'])
+            .map(|i| scheme_end + i)
+            .unwrap_or(url.len());
+        &url[..end]
+    }
+
+    /// Resolve a `Location` header against the URL that returned it.
+    fn resolve(base: &str, location: &str) -> String {
+        if super::is_url(location) {
+            return location.to_string();
+        }
+        let scheme_end = base.find("://").map(|i| i + 3).unwrap_or(0);
+        let origin_end = origin(base).len();
+        if let Some(rest) = location.strip_prefix("//") {
+            format!("{}{}", &base[..scheme_end], rest)
+        } else if location.starts_with('/') {
+            format!("{}{}", &base[..origin_end], location)
+        } else {
+            let path = &base[origin_end..];
+            let path = path.split(['?', '#']).next().unwrap_or("");
+            let dir = &path[..path.rfind('/').map(|i| i + 1).unwrap_or(0)];
+            let dir = if dir.is_empty() { "/" } else { dir };
+            format!("{}{}{}", &base[..origin_end], dir, location)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use std::io::{Read, Write};
+        use std::net::TcpListener;
+        use std::thread;
+
+        #[test]
+        fn test_resolve() {
+            let base = "http://example.com/a/b.yay?x=1";
+            assert_eq!(resolve(base, "c.yay"), "http://example.com/a/c.yay");
+            assert_eq!(resolve(base, "/c.yay"), "http://example.com/c.yay");
+            assert_eq!(
+                resolve(base, "//cdn.example.com/c"),
+                "http://cdn.example.com/c"
+            );
+            assert_eq!(resolve(base, "https://x.org/c"), "https://x.org/c");
+            assert_eq!(origin("http://example.com?x"), "http://example.com");
+        }
+
+        fn serve(responses: Vec<String>) -> String {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let port = listener.local_addr().unwrap().port();
+            thread::spawn(move || {
+                for response in responses {
+                    let (mut stream, _) = listener.accept().unwrap();
+                    let mut request = Vec::new();
+                    let mut byte = [0];
+                    while !request.ends_with(b"\r\n\r\n") {
+                        stream.read_exact(&mut byte).unwrap();
+                        request.push(byte[0]);
+                    }
+                    let request = String::from_utf8(request).unwrap().to_ascii_lowercase();
+                    let response = if request.contains("x-token: secret\r\n") {
+                        response
+                    } else {
+                        "HTTP/1.1 401 Unauthorized\r\nContent-Length: 0\r\n\r\n".to_string()
+                    };
+                    stream.write_all(response.as_bytes()).unwrap();
+                }
+            });
+            format!("http://127.0.0.1:{}", port)
+        }
+
+        #[test]
+        fn test_fetch() {
+            let base = serve(vec![
+                "HTTP/1.1 301 Moved\r\nLocation: /config.yay\r\nContent-Length: 0\r\n\r\n".into(),
+                "HTTP/1.1 200 OK\r\nContent-Type: text/yaml\r\nContent-Length: 5\r\n\r\na: 1\n"
+                    .into(),
+            ]);
+            let headers = vec!["X-Token: secret".to_string()];
+            let response =
+                fetch(&format!("{}/old", base), &headers, Duration::from_secs(5)).unwrap();
+            assert_eq!(response.body, b"a: 1\n");
+            assert_eq!(response.content_type.as_deref(), Some("text/yaml"));
+
+            let base = serve(vec!["HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n".into()]);
+            let error = fetch(&base, &[], Duration::from_secs(5)).err().unwrap();
+            assert!(error.ends_with("HTTP 401 Unauthorized"), "{}", error);
+        }
+
+        #[test]
+        fn test_cross_origin_redirect_drops_headers() {
+            // The second server wants the token, and must not be sent it.
+            let other = serve(vec![
+                "HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\na: 1\n".into()
+            ]);
+            let base = serve(vec![format!(
+                "HTTP/1.1 302 Found\r\nLocation: {}/config.yay\r\nContent-Length: 0\r\n\r\n",
+                other
+            )]);
+            let headers = vec!["X-Token: secret".to_string()];
+            let error = fetch(&base, &headers, Duration::from_secs(5))
+                .err()
+                .unwrap();
+            assert_eq!(
+                error,
+                format!("{}/config.yay: HTTP 401 Unauthorized", other)
+            );
+        }
+
+        #[test]
+        fn test_timeout() {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let url = format!("http://{}/", listener.local_addr().unwrap());
+            let error = fetch(&url, &[], Duration::from_millis(200)).err().unwrap();
+            assert!(error.ends_with("timed out"), "{}", error);
+            drop(listener);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_for_content_type() {
+        assert_eq!(
+            format_for_content_type("application/json; charset=utf-8"),
+            Some("json")
+        );
+        assert_eq!(
+            format_for_content_type("application/vnd.api+json"),
+            Some("json")
+        );
+        assert_eq!(format_for_content_type("Text/YAML"), Some("yaml"));
+        assert_eq!(format_for_content_type("application/toml"), Some("toml"));
+        assert_eq!(format_for_content_type("application/cbor"), Some("cbor"));
+        assert_eq!(format_for_content_type("text/plain"), None);
+        assert!(is_url("https://example.com/config.yay"));
+        assert!(!is_url("config.yay"));
+    }
+}
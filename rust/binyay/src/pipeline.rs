@@ -0,0 +1,200 @@
+//! Directory mode's file pipeline.
+//!
+//! The files of a directory are shared out among a fixed set of worker
+//! threads, which all finish before `run` returns. With `fail_fast`, the
+//! first failure stops the workers from taking more files; files already
+//! underway still finish. The run ends with a `Summary` of what happened.
+
+use std::fmt;
+use std::panic;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How many of the slowest files a summary lists.
+pub const SLOWEST: usize = 5;
+
+/// What became of one file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// Processed, and nothing on disk changed.
+    Unchanged,
+    /// Processed, and `--write` changed the output file.
+    Reformatted,
+    /// Could not be read, parsed, or converted.
+    Failed,
+}
+
+/// The end-of-run report for a directory.
+#[derive(Debug, Default)]
+pub struct Summary {
+    pub processed: usize,
+    pub reformatted: usize,
+    pub failed: usize,
+    /// Files never started because `fail_fast` stopped the run.
+    pub skipped: usize,
+    pub elapsed: Duration,
+    /// The slowest files, slowest first.
+    pub slowest: Vec<(String, Duration)>,
+}
+
+impl Summary {
+    pub fn success(&self) -> bool {
+        self.failed == 0
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} processed, {} reformatted, {} failed in {:.2}s",
+            self.processed,
+            if self.processed == 1 { "file" } else { "files" },
+            self.reformatted,
+            self.failed,
+            self.elapsed.as_secs_f64()
+        )?;
+        if self.skipped > 0 {
+            write!(f, "\n{} skipped after the first failure", self.skipped)?;
+        }
+        if !self.slowest.is_empty() {
+            write!(f, "\nslowest:")?;
+            for (path, time) in &self.slowest {
+                write!(f, "\n  {:>8.3}s  {}", time.as_secs_f64(), path)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The number of workers to use when none is given.
+pub fn default_jobs() -> usize {
+    thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+/// Run `process` over every path on up to `jobs` threads.
+pub fn run<F>(paths: &[String], jobs: usize, fail_fast: bool, process: F) -> Summary
+where
+    F: Fn(&str) -> Outcome + Sync,
+{
+    let start = Instant::now();
+    let next = AtomicUsize::new(0);
+    let stop = AtomicBool::new(false);
+
+    let finished: Vec<(usize, Outcome, Duration)> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..jobs.clamp(1, paths.len().max(1)))
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut finished = Vec::new();
+                    while !stop.load(Ordering::Relaxed) {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(path) = paths.get(index) else {
+                            break;
+                        };
+                        let started = Instant::now();
+                        let outcome = process(path);
+                        if fail_fast && outcome == Outcome::Failed {
+                            stop.store(true, Ordering::Relaxed);
+                        }
+                        finished.push((index, outcome, started.elapsed()));
+                    }
+                    finished
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap_or_else(|e| panic::resume_unwind(e)))
+            .collect()
+    });
+
+    let mut summary = Summary {
+        processed: finished.len(),
+        skipped: paths.len() - finished.len(),
+        elapsed: start.elapsed(),
+        ..Summary::default()
+    };
+    for (_, outcome, _) in &finished {
+        match outcome {
+            Outcome::Unchanged => {}
+            Outcome::Reformatted => summary.reformatted += 1,
+            Outcome::Failed => summary.failed += 1,
+        }
+    }
+    let mut times: Vec<(usize, Duration)> = finished.iter().map(|&(i, _, t)| (i, t)).collect();
+    times.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    summary.slowest = times
+        .into_iter()
+        .take(SLOWEST)
+        .map(|(i, t)| (paths[i].clone(), t))
+        .collect();
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    fn paths(n: usize) -> Vec<String> {
+        (0..n).map(|i| format!("{}.yay", i)).collect()
+    }
+
+    #[test]
+    fn test_counts_every_outcome() {
+        let seen = Mutex::new(Vec::new());
+        let summary = run(&paths(20), 4, false, |path| {
+            seen.lock().unwrap().push(path.to_string());
+            match path.trim_end_matches(".yay").parse::<usize>().unwrap() % 5 {
+                0 => Outcome::Failed,
+                1 => Outcome::Reformatted,
+                _ => Outcome::Unchanged,
+            }
+        });
+        assert_eq!(seen.into_inner().unwrap().len(), 20);
+        assert_eq!(summary.processed, 20);
+        assert_eq!(summary.reformatted, 4);
+        assert_eq!(summary.failed, 4);
+        assert_eq!(summary.skipped, 0);
+        assert_eq!(summary.slowest.len(), SLOWEST);
+        assert!(!summary.success());
+    }
+
+    #[test]
+    fn test_fail_fast_stops_scheduling() {
+        let summary = run(&paths(100), 1, true, |path| {
+            if path == "3.yay" {
+                Outcome::Failed
+            } else {
+                Outcome::Unchanged
+            }
+        });
+        assert_eq!(summary.processed, 4);
+        assert_eq!(summary.failed, 1);
+        assert_eq!(summary.skipped, 96);
+        assert!(summary.to_string().contains("96 skipped"));
+    }
+
+    #[test]
+    fn test_slowest_first() {
+        let summary = run(&paths(3), 3, false, |path| {
+            if path == "1.yay" {
+                thread::sleep(Duration::from_millis(30));
+            }
+            Outcome::Unchanged
+        });
+        assert_eq!(summary.slowest[0].0, "1.yay");
+        assert!(summary.success());
+        assert!(summary
+            .to_string()
+            .starts_with("3 files processed, 0 reformatted, 0 failed in "));
+    }
+
+    #[test]
+    fn test_empty() {
+        let summary = run(&[], 8, true, |_| Outcome::Failed);
+        assert_eq!(summary.processed, 0);
+        assert!(summary.slowest.is_empty());
+    }
+}
@@ -0,0 +1,287 @@
+//! `yay -f cbor -o OUT.yay --resume IN.cbor`: a CBOR-to-YAY conversion that
+//! can pick up where it left off.
+//!
+//! The input must be a top-level array. Its items are decoded and written
+//! one at a time, and every so often the output is synced to disk and a
+//! checkpoint is written beside it, `OUT.yay.resume`, recording the input
+//! offset just past the last item written and the output length at that
+//! point. Run again after an interruption, the conversion truncates the
+//! output to the checkpoint and continues from the recorded offset. The
+//! checkpoint is removed when the conversion finishes, and the output is
+//! the same as a conversion without `--resume` would have written.
+
+use libyay::{encode, parse, ArrayEncoder, FloatFormat, Format, Map, Value};
+use num_bigint::BigInt;
+use num_traits::ToPrimitive;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use crate::transcode::cbor::{ArrayItems, DecodeOptions};
+
+/// Input bytes converted between checkpoints.
+const CHECKPOINT_BYTES: u64 = 4 << 20;
+
+/// How far a conversion had got when it last synced its output.
+#[derive(Debug, PartialEq)]
+struct Checkpoint {
+    /// The input path, and its length, to recognize a stale checkpoint.
+    input: String,
+    input_len: u64,
+    /// The input offset of the next item.
+    offset: u64,
+    /// Items left in a definite-length array.
+    remaining: Option<u64>,
+    /// Items written so far.
+    items: usize,
+    /// The output length after those items.
+    output: u64,
+}
+
+impl Checkpoint {
+    fn path(output: &str) -> PathBuf {
+        PathBuf::from(format!("{}.resume", output))
+    }
+
+    fn to_yay(&self) -> String {
+        let mut map = Map::new();
+        map.insert("input".to_string(), Value::String(self.input.clone()));
+        let mut number = |key: &str, n: u64| {
+            map.insert(key.to_string(), Value::Integer(BigInt::from(n)));
+        };
+        number("input_len", self.input_len);
+        number("offset", self.offset);
+        number("items", self.items as u64);
+        number("output", self.output);
+        if let Some(remaining) = self.remaining {
+            number("remaining", remaining);
+        }
+        format!("{}\n", encode(&Value::Object(map), Format::Yay))
+    }
+
+    fn from_yay(source: &str) -> Option<Checkpoint> {
+        let value = parse(source).ok()?;
+        let number = |key: &str| value.get(key)?.as_integer()?.to_u64();
+        Some(Checkpoint {
+            input: value.get("input")?.as_str()?.to_string(),
+            input_len: number("input_len")?,
+            offset: number("offset")?,
+            remaining: match value.get("remaining") {
+                Some(_) => Some(number("remaining")?),
+                None => None,
+            },
+            items: number("items")?.try_into().ok()?,
+            output: number("output")?,
+        })
+    }
+
+    /// Write the checkpoint atomically, so an interruption leaves either
+    /// this one or the last.
+    fn save(&self, path: &Path) -> Result<(), String> {
+        let temp = path.with_extension("resume.tmp");
+        fs::write(&temp, self.to_yay())
+            .and_then(|_| fs::rename(&temp, path))
+            .map_err(|e| format!("Error writing {}: {}", path.display(), e))
+    }
+}
+
+/// Convert the CBOR array at `input` to YAY at `output`, resuming from a
+/// checkpoint if there is one. Returns the number of items converted.
+pub fn convert(input: &str, output: &str, options: DecodeOptions) -> Result<usize, String> {
+    convert_with(input, output, options, CHECKPOINT_BYTES)
+}
+
+fn convert_with(
+    input: &str,
+    output: &str,
+    options: DecodeOptions,
+    checkpoint_bytes: u64,
+) -> Result<usize, String> {
+    let read_error = |e: std::io::Error| format!("Error reading {}: {}", input, e);
+    let write_error = |e: std::io::Error| format!("Error writing {}: {}", output, e);
+
+    let mut file = File::open(input).map_err(read_error)?;
+    let input_len = file.metadata().map_err(read_error)?.len();
+    let mut magic = [0; 4];
+    let magic_len = file.read(&mut magic).map_err(read_error)?;
+    if libyay::compress::Compression::detect(&magic[..magic_len])
+        != libyay::compress::Compression::None
+    {
+        return Err(format!(
+            "{}: --resume needs uncompressed input, since it records byte offsets",
+            input
+        ));
+    }
+
+    let checkpoint_path = Checkpoint::path(output);
+    let checkpoint = fs::read_to_string(&checkpoint_path)
+        .ok()
+        .and_then(|source| Checkpoint::from_yay(&source))
+        .filter(|c| c.input == input && c.input_len == input_len && c.items > 0);
+
+    let (mut items, mut encoder, mut out, mut position) = match checkpoint {
+        Some(checkpoint) => {
+            let out = OpenOptions::new()
+                .write(true)
+                .open(output)
+                .map_err(write_error)?;
+            if out.metadata().map_err(write_error)?.len() < checkpoint.output {
+                return Err(format!(
+                    "{} is shorter than its checkpoint {}; remove the checkpoint to start over",
+                    output,
+                    checkpoint_path.display()
+                ));
+            }
+            out.set_len(checkpoint.output).map_err(write_error)?;
+            let mut out = BufWriter::new(out);
+            out.seek(SeekFrom::End(0)).map_err(write_error)?;
+            file.seek(SeekFrom::Start(checkpoint.offset))
+                .map_err(read_error)?;
+            eprintln!(
+                "Resuming {} at item {} (byte {})",
+                input, checkpoint.items, checkpoint.offset
+            );
+            let items = ArrayItems::resume(
+                BufReader::new(file),
+                checkpoint.offset,
+                checkpoint.remaining,
+                options,
+            );
+            let encoder = ArrayEncoder::resume(checkpoint.items, FloatFormat::default());
+            (items, encoder, out, checkpoint.output)
+        }
+        None => {
+            file.seek(SeekFrom::Start(0)).map_err(read_error)?;
+            let items = ArrayItems::new(BufReader::new(file), options)
+                .map_err(|e| format!("{}: {}", input, e))?;
+            let out = BufWriter::new(File::create(output).map_err(write_error)?);
+            (items, ArrayEncoder::new(FloatFormat::default()), out, 0)
+        }
+    };
+
+    let mut last_checkpoint = items.offset();
+    let mut converted = encoder.written();
+    while let Some(item) = items.next() {
+        let item = item.map_err(|e| {
+            format!(
+                "{}: {}\nRun the same command again to resume from the last checkpoint.",
+                input, e
+            )
+        })?;
+        let text = encoder.push(item);
+        converted += 1;
+        out.write_all(text.as_bytes()).map_err(write_error)?;
+        position += text.len() as u64;
+
+        if encoder.written() > 0 && items.offset() - last_checkpoint >= checkpoint_bytes {
+            out.flush().map_err(write_error)?;
+            out.get_ref().sync_data().map_err(write_error)?;
+            Checkpoint {
+                input: input.to_string(),
+                input_len,
+                offset: items.offset(),
+                remaining: items.remaining(),
+                items: encoder.written(),
+                output: position,
+            }
+            .save(&checkpoint_path)?;
+            last_checkpoint = items.offset();
+        }
+    }
+
+    out.write_all(encoder.finish().as_bytes())
+        .map_err(write_error)?;
+    out.flush().map_err(write_error)?;
+    out.get_ref().sync_data().map_err(write_error)?;
+    if checkpoint_path.exists() {
+        fs::remove_file(&checkpoint_path)
+            .map_err(|e| format!("Error removing {}: {}", checkpoint_path.display(), e))?;
+    }
+    Ok(converted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcode::cbor;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("yay-resume-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn sample() -> Value {
+        Value::Array(
+            (0..200)
+                .map(|i| {
+                    let mut map = Map::new();
+                    map.insert("id".to_string(), Value::Integer(BigInt::from(i)));
+                    map.insert("tags".to_string(), Value::Array(vec![Value::Null; i % 7]));
+                    Value::Object(map)
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_checkpoint_round_trip() {
+        let checkpoint = Checkpoint {
+            input: "big.cbor".to_string(),
+            input_len: 100,
+            offset: 40,
+            remaining: None,
+            items: 3,
+            output: 77,
+        };
+        assert_eq!(Checkpoint::from_yay(&checkpoint.to_yay()), Some(checkpoint));
+    }
+
+    #[test]
+    fn test_resume() {
+        let dir = temp_dir("resume");
+        let input = dir.join("in.cbor").to_string_lossy().to_string();
+        let output = dir.join("out.yay").to_string_lossy().to_string();
+        let value = sample();
+        fs::write(&input, cbor::encode(&value).unwrap()).unwrap();
+        let expected = encode(&value, Format::Yay);
+
+        // A checkpoint after every item, removed at the end.
+        let count = convert_with(&input, &output, DecodeOptions::default(), 1).unwrap();
+        assert_eq!(count, 200);
+        assert_eq!(fs::read_to_string(&output).unwrap(), expected);
+        assert!(!Checkpoint::path(&output).exists());
+
+        // Interrupted after 50 items, with a partly written item after the
+        // checkpoint.
+        let bytes = fs::read(&input).unwrap();
+        let mut items = ArrayItems::new(&bytes[..], DecodeOptions::default()).unwrap();
+        let mut encoder = ArrayEncoder::new(FloatFormat::default());
+        let mut partial = String::new();
+        for item in items.by_ref().take(50) {
+            partial.push_str(&encoder.push(item.unwrap()));
+        }
+        let checkpoint = Checkpoint {
+            input: input.clone(),
+            input_len: bytes.len() as u64,
+            offset: items.offset(),
+            remaining: items.remaining(),
+            items: encoder.written(),
+            output: partial.len() as u64,
+        };
+        checkpoint.save(&Checkpoint::path(&output)).unwrap();
+        fs::write(&output, format!("{}\n- {{id: 5", partial)).unwrap();
+        convert(&input, &output, DecodeOptions::default()).unwrap();
+        assert_eq!(fs::read_to_string(&output).unwrap(), expected);
+        assert!(!Checkpoint::path(&output).exists());
+
+        // A checkpoint for a different input is ignored.
+        checkpoint.save(&Checkpoint::path(&output)).unwrap();
+        fs::write(&input, cbor::encode(&Value::Array(vec![])).unwrap()).unwrap();
+        convert(&input, &output, DecodeOptions::default()).unwrap();
+        assert_eq!(fs::read_to_string(&output).unwrap(), "[]");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
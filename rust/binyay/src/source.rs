@@ -0,0 +1,290 @@
+//! Where single-document input comes from.
+//!
+//! Each [`Source`] claims the input arguments it understands: a URL, an
+//! object in a bucket, or a local file, with stdin standing in when there
+//! is no argument. Adding a scheme means adding a source to [`SOURCES`].
+//!
+//! Object stores are read through their own command-line tools, `aws` for
+//! `s3://` and `gcloud` for `gs://`, so credentials, profiles, and regions
+//! work exactly as they do in the scripts that would otherwise download the
+//! object first. Each is compiled in with a feature of the same name.
+
+use libyay::compress::decompress_limited;
+use std::borrow::Cow;
+use std::io::{self, Read};
+use std::time::Duration;
+
+use crate::{commands, net};
+
+/// Settings that apply to remote sources.
+pub struct SourceOptions {
+    /// Extra HTTP request headers, as `Name: value`.
+    pub headers: Vec<String>,
+    /// How long to wait for a remote source before giving up.
+    pub timeout: Duration,
+    /// The most bytes compressed input may decompress to.
+    pub max_output: usize,
+}
+
+/// The bytes read from a source, already decompressed, and the input
+/// format the source implies, if any.
+pub struct Input {
+    pub bytes: Vec<u8>,
+    pub format: Option<&'static str>,
+}
+
+pub trait Source: Sync {
+    /// Whether this source reads `location`.
+    fn handles(&self, location: &str) -> bool;
+
+    /// Whether `location` is read over the network.
+    fn is_remote(&self) -> bool;
+
+    /// Read the raw bytes at `location`.
+    fn read(&self, location: &str, options: &SourceOptions) -> Result<Input, String>;
+}
+
+/// The sources to try, in order. Files come last and claim everything.
+static SOURCES: &[&dyn Source] = &[&Url, &S3, &Gcs, &File];
+
+fn source_for(location: &str) -> &'static dyn Source {
+    SOURCES
+        .iter()
+        .copied()
+        .find(|source| source.handles(location))
+        .unwrap_or(&File)
+}
+
+/// Whether `location` names something other than a local file.
+pub fn is_remote(location: &str) -> bool {
+    source_for(location).is_remote()
+}
+
+/// Read and decompress the input at `location`, or stdin when `None`.
+pub fn read(location: Option<&str>, options: &SourceOptions) -> Result<Input, String> {
+    let (input, label) = match location {
+        Some(location) => (source_for(location).read(location, options)?, location),
+        None => {
+            let mut bytes = Vec::new();
+            io::stdin()
+                .read_to_end(&mut bytes)
+                .map_err(|e| format!("Error reading stdin: {}", e))?;
+            (
+                Input {
+                    bytes,
+                    format: None,
+                },
+                "stdin",
+            )
+        }
+    };
+    let bytes = match decompress_limited(&input.bytes, options.max_output) {
+        Ok(Cow::Borrowed(_)) => input.bytes,
+        Ok(Cow::Owned(data)) => data,
+        Err(e) => return Err(format!("Error reading {}: {}", label, e)),
+    };
+    Ok(Input {
+        bytes,
+        format: input.format,
+    })
+}
+
+/// A local file. Its format is left to `--from`, as it always has been.
+struct File;
+
+impl Source for File {
+    fn handles(&self, _location: &str) -> bool {
+        true
+    }
+
+    fn is_remote(&self) -> bool {
+        false
+    }
+
+    fn read(&self, location: &str, _options: &SourceOptions) -> Result<Input, String> {
+        Ok(Input {
+            bytes: commands::read_file(location)?,
+            format: None,
+        })
+    }
+}
+
+/// An `http://` or `https://` URL. The `Content-Type` implies the format.
+struct Url;
+
+impl Source for Url {
+    fn handles(&self, location: &str) -> bool {
+        net::is_url(location)
+    }
+
+    fn is_remote(&self) -> bool {
+        true
+    }
+
+    fn read(&self, location: &str, options: &SourceOptions) -> Result<Input, String> {
+        let response = net::fetch(location, &options.headers, options.timeout)?;
+        Ok(Input {
+            format: response
+                .content_type
+                .as_deref()
+                .and_then(net::format_for_content_type),
+            bytes: response.body,
+        })
+    }
+}
+
+/// An `s3://bucket/key` object, read with `aws s3 cp`.
+struct S3;
+
+impl Source for S3 {
+    fn handles(&self, location: &str) -> bool {
+        location.starts_with("s3://")
+    }
+
+    fn is_remote(&self) -> bool {
+        true
+    }
+
+    fn read(&self, location: &str, options: &SourceOptions) -> Result<Input, String> {
+        if !cfg!(feature = "s3") {
+            return Err(format!(
+                "{}: s3:// input requires yay built with the `s3` feature",
+                location
+            ));
+        }
+        read_object(
+            location,
+            options,
+            "aws",
+            &["s3", "cp", "--quiet", location, "-"],
+        )
+    }
+}
+
+/// A `gs://bucket/object` object, read with `gcloud storage cat`.
+struct Gcs;
+
+impl Source for Gcs {
+    fn handles(&self, location: &str) -> bool {
+        location.starts_with("gs://")
+    }
+
+    fn is_remote(&self) -> bool {
+        true
+    }
+
+    fn read(&self, location: &str, options: &SourceOptions) -> Result<Input, String> {
+        if !cfg!(feature = "gcs") {
+            return Err(format!(
+                "{}: gs:// input requires yay built with the `gcs` feature",
+                location
+            ));
+        }
+        read_object(location, options, "gcloud", &["storage", "cat", location])
+    }
+}
+
+/// Run an object store's tool to print the object at `location`. The
+/// object's extension implies its format, since the tools do not report a
+/// content type.
+fn read_object(
+    location: &str,
+    options: &SourceOptions,
+    program: &str,
+    args: &[&str],
+) -> Result<Input, String> {
+    if !options.headers.is_empty() {
+        return Err(format!("{}: --header only applies to URL input", location));
+    }
+    let bytes = run(program, args, options.timeout).map_err(|e| format!("{}: {}", location, e))?;
+    Ok(Input {
+        bytes,
+        format: match commands::infer_format(location) {
+            "meh" => None,
+            format => Some(format),
+        },
+    })
+}
+
+/// Run `program` and return what it writes to stdout, killing it if it
+/// runs past `timeout`.
+fn run(program: &str, args: &[&str], timeout: Duration) -> Result<Vec<u8>, String> {
+    use std::process::{Command, Stdio};
+    use std::thread;
+    use std::time::Instant;
+
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("could not run {}: {}", program, e))?;
+    let mut stdout = child.stdout.take().expect("piped stdout");
+    let mut stderr = child.stderr.take().expect("piped stderr");
+    let out = thread::spawn(move || {
+        let mut bytes = Vec::new();
+        stdout.read_to_end(&mut bytes).map(|_| bytes)
+    });
+    let err = thread::spawn(move || {
+        let mut bytes = Vec::new();
+        let _ = stderr.read_to_end(&mut bytes);
+        bytes
+    });
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("{} timed out", program));
+            }
+            Ok(None) => thread::sleep(Duration::from_millis(10)),
+            Err(e) => return Err(e.to_string()),
+        }
+    };
+
+    let bytes = out
+        .join()
+        .map_err(|_| format!("{} output could not be read", program))?
+        .map_err(|e| e.to_string())?;
+    let message = err.join().unwrap_or_default();
+    if !status.success() {
+        let message = String::from_utf8_lossy(&message);
+        return Err(match message.trim() {
+            "" => format!("{} failed ({})", program, status),
+            message => message.to_string(),
+        });
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_for() {
+        assert!(is_remote("https://example.com/config.yay"));
+        assert!(is_remote("s3://bucket/config.yay"));
+        assert!(is_remote("gs://bucket/config.yay"));
+        assert!(!is_remote("config.yay"));
+        assert!(!is_remote("s3-configs/config.yay"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run() {
+        let timeout = Duration::from_secs(5);
+        assert_eq!(
+            run("sh", &["-c", "printf 'a: 1'"], timeout).unwrap(),
+            b"a: 1"
+        );
+        let error = run("sh", &["-c", "echo denied >&2; exit 1"], timeout).unwrap_err();
+        assert_eq!(error, "denied");
+        let error = run("sh", &["-c", "sleep 5"], Duration::from_millis(50)).unwrap_err();
+        assert_eq!(error, "sh timed out");
+    }
+}
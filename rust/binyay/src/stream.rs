@@ -0,0 +1,124 @@
+//! `yay -f cbor --stream [--max-memory SIZE] IN.cbor`: a CBOR conversion
+//! that never holds the whole document.
+//!
+//! The input is read as events and handed to `libyay::stream`, which holds
+//! at most about `SIZE` bytes of the document at a time and spills the
+//! members of large objects to temporary files while it sorts their keys.
+//! The output is the same as a conversion without `--stream` would write.
+
+use libyay::compress::DecompressReader;
+use libyay::stream::encode_events;
+use libyay::Format;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use crate::transcode::cbor::{DecodeOptions, Events};
+
+/// The default memory budget.
+pub const DEFAULT_MAX_MEMORY: usize = 64 << 20;
+
+/// Convert the CBOR document at `input` (stdin if `None`) to `format` at
+/// `output` (stdout if `None`), holding about `max_memory` bytes at a time.
+pub fn convert(
+    input: Option<&str>,
+    output: Option<&str>,
+    format: Format,
+    options: DecodeOptions,
+    max_memory: usize,
+) -> Result<(), String> {
+    let label = input.unwrap_or("stdin");
+    let reader: Box<dyn Read> = match input {
+        Some(path) => {
+            Box::new(File::open(path).map_err(|e| format!("Error reading {}: {}", path, e))?)
+        }
+        None => Box::new(io::stdin().lock()),
+    };
+    let events = Events::new(BufReader::new(DecompressReader::new(reader)), options);
+
+    let write_error = |e: io::Error| format!("Error writing {}: {}", output.unwrap_or("stdout"), e);
+    // A file is written beside the output and renamed over it once
+    // complete, so a conversion cut short leaves the output as it was.
+    let temp = output.map(|path| Path::new(path).with_extension("stream.tmp"));
+    let mut out: BufWriter<Box<dyn Write>> = BufWriter::new(match &temp {
+        Some(temp) => Box::new(File::create(temp).map_err(write_error)?),
+        None => Box::new(io::stdout().lock()),
+    });
+    let result = encode_events(events, &mut out, format, max_memory)
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::InvalidData => format!("{}: {}", label, e),
+            _ => write_error(e),
+        })
+        .and_then(|()| {
+            if output.is_none() {
+                out.write_all(b"\n").map_err(write_error)?;
+            }
+            out.flush().map_err(write_error)
+        });
+    drop(out);
+    match (temp, output) {
+        (Some(temp), Some(path)) => match result {
+            Ok(()) => fs::rename(&temp, path).map_err(write_error),
+            Err(e) => {
+                let _ = fs::remove_file(&temp);
+                Err(e)
+            }
+        },
+        _ => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcode::cbor;
+    use libyay::{encode, Map, Value};
+    use num_bigint::BigInt;
+
+    fn events(bytes: &[u8]) -> Result<Vec<libyay::stream::Event>, String> {
+        Events::new(bytes, DecodeOptions::default())
+            .collect::<io::Result<Vec<_>>>()
+            .map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn test_matches_buffered_conversion() {
+        let rows: Vec<Value> = (0..300)
+            .map(|i| {
+                let mut map = Map::new();
+                map.insert("z".to_string(), Value::Integer(BigInt::from(i)));
+                map.insert("a".to_string(), Value::Array(vec![Value::Null; i % 8]));
+                map.insert("m".to_string(), Value::Bytes(vec![i as u8; 3]));
+                Value::Object(map)
+            })
+            .collect();
+        let mut root = Map::new();
+        root.insert("rows".to_string(), Value::Array(rows));
+        root.insert("name".to_string(), Value::String("big".to_string()));
+        let value = Value::Object(root);
+        let bytes = cbor::encode(&value).unwrap();
+
+        for format in [Format::Yay, Format::Yson] {
+            let mut out = Vec::new();
+            let events = Events::new(&bytes[..], DecodeOptions::default());
+            encode_events(events, &mut out, format, 1024).unwrap();
+            assert_eq!(String::from_utf8(out).unwrap(), encode(&value, format));
+        }
+    }
+
+    #[test]
+    fn test_indefinite_lengths_and_errors() {
+        // {_ "a": [_ 1, "xy"], "b": null}
+        let bytes = [
+            0xbf, 0x61, 0x61, 0x9f, 0x01, 0x62, 0x78, 0x79, 0xff, 0x61, 0x62, 0xf6, 0xff,
+        ];
+        assert_eq!(events(&bytes).unwrap().len(), 9);
+
+        let err = events(&[0xa1, 0x01, 0x02]).unwrap_err();
+        assert!(err.contains("map key at byte offset 1"), "{}", err);
+        let err = events(&[0x81, 0xd8, 0x20, 0x61, 0x78]).unwrap_err();
+        assert!(err.contains("tag 32"), "{}", err);
+        let err = events(&[0x82, 0x01]).unwrap_err();
+        assert!(err.contains("byte offset 2"), "{}", err);
+    }
+}
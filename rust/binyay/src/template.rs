@@ -0,0 +1,158 @@
+//! Output templates for `--template`: a file the encoded document is
+//! written into, so generated code can carry its own package line,
+//! imports, and lint pragmas.
+//!
+//! ```go
+//! // Code generated by yay from {{source_path}}. DO NOT EDIT.
+//! package config
+//!
+//! var {{symbol}} = {{value}}
+//! ```
+//!
+//! `{{value}}` is the encoded document, `{{symbol}}` an identifier made
+//! from the input's file name, and `{{source_path}}` the input path as
+//! given. Text between braces that is not a name, like `{{ }}` or
+//! `{{.Field}}`, is left alone.
+
+use std::fs;
+use std::path::Path;
+
+const PLACEHOLDERS: &[&str] = &["value", "symbol", "source_path"];
+
+#[derive(Debug)]
+pub struct Template {
+    text: String,
+}
+
+impl Template {
+    /// Read the template at `path`, refusing placeholders it does not know
+    /// and a template with nowhere to put the value.
+    pub fn load(path: &str) -> Result<Template, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Error reading template {}: {}", path, e))?;
+        Template::new(text).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    fn new(text: String) -> Result<Template, String> {
+        let found = placeholders(&text);
+        for (_, name) in &found {
+            if !PLACEHOLDERS.contains(name) {
+                return Err(format!("Unknown template placeholder: {{{{{}}}}}", name));
+            }
+        }
+        if !found.iter().any(|(_, name)| *name == "value") {
+            return Err("Template has no {{value}} placeholder".to_string());
+        }
+        Ok(Template { text })
+    }
+
+    /// The template with `value`, the output for `input_file` (stdin if
+    /// `None`), in place of `{{value}}`.
+    pub fn render(&self, value: &str, input_file: Option<&str>) -> String {
+        let value = value.strip_suffix('\n').unwrap_or(value);
+        let mut out = String::with_capacity(self.text.len() + value.len());
+        let mut last = 0;
+        for (range, name) in placeholders(&self.text) {
+            out.push_str(&self.text[last..range.start]);
+            match name {
+                "value" => out.push_str(value),
+                "symbol" => out.push_str(&symbol(input_file)),
+                _ => out.push_str(input_file.unwrap_or("-")),
+            }
+            last = range.end;
+        }
+        out.push_str(&self.text[last..]);
+        out
+    }
+}
+
+/// Each `{{name}}` in `text`, with its byte range.
+fn placeholders(text: &str) -> Vec<(std::ops::Range<usize>, &str)> {
+    let mut found = Vec::new();
+    let mut from = 0;
+    while let Some(open) = text[from..].find("{{").map(|i| from + i) {
+        let Some(close) = text[open + 2..].find("}}").map(|i| open + 2 + i) else {
+            break;
+        };
+        let name = text[open + 2..close].trim();
+        if !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            found.push((open..close + 2, name));
+            from = close + 2;
+        } else {
+            from = open + 2;
+        }
+    }
+    found
+}
+
+/// An identifier for the input: its file name up to the first `.`, with
+/// anything but letters, digits, and `_` made `_`. Stdin is `value`.
+fn symbol(input_file: Option<&str>) -> String {
+    let Some(name) = input_file
+        .and_then(|path| Path::new(path).file_name())
+        .map(|name| name.to_string_lossy())
+    else {
+        return "value".to_string();
+    };
+    let stem = name.split('.').next().unwrap_or_default();
+    let mut symbol: String = stem
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if symbol.is_empty() {
+        return "value".to_string();
+    }
+    if symbol.starts_with(|c: char| c.is_ascii_digit()) {
+        symbol.insert(0, '_');
+    }
+    symbol
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let template =
+            Template::new("// from {{source_path}}\nvar {{ symbol }} = {{value}}\n".to_string())
+                .unwrap();
+        assert_eq!(
+            template.render("map[string]any{}\n", Some("configs/app-settings.yay.gz")),
+            "// from configs/app-settings.yay.gz\nvar app_settings = map[string]any{}\n"
+        );
+        assert_eq!(template.render("1", None), "// from -\nvar value = 1\n");
+        assert_eq!(symbol(Some("2024.yay")), "_2024");
+    }
+
+    #[test]
+    fn test_other_braces() {
+        let template = Template::new("{{ }} {{.Field}} {{value}} {{".to_string()).unwrap();
+        assert_eq!(template.render("1", None), "{{ }} {{.Field}} 1 {{");
+        assert_eq!(
+            Template::new("{{name}}".to_string()).unwrap_err(),
+            "Unknown template placeholder: {{name}}"
+        );
+    }
+
+    #[test]
+    fn test_requires_value() {
+        assert_eq!(
+            Template::new("var {{symbol}} = {{ value  }}".to_string())
+                .unwrap()
+                .render("1", None),
+            "var value = 1"
+        );
+        assert_eq!(
+            Template::new("package config\n// {{source_path}}\n".to_string()).unwrap_err(),
+            "Template has no {{value}} placeholder"
+        );
+        assert!(Template::new("{{.value}}".to_string()).is_err());
+    }
+}
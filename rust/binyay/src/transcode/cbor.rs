@@ -9,7 +9,10 @@
 //!   - CBOR byte string           -> Value::Bytes
 //!   - CBOR array (det/indet)     -> Value::Array
 //!   - CBOR map (det/indet)       -> Value::Object (text string keys only)
-//!   - CBOR tag                   -> error (no YAY equivalent)
+//!   - CBOR tag 0 (date/time)     -> Value::DateTime
+//!   - CBOR tag 4 (decimal)       -> Value::Decimal
+//!   - CBOR tag 1 (epoch time)    -> Value::DateTime (UTC)
+//!   - Any other CBOR tag         -> error (no YAY equivalent)
 //!   - CBOR undefined             -> error (no YAY equivalent)
 //!   - Any other CBOR value       -> error
 //!
@@ -20,31 +23,353 @@
 //!   - Value::Float   -> CBOR float64 (always 9 bytes, never downgraded)
 //!   - Value::String  -> CBOR text string (determinate length)
 //!   - Value::Bytes   -> CBOR byte string (determinate length)
+//!   - Value::Decimal -> CBOR tag 4 [exponent, mantissa]
+//!   - Value::DateTime -> CBOR tag 0 with the RFC 3339 text, or a text
+//!     string for a date or time without an offset, which tag 0 cannot hold
 //!   - Value::Array   -> CBOR array (determinate length)
 //!   - Value::Object  -> CBOR map (determinate length, text string keys)
 //!
 //! Integers that exceed CBOR's native integer range (-2^64 to 2^64-1)
 //! produce an error rather than using bignum tags.
 
+use super::{child_path, DecodeError, Location};
 use ciborium::value::Value as CborValue;
-use libyay::Value;
+use libyay::stream::Event;
+use libyay::{DateTime, Decimal, KeyOrder, Map, Value};
 use num_bigint::BigInt;
 use num_traits::ToPrimitive;
-use std::collections::HashMap;
 use std::fmt::Write as FmtWrite;
+use std::io::{self, Read};
 
 // ---------------------------------------------------------------------------
 // Decode (CBOR -> YAY)
 // ---------------------------------------------------------------------------
 
+/// What to do with a CBOR text string that is not valid UTF-8.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Utf8Policy {
+    /// Fail, reporting the byte offset of the string.
+    #[default]
+    Error,
+    /// Replace invalid sequences with U+FFFD.
+    Lossy,
+    /// Import the string as bytes. Map keys cannot be bytes, so an invalid
+    /// key is still an error.
+    Bytes,
+}
+
+impl Utf8Policy {
+    /// Parse a policy name (`error`, `lossy`, or `bytes`).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "error" => Some(Utf8Policy::Error),
+            "lossy" => Some(Utf8Policy::Lossy),
+            "bytes" => Some(Utf8Policy::Bytes),
+            _ => None,
+        }
+    }
+}
+
+/// Options for decoding CBOR.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DecodeOptions {
+    /// Handling of text strings that are not valid UTF-8.
+    pub invalid_utf8: Utf8Policy,
+}
+
 /// Decode CBOR bytes into a YAY Value.
-pub fn decode(input: &[u8]) -> Result<Value, String> {
+pub fn decode(input: &[u8]) -> Result<Value, DecodeError> {
+    decode_with_options(input, &DecodeOptions::default())
+}
+
+/// Decode CBOR bytes into a YAY Value with the given options.
+pub fn decode_with_options(input: &[u8], options: &DecodeOptions) -> Result<Value, DecodeError> {
+    // ciborium rejects invalid UTF-8 without saying where, so text strings
+    // are checked and rewritten according to the policy beforehand.
+    let repaired = repair_text(input, options.invalid_utf8)?;
+    let input = repaired.as_deref().unwrap_or(input);
     let cbor_value: CborValue =
-        ciborium::de::from_reader(input).map_err(|e| format!("CBOR decode error: {}", e))?;
-    cbor_to_value(&cbor_value)
+        ciborium::de::from_reader(input).map_err(|e| read_error(e, input.len()))?;
+    cbor_to_value(&cbor_value, "")
+}
+
+/// Describe a ciborium error by the byte offset it gives, in place of its
+/// `Debug` form.
+fn read_error(e: ciborium::de::Error<io::Error>, len: usize) -> DecodeError {
+    use ciborium::de::Error;
+    match e {
+        Error::Io(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+            DecodeError::new("CBOR input ends inside an item").at(Location::Offset(len))
+        }
+        Error::Io(e) => DecodeError::new(format!("CBOR read error: {}", e)),
+        Error::Syntax(offset) => DecodeError::new("malformed CBOR").at(Location::Offset(offset)),
+        Error::Semantic(offset, message) => {
+            let error = DecodeError::new(format!("CBOR {}", message));
+            match offset {
+                Some(offset) => error.at(Location::Offset(offset)),
+                None => error,
+            }
+        }
+        Error::RecursionLimitExceeded => {
+            DecodeError::new(format!("CBOR nesting is deeper than {} levels", MAX_DEPTH))
+        }
+    }
+}
+
+/// Describe an error in an item that starts at byte `start` of the input.
+fn in_item(e: DecodeError, start: u64) -> String {
+    match e.location {
+        Some(Location::Offset(offset)) => DecodeError {
+            location: Some(Location::Offset(offset + start as usize)),
+            ..e
+        }
+        .to_string(),
+        _ => format!("{} (in the item at byte offset {})", e, start),
+    }
+}
+
+/// Walk the CBOR item structure, applying `policy` to text strings that are
+/// not valid UTF-8. Returns the rewritten input if any string changed.
+///
+/// Malformed or truncated input is left for ciborium to report.
+fn repair_text(input: &[u8], policy: Utf8Policy) -> Result<Option<Vec<u8>>, DecodeError> {
+    let mut walker = TextWalker {
+        input,
+        pos: 0,
+        out: Vec::with_capacity(input.len()),
+        policy,
+        changed: false,
+    };
+    match walker.item(false) {
+        Ok(Some(())) if walker.changed => {
+            walker.out.extend_from_slice(&input[walker.pos..]);
+            Ok(Some(walker.out))
+        }
+        Ok(_) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+struct TextWalker<'a> {
+    input: &'a [u8],
+    pos: usize,
+    out: Vec<u8>,
+    policy: Utf8Policy,
+    changed: bool,
 }
 
-fn cbor_to_value(cbor: &CborValue) -> Result<Value, String> {
+impl TextWalker<'_> {
+    /// Read an item head, returning the major type and argument. An
+    /// indefinite length is reported as `None`.
+    fn head(&mut self) -> Option<(u8, Option<u64>)> {
+        let initial = *self.input.get(self.pos)?;
+        let (major, info) = (initial >> 5, initial & 0x1f);
+        let size = match info {
+            0..=23 => 0,
+            24 => 1,
+            25 => 2,
+            26 => 4,
+            27 => 8,
+            31 if matches!(major, 2..=5 | 7) => {
+                self.pos += 1;
+                return Some((major, None));
+            }
+            _ => return None,
+        };
+        let arg_bytes = self.input.get(self.pos + 1..self.pos + 1 + size)?;
+        let arg = if size == 0 {
+            info as u64
+        } else {
+            arg_bytes.iter().fold(0u64, |acc, &b| (acc << 8) | b as u64)
+        };
+        self.pos += 1 + size;
+        Some((major, Some(arg)))
+    }
+
+    fn copy_from(&mut self, start: usize) {
+        self.out.extend_from_slice(&self.input[start..self.pos]);
+    }
+
+    fn take(&mut self, len: u64) -> Option<&[u8]> {
+        let end = self.pos.checked_add(usize::try_from(len).ok()?)?;
+        let bytes = self.input.get(self.pos..end)?;
+        self.pos = end;
+        Some(bytes)
+    }
+
+    /// Walk one item. `Ok(None)` means the input is malformed.
+    fn item(&mut self, is_key: bool) -> Result<Option<()>, DecodeError> {
+        let start = self.pos;
+        let Some((major, arg)) = self.head() else {
+            return Ok(None);
+        };
+        match (major, arg) {
+            (3, _) => return self.text(start, arg, is_key),
+            (2, Some(len)) => {
+                if self.take(len).is_none() {
+                    return Ok(None);
+                }
+                self.copy_from(start);
+            }
+            (2, None) => {
+                self.copy_from(start);
+                loop {
+                    if self.input.get(self.pos) == Some(&0xff) {
+                        self.pos += 1;
+                        self.out.push(0xff);
+                        break;
+                    }
+                    let chunk = self.pos;
+                    match self.head() {
+                        Some((2, Some(len))) if self.take(len).is_some() => self.copy_from(chunk),
+                        _ => return Ok(None),
+                    }
+                }
+            }
+            (4, _) | (5, _) => {
+                self.copy_from(start);
+                let per_entry = if major == 5 { 2 } else { 1 };
+                let mut index: u64 = 0;
+                loop {
+                    match arg {
+                        Some(count) if index == count.saturating_mul(per_entry) => break,
+                        None if self.input.get(self.pos) == Some(&0xff) => {
+                            self.pos += 1;
+                            self.out.push(0xff);
+                            break;
+                        }
+                        _ => {}
+                    }
+                    let is_key = major == 5 && index.is_multiple_of(2);
+                    if self.item(is_key)?.is_none() {
+                        return Ok(None);
+                    }
+                    index += 1;
+                }
+            }
+            (6, Some(_)) => {
+                self.copy_from(start);
+                return self.item(false);
+            }
+            (7, None) => return Ok(None),
+            _ => self.copy_from(start),
+        }
+        Ok(Some(()))
+    }
+
+    fn text(
+        &mut self,
+        start: usize,
+        len: Option<u64>,
+        is_key: bool,
+    ) -> Result<Option<()>, DecodeError> {
+        let bytes = match len {
+            Some(len) => match self.take(len) {
+                Some(bytes) => bytes.to_vec(),
+                None => return Ok(None),
+            },
+            None => {
+                let mut bytes = Vec::new();
+                loop {
+                    if self.input.get(self.pos) == Some(&0xff) {
+                        self.pos += 1;
+                        break;
+                    }
+                    match self.head() {
+                        Some((3, Some(len))) => match self.take(len) {
+                            Some(chunk) => bytes.extend_from_slice(chunk),
+                            None => return Ok(None),
+                        },
+                        _ => return Ok(None),
+                    }
+                }
+                bytes
+            }
+        };
+        let error = match std::str::from_utf8(&bytes) {
+            Ok(_) => {
+                self.copy_from(start);
+                return Ok(Some(()));
+            }
+            Err(e) => e,
+        };
+        let describe = |also: &str| {
+            DecodeError::new(format!(
+                "CBOR text string is not valid UTF-8 (its byte {} starts an invalid sequence{})",
+                error.valid_up_to(),
+                also
+            ))
+            .at(Location::Offset(start))
+        };
+        match self.policy {
+            Utf8Policy::Error => return Err(describe("")),
+            Utf8Policy::Bytes if is_key => return Err(describe(", and map keys cannot be bytes")),
+            Utf8Policy::Lossy => {
+                let text = String::from_utf8_lossy(&bytes);
+                write_type_and_length(&mut self.out, 3, text.len() as u64);
+                self.out.extend_from_slice(text.as_bytes());
+            }
+            Utf8Policy::Bytes => {
+                write_type_and_length(&mut self.out, 2, bytes.len() as u64);
+                self.out.extend_from_slice(&bytes);
+            }
+        }
+        self.changed = true;
+        Ok(Some(()))
+    }
+}
+
+/// Convert a CBOR value at `path`. ciborium keeps no offsets once it has
+/// read an item, so errors here carry the path instead.
+fn cbor_to_value(cbor: &CborValue, path: &str) -> Result<Value, DecodeError> {
+    match cbor {
+        CborValue::Array(arr) => {
+            let items: Result<Vec<Value>, DecodeError> = arr
+                .iter()
+                .enumerate()
+                .map(|(i, item)| cbor_to_value(item, &format!("{}[{}]", path, i)))
+                .collect();
+            Ok(Value::Array(items?))
+        }
+        CborValue::Map(pairs) => {
+            let mut obj = Map::new();
+            for (k, v) in pairs {
+                let key = match k {
+                    CborValue::Text(s) => s.clone(),
+                    _ => {
+                        return Err(DecodeError::new(format!(
+                            "CBOR map key must be a text string, not {}",
+                            kind(k)
+                        ))
+                        .in_path(path))
+                    }
+                };
+                let value = cbor_to_value(v, &child_path(path, &key))?;
+                obj.insert(key, value);
+            }
+            Ok(Value::Object(obj))
+        }
+        scalar => cbor_scalar_to_value(scalar).map_err(|e| DecodeError::new(e).in_path(path)),
+    }
+}
+
+/// Name the type of a CBOR value for messages.
+fn kind(cbor: &CborValue) -> String {
+    match cbor {
+        CborValue::Integer(_) => "an integer".to_string(),
+        CborValue::Bytes(_) => "a byte string".to_string(),
+        CborValue::Float(_) => "a float".to_string(),
+        CborValue::Text(_) => "a text string".to_string(),
+        CborValue::Bool(_) => "a boolean".to_string(),
+        CborValue::Null => "null".to_string(),
+        CborValue::Tag(tag, _) => format!("a value with tag {}", tag),
+        CborValue::Array(_) => "an array".to_string(),
+        CborValue::Map(_) => "a map".to_string(),
+        _ => "undefined".to_string(),
+    }
+}
+
+fn cbor_scalar_to_value(cbor: &CborValue) -> Result<Value, String> {
     match cbor {
         CborValue::Null => Ok(Value::Null),
         CborValue::Bool(b) => Ok(Value::Bool(*b)),
@@ -55,26 +380,394 @@ fn cbor_to_value(cbor: &CborValue) -> Result<Value, String> {
         CborValue::Float(f) => Ok(Value::Float(*f)),
         CborValue::Text(s) => Ok(Value::String(s.clone())),
         CborValue::Bytes(b) => Ok(Value::Bytes(b.clone())),
-        CborValue::Array(arr) => {
-            let items: Result<Vec<Value>, String> = arr.iter().map(cbor_to_value).collect();
-            Ok(Value::Array(items?))
+        CborValue::Tag(0, inner) => match inner.as_ref() {
+            CborValue::Text(s) => DateTime::parse(s)
+                .map(Value::DateTime)
+                .ok_or_else(|| format!("CBOR tag 0 holds an invalid date and time: {:?}", s)),
+            _ => Err("CBOR tag 0 must hold a text string".to_string()),
+        },
+        CborValue::Tag(1, inner) => {
+            let (seconds, nanos) = match inner.as_ref() {
+                CborValue::Integer(i) => (i64::try_from(*i).ok(), 0),
+                CborValue::Float(f) if f.is_finite() => {
+                    let nanos = ((f - f.floor()) * 1e9).round().min(999_999_999.0);
+                    (f.floor().to_i64(), nanos as u32)
+                }
+                _ => return Err("CBOR tag 1 must hold a number".to_string()),
+            };
+            seconds
+                .and_then(|seconds| DateTime::from_unix(seconds, nanos))
+                .map(Value::DateTime)
+                .ok_or_else(|| "CBOR tag 1 holds a time outside years 0 to 9999".to_string())
         }
-        CborValue::Map(pairs) => {
-            let mut obj = HashMap::new();
-            for (k, v) in pairs {
-                let key = match k {
-                    CborValue::Text(s) => s.clone(),
-                    _ => return Err(format!("CBOR map key must be a text string, got: {:?}", k)),
-                };
-                obj.insert(key, cbor_to_value(v)?);
+        CborValue::Tag(4, inner) => {
+            let invalid = || "CBOR tag 4 must hold [exponent, mantissa] integers".to_string();
+            let CborValue::Array(pair) = inner.as_ref() else {
+                return Err(invalid());
+            };
+            match pair.as_slice() {
+                [CborValue::Integer(exponent), mantissa] => {
+                    let exponent = i64::try_from(*exponent).map_err(|_| invalid())?;
+                    // The mantissa may be a bignum (tag 2 or 3).
+                    let mantissa = match mantissa {
+                        CborValue::Integer(i) => BigInt::from(i128::from(*i)),
+                        CborValue::Tag(tag @ (2 | 3), bytes) => match bytes.as_ref() {
+                            CborValue::Bytes(bytes) => {
+                                let n = BigInt::from_bytes_be(num_bigint::Sign::Plus, bytes);
+                                if *tag == 2 {
+                                    n
+                                } else {
+                                    -1 - n
+                                }
+                            }
+                            _ => return Err(invalid()),
+                        },
+                        _ => return Err(invalid()),
+                    };
+                    Ok(Value::Decimal(Decimal::new(mantissa, exponent)))
+                }
+                _ => Err(invalid()),
             }
-            Ok(Value::Object(obj))
         }
         CborValue::Tag(tag, _) => Err(format!(
             "CBOR tagged value (tag {}) has no YAY equivalent",
             tag
         )),
-        _ => Err(format!("CBOR value {:?} has no YAY equivalent", cbor)),
+        _ => Err(format!("CBOR {} has no YAY equivalent", kind(cbor))),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Item-at-a-time decoding of a top-level array
+//
+// Each item's bytes are copied out of the reader and decoded on their own,
+// so memory use is bounded by the largest item rather than the whole input.
+// ---------------------------------------------------------------------------
+
+/// The deepest nesting accepted, matching ciborium's recursion limit.
+const MAX_DEPTH: usize = 256;
+
+/// Reads the items of a top-level CBOR array one at a time.
+pub struct ArrayItems<R> {
+    reader: R,
+    offset: u64,
+    remaining: Option<u64>,
+    options: DecodeOptions,
+    done: bool,
+}
+
+impl<R: Read> ArrayItems<R> {
+    /// Read the head of the array at the start of `reader`.
+    pub fn new(mut reader: R, options: DecodeOptions) -> Result<Self, String> {
+        let mut head = Vec::new();
+        let remaining = match read_head(&mut reader, &mut head) {
+            Ok((4, count)) => count,
+            Ok(_) => return Err("CBOR input is not an array".to_string()),
+            Err(e) => return Err(format!("CBOR decode error: {}", e)),
+        };
+        Ok(ArrayItems {
+            reader,
+            offset: head.len() as u64,
+            remaining,
+            options,
+            done: false,
+        })
+    }
+
+    /// Continue an array from a reader positioned at byte `offset` of the
+    /// input, with `remaining` items left, or `None` if the array has an
+    /// indefinite length.
+    pub fn resume(reader: R, offset: u64, remaining: Option<u64>, options: DecodeOptions) -> Self {
+        ArrayItems {
+            reader,
+            offset,
+            remaining,
+            options,
+            done: false,
+        }
+    }
+
+    /// The input offset just past the last item read.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// The items left in a definite-length array.
+    pub fn remaining(&self) -> Option<u64> {
+        self.remaining
+    }
+
+    fn next_item(&mut self) -> Result<Option<Value>, String> {
+        if self.remaining == Some(0) {
+            return Ok(None);
+        }
+        let mut item = Vec::new();
+        let at = |e: io::Error| format!("CBOR decode error at byte offset {}: {}", self.offset, e);
+        let is_item = read_item(&mut self.reader, &mut item, 0).map_err(at)?;
+        if !is_item {
+            if self.remaining.is_some() {
+                return Err(at(malformed()));
+            }
+            self.offset += item.len() as u64;
+            return Ok(None);
+        }
+        let value =
+            decode_with_options(&item, &self.options).map_err(|e| in_item(e, self.offset))?;
+        self.offset += item.len() as u64;
+        if let Some(remaining) = &mut self.remaining {
+            *remaining -= 1;
+        }
+        Ok(Some(value))
+    }
+}
+
+impl<R: Read> Iterator for ArrayItems<R> {
+    type Item = Result<Value, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let result = self.next_item();
+        if !matches!(result, Ok(Some(_))) {
+            self.done = true;
+        }
+        result.transpose()
+    }
+}
+
+fn malformed() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "malformed CBOR")
+}
+
+/// Read an item head into `out`, returning the major type and argument. An
+/// indefinite length, or a break, is reported as `None`.
+fn read_head<R: Read>(reader: &mut R, out: &mut Vec<u8>) -> io::Result<(u8, Option<u64>)> {
+    let mut initial = [0];
+    reader.read_exact(&mut initial)?;
+    out.push(initial[0]);
+    let (major, info) = (initial[0] >> 5, initial[0] & 0x1f);
+    let size = match info {
+        0..=23 => return Ok((major, Some(info as u64))),
+        24 => 1,
+        25 => 2,
+        26 => 4,
+        27 => 8,
+        31 if matches!(major, 2..=5 | 7) => return Ok((major, None)),
+        _ => return Err(malformed()),
+    };
+    let mut arg = [0; 8];
+    reader.read_exact(&mut arg[8 - size..])?;
+    out.extend_from_slice(&arg[8 - size..]);
+    Ok((major, Some(u64::from_be_bytes(arg))))
+}
+
+/// Copy one complete item from `reader` into `out`. Returns `false` if a
+/// break was read instead.
+fn read_item<R: Read>(reader: &mut R, out: &mut Vec<u8>, depth: usize) -> io::Result<bool> {
+    if depth > MAX_DEPTH {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "CBOR nesting is too deep",
+        ));
+    }
+    match read_head(reader, out)? {
+        (7, None) => return Ok(false),
+        (major @ (2 | 3), len) => read_string(reader, out, major, len)?,
+        (major @ (4..=6), Some(count)) => {
+            // A tag is followed by exactly one item.
+            let items = match major {
+                4 => count,
+                5 => count.saturating_mul(2),
+                _ => 1,
+            };
+            for _ in 0..items {
+                if !read_item(reader, out, depth + 1)? {
+                    return Err(malformed());
+                }
+            }
+        }
+        (4 | 5, None) => while read_item(reader, out, depth + 1)? {},
+        _ => {}
+    }
+    Ok(true)
+}
+
+/// Copy the payload of a byte or text string whose head has been read,
+/// including the chunks of an indefinite-length string.
+fn read_string<R: Read>(
+    reader: &mut R,
+    out: &mut Vec<u8>,
+    major: u8,
+    len: Option<u64>,
+) -> io::Result<()> {
+    if let Some(len) = len {
+        return read_payload(reader, out, len);
+    }
+    loop {
+        match read_head(reader, out)? {
+            (7, None) => return Ok(()),
+            (chunk, Some(len)) if chunk == major => read_payload(reader, out, len)?,
+            _ => return Err(malformed()),
+        }
+    }
+}
+
+fn read_payload<R: Read>(reader: &mut R, out: &mut Vec<u8>, len: u64) -> io::Result<()> {
+    let copied = io::copy(&mut reader.by_ref().take(len), out)?;
+    if copied < len {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Event-at-a-time decoding
+//
+// Arrays and maps become start and end events, so a document of any size
+// can be converted by `libyay::stream` without holding it whole. Only
+// scalars are read into memory.
+// ---------------------------------------------------------------------------
+
+/// Reads a CBOR document as a sequence of events.
+pub struct Events<R> {
+    reader: R,
+    offset: u64,
+    options: DecodeOptions,
+    open: Vec<Container>,
+    started: bool,
+    done: bool,
+}
+
+/// An array or map whose end has not been read.
+struct Container {
+    is_map: bool,
+    /// Items in a definite-length container, counting keys and values of a
+    /// map separately.
+    len: Option<u64>,
+    read: u64,
+}
+
+impl<R: Read> Events<R> {
+    pub fn new(reader: R, options: DecodeOptions) -> Self {
+        Events {
+            reader,
+            offset: 0,
+            options,
+            open: Vec::new(),
+            started: false,
+            done: false,
+        }
+    }
+
+    fn next_event(&mut self) -> Result<Option<Event>, String> {
+        match self.open.last() {
+            Some(c) if c.len == Some(c.read) => {
+                self.open.pop();
+                return Ok(Some(Event::End));
+            }
+            None if self.started => return Ok(None),
+            _ => {}
+        }
+        self.started = true;
+        let start = self.offset;
+        let at = |e: io::Error| format!("CBOR decode error at byte offset {}: {}", start, e);
+
+        let mut item = Vec::new();
+        let (major, arg) = read_head(&mut self.reader, &mut item).map_err(at)?;
+        self.offset += item.len() as u64;
+        let is_key = match self.open.last_mut() {
+            Some(c) if (major, arg) == (7, None) => {
+                if c.len.is_some() || (c.is_map && c.read % 2 == 1) {
+                    return Err(at(malformed()));
+                }
+                self.open.pop();
+                return Ok(Some(Event::End));
+            }
+            Some(c) => {
+                c.read += 1;
+                c.is_map && c.read % 2 == 1
+            }
+            None => false,
+        };
+
+        match (major, arg) {
+            (4 | 5, len) => {
+                if is_key {
+                    return Err(format!(
+                        "CBOR map key at byte offset {} must be a text string",
+                        start
+                    ));
+                }
+                if self.open.len() >= MAX_DEPTH {
+                    return Err(at(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "CBOR nesting is too deep",
+                    )));
+                }
+                let is_map = major == 5;
+                let len = match len {
+                    Some(n) if is_map => Some(n.checked_mul(2).ok_or_else(|| at(malformed()))?),
+                    len => len,
+                };
+                self.open.push(Container {
+                    is_map,
+                    len,
+                    read: 0,
+                });
+                Ok(Some(if is_map {
+                    Event::StartObject
+                } else {
+                    Event::StartArray
+                }))
+            }
+            (6, Some(tag)) if !matches!(tag, 0 | 1 | 4) => Err(format!(
+                "CBOR tagged value (tag {}) at byte offset {} has no YAY equivalent",
+                tag, start
+            )),
+            (7, None) => Err(at(malformed())),
+            _ => {
+                let head = item.len();
+                match (major, arg) {
+                    (2 | 3, len) => {
+                        read_string(&mut self.reader, &mut item, major, len).map_err(at)?
+                    }
+                    // A date and time or a decimal: the tag's one item.
+                    (6, _) if !read_item(&mut self.reader, &mut item, 0).map_err(at)? => {
+                        return Err(at(malformed()));
+                    }
+                    _ => {}
+                }
+                self.offset += (item.len() - head) as u64;
+                let value =
+                    decode_with_options(&item, &self.options).map_err(|e| in_item(e, start))?;
+                match value {
+                    Value::String(key) if is_key => Ok(Some(Event::Key(key))),
+                    _ if is_key => Err(format!(
+                        "CBOR map key at byte offset {} must be a text string",
+                        start
+                    )),
+                    value => Ok(Some(Event::Scalar(value))),
+                }
+            }
+        }
+    }
+}
+
+impl<R: Read> Iterator for Events<R> {
+    type Item = io::Result<Event>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let result = self.next_event();
+        if !matches!(result, Ok(Some(_))) {
+            self.done = true;
+        }
+        result
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+            .transpose()
     }
 }
 
@@ -90,12 +783,18 @@ fn cbor_to_value(cbor: &CborValue) -> Result<Value, String> {
 
 /// Encode a YAY Value as CBOR bytes.
 pub fn encode(value: &Value) -> Result<Vec<u8>, String> {
+    encode_ordered(value, KeyOrder::Bytes)
+}
+
+/// Encode a YAY Value as CBOR bytes, with the keys of each map in `keys`
+/// order.
+pub fn encode_ordered(value: &Value, keys: KeyOrder) -> Result<Vec<u8>, String> {
     let mut buf = Vec::new();
-    write_value(&mut buf, value)?;
+    write_value(&mut buf, value, keys)?;
     Ok(buf)
 }
 
-fn write_value(buf: &mut Vec<u8>, value: &Value) -> Result<(), String> {
+fn write_value(buf: &mut Vec<u8>, value: &Value, order: KeyOrder) -> Result<(), String> {
     match value {
         Value::Null => {
             // CBOR simple value 22 = null
@@ -125,16 +824,31 @@ fn write_value(buf: &mut Vec<u8>, value: &Value) -> Result<(), String> {
             buf.extend_from_slice(b);
             Ok(())
         }
+        Value::Decimal(d) => {
+            write_type_and_length(buf, 6, 4); // major 6 = tag, 4 = decimal fraction
+            write_type_and_length(buf, 4, 2);
+            write_integer(buf, &BigInt::from(d.exponent()))?;
+            write_integer(buf, d.mantissa())
+        }
+        Value::DateTime(dt) => {
+            if dt.is_rfc3339() {
+                write_type_and_length(buf, 6, 0); // major 6 = tag, 0 = date/time
+            }
+            let bytes = dt.as_str().as_bytes();
+            write_type_and_length(buf, 3, bytes.len() as u64);
+            buf.extend_from_slice(bytes);
+            Ok(())
+        }
         Value::Array(arr) => {
             write_type_and_length(buf, 4, arr.len() as u64); // major 4 = array
             for item in arr {
-                write_value(buf, item)?;
+                write_value(buf, item, order)?;
             }
             Ok(())
         }
         Value::Object(obj) => {
             let mut keys: Vec<&String> = obj.keys().collect();
-            keys.sort();
+            order.sort(&mut keys);
             write_type_and_length(buf, 5, keys.len() as u64); // major 5 = map
             for k in keys {
                 // Key: text string
@@ -142,10 +856,14 @@ fn write_value(buf: &mut Vec<u8>, value: &Value) -> Result<(), String> {
                 write_type_and_length(buf, 3, key_bytes.len() as u64);
                 buf.extend_from_slice(key_bytes);
                 // Value
-                write_value(buf, &obj[k])?;
+                write_value(buf, &obj[k], order)?;
             }
             Ok(())
         }
+        _ => Err(format!(
+            "CBOR has no {} type",
+            libyay::stats::type_name(value)
+        )),
     }
 }
 
@@ -224,7 +942,7 @@ fn write_integer(buf: &mut Vec<u8>, n: &BigInt) -> Result<(), String> {
 /// faithfully represents the actual wire encoding.
 pub fn diagnostic(input: &[u8]) -> Result<String, String> {
     let cbor_value: CborValue =
-        ciborium::de::from_reader(input).map_err(|e| format!("CBOR decode error: {}", e))?;
+        ciborium::de::from_reader(input).map_err(|e| read_error(e, input.len()))?;
     let mut out = String::new();
     diag_value(&mut out, &cbor_value, 0);
     out.push('\n');
@@ -384,3 +1102,196 @@ fn is_simple_value(val: &CborValue) -> bool {
             | CborValue::Bytes(_)
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // {"a": "h\xffi"} with a definite-length text string at offset 3.
+    const INVALID: &[u8] = &[0xa1, 0x61, 0x61, 0x63, 0x68, 0xff, 0x69];
+
+    fn decode_with(input: &[u8], policy: Utf8Policy) -> Result<Value, DecodeError> {
+        decode_with_options(
+            input,
+            &DecodeOptions {
+                invalid_utf8: policy,
+            },
+        )
+    }
+
+    #[test]
+    fn test_array_items() {
+        let values = vec![
+            Value::Integer(BigInt::from(1)),
+            Value::String("two".to_string()),
+            Value::Array(vec![Value::Bytes(vec![3]), Value::Null]),
+        ];
+        let definite = encode(&Value::Array(values.clone())).unwrap();
+        let items = ArrayItems::new(&definite[..], DecodeOptions::default()).unwrap();
+        assert_eq!(items.collect::<Result<Vec<_>, _>>().unwrap(), values);
+
+        // An indefinite-length array with an indefinite-length string, read
+        // from the middle after the first item.
+        let mut indefinite = vec![0x9f, 0x01];
+        indefinite.extend_from_slice(&[0x7f, 0x62, 0x74, 0x77, 0x61, 0x6f, 0xff]);
+        indefinite.extend_from_slice(&definite[6..]);
+        indefinite.push(0xff);
+        let mut items = ArrayItems::resume(&indefinite[2..], 2, None, DecodeOptions::default());
+        assert_eq!(items.next().unwrap().unwrap(), values[1]);
+        assert_eq!(items.offset(), 9);
+        assert_eq!(items.next().unwrap().unwrap(), values[2]);
+        assert!(items.next().is_none());
+
+        let err = ArrayItems::new(&definite[..definite.len() - 1], DecodeOptions::default())
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap_err();
+        assert!(err.contains("byte offset"), "{}", err);
+        assert!(ArrayItems::new(&[0xa0][..], DecodeOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_invalid_utf8_error_reports_offset() {
+        let err = decode(INVALID).unwrap_err();
+        assert_eq!(err.location, Some(Location::Offset(3)));
+        assert_eq!(err.path, "");
+        let err = err.to_string();
+        assert!(err.ends_with("at byte offset 3"), "{}", err);
+        assert!(err.contains("its byte 1 starts"), "{}", err);
+    }
+
+    #[test]
+    fn test_invalid_utf8_lossy_and_bytes() {
+        let lossy = decode_with(INVALID, Utf8Policy::Lossy).unwrap();
+        assert_eq!(
+            lossy.as_object().unwrap()["a"],
+            Value::String("h\u{fffd}i".into())
+        );
+        let bytes = decode_with(INVALID, Utf8Policy::Bytes).unwrap();
+        assert_eq!(
+            bytes.as_object().unwrap()["a"],
+            Value::Bytes(vec![0x68, 0xff, 0x69])
+        );
+    }
+
+    #[test]
+    fn test_invalid_utf8_key_and_chunks() {
+        // {"\xff": 1} cannot import the key as bytes.
+        let key = [0xa1, 0x61, 0xff, 0x01];
+        let err = decode_with(&key, Utf8Policy::Bytes)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("map keys cannot be bytes"), "{}", err);
+        // An indefinite-length text string split into chunks, in an array.
+        let chunked = [0x82, 0x7f, 0x61, 0x68, 0x61, 0xff, 0xff, 0xf6];
+        let value = decode_with(&chunked, Utf8Policy::Bytes).unwrap();
+        assert_eq!(
+            value,
+            Value::Array(vec![Value::Bytes(vec![0x68, 0xff]), Value::Null])
+        );
+    }
+
+    #[test]
+    fn test_error_locations() {
+        // [1, {1: 2}]: the key is an integer.
+        let err = decode(&[0x82, 0x01, 0xa1, 0x01, 0x02]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "CBOR map key must be a text string, not an integer in [1]"
+        );
+        assert!(err.location.is_none());
+        // [1, {"a": ...} with the value missing.
+        let err = decode(&[0x82, 0x01, 0xa1, 0x61, 0x61]).unwrap_err();
+        assert_eq!(err.location, Some(Location::Offset(5)));
+        assert_eq!(
+            err.in_file("a.cbor"),
+            "a.cbor: CBOR input ends inside an item at byte offset 5"
+        );
+        // {"a": [tag 5 ...]}: the path names the item with no equivalent.
+        let err = decode(&[0xa1, 0x61, 0x61, 0x81, 0xc5, 0x00]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "CBOR tagged value (tag 5) has no YAY equivalent in a[0]"
+        );
+    }
+
+    #[test]
+    fn test_valid_input_unchanged() {
+        let encoded = encode(&Value::Array(vec![Value::String("ok".into())])).unwrap();
+        assert_eq!(repair_text(&encoded, Utf8Policy::Bytes).unwrap(), None);
+    }
+
+    #[test]
+    fn test_integer_range() {
+        let edges = Value::Array(vec![
+            Value::Integer(BigInt::from(u64::MAX)),
+            Value::Integer(-BigInt::from(u64::MAX) - 1),
+        ]);
+        assert_eq!(decode(&encode(&edges).unwrap()).unwrap(), edges);
+
+        let digits = "1234567890".repeat(10);
+        let big = Value::Integer(digits.parse().unwrap());
+        let err = encode(&big).unwrap_err();
+        assert_eq!(
+            err,
+            format!(
+                "integer {} exceeds CBOR's native integer range (-2^64 to 2^64-1)",
+                digits
+            )
+        );
+    }
+
+    #[test]
+    fn test_date_tags() {
+        let dt = |text: &str| Value::DateTime(DateTime::parse(text).unwrap());
+        let values = Value::Array(vec![dt("1979-05-27T07:32:00Z"), dt("1979-05-27T07:32:00")]);
+        let encoded = encode(&values).unwrap();
+        // Tag 0 only for the date-time with an offset; the other is text.
+        assert_eq!(&encoded[1..3], &[0xc0, 0x74]);
+        assert_eq!(encoded[23], 0x73);
+        assert_eq!(
+            decode(&encoded).unwrap(),
+            Value::Array(vec![
+                dt("1979-05-27T07:32:00Z"),
+                Value::String("1979-05-27T07:32:00".into()),
+            ])
+        );
+        let events = Events::new(&encoded[..], DecodeOptions::default())
+            .collect::<io::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(events[1], Event::Scalar(dt("1979-05-27T07:32:00Z")));
+
+        // Tag 1 with integer and float seconds.
+        assert_eq!(
+            decode(&[0xc1, 0x1a, 0x51, 0x4b, 0x67, 0xb0]).unwrap(),
+            dt("2013-03-21T20:04:00Z")
+        );
+        let float = [0xc1, 0xfb, 0x41, 0xd4, 0x52, 0xd9, 0xec, 0x20, 0x00, 0x00];
+        assert_eq!(decode(&float).unwrap(), dt("2013-03-21T20:04:00.5Z"));
+
+        assert!(decode(&[0xc0, 0x61, 0x78]).is_err());
+        assert!(decode(&[0xd8, 0x20, 0x61, 0x78]).is_err());
+    }
+
+    #[test]
+    fn test_decimal_fractions() {
+        let price = Value::Decimal("12.30".parse().unwrap());
+        let encoded = encode(&price).unwrap();
+        // 4([-2, 1230])
+        assert_eq!(encoded, [0xc4, 0x82, 0x21, 0x19, 0x04, 0xce]);
+        assert_eq!(decode(&encoded).unwrap(), price);
+        let events = Events::new(&encoded[..], DecodeOptions::default())
+            .collect::<io::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(events, [Event::Scalar(price)]);
+
+        // A bignum mantissa, 4([1, 2(h'010000000000000000')]).
+        let mut big = vec![0xc4, 0x82, 0x01, 0xc2, 0x49, 0x01];
+        big.extend_from_slice(&[0; 8]);
+        let expected = Decimal::new(BigInt::from(u64::MAX) + 1u8, 1);
+        assert_eq!(decode(&big).unwrap(), Value::Decimal(expected));
+
+        assert!(decode(&[0xc4, 0x82, 0x21, 0x61, 0x78]).is_err());
+        assert!(decode(&[0xc4, 0x01]).is_err());
+    }
+}
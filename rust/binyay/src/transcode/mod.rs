@@ -1,3 +1,143 @@
+//! Transcoding between YAY values and other formats.
+//!
+//! Decoders report problems that do not stop decoding, such as keys that
+//! collide after conversion, as [`Warning`]s alongside the decoded value.
+//! Problems that do stop it are [`DecodeError`]s, which place the error in
+//! the source rather than passing on the underlying crate's message as is.
+
+use libyay::DuplicateMember;
+use std::fmt;
+
 pub mod cbor;
 pub mod toml;
 pub mod yaml;
+
+/// A problem in an input document that did not stop it from decoding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warning {
+    /// One-based source line, when known.
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl From<&DuplicateMember> for Warning {
+    fn from(d: &DuplicateMember) -> Self {
+        Warning {
+            line: Some(d.line + 1),
+            message: format!(
+                "duplicate member \"{}\"{} (first at line {}); last value wins",
+                d.key,
+                in_path(&d.path),
+                d.first_line + 1
+            ),
+        }
+    }
+}
+
+/// Where in an input document decoding failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Location {
+    /// One-based line and column, in characters, of a text format.
+    Text { line: usize, column: usize },
+    /// Byte offset into a binary format.
+    Offset(usize),
+}
+
+impl Location {
+    /// The line and column of a byte offset into `source`. The end of a
+    /// source that ends with a newline is placed at the end of its last
+    /// line, where an unfinished value was cut off.
+    fn in_text(source: &str, offset: usize) -> Self {
+        let mut offset = offset.min(source.len());
+        if offset == source.len() && source.ends_with('\n') {
+            offset -= 1;
+        }
+        let before = source.get(..offset).unwrap_or(source);
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        Location::Text {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+}
+
+/// An input document that could not be decoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodeError {
+    /// Where the error is, when the underlying crate says.
+    pub location: Option<Location>,
+    /// The path of the value that could not be converted, or empty for the
+    /// root or when the error is in the syntax.
+    pub path: String,
+    pub message: String,
+}
+
+impl DecodeError {
+    fn new(message: impl Into<String>) -> Self {
+        DecodeError {
+            location: None,
+            path: String::new(),
+            message: message.into(),
+        }
+    }
+
+    fn at(self, location: Location) -> Self {
+        DecodeError {
+            location: Some(location),
+            ..self
+        }
+    }
+
+    fn in_path(self, path: &str) -> Self {
+        DecodeError {
+            path: path.to_string(),
+            ..self
+        }
+    }
+
+    /// Describe the error in `file`, as `file:line:col: message` for text
+    /// formats, like the locations of YAY parse errors.
+    pub fn in_file(&self, file: &str) -> String {
+        match self.location {
+            Some(Location::Text { .. }) => format!("{}:{}", file, self),
+            _ => format!("{}: {}", file, self),
+        }
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(Location::Text { line, column }) = self.location {
+            write!(f, "{}:{}: ", line, column)?;
+        }
+        write!(f, "{}{}", self.message, in_path(&self.path))?;
+        if let Some(Location::Offset(offset)) = self.location {
+            write!(f, " at byte offset {}", offset)?;
+        }
+        Ok(())
+    }
+}
+
+impl From<DecodeError> for String {
+    fn from(e: DecodeError) -> Self {
+        e.to_string()
+    }
+}
+
+/// Describe a path for a message, or nothing for the root.
+fn in_path(path: &str) -> String {
+    if path.is_empty() {
+        String::new()
+    } else {
+        format!(" in {}", path)
+    }
+}
+
+/// Join a parent path and a member name.
+fn child_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
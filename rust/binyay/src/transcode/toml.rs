@@ -7,7 +7,7 @@
 //!   - TOML boolean        -> Value::Bool
 //!   - TOML array          -> Value::Array
 //!   - TOML table          -> Value::Object
-//!   - TOML datetime       -> Value::String (ISO 8601 representation)
+//!   - TOML datetime       -> Value::DateTime
 //!
 //! Mapping from YAY to TOML:
 //!   - Value::Null          -> error (TOML has no null)
@@ -18,35 +18,107 @@
 //!   - Value::Array         -> TOML array
 //!   - Value::Object        -> TOML table
 //!   - Value::Bytes         -> error (TOML has no binary type)
+//!   - Value::Decimal       -> error (TOML has no decimal type)
+//!   - Value::DateTime      -> TOML datetime
 //!
 //! Lossy edges:
 //!   - TOML has no null type; YAY null values cause an error.
 //!   - TOML integers are i64; YAY big integers that overflow will error.
 //!   - TOML has no binary type; YAY bytes cause an error.
 //!   - TOML floats don't preserve negative zero distinctly (implementation-dependent).
-//!   - TOML datetimes become YAY strings (no dedicated datetime type in YAY).
+//!   - YAY has no datetime syntax, so datetimes written as YAY become strings.
 //!   - TOML requires the top-level value to be a table; non-table YAY values error.
+//!
+//! TOML rejects duplicate keys outright, but allows a table header to
+//! appear after headers of its subtables (`[a.b]` then `[a]`). Decoding
+//! reports such re-opened tables as [`Warning`]s.
 
-use libyay::Value;
+use super::{child_path, DecodeError, Location, Warning};
+use libyay::{DateTime, KeyOrder, Map, Value};
 use num_bigint::BigInt;
 use num_traits::ToPrimitive;
-use std::collections::HashMap;
 use toml_edit::DocumentMut;
 
 /// Decode a TOML string into a YAY Value.
-pub fn decode(input: &str) -> Result<Value, String> {
-    let doc: DocumentMut = input
-        .parse::<DocumentMut>()
-        .map_err(|e| format!("TOML parse error: {}", e))?;
-    toml_table_to_value(doc.as_table())
+pub fn decode(input: &str) -> Result<Value, DecodeError> {
+    decode_with_warnings(input).map(|(value, _)| value)
+}
+
+/// Decode a TOML string, also returning warnings about tables defined
+/// after their subtables.
+pub fn decode_with_warnings(input: &str) -> Result<(Value, Vec<Warning>), DecodeError> {
+    // Unlike DocumentMut, ImDocument keeps the source spans that locate
+    // warnings.
+    let doc = toml_edit::ImDocument::parse(input).map_err(|e| parse_error(&e, input))?;
+    let mut warnings = Vec::new();
+    check_reopened_tables(doc.as_table(), "", input, &mut warnings);
+    Ok((toml_table_to_value(doc.as_table()), warnings))
+}
+
+/// Locate a toml_edit error by its span, without the source excerpt its
+/// message carries.
+fn parse_error(e: &toml_edit::TomlError, source: &str) -> DecodeError {
+    let message = e
+        .message()
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let error = DecodeError::new(message);
+    match e.span() {
+        Some(span) => error.at(Location::in_text(source, span.start)),
+        None => error,
+    }
+}
+
+/// Warn about each table whose header follows a header of one of its
+/// subtables.
+fn check_reopened_tables(
+    table: &toml_edit::Table,
+    path: &str,
+    source: &str,
+    warnings: &mut Vec<Warning>,
+) {
+    for (key, item) in table.iter() {
+        let Some(child) = item.as_table() else {
+            continue;
+        };
+        let child_path = child_path(path, key);
+        if let (false, Some(position)) = (child.is_implicit(), child.position()) {
+            let earlier = child.iter().find_map(|(sub_key, sub_item)| {
+                let sub = sub_item.as_table()?;
+                (sub.position()? < position).then(|| (sub_key, sub.span()))
+            });
+            if let Some((sub_key, sub_span)) = earlier {
+                let first_at = sub_span
+                    .map(|span| format!(" (line {})", line_at(source, span.start)))
+                    .unwrap_or_default();
+                warnings.push(Warning {
+                    line: child.span().map(|span| line_at(source, span.start)),
+                    message: format!(
+                        "table [{}] is re-opened after its subtable [{}.{}]{}",
+                        child_path, child_path, sub_key, first_at
+                    ),
+                });
+            }
+        }
+        check_reopened_tables(child, &child_path, source, warnings);
+    }
+}
+
+/// One-based line of a byte offset.
+fn line_at(source: &str, offset: usize) -> usize {
+    source[..offset.min(source.len())].matches('\n').count() + 1
 }
 
-/// Encode a YAY Value as a TOML string.
-pub fn encode(value: &Value) -> Result<String, String> {
+/// Encode a YAY Value as a TOML string, with the keys of each table in
+/// `keys` order.
+pub fn encode(value: &Value, keys: KeyOrder) -> Result<String, String> {
     check_toml_compatibility(value)?;
     match value {
         Value::Object(_) => {
-            let toml_item = value_to_toml(value)?;
+            let toml_item = value_to_toml(value, keys)?;
             match toml_item {
                 toml_edit::Item::Table(table) => {
                     let mut doc = DocumentMut::new();
@@ -66,6 +138,7 @@ fn check_toml_compatibility(value: &Value) -> Result<(), String> {
     match value {
         Value::Null => Err("TOML has no null type".to_string()),
         Value::Bytes(_) => Err("TOML has no binary data type".to_string()),
+        Value::Decimal(_) => Err("TOML has no decimal type".to_string()),
         Value::Integer(n) => {
             if n.to_i64().is_none() {
                 Err(format!("TOML integers must fit in i64; {} is too large", n))
@@ -89,51 +162,50 @@ fn check_toml_compatibility(value: &Value) -> Result<(), String> {
     }
 }
 
-fn toml_table_to_value(table: &toml_edit::Table) -> Result<Value, String> {
-    let mut obj = HashMap::new();
+// Every TOML value has a YAY equivalent, so once toml_edit has parsed a
+// document, converting it cannot fail.
+
+fn toml_table_to_value(table: &toml_edit::Table) -> Value {
+    let mut obj = Map::new();
     for (key, item) in table.iter() {
-        obj.insert(key.to_string(), toml_item_to_value(item)?);
+        obj.insert(key.to_string(), toml_item_to_value(item));
     }
-    Ok(Value::Object(obj))
+    Value::Object(obj)
 }
 
-fn toml_item_to_value(item: &toml_edit::Item) -> Result<Value, String> {
+fn toml_item_to_value(item: &toml_edit::Item) -> Value {
     match item {
         toml_edit::Item::Value(v) => toml_value_to_yay(v),
         toml_edit::Item::Table(t) => toml_table_to_value(t),
         toml_edit::Item::ArrayOfTables(arr) => {
-            let items: Result<Vec<Value>, String> = arr.iter().map(toml_table_to_value).collect();
-            Ok(Value::Array(items?))
+            Value::Array(arr.iter().map(toml_table_to_value).collect())
         }
-        toml_edit::Item::None => Ok(Value::Null),
+        toml_edit::Item::None => Value::Null,
     }
 }
 
-fn toml_value_to_yay(v: &toml_edit::Value) -> Result<Value, String> {
+fn toml_value_to_yay(v: &toml_edit::Value) -> Value {
     match v {
-        toml_edit::Value::String(s) => Ok(Value::String(s.value().clone())),
-        toml_edit::Value::Integer(i) => Ok(Value::Integer(BigInt::from(*i.value()))),
-        toml_edit::Value::Float(f) => Ok(Value::Float(*f.value())),
-        toml_edit::Value::Boolean(b) => Ok(Value::Bool(*b.value())),
+        toml_edit::Value::String(s) => Value::String(s.value().clone()),
+        toml_edit::Value::Integer(i) => Value::Integer(BigInt::from(*i.value())),
+        toml_edit::Value::Float(f) => Value::Float(*f.value()),
+        toml_edit::Value::Boolean(b) => Value::Bool(*b.value()),
         toml_edit::Value::Datetime(dt) => {
-            // Convert datetime to string representation
-            Ok(Value::String(dt.value().to_string()))
-        }
-        toml_edit::Value::Array(arr) => {
-            let items: Result<Vec<Value>, String> = arr.iter().map(toml_value_to_yay).collect();
-            Ok(Value::Array(items?))
+            let text = dt.value().to_string();
+            DateTime::parse(&text).map_or(Value::String(text), Value::DateTime)
         }
+        toml_edit::Value::Array(arr) => Value::Array(arr.iter().map(toml_value_to_yay).collect()),
         toml_edit::Value::InlineTable(table) => {
-            let mut obj = HashMap::new();
+            let mut obj = Map::new();
             for (key, val) in table.iter() {
-                obj.insert(key.to_string(), toml_value_to_yay(val)?);
+                obj.insert(key.to_string(), toml_value_to_yay(val));
             }
-            Ok(Value::Object(obj))
+            Value::Object(obj)
         }
     }
 }
 
-fn value_to_toml(value: &Value) -> Result<toml_edit::Item, String> {
+fn value_to_toml(value: &Value, order: KeyOrder) -> Result<toml_edit::Item, String> {
     match value {
         Value::Null => Err("TOML has no null type".to_string()),
         Value::Bool(b) => Ok(toml_edit::Item::Value(toml_edit::Value::Boolean(
@@ -153,11 +225,21 @@ fn value_to_toml(value: &Value) -> Result<toml_edit::Item, String> {
         Value::String(s) => Ok(toml_edit::Item::Value(toml_edit::Value::String(
             toml_edit::Formatted::new(s.clone()),
         ))),
+        Value::DateTime(dt) => {
+            let dt: toml_edit::Datetime = dt
+                .as_str()
+                .parse()
+                .map_err(|e| format!("Invalid TOML datetime {}: {}", dt, e))?;
+            Ok(toml_edit::Item::Value(toml_edit::Value::Datetime(
+                toml_edit::Formatted::new(dt),
+            )))
+        }
         Value::Bytes(_) => Err("TOML has no binary data type".to_string()),
+        Value::Decimal(_) => Err("TOML has no decimal type".to_string()),
         Value::Array(arr) => {
             let mut toml_arr = toml_edit::Array::new();
             for v in arr {
-                match value_to_toml(v)? {
+                match value_to_toml(v, order)? {
                     toml_edit::Item::Value(val) => toml_arr.push(val),
                     toml_edit::Item::Table(t) => {
                         // Convert table to inline table for array elements
@@ -177,11 +259,71 @@ fn value_to_toml(value: &Value) -> Result<toml_edit::Item, String> {
         Value::Object(obj) => {
             let mut table = toml_edit::Table::new();
             let mut keys: Vec<&String> = obj.keys().collect();
-            keys.sort();
+            order.sort(&mut keys);
             for k in keys {
-                table.insert(k, value_to_toml(&obj[k])?);
+                table.insert(k, value_to_toml(&obj[k], order)?);
             }
             Ok(toml_edit::Item::Table(table))
         }
+        _ => Err(format!(
+            "TOML has no {} type",
+            libyay::stats::type_name(value)
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reopened_table_warns() {
+        let input = "[t.u]\nx = 1\n\n[t]\ny = 2\n";
+        let (value, warnings) = decode_with_warnings(input).unwrap();
+        assert_eq!(
+            value.as_object().unwrap()["t"].as_object().unwrap().len(),
+            2
+        );
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].line, Some(4));
+        assert!(warnings[0]
+            .message
+            .contains("[t] is re-opened after its subtable [t.u]"));
+    }
+
+    #[test]
+    fn test_ordered_tables_do_not_warn() {
+        let input = "[t]\ny = 2\n\n[t.u]\nx = 1\n";
+        assert!(decode_with_warnings(input).unwrap().1.is_empty());
+    }
+
+    #[test]
+    fn test_parse_error_location() {
+        let e = decode("x = 1\n[a]\nb = [1,\n").unwrap_err();
+        assert_eq!(e.location, Some(Location::Text { line: 3, column: 8 }));
+        assert_eq!(
+            e.in_file("a.toml"),
+            "a.toml:3:8: invalid array, expected `]`"
+        );
+    }
+
+    #[test]
+    fn test_datetimes() {
+        let input = "a = 1979-05-27T07:32:00-08:00\nb = 1979-05-27 07:32:00\nc = 1979-05-27\nd = 07:32:00\n";
+        let value = decode(input).unwrap();
+        let obj = value.as_object().unwrap();
+        assert_eq!(
+            obj["a"],
+            Value::DateTime(DateTime::parse("1979-05-27T07:32:00-08:00").unwrap())
+        );
+        assert_eq!(
+            obj["b"],
+            Value::DateTime(DateTime::parse("1979-05-27T07:32:00").unwrap())
+        );
+        assert!(obj.values().all(|v| matches!(v, Value::DateTime(_))));
+        assert_eq!(
+            encode(&value, KeyOrder::Bytes).unwrap(),
+            "a = 1979-05-27T07:32:00-08:00\nb = 1979-05-27T07:32:00\nc = 1979-05-27\nd = 07:32:00\n"
+        );
     }
 }
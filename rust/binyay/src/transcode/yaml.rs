@@ -15,31 +15,185 @@
 //!   - Value::Bool         -> YAML bool
 //!   - Value::Integer      -> YAML integer (arbitrary precision as string if > i64)
 //!   - Value::Float        -> YAML float (including .nan, .inf, -.inf)
+//!   - Value::Decimal      -> YAML float (nearest)
 //!   - Value::String       -> YAML string
 //!   - Value::Array        -> YAML sequence
 //!   - Value::Object       -> YAML mapping
 //!   - Value::Bytes        -> YAML !!binary (base64-encoded)
+//!
+//! Mapping keys become strings, so distinct YAML keys such as `1` and `"1"`
+//! can collide. The last value wins and decoding reports a [`Warning`].
 
+use super::{child_path, in_path, DecodeError, Location, Warning};
 use base64::prelude::*;
-use libyay::Value;
+use libyay::{KeyOrder, Map, Value};
 use num_bigint::BigInt;
 use num_traits::ToPrimitive;
 use std::collections::HashMap;
 
 /// Decode a YAML string into a YAY Value.
-pub fn decode(input: &str) -> Result<Value, String> {
-    let yaml_value: serde_yaml::Value =
-        serde_yaml::from_str(input).map_err(|e| format!("YAML parse error: {}", e))?;
-    yaml_to_value(&yaml_value)
+pub fn decode(input: &str) -> Result<Value, DecodeError> {
+    decode_with_warnings(input).map(|(value, _)| value)
+}
+
+/// Decode a YAML string, also returning warnings about mapping keys that
+/// collide once converted to strings.
+pub fn decode_with_warnings(input: &str) -> Result<(Value, Vec<Warning>), DecodeError> {
+    let yaml_value: serde_yaml::Value = serde_yaml::from_str(input).map_err(parse_error)?;
+    let mut collisions = Vec::new();
+    let value = yaml_to_value(&yaml_value, "", &mut collisions)?;
+    let warnings = collisions.iter().map(|c| c.warning(input)).collect();
+    Ok((value, warnings))
+}
+
+/// Move the position serde_yaml writes into its message to the error's
+/// location, and shorten any others, such as where an unclosed flow
+/// collection began, to `line:col`.
+fn parse_error(e: serde_yaml::Error) -> DecodeError {
+    let mut message = e.to_string();
+    let location = e.location().map(|at| {
+        let mark = format!(" at line {} column {}", at.line(), at.column());
+        if let Some(start) = message.find(&mark) {
+            message.replace_range(start..start + mark.len(), "");
+        }
+        Location::Text {
+            line: at.line(),
+            column: at.column(),
+        }
+    });
+    let mut rest = message.as_str();
+    let mut short = String::new();
+    while let Some(start) = rest.find(" at line ") {
+        let mark = &rest[start + " at line ".len()..];
+        let digits = |s: &str| s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        let line_len = digits(mark);
+        let column = mark[line_len..].strip_prefix(" column ");
+        match column {
+            Some(column) if line_len > 0 && digits(column) > 0 => {
+                let column_len = digits(column);
+                short.push_str(&rest[..start]);
+                short.push_str(&format!(
+                    " at {}:{}",
+                    &mark[..line_len],
+                    &column[..column_len]
+                ));
+                rest = &column[column_len..];
+            }
+            _ => {
+                short.push_str(&rest[..start + 1]);
+                rest = &rest[start + 1..];
+            }
+        }
+    }
+    short.push_str(rest);
+    let error = DecodeError::new(short);
+    match location {
+        Some(location) => error.at(location),
+        None => error,
+    }
+}
+
+/// Two keys of one mapping that convert to the same string.
+struct Collision {
+    path: String,
+    key: String,
+    first: String,
+    second: String,
+}
+
+impl Collision {
+    fn warning(&self, source: &str) -> Warning {
+        // serde_yaml does not expose positions, so find the lines by looking
+        // for block mapping keys that convert to the same string.
+        let lines: Vec<usize> = source
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| line_key(line).as_deref() == Some(self.key.as_str()))
+            .map(|(i, _)| i + 1)
+            .collect();
+        let first_at = match lines.first() {
+            Some(line) if lines.len() > 1 => format!(" (first at line {})", line),
+            _ => String::new(),
+        };
+        Warning {
+            line: lines.get(1).copied(),
+            message: format!(
+                "keys {} and {}{} both become \"{}\"{}; last value wins",
+                self.first,
+                self.second,
+                in_path(&self.path),
+                self.key,
+                first_at
+            ),
+        }
+    }
+}
+
+/// The string a block mapping key on this line converts to, if the line
+/// starts with one.
+fn line_key(line: &str) -> Option<String> {
+    let mut rest = line.trim_start();
+    while let Some(after) = rest.strip_prefix("- ") {
+        rest = after.trim_start();
+    }
+    if let Some(quote) = rest.chars().next().filter(|c| *c == '"' || *c == '\'') {
+        let end = rest[1..].find(quote)? + 1;
+        return rest[end + 1..]
+            .starts_with(':')
+            .then(|| rest[1..end].to_string());
+    }
+    let end = rest
+        .find(": ")
+        .or_else(|| rest.strip_suffix(':').map(str::len))?;
+    let token = rest[..end].trim_end();
+    if token.is_empty() || token.starts_with('#') {
+        return None;
+    }
+    Some(match token {
+        "~" | "null" | "Null" | "NULL" => "null".to_string(),
+        "true" | "True" | "TRUE" => "true".to_string(),
+        "false" | "False" | "FALSE" => "false".to_string(),
+        _ => match token.parse::<i64>() {
+            Ok(n) => n.to_string(),
+            Err(_) => token.to_string(),
+        },
+    })
+}
+
+fn key_string(key: &serde_yaml::Value) -> Result<String, String> {
+    match key {
+        serde_yaml::Value::String(s) => Ok(s.clone()),
+        serde_yaml::Value::Number(n) => Ok(format!("{}", n)),
+        serde_yaml::Value::Bool(b) => Ok(format!("{}", b)),
+        serde_yaml::Value::Null => Ok("null".to_string()),
+        serde_yaml::Value::Sequence(_) => Err("a sequence".to_string()),
+        serde_yaml::Value::Mapping(_) => Err("a mapping".to_string()),
+        serde_yaml::Value::Tagged(_) => Err("a tagged value".to_string()),
+    }
 }
 
-/// Encode a YAY Value as a YAML string.
-pub fn encode(value: &Value) -> Result<String, String> {
-    let yaml_value = value_to_yaml(value)?;
+/// How a key appeared in the source, for messages.
+fn key_source(key: &serde_yaml::Value) -> String {
+    match key {
+        serde_yaml::Value::String(s) => format!("\"{}\"", s),
+        other => key_string(other).unwrap_or_default(),
+    }
+}
+
+/// Encode a YAY Value as a YAML string, with the keys of each mapping in
+/// `keys` order.
+pub fn encode(value: &Value, keys: KeyOrder) -> Result<String, String> {
+    let yaml_value = value_to_yaml(value, keys)?;
     serde_yaml::to_string(&yaml_value).map_err(|e| format!("YAML encode error: {}", e))
 }
 
-fn yaml_to_value(yaml: &serde_yaml::Value) -> Result<Value, String> {
+/// Convert a YAML value at `path`. serde_yaml keeps no positions once it
+/// has parsed a document, so errors here carry the path instead.
+fn yaml_to_value(
+    yaml: &serde_yaml::Value,
+    path: &str,
+    collisions: &mut Vec<Collision>,
+) -> Result<Value, DecodeError> {
     match yaml {
         serde_yaml::Value::Null => Ok(Value::Null),
         serde_yaml::Value::Bool(b) => Ok(Value::Bool(*b)),
@@ -51,25 +205,35 @@ fn yaml_to_value(yaml: &serde_yaml::Value) -> Result<Value, String> {
             } else if let Some(f) = n.as_f64() {
                 Ok(Value::Float(f))
             } else {
-                Err(format!("Unsupported YAML number: {:?}", n))
+                Err(DecodeError::new(format!("unsupported YAML number {}", n)).in_path(path))
             }
         }
         serde_yaml::Value::String(s) => Ok(Value::String(s.clone())),
         serde_yaml::Value::Sequence(seq) => {
-            let items: Result<Vec<Value>, String> = seq.iter().map(yaml_to_value).collect();
+            let items: Result<Vec<Value>, DecodeError> = seq
+                .iter()
+                .enumerate()
+                .map(|(i, item)| yaml_to_value(item, &format!("{}[{}]", path, i), collisions))
+                .collect();
             Ok(Value::Array(items?))
         }
         serde_yaml::Value::Mapping(map) => {
-            let mut obj = HashMap::new();
+            let mut obj = Map::new();
+            let mut sources: HashMap<String, String> = HashMap::new();
             for (k, v) in map {
-                let key = match k {
-                    serde_yaml::Value::String(s) => s.clone(),
-                    serde_yaml::Value::Number(n) => format!("{}", n),
-                    serde_yaml::Value::Bool(b) => format!("{}", b),
-                    serde_yaml::Value::Null => "null".to_string(),
-                    _ => return Err(format!("Unsupported YAML mapping key type: {:?}", k)),
-                };
-                obj.insert(key, yaml_to_value(v)?);
+                let key = key_string(k).map_err(|kind| {
+                    DecodeError::new(format!("a mapping key cannot be {}", kind)).in_path(path)
+                })?;
+                let value = yaml_to_value(v, &child_path(path, &key), collisions)?;
+                if let Some(first) = sources.insert(key.clone(), key_source(k)) {
+                    collisions.push(Collision {
+                        path: path.to_string(),
+                        key: key.clone(),
+                        first,
+                        second: key_source(k),
+                    });
+                }
+                obj.insert(key, value);
             }
             Ok(Value::Object(obj))
         }
@@ -80,19 +244,24 @@ fn yaml_to_value(yaml: &serde_yaml::Value) -> Result<Value, String> {
             if bare_tag == "binary" {
                 if let serde_yaml::Value::String(s) = &tagged.value {
                     let clean: String = s.chars().filter(|c| !c.is_whitespace()).collect();
-                    let bytes = BASE64_STANDARD
-                        .decode(&clean)
-                        .map_err(|e| format!("Invalid base64 in !!binary: {}", e))?;
+                    let bytes = BASE64_STANDARD.decode(&clean).map_err(|e| {
+                        let e = e.to_string();
+                        DecodeError::new(format!(
+                            "invalid base64 in !!binary: {}",
+                            e.trim_end_matches('.')
+                        ))
+                        .in_path(path)
+                    })?;
                     return Ok(Value::Bytes(bytes));
                 }
             }
             // For other tags, try to decode the inner value
-            yaml_to_value(&tagged.value)
+            yaml_to_value(&tagged.value, path, collisions)
         }
     }
 }
 
-fn value_to_yaml(value: &Value) -> Result<serde_yaml::Value, String> {
+fn value_to_yaml(value: &Value, order: KeyOrder) -> Result<serde_yaml::Value, String> {
     match value {
         Value::Null => Ok(serde_yaml::Value::Null),
         Value::Bool(b) => Ok(serde_yaml::Value::Bool(*b)),
@@ -110,6 +279,8 @@ fn value_to_yaml(value: &Value) -> Result<serde_yaml::Value, String> {
         }
         Value::Float(f) => Ok(serde_yaml::Value::Number(serde_yaml::Number::from(*f))),
         Value::String(s) => Ok(serde_yaml::Value::String(s.clone())),
+        Value::Decimal(d) => Ok(serde_yaml::Value::Number(d.to_f64().into())),
+        Value::DateTime(dt) => Ok(serde_yaml::Value::String(dt.to_string())),
         Value::Bytes(b) => {
             let b64 = BASE64_STANDARD.encode(b);
             Ok(serde_yaml::Value::Tagged(Box::new(
@@ -121,20 +292,71 @@ fn value_to_yaml(value: &Value) -> Result<serde_yaml::Value, String> {
         }
         Value::Array(arr) => {
             let items: Result<Vec<serde_yaml::Value>, String> =
-                arr.iter().map(value_to_yaml).collect();
+                arr.iter().map(|v| value_to_yaml(v, order)).collect();
             Ok(serde_yaml::Value::Sequence(items?))
         }
         Value::Object(obj) => {
             let mut map = serde_yaml::Mapping::new();
             let mut keys: Vec<&String> = obj.keys().collect();
-            keys.sort();
+            order.sort(&mut keys);
             for k in keys {
                 map.insert(
                     serde_yaml::Value::String(k.clone()),
-                    value_to_yaml(&obj[k])?,
+                    value_to_yaml(&obj[k], order)?,
                 );
             }
             Ok(serde_yaml::Value::Mapping(map))
         }
+        _ => Err(format!(
+            "YAML has no {} type",
+            libyay::stats::type_name(value)
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_colliding_keys_warn() {
+        let input = "a:\n  1: x\n  \"1\": y\n  true: z\n";
+        let (value, warnings) = decode_with_warnings(input).unwrap();
+        assert_eq!(
+            value.as_object().unwrap()["a"].as_object().unwrap()["1"],
+            Value::String("y".into())
+        );
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].line, Some(3));
+        assert!(warnings[0].message.contains("keys 1 and \"1\" in a"));
+    }
+
+    #[test]
+    fn test_parse_error_location() {
+        let e = decode("a:\n  b: [1\n c: 2\n").unwrap_err();
+        assert_eq!(e.location, Some(Location::Text { line: 3, column: 3 }));
+        assert_eq!(
+            e.in_file("a.yaml"),
+            "a.yaml:3:3: did not find expected ',' or ']', while parsing a flow sequence at 2:6"
+        );
+    }
+
+    #[test]
+    fn test_conversion_error_path() {
+        let e = decode("a:\n  - 1\n  - !binary a@\n").unwrap_err();
+        assert_eq!(e.path, "a[1]");
+        assert!(e.to_string().starts_with("invalid base64 in !!binary"));
+        assert!(e.to_string().ends_with(" in a[1]"));
+        let e = decode("a: {[1]: x}\n").unwrap_err();
+        assert_eq!(e.to_string(), "a mapping key cannot be a sequence in a");
+    }
+
+    #[test]
+    fn test_line_key() {
+        assert_eq!(line_key("  - - key: 1").as_deref(), Some("key"));
+        assert_eq!(line_key("'a b': 1").as_deref(), Some("a b"));
+        assert_eq!(line_key("~:").as_deref(), Some("null"));
+        assert_eq!(line_key("# note: here"), None);
+        assert_eq!(line_key("- plain"), None);
     }
 }
@@ -0,0 +1,237 @@
+/*
+ * YAY - C API for the Rust implementation (libyay-capi)
+ *
+ * Generated from src/lib.rs by tests/header.rs; do not edit.
+ *
+ * Values are opaque. Those returned by yay_parse and the constructors
+ * belong to the caller, who frees them with yay_free; those returned by
+ * the accessors borrow from the value they came from. Strings the library
+ * allocates are freed with yay_string_free.
+ */
+
+#ifndef YAY_CAPI_H
+#define YAY_CAPI_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct yay_value yay_value_t;
+
+/**
+ * The type of a value.
+ */
+typedef enum {
+    YAY_NULL,
+    YAY_BOOL,
+    YAY_INT,
+    YAY_FLOAT,
+    YAY_STRING,
+    YAY_BYTES,
+    YAY_ARRAY,
+    YAY_OBJECT,
+    YAY_DECIMAL,
+    YAY_DATETIME,
+} yay_type_t;
+
+/* Parsing and encoding */
+
+/**
+ * Parse a YAY document of `length` bytes, which need not end with a NUL.
+ * On failure, return null and, if `error` is not null, point it at the
+ * message.
+ */
+yay_value_t *yay_parse(const char *source, size_t length, const char *filename, char **error);
+
+/**
+ * Parse a NUL-terminated YAY document, as `yay_parse` does.
+ */
+yay_value_t *yay_parse_cstr(const char *source, const char *filename, char **error);
+
+/**
+ * Encode `value` in the format named `format`, with the names `yay --to`
+ * takes, to free with `yay_string_free`. Return null for a format libyay
+ * does not encode.
+ */
+char *yay_encode(const yay_value_t *value, const char *format);
+
+/**
+ * Free a value and everything in it.
+ */
+void yay_free(yay_value_t *value);
+
+/**
+ * Free a string this library returned.
+ */
+void yay_string_free(char *s);
+
+/* Accessors */
+
+/**
+ * The type of a value, or `YAY_NULL` for a null pointer.
+ */
+yay_type_t yay_value_type(const yay_value_t *value);
+
+/**
+ * Whether a value is `true`.
+ */
+bool yay_bool_value(const yay_value_t *value);
+
+/**
+ * Store an integer in `out` if it fits in 64 bits.
+ */
+bool yay_int_value(const yay_value_t *value, int64_t *out);
+
+/**
+ * The decimal digits of an integer of any size, or of a decimal, to free
+ * with `yay_string_free`.
+ */
+char *yay_int_string(const yay_value_t *value);
+
+/**
+ * The number in a float, or NaN for any other value.
+ */
+double yay_float_value(const yay_value_t *value);
+
+/**
+ * The UTF-8 of a string or date-time, not NUL-terminated.
+ */
+const char *yay_string_value(const yay_value_t *value, size_t *length);
+
+/**
+ * The bytes of a byte array.
+ */
+const uint8_t *yay_bytes_value(const yay_value_t *value, size_t *length);
+
+/**
+ * The number of items in an array, members in an object, bytes in a byte
+ * array, or bytes of UTF-8 in a string or date-time, as `yay_string_value`
+ * gives it.
+ */
+size_t yay_length(const yay_value_t *value);
+
+/**
+ * The item at `index` of an array, or null past its end.
+ */
+const yay_value_t *yay_array_get(const yay_value_t *value, size_t index);
+
+/**
+ * The member `key` of an object, or null if it has none.
+ */
+const yay_value_t *yay_object_get(const yay_value_t *value, const char *key);
+
+/**
+ * The key of the member at `index`, in the object's own order, not
+ * NUL-terminated.
+ */
+const char *yay_object_key(const yay_value_t *value, size_t index, size_t *length);
+
+/**
+ * The value of the member at `index`, in the object's own order.
+ */
+const yay_value_t *yay_object_value(const yay_value_t *value, size_t index);
+
+/* Constructors */
+
+/**
+ * A null.
+ */
+yay_value_t *yay_null(void);
+
+/**
+ * A boolean.
+ */
+yay_value_t *yay_bool(bool b);
+
+/**
+ * An integer.
+ */
+yay_value_t *yay_int(int64_t n);
+
+/**
+ * An integer from its decimal digits, or null if they are not digits.
+ */
+yay_value_t *yay_int_from_str(const char *digits, bool negative);
+
+/**
+ * A float.
+ */
+yay_value_t *yay_float(double f);
+
+/**
+ * A string from NUL-terminated UTF-8, or null if it is not UTF-8.
+ */
+yay_value_t *yay_string(const char *s);
+
+/**
+ * A string from `length` bytes of UTF-8, or null if they are not UTF-8.
+ */
+yay_value_t *yay_string_len(const char *s, size_t length);
+
+/**
+ * A byte array of `length` bytes.
+ */
+yay_value_t *yay_bytes(const uint8_t *data, size_t length);
+
+/**
+ * Bytes from lowercase hex digits, or null if they are not pairs of them.
+ */
+yay_value_t *yay_bytes_from_hex(const char *hex);
+
+/**
+ * An empty array.
+ */
+yay_value_t *yay_array(void);
+
+/**
+ * An empty object.
+ */
+yay_value_t *yay_object(void);
+
+/**
+ * Append `item` to `array`, which takes it over, and return `array`.
+ */
+yay_value_t *yay_array_push(yay_value_t *array, yay_value_t *item);
+
+/**
+ * Set member `key` of `object`, which takes `value` over, and return
+ * `object`.
+ */
+yay_value_t *yay_object_set(yay_value_t *object, const char *key, yay_value_t *value);
+
+/**
+ * An array of `count` items, which it takes over; behind `YAY_ARRAY`.
+ */
+yay_value_t *yay_array_of(yay_value_t *const *items, size_t count);
+
+/**
+ * An object of `count` alternating keys and values, taking over the
+ * values; behind `YAY_OBJECT`.
+ */
+yay_value_t *yay_object_of(void *const *kvs, size_t count);
+
+/*
+ * Build arrays and objects from inline lists, as `yay -t c` writes them:
+ *
+ *   YAY_ARRAY(yay_int(1), yay_string("two"), yay_float(3.0))
+ *   YAY_OBJECT("a", yay_int(1), "b", yay_string("two"))
+ */
+#define YAY_ARRAY(...) \
+    yay_array_of( \
+        (yay_value_t *[]){__VA_ARGS__}, \
+        sizeof((yay_value_t *[]){__VA_ARGS__}) / sizeof(yay_value_t *))
+
+#define YAY_OBJECT(...) \
+    yay_object_of( \
+        (void *[]){__VA_ARGS__}, \
+        sizeof((void *[]){__VA_ARGS__}) / sizeof(void *))
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* YAY_CAPI_H */
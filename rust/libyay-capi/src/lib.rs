@@ -0,0 +1,565 @@
+//! A C API for libyay.
+//!
+//! `include/yay_capi.h` declares every function here. Values are opaque
+//! `yay_value_t` pointers: those returned by `yay_parse` and the
+//! constructors are owned by the caller and freed with `yay_free`, and
+//! those returned by the accessors borrow from the value they came from.
+//! Strings this library allocates are freed with `yay_string_free`.
+//!
+//! The constructors and the `YAY_ARRAY` and `YAY_OBJECT` macros are the
+//! runtime that `yay -t c` output calls, so that output compiles against
+//! this library as it does against the C implementation in `c/`.
+
+// Every function takes pointers from C; the header states what each
+// expects of them.
+#![allow(clippy::missing_safety_doc)]
+
+use std::ffi::{c_char, c_void, CStr, CString};
+use std::ptr;
+use std::slice;
+
+use libyay::{encode, parse_bytes, Format, Map, ParseOptions, Value};
+use num_bigint::BigInt;
+use num_traits::ToPrimitive;
+
+/// The type of a value.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueType {
+    Null,
+    Bool,
+    Int,
+    Float,
+    String,
+    Bytes,
+    Array,
+    Object,
+    Decimal,
+    DateTime,
+}
+
+fn boxed(value: Value) -> *mut Value {
+    Box::into_raw(Box::new(value))
+}
+
+/// Copy `s` into a string for C, or null if it holds a NUL.
+fn c_string(s: String) -> *mut c_char {
+    CString::new(s).map_or(ptr::null_mut(), CString::into_raw)
+}
+
+/// The `length` bytes at `data`, which may be null if `length` is zero.
+unsafe fn bytes<'a>(data: *const c_char, length: usize) -> &'a [u8] {
+    if data.is_null() || length == 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(data.cast(), length)
+    }
+}
+
+/// The bytes at `data` up to its NUL, or none if `data` is null.
+unsafe fn c_bytes<'a>(data: *const c_char) -> &'a [u8] {
+    if data.is_null() {
+        &[]
+    } else {
+        CStr::from_ptr(data).to_bytes()
+    }
+}
+
+/// The NUL-terminated UTF-8 text at `s`, or `None` if `s` is null.
+unsafe fn str<'a>(s: *const c_char) -> Option<&'a str> {
+    if s.is_null() {
+        return None;
+    }
+    std::str::from_utf8(c_bytes(s)).ok()
+}
+
+// Parsing and encoding
+
+/// Parse a YAY document of `length` bytes, which need not end with a NUL.
+/// On failure, return null and, if `error` is not null, point it at the
+/// message.
+#[no_mangle]
+pub unsafe extern "C" fn yay_parse(
+    source: *const c_char,
+    length: usize,
+    filename: *const c_char,
+    error: *mut *mut c_char,
+) -> *mut Value {
+    let mut options = ParseOptions::default();
+    options.filename = str(filename).map(str::to_string);
+    match parse_bytes(bytes(source, length), &options) {
+        Ok(value) => boxed(value),
+        Err(e) => {
+            if !error.is_null() {
+                *error = c_string(e.to_string());
+            }
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Parse a NUL-terminated YAY document, as `yay_parse` does.
+#[no_mangle]
+pub unsafe extern "C" fn yay_parse_cstr(
+    source: *const c_char,
+    filename: *const c_char,
+    error: *mut *mut c_char,
+) -> *mut Value {
+    yay_parse(source, c_bytes(source).len(), filename, error)
+}
+
+/// Encode `value` in the format named `format`, with the names `yay --to`
+/// takes, to free with `yay_string_free`. Return null for a format libyay
+/// does not encode.
+#[no_mangle]
+pub unsafe extern "C" fn yay_encode(value: *const Value, format: *const c_char) -> *mut c_char {
+    let (Some(value), Some(format)) = (value.as_ref(), str(format)) else {
+        return ptr::null_mut();
+    };
+    let format = match format {
+        "yay" => Format::Yay,
+        "json" => Format::Json,
+        "yson" => Format::Yson,
+        "ndyay" => Format::Ndyay,
+        "js" | "javascript" => Format::JavaScript,
+        "go" => Format::Go,
+        "python" | "py" => Format::Python,
+        "rust" | "rs" => Format::Rust,
+        "c" => Format::C,
+        "java" => Format::Java,
+        "scheme" | "scm" => Format::Scheme,
+        _ => return ptr::null_mut(),
+    };
+    c_string(encode(value, format))
+}
+
+/// Free a value and everything in it.
+#[no_mangle]
+pub unsafe extern "C" fn yay_free(value: *mut Value) {
+    if !value.is_null() {
+        drop(Box::from_raw(value));
+    }
+}
+
+/// Free a string this library returned.
+#[no_mangle]
+pub unsafe extern "C" fn yay_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+// Accessors
+
+/// The type of a value, or `YAY_NULL` for a null pointer.
+#[no_mangle]
+pub unsafe extern "C" fn yay_value_type(value: *const Value) -> ValueType {
+    match value.as_ref() {
+        Some(Value::Bool(_)) => ValueType::Bool,
+        Some(Value::Integer(_)) => ValueType::Int,
+        Some(Value::Float(_)) => ValueType::Float,
+        Some(Value::Decimal(_)) => ValueType::Decimal,
+        Some(Value::String(_)) => ValueType::String,
+        Some(Value::Bytes(_)) => ValueType::Bytes,
+        Some(Value::DateTime(_)) => ValueType::DateTime,
+        Some(Value::Array(_)) => ValueType::Array,
+        Some(Value::Object(_)) => ValueType::Object,
+        _ => ValueType::Null,
+    }
+}
+
+/// Whether a value is `true`.
+#[no_mangle]
+pub unsafe extern "C" fn yay_bool_value(value: *const Value) -> bool {
+    matches!(value.as_ref(), Some(Value::Bool(true)))
+}
+
+/// Store an integer in `out` if it fits in 64 bits.
+#[no_mangle]
+pub unsafe extern "C" fn yay_int_value(value: *const Value, out: *mut i64) -> bool {
+    match value.as_ref() {
+        Some(Value::Integer(n)) => match n.to_i64() {
+            Some(n) if !out.is_null() => {
+                *out = n;
+                true
+            }
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// The decimal digits of an integer of any size, or of a decimal, to free
+/// with `yay_string_free`.
+#[no_mangle]
+pub unsafe extern "C" fn yay_int_string(value: *const Value) -> *mut c_char {
+    match value.as_ref() {
+        Some(Value::Integer(n)) => c_string(n.to_string()),
+        Some(Value::Decimal(d)) => c_string(d.to_string()),
+        _ => ptr::null_mut(),
+    }
+}
+
+/// The number in a float, or NaN for any other value.
+#[no_mangle]
+pub unsafe extern "C" fn yay_float_value(value: *const Value) -> f64 {
+    match value.as_ref() {
+        Some(Value::Float(f)) => *f,
+        _ => f64::NAN,
+    }
+}
+
+/// The UTF-8 of a string or date-time, not NUL-terminated.
+#[no_mangle]
+pub unsafe extern "C" fn yay_string_value(
+    value: *const Value,
+    length: *mut usize,
+) -> *const c_char {
+    let s = match value.as_ref() {
+        Some(Value::String(s)) => s.as_str(),
+        Some(Value::DateTime(t)) => t.as_str(),
+        _ => return ptr::null(),
+    };
+    if !length.is_null() {
+        *length = s.len();
+    }
+    s.as_ptr().cast()
+}
+
+/// The bytes of a byte array.
+#[no_mangle]
+pub unsafe extern "C" fn yay_bytes_value(value: *const Value, length: *mut usize) -> *const u8 {
+    let Some(Value::Bytes(bytes)) = value.as_ref() else {
+        return ptr::null();
+    };
+    if !length.is_null() {
+        *length = bytes.len();
+    }
+    bytes.as_ptr()
+}
+
+/// The number of items in an array, members in an object, bytes in a byte
+/// array, or bytes of UTF-8 in a string or date-time, as `yay_string_value`
+/// gives it.
+#[no_mangle]
+pub unsafe extern "C" fn yay_length(value: *const Value) -> usize {
+    match value.as_ref() {
+        Some(Value::Array(items)) => items.len(),
+        Some(Value::Object(map)) => map.len(),
+        Some(Value::Bytes(bytes)) => bytes.len(),
+        Some(Value::String(s)) => s.len(),
+        Some(Value::DateTime(t)) => t.as_str().len(),
+        _ => 0,
+    }
+}
+
+/// The item at `index` of an array, or null past its end.
+#[no_mangle]
+pub unsafe extern "C" fn yay_array_get(value: *const Value, index: usize) -> *const Value {
+    match value.as_ref() {
+        Some(Value::Array(items)) => items.get(index).map_or(ptr::null(), |v| v as *const Value),
+        _ => ptr::null(),
+    }
+}
+
+/// The member `key` of an object, or null if it has none.
+#[no_mangle]
+pub unsafe extern "C" fn yay_object_get(value: *const Value, key: *const c_char) -> *const Value {
+    match (value.as_ref(), str(key)) {
+        (Some(Value::Object(map)), Some(key)) => {
+            map.get(key).map_or(ptr::null(), |v| v as *const Value)
+        }
+        _ => ptr::null(),
+    }
+}
+
+/// The key of the member at `index`, in the object's own order, not
+/// NUL-terminated.
+#[no_mangle]
+pub unsafe extern "C" fn yay_object_key(
+    value: *const Value,
+    index: usize,
+    length: *mut usize,
+) -> *const c_char {
+    let Some((key, _)) = member(value, index) else {
+        return ptr::null();
+    };
+    if !length.is_null() {
+        *length = key.len();
+    }
+    key.as_ptr().cast()
+}
+
+/// The value of the member at `index`, in the object's own order.
+#[no_mangle]
+pub unsafe extern "C" fn yay_object_value(value: *const Value, index: usize) -> *const Value {
+    member(value, index).map_or(ptr::null(), |(_, v)| v as *const Value)
+}
+
+unsafe fn member<'a>(value: *const Value, index: usize) -> Option<(&'a String, &'a Value)> {
+    match value.as_ref() {
+        Some(Value::Object(map)) => map.get_index(index),
+        _ => None,
+    }
+}
+
+// Constructors
+
+/// A null.
+#[no_mangle]
+pub extern "C" fn yay_null() -> *mut Value {
+    boxed(Value::Null)
+}
+
+/// A boolean.
+#[no_mangle]
+pub extern "C" fn yay_bool(b: bool) -> *mut Value {
+    boxed(Value::Bool(b))
+}
+
+/// An integer.
+#[no_mangle]
+pub extern "C" fn yay_int(n: i64) -> *mut Value {
+    boxed(Value::Integer(n.into()))
+}
+
+/// An integer from its decimal digits, or null if they are not digits.
+#[no_mangle]
+pub unsafe extern "C" fn yay_int_from_str(digits: *const c_char, negative: bool) -> *mut Value {
+    match str(digits).filter(|d| !d.is_empty() && d.bytes().all(|b| b.is_ascii_digit())) {
+        Some(digits) => {
+            let n: BigInt = digits.parse().unwrap_or_default();
+            boxed(Value::Integer(if negative { -n } else { n }))
+        }
+        None => ptr::null_mut(),
+    }
+}
+
+/// A float.
+#[no_mangle]
+pub extern "C" fn yay_float(f: f64) -> *mut Value {
+    boxed(Value::Float(f))
+}
+
+/// A string from NUL-terminated UTF-8, or null if it is not UTF-8.
+#[no_mangle]
+pub unsafe extern "C" fn yay_string(s: *const c_char) -> *mut Value {
+    yay_string_len(s, c_bytes(s).len())
+}
+
+/// A string from `length` bytes of UTF-8, or null if they are not UTF-8.
+#[no_mangle]
+pub unsafe extern "C" fn yay_string_len(s: *const c_char, length: usize) -> *mut Value {
+    match std::str::from_utf8(bytes(s, length)) {
+        Ok(s) => boxed(Value::String(s.to_string())),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// A byte array of `length` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn yay_bytes(data: *const u8, length: usize) -> *mut Value {
+    if data.is_null() || length == 0 {
+        return boxed(Value::Bytes(Vec::new()));
+    }
+    boxed(Value::Bytes(slice::from_raw_parts(data, length).to_vec()))
+}
+
+/// Bytes from lowercase hex digits, or null if they are not pairs of them.
+#[no_mangle]
+pub unsafe extern "C" fn yay_bytes_from_hex(hex: *const c_char) -> *mut Value {
+    let digits = c_bytes(hex);
+    let valid = |b: &u8| b.is_ascii_digit() || (b'a'..=b'f').contains(b);
+    if digits.len() % 2 != 0 || !digits.iter().all(valid) {
+        return ptr::null_mut();
+    }
+    let value = |b: u8| (b as char).to_digit(16).unwrap_or(0) as u8;
+    let bytes = digits
+        .chunks(2)
+        .map(|pair| value(pair[0]) << 4 | value(pair[1]))
+        .collect();
+    boxed(Value::Bytes(bytes))
+}
+
+/// An empty array.
+#[no_mangle]
+pub extern "C" fn yay_array() -> *mut Value {
+    boxed(Value::Array(Vec::new()))
+}
+
+/// An empty object.
+#[no_mangle]
+pub extern "C" fn yay_object() -> *mut Value {
+    boxed(Value::Object(Map::default()))
+}
+
+/// Append `item` to `array`, which takes it over, and return `array`.
+#[no_mangle]
+pub unsafe extern "C" fn yay_array_push(array: *mut Value, item: *mut Value) -> *mut Value {
+    if item.is_null() {
+        return array;
+    }
+    let item = *Box::from_raw(item);
+    if let Some(Value::Array(items)) = array.as_mut() {
+        items.push(item);
+    }
+    array
+}
+
+/// Set member `key` of `object`, which takes `value` over, and return
+/// `object`.
+#[no_mangle]
+pub unsafe extern "C" fn yay_object_set(
+    object: *mut Value,
+    key: *const c_char,
+    value: *mut Value,
+) -> *mut Value {
+    if value.is_null() {
+        return object;
+    }
+    let value = *Box::from_raw(value);
+    if let (Some(Value::Object(map)), Some(key)) = (object.as_mut(), str(key)) {
+        map.insert(key.to_string(), value);
+    }
+    object
+}
+
+/// An array of `count` items, which it takes over; behind `YAY_ARRAY`.
+#[no_mangle]
+pub unsafe extern "C" fn yay_array_of(items: *const *mut Value, count: usize) -> *mut Value {
+    let array = yay_array();
+    if !items.is_null() {
+        for &item in slice::from_raw_parts(items, count) {
+            yay_array_push(array, item);
+        }
+    }
+    array
+}
+
+/// An object of `count` alternating keys and values, taking over the
+/// values; behind `YAY_OBJECT`.
+#[no_mangle]
+pub unsafe extern "C" fn yay_object_of(kvs: *const *mut c_void, count: usize) -> *mut Value {
+    let object = yay_object();
+    if !kvs.is_null() {
+        for pair in slice::from_raw_parts(kvs, count).chunks_exact(2) {
+            yay_object_set(object, pair[0].cast(), pair[1].cast());
+        }
+    }
+    object
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(s: *mut c_char) -> String {
+        let text = unsafe { CStr::from_ptr(s) }.to_str().unwrap().to_string();
+        unsafe { yay_string_free(s) };
+        text
+    }
+
+    #[test]
+    fn test_parse_and_read() {
+        let source = c"name: \"web\"\nports: [80, 443]\nkey: <cafe>\nbig: 123456789012345678901\n";
+        let value = unsafe { yay_parse_cstr(source.as_ptr(), ptr::null(), ptr::null_mut()) };
+        assert!(!value.is_null());
+        unsafe {
+            assert_eq!(yay_value_type(value), ValueType::Object);
+            assert_eq!(yay_length(value), 4);
+
+            let name = yay_object_get(value, c"name".as_ptr());
+            let mut length = 0;
+            let s = yay_string_value(name, &mut length);
+            assert_eq!(slice::from_raw_parts(s.cast::<u8>(), length), b"web");
+
+            let ports = yay_object_get(value, c"ports".as_ptr());
+            let mut port = 0;
+            assert!(yay_int_value(yay_array_get(ports, 1), &mut port));
+            assert_eq!(port, 443);
+            assert!(yay_array_get(ports, 2).is_null());
+
+            let key = yay_object_get(value, c"key".as_ptr());
+            let b = yay_bytes_value(key, &mut length);
+            assert_eq!(slice::from_raw_parts(b, length), [0xca, 0xfe]);
+
+            let big = yay_object_get(value, c"big".as_ptr());
+            assert!(!yay_int_value(big, &mut port));
+            assert_eq!(text(yay_int_string(big)), "123456789012345678901");
+
+            let mut key_length = 0;
+            let first = yay_object_key(value, 0, &mut key_length);
+            assert_eq!(key_length, 4);
+            assert!(!first.is_null());
+            assert!(yay_object_value(value, 4).is_null());
+
+            assert_eq!(text(yay_encode(ports, c"yay".as_ptr())), "[80, 443]");
+            assert!(yay_encode(ports, c"toml".as_ptr()).is_null());
+            yay_free(value);
+        }
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let mut error = ptr::null_mut();
+        let value = unsafe { yay_parse_cstr(c"a:".as_ptr(), c"x.yay".as_ptr(), &mut error) };
+        assert!(value.is_null());
+        assert!(text(error).contains("<x.yay>"));
+
+        // A length reads only that much of the source.
+        let value = unsafe { yay_parse(c"1 2".as_ptr(), 1, ptr::null(), ptr::null_mut()) };
+        assert_eq!(unsafe { &*value }, &Value::Integer(1.into()));
+        unsafe { yay_free(value) };
+
+        // An empty buffer is an empty document, which has no value, read
+        // without looking past it for a NUL.
+        let empty = [b'x'];
+        let mut error = ptr::null_mut();
+        let value = unsafe { yay_parse(empty.as_ptr().cast(), 0, ptr::null(), &mut error) };
+        assert!(value.is_null());
+        assert!(text(error).starts_with("No value found"));
+    }
+
+    #[test]
+    fn test_date_time_length() {
+        // YAY has no date-times, but values built from TOML or CBOR do.
+        let date_time = libyay::DateTime::parse("2026-10-16T12:00:00Z").unwrap();
+        let value = boxed(Value::DateTime(date_time));
+        unsafe {
+            assert_eq!(yay_value_type(value), ValueType::DateTime);
+            let mut length = 0;
+            yay_string_value(value, &mut length);
+            assert_eq!(yay_length(value), length);
+            assert_eq!(length, 20);
+            yay_free(value);
+        }
+    }
+
+    #[test]
+    fn test_construct() {
+        unsafe {
+            // What `yay -t c` writes for `a: [1, "x", <ff>]`, with the
+            // macros spelled out.
+            let items = [
+                yay_int(1),
+                yay_string(c"x".as_ptr()),
+                yay_bytes_from_hex(c"ff".as_ptr()),
+            ];
+            let array = yay_array_of(items.as_ptr(), items.len());
+            let kvs = [c"a".as_ptr() as *mut c_void, array.cast()];
+            let object = yay_object_of(kvs.as_ptr(), kvs.len());
+            assert_eq!(*object, libyay::parse("a: [1, \"x\", <ff>]").unwrap());
+            yay_free(object);
+
+            let n = yay_int_from_str(c"18446744073709551616".as_ptr(), true);
+            assert_eq!(
+                text(yay_encode(n, c"yay".as_ptr())),
+                "-18446744073709551616"
+            );
+            yay_free(n);
+            assert!(yay_int_from_str(c"12a".as_ptr(), false).is_null());
+            assert!(yay_bytes_from_hex(c"FF".as_ptr()).is_null());
+            assert!(yay_bytes_from_hex(c"f".as_ptr()).is_null());
+        }
+    }
+}
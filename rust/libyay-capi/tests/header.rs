@@ -0,0 +1,196 @@
+//! `include/yay_capi.h` is generated from `src/lib.rs`: the `yay_type_t`
+//! enum from `ValueType`, and a declaration for each `extern "C"` function
+//! from its signature and doc comment. This test fails when the header is
+//! out of date. After changing the API, rewrite the header with:
+//!
+//! ```text
+//! UPDATE_HEADER=1 cargo test -p libyay-capi --test header
+//! ```
+
+use std::fs;
+use std::path::Path;
+
+const PREAMBLE: &str = "\
+/*
+ * YAY - C API for the Rust implementation (libyay-capi)
+ *
+ * Generated from src/lib.rs by tests/header.rs; do not edit.
+ *
+ * Values are opaque. Those returned by yay_parse and the constructors
+ * belong to the caller, who frees them with yay_free; those returned by
+ * the accessors borrow from the value they came from. Strings the library
+ * allocates are freed with yay_string_free.
+ */
+
+#ifndef YAY_CAPI_H
+#define YAY_CAPI_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern \"C\" {
+#endif
+
+typedef struct yay_value yay_value_t;
+";
+
+const POSTAMBLE: &str = "
+/*
+ * Build arrays and objects from inline lists, as `yay -t c` writes them:
+ *
+ *   YAY_ARRAY(yay_int(1), yay_string(\"two\"), yay_float(3.0))
+ *   YAY_OBJECT(\"a\", yay_int(1), \"b\", yay_string(\"two\"))
+ */
+#define YAY_ARRAY(...) \\
+    yay_array_of( \\
+        (yay_value_t *[]){__VA_ARGS__}, \\
+        sizeof((yay_value_t *[]){__VA_ARGS__}) / sizeof(yay_value_t *))
+
+#define YAY_OBJECT(...) \\
+    yay_object_of( \\
+        (void *[]){__VA_ARGS__}, \\
+        sizeof((void *[]){__VA_ARGS__}) / sizeof(void *))
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* YAY_CAPI_H */
+";
+
+/// The C spelling of a Rust type in the API.
+fn c_type(rust: &str) -> String {
+    let pointee = |rest: &str, qualifier: &str| {
+        let inner = c_type(rest.trim());
+        format!("{}{} *", qualifier, inner.trim_end())
+            .replace("* *", "**")
+            .replace("const const", "const")
+    };
+    match rust.trim() {
+        "Value" => "yay_value_t".to_string(),
+        "ValueType" => "yay_type_t".to_string(),
+        "c_char" => "char".to_string(),
+        "c_void" => "void".to_string(),
+        "u8" => "uint8_t".to_string(),
+        "i64" => "int64_t".to_string(),
+        "usize" => "size_t".to_string(),
+        "f64" => "double".to_string(),
+        "bool" => "bool".to_string(),
+        // A pointer to a pointer the callee only reads.
+        t if t.starts_with("*const *mut ") => {
+            format!("{} *const *", c_type(&t["*const *mut ".len()..]))
+        }
+        t if t.starts_with("*const ") => pointee(&t["*const ".len()..], "const "),
+        t if t.starts_with("*mut ") => pointee(&t["*mut ".len()..], ""),
+        t => panic!("no C type for {}", t),
+    }
+}
+
+/// A declaration with `type` before `name`, spaced as C is written here.
+fn declare(c_type: &str, name: &str) -> String {
+    if c_type.ends_with('*') {
+        format!("{}{}", c_type, name)
+    } else {
+        format!("{} {}", c_type, name)
+    }
+}
+
+fn comment(doc: &[String]) -> String {
+    if doc.is_empty() {
+        return String::new();
+    }
+    let mut out = String::from("/**\n");
+    for line in doc {
+        if line.is_empty() {
+            out.push_str(" *\n");
+        } else {
+            out.push_str(&format!(" * {}\n", line));
+        }
+    }
+    out.push_str(" */\n");
+    out
+}
+
+fn generate(source: &str) -> String {
+    let source = source.split("#[cfg(test)]").next().unwrap();
+    let mut out = String::from(PREAMBLE);
+    let mut doc: Vec<String> = Vec::new();
+    let mut lines = source.lines().peekable();
+    while let Some(line) = lines.next() {
+        let line = line.trim_end();
+        if let Some(text) = line.strip_prefix("///") {
+            doc.push(text.trim().to_string());
+        } else if line.starts_with("pub enum ValueType") {
+            out.push('\n');
+            out.push_str(&comment(&doc));
+            out.push_str("typedef enum {\n");
+            for variant in lines.by_ref().map(str::trim).take_while(|l| *l != "}") {
+                out.push_str(&format!(
+                    "    YAY_{},\n",
+                    variant.trim_end_matches(',').to_uppercase()
+                ));
+            }
+            out.push_str("} yay_type_t;\n");
+            doc.clear();
+        } else if line.starts_with("pub unsafe extern \"C\" fn")
+            || line.starts_with("pub extern \"C\" fn")
+        {
+            let mut signature = line.to_string();
+            while !signature.ends_with('{') {
+                signature.push_str(lines.next().unwrap().trim());
+            }
+            let (head, rest) = signature.split_once('(').unwrap();
+            let name = head.rsplit(' ').next().unwrap();
+            let (params, ret) = rest.rsplit_once(')').unwrap();
+            let ret = ret
+                .trim_end_matches('{')
+                .trim()
+                .strip_prefix("->")
+                .map_or("void".to_string(), c_type);
+            let params: Vec<String> = params
+                .split(',')
+                .map(str::trim)
+                .filter(|p| !p.is_empty())
+                .map(|p| {
+                    let (name, rust) = p.split_once(':').unwrap();
+                    declare(&c_type(rust), name.trim())
+                })
+                .collect();
+            let params = if params.is_empty() {
+                "void".to_string()
+            } else {
+                params.join(", ")
+            };
+            out.push('\n');
+            out.push_str(&comment(&doc));
+            out.push_str(&format!("{}({});\n", declare(&ret, name), params));
+            doc.clear();
+        } else if let Some(section) = line.strip_prefix("// ") {
+            if lines.peek().is_some_and(|next| next.is_empty()) {
+                out.push_str(&format!("\n/* {} */\n", section));
+            }
+            doc.clear();
+        } else if !line.starts_with("#[") {
+            doc.clear();
+        }
+    }
+    out.push_str(POSTAMBLE);
+    out
+}
+
+#[test]
+fn test_header() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let header = generate(&fs::read_to_string(root.join("src/lib.rs")).unwrap());
+    let path = root.join("include/yay_capi.h");
+    if std::env::var_os("UPDATE_HEADER").is_some() {
+        fs::write(&path, header).unwrap();
+        return;
+    }
+    assert!(
+        fs::read_to_string(&path).unwrap_or_default() == header,
+        "include/yay_capi.h is out of date (UPDATE_HEADER=1 to rewrite it)"
+    );
+}
@@ -0,0 +1,44 @@
+/*
+ * Parse, read, encode, and build values through the C API, as a C program
+ * linked against libyay-capi sees it. Run by CI; see README.md.
+ */
+
+#include <assert.h>
+#include <stdio.h>
+#include <string.h>
+
+#include "yay_capi.h"
+
+int main(void) {
+    char *error = NULL;
+    yay_value_t *value = yay_parse_cstr("name: \"web\"\nports: [80, 443]\n", NULL, &error);
+    assert(value != NULL && error == NULL);
+    assert(yay_value_type(value) == YAY_OBJECT);
+
+    size_t length = 0;
+    const char *name = yay_string_value(yay_object_get(value, "name"), &length);
+    assert(length == 3 && memcmp(name, "web", 3) == 0);
+
+    int64_t port = 0;
+    assert(yay_int_value(yay_array_get(yay_object_get(value, "ports"), 1), &port));
+    assert(port == 443);
+
+    /* What `yay -t c` writes for the same document. */
+    yay_value_t *built = YAY_OBJECT(
+        "name", yay_string("web"),
+        "ports", YAY_ARRAY(yay_int(80), yay_int(443))
+    );
+    char *a = yay_encode(value, "yay");
+    char *b = yay_encode(built, "yay");
+    assert(strcmp(a, b) == 0);
+    printf("%s\n", a);
+    yay_string_free(a);
+    yay_string_free(b);
+    yay_free(built);
+    yay_free(value);
+
+    assert(yay_parse("a:", 2, "bad.yay", &error) == NULL);
+    assert(strstr(error, "bad.yay") != NULL);
+    yay_string_free(error);
+    return 0;
+}
@@ -0,0 +1,172 @@
+//! WebAssembly bindings for libyay.
+//!
+//! The functions here are the Rust parser, formatter, and encoders, built
+//! for `wasm32-unknown-unknown` with wasm-bindgen, so a browser playground
+//! or a Node tool gets exactly what the `yay` CLI does rather than a
+//! second implementation that may drift from it.
+//!
+//! ```js
+//! import { parse, format, transcode } from "libyay-wasm";
+//!
+//! parse("port: 8080");                       // { port: 8080n }
+//! format("a:   1");                          // "a: 1\n"
+//! transcode("port: 8080.0", "yay", "json");  // "{\n  \"port\": 8080\n}"
+//! transcode("port: 8080", "yay", "json");    // throws: JSON has no BigInts
+//! ```
+//!
+//! Values come back as the JavaScript `libyay` package builds them:
+//! integers are `bigint`, floats `number`, byte arrays `Uint8Array`, and
+//! objects plain objects.
+
+use js_sys::{Array, BigInt, Object, Reflect, Uint8Array};
+use libyay::{encode, format_yay, parse_yson, Format, ParseOptions, Value};
+use wasm_bindgen::prelude::*;
+
+/// Parse a YAY document into a JavaScript value.
+#[wasm_bindgen]
+pub fn parse(input: &str) -> Result<JsValue, JsError> {
+    let value = libyay::parse(input).map_err(|e| JsError::new(&e.to_string()))?;
+    to_js(&value)
+}
+
+/// Reformat a YAY document canonically, as `yay fmt` does.
+#[wasm_bindgen]
+pub fn format(input: &str) -> Result<String, JsError> {
+    format_yay(input).map_err(|e| JsError::new(&e))
+}
+
+/// Read `input` in the format named `from` and write it in the format
+/// named `to`, with the names the CLI takes for `--from` and `--to`.
+///
+/// Input may be `yay`, `meh`, `json`, or `yson`; output may be any format
+/// libyay encodes. YAML, TOML, and CBOR need the libraries the CLI links
+/// and are not available here.
+#[wasm_bindgen]
+pub fn transcode(input: &str, from: &str, to: &str) -> Result<String, JsError> {
+    transcode_text(input, from, to).map_err(|e| JsError::new(&e))
+}
+
+fn transcode_text(input: &str, from: &str, to: &str) -> Result<String, String> {
+    let format = output_format(to)?;
+    let value = match from {
+        "yay" => libyay::parse(input).map_err(|e| e.to_string())?,
+        "meh" => format_yay(input).and_then(|canonical| {
+            let mut options = ParseOptions::default();
+            options.version_directive = true;
+            libyay::parse_with_options(&canonical, &options).map_err(|e| e.to_string())
+        })?,
+        "json" | "yson" => parse_yson(input)?,
+        _ => return Err(format!("Unknown input format: {}", from)),
+    };
+    // As in the CLI, JSON refuses what it cannot hold rather than writing
+    // integers as floats and dropping byte arrays.
+    if format == Format::Json {
+        if let Some(first) = value.compatibility(format).first() {
+            return Err(format!("Cannot convert to JSON: {}", first));
+        }
+    }
+    Ok(encode(&value, format))
+}
+
+/// The format named `name`, if libyay can encode it.
+fn output_format(name: &str) -> Result<Format, String> {
+    Ok(match name {
+        "yay" | "meh" => Format::Yay,
+        "json" => Format::Json,
+        "yson" => Format::Yson,
+        "ndyay" => Format::Ndyay,
+        "js" | "javascript" => Format::JavaScript,
+        "go" => Format::Go,
+        "python" | "py" => Format::Python,
+        "rust" | "rs" => Format::Rust,
+        "c" => Format::C,
+        "java" => Format::Java,
+        "scheme" | "scm" => Format::Scheme,
+        "yaml" | "yml" | "toml" | "cbor" | "diag" => {
+            return Err(format!("Unsupported output format: {}", name))
+        }
+        _ => return Err(format!("Unknown output format: {}", name)),
+    })
+}
+
+/// Convert a value to the JavaScript value the `libyay` package would
+/// parse from the same document.
+fn to_js(value: &Value) -> Result<JsValue, JsError> {
+    Ok(match value {
+        Value::Null => JsValue::NULL,
+        Value::Bool(b) => JsValue::from_bool(*b),
+        Value::Integer(n) => BigInt::new(&JsValue::from_str(&n.to_string()))
+            .map_err(|_| JsError::new("Integer out of range"))?
+            .into(),
+        Value::Float(f) => JsValue::from_f64(*f),
+        Value::Decimal(d) => JsValue::from_str(&d.to_string()),
+        Value::String(s) => JsValue::from_str(s),
+        Value::DateTime(t) => JsValue::from_str(t.as_str()),
+        Value::Bytes(bytes) => Uint8Array::from(bytes.as_slice()).into(),
+        Value::Array(items) => {
+            let array = Array::new_with_length(items.len() as u32);
+            for (i, item) in items.iter().enumerate() {
+                array.set(i as u32, to_js(item)?);
+            }
+            array.into()
+        }
+        Value::Object(map) => {
+            // Define each member rather than assign it, so that a key like
+            // "__proto__" becomes an own property instead of a prototype.
+            let object = Object::new();
+            for (key, item) in map {
+                let descriptor = Object::new();
+                for (name, setting) in [
+                    ("value", to_js(item)?),
+                    ("writable", JsValue::TRUE),
+                    ("enumerable", JsValue::TRUE),
+                    ("configurable", JsValue::TRUE),
+                ] {
+                    Reflect::set(&descriptor, &JsValue::from_str(name), &setting)
+                        .map_err(|_| JsError::new("Cannot set property"))?;
+                }
+                Object::define_property(&object, &JsValue::from_str(key), &descriptor);
+            }
+            object.into()
+        }
+        _ => return Err(JsError::new("Unsupported value type")),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transcode_text() {
+        assert_eq!(
+            transcode_text("a: [1, <ff>]", "yay", "yson").unwrap(),
+            encode(&libyay::parse("a: [1, <ff>]").unwrap(), Format::Yson)
+        );
+        assert_eq!(
+            transcode_text("{\"a\": 1}", "json", "yay").unwrap(),
+            "{a: 1.0}"
+        );
+        assert_eq!(
+            transcode_text("a:   1", "meh", "yay").unwrap(),
+            transcode_text("a: 1", "yay", "yay").unwrap()
+        );
+        assert_eq!(
+            transcode_text("1", "yay", "toml").unwrap_err(),
+            "Unsupported output format: toml"
+        );
+        assert_eq!(
+            transcode_text("1", "xml", "yay").unwrap_err(),
+            "Unknown input format: xml"
+        );
+        assert!(transcode_text("a:", "yay", "json").is_err());
+        assert_eq!(
+            transcode_text("port: 8080", "yay", "json").unwrap_err(),
+            "Cannot convert to JSON: port: integer (YAY integers are BigInts)"
+        );
+        assert_eq!(
+            transcode_text("port: 8080.0", "yay", "json").unwrap(),
+            "{\n  \"port\": 8080\n}"
+        );
+    }
+}
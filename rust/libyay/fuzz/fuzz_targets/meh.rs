@@ -0,0 +1,18 @@
+//! Format arbitrary text as MEH, and check that what formats is canonical:
+//! formatting it again changes nothing.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use libyay::format_yay;
+
+fuzz_target!(|text: &str| {
+    if let Ok(formatted) = format_yay(text) {
+        assert_eq!(
+            format_yay(&formatted).as_ref(),
+            Ok(&formatted),
+            "{:?}",
+            text
+        );
+    }
+});
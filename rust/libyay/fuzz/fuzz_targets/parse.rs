@@ -0,0 +1,36 @@
+//! Parse arbitrary bytes with `parse_bytes` and with `PushParser`, which
+//! must agree, and check that what parses encodes to a document that
+//! parses back to the same value.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use libyay::{encode, parse, parse_bytes, Format, ParseOptions, PushParser};
+
+fuzz_target!(|data: &[u8]| {
+    // The first byte chooses the size of the pieces fed to `PushParser`.
+    let Some((&chunk, data)) = data.split_first() else {
+        return;
+    };
+    let options = ParseOptions::default();
+    let whole = parse_bytes(data, &options);
+
+    let mut parser = PushParser::new(&options);
+    let pushed = data
+        .chunks(usize::from(chunk).max(1))
+        .try_for_each(|bytes| parser.feed(bytes))
+        .and_then(|()| parser.finish());
+    match (&whole, &pushed) {
+        (Ok(a), Ok(b)) => assert_eq!(a, b),
+        (Err(a), Err(b)) => assert_eq!(a.to_string(), b.to_string()),
+        _ => panic!("parse_bytes gave {:?}, PushParser gave {:?}", whole, pushed),
+    }
+
+    if let Ok(value) = whole {
+        let encoded = encode(&value, Format::Yay);
+        match parse(&encoded) {
+            Ok(reparsed) => assert_eq!(reparsed, value, "{}", encoded),
+            Err(e) => panic!("{:?} encoded to {:?}: {}", value, encoded, e),
+        }
+    }
+});
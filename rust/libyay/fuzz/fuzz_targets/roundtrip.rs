@@ -0,0 +1,17 @@
+//! Encode arbitrary values as YAY and YSON, and check that each parses back
+//! to the same value, as `tests/roundtrip.rs` does for generated ones.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use libyay::{encode, parse, parse_yson, Format, Value};
+
+fuzz_target!(|value: Value| {
+    let yay = encode(&value, Format::Yay);
+    match parse(&yay) {
+        Ok(parsed) => assert_eq!(parsed, value, "{}", yay),
+        Err(e) => panic!("{:?} encoded to {:?}: {}", value, yay, e),
+    }
+    let yson = encode(&value, Format::Yson);
+    assert_eq!(parse_yson(&yson), Ok(value), "{}", yson);
+});
@@ -0,0 +1,73 @@
+//! Values that `arbitrary` makes, for fuzzing with structured input.
+//!
+//! Requires the `arbitrary` feature.
+
+use ::arbitrary::{Arbitrary, Result, Unstructured};
+use num_bigint::BigInt;
+
+use crate::{Map, Value};
+
+/// How deep arrays and objects nest.
+const MAX_DEPTH: u32 = 4;
+
+/// Makes only the values a YAY document reads back as themselves, like
+/// `testing::Gen::value`: no decimals, which parse as floats, and no dates,
+/// which YAY writes as strings. Arrays and objects nest at most four deep
+/// and have at most four members each.
+impl<'a> Arbitrary<'a> for Value {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        value(u, MAX_DEPTH)
+    }
+}
+
+fn value(u: &mut Unstructured, depth: u32) -> Result<Value> {
+    // Past the depth, only scalars.
+    let last = if depth == 0 { 5 } else { 7 };
+    Ok(match u.int_in_range(0..=last)? {
+        0 => Value::Null,
+        1 => Value::Bool(u.arbitrary()?),
+        2 => Value::Integer(BigInt::from_signed_bytes_le(u.arbitrary()?)),
+        3 => Value::Float(u.arbitrary()?),
+        4 => Value::String(u.arbitrary()?),
+        5 => Value::Bytes(u.arbitrary()?),
+        6 => {
+            let len = u.int_in_range(0..=4)?;
+            Value::Array(
+                (0..len)
+                    .map(|_| value(u, depth - 1))
+                    .collect::<Result<_>>()?,
+            )
+        }
+        _ => {
+            let mut map = Map::new();
+            for _ in 0..u.int_in_range(0..=4)? {
+                map.insert(u.arbitrary()?, value(u, depth - 1)?);
+            }
+            Value::Object(map)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{encode, parse, Format};
+
+    #[test]
+    fn test_values_round_trip() {
+        let mut seed = 1u64;
+        for len in 0..200 {
+            let bytes: Vec<u8> = (0..len * 8)
+                .map(|_| {
+                    seed ^= seed << 13;
+                    seed ^= seed >> 7;
+                    seed ^= seed << 17;
+                    seed as u8
+                })
+                .collect();
+            let value = Value::arbitrary(&mut Unstructured::new(&bytes)).unwrap();
+            let yay = encode(&value, Format::Yay);
+            assert_eq!(parse(&yay).ok(), Some(value), "{}", yay);
+        }
+    }
+}
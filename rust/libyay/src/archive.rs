@@ -0,0 +1,440 @@
+//! Reading the members of tar and zip archives in memory.
+//!
+//! Fixture bundles often arrive as `fixtures.tar.gz` or `fixtures.zip`.
+//! [`members`] walks such an archive without extracting it: tar members
+//! are borrowed straight from the archive bytes, and zip members are
+//! inflated one at a time as the iteration reaches them. Decompress a
+//! `.tar.gz` or `.tar.zst` with [`compress::decompress`](crate::compress::decompress)
+//! first.
+
+use std::borrow::Cow;
+use std::io;
+
+use crate::compress::{crc32, inflate_raw, DEFAULT_MAX_OUTPUT};
+
+const BLOCK: usize = 512;
+const ZIP_LOCAL: u32 = 0x0403_4b50;
+const ZIP_CENTRAL: u32 = 0x0201_4b50;
+const ZIP_END: u32 = 0x0605_4b50;
+
+/// An archive format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveKind {
+    Tar,
+    Zip,
+}
+
+impl ArchiveKind {
+    /// Recognize an archive by its signature: a zip local or end record at
+    /// the start, or a tar header with a valid checksum.
+    pub fn detect(bytes: &[u8]) -> Option<ArchiveKind> {
+        if bytes.len() >= 4 && matches!(le32(bytes, 0), ZIP_LOCAL | ZIP_END) {
+            Some(ArchiveKind::Zip)
+        } else if bytes.len() >= BLOCK && tar_checksum_ok(&bytes[..BLOCK]) {
+            Some(ArchiveKind::Tar)
+        } else {
+            None
+        }
+    }
+}
+
+/// A regular file in an archive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Member<'a> {
+    /// The member's path within the archive.
+    pub name: String,
+    pub data: Cow<'a, [u8]>,
+}
+
+/// Iterate over the regular files in a tar or zip archive. Directories,
+/// links, and other special members are skipped.
+pub fn members(bytes: &[u8]) -> io::Result<Members<'_>> {
+    let inner = match ArchiveKind::detect(bytes) {
+        Some(ArchiveKind::Tar) => Inner::Tar { pos: 0 },
+        Some(ArchiveKind::Zip) => {
+            let (count, offset) = zip_directory(bytes)?;
+            Inner::Zip {
+                remaining: count,
+                pos: offset,
+            }
+        }
+        None => return Err(invalid("not a tar or zip archive")),
+    };
+    Ok(Members {
+        bytes,
+        inner,
+        done: false,
+    })
+}
+
+/// The iterator [`members`] returns. It stops after the first error.
+pub struct Members<'a> {
+    bytes: &'a [u8],
+    inner: Inner,
+    done: bool,
+}
+
+enum Inner {
+    Tar { pos: usize },
+    Zip { remaining: usize, pos: usize },
+}
+
+impl<'a> Iterator for Members<'a> {
+    type Item = io::Result<Member<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let result = match &mut self.inner {
+            Inner::Tar { pos } => next_tar(self.bytes, pos),
+            Inner::Zip { remaining, pos } => next_zip(self.bytes, remaining, pos),
+        };
+        match result {
+            Ok(Some(member)) => Some(Ok(member)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+fn le16(bytes: &[u8], at: usize) -> u16 {
+    u16::from_le_bytes([bytes[at], bytes[at + 1]])
+}
+
+fn le32(bytes: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]])
+}
+
+/// A NUL-terminated string field.
+fn field(bytes: &[u8]) -> &[u8] {
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    &bytes[..end]
+}
+
+/// A tar numeric field: octal text, or big-endian binary when the high
+/// bit of the first byte is set.
+fn tar_number(bytes: &[u8]) -> io::Result<u64> {
+    if bytes[0] & 0x80 != 0 {
+        return Ok(bytes[1..]
+            .iter()
+            .fold((bytes[0] & 0x7f) as u64, |n, &b| (n << 8) | b as u64));
+    }
+    let text = std::str::from_utf8(field(bytes)).map_err(|_| invalid("bad tar number"))?;
+    let text = text.trim_matches(' ');
+    if text.is_empty() {
+        return Ok(0);
+    }
+    u64::from_str_radix(text, 8).map_err(|_| invalid("bad tar number"))
+}
+
+fn tar_checksum_ok(header: &[u8]) -> bool {
+    let Ok(expected) = tar_number(&header[148..156]) else {
+        return false;
+    };
+    let sum: u64 = header
+        .iter()
+        .enumerate()
+        .map(|(i, &b)| {
+            if (148..156).contains(&i) {
+                32
+            } else {
+                b as u64
+            }
+        })
+        .sum();
+    sum == expected
+}
+
+/// The `path` record of a pax extended header, if any.
+fn pax_path(data: &[u8]) -> Option<String> {
+    let mut rest = data;
+    let mut path = None;
+    while !rest.is_empty() {
+        let space = rest.iter().position(|&b| b == b' ')?;
+        let len: usize = std::str::from_utf8(&rest[..space]).ok()?.parse().ok()?;
+        let record = rest.get(space + 1..len)?;
+        let record = record.strip_suffix(b"\n").unwrap_or(record);
+        if let Some(value) = record.strip_prefix(b"path=") {
+            path = Some(String::from_utf8_lossy(value).into_owned());
+        }
+        rest = &rest[len..];
+    }
+    path
+}
+
+fn next_tar<'a>(bytes: &'a [u8], pos: &mut usize) -> io::Result<Option<Member<'a>>> {
+    let mut long_name: Option<String> = None;
+    loop {
+        let Some(header) = bytes.get(*pos..*pos + BLOCK) else {
+            return if *pos == bytes.len() {
+                Ok(None)
+            } else {
+                Err(invalid("truncated tar header"))
+            };
+        };
+        if header.iter().all(|&b| b == 0) {
+            return Ok(None);
+        }
+        if !tar_checksum_ok(header) {
+            return Err(invalid("bad tar header checksum"));
+        }
+        let size = tar_number(&header[124..136])? as usize;
+        let start = *pos + BLOCK;
+        let data = bytes
+            .get(start..start + size)
+            .ok_or_else(|| invalid("truncated tar member"))?;
+        *pos = start + size.div_ceil(BLOCK) * BLOCK;
+
+        match header[156] {
+            b'L' => long_name = Some(String::from_utf8_lossy(field(data)).into_owned()),
+            b'x' => long_name = pax_path(data).or(long_name),
+            b'0' | 0 | b'7' => {
+                let name = long_name.take().unwrap_or_else(|| {
+                    let name = String::from_utf8_lossy(field(&header[..100]));
+                    let prefix = field(&header[345..500]);
+                    if &header[257..263] == b"ustar\0" && !prefix.is_empty() {
+                        format!("{}/{}", String::from_utf8_lossy(prefix), name)
+                    } else {
+                        name.into_owned()
+                    }
+                });
+                return Ok(Some(Member {
+                    name,
+                    data: Cow::Borrowed(data),
+                }));
+            }
+            _ => long_name = None,
+        }
+    }
+}
+
+/// Find the zip central directory, returning its entry count and offset.
+fn zip_directory(bytes: &[u8]) -> io::Result<(usize, usize)> {
+    if bytes.len() < 22 {
+        return Err(invalid("truncated zip archive"));
+    }
+    let earliest = bytes.len().saturating_sub(22 + 0xffff);
+    let end = (earliest..=bytes.len().saturating_sub(22))
+        .rev()
+        .find(|&i| le32(bytes, i) == ZIP_END)
+        .ok_or_else(|| invalid("missing zip end of central directory"))?;
+    let count = le16(bytes, end + 10);
+    let offset = le32(bytes, end + 16);
+    if count == 0xffff || offset == 0xffff_ffff {
+        return Err(invalid("zip64 archives are not supported"));
+    }
+    Ok((count as usize, offset as usize))
+}
+
+fn next_zip<'a>(
+    bytes: &'a [u8],
+    remaining: &mut usize,
+    pos: &mut usize,
+) -> io::Result<Option<Member<'a>>> {
+    while *remaining > 0 {
+        *remaining -= 1;
+        let entry = bytes
+            .get(*pos..*pos + 46)
+            .ok_or_else(|| invalid("truncated zip central directory"))?;
+        if le32(entry, 0) != ZIP_CENTRAL {
+            return Err(invalid("bad zip central directory entry"));
+        }
+        let flags = le16(entry, 8);
+        let method = le16(entry, 10);
+        let crc = le32(entry, 16);
+        let compressed = le32(entry, 20) as usize;
+        let size = le32(entry, 24) as usize;
+        let name_len = le16(entry, 28) as usize;
+        let extra_len = le16(entry, 30) as usize;
+        let comment_len = le16(entry, 32) as usize;
+        let local = le32(entry, 42) as usize;
+        let name = bytes
+            .get(*pos + 46..*pos + 46 + name_len)
+            .ok_or_else(|| invalid("truncated zip central directory"))?;
+        let name = String::from_utf8_lossy(name).into_owned();
+        *pos += 46 + name_len + extra_len + comment_len;
+        if name.ends_with('/') {
+            continue;
+        }
+        if flags & 1 != 0 {
+            return Err(invalid(&format!(
+                "{}: encrypted zip members are not supported",
+                name
+            )));
+        }
+
+        let header = bytes
+            .get(local..local + 30)
+            .ok_or_else(|| invalid("truncated zip local header"))?;
+        if le32(header, 0) != ZIP_LOCAL {
+            return Err(invalid("bad zip local header"));
+        }
+        let start = local + 30 + le16(header, 26) as usize + le16(header, 28) as usize;
+        let raw = bytes
+            .get(start..start + compressed)
+            .ok_or_else(|| invalid("truncated zip member"))?;
+        let data = match method {
+            0 => Cow::Borrowed(raw),
+            // A member may not inflate past the size it declares.
+            8 => Cow::Owned(
+                inflate_raw(raw, size.min(DEFAULT_MAX_OUTPUT))
+                    .map_err(|e| invalid(&format!("{}: {}", name, e)))?,
+            ),
+            _ => {
+                return Err(invalid(&format!(
+                    "{}: unsupported zip compression method {}",
+                    name, method
+                )))
+            }
+        };
+        if data.len() != size || crc32(&data) != crc {
+            return Err(invalid(&format!("{}: zip member checksum mismatch", name)));
+        }
+        return Ok(Some(Member { name, data }));
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compress::Compression;
+
+    fn tar_header(name: &str, kind: u8, size: usize) -> Vec<u8> {
+        let mut header = vec![0u8; BLOCK];
+        header[..name.len()].copy_from_slice(name.as_bytes());
+        header[124..135].copy_from_slice(format!("{:011o}", size).as_bytes());
+        header[156] = kind;
+        header[257..263].copy_from_slice(b"ustar\0");
+        header[263..265].copy_from_slice(b"00");
+        header[148..156].copy_from_slice(b"        ");
+        let sum: u32 = header.iter().map(|&b| b as u32).sum();
+        header[148..155].copy_from_slice(format!("{:06o}\0", sum).as_bytes());
+        header
+    }
+
+    fn tar(entries: &[(&str, u8, &[u8])]) -> Vec<u8> {
+        let mut out = Vec::new();
+        for (name, kind, data) in entries {
+            out.extend(tar_header(name, *kind, data.len()));
+            out.extend_from_slice(data);
+            out.resize(out.len().div_ceil(BLOCK) * BLOCK, 0);
+        }
+        out.resize(out.len() + 2 * BLOCK, 0);
+        out
+    }
+
+    fn names(bytes: &[u8]) -> Vec<(String, Vec<u8>)> {
+        members(bytes)
+            .unwrap()
+            .map(|m| m.map(|m| (m.name, m.data.into_owned())))
+            .collect::<io::Result<_>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_tar() {
+        let long = "deep/".repeat(30) + "b.yay";
+        let record = format!(" path={}\n", long);
+        let pax = format!("{}{}", record.len() + 3, record);
+        let archive = tar(&[
+            ("dir/", b'5', b""),
+            ("dir/a.yay", b'0', b"a: 1\n"),
+            ("PaxHeader", b'x', pax.as_bytes()),
+            ("ignored", b'0', b"b: 2\n"),
+        ]);
+        assert_eq!(ArchiveKind::detect(&archive), Some(ArchiveKind::Tar));
+        assert_eq!(
+            names(&archive),
+            vec![
+                ("dir/a.yay".to_string(), b"a: 1\n".to_vec()),
+                (long, b"b: 2\n".to_vec()),
+            ]
+        );
+        let mut truncated = archive.clone();
+        truncated.truncate(BLOCK + 3);
+        let results: Vec<_> = members(&truncated).unwrap().collect();
+        assert!(results[0].is_err());
+    }
+
+    fn zip(entries: &[(&str, &[u8], bool)]) -> Vec<u8> {
+        let mut out = Vec::new();
+        let mut central = Vec::new();
+        for (name, data, deflate) in entries {
+            let stored = if *deflate {
+                let gz = Compression::Gzip.compress(data);
+                gz[10..gz.len() - 8].to_vec()
+            } else {
+                data.to_vec()
+            };
+            let method: u16 = if *deflate { 8 } else { 0 };
+            let mut fields = Vec::new();
+            fields.extend_from_slice(&method.to_le_bytes());
+            fields.extend_from_slice(&[0; 4]);
+            fields.extend_from_slice(&crc32(data).to_le_bytes());
+            fields.extend_from_slice(&(stored.len() as u32).to_le_bytes());
+            fields.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            fields.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            fields.extend_from_slice(&[0; 2]);
+
+            central.extend_from_slice(&ZIP_CENTRAL.to_le_bytes());
+            central.extend_from_slice(&[20, 0, 20, 0, 0, 0]);
+            central.extend_from_slice(&fields);
+            central.extend_from_slice(&[0; 10]);
+            central.extend_from_slice(&(out.len() as u32).to_le_bytes());
+            central.extend_from_slice(name.as_bytes());
+
+            out.extend_from_slice(&ZIP_LOCAL.to_le_bytes());
+            out.extend_from_slice(&[20, 0, 0, 0]);
+            out.extend_from_slice(&fields);
+            out.extend_from_slice(name.as_bytes());
+            out.extend_from_slice(&stored);
+        }
+        let offset = out.len() as u32;
+        out.extend_from_slice(&central);
+        out.extend_from_slice(&ZIP_END.to_le_bytes());
+        out.extend_from_slice(&[0; 4]);
+        out.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+        out.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+        out.extend_from_slice(&(central.len() as u32).to_le_bytes());
+        out.extend_from_slice(&offset.to_le_bytes());
+        out.extend_from_slice(&[0; 2]);
+        out
+    }
+
+    #[test]
+    fn test_zip() {
+        let text = b"items:\n  - 1\n  - 1\n  - 1\n  - 1\n".repeat(10);
+        let archive = zip(&[
+            ("a.yay", b"a: 1\n", false),
+            ("dir/", b"", false),
+            ("dir/b.yay", &text, true),
+        ]);
+        assert_eq!(ArchiveKind::detect(&archive), Some(ArchiveKind::Zip));
+        assert_eq!(
+            names(&archive),
+            vec![
+                ("a.yay".to_string(), b"a: 1\n".to_vec()),
+                ("dir/b.yay".to_string(), text),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_not_an_archive() {
+        assert_eq!(ArchiveKind::detect(b"a: 1\n"), None);
+        assert_eq!(ArchiveKind::detect(&[b' '; 600]), None);
+        assert!(members(b"a: 1\n").is_err());
+    }
+}
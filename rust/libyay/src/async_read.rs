@@ -0,0 +1,189 @@
+//! Parsing from tokio's `AsyncRead` and `AsyncBufRead`, for services that
+//! take YAY request bodies without a blocked thread for each one.
+//!
+//! These are the async counterparts of [`parse_reader`](crate::parse_reader),
+//! [`parse_reader_with_options`](crate::parse_reader_with_options), and
+//! [`Documents`](crate::Documents), and read and report errors the same way.
+
+use alloc::string::ToString;
+use alloc::vec;
+use alloc::vec::Vec;
+use std::io;
+
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncRead, AsyncReadExt};
+
+use crate::error::{ParseError, Result};
+use crate::metrics;
+use crate::ndyay::{Record, Records};
+use crate::{ParseOptions, PushParser, Value};
+
+/// Parse a YAY document from an async reader.
+///
+/// The input is parsed as it arrives with a [`PushParser`], so it is never
+/// held whole as text.
+///
+/// # Example
+///
+/// ```
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+/// use libyay::{parse_async, Value};
+///
+/// let value = parse_async(&b"answer: 42\n"[..]).await.unwrap();
+/// assert_eq!(value["answer"], Value::from(42));
+/// # });
+/// ```
+pub async fn parse_async(reader: impl AsyncRead + Unpin) -> Result<Value> {
+    parse_async_with_options(reader, &ParseOptions::default()).await
+}
+
+/// Parse a YAY document from an async reader with options.
+///
+/// Errors reading the input, like errors in the document, name
+/// `options.filename` if it is set.
+pub async fn parse_async_with_options(
+    mut reader: impl AsyncRead + Unpin,
+    options: &ParseOptions,
+) -> Result<Value> {
+    let mut parser = PushParser::new(options);
+    let mut buffer = vec![0; 64 * 1024];
+    let mut bytes = 0;
+    let result = loop {
+        match reader.read(&mut buffer).await {
+            Ok(0) => break parser.finish(),
+            Ok(n) => {
+                bytes += n;
+                if let Err(e) = parser.feed(&buffer[..n]) {
+                    break Err(e);
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => {
+                let what = options.filename.as_deref().unwrap_or("input");
+                break Err(ParseError::Read(what.to_string(), e.to_string()));
+            }
+        }
+    };
+    metrics::parsed(bytes, result)
+}
+
+/// The documents in an async reader of newline-delimited YAY, parsed as
+/// they are read, as [`Documents`](crate::Documents) reads them from a
+/// blocking one.
+///
+/// # Example
+///
+/// ```
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+/// use libyay::{AsyncDocuments, ParseOptions, Value};
+///
+/// let input = b"{n: 1}\n{n: 2}\n";
+/// let mut documents = AsyncDocuments::new(&input[..], &ParseOptions::default());
+/// let mut values = Vec::new();
+/// while let Some(value) = documents.next().await {
+///     values.push(value.unwrap()["n"].clone());
+/// }
+/// assert_eq!(values, [Value::from(1), Value::from(2)]);
+/// # });
+/// ```
+pub struct AsyncDocuments<R> {
+    reader: R,
+    records: Records,
+}
+
+impl<R: AsyncBufRead + Unpin> AsyncDocuments<R> {
+    /// The documents in `reader`, each parsed with `options`.
+    pub fn new(reader: R, options: &ParseOptions) -> Self {
+        AsyncDocuments {
+            reader,
+            records: Records::new(options),
+        }
+    }
+
+    /// The one-based line where the last document returned began.
+    pub fn line(&self) -> usize {
+        self.records.start + 1
+    }
+
+    /// The next document, or `None` after the last. A document that does
+    /// not parse yields its error, and the one after it comes next.
+    #[allow(clippy::should_implement_trait)]
+    pub async fn next(&mut self) -> Option<Result<Value>> {
+        if self.records.done {
+            return None;
+        }
+        if let Some(e) = self.records.failed.take() {
+            self.records.done = true;
+            return Some(Err(e));
+        }
+        let record = self.read_record().await;
+        self.records.parse(record)
+    }
+
+    /// Read the next record, or `None` at the end of the input.
+    async fn read_record(&mut self) -> Result<Option<Record>> {
+        let mut record = self.records.next.take().unwrap_or_default();
+        loop {
+            let mut bytes = Vec::new();
+            let read = self.reader.read_until(b'\n', &mut bytes).await;
+            let line = self.records.decode(read, bytes);
+            if self.records.take(&mut record, line)? {
+                break;
+            }
+        }
+        Ok(record.start.is_some().then_some(record))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse, Documents};
+    use core::future::Future;
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    #[test]
+    fn test_parse_async() {
+        let options = ParseOptions {
+            filename: Some("body.yay".into()),
+            ..ParseOptions::default()
+        };
+        let input = "a: 1\nb: [2, 3]\n";
+        assert_eq!(
+            block_on(parse_async_with_options(input.as_bytes(), &options)).unwrap(),
+            parse(input).unwrap()
+        );
+        assert_eq!(
+            block_on(parse_async_with_options(&b"a: \"\xFF\"\n"[..], &options))
+                .unwrap_err()
+                .to_string(),
+            "Invalid UTF-8 at byte offset 4 of <body.yay>"
+        );
+    }
+
+    #[test]
+    fn test_documents() {
+        let input: &[u8] = b"# log\n1\n\na:\n  b: 3\n{c:\n- 5\n\"\xFF\"\n";
+        let options = ParseOptions::default();
+        let mut expected = Documents::new(input, &options);
+        let mut documents = AsyncDocuments::new(input, &options);
+        block_on(async {
+            loop {
+                let (want, got) = (expected.next(), documents.next().await);
+                assert_eq!(
+                    got.as_ref().map(|r| r.as_ref().map_err(|e| e.to_string())),
+                    want.as_ref().map(|r| r.as_ref().map_err(|e| e.to_string()))
+                );
+                assert_eq!(documents.line(), expected.line());
+                if got.is_none() {
+                    break;
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,435 @@
+//! Reading a document without copying its strings.
+//!
+//! [`parse_borrowed`] returns a [`BorrowedValue`] whose strings and keys
+//! are slices of the input wherever they need no unescaping, for read-only
+//! inspection of large documents where allocating each scalar would
+//! dominate the time spent.
+//!
+//! Documents in the form the encoder writes are read directly: block
+//! objects and arrays indented by two spaces, inline arrays and objects,
+//! keywords, numbers, double-quoted strings, and inline byte arrays. Any
+//! other document, including one with comments, blank lines, block strings,
+//! or an error, is parsed by [`parse`](crate::parse) and converted, so the
+//! result and the errors are always the same as `parse`'s.
+
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+
+use indexmap::IndexMap;
+use num_bigint::BigInt;
+
+use crate::error::ParseContext;
+use crate::parser::{parse_double_quoted_string, parse_keyword, parse_number};
+use crate::scanner::validate_line_code_points;
+use crate::value::MapState;
+use crate::{DateTime, Decimal, Map, Result, Value};
+
+/// The members of a [`BorrowedValue::Object`], in the order of the source.
+pub type BorrowedMap<'a> = IndexMap<Cow<'a, str>, BorrowedValue<'a>, MapState>;
+
+/// A YAY value whose strings may borrow from the source text.
+///
+/// Byte arrays are written in hex, so they are always decoded into a
+/// vector of their own.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BorrowedValue<'a> {
+    Null,
+    Bool(bool),
+    Integer(BigInt),
+    Float(f64),
+    Decimal(Decimal),
+    String(Cow<'a, str>),
+    Array(Vec<BorrowedValue<'a>>),
+    Object(BorrowedMap<'a>),
+    Bytes(Vec<u8>),
+    DateTime(DateTime),
+}
+
+impl<'a> BorrowedValue<'a> {
+    /// Returns the string if this is a `String`.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            BorrowedValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Returns the items if this is an `Array`.
+    pub fn as_array(&self) -> Option<&[BorrowedValue<'a>]> {
+        match self {
+            BorrowedValue::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    /// Returns the members if this is an `Object`.
+    pub fn as_object(&self) -> Option<&BorrowedMap<'a>> {
+        match self {
+            BorrowedValue::Object(members) => Some(members),
+            _ => None,
+        }
+    }
+
+    /// Returns the member named `key` if this is an `Object`.
+    pub fn get(&self, key: &str) -> Option<&BorrowedValue<'a>> {
+        self.as_object()?.get(key)
+    }
+
+    /// Copy the value out of the source.
+    pub fn into_owned(self) -> Value {
+        match self {
+            BorrowedValue::Null => Value::Null,
+            BorrowedValue::Bool(b) => Value::Bool(b),
+            BorrowedValue::Integer(n) => Value::Integer(n),
+            BorrowedValue::Float(f) => Value::Float(f),
+            BorrowedValue::Decimal(d) => Value::Decimal(d),
+            BorrowedValue::String(s) => Value::String(s.into_owned()),
+            BorrowedValue::Array(items) => {
+                Value::Array(items.into_iter().map(BorrowedValue::into_owned).collect())
+            }
+            BorrowedValue::Object(members) => Value::Object(
+                members
+                    .into_iter()
+                    .map(|(k, v)| (k.into_owned(), v.into_owned()))
+                    .collect::<Map>(),
+            ),
+            BorrowedValue::Bytes(b) => Value::Bytes(b),
+            BorrowedValue::DateTime(dt) => Value::DateTime(dt),
+        }
+    }
+}
+
+impl From<Value> for BorrowedValue<'_> {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Null => BorrowedValue::Null,
+            Value::Bool(b) => BorrowedValue::Bool(b),
+            Value::Integer(n) => BorrowedValue::Integer(n),
+            Value::Float(f) => BorrowedValue::Float(f),
+            Value::Decimal(d) => BorrowedValue::Decimal(d),
+            Value::String(s) => BorrowedValue::String(Cow::Owned(s)),
+            Value::Array(items) => {
+                BorrowedValue::Array(items.into_iter().map(BorrowedValue::from).collect())
+            }
+            Value::Object(members) => BorrowedValue::Object(
+                members
+                    .into_iter()
+                    .map(|(k, v)| (Cow::Owned(k), BorrowedValue::from(v)))
+                    .collect(),
+            ),
+            Value::Bytes(b) => BorrowedValue::Bytes(b),
+            Value::DateTime(dt) => BorrowedValue::DateTime(dt),
+        }
+    }
+}
+
+/// Parse a YAY document, borrowing its strings from `input` where they
+/// need no unescaping.
+///
+/// # Example
+///
+/// ```
+/// use std::borrow::Cow;
+/// use libyay::{parse, parse_borrowed, BorrowedValue};
+///
+/// let source = "name: \"yay\"\ntags: [\"a\\/b\", \"c\"]\n";
+/// let value = parse_borrowed(source).unwrap();
+/// assert!(matches!(value.get("name"), Some(BorrowedValue::String(Cow::Borrowed("yay")))));
+/// assert_eq!(value.get("tags").unwrap().as_array().unwrap()[0].as_str(), Some("a/b"));
+/// assert_eq!(value.into_owned(), parse(source).unwrap());
+/// ```
+pub fn parse_borrowed(input: &str) -> Result<BorrowedValue<'_>> {
+    match read(input) {
+        Some(value) => Ok(value),
+        None => crate::parse(input).map(BorrowedValue::from),
+    }
+}
+
+/// Read a document in the encoder's form, or return `None` to leave it to
+/// the full parser.
+fn read(input: &str) -> Option<BorrowedValue<'_>> {
+    let ctx = ParseContext::new(None);
+    let body = input.strip_suffix('\n').unwrap_or(input);
+    if body.is_empty() || body.contains('\r') {
+        return None;
+    }
+    let lines: Vec<&str> = body.split('\n').collect();
+    for (line_num, line) in lines.iter().enumerate() {
+        if line.is_empty()
+            || line.ends_with(' ')
+            || line.contains(['\t', '#', '\u{FEFF}'])
+            || validate_line_code_points(line, line_num, &ctx).is_err()
+        {
+            return None;
+        }
+    }
+    let mut reader = Reader {
+        lines,
+        ctx,
+        line: 0,
+    };
+    let value = reader.block(0)?;
+    (reader.line == reader.lines.len()).then_some(value)
+}
+
+struct Reader<'a> {
+    lines: Vec<&'a str>,
+    ctx: ParseContext,
+    /// The line being read.
+    line: usize,
+}
+
+impl<'a> Reader<'a> {
+    /// Whether line `i` is indented by exactly `col` spaces.
+    fn indented(&self, i: usize, col: usize) -> bool {
+        self.lines.get(i).is_some_and(|line| {
+            line.len() > col
+                && line.as_bytes()[..col].iter().all(|&b| b == b' ')
+                && line.as_bytes()[col] != b' '
+        })
+    }
+
+    /// Read the value that starts at column `col` of the current line.
+    fn block(&mut self, col: usize) -> Option<BorrowedValue<'a>> {
+        let text = self.lines[self.line].get(col..)?;
+        if text.starts_with("- ") {
+            self.array(col)
+        } else if key(text).is_some() {
+            self.object(col)
+        } else {
+            let value = self.inline_all(text)?;
+            self.line += 1;
+            Some(value)
+        }
+    }
+
+    /// Read a block array whose items start with `- ` at column `col`.
+    fn array(&mut self, col: usize) -> Option<BorrowedValue<'a>> {
+        let mut items = Vec::new();
+        loop {
+            items.push(self.block(col + 2)?);
+            if !(self.indented(self.line, col) && self.lines[self.line][col..].starts_with("- ")) {
+                return Some(BorrowedValue::Array(items));
+            }
+        }
+    }
+
+    /// Read a block object whose keys are at column `col`.
+    fn object(&mut self, col: usize) -> Option<BorrowedValue<'a>> {
+        let mut members = BorrowedMap::default();
+        loop {
+            let (k, rest) = key(&self.lines[self.line][col..])?;
+            let value = if rest.is_empty() {
+                self.line += 1;
+                if !self.indented(self.line, col + 2) {
+                    return None;
+                }
+                let text = &self.lines[self.line][col + 2..];
+                if !text.starts_with("- ") && key(text).is_none() {
+                    return None;
+                }
+                self.block(col + 2)?
+            } else {
+                let value = self.inline_all(rest.strip_prefix(' ')?)?;
+                self.line += 1;
+                value
+            };
+            members.insert(k, value);
+            if !self.indented(self.line, col) || self.lines[self.line][col..].starts_with("- ") {
+                return Some(BorrowedValue::Object(members));
+            }
+        }
+    }
+
+    /// Read an inline value that is all of `text`.
+    fn inline_all(&self, text: &'a str) -> Option<BorrowedValue<'a>> {
+        let (value, used) = self.inline(text)?;
+        (used == text.len()).then_some(value)
+    }
+
+    /// Read the inline value at the start of `text`, and how many bytes it
+    /// takes.
+    fn inline(&self, text: &'a str) -> Option<(BorrowedValue<'a>, usize)> {
+        match text.as_bytes().first()? {
+            b'"' => {
+                let (s, used) = self.string(text)?;
+                Some((BorrowedValue::String(s), used))
+            }
+            b'[' => self.inline_array(text),
+            b'{' => self.inline_object(text),
+            b'<' => {
+                let end = text.find('>')?;
+                Some((BorrowedValue::Bytes(hex(&text[1..end])?), end + 1))
+            }
+            _ => {
+                let end = text.find([',', ']', '}']).unwrap_or(text.len());
+                let token = &text[..end];
+                if !token
+                    .bytes()
+                    .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b"+-.".contains(&b))
+                {
+                    return None;
+                }
+                let value = match parse_keyword(token) {
+                    Some(value) => value,
+                    None if token.bytes().all(|b| !b.is_ascii_lowercase() || b == b'e') => {
+                        parse_number(token)?
+                    }
+                    None => return None,
+                };
+                Some((BorrowedValue::from(value), end))
+            }
+        }
+    }
+
+    fn inline_array(&self, text: &'a str) -> Option<(BorrowedValue<'a>, usize)> {
+        if text.starts_with("[]") {
+            return Some((BorrowedValue::Array(Vec::new()), 2));
+        }
+        let mut items = Vec::new();
+        let mut at = 1;
+        loop {
+            let (item, used) = self.inline(&text[at..])?;
+            items.push(item);
+            at += used;
+            if text[at..].starts_with(", ") {
+                at += 2;
+            } else if text[at..].starts_with(']') {
+                return Some((BorrowedValue::Array(items), at + 1));
+            } else {
+                return None;
+            }
+        }
+    }
+
+    fn inline_object(&self, text: &'a str) -> Option<(BorrowedValue<'a>, usize)> {
+        if text.starts_with("{}") {
+            return Some((BorrowedValue::Object(BorrowedMap::default()), 2));
+        }
+        let mut members = BorrowedMap::default();
+        let mut at = 1;
+        loop {
+            let (k, used) = key_name(&text[at..])?;
+            at += used;
+            at += text[at..].starts_with(": ").then_some(2)?;
+            let (value, used) = self.inline(&text[at..])?;
+            members.insert(k, value);
+            at += used;
+            if text[at..].starts_with(", ") {
+                at += 2;
+            } else if text[at..].starts_with('}') {
+                return Some((BorrowedValue::Object(members), at + 1));
+            } else {
+                return None;
+            }
+        }
+    }
+
+    /// Read the double-quoted string at the start of `text`, borrowing it
+    /// unless it has escapes.
+    fn string(&self, text: &'a str) -> Option<(Cow<'a, str>, usize)> {
+        let bytes = text.as_bytes();
+        let mut escaped = false;
+        let mut i = 1;
+        while i < bytes.len() {
+            match bytes[i] {
+                b'"' => break,
+                b'\\' => {
+                    escaped = true;
+                    i += 1;
+                }
+                b if b < 0x20 => return None,
+                _ => {}
+            }
+            i += 1;
+        }
+        if i >= bytes.len() {
+            return None;
+        }
+        let s = if escaped {
+            Cow::Owned(parse_double_quoted_string(&text[..=i], &self.ctx, 0, 0).ok()?)
+        } else {
+            Cow::Borrowed(&text[1..i])
+        };
+        Some((s, i + 1))
+    }
+}
+
+/// Split a `key:` or `key: value` line into the key and what follows the
+/// colon.
+fn key(text: &str) -> Option<(Cow<'_, str>, &str)> {
+    let (k, used) = key_name(text)?;
+    let rest = text[used..].strip_prefix(':')?;
+    (rest.is_empty() || rest.starts_with(' ')).then_some((k, rest))
+}
+
+/// Read a bare key, or a double-quoted key without escapes.
+fn key_name(text: &str) -> Option<(Cow<'_, str>, usize)> {
+    if let Some(quoted) = text.strip_prefix('"') {
+        let end = quoted.find('"')?;
+        let k = &quoted[..end];
+        if k.contains('\\') || k.bytes().any(|b| b < 0x20) {
+            return None;
+        }
+        return Some((Cow::Borrowed(k), end + 2));
+    }
+    let end = text
+        .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-'))
+        .unwrap_or(text.len());
+    (end > 0).then(|| (Cow::Borrowed(&text[..end]), end))
+}
+
+/// Decode lowercase hex digits, two to a byte.
+fn hex(digits: &str) -> Option<Vec<u8>> {
+    if !digits.len().is_multiple_of(2)
+        || !digits
+            .bytes()
+            .all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+    {
+        return None;
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&digits[i..i + 2], 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    #[test]
+    fn test_reads_encoded_documents_directly() {
+        let source = "a: [1, -2.5, \"x\"]\nb:\n  - {c: null}\n  - - <cafe>\n    - 7\n  - d: \"e\"\n    f:\n      - true\n\"g h\": {}\n";
+        let value = parse(source).unwrap();
+        let borrowed = read(source).unwrap();
+        assert!(matches!(
+            &borrowed.get("b").unwrap().as_array().unwrap()[2].get("d"),
+            Some(BorrowedValue::String(Cow::Borrowed("e")))
+        ));
+        assert_eq!(borrowed.into_owned(), value);
+    }
+
+    #[test]
+    fn test_falls_back_to_parse() {
+        for source in [
+            "# comment\na: 1\n",
+            "a:  1\n",
+            "a: 'x'\n",
+            "a:\n- 1\n",
+            "a: 1\n\nb: 2\n",
+            "b: [1,2]\n",
+            "x",
+        ] {
+            assert!(read(source).is_none(), "{:?}", source);
+            match parse(source) {
+                Ok(value) => assert_eq!(parse_borrowed(source).unwrap().into_owned(), value),
+                Err(e) => assert_eq!(
+                    parse_borrowed(source).unwrap_err().to_string(),
+                    e.to_string()
+                ),
+            }
+        }
+    }
+}
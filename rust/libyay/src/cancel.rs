@@ -0,0 +1,71 @@
+//! Stopping a parse or an encoding from another thread.
+
+use alloc::sync::Arc;
+use core::sync::atomic::{AtomicBool, Ordering};
+#[cfg(feature = "std")]
+use std::io::{self, Write};
+
+/// A flag that stops the parses and encodings it is given once it is
+/// cancelled, so an editor or language server can abandon the parse of a
+/// large document that the user has already changed again.
+///
+/// Clones share the flag: keep one, and give another to
+/// [`ParseOptions::cancel`](crate::ParseOptions::cancel) or
+/// [`encode_to_cancellable`](crate::encode_to_cancellable). The scanner
+/// checks it at every line and the parser at every array item and object
+/// member, and a cancelled parse fails with
+/// [`ParseError::Cancelled`](crate::ParseError::Cancelled).
+///
+/// # Example
+///
+/// ```
+/// use libyay::{parse_with_options, CancellationToken, ParseError, ParseOptions};
+///
+/// let token = CancellationToken::new();
+/// let mut options = ParseOptions::default();
+/// options.cancel = Some(token.clone());
+/// assert!(parse_with_options("a: 1", &options).is_ok());
+/// token.cancel();
+/// let result = parse_with_options("a: 1", &options);
+/// assert!(matches!(result, Err(ParseError::Cancelled(_))));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// A token that has not been cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop every parse and encoding given this token or a clone of it.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether [`cancel`](Self::cancel) has been called.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// A writer that fails once its token is cancelled.
+#[cfg(feature = "std")]
+pub(crate) struct Cancellable<'a, W> {
+    pub out: W,
+    pub token: &'a CancellationToken,
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> Write for Cancellable<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.token.is_cancelled() {
+            return Err(io::Error::other("Encoding cancelled"));
+        }
+        self.out.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
@@ -0,0 +1,234 @@
+//! Checksum comments on block byte arrays.
+//!
+//! A block byte array whose leader line carries only a comment of the form
+//! `# sha256: <hex digest>` declares the SHA-256 digest of its bytes:
+//!
+//! ```yay
+//! firmware: > # sha256: 385628c005a5edaa422e8240a85db8409a90c72cb844580dedae8f30f2d70e41
+//!   b0b5 c0ff
+//! ```
+//!
+//! The convention lives entirely in comments, so the document means the same
+//! to any YAY reader. [`verify_checksums`] checks the digests and
+//! [`add_checksums`] writes or refreshes them. Both work on source text,
+//! line by line, so they preserve everything else about the document.
+//! They need the `checksum` feature.
+
+use std::fmt;
+
+use sha2::{Digest, Sha256};
+
+/// The comment prefix that introduces a digest.
+const PREFIX: &str = "sha256:";
+
+/// A block byte array whose bytes do not match its checksum comment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ChecksumMismatch {
+    /// One-based line of the block's leader.
+    pub line: usize,
+    /// The digest in the comment.
+    pub expected: String,
+    /// The digest of the bytes.
+    pub actual: String,
+}
+
+impl fmt::Display for ChecksumMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}: sha256 checksum mismatch (comment says {}, bytes hash to {})",
+            self.line, self.expected, self.actual
+        )
+    }
+}
+
+/// Check every block byte array that has a `# sha256:` comment.
+pub fn verify_checksums(source: &str) -> Vec<ChecksumMismatch> {
+    let lines: Vec<&str> = source.lines().collect();
+    let mut mismatches = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        let Some(leader) = Leader::parse(line) else {
+            continue;
+        };
+        let Some(expected) = leader.digest() else {
+            continue;
+        };
+        let actual = hex(&sha256(&block_bytes(&lines[i + 1..], leader.base)));
+        if !expected.eq_ignore_ascii_case(&actual) {
+            mismatches.push(ChecksumMismatch {
+                line: i + 1,
+                expected: expected.to_string(),
+                actual,
+            });
+        }
+    }
+    mismatches
+}
+
+/// Write a `# sha256:` comment on every block byte array whose leader has
+/// no comment, and refresh the ones that already have one. Leaders with
+/// other comments, and blocks that start on the leader line, are left
+/// alone.
+pub fn add_checksums(source: &str) -> String {
+    let lines: Vec<&str> = source.lines().collect();
+    let mut out = String::with_capacity(source.len());
+    for (i, line) in lines.iter().enumerate() {
+        match Leader::parse(line) {
+            Some(leader) if leader.comment.is_none() || leader.digest().is_some() => {
+                let digest = hex(&sha256(&block_bytes(&lines[i + 1..], leader.base)));
+                out.push_str(&line[..=leader.gt]);
+                out.push_str(&format!(" # {} {}", PREFIX, digest));
+            }
+            _ => out.push_str(line),
+        }
+        out.push('\n');
+    }
+    if !source.ends_with('\n') {
+        out.pop();
+    }
+    out
+}
+
+/// The leader line of a block byte array with no bytes on it.
+struct Leader<'a> {
+    /// Byte offset of the `>`.
+    gt: usize,
+    /// Content lines are indented more than this.
+    base: usize,
+    /// The comment after `>`, without the `#`.
+    comment: Option<&'a str>,
+}
+
+impl<'a> Leader<'a> {
+    fn parse(line: &'a str) -> Option<Leader<'a>> {
+        let mut pos = line.len() - line.trim_start_matches(' ').len();
+        let mut base = pos;
+        while line[pos..].starts_with("- ") {
+            base = pos;
+            pos += 2;
+        }
+        if !line[pos..].starts_with('>') {
+            base = pos;
+            pos += key_len(&line[pos..])?;
+            if !line[pos..].starts_with(": >") {
+                return None;
+            }
+            pos += 2;
+        }
+        let rest = line[pos + 1..].trim();
+        let comment = match rest.strip_prefix('#') {
+            Some(comment) => Some(comment.trim()),
+            None if rest.is_empty() => None,
+            None => return None,
+        };
+        Some(Leader {
+            gt: pos,
+            base,
+            comment,
+        })
+    }
+
+    fn digest(&self) -> Option<&'a str> {
+        self.comment?.strip_prefix(PREFIX).map(str::trim)
+    }
+}
+
+/// The length of a bare or quoted key at the start of `s`.
+fn key_len(s: &str) -> Option<usize> {
+    let quote = s.chars().next()?;
+    if quote == '"' || quote == '\'' {
+        let mut escaped = false;
+        for (i, c) in s.char_indices().skip(1) {
+            match c {
+                '\\' if quote == '"' && !escaped => escaped = true,
+                c if c == quote && !escaped => return Some(i + 1),
+                _ => escaped = false,
+            }
+        }
+        return None;
+    }
+    let len = s
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '-'))
+        .unwrap_or(s.len());
+    (len > 0).then_some(len)
+}
+
+/// Decode the hex lines of a block indented more than `base`.
+fn block_bytes(lines: &[&str], base: usize) -> Vec<u8> {
+    let mut digits = String::new();
+    for line in lines {
+        let indent = line.len() - line.trim_start_matches(' ').len();
+        if line.trim().is_empty() || indent <= base {
+            break;
+        }
+        let content = line.split('#').next().unwrap_or("");
+        digits.extend(content.chars().filter(|c| !c.is_whitespace()));
+    }
+    (0..digits.len() / 2)
+        .filter_map(|i| u8::from_str_radix(&digits[2 * i..2 * i + 2], 16).ok())
+        .collect()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// SHA-256 (FIPS 180-4).
+pub(crate) fn sha256(data: &[u8]) -> [u8; 32] {
+    Sha256::digest(data).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sha256() {
+        assert_eq!(
+            hex(&sha256(b"")),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            hex(&sha256(b"abc")),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        let long = vec![b'a'; 1000];
+        assert_eq!(
+            hex(&sha256(&long)),
+            "41edece42d63e8d9bf515a9ba6932e1c20cbc9f5a5d134645adb5db1b9737ea3"
+        );
+    }
+
+    #[test]
+    fn test_add_then_verify() {
+        let source = "a:\n  data: >\n    b0b5 c0ff\n  other: 1\nlist:\n  - >\n    cafe # note\n";
+        let annotated = add_checksums(source);
+        let digest = hex(&sha256(&[0xb0, 0xb5, 0xc0, 0xff]));
+        assert!(
+            annotated.contains(&format!("  data: > # sha256: {}\n", digest)),
+            "{}",
+            annotated
+        );
+        assert!(annotated.contains(&format!(
+            "  - > # sha256: {}\n",
+            hex(&sha256(&[0xca, 0xfe]))
+        )));
+        assert!(verify_checksums(&annotated).is_empty());
+        assert_eq!(add_checksums(&annotated), annotated);
+        assert!(crate::parse(&annotated).is_ok());
+    }
+
+    #[test]
+    fn test_mismatch_and_skipped_leaders() {
+        let source = "data: > # sha256: 00\n  b0b5\nraw: > # raw bytes\n  cafe\ninline: > b0b5\n";
+        let mismatches = verify_checksums(source);
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].line, 1);
+        assert_eq!(mismatches[0].expected, "00");
+        let annotated = add_checksums(source);
+        assert!(annotated.contains("raw: > # raw bytes\n"));
+        assert!(annotated.contains("inline: > b0b5\n"));
+        assert!(verify_checksums(&annotated).is_empty());
+    }
+}
@@ -0,0 +1,135 @@
+//! Ordering keys for readers rather than by code point.
+
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+/// The order in which the encoders write the keys of an object.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum KeyOrder {
+    /// By the bytes of their UTF-8 encoding: `Zebra` before `apple`, and
+    /// `éclair` after `zebra`.
+    #[default]
+    Bytes,
+    /// By [`compare_collated`]: `apple`, `éclair`, `Zebra`.
+    Collated,
+    /// In the order the document wrote them, or they were inserted in,
+    /// which a [`Value`](crate::Value) keeps.
+    Document,
+}
+
+impl KeyOrder {
+    /// Parse an order name (`bytes`, `collated`, or `document`).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "bytes" => Some(KeyOrder::Bytes),
+            "collated" => Some(KeyOrder::Collated),
+            "document" => Some(KeyOrder::Document),
+            _ => None,
+        }
+    }
+
+    /// Sort `keys` in this order, which leaves them as they are for
+    /// [`KeyOrder::Document`].
+    pub fn sort<K: AsRef<str>>(self, keys: &mut [K]) {
+        match self {
+            KeyOrder::Document => {}
+            KeyOrder::Bytes => keys.sort_by(|a, b| a.as_ref().cmp(b.as_ref())),
+            KeyOrder::Collated => keys.sort_by(|a, b| compare_collated(a.as_ref(), b.as_ref())),
+        }
+    }
+}
+
+/// Compare two strings as a dictionary orders words, without ICU: first by
+/// their letters ignoring case and accents, then by accents, then with
+/// lowercase before uppercase, and last by bytes, so that only equal
+/// strings compare equal and the order is the same everywhere.
+///
+/// Accented Latin letters sort with their base letters, `ß` as `ss` and
+/// `æ` as `ae`, and `ё` with `е`. Other scripts sort by code point within
+/// each level, which suits alphabets encoded in order, like Greek and
+/// Cyrillic. No language's tailoring applies, so Swedish `å` sorts with
+/// `a` rather than after `z`.
+///
+/// # Example
+///
+/// ```
+/// use libyay::compare_collated;
+///
+/// let mut words = vec!["zèbre", "Éclair", "eclair", "éclair", "apple"];
+/// words.sort_by(|a, b| compare_collated(a, b));
+/// assert_eq!(words, ["apple", "eclair", "éclair", "Éclair", "zèbre"]);
+/// ```
+pub fn compare_collated(a: &str, b: &str) -> Ordering {
+    let letters = |s: &str| -> Vec<char> { s.chars().flat_map(fold).collect() };
+    let accents = |s: &str| -> Vec<char> { s.chars().flat_map(char::to_lowercase).collect() };
+    let cases = |s: &str| -> Vec<bool> { s.chars().map(char::is_uppercase).collect() };
+    letters(a)
+        .cmp(&letters(b))
+        .then_with(|| accents(a).cmp(&accents(b)))
+        .then_with(|| cases(a).cmp(&cases(b)))
+        .then_with(|| a.cmp(b))
+}
+
+/// A character lowercased and without its accent, as one or more letters.
+fn fold(c: char) -> impl Iterator<Item = char> {
+    let base = match c {
+        'À'..='Å' | 'à'..='å' | 'Ā'..='ą' => "a",
+        'Æ' | 'æ' => "ae",
+        'Ç' | 'ç' | 'Ć'..='č' => "c",
+        'Ð' | 'ð' | 'Ď'..='đ' => "d",
+        'È'..='Ë' | 'è'..='ë' | 'Ē'..='ě' => "e",
+        'Ĝ'..='ģ' => "g",
+        'Ĥ'..='ħ' => "h",
+        'Ì'..='Ï' | 'ì'..='ï' | 'Ĩ'..='ı' => "i",
+        'Ĳ' | 'ĳ' => "ij",
+        'Ĵ' | 'ĵ' => "j",
+        'Ķ'..='ĸ' => "k",
+        'Ĺ'..='ł' => "l",
+        'Ñ' | 'ñ' | 'Ń'..='ŋ' => "n",
+        'Ò'..='Ö' | 'Ø' | 'ò'..='ö' | 'ø' | 'Ō'..='ő' => "o",
+        'Œ' | 'œ' => "oe",
+        'Ŕ'..='ř' => "r",
+        'ß' => "ss",
+        'Ś'..='š' | 'ſ' => "s",
+        'Ţ'..='ŧ' => "t",
+        'Þ' | 'þ' => "th",
+        'Ù'..='Ü' | 'ù'..='ü' | 'Ũ'..='ų' => "u",
+        'Ŵ' | 'ŵ' => "w",
+        'Ý' | 'ý' | 'ÿ' | 'Ŷ'..='Ÿ' => "y",
+        'Ź'..='ž' => "z",
+        'Ё' | 'ё' => "е",
+        _ => "",
+    };
+    let folded: Vec<char> = if base.is_empty() {
+        c.to_lowercase().collect()
+    } else {
+        base.chars().collect()
+    };
+    folded.into_iter()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_collated() {
+        let mut words = vec![
+            "Zürich", "zoo", "Straße", "strasse", "Strasse", "stras", "Ärger", "arm", "Œuvre",
+            "ofen", "ёж", "еда", "жук", "Łódź", "lodz", "b", "B", "a10", "a2",
+        ];
+        KeyOrder::Collated.sort(&mut words);
+        assert_eq!(
+            words,
+            [
+                "a10", "a2", "Ärger", "arm", "b", "B", "lodz", "Łódź", "Œuvre", "ofen", "stras",
+                "strasse", "Strasse", "Straße", "zoo", "Zürich", "еда", "ёж", "жук",
+            ]
+        );
+        KeyOrder::Bytes.sort(&mut words);
+        assert_eq!(words.first(), Some(&"B"));
+        assert_eq!(compare_collated("é", "é"), Ordering::Equal);
+        assert_ne!(compare_collated("a", "A"), Ordering::Equal);
+    }
+}
@@ -0,0 +1,295 @@
+//! Checks for values that a target format cannot represent, and a lossy
+//! mapping of the values JSON cannot represent to ones it can.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+
+use num_bigint::BigInt;
+use num_traits::ToPrimitive;
+
+use crate::{Format, Map, Value};
+
+/// A value that a target format cannot represent faithfully.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Incompatibility {
+    /// Path to the value, like `servers[0].port` (empty for the root).
+    pub path: String,
+    /// What the target format cannot represent.
+    pub reason: &'static str,
+}
+
+impl fmt::Display for Incompatibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = if self.path.is_empty() {
+            "(root)"
+        } else {
+            &self.path
+        };
+        write!(f, "{}: {}", path, self.reason)
+    }
+}
+
+impl Value {
+    /// Returns every value in this document that `format` cannot represent,
+    /// in document order.
+    ///
+    /// - JSON has no byte arrays and no integers (YAY integers are BigInts).
+    /// - TOML has no null and no byte arrays, limits integers to 64 bits,
+    ///   and requires the root to be a table.
+    /// - YAML has byte arrays only through the `!!binary` tag, and integers
+    ///   beyond 64 bits become strings.
+    /// - TOML has no decimals, and YAML decimals become floats.
+    /// - CBOR limits integers and decimal mantissas to its native range
+    ///   (-2^64 to 2^64-1).
+    ///
+    /// The other formats represent every YAY value.
+    pub fn compatibility(&self, format: Format) -> Vec<Incompatibility> {
+        let mut found = Vec::new();
+        if format == Format::Toml && !matches!(self, Value::Object(_)) {
+            found.push(Incompatibility {
+                path: String::new(),
+                reason: "TOML requires the root to be a table",
+            });
+        }
+        collect(self, format, &mut String::new(), &mut found);
+        found
+    }
+}
+
+fn collect(value: &Value, format: Format, path: &mut String, found: &mut Vec<Incompatibility>) {
+    if let Some(reason) = reason(value, format) {
+        found.push(Incompatibility {
+            path: path.clone(),
+            reason,
+        });
+    }
+    let len = path.len();
+    match value {
+        Value::Array(arr) => {
+            for (i, item) in arr.iter().enumerate() {
+                path.push_str(&format!("[{}]", i));
+                collect(item, format, path, found);
+                path.truncate(len);
+            }
+        }
+        Value::Object(obj) => {
+            for (key, item) in obj {
+                if !path.is_empty() {
+                    path.push('.');
+                }
+                path.push_str(key);
+                collect(item, format, path, found);
+                path.truncate(len);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn reason(value: &Value, format: Format) -> Option<&'static str> {
+    match (format, value) {
+        (Format::Json, Value::Bytes(_)) => Some("byte array"),
+        (Format::Json, Value::Integer(_)) => Some("integer (YAY integers are BigInts)"),
+        (Format::Toml, Value::Null) => Some("TOML has no null type"),
+        (Format::Toml, Value::Bytes(_)) => Some("TOML has no binary data type"),
+        (Format::Toml, Value::Integer(n)) if n.to_i64().is_none() => {
+            Some("TOML integers must fit in 64 bits (signed)")
+        }
+        (Format::Toml, Value::Decimal(_)) => Some("TOML has no decimal type"),
+        (Format::Yaml, Value::Decimal(_)) => Some("YAML decimals become floats"),
+        (Format::Yaml, Value::Bytes(_)) => Some("YAML has bytes only as the !!binary tag"),
+        (Format::Yaml, Value::Integer(n)) if n.to_i64().is_none() && n.to_u64().is_none() => {
+            Some("YAML integers beyond 64 bits become strings")
+        }
+        (Format::Cbor | Format::CborDiag, Value::Integer(n)) if !fits_cbor(n) => {
+            Some("integer exceeds CBOR's native range (-2^64 to 2^64-1)")
+        }
+        (Format::Cbor | Format::CborDiag, Value::Decimal(d)) if !fits_cbor(d.mantissa()) => {
+            Some("decimal mantissa exceeds CBOR's native range (-2^64 to 2^64-1)")
+        }
+        _ => None,
+    }
+}
+
+/// How [`Value::to_json_lossy`] writes the values JSON cannot represent.
+///
+/// Byte arrays always become base64 strings, with padding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JsonCompatOptions {
+    /// The largest magnitude of an integer that stays a number; larger
+    /// integers become strings of their digits. The default is 2^53 - 1,
+    /// the largest integer a JavaScript number holds exactly.
+    pub max_integer: u64,
+    /// What NaN and the infinities become.
+    pub non_finite: NonFinite,
+}
+
+impl Default for JsonCompatOptions {
+    fn default() -> Self {
+        JsonCompatOptions {
+            max_integer: (1 << 53) - 1,
+            non_finite: NonFinite::default(),
+        }
+    }
+}
+
+/// What [`Value::to_json_lossy`] makes of NaN and the infinities.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum NonFinite {
+    /// `null`, as JavaScript's `JSON.stringify` writes them.
+    #[default]
+    Null,
+    /// The strings `"NaN"`, `"Infinity"`, and `"-Infinity"`, which keep
+    /// them apart.
+    String,
+}
+
+impl Value {
+    /// Returns this value with everything JSON cannot represent replaced
+    /// by something it can, so that it encodes as JSON without an
+    /// incompatibility, at the cost of reading back differently.
+    ///
+    /// Byte arrays become base64 strings, integers beyond
+    /// `options.max_integer` become strings of their digits, and NaN and
+    /// the infinities become `options.non_finite`. Other integers stay
+    /// integers, which JSON writes as numbers.
+    ///
+    /// ```
+    /// use libyay::{encode, parse, Format, JsonCompatOptions};
+    ///
+    /// let value = parse("id: 18446744073709551616\nkey: <cafe>\nn: 1").unwrap();
+    /// let lossy = value.to_json_lossy(&JsonCompatOptions::default());
+    /// assert_eq!(
+    ///     encode(&lossy, Format::Json),
+    ///     "{\n  \"id\": \"18446744073709551616\",\n  \"key\": \"yv4=\",\n  \"n\": 1\n}"
+    /// );
+    /// ```
+    pub fn to_json_lossy(&self, options: &JsonCompatOptions) -> Value {
+        match self {
+            Value::Bytes(bytes) => Value::String(base64(bytes)),
+            Value::Integer(n) if n.magnitude() > &options.max_integer.into() => {
+                Value::String(n.to_string())
+            }
+            Value::Float(f) if !f.is_finite() => match options.non_finite {
+                NonFinite::Null => Value::Null,
+                NonFinite::String if f.is_nan() => Value::String("NaN".to_string()),
+                NonFinite::String if *f > 0.0 => Value::String("Infinity".to_string()),
+                NonFinite::String => Value::String("-Infinity".to_string()),
+            },
+            Value::Array(items) => Value::Array(
+                items
+                    .iter()
+                    .map(|item| item.to_json_lossy(options))
+                    .collect(),
+            ),
+            Value::Object(members) => Value::Object(
+                members
+                    .iter()
+                    .map(|(key, value)| (key.clone(), value.to_json_lossy(options)))
+                    .collect::<Map>(),
+            ),
+            _ => self.clone(),
+        }
+    }
+}
+
+/// Standard base64 with padding (RFC 4648 §4).
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Whether an integer fits a CBOR major type 0 or 1 argument.
+fn fits_cbor(n: &BigInt) -> bool {
+    n.to_u64().is_some() || (-n - 1u8).to_u64().is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    #[test]
+    fn test_reports_every_path() {
+        let value = parse("a: <cafe>\nb: [1.5, 2]\nc: \"ok\"\n").unwrap();
+        let found = value.compatibility(Format::Json);
+        let paths: Vec<String> = found.iter().map(|i| i.to_string()).collect();
+        assert_eq!(
+            paths,
+            vec!["a: byte array", "b[1]: integer (YAY integers are BigInts)"]
+        );
+        assert!(value.compatibility(Format::Yson).is_empty());
+    }
+
+    #[test]
+    fn test_toml_and_cbor() {
+        let value = parse("[null, 18446744073709551616]").unwrap();
+        let toml: Vec<String> = value
+            .compatibility(Format::Toml)
+            .iter()
+            .map(|i| i.to_string())
+            .collect();
+        assert_eq!(
+            toml,
+            vec![
+                "(root): TOML requires the root to be a table",
+                "[0]: TOML has no null type",
+                "[1]: TOML integers must fit in 64 bits (signed)"
+            ]
+        );
+        assert_eq!(value.compatibility(Format::Cbor).len(), 1);
+        let edge = parse("-18446744073709551616").unwrap();
+        assert!(edge.compatibility(Format::Cbor).is_empty());
+    }
+
+    #[test]
+    fn test_to_json_lossy() {
+        let value = parse(
+            "big: -9007199254740992\nbytes: [<>, <66>, <666f>, <666f6f>, <666f6f62>]\n\
+             floats: [nan, infinity, -infinity, 1.5]\nsafe: 9007199254740991",
+        )
+        .unwrap();
+        let lossy = value.to_json_lossy(&JsonCompatOptions::default());
+        assert!(lossy
+            .compatibility(Format::Json)
+            .iter()
+            .all(|i| i.reason.starts_with("integer")));
+        assert_eq!(
+            lossy,
+            parse(
+                "big: \"-9007199254740992\"\nbytes: [\"\", \"Zg==\", \"Zm8=\", \"Zm9v\", \"Zm9vYg==\"]\n\
+                 floats: [null, null, null, 1.5]\nsafe: 9007199254740991"
+            )
+            .unwrap()
+        );
+
+        let options = JsonCompatOptions {
+            max_integer: 0,
+            non_finite: NonFinite::String,
+        };
+        assert_eq!(
+            parse("[nan, infinity, -infinity, 0, 1]")
+                .unwrap()
+                .to_json_lossy(&options),
+            parse("k: [\"NaN\", \"Infinity\", \"-Infinity\", 0, \"1\"]").unwrap()["k"]
+        );
+    }
+}
@@ -0,0 +1,492 @@
+//! Schema-driven completion for editors.
+//!
+//! Given a schema and a cursor position, [`complete`] lists the keys that
+//! may be typed there, or the values a key may take, which is what a
+//! language server answers for a completion request.
+//!
+//! Schemas are JSON Schema documents, read as a [`Value`] from YAY or JSON.
+//! Completion uses the parts that describe what may appear where:
+//! `properties`, `additionalProperties`, `items`, `enum`, `const`,
+//! `default`, `type`, `description`, `allOf`, `anyOf`, `oneOf`, and `$ref`
+//! to a JSON Pointer within the same schema, like `#/$defs/server`.
+//! The document need not be complete: the cursor's place is read from the
+//! indentation of the lines around it.
+
+use crate::encode::{encode_yay, encode_yay_key};
+use crate::stats::type_name;
+use crate::Value;
+
+/// Whether a completion is an object key or a value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompletionKind {
+    Key,
+    Value,
+}
+
+/// One suggestion for the cursor's position.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completion {
+    /// The text to show: a key, or a value as YAY.
+    pub label: String,
+    pub kind: CompletionKind,
+    /// The text to insert: a key is quoted if it must be and followed by
+    /// its colon.
+    pub insert: String,
+    /// The schema's types for a key, or a value's type.
+    pub detail: Option<String>,
+    /// The schema's description.
+    pub documentation: Option<String>,
+}
+
+/// The completions for the cursor at `column` of line `line` (both
+/// 0-based) of `source`, as allowed by `schema`.
+///
+/// At the start of a line, or after a `- ` marker, the keys of the object
+/// there are offered, leaving out keys already present; after `key: `,
+/// the values that key may take. Either way, only suggestions starting
+/// with the text already typed are returned.
+///
+/// ```
+/// use libyay::completion::{complete, CompletionKind};
+/// use libyay::parse;
+///
+/// let schema = parse(
+///     "properties:\n  \
+///        name: {type: \"string\"}\n  \
+///        mode:\n    \
+///          enum: [\"fast\", \"safe\"]\n",
+/// )
+/// .unwrap();
+///
+/// let keys = complete(&schema, "name: \"web\"\nm", 1, 1);
+/// assert_eq!(keys[0].label, "mode");
+/// assert_eq!(keys[0].insert, "mode: ");
+/// assert_eq!(keys[0].kind, CompletionKind::Key);
+///
+/// let values = complete(&schema, "mode: ", 0, 6);
+/// let labels: Vec<&str> = values.iter().map(|c| c.label.as_str()).collect();
+/// assert_eq!(labels, ["\"fast\"", "\"safe\""]);
+/// ```
+pub fn complete(schema: &Value, source: &str, line: usize, column: usize) -> Vec<Completion> {
+    let lines: Vec<&str> = source.split('\n').collect();
+    let current = lines.get(line).copied().unwrap_or("");
+    let before = current.get(..column).unwrap_or(current);
+    let cursor = read_line(before);
+
+    // What the cursor line says: its markers, and a key if one is typed.
+    let mut markers = false;
+    let mut value = None;
+    let mut target = cursor.indent;
+    for (index, (col, step)) in cursor.steps.iter().enumerate() {
+        if index == 0 {
+            target = *col;
+        }
+        match step {
+            Step::Item => markers = true,
+            Step::Key(_) => value = Some(cursor.rest.trim_start()),
+        }
+    }
+    let prefix = value.unwrap_or(cursor.rest.trim_start());
+
+    let line = line.min(lines.len() - 1);
+    let schemas = Schemas { root: schema };
+    let mut found = schemas.resolve(schema, 0);
+    let above = enclosing(&lines[..line], target);
+    for step in above
+        .iter()
+        .chain(cursor.steps.iter().map(|(_, step)| step))
+    {
+        found = schemas.step(&found, step);
+    }
+
+    let mut completions = Vec::new();
+    if value.is_some() {
+        schemas.values(&found, prefix, &mut completions);
+        return completions;
+    }
+    let present = if markers {
+        Vec::new()
+    } else {
+        siblings(&lines, line, target)
+    };
+    schemas.keys(&found, prefix, &present, &mut completions);
+    if markers {
+        schemas.values(&found, prefix, &mut completions);
+    }
+    completions
+}
+
+/// A step into a document: an object member or an array item.
+#[derive(Debug, Clone, PartialEq)]
+enum Step {
+    Key(String),
+    Item,
+}
+
+/// A line's markers and key, each with its column, and the text after.
+struct Line<'a> {
+    indent: usize,
+    steps: Vec<(usize, Step)>,
+    rest: &'a str,
+}
+
+impl Line<'_> {
+    /// The column of the line's first marker or key.
+    fn first(&self) -> usize {
+        self.steps.first().map_or(self.indent, |&(col, _)| col)
+    }
+}
+
+fn read_line(text: &str) -> Line<'_> {
+    let indent = text.len() - text.trim_start_matches(' ').len();
+    let mut rest = &text[indent..];
+    let mut col = indent;
+    let mut steps = Vec::new();
+    while rest == "-" || rest.starts_with("- ") {
+        steps.push((col, Step::Item));
+        rest = rest.get(2..).unwrap_or("");
+        col += 2;
+    }
+    if let Some((key, after)) = split_key(rest) {
+        steps.push((col, Step::Key(key)));
+        rest = after;
+    }
+    Line {
+        indent,
+        steps,
+        rest,
+    }
+}
+
+/// Split `key: rest` or `key:` into the key and the rest.
+fn split_key(text: &str) -> Option<(String, &str)> {
+    let end = match text.chars().next()? {
+        quote @ ('"' | '\'') => {
+            let mut escaped = false;
+            let close = text[1..].find(|c| {
+                let close = !escaped && c == quote;
+                escaped = !escaped && c == '\\';
+                close
+            })?;
+            close + 2
+        }
+        _ => text.find(':')?,
+    };
+    let after = text[end..].strip_prefix(':')?;
+    if !(after.is_empty() || after.starts_with(' ')) {
+        return None;
+    }
+    let key = match crate::parse(&text[..end]) {
+        Ok(Value::String(key)) if text.starts_with(['"', '\'']) => key,
+        _ if text.starts_with(['"', '\'']) => return None,
+        _ => text[..end].to_string(),
+    };
+    Some((key, after))
+}
+
+/// Whether a line holds neither content nor a comment.
+fn is_blank(line: &str) -> bool {
+    let text = line.trim();
+    text.is_empty() || text.starts_with('#')
+}
+
+/// The steps from the root to the block at column `target` below `above`.
+fn enclosing(above: &[&str], mut target: usize) -> Vec<Step> {
+    let mut path = Vec::new();
+    for line in above.iter().rev().filter(|l| !is_blank(l)) {
+        if target == 0 {
+            break;
+        }
+        let line = read_line(line);
+        let first = line.first();
+        if first < target {
+            let steps = line.steps.into_iter().filter(|&(col, _)| col < target);
+            let steps: Vec<Step> = steps.map(|(_, step)| step).collect();
+            path.splice(0..0, steps);
+            target = first;
+        }
+    }
+    path
+}
+
+/// The keys already present in the object at column `target` around
+/// line `line`.
+fn siblings(lines: &[&str], line: usize, target: usize) -> Vec<String> {
+    let keys_at = |text: &str| -> Vec<String> {
+        let steps = read_line(text).steps.into_iter();
+        steps
+            .filter_map(|(col, step)| match step {
+                Step::Key(key) if col == target => Some(key),
+                _ => None,
+            })
+            .collect()
+    };
+    let mut keys = Vec::new();
+    for text in lines[..line].iter().rev().filter(|l| !is_blank(l)) {
+        keys.extend(keys_at(text));
+        if read_line(text).first() < target {
+            break;
+        }
+    }
+    for text in lines.iter().skip(line + 1).filter(|l| !is_blank(l)) {
+        if read_line(text).first() < target {
+            break;
+        }
+        keys.extend(keys_at(text));
+    }
+    keys
+}
+
+/// How deep `$ref`s are followed, so that a cycle ends.
+const MAX_REFS: usize = 32;
+
+/// A schema and the lookups into it.
+struct Schemas<'a> {
+    root: &'a Value,
+}
+
+impl<'a> Schemas<'a> {
+    /// `schema` with its `$ref`s followed and its `allOf`, `anyOf`, and
+    /// `oneOf` alternatives listed alongside it.
+    fn resolve(&self, schema: &'a Value, depth: usize) -> Vec<&'a Value> {
+        if depth > MAX_REFS {
+            return Vec::new();
+        }
+        let mut found = Vec::new();
+        if let Some(target) = schema
+            .get("$ref")
+            .and_then(Value::as_str)
+            .and_then(|r| r.strip_prefix('#'))
+            .and_then(|pointer| self.root.pointer(pointer))
+        {
+            found.extend(self.resolve(target, depth + 1));
+        }
+        found.push(schema);
+        for combinator in ["allOf", "anyOf", "oneOf"] {
+            for alternative in schema
+                .get(combinator)
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+            {
+                found.extend(self.resolve(alternative, depth + 1));
+            }
+        }
+        found
+    }
+
+    /// The schemas for `step` into a value described by `schemas`.
+    fn step(&self, schemas: &[&'a Value], step: &Step) -> Vec<&'a Value> {
+        let mut found = Vec::new();
+        for schema in schemas {
+            let next = match step {
+                Step::Key(key) => schema
+                    .get("properties")
+                    .and_then(|properties| properties.get(key))
+                    .or_else(|| schema.get("additionalProperties")),
+                Step::Item => schema.get("items"),
+            };
+            if let Some(next) = next.filter(|next| next.as_object().is_some()) {
+                found.extend(self.resolve(next, 0));
+            }
+        }
+        found
+    }
+
+    /// Add the keys of `schemas` that start with `prefix` and are not
+    /// `present`.
+    fn keys(
+        &self,
+        schemas: &[&'a Value],
+        prefix: &str,
+        present: &[String],
+        out: &mut Vec<Completion>,
+    ) {
+        for schema in schemas {
+            let Some(properties) = schema.get("properties").and_then(Value::as_object) else {
+                continue;
+            };
+            for (key, property) in properties {
+                if !key.starts_with(prefix)
+                    || present.contains(key)
+                    || out
+                        .iter()
+                        .any(|c| c.kind == CompletionKind::Key && c.label == *key)
+                {
+                    continue;
+                }
+                let property = self.resolve(property, 0);
+                let types = types(&property);
+                let block =
+                    !types.is_empty() && types.iter().all(|t| *t == "object" || *t == "array");
+                out.push(Completion {
+                    label: key.clone(),
+                    kind: CompletionKind::Key,
+                    insert: format!("{}:{}", encode_yay_key(key), if block { "" } else { " " }),
+                    detail: (!types.is_empty()).then(|| types.join(" | ")),
+                    documentation: description(&property),
+                });
+            }
+        }
+    }
+
+    /// Add the values `schemas` allow that start with `prefix`.
+    fn values(&self, schemas: &[&'a Value], prefix: &str, out: &mut Vec<Completion>) {
+        let mut add = |value: &Value, documentation: Option<String>| {
+            let label = encode_yay(value, 0).trim_end().to_string();
+            let quoted = format!("\"{}", prefix);
+            if !(label.starts_with(prefix) || label.starts_with(&quoted))
+                || out
+                    .iter()
+                    .any(|c| c.kind == CompletionKind::Value && c.label == label)
+            {
+                return;
+            }
+            out.push(Completion {
+                insert: label.clone(),
+                label,
+                kind: CompletionKind::Value,
+                detail: Some(type_name(value).to_string()),
+                documentation,
+            });
+        };
+        for schema in schemas {
+            let documentation = description(&[schema]);
+            if let Some(value) = schema.get("const") {
+                add(value, documentation.clone());
+            }
+            for value in schema
+                .get("enum")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+            {
+                add(value, documentation.clone());
+            }
+            if let Some(value) = schema.get("default") {
+                add(value, documentation.clone());
+            }
+            for typ in types(&[schema]) {
+                match typ {
+                    "boolean" => {
+                        add(&Value::Bool(true), documentation.clone());
+                        add(&Value::Bool(false), documentation.clone());
+                    }
+                    "null" => add(&Value::Null, documentation.clone()),
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+/// The `type`s the schemas name, in order and without repeats.
+fn types<'a>(schemas: &[&'a Value]) -> Vec<&'a str> {
+    let mut types = Vec::new();
+    for schema in schemas {
+        let named = match schema.get("type") {
+            Some(Value::String(typ)) => vec![typ.as_str()],
+            Some(Value::Array(typs)) => typs.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        for typ in named {
+            if !types.contains(&typ) {
+                types.push(typ);
+            }
+        }
+    }
+    types
+}
+
+/// The first `description` among the schemas.
+fn description(schemas: &[&Value]) -> Option<String> {
+    schemas
+        .iter()
+        .find_map(|schema| schema.get("description").and_then(Value::as_str))
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    fn labels(completions: &[Completion]) -> Vec<&str> {
+        completions.iter().map(|c| c.label.as_str()).collect()
+    }
+
+    fn schema() -> Value {
+        parse(
+            r##"
+"$defs":
+  server:
+    properties:
+      host: {type: "string", description: "Host name."}
+      port: {type: "integer", default: 80}
+      tls: {type: "boolean"}
+properties:
+  name: {type: "string"}
+  servers:
+    type: "array"
+    items: {"$ref": "#/$defs/server"}
+  mode:
+    anyOf: [{enum: ["fast", "safe"]}, {type: "null"}]
+  labels:
+    type: "object"
+    additionalProperties: {enum: ["on", "off"]}
+"##,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_root_keys() {
+        let schema = schema();
+        let completions = complete(&schema, "name: \"web\"\n\nmode: null\n", 1, 0);
+        assert_eq!(labels(&completions), ["servers", "labels"]);
+        assert_eq!(completions[0].insert, "servers:");
+        assert_eq!(completions[0].detail.as_deref(), Some("array"));
+        assert_eq!(labels(&complete(&schema, "s", 0, 1)), ["servers"]);
+    }
+
+    #[test]
+    fn test_keys_in_items() {
+        let schema = schema();
+        let source = "servers:\n  - host: \"a\"\n    \n  - \n";
+        let completions = complete(&schema, source, 2, 4);
+        assert_eq!(labels(&completions), ["port", "tls"]);
+        assert_eq!(
+            labels(&complete(&schema, source, 3, 4)),
+            ["host", "port", "tls"]
+        );
+        let host = &complete(&schema, "servers:\n  - h", 1, 5)[0];
+        assert_eq!(host.documentation.as_deref(), Some("Host name."));
+    }
+
+    #[test]
+    fn test_values() {
+        let schema = schema();
+        let source = "servers:\n  - tls: \n    port: ";
+        assert_eq!(labels(&complete(&schema, source, 1, 9)), ["true", "false"]);
+        assert_eq!(labels(&complete(&schema, source, 2, 10)), ["80"]);
+        assert_eq!(
+            labels(&complete(&schema, "mode: ", 0, 6)),
+            ["\"fast\"", "\"safe\"", "null"]
+        );
+        assert_eq!(labels(&complete(&schema, "mode: s", 0, 7)), ["\"safe\""]);
+        let source = "labels:\n  \"any key\": \"o";
+        assert_eq!(
+            labels(&complete(&schema, source, 1, 15)),
+            ["\"on\"", "\"off\""]
+        );
+    }
+
+    #[test]
+    fn test_unknown_places() {
+        let schema = schema();
+        assert!(complete(&schema, "other:\n  ", 1, 2).is_empty());
+        assert!(complete(&Value::Null, "a", 0, 1).is_empty());
+        let cycle = parse("\"$ref\": \"#\"").unwrap();
+        assert!(complete(&cycle, "", 0, 0).is_empty());
+    }
+}
@@ -0,0 +1,291 @@
+//! Reading and writing gzip- and zstd-compressed documents.
+//!
+//! Archived corpora are usually stored as `.yay.gz` or `.yay.zst`.
+//! [`decompress`] recognizes either by its magic number and passes anything
+//! else through unchanged, so callers can hand it every input they read.
+//! [`Compression::compress`] writes the same formats back.
+//!
+//! This module needs the `compress` feature. The codecs are `flate2` and
+//! `zstd`.
+//!
+//! A few kilobytes of compressed input can decompress to gigabytes, so the
+//! decoders stop at a limit: [`DEFAULT_MAX_OUTPUT`], or what
+//! [`decompress_limited`] is given.
+
+use std::borrow::Cow;
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+use flate2::read::{DeflateDecoder, MultiGzDecoder};
+use flate2::write::GzEncoder;
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// A compression format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Compression {
+    /// Not compressed.
+    #[default]
+    None,
+    /// gzip (RFC 1952).
+    Gzip,
+    /// Zstandard (RFC 8878).
+    Zstd,
+}
+
+impl Compression {
+    /// Recognize a compressed stream by its magic number.
+    pub fn detect(bytes: &[u8]) -> Compression {
+        if bytes.starts_with(&GZIP_MAGIC) {
+            Compression::Gzip
+        } else if bytes.starts_with(&ZSTD_MAGIC) {
+            Compression::Zstd
+        } else {
+            Compression::None
+        }
+    }
+
+    /// The compression a file name implies: `.gz` or `.zst`.
+    pub fn from_path(path: &Path) -> Compression {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("gz") => Compression::Gzip,
+            Some("zst") => Compression::Zstd,
+            _ => Compression::None,
+        }
+    }
+
+    /// Look up a compression by name: `none`, `gzip` (or `gz`), or `zstd`
+    /// (or `zst`).
+    pub fn from_name(name: &str) -> Option<Compression> {
+        match name {
+            "none" => Some(Compression::None),
+            "gzip" | "gz" => Some(Compression::Gzip),
+            "zstd" | "zst" => Some(Compression::Zstd),
+            _ => None,
+        }
+    }
+
+    /// The file extension for this compression, without the dot.
+    pub fn extension(self) -> Option<&'static str> {
+        match self {
+            Compression::None => None,
+            Compression::Gzip => Some("gz"),
+            Compression::Zstd => Some("zst"),
+        }
+    }
+
+    /// Compress `data` in this format.
+    pub fn compress(self, data: &[u8]) -> Vec<u8> {
+        match self {
+            Compression::None => data.to_vec(),
+            Compression::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(data).expect("writing to memory");
+                encoder.finish().expect("writing to memory")
+            }
+            Compression::Zstd => {
+                // With a checksum, as the `zstd` tool writes by default.
+                let mut encoder = zstd::Encoder::new(Vec::new(), 0).expect("writing to memory");
+                encoder.include_checksum(true).expect("writing to memory");
+                encoder.write_all(data).expect("writing to memory");
+                encoder.finish().expect("writing to memory")
+            }
+        }
+    }
+}
+
+/// The most bytes [`decompress`] and [`DecompressReader`] produce: 1 GiB.
+pub const DEFAULT_MAX_OUTPUT: usize = 1 << 30;
+
+/// Decompress `bytes` if they start with a gzip or zstd magic number, and
+/// return them unchanged otherwise. Fails if they decompress to more than
+/// [`DEFAULT_MAX_OUTPUT`] bytes.
+pub fn decompress(bytes: &[u8]) -> io::Result<Cow<'_, [u8]>> {
+    decompress_limited(bytes, DEFAULT_MAX_OUTPUT)
+}
+
+/// Decompress `bytes` as [`decompress`] does, failing if they decompress
+/// to more than `max_output` bytes.
+pub fn decompress_limited(bytes: &[u8], max_output: usize) -> io::Result<Cow<'_, [u8]>> {
+    match Compression::detect(bytes) {
+        Compression::None => Ok(Cow::Borrowed(bytes)),
+        Compression::Gzip => read_limited(MultiGzDecoder::new(bytes), max_output).map(Cow::Owned),
+        Compression::Zstd => {
+            read_limited(zstd::Decoder::with_buffer(bytes)?, max_output).map(Cow::Owned)
+        }
+    }
+}
+
+/// Decompress a raw DEFLATE stream, as zip archives store it, to at most
+/// `limit` bytes.
+pub(crate) fn inflate_raw(data: &[u8], limit: usize) -> io::Result<Vec<u8>> {
+    read_limited(DeflateDecoder::new(data), limit)
+}
+
+/// The CRC-32 of `data`, as gzip and zip use it.
+pub(crate) fn crc32(data: &[u8]) -> u32 {
+    let mut crc = flate2::Crc::new();
+    crc.update(data);
+    crc.sum()
+}
+
+/// Read all of `decoder`, failing once it produces more than `limit` bytes.
+fn read_limited(decoder: impl Read, limit: usize) -> io::Result<Vec<u8>> {
+    let mut out = Vec::new();
+    decoder
+        .take(limit.saturating_add(1) as u64)
+        .read_to_end(&mut out)?;
+    if out.len() > limit {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("decompresses to more than {} bytes", limit),
+        ));
+    }
+    Ok(out)
+}
+
+/// A reader that decompresses what it reads from another reader.
+///
+/// The first read drains the inner reader and decompresses all of it, so
+/// this suits documents, which are parsed whole anyway, rather than
+/// unbounded streams.
+pub struct DecompressReader<R> {
+    inner: Option<R>,
+    buffer: io::Cursor<Vec<u8>>,
+    max_output: usize,
+}
+
+impl<R: Read> DecompressReader<R> {
+    pub fn new(inner: R) -> Self {
+        Self::with_limit(inner, DEFAULT_MAX_OUTPUT)
+    }
+
+    /// A reader that fails if its input decompresses to more than
+    /// `max_output` bytes.
+    pub fn with_limit(inner: R, max_output: usize) -> Self {
+        DecompressReader {
+            inner: Some(inner),
+            buffer: io::Cursor::new(Vec::new()),
+            max_output,
+        }
+    }
+}
+
+impl<R: Read> Read for DecompressReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if let Some(mut inner) = self.inner.take() {
+            let mut raw = Vec::new();
+            inner.read_to_end(&mut raw)?;
+            let data = match decompress_limited(&raw, self.max_output)? {
+                Cow::Borrowed(_) => raw,
+                Cow::Owned(data) => data,
+            };
+            self.buffer = io::Cursor::new(data);
+        }
+        self.buffer.read(buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn samples() -> Vec<Vec<u8>> {
+        let mut yay = String::new();
+        for i in 0..2000 {
+            yay.push_str(&format!("- name: \"fixture {}\"\n  size: {}\n", i, i * 37));
+        }
+        let mut noise = Vec::new();
+        let mut x: u32 = 0x1234_5678;
+        for _ in 0..300_000 {
+            x ^= x << 13;
+            x ^= x >> 17;
+            x ^= x << 5;
+            noise.push((x >> 24) as u8);
+        }
+        vec![
+            Vec::new(),
+            b"a".to_vec(),
+            b"abcabcabcabcabcabc".to_vec(),
+            vec![0; 200_000],
+            yay.into_bytes(),
+            noise,
+        ]
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for compression in [Compression::Gzip, Compression::Zstd] {
+            for sample in samples() {
+                let compressed = compression.compress(&sample);
+                assert_eq!(Compression::detect(&compressed), compression);
+                assert_eq!(decompress(&compressed).unwrap(), sample);
+            }
+        }
+    }
+
+    #[test]
+    fn test_compresses_text() {
+        let text = &samples()[4];
+        for compression in [Compression::Gzip, Compression::Zstd] {
+            let compressed = compression.compress(text);
+            assert!(compressed.len() * 3 < text.len(), "{:?}", compression);
+        }
+    }
+
+    #[test]
+    fn test_reference_streams() {
+        // `printf 'a: 1\n' | gzip -n` and `printf 'a: 1\n' | zstd`
+        let gz = [
+            0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03, 0x4b, 0xb4, 0x52, 0x30,
+            0xe4, 0x02, 0x00, 0x69, 0x19, 0x23, 0xe3, 0x05, 0x00, 0x00, 0x00,
+        ];
+        let zst = [
+            0x28, 0xb5, 0x2f, 0xfd, 0x04, 0x58, 0x29, 0x00, 0x00, 0x61, 0x3a, 0x20, 0x31, 0x0a,
+            0xe6, 0xea, 0xe5, 0x83,
+        ];
+        assert_eq!(decompress(&gz).unwrap(), &b"a: 1\n"[..]);
+        assert_eq!(decompress(&zst).unwrap(), &b"a: 1\n"[..]);
+    }
+
+    #[test]
+    fn test_output_limit() {
+        // 200,000 zeros compress to a couple of kilobytes at most.
+        let zeros = &samples()[3];
+        for compression in [Compression::Gzip, Compression::Zstd] {
+            let compressed = compression.compress(zeros);
+            assert!(compressed.len() * 50 < zeros.len(), "{:?}", compression);
+            assert_eq!(
+                decompress_limited(&compressed, zeros.len()).unwrap(),
+                *zeros
+            );
+            let e = decompress_limited(&compressed, 10_000).unwrap_err();
+            assert!(e.to_string().contains("more than 10000 bytes"), "{}", e);
+            let mut reader = DecompressReader::with_limit(&compressed[..], 10_000);
+            assert!(reader.read_to_end(&mut Vec::new()).is_err());
+        }
+    }
+
+    #[test]
+    fn test_passthrough_and_errors() {
+        assert!(matches!(decompress(b"a: 1\n").unwrap(), Cow::Borrowed(_)));
+        let mut truncated = Compression::Gzip.compress(b"a: 1\n");
+        truncated.truncate(12);
+        assert!(decompress(&truncated).is_err());
+        let mut flipped = Compression::Zstd.compress(b"a: 1\nb: 2\n");
+        let last = flipped.len() - 1;
+        flipped[last] ^= 1;
+        assert!(decompress(&flipped).is_err());
+    }
+
+    #[test]
+    fn test_reader() {
+        let compressed = Compression::Zstd.compress(b"a: 1\n");
+        let mut text = String::new();
+        DecompressReader::new(&compressed[..])
+            .read_to_string(&mut text)
+            .unwrap();
+        assert_eq!(text, "a: 1\n");
+    }
+}
@@ -0,0 +1,495 @@
+//! The concrete syntax tree of a YAY document.
+//!
+//! Unlike a [`Value`](crate::Value), the tree keeps everything a person
+//! wrote besides the data: comments, blank lines, key order, quoting, and
+//! the block or inline form of each value. Linters, editors, and migration
+//! scripts can read and change a document through it and write it back
+//! with its comments intact.
+//!
+//! [`parse`] accepts loose YAY (MEH) as well as strict YAY, and
+//! [`to_string`] writes a tree as it stands. [`canonicalize`] normalizes a
+//! tree to canonical YAY, as `yay` formatting does.
+//!
+//! # Example
+//!
+//! ```
+//! use libyay::cst::{self, CstValue, Item, PropertyValue};
+//!
+//! let source = "# Where to listen\nport: 8080\n";
+//! let mut document = cst::parse(source).unwrap();
+//! for item in &mut document.items {
+//!     if let Item::Property(property) = item {
+//!         property.value = Some(PropertyValue::Inline(CstValue::Integer("9090".into())));
+//!     }
+//! }
+//! assert_eq!(cst::to_string(&document), "# Where to listen\nport: 9090\n");
+//! ```
+
+use crate::encode::{
+    encode_yay_array_item, encode_yay_inline, encode_yay_key, encode_yay_property,
+};
+use crate::meh::{format_document, parse_key, transform_to_canonical, MehParser};
+pub use crate::meh::{
+    ArrayItem, ArrayItemValue, Block, BlockBytes, BlockBytesLine, BlockString, BlockStringLine,
+    Comment, CstArray, CstArrayItem, CstBytes, CstObject, CstObjectEntry, CstString, CstValue,
+    Document, InlineBytes, Item, Key, Property, PropertyValue,
+};
+use crate::refactor::unquote;
+use crate::{FloatFormat, Map, Value};
+
+/// Parse a YAY or MEH document into its syntax tree.
+pub fn parse(source: &str) -> Result<Document, String> {
+    MehParser::new(source).parse()
+}
+
+/// Write a syntax tree as YAY text.
+pub fn to_string(document: &Document) -> String {
+    format_document(document)
+}
+
+/// Normalize a syntax tree to canonical YAY, keeping its comments, so that
+/// [`to_string`] writes what [`format_yay`](crate::format_yay) would.
+pub fn canonicalize(document: &Document) -> Document {
+    transform_to_canonical(document)
+}
+
+impl Document {
+    /// Set the value at `path`, like `server.port` or `servers[0].host`,
+    /// leaving the rest of the document as it was.
+    ///
+    /// A property keeps its comment and its place; a missing property is
+    /// added after the last one in its object, with any missing objects on
+    /// the way to it, and index `n` of an array with `n` items is added
+    /// after the last. The new value is written as YAY formatting would
+    /// write it, and inline inside an inline array or object.
+    ///
+    /// ```
+    /// use libyay::{cst, Value};
+    ///
+    /// let mut document = cst::parse("# The public server\nserver:\n  port: 80  # http\n").unwrap();
+    /// document.set("server.port", &Value::Integer(8080.into())).unwrap();
+    /// document.set("server.tls", &Value::Bool(true)).unwrap();
+    /// assert_eq!(
+    ///     cst::to_string(&document),
+    ///     "# The public server\nserver:\n  port: 8080  # http\n  tls: true\n"
+    /// );
+    /// ```
+    pub fn set(&mut self, path: &str, value: &Value) -> Result<(), String> {
+        self.edit(path, Edit::Set(value))
+    }
+
+    /// Remove the property or array item at `path`.
+    pub fn remove(&mut self, path: &str) -> Result<(), String> {
+        self.edit(path, Edit::Remove)
+    }
+
+    /// Add a property named `key` after the property at `path`, in the
+    /// same object.
+    pub fn insert_after(&mut self, path: &str, key: &str, value: &Value) -> Result<(), String> {
+        self.edit(path, Edit::InsertAfter(key, value))
+    }
+
+    fn edit(&mut self, path: &str, edit: Edit) -> Result<(), String> {
+        let steps = steps(path)?;
+        edit_items(&mut self.items, &steps, &edit).map_err(|e| format!("{}: {}", path, e))
+    }
+}
+
+/// A step of a path into a document.
+#[derive(Clone, Copy)]
+enum Step<'p> {
+    Key(&'p str),
+    Index(usize),
+}
+
+/// A change to the member at the end of a path.
+enum Edit<'e> {
+    Set(&'e Value),
+    Remove,
+    InsertAfter(&'e str, &'e Value),
+}
+
+/// Split a path like `servers[0].host` into its steps.
+fn steps(path: &str) -> Result<Vec<Step<'_>>, String> {
+    let invalid = || format!("invalid path {:?}", path);
+    let mut steps = Vec::new();
+    for part in path.split('.') {
+        let (key, mut rest) = part.split_at(part.find('[').unwrap_or(part.len()));
+        if !key.is_empty() {
+            steps.push(Step::Key(key));
+        } else if rest.is_empty() {
+            return Err(invalid());
+        }
+        while !rest.is_empty() {
+            let (index, after) = rest
+                .strip_prefix('[')
+                .and_then(|r| r.split_once(']'))
+                .ok_or_else(invalid)?;
+            steps.push(Step::Index(index.parse().map_err(|_| invalid())?));
+            rest = after;
+        }
+    }
+    Ok(steps)
+}
+
+/// Apply `edit` along `steps` in the items of a document or block.
+fn edit_items(items: &mut Vec<Item>, steps: &[Step], edit: &Edit) -> Result<(), String> {
+    if let Some(value) = sole_value(items) {
+        return edit_value(value, steps, edit);
+    }
+    let (step, rest) = steps.split_first().expect("a path has steps");
+    match *step {
+        Step::Key(key) => {
+            if items.iter().any(|item| matches!(item, Item::ArrayItem(_))) {
+                return Err("not an object".to_string());
+            }
+            let found = items
+                .iter()
+                .position(|item| matches!(item, Item::Property(p) if unquote(p.key.name()) == key));
+            let Some(index) = found else {
+                let Edit::Set(value) = edit else {
+                    return Err(format!("no property {:?}", key));
+                };
+                let value = nest(rest, value).ok_or_else(|| format!("no property {:?}", key))?;
+                let at = after_last(items, |item| matches!(item, Item::Property(_)));
+                items.insert(at, Item::Property(property(key, &value)?));
+                return Ok(());
+            };
+            if rest.is_empty() {
+                return match edit {
+                    Edit::Set(value) => {
+                        if let Item::Property(p) = &mut items[index] {
+                            p.value = property(key, value)?.value;
+                        }
+                        Ok(())
+                    }
+                    Edit::Remove => {
+                        items.remove(index);
+                        Ok(())
+                    }
+                    Edit::InsertAfter(name, value) => {
+                        if items.iter().any(
+                            |item| matches!(item, Item::Property(p) if unquote(p.key.name()) == *name),
+                        ) {
+                            return Err(format!("property {:?} already exists", name));
+                        }
+                        items.insert(index + 1, Item::Property(property(name, value)?));
+                        Ok(())
+                    }
+                };
+            }
+            let Item::Property(p) = &mut items[index] else {
+                unreachable!("found a property");
+            };
+            match &mut p.value {
+                Some(PropertyValue::Block(block)) => edit_items(&mut block.items, rest, edit),
+                Some(PropertyValue::Inline(value)) => edit_value(value, rest, edit),
+                None => match edit {
+                    Edit::Set(value) => {
+                        let value = nest(rest, value).ok_or("no such item")?;
+                        p.value = property(key, &value)?.value;
+                        Ok(())
+                    }
+                    _ => Err(format!("property {:?} has no value", key)),
+                },
+            }
+        }
+        Step::Index(n) => {
+            if items.iter().any(|item| matches!(item, Item::Property(_))) {
+                return Err("not an array".to_string());
+            }
+            let positions: Vec<usize> = items
+                .iter()
+                .enumerate()
+                .filter(|(_, item)| matches!(item, Item::ArrayItem(_)))
+                .map(|(i, _)| i)
+                .collect();
+            let Some(&index) = positions.get(n) else {
+                return match edit {
+                    Edit::Set(value) if n == positions.len() && rest.is_empty() => {
+                        let at = after_last(items, |item| matches!(item, Item::ArrayItem(_)));
+                        items.insert(at, Item::ArrayItem(array_item(value)?));
+                        Ok(())
+                    }
+                    _ => Err(format!("no item {}", n)),
+                };
+            };
+            let Item::ArrayItem(item) = &mut items[index] else {
+                unreachable!("found an array item");
+            };
+            if rest.is_empty() {
+                return match edit {
+                    Edit::Set(value) => {
+                        item.value = array_item(value)?.value;
+                        Ok(())
+                    }
+                    Edit::Remove => {
+                        items.remove(index);
+                        Ok(())
+                    }
+                    Edit::InsertAfter(..) => Err("not a property".to_string()),
+                };
+            }
+            match &mut item.value {
+                Some(ArrayItemValue::Block(block)) => edit_items(&mut block.items, rest, edit),
+                Some(ArrayItemValue::Inline(value)) => edit_value(value, rest, edit),
+                None => Err(format!("item {} has no value", n)),
+            }
+        }
+    }
+}
+
+/// Apply `edit` along `steps` in an inline value.
+fn edit_value(value: &mut CstValue, steps: &[Step], edit: &Edit) -> Result<(), String> {
+    let (step, rest) = steps.split_first().expect("a path has steps");
+    match (*step, value) {
+        (Step::Key(key), CstValue::Object(object)) => {
+            let entries = &mut object.entries;
+            let found = entries.iter().position(|e| unquote(e.key.name()) == key);
+            let Some(index) = found else {
+                let Edit::Set(value) = edit else {
+                    return Err(format!("no property {:?}", key));
+                };
+                let value = nest(rest, value).ok_or_else(|| format!("no property {:?}", key))?;
+                entries.push(entry(key, &value)?);
+                return Ok(());
+            };
+            if !rest.is_empty() {
+                return edit_value(&mut entries[index].value, rest, edit);
+            }
+            match edit {
+                Edit::Set(value) => entries[index].value = inline(value)?,
+                Edit::Remove => {
+                    entries.remove(index);
+                }
+                Edit::InsertAfter(name, value) => {
+                    if entries.iter().any(|e| unquote(e.key.name()) == *name) {
+                        return Err(format!("property {:?} already exists", name));
+                    }
+                    entries.insert(index + 1, entry(name, value)?);
+                }
+            }
+            Ok(())
+        }
+        (Step::Index(n), CstValue::Array(array)) => {
+            let items = &mut array.items;
+            if n == items.len() && rest.is_empty() {
+                if let Edit::Set(value) = edit {
+                    items.push(CstArrayItem {
+                        value: inline(value)?,
+                    });
+                    return Ok(());
+                }
+            }
+            if n >= items.len() {
+                return Err(format!("no item {}", n));
+            }
+            if !rest.is_empty() {
+                return edit_value(&mut items[n].value, rest, edit);
+            }
+            match edit {
+                Edit::Set(value) => items[n].value = inline(value)?,
+                Edit::Remove => {
+                    items.remove(n);
+                }
+                Edit::InsertAfter(..) => return Err("not a property".to_string()),
+            }
+            Ok(())
+        }
+        (Step::Key(_), _) => Err("not an object".to_string()),
+        (Step::Index(_), _) => Err("not an array".to_string()),
+    }
+}
+
+/// The one value among `items`, less comments and blank lines, if they
+/// hold nothing else, as a document of an inline value does.
+fn sole_value(items: &mut [Item]) -> Option<&mut CstValue> {
+    let mut content = items
+        .iter_mut()
+        .filter(|item| !matches!(item, Item::BlankLine | Item::Comment(_)));
+    match (content.next(), content.next()) {
+        (Some(Item::Value(value)), None) => Some(value),
+        _ => None,
+    }
+}
+
+/// Where to add an item: after the last of `items` that `is_member`, or at
+/// the end if there are none.
+fn after_last(items: &[Item], is_member: impl Fn(&Item) -> bool) -> usize {
+    items
+        .iter()
+        .rposition(is_member)
+        .map_or(items.len(), |i| i + 1)
+}
+
+/// The value to set at the first of `steps` so that `value` is at the end
+/// of them, or `None` if they go through an array that is not there.
+fn nest(steps: &[Step], value: &Value) -> Option<Value> {
+    steps
+        .iter()
+        .rev()
+        .try_fold(value.clone(), |value, step| match step {
+            Step::Key(key) => Some(Value::Object(Map::from([(key.to_string(), value)]))),
+            Step::Index(_) => None,
+        })
+}
+
+/// Parse YAY text written by the encoder, which always parses.
+fn parse_encoded(text: &str) -> Result<Vec<Item>, String> {
+    Ok(parse(text)?.items)
+}
+
+/// A property as YAY formatting writes it.
+fn property(key: &str, value: &Value) -> Result<Property, String> {
+    parse_encoded(&encode_yay_property(key, value, 0))?
+        .into_iter()
+        .find_map(|item| match item {
+            Item::Property(p) => Some(p),
+            _ => None,
+        })
+        .ok_or_else(|| format!("cannot write property {:?}", key))
+}
+
+/// An array item as YAY formatting writes it.
+fn array_item(value: &Value) -> Result<ArrayItem, String> {
+    let text = encode_yay_array_item(value, 0, &FloatFormat::default());
+    parse_encoded(&format!("- {}", text))?
+        .into_iter()
+        .find_map(|item| match item {
+            Item::ArrayItem(item) => Some(item),
+            _ => None,
+        })
+        .ok_or_else(|| "cannot write array item".to_string())
+}
+
+/// A value on one line.
+fn inline(value: &Value) -> Result<CstValue, String> {
+    parse_encoded(&encode_yay_inline(value))?
+        .into_iter()
+        .find_map(|item| match item {
+            Item::Value(value) => Some(value),
+            _ => None,
+        })
+        .ok_or_else(|| "cannot write inline value".to_string())
+}
+
+/// An entry of an inline object.
+fn entry(key: &str, value: &Value) -> Result<CstObjectEntry, String> {
+    Ok(CstObjectEntry {
+        key: parse_key(&encode_yay_key(key)),
+        value: inline(value)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edits() {
+        let source = "# Config\nname: \"app\"  # the name\n\nserver:\n  port: 80\n  \
+                      hosts:\n    - \"a\"\n    - \"b\"\n  limits: {cpu: 2, mem: \"1G\"}\n\n\
+                      # Tail\nlist: [1, 2]\n";
+        let edited = |edit: &dyn Fn(&mut Document) -> Result<(), String>| {
+            let mut document = parse(source).unwrap();
+            edit(&mut document).map(|()| to_string(&document))
+        };
+        let int = |n: i64| Value::Integer(n.into());
+
+        let text = edited(&|d| {
+            d.set("server.hosts[1]", &Value::String("c".into()))?;
+            d.set(
+                "server.hosts[2]",
+                &crate::parse("x: 1\ny: [1, 2, 3, 4, 5, 6]").unwrap(),
+            )?;
+            d.set("server.limits.cpu", &int(4))?;
+            d.set("server.limits.disk", &int(8))?;
+            d.set("list[2]", &int(3))?;
+            d.remove("server.port")?;
+            d.insert_after("name", "version", &int(2))?;
+            d.set("db.primary.host", &Value::String("h".into()))
+        })
+        .unwrap();
+        assert_eq!(
+            text,
+            "# Config\nname: \"app\"  # the name\nversion: 2\n\nserver:\n  hosts:\n    \
+             - \"a\"\n    - \"c\"\n    - x: 1\n      y:\n        - 1\n        - 2\n        \
+             - 3\n        - 4\n        - 5\n        - 6\n  \
+             limits: {cpu: 4, mem: \"1G\", disk: 8}\n\n# Tail\nlist: [1, 2, 3]\n\
+             db:\n  primary: {host: \"h\"}\n"
+        );
+        assert!(crate::parse(&text).is_ok());
+
+        // A value written on the lines below moves the key's comment to
+        // where strict YAY allows it.
+        let text = edited(&|d| d.set("name", &Value::Bytes((0..20).collect()))).unwrap();
+        assert!(text.starts_with("# Config\nname: <000102"));
+        let text = edited(&|d| d.set("name", &Value::Bytes((0..40).collect()))).unwrap();
+        assert!(text.starts_with("# Config\nname: >\n  00 01"));
+        assert!(crate::parse(&text).is_ok());
+        let text = edited(&|d| d.set("name", &crate::parse("[1, 2, 3, 4, 5, 6]").unwrap()));
+        assert!(text
+            .unwrap()
+            .starts_with("# Config\nname: # the name\n  - 1\n"));
+
+        for (edit, error) in [
+            (
+                &(|d: &mut Document| d.remove("server.nope")) as &dyn Fn(&mut Document) -> _,
+                "server.nope: no property \"nope\"",
+            ),
+            (
+                &|d| d.set("name.first", &int(1)),
+                "name.first: not an object",
+            ),
+            (&|d| d.set("list[5]", &int(1)), "list[5]: no item 5"),
+            (
+                &|d| d.set("server.ports[0]", &int(1)),
+                "server.ports[0]: no property \"ports\"",
+            ),
+            (
+                &|d| d.insert_after("list[0]", "a", &int(1)),
+                "list[0]: not a property",
+            ),
+            (
+                &|d| d.insert_after("name", "list", &int(1)),
+                "name: property \"list\" already exists",
+            ),
+            (&|d| d.set("a..b", &int(1)), "invalid path \"a..b\""),
+        ] {
+            assert_eq!(edited(edit).unwrap_err(), error);
+        }
+    }
+
+    #[test]
+    fn test_edit_roots() {
+        let mut document = parse("").unwrap();
+        document.set("a", &Value::Null).unwrap();
+        assert_eq!(to_string(&document), "a: null\n");
+        let mut document = parse("[1, 2]\n").unwrap();
+        document.set("[0]", &Value::Bool(true)).unwrap();
+        document.remove("[1]").unwrap();
+        assert_eq!(to_string(&document), "[true]\n");
+        let mut document = parse("- 1\n- 2\n").unwrap();
+        document
+            .set("[2]", &Value::Bytes((0..30).collect()))
+            .unwrap();
+        let text = to_string(&document);
+        assert!(text.starts_with("- 1\n- 2\n- > 00 01"));
+        assert_eq!(crate::format_yay(&text).unwrap(), text);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let source = "# Servers\nservers:\n  - name: \"a\"\n    ports: [80, 443]\n\n  \
+                      - name: \"b\"\nport: 80  # http\ndata: >\n  ca fe ba be\n";
+        let document = parse(source).unwrap();
+        assert_eq!(to_string(&document), source);
+        assert_eq!(parse(&to_string(&document)).unwrap(), document);
+
+        let loose = parse("a:   1\nb: [1,2]\n").unwrap();
+        assert_eq!(
+            to_string(&canonicalize(&loose)),
+            crate::format_yay("a:   1\nb: [1,2]\n").unwrap()
+        );
+    }
+}
@@ -0,0 +1,261 @@
+//! Dates and times, as TOML and CBOR carry them.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use core::fmt;
+use core::str::FromStr;
+
+/// A date, a time, or both, kept as RFC 3339 text.
+///
+/// Besides an RFC 3339 date-time like `1979-05-27T07:32:00Z`, a `DateTime`
+/// may leave out the offset (`1979-05-27T07:32:00`) or be only a date
+/// (`1979-05-27`) or only a time (`07:32:00`), as TOML allows. The text is
+/// kept as written, except that the separator and `Z` are uppercase and a
+/// space between date and time becomes `T`.
+///
+/// YAY has no datetime syntax, so YAY output writes the text as a string.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DateTime(String);
+
+impl DateTime {
+    /// Read RFC 3339 text, or `None` if it is not a valid date or time.
+    ///
+    /// ```
+    /// use libyay::DateTime;
+    ///
+    /// let dt = DateTime::parse("1979-05-27 07:32:00.5-08:00").unwrap();
+    /// assert_eq!(dt.as_str(), "1979-05-27T07:32:00.5-08:00");
+    /// assert!(DateTime::parse("1979-02-30").is_none());
+    /// ```
+    pub fn parse(text: &str) -> Option<DateTime> {
+        let mut normal = String::with_capacity(text.len());
+        let time = if text.as_bytes().get(2) == Some(&b':') {
+            text
+        } else if text.len() == 10 {
+            check_date(text)?;
+            return Some(DateTime(text.to_string()));
+        } else {
+            let date = text.get(..10)?;
+            check_date(date)?;
+            normal.push_str(date);
+            normal.push('T');
+            let mut chars = text[10..].chars();
+            match chars.next()? {
+                'T' | 't' | ' ' => chars.as_str(),
+                _ => return None,
+            }
+        };
+
+        let (clock, offset) = match time.find(['Z', 'z', '+', '-']) {
+            Some(_) if normal.is_empty() => return None,
+            Some(i) => time.split_at(i),
+            None => (time, ""),
+        };
+        check_time(clock)?;
+        normal.push_str(clock);
+        match offset {
+            "" => {}
+            "Z" | "z" => normal.push('Z'),
+            _ => {
+                let (sign, hhmm) = offset.split_at(1);
+                let (h, m) = hhmm.split_once(':')?;
+                if h.len() != 2 || m.len() != 2 {
+                    return None;
+                }
+                number(h, 0, 23)?;
+                number(m, 0, 59)?;
+                normal.push_str(sign);
+                normal.push_str(hhmm);
+            }
+        }
+        Some(DateTime(normal))
+    }
+
+    /// The date and time `seconds` after 1970-01-01T00:00:00Z, as UTC, or
+    /// `None` if that is before year 0 or after year 9999. Fractions are
+    /// kept to the nanosecond.
+    ///
+    /// ```
+    /// use libyay::DateTime;
+    ///
+    /// let dt = DateTime::from_unix(1_000_000_000, 0).unwrap();
+    /// assert_eq!(dt.as_str(), "2001-09-09T01:46:40Z");
+    /// ```
+    pub fn from_unix(seconds: i64, nanos: u32) -> Option<DateTime> {
+        if nanos >= 1_000_000_000 {
+            return None;
+        }
+        let days = seconds.div_euclid(86_400);
+        let secs = seconds.rem_euclid(86_400);
+        let (year, month, day) = civil_from_days(days);
+        if !(0..=9999).contains(&year) {
+            return None;
+        }
+        let mut text = format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+            year,
+            month,
+            day,
+            secs / 3600,
+            secs / 60 % 60,
+            secs % 60
+        );
+        if nanos > 0 {
+            let fraction = format!("{:09}", nanos);
+            text.push('.');
+            text.push_str(fraction.trim_end_matches('0'));
+        }
+        text.push('Z');
+        Some(DateTime(text))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Whether this is a full RFC 3339 date-time, with a date, a time, and
+    /// an offset.
+    pub fn is_rfc3339(&self) -> bool {
+        self.0.len() > 10
+            && self.0.as_bytes()[10] == b'T'
+            && (self.0.ends_with('Z') || self.0[11..].contains(['+', '-']))
+    }
+}
+
+impl fmt::Display for DateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromStr for DateTime {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        DateTime::parse(s).ok_or_else(|| format!("Invalid date or time: {:?}", s))
+    }
+}
+
+/// `YYYY-MM-DD`, with a day that exists.
+fn check_date(date: &str) -> Option<()> {
+    let b = date.as_bytes();
+    if b.len() != 10 || b[4] != b'-' || b[7] != b'-' {
+        return None;
+    }
+    let year = number(&date[..4], 0, 9999)?;
+    let month = number(&date[5..7], 1, 12)?;
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days = match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    };
+    number(&date[8..], 1, days).map(|_| ())
+}
+
+/// `HH:MM:SS` with an optional fraction; second 60 is a leap second.
+fn check_time(time: &str) -> Option<()> {
+    let (clock, fraction) = match time.split_once('.') {
+        Some((clock, fraction)) => (clock, Some(fraction)),
+        None => (time, None),
+    };
+    let b = clock.as_bytes();
+    if b.len() != 8 || b[2] != b':' || b[5] != b':' {
+        return None;
+    }
+    number(&clock[..2], 0, 23)?;
+    number(&clock[3..5], 0, 59)?;
+    number(&clock[6..], 0, 60)?;
+    match fraction {
+        Some(f) if f.is_empty() || !f.bytes().all(|b| b.is_ascii_digit()) => None,
+        _ => Some(()),
+    }
+}
+
+/// Decimal digits between `min` and `max`.
+fn number(digits: &str, min: u32, max: u32) -> Option<u32> {
+    if !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok().filter(|n| (min..=max).contains(n))
+}
+
+/// The proleptic Gregorian date `days` after 1970-01-01.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        for text in [
+            "1979-05-27T07:32:00Z",
+            "1979-05-27T07:32:00-08:00",
+            "1979-05-27T07:32:00.999999",
+            "2000-02-29",
+            "07:32:00",
+            "23:59:60.5",
+        ] {
+            assert_eq!(DateTime::parse(text).unwrap().as_str(), text);
+        }
+        assert_eq!(
+            DateTime::parse("1979-05-27t07:32:00z").unwrap().as_str(),
+            "1979-05-27T07:32:00Z"
+        );
+        for text in [
+            "",
+            "1979-5-27",
+            "1900-02-29",
+            "1979-05-27T24:00:00",
+            "1979-05-27T07:32",
+            "1979-05-27T07:32:00+8",
+            "07:32:00Z",
+            "07:32:00.",
+            "1979-05-27X07:32:00",
+        ] {
+            assert_eq!(DateTime::parse(text), None, "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_rfc3339() {
+        assert!(DateTime::parse("1979-05-27T07:32:00Z")
+            .unwrap()
+            .is_rfc3339());
+        assert!(DateTime::parse("1979-05-27T07:32:00-08:00")
+            .unwrap()
+            .is_rfc3339());
+        assert!(!DateTime::parse("1979-05-27T07:32:00").unwrap().is_rfc3339());
+        assert!(!DateTime::parse("1979-05-27").unwrap().is_rfc3339());
+    }
+
+    #[test]
+    fn test_from_unix() {
+        assert_eq!(
+            DateTime::from_unix(0, 0).unwrap().as_str(),
+            "1970-01-01T00:00:00Z"
+        );
+        assert_eq!(
+            DateTime::from_unix(-1, 500_000_000).unwrap().as_str(),
+            "1969-12-31T23:59:59.5Z"
+        );
+        assert_eq!(
+            DateTime::from_unix(951_782_400, 0).unwrap().as_str(),
+            "2000-02-29T00:00:00Z"
+        );
+        assert_eq!(DateTime::from_unix(i64::MAX, 0), None);
+    }
+}
@@ -0,0 +1,603 @@
+//! Deserializing Rust types from YAY documents with serde.
+//!
+//! The document is parsed into a [`Value`] first, and the value is then
+//! walked to drive the target type's `Deserialize` implementation. Errors
+//! report the path to the offending value, like `servers[0].port`.
+//!
+//! Integers deserialize into any Rust integer type they fit, and into
+//! floats. Byte arrays deserialize through `deserialize_bytes`, as
+//! `serde_bytes::ByteBuf` and `#[serde(with = "serde_bytes")]` request, and
+//! also as a sequence of `u8`. An enum is a string naming a unit variant or
+//! an object with the variant name as its only key.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use core::fmt;
+
+use num_traits::ToPrimitive;
+use serde::de::{
+    self, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess, VariantAccess,
+    Visitor,
+};
+use serde::forward_to_deserialize_any;
+
+use crate::Value;
+
+/// An error deserializing a YAY document into a Rust type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    path: Option<String>,
+    message: String,
+}
+
+impl Error {
+    /// Path to the value that could not be deserialized, like
+    /// `servers[0].port`, empty for the root. `None` if the document
+    /// could not be parsed.
+    pub fn path(&self) -> Option<&str> {
+        self.path.as_deref()
+    }
+
+    /// The error without its path.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.path.as_deref() {
+            None | Some("") => f.write_str(&self.message),
+            Some(path) => write!(f, "{}: {}", path, self.message),
+        }
+    }
+}
+
+impl core::error::Error for Error {}
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error {
+            path: None,
+            message: msg.to_string(),
+        }
+    }
+}
+
+/// Parse a YAY document and deserialize it into `T`.
+///
+/// # Example
+///
+/// ```
+/// #[derive(serde::Deserialize)]
+/// struct Server {
+///     host: String,
+///     port: u16,
+/// }
+///
+/// let server: Server = libyay::from_str("host: \"example.com\"\nport: 8080\n").unwrap();
+/// assert_eq!(server.port, 8080);
+/// ```
+pub fn from_str<T: de::DeserializeOwned>(input: &str) -> Result<T, Error> {
+    let value = crate::parse(input).map_err(|e| Error {
+        path: None,
+        message: e.to_string(),
+    })?;
+    from_value(&value)
+}
+
+/// Deserialize `T` from a parsed YAY value. Strings and byte arrays may be
+/// borrowed from `value`.
+pub fn from_value<'de, T: de::Deserialize<'de>>(value: &'de Value) -> Result<T, Error> {
+    T::deserialize(Deserializer::new(value))
+}
+
+/// A serde `Deserializer` over a YAY [`Value`].
+pub struct Deserializer<'de> {
+    value: &'de Value,
+    path: String,
+}
+
+impl<'de> Deserializer<'de> {
+    pub fn new(value: &'de Value) -> Self {
+        Deserializer {
+            value,
+            path: String::new(),
+        }
+    }
+
+    fn item(&self, value: &'de Value, index: usize) -> Deserializer<'de> {
+        Deserializer {
+            value,
+            path: format!("{}[{}]", self.path, index),
+        }
+    }
+
+    fn member(&self, value: &'de Value, key: &str) -> Deserializer<'de> {
+        let path = if self.path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", self.path, key)
+        };
+        Deserializer { value, path }
+    }
+
+    /// Attribute an error from below to this value, unless a deeper value
+    /// has already claimed it.
+    fn locate<T>(&self, result: Result<T, Error>) -> Result<T, Error> {
+        result.map_err(|mut e| {
+            e.path.get_or_insert_with(|| self.path.clone());
+            e
+        })
+    }
+
+    fn integer<V: Visitor<'de>>(&self, visitor: V) -> Result<V::Value, Error> {
+        let Value::Integer(n) = self.value else {
+            unreachable!()
+        };
+        if let Some(n) = n.to_i64() {
+            visitor.visit_i64(n)
+        } else if let Some(n) = n.to_u64() {
+            visitor.visit_u64(n)
+        } else if let Some(n) = n.to_i128() {
+            visitor.visit_i128(n)
+        } else if let Some(n) = n.to_u128() {
+            visitor.visit_u128(n)
+        } else {
+            Err(de::Error::custom(format_args!(
+                "integer {} is too large for any Rust integer type",
+                n
+            )))
+        }
+    }
+}
+
+impl<'de> de::Deserializer<'de> for Deserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let result = match self.value {
+            Value::Null => visitor.visit_unit(),
+            Value::Bool(b) => visitor.visit_bool(*b),
+            Value::Integer(_) => self.integer(visitor),
+            Value::Float(f) => visitor.visit_f64(*f),
+            Value::Decimal(d) => visitor.visit_f64(d.to_f64()),
+            Value::String(s) => visitor.visit_borrowed_str(s),
+            Value::DateTime(dt) => visitor.visit_borrowed_str(dt.as_str()),
+            Value::Bytes(b) => visitor.visit_borrowed_bytes(b),
+            Value::Array(items) => visitor.visit_seq(Items {
+                parent: &self,
+                items: items.iter().enumerate(),
+            }),
+            Value::Object(map) => visitor.visit_map(Members {
+                parent: &self,
+                members: map.iter(),
+                value: None,
+            }),
+        };
+        self.locate(result)
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_f64(visitor)
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.value {
+            Value::Integer(n) => {
+                let result = visitor.visit_f64(n.to_f64().unwrap_or(f64::NAN));
+                self.locate(result)
+            }
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.value {
+            Value::Bytes(bytes) => {
+                let result = visitor.visit_seq(Octets {
+                    bytes: bytes.iter(),
+                });
+                self.locate(result)
+            }
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.value {
+            Value::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let result = match self.value {
+            Value::String(s) => visitor.visit_enum(s.as_str().into_deserializer()),
+            Value::Object(map) if map.len() == 1 => {
+                let (variant, value) = map.first().unwrap();
+                visitor.visit_enum(Variant {
+                    value: self.member(value, variant),
+                    variant,
+                })
+            }
+            _ => Err(de::Error::invalid_type(
+                unexpected(self.value),
+                &"a string or an object with a single key",
+            )),
+        };
+        self.locate(result)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 char str string
+        unit unit_struct map struct identifier
+    }
+}
+
+fn unexpected(value: &Value) -> de::Unexpected<'_> {
+    match value {
+        Value::Null => de::Unexpected::Unit,
+        Value::Bool(b) => de::Unexpected::Bool(*b),
+        Value::Integer(_) => de::Unexpected::Other("integer"),
+        Value::Float(f) => de::Unexpected::Float(*f),
+        Value::Decimal(d) => de::Unexpected::Float(d.to_f64()),
+        Value::String(s) => de::Unexpected::Str(s),
+        Value::DateTime(dt) => de::Unexpected::Str(dt.as_str()),
+        Value::Bytes(b) => de::Unexpected::Bytes(b),
+        Value::Array(_) => de::Unexpected::Seq,
+        Value::Object(_) => de::Unexpected::Map,
+    }
+}
+
+struct Items<'a, 'de> {
+    parent: &'a Deserializer<'de>,
+    items: core::iter::Enumerate<core::slice::Iter<'de, Value>>,
+}
+
+impl<'de> SeqAccess<'de> for Items<'_, 'de> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        match self.items.next() {
+            Some((i, item)) => seed.deserialize(self.parent.item(item, i)).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.items.len())
+    }
+}
+
+/// The bytes of a byte array, for types that want a sequence.
+struct Octets<'de> {
+    bytes: core::slice::Iter<'de, u8>,
+}
+
+impl<'de> SeqAccess<'de> for Octets<'de> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        self.bytes
+            .next()
+            .map(|b| seed.deserialize(b.into_deserializer()))
+            .transpose()
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.bytes.len())
+    }
+}
+
+struct Members<'a, 'de> {
+    parent: &'a Deserializer<'de>,
+    members: indexmap::map::Iter<'de, String, Value>,
+    value: Option<(&'de String, &'de Value)>,
+}
+
+impl<'de> MapAccess<'de> for Members<'_, 'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        match self.members.next() {
+            Some((key, value)) => {
+                self.value = Some((key, value));
+                seed.deserialize(Key(key)).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        let (key, value) = self
+            .value
+            .take()
+            .expect("next_value_seed called before next_key_seed");
+        seed.deserialize(self.parent.member(value, key))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.members.len())
+    }
+}
+
+/// An object key, which is always a string in YAY but may be deserialized
+/// as a number for maps like `HashMap<u32, T>`.
+struct Key<'de>(&'de str);
+
+macro_rules! parse_key {
+    ($($method:ident => $visit:ident),*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                match self.0.parse() {
+                    Ok(n) => visitor.$visit(n),
+                    Err(_) => self.deserialize_any(visitor),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for Key<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_borrowed_str(self.0)
+    }
+
+    parse_key! {
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_enum(self.0.into_deserializer())
+    }
+
+    forward_to_deserialize_any! {
+        bool i128 u128 f32 f64 char str string bytes byte_buf option unit
+        unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+struct Variant<'de> {
+    variant: &'de str,
+    value: Deserializer<'de>,
+}
+
+impl<'de> EnumAccess<'de> for Variant<'de> {
+    type Error = Error;
+    type Variant = Deserializer<'de>;
+
+    fn variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<(T::Value, Deserializer<'de>), Error> {
+        let variant = seed.deserialize(Key(self.variant))?;
+        Ok((variant, self.value))
+    }
+}
+
+impl<'de> VariantAccess<'de> for Deserializer<'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        de::Deserialize::deserialize(self)
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_seq(self, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_map(self, visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num_bigint::BigInt;
+    use serde::Deserialize;
+    use std::collections::{BTreeMap, HashMap};
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Config {
+        name: String,
+        port: u16,
+        ratio: f64,
+        tags: Vec<String>,
+        #[serde(default)]
+        comment: Option<String>,
+        #[serde(with = "serde_bytes")]
+        key: Vec<u8>,
+        mode: Mode,
+        servers: Vec<Server>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Server {
+        host: String,
+        weight: i8,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(rename_all = "lowercase")]
+    enum Mode {
+        Fast,
+        Limited { rate: u32 },
+    }
+
+    const CONFIG: &str = "\
+name: \"demo\"
+port: 8080
+ratio: 2
+tags: [\"a\", \"b\"]
+key: <cafe>
+mode: \"fast\"
+servers:
+- host: \"one\"
+  weight: 1
+- host: \"two\"
+  weight: -3
+";
+
+    #[test]
+    fn test_struct() {
+        let config: Config = from_str(CONFIG).unwrap();
+        assert_eq!(
+            config,
+            Config {
+                name: "demo".to_string(),
+                port: 8080,
+                ratio: 2.0,
+                tags: vec!["a".to_string(), "b".to_string()],
+                comment: None,
+                key: vec![0xca, 0xfe],
+                mode: Mode::Fast,
+                servers: vec![
+                    Server {
+                        host: "one".to_string(),
+                        weight: 1,
+                    },
+                    Server {
+                        host: "two".to_string(),
+                        weight: -3,
+                    },
+                ],
+            }
+        );
+        let limited: Mode = from_str("limited: {rate: 5}").unwrap();
+        assert_eq!(limited, Mode::Limited { rate: 5 });
+    }
+
+    #[test]
+    fn test_bytes_and_big_integers() {
+        let bytes: serde_bytes::ByteBuf = from_str("<0102>").unwrap();
+        assert_eq!(bytes.into_vec(), vec![1, 2]);
+        let octets: Vec<u8> = from_str("<0102>").unwrap();
+        assert_eq!(octets, vec![1, 2]);
+
+        let big: u128 = from_str("170141183460469231731687303715884105728").unwrap();
+        assert_eq!(big, 1 << 127);
+        let unsigned: u64 = from_str("18446744073709551615").unwrap();
+        assert_eq!(unsigned, u64::MAX);
+        let value = Value::Integer(BigInt::from(1) << 200);
+        assert!(from_value::<i64>(&value).is_err());
+        let float: f64 = from_value(&value).unwrap();
+        assert_eq!(float, 2f64.powi(200));
+
+        let keyed: BTreeMap<u32, bool> = from_str("{1: true, 20: false}").unwrap();
+        assert_eq!(keyed, BTreeMap::from([(1, true), (20, false)]));
+        let value = crate::parse("a: 'b'").unwrap();
+        let borrowed: HashMap<&str, &str> = from_value(&value).unwrap();
+        assert_eq!(borrowed["a"], "b");
+    }
+
+    #[test]
+    fn test_error_paths() {
+        let err = from_str::<Config>(&CONFIG.replace("weight: -3", "weight: 300")).unwrap_err();
+        assert_eq!(err.path(), Some("servers[1].weight"));
+        assert!(
+            err.to_string()
+                .starts_with("servers[1].weight: invalid value"),
+            "{}",
+            err
+        );
+
+        let err = from_str::<Config>(&CONFIG.replace("  weight: 1\n", "")).unwrap_err();
+        assert_eq!(err.to_string(), "servers[0]: missing field `weight`");
+
+        let err = from_str::<Config>(&CONFIG.replace("\"fast\"", "\"slow\"")).unwrap_err();
+        assert_eq!(err.path(), Some("mode"));
+
+        let err = from_str::<Config>("name: 1").unwrap_err();
+        assert_eq!(err.path(), Some("name"));
+        assert_eq!(
+            err.message(),
+            "invalid type: integer `1`, expected a string"
+        );
+
+        let err = from_str::<Config>("[").unwrap_err();
+        assert_eq!(err.path(), None);
+    }
+}
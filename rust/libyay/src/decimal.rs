@@ -0,0 +1,237 @@
+//! Exact decimal numbers, as CBOR carries them.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use core::cmp::Ordering;
+use core::fmt;
+use core::str::FromStr;
+use num_bigint::BigInt;
+use num_traits::{Signed, Zero};
+
+/// An exact decimal number: `mantissa × 10^exponent`.
+///
+/// Unlike a float, a `Decimal` keeps every digit it was written with, so
+/// `12.30` stays `12.30` rather than becoming the nearest binary fraction
+/// to 12.3. Trailing zeros count: `12.30` and `12.3` are different
+/// decimals, as they are in CBOR decimal fractions (tag 4).
+///
+/// YAY writes a decimal as a float literal with its exact digits.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Decimal {
+    mantissa: BigInt,
+    exponent: i64,
+}
+
+/// Beyond this many fractional digits, a decimal is written with an
+/// exponent rather than a run of zeros.
+const MAX_POSITIONAL_DIGITS: u64 = 100;
+
+impl Decimal {
+    pub fn new(mantissa: BigInt, exponent: i64) -> Decimal {
+        Decimal { mantissa, exponent }
+    }
+
+    /// Read a number like `-12.30`, `.5`, or `1.5e10`, or `None` if the
+    /// text is not one. Spaces between digits are ignored, as in YAY.
+    ///
+    /// ```
+    /// use libyay::Decimal;
+    ///
+    /// let d = Decimal::parse("12.30").unwrap();
+    /// assert_eq!((d.mantissa().to_string(), d.exponent()), ("1230".into(), -2));
+    /// assert_eq!(d.to_string(), "12.30");
+    /// ```
+    pub fn parse(text: &str) -> Option<Decimal> {
+        let text: String = text.chars().filter(|c| *c != ' ').collect();
+        let (number, exponent) = match text.split_once('e') {
+            Some((number, exponent)) => {
+                let exponent = exponent.strip_prefix('+').unwrap_or(exponent);
+                if exponent.is_empty() || exponent.starts_with('+') {
+                    return None;
+                }
+                (number, exponent.parse::<i64>().ok()?)
+            }
+            None => (text.as_str(), 0),
+        };
+        let (sign, digits) = match number.strip_prefix('-') {
+            Some(digits) => ("-", digits),
+            None => ("", number),
+        };
+        let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+        if whole.is_empty() && fraction.is_empty()
+            || !whole
+                .bytes()
+                .chain(fraction.bytes())
+                .all(|b| b.is_ascii_digit())
+        {
+            return None;
+        }
+        let mantissa = format!("{}{}{}", sign, whole, fraction).parse().ok()?;
+        let exponent = exponent.checked_sub(i64::try_from(fraction.len()).ok()?)?;
+        Some(Decimal { mantissa, exponent })
+    }
+
+    pub fn mantissa(&self) -> &BigInt {
+        &self.mantissa
+    }
+
+    pub fn exponent(&self) -> i64 {
+        self.exponent
+    }
+
+    /// The nearest float, which may be infinite or zero if the exponent is
+    /// beyond a float's range.
+    pub fn to_f64(&self) -> f64 {
+        format!("{}e{}", self.mantissa, self.exponent)
+            .parse()
+            .unwrap_or(f64::NAN)
+    }
+}
+
+/// Positional notation with exactly as many fractional digits as the
+/// exponent calls for, like `12.30`, or `15e9` for a positive exponent, so
+/// that [`Decimal::parse`] reads back the same mantissa and exponent.
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.exponent >= 0 || self.exponent.unsigned_abs() > MAX_POSITIONAL_DIGITS {
+            return write!(f, "{}e{}", self.mantissa, self.exponent);
+        }
+        let scale = self.exponent.unsigned_abs() as usize;
+        let digits = self.mantissa.abs().to_string();
+        let digits = format!("{:0>width$}", digits, width = scale + 1);
+        let (whole, fraction) = digits.split_at(digits.len() - scale);
+        let sign = if self.mantissa.is_negative() { "-" } else { "" };
+        write!(f, "{}{}.{}", sign, whole, fraction)
+    }
+}
+
+/// Decimals order by value, and decimals of the same value, like `1.0`
+/// and `1.00`, by exponent, largest first.
+///
+/// ```
+/// use libyay::Decimal;
+///
+/// let mut decimals: Vec<Decimal> = ["2.5", "-1", "1.00", "1e1", "1.0"]
+///     .iter()
+///     .map(|s| s.parse().unwrap())
+///     .collect();
+/// decimals.sort();
+/// let sorted: Vec<String> = decimals.iter().map(Decimal::to_string).collect();
+/// assert_eq!(sorted, ["-1e0", "1.0", "1.00", "2.5", "1e1"]);
+/// ```
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.cmp_value(other)
+            .then_with(|| other.exponent.cmp(&self.exponent))
+    }
+}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Decimal {
+    /// Compare values, without scaling a mantissa by more than the
+    /// difference in the number of digits.
+    fn cmp_value(&self, other: &Self) -> Ordering {
+        let sign = self.mantissa.sign().cmp(&other.mantissa.sign());
+        if sign != Ordering::Equal || self.mantissa.is_zero() {
+            return sign;
+        }
+        // Compare the positions of the leading digits first.
+        let lead = |d: &Decimal| d.mantissa.abs().to_string().len() as i128 + d.exponent as i128;
+        let magnitude = lead(self).cmp(&lead(other)).then_with(|| {
+            let (a, b) = (self.mantissa.abs(), other.mantissa.abs());
+            // The leading digits are in the same place, so the exponents
+            // differ by less than the digits of a mantissa.
+            let shift = self.exponent.abs_diff(other.exponent) as usize;
+            let scale = BigInt::from(10u8).pow(shift as u32);
+            match self.exponent.cmp(&other.exponent) {
+                Ordering::Greater => (a * scale).cmp(&b),
+                _ => a.cmp(&(b * scale)),
+            }
+        });
+        if self.mantissa.is_negative() {
+            magnitude.reverse()
+        } else {
+            magnitude
+        }
+    }
+}
+
+impl FromStr for Decimal {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Decimal::parse(s).ok_or_else(|| format!("Invalid decimal: {:?}", s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{encode, parse_with_options, Format, ParseOptions, Value};
+
+    #[test]
+    fn test_parse_and_display() {
+        for (text, mantissa, exponent, display) in [
+            ("12.30", 1230, -2, "12.30"),
+            ("-0.05", -5, -2, "-0.05"),
+            (".5", 5, -1, "0.5"),
+            ("1.", 1, 0, "1e0"),
+            ("1.5e10", 15, 9, "15e9"),
+            ("2e-3", 2, -3, "0.002"),
+            ("1 000.25", 100025, -2, "1000.25"),
+        ] {
+            let d = Decimal::parse(text).unwrap();
+            assert_eq!(d, Decimal::new(mantissa.into(), exponent), "{}", text);
+            assert_eq!(d.to_string(), display);
+            assert_eq!(Decimal::parse(display), Some(d));
+        }
+        assert_eq!(Decimal::new(7.into(), -200).to_string(), "7e-200");
+        for text in ["", ".", "-", "1e", "1e++2", "1.2.3", "0x10", "nan"] {
+            assert_eq!(Decimal::parse(text), None, "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_to_f64() {
+        assert_eq!(Decimal::parse("12.30").unwrap().to_f64(), 12.3);
+        assert_eq!(Decimal::new(1.into(), 400).to_f64(), f64::INFINITY);
+    }
+
+    #[test]
+    fn test_parse_option() {
+        let options = ParseOptions {
+            decimals: true,
+            hex_floats: true,
+            ..ParseOptions::default()
+        };
+        let source = "price: 12.30\nrates:\n  - 0.10\n  - 1e3\n  - 0x1p3\n  - infinity\ncount: 7\n";
+        let value = parse_with_options(source, &options).unwrap();
+        let decimal = |text: &str| Value::Decimal(text.parse().unwrap());
+        assert_eq!(value["price"], decimal("12.30"));
+        assert_eq!(
+            value["rates"],
+            Value::Array(vec![
+                decimal("0.10"),
+                decimal("1e3"),
+                Value::Float(8.0),
+                Value::Float(f64::INFINITY),
+            ])
+        );
+        assert_eq!(value["count"], Value::from(7));
+        let inline = parse_with_options("[0.10, 2.50]", &options).unwrap();
+        assert_eq!(inline, Value::Array(vec![decimal("0.10"), decimal("2.50")]));
+
+        let yay = encode(&value, Format::Yay);
+        assert!(yay.contains("price: 12.30"), "{}", yay);
+        assert_eq!(
+            parse_with_options(&yay, &options).unwrap()["price"],
+            value["price"]
+        );
+        assert_eq!(encode(&value["rates"][0], Format::Json), "0.10");
+    }
+}
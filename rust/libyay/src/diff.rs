@@ -0,0 +1,180 @@
+//! Structural differences between values.
+
+use std::fmt;
+
+use crate::encode::encode_yay_inline;
+use crate::Value;
+
+/// One difference between two values, at a path like `servers[0].port`.
+/// The root's path is the empty string.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum Change {
+    /// An object member or array item only the new value has.
+    Added { path: String, value: Value },
+    /// An object member or array item only the old value has.
+    Removed { path: String, value: Value },
+    /// A value that differs, or is of another type, in the new value.
+    Changed {
+        path: String,
+        old: Value,
+        new: Value,
+    },
+}
+
+impl Change {
+    /// The path of the value that changed.
+    pub fn path(&self) -> &str {
+        match self {
+            Change::Added { path, .. }
+            | Change::Removed { path, .. }
+            | Change::Changed { path, .. } => path,
+        }
+    }
+}
+
+/// One line: `+ path: value`, `- path: value`, or `~ path: old -> new`,
+/// with values in inline YAY.
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = match self.path() {
+            "" => "(root)",
+            path => path,
+        };
+        match self {
+            Change::Added { value, .. } => {
+                write!(f, "+ {}: {}", path, encode_yay_inline(value))
+            }
+            Change::Removed { value, .. } => {
+                write!(f, "- {}: {}", path, encode_yay_inline(value))
+            }
+            Change::Changed { old, new, .. } => write!(
+                f,
+                "~ {}: {} -> {}",
+                path,
+                encode_yay_inline(old),
+                encode_yay_inline(new)
+            ),
+        }
+    }
+}
+
+/// The changes that turn `old` into `new`, in the order of their paths in
+/// `old` and then `new`.
+///
+/// Objects compare member by member, whatever their order. Arrays compare
+/// item by item, so an item inserted in the middle of an array changes
+/// every item after it. Values of different types, like `1` and `1.0`,
+/// are a change.
+///
+/// # Example
+///
+/// ```
+/// use libyay::{diff, parse};
+///
+/// let old = parse("name: \"web\"\nports: [80]\ndebug: true").unwrap();
+/// let new = parse("name: \"web\"\nports: [80, 443]\nreplicas: 3").unwrap();
+/// let changes: Vec<String> = diff(&old, &new).iter().map(|c| c.to_string()).collect();
+/// assert_eq!(changes, ["+ ports[1]: 443", "- debug: true", "+ replicas: 3"]);
+/// ```
+pub fn diff(old: &Value, new: &Value) -> Vec<Change> {
+    let mut changes = Vec::new();
+    collect(old, new, &mut String::new(), &mut changes);
+    changes
+}
+
+fn collect(old: &Value, new: &Value, path: &mut String, changes: &mut Vec<Change>) {
+    let len = path.len();
+    match (old, new) {
+        (Value::Object(old_members), Value::Object(new_members)) => {
+            for (key, old_value) in old_members {
+                push_key(path, key);
+                match new_members.get(key) {
+                    Some(new_value) => collect(old_value, new_value, path, changes),
+                    None => changes.push(Change::Removed {
+                        path: path.clone(),
+                        value: old_value.clone(),
+                    }),
+                }
+                path.truncate(len);
+            }
+            for (key, new_value) in new_members {
+                if !old_members.contains_key(key) {
+                    push_key(path, key);
+                    changes.push(Change::Added {
+                        path: path.clone(),
+                        value: new_value.clone(),
+                    });
+                    path.truncate(len);
+                }
+            }
+        }
+        (Value::Array(old_items), Value::Array(new_items)) => {
+            for i in 0..old_items.len().max(new_items.len()) {
+                path.push_str(&format!("[{}]", i));
+                match (old_items.get(i), new_items.get(i)) {
+                    (Some(old_item), Some(new_item)) => collect(old_item, new_item, path, changes),
+                    (Some(old_item), None) => changes.push(Change::Removed {
+                        path: path.clone(),
+                        value: old_item.clone(),
+                    }),
+                    (None, Some(new_item)) => changes.push(Change::Added {
+                        path: path.clone(),
+                        value: new_item.clone(),
+                    }),
+                    (None, None) => {}
+                }
+                path.truncate(len);
+            }
+        }
+        _ if old == new => {}
+        _ => changes.push(Change::Changed {
+            path: path.clone(),
+            old: old.clone(),
+            new: new.clone(),
+        }),
+    }
+}
+
+fn push_key(path: &mut String, key: &str) {
+    if !path.is_empty() {
+        path.push('.');
+    }
+    path.push_str(key);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    fn changes(old: &str, new: &str) -> Vec<String> {
+        diff(&parse(old).unwrap(), &parse(new).unwrap())
+            .iter()
+            .map(Change::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_diff() {
+        assert_eq!(
+            changes(
+                "a:\n  b: 1\n  c: [1, 2, 3]\nd: \"x\"\ne: nan\nf: 0.0",
+                "d: \"y\"\ne: nan\nf: -0.0\na:\n  c: [1, 4]\n  b: 1.0\ng: {h: null}"
+            ),
+            [
+                "~ a.b: 1 -> 1.0",
+                "~ a.c[1]: 2 -> 4",
+                "- a.c[2]: 3",
+                "~ d: \"x\" -> \"y\"",
+                "~ f: 0.0 -> -0.0",
+                "+ g: {h: null}",
+            ]
+        );
+        assert_eq!(changes("[1]", "{a: 1}"), ["~ (root): [1] -> {a: 1}"]);
+        assert!(changes("a: [1, {b: 2}]", "a: [1, {b: 2}]").is_empty());
+
+        let change = &diff(&parse("a: 1").unwrap(), &parse("a: 2").unwrap())[0];
+        assert_eq!(change.path(), "a");
+    }
+}
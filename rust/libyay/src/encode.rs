@@ -3,11 +3,25 @@
 //! This module provides functions to convert YAY values into source code
 //! literals for various programming languages, as well as YSON format.
 
-use crate::Value;
-use std::collections::HashMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::io::BufWriter;
+
+#[cfg(feature = "std")]
+use crate::cancel::Cancellable;
+use crate::float::{format_float, positional, FloatFormat};
+use crate::io::{self, Write};
+use crate::metrics::{self, Metric};
+#[cfg(feature = "std")]
+use crate::CancellationToken;
+use crate::{KeyOrder, Map, Value, YAY_VERSION};
 
 /// Output format for encoding.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum Format {
     /// YAY format (canonical)
     Yay,
@@ -37,6 +51,33 @@ pub enum Format {
     Cbor,
     /// CBOR diagnostic notation (RFC 8949 §8)
     CborDiag,
+    /// Newline-delimited YAY: each item of a root array as a document on
+    /// one line, read back by [`Documents`](crate::Documents)
+    Ndyay,
+}
+
+impl Format {
+    /// The usual file extension for the format, without a dot: `yay`,
+    /// `json`, `py`, and so on.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Format::Yay => "yay",
+            Format::Json => "json",
+            Format::Yson => "yson",
+            Format::Ndyay => "ndyay",
+            Format::JavaScript => "js",
+            Format::Go => "go",
+            Format::Python => "py",
+            Format::Rust => "rs",
+            Format::C => "c",
+            Format::Java => "java",
+            Format::Scheme => "scm",
+            Format::Yaml => "yaml",
+            Format::Toml => "toml",
+            Format::Cbor => "cbor",
+            Format::CborDiag => "diag",
+        }
+    }
 }
 
 /// Encode a YAY value to a string in the specified format.
@@ -45,17 +86,47 @@ pub enum Format {
 /// (binyay) using dedicated libraries. Calling this function with those formats
 /// will panic.
 pub fn encode(value: &Value, format: Format) -> String {
+    encode_ordered(value, format, KeyOrder::Bytes)
+}
+
+/// Encode like [`encode`], writing the keys of each object in `keys`
+/// order. [`KeyOrder::Document`] keeps the order the keys were parsed or
+/// inserted in, for generated files that people review.
+///
+/// ```
+/// use libyay::{encode_ordered, parse, Format, KeyOrder};
+///
+/// let value = parse("name: \"web\"\nport: 80\nhost: \"a\"").unwrap();
+/// assert_eq!(
+///     encode_ordered(&value, Format::Json, KeyOrder::Document),
+///     "{\n  \"name\": \"web\",\n  \"port\": 80,\n  \"host\": \"a\"\n}"
+/// );
+/// ```
+pub fn encode_ordered(value: &Value, format: Format, keys: KeyOrder) -> String {
+    metrics::record(Metric::Encoded { format });
+    encode_uncounted(value, format, keys)
+}
+
+/// Encode like [`encode_ordered`], without reporting it to [`metrics`].
+fn encode_uncounted(value: &Value, format: Format, order: KeyOrder) -> String {
     match format {
-        Format::Yay => encode_yay(value, 0),
-        Format::JavaScript => encode_js(value, 0),
-        Format::Go => encode_go(value, 0),
-        Format::Python => encode_python(value, 0),
-        Format::Rust => encode_rust(value, 0),
-        Format::C => encode_c(value),
-        Format::Java => encode_java(value, 0),
-        Format::Scheme => encode_scheme(value),
-        Format::Json => encode_json(value, 0),
-        Format::Yson => encode_yson(value, 0),
+        Format::Yay => {
+            let options = EncodeOptions {
+                key_order: order,
+                ..EncodeOptions::default()
+            };
+            to_string(|out| write_yay_value(out, value, 0, 0, &options))
+        }
+        Format::JavaScript => encode_js(value, 0, order),
+        Format::Go => encode_go(value, 0, order),
+        Format::Python => encode_python(value, 0, order),
+        Format::Rust => encode_rust(value, 0, order),
+        Format::C => encode_c(value, order),
+        Format::Java => encode_java(value, 0, order),
+        Format::Scheme => encode_scheme(value, order),
+        Format::Json => encode_json(value, 0, order),
+        Format::Yson => encode_yson(value, 0, order),
+        Format::Ndyay => encode_ndyay(value, order),
         Format::Yaml | Format::Toml | Format::Cbor | Format::CborDiag => {
             panic!(
                 "Format {:?} must be handled by the CLI tool, not libyay::encode",
@@ -65,89 +136,501 @@ pub fn encode(value: &Value, format: Format) -> String {
     }
 }
 
+/// Write a YAY value to `out` in the specified format, the same text
+/// [`encode`] returns.
+///
+/// YAY, JSON, and YSON are written a piece at a time, so the output is
+/// never held whole in memory, and byte arrays are written as hex a few
+/// kilobytes at a time. The other formats are encoded whole and then
+/// written. Like [`encode`], this panics for YAML, TOML, and CBOR.
+///
+/// ```
+/// use libyay::{encode, encode_to, Format, Value};
+///
+/// let value = Value::Bytes(vec![0xca; 10_000]);
+/// let mut out = Vec::new();
+/// encode_to(&mut out, &value, Format::Yay).unwrap();
+/// assert_eq!(out, encode(&value, Format::Yay).into_bytes());
+/// ```
+#[cfg(feature = "std")]
+pub fn encode_to(out: &mut impl Write, value: &Value, format: Format) -> io::Result<()> {
+    metrics::record(Metric::Encoded { format });
+    let mut out = BufWriter::new(out);
+    match format {
+        Format::Yay => write_yay_value(&mut out, value, 0, 0, &EncodeOptions::default())?,
+        Format::Json => write_json(&mut out, value, 0, KeyOrder::Bytes)?,
+        Format::Yson => write_yson(&mut out, value, 0, KeyOrder::Bytes)?,
+        _ => out.write_all(encode_uncounted(value, format, KeyOrder::Bytes).as_bytes())?,
+    }
+    out.flush()
+}
+
+/// Write a value like [`encode_to`], but stop with an error once `cancel`
+/// is cancelled.
+///
+/// YAY, JSON, and YSON check the token every few kilobytes as they are
+/// written; the other formats are encoded whole before they are written,
+/// and check it only then.
+///
+/// ```
+/// use libyay::{encode_to_cancellable, CancellationToken, Format, Value};
+///
+/// let value = Value::Array(vec![Value::from("item"); 100_000]);
+/// let token = CancellationToken::new();
+/// token.cancel();
+/// let error = encode_to_cancellable(&mut Vec::new(), &value, Format::Json, &token);
+/// assert_eq!(error.unwrap_err().to_string(), "Encoding cancelled");
+/// ```
+#[cfg(feature = "std")]
+pub fn encode_to_cancellable(
+    out: &mut impl Write,
+    value: &Value,
+    format: Format,
+    cancel: &CancellationToken,
+) -> io::Result<()> {
+    let mut out = Cancellable { out, token: cancel };
+    encode_to(&mut out, value, format)
+}
+
+/// Collect what `write` writes, for the encoders that return a string.
+fn to_string(write: impl FnOnce(&mut dyn Write) -> io::Result<()>) -> String {
+    let mut out = Vec::new();
+    write(&mut out).expect("writing to a Vec cannot fail");
+    String::from_utf8(out).expect("encoders write UTF-8")
+}
+
+/// Encode like [`encode`], but write each [`Decimal`](crate::Decimal) as a
+/// string of its exact digits, for readers of JSON and other formats that
+/// would round a number to the nearest float.
+///
+/// ```
+/// use libyay::{encode, encode_decimals_as_strings, Format, Value};
+///
+/// let price = Value::Decimal("12.30".parse().unwrap());
+/// assert_eq!(encode(&price, Format::Json), "12.30");
+/// assert_eq!(encode_decimals_as_strings(&price, Format::Json), "\"12.30\"");
+/// ```
+pub fn encode_decimals_as_strings(value: &Value, format: Format) -> String {
+    encode(&decimals_to_strings(value), format)
+}
+
+fn decimals_to_strings(value: &Value) -> Value {
+    match value {
+        Value::Decimal(d) => Value::String(d.to_string()),
+        Value::Array(arr) => Value::Array(arr.iter().map(decimals_to_strings).collect()),
+        Value::Object(obj) => Value::Object(
+            obj.iter()
+                .map(|(k, v)| (k.clone(), decimals_to_strings(v)))
+                .collect(),
+        ),
+        _ => value.clone(),
+    }
+}
+
 // =============================================================================
 // YAY Encoder
 // =============================================================================
 
-fn encode_yay(value: &Value, indent: usize) -> String {
-    let pad = "  ".repeat(indent);
+/// How the YAY encoder lays out a document, for
+/// [`encode_with_options`].
+///
+/// The default is the canonical style [`encode`] writes with
+/// [`Format::Yay`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct EncodeOptions {
+    /// Spaces for each level of nesting. YAY documents use two, the
+    /// default; other widths read back but are not canonical.
+    pub indent: usize,
+    /// The column an inline array or object may not pass. One that would
+    /// is written in block form instead. `None`, the default, sets no
+    /// limit; strings and other scalars are never broken.
+    pub wrap_column: Option<usize>,
+    /// Whether to sort the keys of each object in `key_order`, as by
+    /// default, or keep them in the order of the object's members.
+    pub sort_keys: bool,
+    /// The order of keys when `sort_keys` is set.
+    pub key_order: KeyOrder,
+    /// The most items an inline array may have. Longer arrays, and arrays
+    /// holding arrays, objects, or long byte arrays, are written in block
+    /// form. The default is 5.
+    pub inline_array_items: usize,
+    /// The most members an inline object may have. The default is 3.
+    pub inline_object_members: usize,
+    /// Which quotes to put around strings and keys that need them.
+    pub quotes: Quotes,
+    /// How to write floats.
+    pub floats: FloatFormat,
+    /// Whether to begin the document with a `%yay 1` line declaring the
+    /// version of YAY it is written in. Off by default, since readers need
+    /// [`ParseOptions::version_directive`](crate::ParseOptions) to read it.
+    pub version_directive: bool,
+}
+
+impl Default for EncodeOptions {
+    fn default() -> Self {
+        EncodeOptions {
+            indent: 2,
+            wrap_column: None,
+            sort_keys: true,
+            key_order: KeyOrder::default(),
+            inline_array_items: 5,
+            inline_object_members: 3,
+            quotes: Quotes::default(),
+            floats: FloatFormat::default(),
+            version_directive: false,
+        }
+    }
+}
+
+impl From<&FloatFormat> for EncodeOptions {
+    fn from(floats: &FloatFormat) -> Self {
+        EncodeOptions {
+            floats: *floats,
+            ..EncodeOptions::default()
+        }
+    }
+}
 
+/// Which quotes [`EncodeOptions`] puts around strings.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Quotes {
+    /// Double quotes, with escapes: `"it's"`.
+    #[default]
+    Double,
+    /// Single quotes, which take no escapes, for strings without a single
+    /// quote, a backslash, or a control character, and double quotes for
+    /// the rest: `'a/b'`, `"it's"`.
+    Single,
+}
+
+impl EncodeOptions {
+    /// The keys of `obj` in the order they are written.
+    fn keys<'a>(&self, obj: &'a Map) -> Vec<&'a String> {
+        let mut keys: Vec<&String> = obj.keys().collect();
+        if self.sort_keys {
+            self.key_order.sort(&mut keys);
+        }
+        keys
+    }
+
+    /// Whether `v`, starting at column `start`, is written on one line.
+    fn inline(&self, v: &Value, start: usize) -> bool {
+        let fits = match v {
+            Value::Array(arr) if arr.is_empty() => return true,
+            Value::Object(obj) if obj.is_empty() => return true,
+            Value::Array(arr) => {
+                arr.len() <= self.inline_array_items && arr.iter().all(is_simple_value)
+            }
+            Value::Object(obj) => {
+                obj.len() <= self.inline_object_members && obj.values().all(is_simple_value)
+            }
+            _ => return true,
+        };
+        fits && self.wrap_column.is_none_or(|wrap| {
+            let text = to_string(|out| write_yay_inline(out, v, self));
+            start + text.chars().count() <= wrap
+        })
+    }
+
+    fn pad(&self, col: usize) -> String {
+        " ".repeat(col)
+    }
+
+    fn string(&self, s: &str) -> String {
+        match self.quotes {
+            Quotes::Single if !s.chars().any(|c| c == '\'' || c == '\\' || c.is_control()) => {
+                format!("'{}'", s)
+            }
+            _ => encode_yay_string(s),
+        }
+    }
+
+    fn key(&self, key: &str) -> String {
+        if is_bare_key(key) {
+            key.to_string()
+        } else {
+            self.string(key)
+        }
+    }
+}
+
+/// Encode a value as YAY in the style `options` describe.
+///
+/// ```
+/// use libyay::{encode_with_options, parse, EncodeOptions, Quotes};
+///
+/// let value = parse("{name: \"web\", ports: [80, 443]}").unwrap();
+/// let mut options = EncodeOptions::default();
+/// options.indent = 4;
+/// options.inline_array_items = 1;
+/// options.quotes = Quotes::Single;
+/// assert_eq!(
+///     encode_with_options(&value, &options),
+///     "name: 'web'\nports:\n    - 80\n    - 443"
+/// );
+/// ```
+pub fn encode_with_options(value: &Value, options: &EncodeOptions) -> String {
+    metrics::record(Metric::Encoded {
+        format: Format::Yay,
+    });
+    to_string(|out| {
+        if options.version_directive {
+            writeln!(out, "%yay {}", YAY_VERSION)?;
+        }
+        write_yay_value(out, value, 0, 0, options)
+    })
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn encode_yay(value: &Value, indent: usize) -> String {
+    encode_yay_value(value, indent, &FloatFormat::default())
+}
+
+/// Encode a value as YAY, writing floats according to `floats`.
+///
+/// [`encode`] with [`Format::Yay`] uses the default [`FloatFormat`]; set
+/// `hex_above_digits` to write floats with long decimal spellings in
+/// hexadecimal instead.
+pub fn encode_yay_with(value: &Value, floats: &FloatFormat) -> String {
+    encode_yay_value(value, 0, floats)
+}
+
+/// Encode a value as YAY like [`encode_yay_with`], writing the keys of
+/// each object in `keys` order.
+///
+/// ```
+/// use libyay::{encode_yay_ordered, parse, FloatFormat, KeyOrder};
+///
+/// let value = parse("{Zoe: 1, émile: 2, adam: 3}").unwrap();
+/// let floats = FloatFormat::default();
+/// assert_eq!(
+///     encode_yay_ordered(&value, &floats, KeyOrder::Bytes),
+///     "{Zoe: 1, adam: 3, émile: 2}"
+/// );
+/// assert_eq!(
+///     encode_yay_ordered(&value, &floats, KeyOrder::Collated),
+///     "{adam: 3, émile: 2, Zoe: 1}"
+/// );
+/// ```
+pub fn encode_yay_ordered(value: &Value, floats: &FloatFormat, keys: KeyOrder) -> String {
+    let options = EncodeOptions {
+        floats: *floats,
+        key_order: keys,
+        ..EncodeOptions::default()
+    };
+    encode_with_options(value, &options)
+}
+
+/// Encode each item of a root array, or any other value, as a YAY document
+/// on a line of its own.
+fn encode_ndyay(value: &Value, order: KeyOrder) -> String {
+    let style = EncodeOptions {
+        key_order: order,
+        ..EncodeOptions::default()
+    };
+    let records = match value {
+        Value::Array(items) => items.as_slice(),
+        _ => core::slice::from_ref(value),
+    };
+    to_string(|out| {
+        for (i, record) in records.iter().enumerate() {
+            if i > 0 {
+                out.write_all(b"\n")?;
+            }
+            write_yay_inline(out, record, &style)?;
+        }
+        Ok(())
+    })
+}
+
+#[cfg(feature = "std")]
+/// Encode a value on one line, as an inline value in a longer line.
+pub(crate) fn encode_yay_inline(value: &Value) -> String {
+    to_string(|out| write_yay_inline(out, value, &EncodeOptions::default()))
+}
+
+/// Write a value on one line, with its arrays and objects inline however
+/// many items they have.
+fn write_yay_inline(out: &mut dyn Write, value: &Value, style: &EncodeOptions) -> io::Result<()> {
     match value {
-        Value::Null => "null".to_string(),
-        Value::Bool(true) => "true".to_string(),
-        Value::Bool(false) => "false".to_string(),
-        Value::Integer(n) => n.to_string(),
-        Value::Float(f) => {
-            if f.is_nan() {
-                "nan".to_string()
-            } else if f.is_infinite() {
-                if *f > 0.0 {
-                    "infinity".to_string()
-                } else {
-                    "-infinity".to_string()
+        Value::Array(arr) => {
+            out.write_all(b"[")?;
+            for (i, v) in arr.iter().enumerate() {
+                if i > 0 {
+                    out.write_all(b", ")?;
                 }
-            } else if *f == 0.0 && f.is_sign_negative() {
-                "-0.0".to_string()
-            } else {
-                let s = format!("{}", f);
-                if s.contains('.') || s.contains('e') {
-                    s
-                } else {
-                    format!("{}.0", s)
+                write_yay_inline(out, v, style)?;
+            }
+            out.write_all(b"]")
+        }
+        Value::Object(obj) => {
+            out.write_all(b"{")?;
+            for (i, k) in style.keys(obj).iter().enumerate() {
+                if i > 0 {
+                    out.write_all(b", ")?;
                 }
+                write!(out, "{}: ", style.key(k))?;
+                write_yay_inline(out, &obj[*k], style)?;
             }
+            out.write_all(b"}")
         }
-        Value::String(s) => encode_yay_string(s),
-        Value::Bytes(b) => encode_yay_bytes(b),
-        Value::Array(arr) => {
-            if arr.is_empty() {
-                "[]".to_string()
-            } else if can_inline_array(arr) {
-                let items: Vec<String> = arr.iter().map(|v| encode_yay(v, 0)).collect();
-                format!("[{}]", items.join(", "))
-            } else {
-                encode_yay_multiline_array(arr, indent)
+        Value::Bytes(b) => write_inline_bytes(out, b),
+        _ => write_yay_value(out, value, 0, 0, style),
+    }
+}
+
+/// Encodes a top-level YAY array one item at a time, producing the same text
+/// [`encode`] would for the whole array, so long arrays can be written
+/// without holding them in memory.
+///
+/// Up to five scalar items are held back, since such an array is written
+/// inline; after that every item is written as soon as it is pushed.
+pub struct ArrayEncoder {
+    pending: Vec<Value>,
+    written: usize,
+    floats: FloatFormat,
+}
+
+impl ArrayEncoder {
+    pub fn new(floats: FloatFormat) -> Self {
+        ArrayEncoder {
+            pending: Vec::new(),
+            written: 0,
+            floats,
+        }
+    }
+
+    /// Continue a block array of which `written` items have already been
+    /// output.
+    pub fn resume(written: usize, floats: FloatFormat) -> Self {
+        ArrayEncoder {
+            pending: Vec::new(),
+            written,
+            floats,
+        }
+    }
+
+    /// The number of items output so far. Once nonzero, the array is in
+    /// block form and everything pushed is output immediately.
+    pub fn written(&self) -> usize {
+        self.written
+    }
+
+    /// Add an item, returning the text to append to the output, if any.
+    pub fn push(&mut self, item: Value) -> String {
+        self.pending.push(item);
+        if self.written == 0 && can_inline_array(&self.pending) {
+            return String::new();
+        }
+        let mut out = String::new();
+        for item in self.pending.drain(..) {
+            if self.written > 0 {
+                out.push('\n');
             }
+            out.push_str("- ");
+            out.push_str(&encode_yay_array_item(&item, 0, &self.floats));
+            self.written += 1;
+        }
+        out
+    }
+
+    /// End the array, returning the rest of its text.
+    pub fn finish(self) -> String {
+        if self.written > 0 {
+            String::new()
+        } else {
+            encode_yay_value(&Value::Array(self.pending), 0, &self.floats)
+        }
+    }
+}
+
+pub(crate) fn encode_yay_value(value: &Value, indent: usize, floats: &FloatFormat) -> String {
+    let col = indent * 2;
+    to_string(|out| write_yay_value(out, value, col, col, &floats.into()))
+}
+
+/// Write a value that starts at column `start`, with the lines of a block
+/// object at column `col`.
+fn write_yay_value(
+    out: &mut dyn Write,
+    value: &Value,
+    col: usize,
+    start: usize,
+    style: &EncodeOptions,
+) -> io::Result<()> {
+    match value {
+        Value::Null => out.write_all(b"null"),
+        Value::Bool(true) => out.write_all(b"true"),
+        Value::Bool(false) => out.write_all(b"false"),
+        Value::Integer(n) => write!(out, "{}", n),
+        Value::Float(f) => out.write_all(format_float(*f, &style.floats).as_bytes()),
+        Value::String(s) => out.write_all(style.string(s).as_bytes()),
+        Value::Decimal(d) => write!(out, "{}", d),
+        Value::DateTime(dt) => out.write_all(style.string(dt.as_str()).as_bytes()),
+        Value::Bytes(b) if is_block_bytes(b) => {
+            // The first line follows the `>`, and the rest are indented
+            // under it, past the column of a root value.
+            let (first, rest) = b.split_at(BLOCK_BYTES_PER_LINE);
+            out.write_all(b"> ")?;
+            write_hex_line(out, first)?;
+            write_hex_lines(out, rest, col.max(style.indent))
         }
+        Value::Bytes(b) => write_inline_bytes(out, b),
+        Value::Array(arr) if arr.is_empty() => out.write_all(b"[]"),
+        Value::Object(obj) if obj.is_empty() => out.write_all(b"{}"),
+        _ if style.inline(value, start) => write_yay_inline(out, value, style),
+        Value::Array(arr) => write_yay_multiline_array(out, arr, col, style),
         Value::Object(obj) => {
-            if obj.is_empty() {
-                "{}".to_string()
-            } else if can_inline_object(obj) {
-                let mut keys: Vec<&String> = obj.keys().collect();
-                keys.sort();
-                let items: Vec<String> = keys
-                    .iter()
-                    .map(|k| format!("{}: {}", encode_yay_key(k), encode_yay(&obj[*k], 0)))
-                    .collect();
-                format!("{{{}}}", items.join(", "))
-            } else {
-                let mut keys: Vec<&String> = obj.keys().collect();
-                keys.sort();
-                let items: Vec<String> = keys
-                    .iter()
-                    .map(|k| {
-                        let v = &obj[*k];
-                        if is_block_value(v) {
-                            // Nested block value: put on next line
-                            format!(
-                                "{}{}:\n{}",
-                                pad,
-                                encode_yay_key(k),
-                                encode_yay(v, indent + 1)
-                            )
-                        } else {
-                            format!(
-                                "{}{}: {}",
-                                pad,
-                                encode_yay_key(k),
-                                encode_yay(v, indent + 1)
-                            )
-                        }
-                    })
-                    .collect();
-                items.join("\n")
-            }
+            out.write_all(style.pad(col).as_bytes())?;
+            write_yay_block_object(out, obj, col, style)
+        }
+    }
+}
+
+/// Write the members of a block object at column `col`, without the
+/// indent of the first.
+fn write_yay_block_object(
+    out: &mut dyn Write,
+    obj: &Map,
+    col: usize,
+    style: &EncodeOptions,
+) -> io::Result<()> {
+    let pad = style.pad(col);
+    for (i, k) in style.keys(obj).iter().enumerate() {
+        if i > 0 {
+            write!(out, "\n{}", pad)?;
         }
+        write_yay_property(out, k, &obj[*k], col, style)?;
+    }
+    Ok(())
+}
+
+#[cfg(feature = "std")]
+/// Encode a property of a block object at `indent`, without the indent of
+/// its first line.
+pub(crate) fn encode_yay_property(key: &str, v: &Value, indent: usize) -> String {
+    to_string(|out| write_yay_property(out, key, v, indent * 2, &EncodeOptions::default()))
+}
+
+/// Write a property whose key is at column `col`.
+fn write_yay_property(
+    out: &mut dyn Write,
+    key: &str,
+    v: &Value,
+    col: usize,
+    style: &EncodeOptions,
+) -> io::Result<()> {
+    let key = style.key(key);
+    let start = col + key.chars().count() + 2;
+    if style.inline(v, start) {
+        write!(out, "{}: ", key)?;
+        write_yay_member_value(out, v, col + style.indent, start, style)
+    } else {
+        // Nested block value: put on next line
+        writeln!(out, "{}:", key)?;
+        write_yay_block_member(out, v, col, style)
     }
 }
 
@@ -174,122 +657,247 @@ fn encode_yay_string(s: &str) -> String {
     result
 }
 
-fn encode_yay_bytes(bytes: &[u8]) -> String {
-    if bytes.is_empty() {
-        "<>".to_string()
-    } else {
-        let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
-        format!("<{}>", hex)
+/// The most bytes written inline as `<hex>`. Longer byte arrays are
+/// written as `>` blocks, as the MEH formatter writes them at its default
+/// line width.
+const INLINE_BYTES_MAX: usize = 24;
+
+/// Bytes on each line of a `>` block.
+const BLOCK_BYTES_PER_LINE: usize = 16;
+
+/// Whether a byte array is written as a `>` block.
+fn is_block_bytes(bytes: &[u8]) -> bool {
+    bytes.len() > INLINE_BYTES_MAX
+}
+
+/// Write bytes as lowercase hex a piece at a time, so a large byte array
+/// is never held as hex text.
+fn write_hex(out: &mut dyn Write, bytes: &[u8]) -> io::Result<()> {
+    for chunk in bytes.chunks(4096) {
+        let hex: String = chunk.iter().map(|b| format!("{:02x}", b)).collect();
+        out.write_all(hex.as_bytes())?;
     }
+    Ok(())
+}
+
+/// Write bytes as `<hex>`, however long.
+fn write_inline_bytes(out: &mut dyn Write, bytes: &[u8]) -> io::Result<()> {
+    out.write_all(b"<")?;
+    write_hex(out, bytes)?;
+    out.write_all(b">")
 }
 
-fn encode_yay_key(key: &str) -> String {
-    // Check if key needs quoting
-    if key
+#[cfg(feature = "std")]
+pub(crate) fn encode_yay_inline_bytes(bytes: &[u8]) -> String {
+    to_string(|out| write_inline_bytes(out, bytes))
+}
+
+/// Write one line of a `>` block: hex in groups of four bytes.
+fn write_hex_line(out: &mut dyn Write, bytes: &[u8]) -> io::Result<()> {
+    let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    out.write_all(normalize_hex_spaces(&hex).as_bytes())
+}
+
+/// Hex digits in canonical spacing: lowercase, a space between bytes, and
+/// two between groups of four bytes.
+pub(crate) fn normalize_hex_spaces(s: &str) -> String {
+    // Remove all spaces, lowercase, then re-add with proper grouping
+    let hex: String = s
         .chars()
-        .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
-        && !key.is_empty()
-    {
+        .filter(|c| !c.is_whitespace())
+        .flat_map(|c| c.to_lowercase())
+        .collect();
+
+    if hex.is_empty() {
+        return String::new();
+    }
+
+    // Group into bytes (2 chars each), then words (4 bytes = 8 chars)
+    let mut result = String::new();
+    let chars: Vec<char> = hex.chars().collect();
+
+    for (i, chunk) in chars.chunks(2).enumerate() {
+        if i > 0 {
+            if i % 4 == 0 {
+                result.push_str("  "); // Double space between words
+            } else {
+                result.push(' '); // Single space between bytes
+            }
+        }
+        for &c in chunk {
+            result.push(c);
+        }
+    }
+
+    result
+}
+
+/// Write the lines of a `>` block at column `col`, each after a newline.
+fn write_hex_lines(out: &mut dyn Write, bytes: &[u8], col: usize) -> io::Result<()> {
+    let pad = " ".repeat(col);
+    for line in bytes.chunks(BLOCK_BYTES_PER_LINE) {
+        write!(out, "\n{}", pad)?;
+        write_hex_line(out, line)?;
+    }
+    Ok(())
+}
+
+#[cfg(feature = "std")]
+/// Encode a value that follows its key and `: ` on the same line, with
+/// any further lines at `indent`.
+pub(crate) fn encode_yay_member_value(v: &Value, indent: usize, floats: &FloatFormat) -> String {
+    let col = indent * 2;
+    to_string(|out| write_yay_member_value(out, v, col, col, &floats.into()))
+}
+
+/// Write a value that follows its key and `: ` at column `start`, with any
+/// further lines at column `col`.
+fn write_yay_member_value(
+    out: &mut dyn Write,
+    v: &Value,
+    col: usize,
+    start: usize,
+    style: &EncodeOptions,
+) -> io::Result<()> {
+    match v {
+        // A property's `>` ends its line, and every line of hex follows.
+        Value::Bytes(b) if is_block_bytes(b) => {
+            out.write_all(b">")?;
+            write_hex_lines(out, b, col)
+        }
+        _ => write_yay_value(out, v, col, start, style),
+    }
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn encode_yay_key(key: &str) -> String {
+    if is_bare_key(key) {
         key.to_string()
     } else {
         encode_yay_string(key)
     }
 }
 
-fn can_inline_array(arr: &[Value]) -> bool {
-    arr.len() <= 5 && arr.iter().all(is_simple_value)
+/// Whether a key can be written without quotes.
+fn is_bare_key(key: &str) -> bool {
+    // A leading `-` would read as a list item's bullet.
+    !key.is_empty()
+        && !key.starts_with('-')
+        && key
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
 }
 
-fn can_inline_object(obj: &HashMap<String, Value>) -> bool {
-    obj.len() <= 3 && obj.values().all(is_simple_value)
+fn can_inline_array(arr: &[Value]) -> bool {
+    let style = EncodeOptions::default();
+    arr.len() <= style.inline_array_items && arr.iter().all(is_simple_value)
 }
 
 fn is_simple_value(v: &Value) -> bool {
-    matches!(
-        v,
-        Value::Null
-            | Value::Bool(_)
-            | Value::Integer(_)
-            | Value::Float(_)
-            | Value::String(_)
-            | Value::Bytes(_)
-    )
-}
-
-fn is_block_value(v: &Value) -> bool {
     match v {
-        Value::Array(arr) => !can_inline_array(arr),
-        Value::Object(obj) => !can_inline_object(obj),
-        _ => false,
+        Value::Bytes(b) => !is_block_bytes(b),
+        _ => matches!(
+            v,
+            Value::Null
+                | Value::Bool(_)
+                | Value::Integer(_)
+                | Value::Float(_)
+                | Value::Decimal(_)
+                | Value::String(_)
+                | Value::DateTime(_)
+        ),
     }
 }
 
-fn encode_yay_multiline_array(arr: &[Value], indent: usize) -> String {
-    let pad = "  ".repeat(indent);
-    let mut result = Vec::new();
+#[cfg(feature = "std")]
+/// Encode a block value for the lines after its key, where the key is at
+/// `indent`. Both block arrays and block objects are indented under it.
+pub(crate) fn encode_yay_block_member(v: &Value, indent: usize, floats: &FloatFormat) -> String {
+    to_string(|out| write_yay_block_member(out, v, indent * 2, &floats.into()))
+}
+
+/// Write a block array or object on the lines after its key, which is at
+/// column `col`.
+fn write_yay_block_member(
+    out: &mut dyn Write,
+    v: &Value,
+    col: usize,
+    style: &EncodeOptions,
+) -> io::Result<()> {
+    let col = col + style.indent;
+    out.write_all(style.pad(col).as_bytes())?;
+    match v {
+        Value::Array(arr) => write_yay_multiline_array(out, arr, col, style),
+        Value::Object(obj) => write_yay_block_object(out, obj, col, style),
+        _ => write_yay_value(out, v, col, col, style),
+    }
+}
+
+#[cfg(feature = "std")]
+/// Whether a value is written in block form, on the lines after its key.
+pub(crate) fn is_block_value(v: &Value) -> bool {
+    !EncodeOptions::default().inline(v, 0)
+}
+
+/// Write the items of a block array at column `col`, without the indent
+/// of the first.
+fn write_yay_multiline_array(
+    out: &mut dyn Write,
+    arr: &[Value],
+    col: usize,
+    style: &EncodeOptions,
+) -> io::Result<()> {
+    let pad = style.pad(col);
 
     for (i, v) in arr.iter().enumerate() {
-        if i == 0 {
-            // First item: no leading pad (caller handles it)
-            let encoded = encode_yay_array_item(v, indent);
-            result.push(format!("- {}", encoded));
-        } else {
-            let encoded = encode_yay_array_item(v, indent);
-            result.push(format!("{}- {}", pad, encoded));
+        // First item: no leading pad (caller handles it)
+        if i > 0 {
+            write!(out, "\n{}", pad)?;
         }
+        out.write_all(b"- ")?;
+        write_yay_array_item(out, v, col, style)?;
     }
+    Ok(())
+}
 
-    result.join("\n")
+pub(crate) fn encode_yay_array_item(v: &Value, indent: usize, floats: &FloatFormat) -> String {
+    to_string(|out| write_yay_array_item(out, v, indent * 2, &floats.into()))
 }
 
-fn encode_yay_array_item(v: &Value, indent: usize) -> String {
+/// Write an array item after its `- `, which is at column `col`.
+fn write_yay_array_item(
+    out: &mut dyn Write,
+    v: &Value,
+    col: usize,
+    style: &EncodeOptions,
+) -> io::Result<()> {
+    // The item's lines line up after the `- `.
+    let inner = col + 2;
+    let inner_pad = style.pad(inner);
+    if style.inline(v, inner) {
+        return write_yay_value(out, v, inner, inner, style);
+    }
     match v {
-        Value::Array(arr) if !can_inline_array(arr) => {
+        Value::Array(arr) => {
             // Nested multiline array: first item on same line, rest indented
-            let inner_pad = "  ".repeat(indent + 1);
-            let mut items = Vec::new();
             for (i, item) in arr.iter().enumerate() {
-                let encoded = encode_yay_array_item(item, indent + 1);
-                if i == 0 {
-                    // First item: add "- " prefix on same line
-                    items.push(format!("- {}", encoded));
-                } else {
-                    items.push(format!("{}- {}", inner_pad, encoded));
+                if i > 0 {
+                    write!(out, "\n{}", inner_pad)?;
+                }
+                out.write_all(b"- ")?;
+                match item {
+                    // A `>` block cannot follow a second bullet on a line.
+                    Value::Bytes(b) if i == 0 => write_inline_bytes(out, b)?,
+                    // Nor can a block object.
+                    Value::Object(_) if i == 0 => write_yay_inline(out, item, style)?,
+                    _ => write_yay_array_item(out, item, inner, style)?,
                 }
             }
-            items.join("\n")
-        }
-        Value::Object(obj) if !can_inline_object(obj) => {
-            // Nested multiline object
-            let inner_pad = "  ".repeat(indent + 1);
-            let mut keys: Vec<&String> = obj.keys().collect();
-            keys.sort();
-            let items: Vec<String> = keys
-                .iter()
-                .enumerate()
-                .map(|(i, k)| {
-                    let v = &obj[*k];
-                    if is_block_value(v) {
-                        // Block value: put on next line with proper indentation
-                        let encoded = encode_yay(v, indent + 2);
-                        if i == 0 {
-                            format!("{}:\n{}", encode_yay_key(k), encoded)
-                        } else {
-                            format!("{}{}:\n{}", inner_pad, encode_yay_key(k), encoded)
-                        }
-                    } else {
-                        let encoded = encode_yay(v, indent + 2);
-                        if i == 0 {
-                            format!("{}: {}", encode_yay_key(k), encoded)
-                        } else {
-                            format!("{}{}: {}", inner_pad, encode_yay_key(k), encoded)
-                        }
-                    }
-                })
-                .collect();
-            items.join("\n")
+            Ok(())
         }
-        _ => encode_yay(v, indent + 1),
+        // Nested multiline object
+        Value::Object(obj) => write_yay_block_object(out, obj, inner, style),
+        _ => write_yay_value(out, v, inner, inner, style),
     }
 }
 
@@ -297,11 +905,11 @@ fn encode_yay_array_item(v: &Value, indent: usize) -> String {
 // JavaScript Encoder
 // =============================================================================
 
-fn encode_js(value: &Value, indent: usize) -> String {
-    encode_js_inner(value, indent, true)
+fn encode_js(value: &Value, indent: usize, order: KeyOrder) -> String {
+    encode_js_inner(value, indent, true, order)
 }
 
-fn encode_js_inner(value: &Value, indent: usize, is_top_level: bool) -> String {
+fn encode_js_inner(value: &Value, indent: usize, is_top_level: bool, order: KeyOrder) -> String {
     let pad = "  ".repeat(indent);
     let pad1 = "  ".repeat(indent + 1);
 
@@ -326,6 +934,8 @@ fn encode_js_inner(value: &Value, indent: usize, is_top_level: bool) -> String {
             }
         }
         Value::String(s) => encode_js_string(s),
+        Value::Decimal(d) => d.to_string(),
+        Value::DateTime(dt) => encode_js_string(dt.as_str()),
         Value::Bytes(b) => {
             if b.is_empty() {
                 "new Uint8Array(0)".to_string()
@@ -340,7 +950,7 @@ fn encode_js_inner(value: &Value, indent: usize, is_top_level: bool) -> String {
             } else {
                 let items: Vec<String> = arr
                     .iter()
-                    .map(|v| encode_js_inner(v, indent + 1, false))
+                    .map(|v| encode_js_inner(v, indent + 1, false, order))
                     .collect();
                 let joined = items.join(", ");
                 // Use multiline if: any item has newlines, or total line too long
@@ -369,14 +979,14 @@ fn encode_js_inner(value: &Value, indent: usize, is_top_level: bool) -> String {
                 }
             } else {
                 let mut keys: Vec<&String> = obj.keys().collect();
-                keys.sort();
+                order.sort(&mut keys);
                 let items: Vec<String> = keys
                     .iter()
                     .map(|k| {
                         format!(
                             "{}: {}",
                             encode_js_string(k),
-                            encode_js_inner(&obj[*k], indent + 1, false)
+                            encode_js_inner(&obj[*k], indent + 1, false, order)
                         )
                     })
                     .collect();
@@ -421,7 +1031,7 @@ fn encode_js_inner(value: &Value, indent: usize, is_top_level: bool) -> String {
 // Go Encoder
 // =============================================================================
 
-fn encode_go(value: &Value, indent: usize) -> String {
+fn encode_go(value: &Value, indent: usize, order: KeyOrder) -> String {
     let pad = "\t".repeat(indent);
     let pad1 = "\t".repeat(indent + 1);
 
@@ -442,15 +1052,12 @@ fn encode_go(value: &Value, indent: usize) -> String {
             } else if *f == 0.0 && f.is_sign_negative() {
                 "math.Copysign(0, -1)".to_string()
             } else {
-                let s = format!("{}", f);
-                if s.contains('.') || s.contains('e') {
-                    s
-                } else {
-                    format!("{}.0", s)
-                }
+                positional(*f)
             }
         }
         Value::String(s) => encode_json_string(s),
+        Value::Decimal(d) => d.to_string(),
+        Value::DateTime(dt) => encode_json_string(dt.as_str()),
         Value::Bytes(b) => {
             if b.is_empty() {
                 "[]byte{}".to_string()
@@ -463,7 +1070,10 @@ fn encode_go(value: &Value, indent: usize) -> String {
             if arr.is_empty() {
                 "[]any{}".to_string()
             } else {
-                let items: Vec<String> = arr.iter().map(|v| encode_go(v, indent + 1)).collect();
+                let items: Vec<String> = arr
+                    .iter()
+                    .map(|v| encode_go(v, indent + 1, order))
+                    .collect();
                 let joined = items.join(", ");
                 // Use multiline if too long or any item has newlines
                 if joined.len() < 60 && !items.iter().any(|i| i.contains('\n')) {
@@ -486,14 +1096,14 @@ fn encode_go(value: &Value, indent: usize) -> String {
                 "map[string]any{}".to_string()
             } else {
                 let mut keys: Vec<&String> = obj.keys().collect();
-                keys.sort();
+                order.sort(&mut keys);
                 let items: Vec<String> = keys
                     .iter()
                     .map(|k| {
                         format!(
                             "{}: {}",
                             encode_json_string(k),
-                            encode_go(&obj[*k], indent + 1)
+                            encode_go(&obj[*k], indent + 1, order)
                         )
                     })
                     .collect();
@@ -521,7 +1131,7 @@ fn encode_go(value: &Value, indent: usize) -> String {
 // Python Encoder
 // =============================================================================
 
-fn encode_python(value: &Value, indent: usize) -> String {
+fn encode_python(value: &Value, indent: usize, order: KeyOrder) -> String {
     let pad = "    ".repeat(indent);
     let pad1 = "    ".repeat(indent + 1);
 
@@ -542,15 +1152,12 @@ fn encode_python(value: &Value, indent: usize) -> String {
             } else if *f == 0.0 && f.is_sign_negative() {
                 "-0.0".to_string()
             } else {
-                let s = format!("{}", f);
-                if s.contains('.') || s.contains('e') {
-                    s
-                } else {
-                    format!("{}.0", s)
-                }
+                positional(*f)
             }
         }
         Value::String(s) => encode_json_string(s),
+        Value::Decimal(d) => d.to_string(),
+        Value::DateTime(dt) => encode_json_string(dt.as_str()),
         Value::Bytes(b) => {
             if b.is_empty() {
                 "b''".to_string()
@@ -563,13 +1170,15 @@ fn encode_python(value: &Value, indent: usize) -> String {
             if arr.is_empty() {
                 "[]".to_string()
             } else {
-                let items: Vec<String> = arr.iter().map(|v| encode_python(v, 0)).collect();
+                let items: Vec<String> = arr.iter().map(|v| encode_python(v, 0, order)).collect();
                 let single_line = format!("[{}]", items.join(", "));
                 if !items.iter().any(|i| i.contains('\n')) {
                     single_line
                 } else {
-                    let items: Vec<String> =
-                        arr.iter().map(|v| encode_python(v, indent + 1)).collect();
+                    let items: Vec<String> = arr
+                        .iter()
+                        .map(|v| encode_python(v, indent + 1, order))
+                        .collect();
                     format!(
                         "[\n{}\n{}]",
                         items
@@ -587,10 +1196,16 @@ fn encode_python(value: &Value, indent: usize) -> String {
                 "{}".to_string()
             } else {
                 let mut keys: Vec<&String> = obj.keys().collect();
-                keys.sort();
+                order.sort(&mut keys);
                 let items: Vec<String> = keys
                     .iter()
-                    .map(|k| format!("{}: {}", encode_json_string(k), encode_python(&obj[*k], 0)))
+                    .map(|k| {
+                        format!(
+                            "{}: {}",
+                            encode_json_string(k),
+                            encode_python(&obj[*k], 0, order)
+                        )
+                    })
                     .collect();
                 let single_line = format!("{{{}}}", items.join(", "));
                 if !items.iter().any(|i| i.contains('\n')) {
@@ -602,7 +1217,7 @@ fn encode_python(value: &Value, indent: usize) -> String {
                             format!(
                                 "{}: {}",
                                 encode_json_string(k),
-                                encode_python(&obj[*k], indent + 1)
+                                encode_python(&obj[*k], indent + 1, order)
                             )
                         })
                         .collect();
@@ -625,7 +1240,7 @@ fn encode_python(value: &Value, indent: usize) -> String {
 // Rust Encoder
 // =============================================================================
 
-fn encode_rust(value: &Value, indent: usize) -> String {
+fn encode_rust(value: &Value, indent: usize, order: KeyOrder) -> String {
     let pad = "    ".repeat(indent);
     let pad1 = "    ".repeat(indent + 1);
 
@@ -645,15 +1260,15 @@ fn encode_rust(value: &Value, indent: usize) -> String {
             } else if *f == 0.0 && f.is_sign_negative() {
                 "Value::Float(-0.0)".to_string()
             } else {
-                let s = format!("{}", f);
-                if s.contains('.') || s.contains('e') {
-                    format!("Value::Float({})", s)
-                } else {
-                    format!("Value::Float({}.0)", s)
-                }
+                format!("Value::Float({})", positional(*f))
             }
         }
         Value::String(s) => format!("Value::String({}.into())", encode_json_string(s)),
+        Value::Decimal(d) => format!("Value::Decimal(\"{}\".parse().unwrap())", d),
+        Value::DateTime(dt) => format!(
+            "Value::DateTime({}.parse().unwrap())",
+            encode_json_string(dt.as_str())
+        ),
         Value::Bytes(b) => {
             if b.is_empty() {
                 "Value::Bytes(vec![])".to_string()
@@ -666,7 +1281,10 @@ fn encode_rust(value: &Value, indent: usize) -> String {
             if arr.is_empty() {
                 "Value::Array(vec![])".to_string()
             } else {
-                let items: Vec<String> = arr.iter().map(|v| encode_rust(v, indent + 1)).collect();
+                let items: Vec<String> = arr
+                    .iter()
+                    .map(|v| encode_rust(v, indent + 1, order))
+                    .collect();
                 let single_line = format!("Value::Array(vec![{}])", items.join(", "));
                 if single_line.len() < 50 && !single_line.contains('\n') {
                     single_line
@@ -685,22 +1303,22 @@ fn encode_rust(value: &Value, indent: usize) -> String {
         }
         Value::Object(obj) => {
             if obj.is_empty() {
-                "Value::Object(HashMap::new())".to_string()
+                "Value::Object(Map::new())".to_string()
             } else {
                 let mut keys: Vec<&String> = obj.keys().collect();
-                keys.sort();
+                order.sort(&mut keys);
                 let items: Vec<String> = keys
                     .iter()
                     .map(|k| {
                         format!(
                             "({}.into(), {})",
                             encode_json_string(k),
-                            encode_rust(&obj[*k], indent + 1)
+                            encode_rust(&obj[*k], indent + 1, order)
                         )
                     })
                     .collect();
                 format!(
-                    "Value::Object(HashMap::from([\n{}\n{}]))",
+                    "Value::Object(Map::from_iter([\n{}\n{}]))",
                     items
                         .iter()
                         .map(|i| format!("{}{},", pad1, i))
@@ -720,11 +1338,11 @@ fn encode_rust(value: &Value, indent: usize) -> String {
 const C_INDENT: &str = "    ";
 const C_MAX_LINE: usize = 72;
 
-fn encode_c(value: &Value) -> String {
-    encode_c_value(value, 0)
+fn encode_c(value: &Value, order: KeyOrder) -> String {
+    encode_c_value(value, 0, order)
 }
 
-fn encode_c_value(value: &Value, indent: usize) -> String {
+fn encode_c_value(value: &Value, indent: usize, order: KeyOrder) -> String {
     match value {
         Value::Null => "yay_null()".to_string(),
         Value::Bool(true) => "yay_bool(true)".to_string(),
@@ -742,15 +1360,12 @@ fn encode_c_value(value: &Value, indent: usize) -> String {
             } else if *f == 0.0 && f.is_sign_negative() {
                 "yay_float(-0.0)".to_string()
             } else {
-                let s = format!("{}", f);
-                if s.contains('.') || s.contains('e') {
-                    format!("yay_float({})", s)
-                } else {
-                    format!("yay_float({}.0)", s)
-                }
+                format!("yay_float({})", positional(*f))
             }
         }
         Value::String(s) => format!("yay_string({})", encode_c_string(s)),
+        Value::Decimal(d) => format!("yay_float({})", d),
+        Value::DateTime(dt) => format!("yay_string({})", encode_c_string(dt.as_str())),
         Value::Bytes(b) => {
             if b.is_empty() {
                 "yay_bytes_from_hex(\"\")".to_string()
@@ -763,8 +1378,10 @@ fn encode_c_value(value: &Value, indent: usize) -> String {
             if arr.is_empty() {
                 "yay_array()".to_string()
             } else {
-                let items: Vec<String> =
-                    arr.iter().map(|v| encode_c_value(v, indent + 1)).collect();
+                let items: Vec<String> = arr
+                    .iter()
+                    .map(|v| encode_c_value(v, indent + 1, order))
+                    .collect();
                 format_c_macro("YAY_ARRAY", &items, indent)
             }
         }
@@ -773,10 +1390,15 @@ fn encode_c_value(value: &Value, indent: usize) -> String {
                 "yay_object()".to_string()
             } else {
                 let mut keys: Vec<&String> = obj.keys().collect();
-                keys.sort();
+                order.sort(&mut keys);
                 let items: Vec<String> = keys
                     .iter()
-                    .flat_map(|k| vec![encode_c_string(k), encode_c_value(&obj[*k], indent + 1)])
+                    .flat_map(|k| {
+                        vec![
+                            encode_c_string(k),
+                            encode_c_value(&obj[*k], indent + 1, order),
+                        ]
+                    })
                     .collect();
                 format_c_macro("YAY_OBJECT", &items, indent)
             }
@@ -849,7 +1471,7 @@ fn encode_c_string(s: &str) -> String {
 // Java Encoder
 // =============================================================================
 
-fn encode_java(value: &Value, indent: usize) -> String {
+fn encode_java(value: &Value, indent: usize, order: KeyOrder) -> String {
     let pad = "    ".repeat(indent);
     let pad1 = "    ".repeat(indent + 1);
 
@@ -870,15 +1492,12 @@ fn encode_java(value: &Value, indent: usize) -> String {
             } else if *f == 0.0 && f.is_sign_negative() {
                 "-0.0".to_string()
             } else {
-                let s = format!("{}", f);
-                if s.contains('.') || s.contains('e') {
-                    s
-                } else {
-                    format!("{}.0", s)
-                }
+                positional(*f)
             }
         }
         Value::String(s) => encode_java_string(s),
+        Value::Decimal(d) => format!("new BigDecimal(\"{}\")", d),
+        Value::DateTime(dt) => encode_java_string(dt.as_str()),
         Value::Bytes(b) => {
             if b.is_empty() {
                 "new byte[0]".to_string()
@@ -895,13 +1514,15 @@ fn encode_java(value: &Value, indent: usize) -> String {
                 "List.of()".to_string()
             } else {
                 // Always try single-line first, only go multi-line if items are multi-line
-                let items: Vec<String> = arr.iter().map(|v| encode_java(v, 0)).collect();
+                let items: Vec<String> = arr.iter().map(|v| encode_java(v, 0, order)).collect();
                 let single_line = format!("List.of({})", items.join(", "));
                 if !items.iter().any(|i| i.contains('\n')) {
                     single_line
                 } else {
-                    let items: Vec<String> =
-                        arr.iter().map(|v| encode_java(v, indent + 1)).collect();
+                    let items: Vec<String> = arr
+                        .iter()
+                        .map(|v| encode_java(v, indent + 1, order))
+                        .collect();
                     format!(
                         "List.of(\n{}\n{})",
                         items
@@ -919,11 +1540,11 @@ fn encode_java(value: &Value, indent: usize) -> String {
                 "Map.of()".to_string()
             } else {
                 let mut keys: Vec<&String> = obj.keys().collect();
-                keys.sort();
+                order.sort(&mut keys);
                 // Always try single-line first
                 let items: Vec<String> = keys
                     .iter()
-                    .flat_map(|k| vec![encode_java_string(k), encode_java(&obj[*k], 0)])
+                    .flat_map(|k| vec![encode_java_string(k), encode_java(&obj[*k], 0, order)])
                     .collect();
                 let single_line = format!("Map.of({})", items.join(", "));
                 if !items.iter().any(|i| i.contains('\n')) {
@@ -936,7 +1557,7 @@ fn encode_java(value: &Value, indent: usize) -> String {
                                 "{}{}, {}",
                                 pad1,
                                 encode_java_string(k),
-                                encode_java(&obj[*k], indent + 1)
+                                encode_java(&obj[*k], indent + 1, order)
                             )
                         })
                         .collect();
@@ -969,7 +1590,7 @@ fn encode_java_string(s: &str) -> String {
 // Scheme Encoder
 // =============================================================================
 
-fn encode_scheme(value: &Value) -> String {
+fn encode_scheme(value: &Value, order: KeyOrder) -> String {
     match value {
         Value::Null => "'null".to_string(),
         Value::Bool(true) => "#t".to_string(),
@@ -987,15 +1608,12 @@ fn encode_scheme(value: &Value) -> String {
             } else if *f == 0.0 && f.is_sign_negative() {
                 "-0.0".to_string()
             } else {
-                let s = format!("{}", f);
-                if s.contains('.') || s.contains('e') {
-                    s
-                } else {
-                    format!("{}.0", s)
-                }
+                positional(*f)
             }
         }
         Value::String(s) => encode_scheme_string(s),
+        Value::Decimal(d) => d.to_string(),
+        Value::DateTime(dt) => encode_scheme_string(dt.as_str()),
         Value::Bytes(b) => {
             if b.is_empty() {
                 "(bytevector)".to_string()
@@ -1008,7 +1626,7 @@ fn encode_scheme(value: &Value) -> String {
             if arr.is_empty() {
                 "#()".to_string()
             } else {
-                let items: Vec<String> = arr.iter().map(encode_scheme).collect();
+                let items: Vec<String> = arr.iter().map(|v| encode_scheme(v, order)).collect();
                 format!("#({})", items.join(" "))
             }
         }
@@ -1017,14 +1635,14 @@ fn encode_scheme(value: &Value) -> String {
                 "()".to_string()
             } else {
                 let mut keys: Vec<&String> = obj.keys().collect();
-                keys.sort();
+                order.sort(&mut keys);
                 let items: Vec<String> = keys
                     .iter()
                     .map(|k| {
                         format!(
                             "({} . {})",
                             encode_scheme_string(k),
-                            encode_scheme(&obj[*k])
+                            encode_scheme(&obj[*k], order)
                         )
                     })
                     .collect();
@@ -1065,71 +1683,89 @@ fn encode_scheme_string(s: &str) -> String {
 // JSON Encoder
 // =============================================================================
 
-fn encode_json(value: &Value, indent: usize) -> String {
-    let pad = "  ".repeat(indent);
-    let pad1 = "  ".repeat(indent + 1);
+pub(crate) fn encode_json(value: &Value, indent: usize, order: KeyOrder) -> String {
+    to_string(|out| write_json(out, value, indent, order))
+}
 
+fn write_json(
+    out: &mut dyn Write,
+    value: &Value,
+    indent: usize,
+    order: KeyOrder,
+) -> io::Result<()> {
     match value {
-        Value::Null => "null".to_string(),
-        Value::Bool(true) => "true".to_string(),
-        Value::Bool(false) => "false".to_string(),
-        Value::Integer(n) => n.to_string(),
+        Value::Null => out.write_all(b"null"),
+        Value::Bool(true) => out.write_all(b"true"),
+        Value::Bool(false) => out.write_all(b"false"),
+        Value::Integer(n) => write!(out, "{}", n),
         Value::Float(f) => {
             if f.is_nan() || f.is_infinite() {
-                "null".to_string() // JSON doesn't support NaN/Infinity
+                out.write_all(b"null") // JSON doesn't support NaN/Infinity
             } else {
-                format!("{}", f)
+                write!(out, "{}", f)
             }
         }
-        Value::String(s) => encode_json_string(s),
-        Value::Bytes(_) => "null".to_string(), // JSON doesn't support bytes
-        Value::Array(arr) => {
-            if arr.is_empty() {
-                "[]".to_string()
-            } else {
-                let items: Vec<String> = arr.iter().map(|v| encode_json(v, indent + 1)).collect();
-                format!(
-                    "[\n{}\n{}]",
-                    items
-                        .iter()
-                        .map(|i| format!("{}{}", pad1, i))
-                        .collect::<Vec<_>>()
-                        .join(",\n"),
-                    pad
-                )
-            }
+        Value::String(s) => out.write_all(encode_json_string(s).as_bytes()),
+        Value::Decimal(d) => write!(out, "{}", d),
+        Value::DateTime(dt) => out.write_all(encode_json_string(dt.as_str()).as_bytes()),
+        Value::Bytes(_) => out.write_all(b"null"), // JSON doesn't support bytes
+        Value::Array(arr) => write_json_array(out, arr, indent, &write_json, order),
+        Value::Object(obj) => write_json_object(out, obj, indent, &write_json, order),
+    }
+}
+
+type WriteJson = dyn Fn(&mut dyn Write, &Value, usize, KeyOrder) -> io::Result<()>;
+
+/// Write an array with each item on its own line, as JSON and YSON do.
+fn write_json_array(
+    out: &mut dyn Write,
+    arr: &[Value],
+    indent: usize,
+    write_item: &WriteJson,
+    order: KeyOrder,
+) -> io::Result<()> {
+    if arr.is_empty() {
+        return out.write_all(b"[]");
+    }
+    let pad1 = "  ".repeat(indent + 1);
+    out.write_all(b"[\n")?;
+    for (i, v) in arr.iter().enumerate() {
+        if i > 0 {
+            out.write_all(b",\n")?;
         }
-        Value::Object(obj) => {
-            if obj.is_empty() {
-                "{}".to_string()
-            } else {
-                let mut keys: Vec<&String> = obj.keys().collect();
-                keys.sort();
-                let items: Vec<String> = keys
-                    .iter()
-                    .map(|k| {
-                        format!(
-                            "{}: {}",
-                            encode_json_string(k),
-                            encode_json(&obj[*k], indent + 1)
-                        )
-                    })
-                    .collect();
-                format!(
-                    "{{\n{}\n{}}}",
-                    items
-                        .iter()
-                        .map(|i| format!("{}{}", pad1, i))
-                        .collect::<Vec<_>>()
-                        .join(",\n"),
-                    pad
-                )
-            }
+        out.write_all(pad1.as_bytes())?;
+        write_item(out, v, indent + 1, order)?;
+    }
+    write!(out, "\n{}]", "  ".repeat(indent))
+}
+
+/// Write an object with each member on its own line, in key order, as JSON
+/// and YSON do.
+fn write_json_object(
+    out: &mut dyn Write,
+    obj: &Map,
+    indent: usize,
+    write_item: &WriteJson,
+    order: KeyOrder,
+) -> io::Result<()> {
+    if obj.is_empty() {
+        return out.write_all(b"{}");
+    }
+    let pad1 = "  ".repeat(indent + 1);
+    let mut keys: Vec<&String> = obj.keys().collect();
+    order.sort(&mut keys);
+    out.write_all(b"{\n")?;
+    for (i, k) in keys.iter().enumerate() {
+        if i > 0 {
+            out.write_all(b",\n")?;
         }
+        write!(out, "{}{}: ", pad1, encode_json_string(k))?;
+        write_item(out, &obj[*k], indent + 1, order)?;
     }
+    write!(out, "\n{}}}", "  ".repeat(indent))
 }
 
-fn encode_json_string(s: &str) -> String {
+pub(crate) fn encode_json_string(s: &str) -> String {
     let mut result = String::from("\"");
     for c in s.chars() {
         match c {
@@ -1185,77 +1821,45 @@ fn encode_js_string(s: &str) -> String {
 // YSON Encoder
 // =============================================================================
 
-fn encode_yson(value: &Value, indent: usize) -> String {
-    let pad = "  ".repeat(indent);
-    let pad1 = "  ".repeat(indent + 1);
+pub(crate) fn encode_yson(value: &Value, indent: usize, order: KeyOrder) -> String {
+    to_string(|out| write_yson(out, value, indent, order))
+}
 
+fn write_yson(
+    out: &mut dyn Write,
+    value: &Value,
+    indent: usize,
+    order: KeyOrder,
+) -> io::Result<()> {
     match value {
-        Value::Null => "null".to_string(),
-        Value::Bool(true) => "true".to_string(),
-        Value::Bool(false) => "false".to_string(),
-        Value::Integer(n) => format!("\"#{}\"", n), // BigInt prefix
+        Value::Null => out.write_all(b"null"),
+        Value::Bool(true) => out.write_all(b"true"),
+        Value::Bool(false) => out.write_all(b"false"),
+        Value::Integer(n) => write!(out, "\"#{}\"", n), // BigInt prefix
         Value::Float(f) => {
             if f.is_nan() {
-                "\"#NaN\"".to_string()
+                out.write_all(b"\"#NaN\"")
             } else if f.is_infinite() {
                 if *f > 0.0 {
-                    "\"#Infinity\"".to_string()
+                    out.write_all(b"\"#Infinity\"")
                 } else {
-                    "\"#-Infinity\"".to_string()
+                    out.write_all(b"\"#-Infinity\"")
                 }
             } else {
-                format!("{}", f)
+                write!(out, "{}", f)
             }
         }
-        Value::String(s) => encode_yson_string(s),
+        Value::String(s) => out.write_all(encode_yson_string(s).as_bytes()),
+        Value::Decimal(d) => write!(out, "{}", d),
+        Value::DateTime(dt) => out.write_all(encode_yson_string(dt.as_str()).as_bytes()),
         Value::Bytes(b) => {
             // Bytes prefix
-            let hex: String = b.iter().map(|byte| format!("{:02x}", byte)).collect();
-            format!("\"*{}\"", hex)
-        }
-        Value::Array(arr) => {
-            if arr.is_empty() {
-                "[]".to_string()
-            } else {
-                let items: Vec<String> = arr.iter().map(|v| encode_yson(v, indent + 1)).collect();
-                format!(
-                    "[\n{}\n{}]",
-                    items
-                        .iter()
-                        .map(|i| format!("{}{}", pad1, i))
-                        .collect::<Vec<_>>()
-                        .join(",\n"),
-                    pad
-                )
-            }
-        }
-        Value::Object(obj) => {
-            if obj.is_empty() {
-                "{}".to_string()
-            } else {
-                let mut keys: Vec<&String> = obj.keys().collect();
-                keys.sort();
-                let items: Vec<String> = keys
-                    .iter()
-                    .map(|k| {
-                        format!(
-                            "{}: {}",
-                            encode_json_string(k),
-                            encode_yson(&obj[*k], indent + 1)
-                        )
-                    })
-                    .collect();
-                format!(
-                    "{{\n{}\n{}}}",
-                    items
-                        .iter()
-                        .map(|i| format!("{}{}", pad1, i))
-                        .collect::<Vec<_>>()
-                        .join(",\n"),
-                    pad
-                )
-            }
+            out.write_all(b"\"*")?;
+            write_hex(out, b)?;
+            out.write_all(b"\"")
         }
+        Value::Array(arr) => write_json_array(out, arr, indent, &write_yson, order),
+        Value::Object(obj) => write_json_object(out, obj, indent, &write_yson, order),
     }
 }
 
@@ -1269,7 +1873,7 @@ fn encode_yson_string(s: &str) -> String {
 
     if needs_escape {
         // Escape with ! prefix
-        format!("\"!{}\"", &encode_json_string(s)[1..s.len() + 1])
+        format!("\"!{}", &encode_json_string(s)[1..])
     } else {
         encode_json_string(s)
     }
@@ -1326,4 +1930,283 @@ mod tests {
         let value = Value::Float(f64::NEG_INFINITY);
         assert_eq!(encode(&value, Format::Yson), "\"#-Infinity\"");
     }
+
+    /// Every encoder that writes finite floats as number literals, with the
+    /// prefix and suffix around the literal.
+    const FLOAT_FORMATS: &[(Format, &str, &str)] = &[
+        (Format::Yay, "", ""),
+        (Format::JavaScript, "", ""),
+        (Format::Go, "", ""),
+        (Format::Python, "", ""),
+        (Format::Rust, "Value::Float(", ")"),
+        (Format::C, "yay_float(", ")"),
+        (Format::Java, "", ""),
+        (Format::Scheme, "", ""),
+        (Format::Json, "", ""),
+        (Format::Yson, "", ""),
+    ];
+
+    /// Encode `f` in every format and check that each literal reads back
+    /// as exactly the same float.
+    fn assert_round_trips(f: f64) {
+        for (format, prefix, suffix) in FLOAT_FORMATS {
+            let encoded = encode(&Value::Float(f), *format);
+            if f == 0.0 && f.is_sign_negative() && *format == Format::Go {
+                assert_eq!(encoded, "math.Copysign(0, -1)");
+                continue;
+            }
+            let literal = encoded
+                .strip_prefix(prefix)
+                .and_then(|s| s.strip_suffix(suffix))
+                .unwrap_or_else(|| panic!("{:?}: {}", format, encoded));
+            let parsed: f64 = literal
+                .parse()
+                .unwrap_or_else(|_| panic!("{:?}: {}", format, encoded));
+            assert_eq!(parsed.to_bits(), f.to_bits(), "{:?}: {}", format, encoded);
+        }
+    }
+
+    #[test]
+    fn test_float_edge_cases_round_trip() {
+        for f in [
+            0.0,
+            -0.0,
+            1e-7,
+            0.1 + 0.2,
+            1.0 / 3.0,
+            // 17 significant digits are needed to round-trip this one.
+            0.30000000000000004,
+            123456789012345680.0,
+            f64::MIN_POSITIVE,
+            // The largest subnormal and the smallest subnormal.
+            f64::from_bits(0x000f_ffff_ffff_ffff),
+            f64::from_bits(1),
+            f64::MAX,
+            -f64::MAX,
+            f64::EPSILON,
+            9007199254740993.0,
+        ] {
+            assert_round_trips(f);
+        }
+    }
+
+    #[test]
+    fn test_float_fuzz_round_trip() {
+        // A fixed-seed xorshift generator keeps failures reproducible.
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        for _ in 0..2000 {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            let f = f64::from_bits(state);
+            if f.is_finite() {
+                assert_round_trips(f);
+            }
+        }
+    }
+
+    #[test]
+    fn test_array_encoder_matches_encode() {
+        let object: Map = [
+            ("b".to_string(), Value::Array(vec![Value::Null; 7])),
+            ("a".to_string(), Value::String("x".to_string())),
+        ]
+        .into_iter()
+        .collect();
+        let arrays = vec![
+            vec![],
+            vec![Value::Bool(true)],
+            vec![Value::Float(1.5); 5],
+            vec![Value::Float(1.5); 6],
+            vec![Value::Null, Value::Object(object.clone())],
+            vec![Value::Object(object), Value::Array(vec![Value::Null; 9])],
+        ];
+        for items in arrays {
+            let mut encoder = ArrayEncoder::new(FloatFormat::default());
+            let mut out = String::new();
+            for item in items.clone() {
+                out.push_str(&encoder.push(item));
+            }
+            out.push_str(&encoder.finish());
+            assert_eq!(out, encode(&Value::Array(items), Format::Yay));
+        }
+    }
+
+    #[test]
+    fn test_encode_to_matches_encode() {
+        let value = crate::parse(
+            "a: [1, 2.5, \"x\"]\nb:\n  - {c: null}\n  - - <cafe>\n    - 7\nd: {}\ne: <>\n",
+        )
+        .unwrap();
+        for (format, _, _) in FLOAT_FORMATS {
+            let mut out = Vec::new();
+            encode_to(&mut out, &value, *format).unwrap();
+            assert_eq!(String::from_utf8(out).unwrap(), encode(&value, *format));
+        }
+    }
+
+    #[test]
+    fn test_long_bytes_are_blocks() {
+        let long = Value::Bytes((0..40).collect());
+        assert_eq!(
+            encode(&long, Format::Yay),
+            "> 00 01 02 03  04 05 06 07  08 09 0a 0b  0c 0d 0e 0f\n  \
+             10 11 12 13  14 15 16 17  18 19 1a 1b  1c 1d 1e 1f\n  \
+             20 21 22 23  24 25 26 27"
+        );
+        let short = Value::Bytes(vec![0xca; 24]);
+        assert!(encode(&short, Format::Yay).starts_with("<caca"));
+
+        let member: Map = [("data".to_string(), long.clone())].into_iter().collect();
+        let value = Value::Array(vec![
+            long.clone(),
+            Value::Array(vec![long.clone(), short.clone()]),
+            Value::Object(member.clone()),
+            Value::Object(
+                [("nested".to_string(), Value::Object(member))]
+                    .into_iter()
+                    .collect(),
+            ),
+        ]);
+        let text = encode(&value, Format::Yay);
+        assert!(text.contains("- data: >\n    00 01 02 03"));
+        assert_eq!(crate::parse(&text).unwrap(), value);
+    }
+
+    #[test]
+    fn test_empty_collections_round_trip() {
+        let value = crate::parse(
+            "a: []\nb: {}\nc:\n  - []\n  - {}\n  - - []\n    - {}\n  - d: []\n    e: {}\n    \
+             f: [1, 2, 3, 4, 5, 6]\n",
+        )
+        .unwrap();
+        for value in [value, Value::Array(vec![]), Value::Object(Map::new())] {
+            let text = encode(&value, Format::Yay);
+            assert_eq!(crate::parse(&text).unwrap(), value, "{}", text);
+            assert_eq!(crate::format_yay(&text).unwrap().trim_end(), text);
+        }
+    }
+
+    #[test]
+    fn test_encode_ndyay() {
+        let value = crate::parse(
+            "- a: 1\n  b:\n    - \"x\"\n    - c: true\n- [1, 2, 3, 4, 5, 6, 7, 8, 9, 10]\n- <0a0b>\n- null\n",
+        )
+        .unwrap();
+        let text = encode(&value, Format::Ndyay);
+        assert_eq!(
+            text,
+            "{a: 1, b: [\"x\", {c: true}]}\n[1, 2, 3, 4, 5, 6, 7, 8, 9, 10]\n<0a0b>\nnull"
+        );
+        let documents: Vec<Value> = crate::Documents::new(text.as_bytes(), &Default::default())
+            .collect::<crate::Result<_>>()
+            .unwrap();
+        assert_eq!(Value::Array(documents), value);
+        assert_eq!(encode(&Value::Integer(1.into()), Format::Ndyay), "1");
+    }
+
+    #[test]
+    fn test_float_literals_have_fraction() {
+        assert_eq!(
+            encode(&Value::Float(1e21), Format::Python),
+            "1000000000000000000000.0"
+        );
+        assert_eq!(encode(&Value::Float(-0.0), Format::Java), "-0.0");
+        assert_eq!(encode(&Value::Float(1e-7), Format::Go), "0.0000001");
+        assert_eq!(
+            encode(&Value::Float(2.0), Format::Rust),
+            "Value::Float(2.0)"
+        );
+    }
+
+    #[test]
+    fn test_encode_with_options() {
+        let value = crate::parse(
+            "b: [1, 2, 3]\na:\n  - x: \"it's\"\n    y: [<cafe>, \"a/b\"]\n  - [true, false]\nc: {q: 1}\n",
+        )
+        .unwrap();
+        let read = crate::ParseOptions {
+            version_directive: true,
+            ..crate::ParseOptions::default()
+        };
+        let encode_as = |options: EncodeOptions| {
+            let text = encode_with_options(&value, &options);
+            let parsed = crate::parse_with_options(&text, &read).unwrap();
+            assert_eq!(parsed, value, "{}", text);
+            text
+        };
+
+        assert_eq!(
+            encode_as(EncodeOptions::default()),
+            encode(&value, Format::Yay)
+        );
+        assert_eq!(
+            encode_as(EncodeOptions {
+                indent: 4,
+                inline_array_items: 1,
+                inline_object_members: 0,
+                ..EncodeOptions::default()
+            }),
+            "a:\n    - x: \"it's\"\n      y:\n          - <cafe>\n          - \"a\\/b\"\n    - - true\n      - false\nb:\n    - 1\n    - 2\n    - 3\nc:\n    q: 1"
+        );
+        assert_eq!(
+            encode_as(EncodeOptions {
+                sort_keys: false,
+                quotes: Quotes::Single,
+                ..EncodeOptions::default()
+            }),
+            "b: [1, 2, 3]\na:\n  - x: \"it's\"\n    y: [<cafe>, 'a/b']\n  - [true, false]\nc: {q: 1}"
+        );
+        // `b: [1, 2, 3]` ends at column 12, and the other arrays would not.
+        assert_eq!(
+            encode_as(EncodeOptions {
+                wrap_column: Some(12),
+                ..EncodeOptions::default()
+            }),
+            "a:\n  - x: \"it's\"\n    y:\n      - <cafe>\n      - \"a\\/b\"\n  - - true\n    - false\nb: [1, 2, 3]\nc: {q: 1}"
+        );
+        assert_eq!(
+            encode_with_options(
+                &crate::parse("[1, 2]").unwrap(),
+                &EncodeOptions {
+                    wrap_column: Some(5),
+                    ..EncodeOptions::default()
+                }
+            ),
+            "- 1\n- 2"
+        );
+        assert_eq!(
+            encode_as(EncodeOptions {
+                version_directive: true,
+                ..EncodeOptions::default()
+            }),
+            format!("%yay 1\n{}", encode(&value, Format::Yay))
+        );
+    }
+
+    #[test]
+    fn test_encode_ordered() {
+        let value = crate::parse("b: {z: 1, y: [{d: 2, c: 3}]}\na: null").unwrap();
+        let ordered = |format| encode_ordered(&value, format, KeyOrder::Document);
+        assert_eq!(
+            ordered(Format::Yay),
+            "b:\n  z: 1\n  y:\n    - {d: 2, c: 3}\na: null"
+        );
+        assert_eq!(
+            ordered(Format::Python),
+            "{\"b\": {\"z\": 1, \"y\": [{\"d\": 2, \"c\": 3}]}, \"a\": None}"
+        );
+        assert_eq!(
+            ordered(Format::Scheme),
+            "((\"b\" . ((\"z\" . 1) (\"y\" . #(((\"d\" . 2) (\"c\" . 3)))))) (\"a\" . 'null))"
+        );
+        assert_eq!(
+            ordered(Format::Ndyay),
+            "{b: {z: 1, y: [{d: 2, c: 3}]}, a: null}"
+        );
+        assert_eq!(
+            encode_ordered(&value, Format::Yson, KeyOrder::Bytes),
+            encode(&value, Format::Yson)
+        );
+    }
 }
@@ -0,0 +1,199 @@
+//! Decoding YAY source text from bytes.
+//!
+//! YAY documents are UTF-8 without a byte order mark, but files arrive from
+//! editors and other tools in UTF-16, with a BOM, or with CRLF line endings.
+//! [`decode_bytes`] detects the encoding, strips any BOM, and optionally
+//! normalizes newlines, so the text can go to the parser (or any of the
+//! text-based decoders) as a `&str`.
+
+use alloc::string::{String, ToString};
+
+use crate::error::{ParseContext, ParseError, Result};
+use crate::ParseOptions;
+
+/// A text encoding recognized by [`detect_encoding`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Encoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Utf32Le,
+    Utf32Be,
+}
+
+impl Encoding {
+    /// The conventional name of the encoding.
+    pub fn name(self) -> &'static str {
+        match self {
+            Encoding::Utf8 => "UTF-8",
+            Encoding::Utf16Le => "UTF-16LE",
+            Encoding::Utf16Be => "UTF-16BE",
+            Encoding::Utf32Le => "UTF-32LE",
+            Encoding::Utf32Be => "UTF-32BE",
+        }
+    }
+}
+
+/// How [`decode_bytes`] determines the encoding of its input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum EncodingDetection {
+    /// Use a byte order mark if present, otherwise guess from the pattern of
+    /// zero bytes at the start of the input, defaulting to UTF-8.
+    #[default]
+    Detect,
+    /// Require UTF-8. A BOM is left in place, so the parser rejects it.
+    Utf8Only,
+}
+
+/// How [`decode_bytes`] treats carriage returns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum Newlines {
+    /// Leave the text as is. The YAY parser rejects carriage returns.
+    #[default]
+    Preserve,
+    /// Convert CRLF and lone CR line endings to LF.
+    Normalize,
+}
+
+/// Determine the encoding of `bytes` and the length of its byte order
+/// mark, if any.
+///
+/// Without a BOM, a document that starts with an ASCII character reveals
+/// UTF-16 and UTF-32 by where its zero bytes fall.
+pub fn detect_encoding(bytes: &[u8]) -> (Encoding, usize) {
+    match bytes {
+        [0xEF, 0xBB, 0xBF, ..] => (Encoding::Utf8, 3),
+        [0xFF, 0xFE, 0, 0, ..] => (Encoding::Utf32Le, 4),
+        [0, 0, 0xFE, 0xFF, ..] => (Encoding::Utf32Be, 4),
+        [0xFF, 0xFE, ..] => (Encoding::Utf16Le, 2),
+        [0xFE, 0xFF, ..] => (Encoding::Utf16Be, 2),
+        [0, 0, 0, a, ..] if *a != 0 => (Encoding::Utf32Be, 0),
+        [a, 0, 0, 0, ..] if *a != 0 => (Encoding::Utf32Le, 0),
+        [0, a, ..] if *a != 0 => (Encoding::Utf16Be, 0),
+        [a, 0, ..] if *a != 0 => (Encoding::Utf16Le, 0),
+        _ => (Encoding::Utf8, 0),
+    }
+}
+
+/// Decode source bytes to text according to `options`.
+pub fn decode_bytes(bytes: &[u8], options: &ParseOptions) -> Result<String> {
+    let (encoding, bom) = match options.encoding {
+        EncodingDetection::Detect => detect_encoding(bytes),
+        EncodingDetection::Utf8Only => (Encoding::Utf8, 0),
+    };
+    let body = &bytes[bom..];
+    let text = match encoding {
+        Encoding::Utf8 => core::str::from_utf8(body)
+            .map(str::to_string)
+            .map_err(|e| invalid(encoding, bom + e.valid_up_to())),
+        Encoding::Utf16Le | Encoding::Utf16Be => decode_utf16(body, bom, encoding),
+        Encoding::Utf32Le | Encoding::Utf32Be => decode_utf32(body, bom, encoding),
+    }
+    .map_err(|e| match e {
+        ParseError::InvalidEncoding(name, offset, _) => ParseError::InvalidEncoding(
+            name,
+            offset,
+            ParseContext::from_options(options).file_suffix(),
+        ),
+        e => e,
+    })?;
+    Ok(match options.newlines {
+        Newlines::Preserve => text,
+        Newlines::Normalize => text.replace("\r\n", "\n").replace('\r', "\n"),
+    })
+}
+
+fn invalid(encoding: Encoding, offset: usize) -> ParseError {
+    ParseError::InvalidEncoding(encoding.name(), offset, String::new())
+}
+
+fn decode_utf16(body: &[u8], bom: usize, encoding: Encoding) -> Result<String> {
+    if !body.len().is_multiple_of(2) {
+        return Err(invalid(encoding, bom + body.len() - 1));
+    }
+    let units = body.chunks_exact(2).map(|pair| match encoding {
+        Encoding::Utf16Le => u16::from_le_bytes([pair[0], pair[1]]),
+        _ => u16::from_be_bytes([pair[0], pair[1]]),
+    });
+    let mut text = String::with_capacity(body.len() / 2);
+    let mut offset = bom;
+    for ch in char::decode_utf16(units) {
+        let ch = ch.map_err(|_| invalid(encoding, offset))?;
+        offset += ch.len_utf16() * 2;
+        text.push(ch);
+    }
+    Ok(text)
+}
+
+fn decode_utf32(body: &[u8], bom: usize, encoding: Encoding) -> Result<String> {
+    if !body.len().is_multiple_of(4) {
+        return Err(invalid(encoding, bom + body.len() - body.len() % 4));
+    }
+    body.chunks_exact(4)
+        .enumerate()
+        .map(|(i, quad)| {
+            let quad = [quad[0], quad[1], quad[2], quad[3]];
+            let cp = match encoding {
+                Encoding::Utf32Le => u32::from_le_bytes(quad),
+                _ => u32::from_be_bytes(quad),
+            };
+            char::from_u32(cp).ok_or_else(|| invalid(encoding, bom + i * 4))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utf16le(s: &str) -> Vec<u8> {
+        s.encode_utf16().flat_map(u16::to_le_bytes).collect()
+    }
+
+    #[test]
+    fn test_detect_encoding() {
+        assert_eq!(detect_encoding(b"\xEF\xBB\xBFa: 1"), (Encoding::Utf8, 3));
+        assert_eq!(detect_encoding(b"\xFF\xFEa\0"), (Encoding::Utf16Le, 2));
+        assert_eq!(detect_encoding(b"\0a\0:"), (Encoding::Utf16Be, 0));
+        assert_eq!(detect_encoding(b"a\0\0\0"), (Encoding::Utf32Le, 0));
+        assert_eq!(detect_encoding(b"a: 1"), (Encoding::Utf8, 0));
+        assert_eq!(detect_encoding(b""), (Encoding::Utf8, 0));
+    }
+
+    #[test]
+    fn test_decode_utf16_with_bom() {
+        let mut bytes = vec![0xFF, 0xFE];
+        bytes.extend(utf16le("k: \"\u{1F600}\"\n"));
+        let text = decode_bytes(&bytes, &ParseOptions::default()).unwrap();
+        assert_eq!(text, "k: \"\u{1F600}\"\n");
+    }
+
+    #[test]
+    fn test_decode_errors_report_offset() {
+        let err = decode_bytes(b"ok\xFF", &ParseOptions::default()).unwrap_err();
+        assert_eq!(err.to_string(), "Invalid UTF-8 at byte offset 2");
+        // An unpaired high surrogate.
+        let err = decode_bytes(b"\xFF\xFEa\0\x00\xD8", &ParseOptions::default()).unwrap_err();
+        assert_eq!(err.to_string(), "Invalid UTF-16LE at byte offset 4");
+    }
+
+    #[test]
+    fn test_newlines_and_strict_utf8() {
+        let options = ParseOptions {
+            newlines: Newlines::Normalize,
+            ..ParseOptions::default()
+        };
+        assert_eq!(decode_bytes(b"a\r\nb\rc", &options).unwrap(), "a\nb\nc");
+        let strict = ParseOptions {
+            encoding: EncodingDetection::Utf8Only,
+            ..ParseOptions::default()
+        };
+        assert_eq!(
+            decode_bytes(b"\xEF\xBB\xBF1", &strict).unwrap(),
+            "\u{FEFF}1"
+        );
+    }
+}
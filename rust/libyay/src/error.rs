@@ -1,14 +1,52 @@
 //! Error types for YAY parsing.
 
-use thiserror::Error;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::cell::{Cell, RefCell};
+use core::fmt;
+#[cfg(feature = "std")]
+use std::path::PathBuf;
+
+use crate::options::{DuplicateKeys, ParseObserver};
+use crate::quantity::Quantities;
+use crate::report::{DuplicateKey, Relaxations};
+use crate::{CancellationToken, ParseOptions, Value};
 
 /// Result type for YAY parsing operations.
-pub type Result<T> = std::result::Result<T, ParseError>;
+pub type Result<T> = core::result::Result<T, ParseError>;
 
-/// Parse context carrying filename for error reporting.
+/// Parse context carrying the filename for error reporting and the
+/// grammar extensions turned on by [`ParseOptions`].
 #[derive(Clone, Debug)]
-pub struct ParseContext {
+pub(crate) struct ParseContext {
     pub filename: Option<String>,
+    pub quantities: Quantities,
+    pub decimals: bool,
+    pub hex_floats: bool,
+    pub version_directive: bool,
+    #[cfg(feature = "std")]
+    pub bytes_root: Option<PathBuf>,
+    pub duplicate_keys: DuplicateKeys,
+    pub empty_values: bool,
+    /// The extensions used so far, for [`ParseReport`](crate::ParseReport).
+    pub relaxations: Cell<Relaxations>,
+    /// The repeated keys found so far with [`DuplicateKeys::Warn`].
+    pub duplicates: RefCell<Vec<DuplicateKey>>,
+    /// The version a `%yay` directive declared, once read.
+    pub version: Cell<Option<u32>>,
+    pub max_bytes: Option<usize>,
+    pub max_depth: Option<usize>,
+    pub max_nodes: Option<usize>,
+    /// How many arrays and objects enclose the one being parsed.
+    pub depth: Cell<usize>,
+    /// How many values the parse has added to arrays and objects so far.
+    pub values: Cell<usize>,
+    /// Where to report the parse's progress, from [`ParseContext::observed`].
+    pub observer: Option<Arc<dyn ParseObserver>>,
+    /// Stops the parse once it is cancelled.
+    pub cancel: Option<CancellationToken>,
 }
 
 impl ParseContext {
@@ -16,9 +54,141 @@ impl ParseContext {
     pub fn new(filename: Option<&str>) -> Self {
         Self {
             filename: filename.map(String::from),
+            quantities: Quantities::Off,
+            decimals: false,
+            hex_floats: false,
+            version_directive: false,
+            #[cfg(feature = "std")]
+            bytes_root: None,
+            duplicate_keys: DuplicateKeys::default(),
+            empty_values: false,
+            relaxations: Cell::default(),
+            duplicates: RefCell::default(),
+            version: Cell::default(),
+            max_bytes: None,
+            max_depth: None,
+            max_nodes: None,
+            depth: Cell::default(),
+            values: Cell::default(),
+            observer: None,
+            cancel: None,
         }
     }
 
+    /// Create a parse context for `options`.
+    pub fn from_options(options: &ParseOptions) -> Self {
+        Self {
+            filename: options.filename.clone(),
+            quantities: options.quantities.clone(),
+            decimals: options.decimals,
+            hex_floats: options.hex_floats,
+            version_directive: options.version_directive,
+            #[cfg(feature = "std")]
+            bytes_root: options.bytes_root.clone(),
+            duplicate_keys: options.duplicate_keys,
+            empty_values: options.empty_values,
+            relaxations: Cell::default(),
+            duplicates: RefCell::default(),
+            version: Cell::default(),
+            max_bytes: options.max_bytes,
+            max_depth: options.max_depth,
+            max_nodes: options.max_nodes,
+            depth: Cell::default(),
+            values: Cell::default(),
+            observer: None,
+            cancel: options.cancel.clone(),
+        }
+    }
+
+    /// Create a parse context for `options` that reports to
+    /// [`ParseOptions::observer`], for the parsers that read a whole
+    /// document at once.
+    pub fn observed(options: &ParseOptions) -> Self {
+        Self {
+            observer: options.observer.clone(),
+            ..Self::from_options(options)
+        }
+    }
+
+    /// Fail if the parse has been cancelled, at `line_num`, 0-based.
+    pub fn check_cancelled(&self, line_num: usize) -> Result<()> {
+        match &self.cancel {
+            Some(token) if token.is_cancelled() => {
+                Err(ParseError::Cancelled(String::new()).with_location(self, line_num, 0))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Report the line at `line_num`, 0-based, of `lines` to the observer.
+    pub fn observe_line(&self, line_num: usize, lines: usize) -> Result<()> {
+        match &self.observer {
+            Some(observer) if observer.on_line(line_num + 1, lines).is_break() => {
+                Err(ParseError::Cancelled(String::new()).with_location(self, line_num, 0))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Report a value at the top of the document to the observer.
+    pub fn observe_value(&self, path: &str, value: &Value) -> Result<()> {
+        match &self.observer {
+            Some(observer) if observer.on_value(path, value).is_break() => {
+                Err(ParseError::Cancelled(self.file_suffix()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Report the error, if the parse failed, to the observer.
+    pub fn observe_result<T>(&self, result: Result<T>) -> Result<T> {
+        if let (Some(observer), Err(e)) = (&self.observer, &result) {
+            observer.on_error(e);
+        }
+        result
+    }
+
+    /// Enter an array or object at `line` and `col`, failing if it nests
+    /// deeper than `max_depth`. The nesting ends when the guard drops.
+    ///
+    /// This bounds recursion while parsing, so a deeply nested document
+    /// fails before it can exhaust the stack. A few block forms are only
+    /// seen to be containers once parsed, so the depth of the whole value
+    /// is checked again at the end.
+    pub fn enter(&self, line: usize, col: usize) -> Result<Nesting<'_>> {
+        let depth = self.depth.get() + 1;
+        if let Some(max) = self.max_depth {
+            if depth > max {
+                return Err(ParseError::TooDeep(max, String::new()).with_location(self, line, col));
+            }
+        }
+        self.depth.set(depth);
+        Ok(Nesting(&self.depth))
+    }
+
+    /// Count a value added to an array or object at `line` and `col`,
+    /// failing once the document, its root included, has more than
+    /// `max_nodes` values.
+    pub fn count_value(&self, line: usize, col: usize) -> Result<()> {
+        let values = self.values.get() + 1;
+        if let Some(max) = self.max_nodes {
+            if values >= max {
+                return Err(
+                    ParseError::TooManyNodes(max, String::new()).with_location(self, line, col)
+                );
+            }
+        }
+        self.values.set(values);
+        Ok(())
+    }
+
+    /// Record that the document used an extension.
+    pub fn relaxed(&self, relax: impl FnOnce(&mut Relaxations)) {
+        let mut relaxations = self.relaxations.get();
+        relax(&mut relaxations);
+        self.relaxations.set(relaxations);
+    }
+
     /// Format a location suffix for error messages.
     pub fn loc_suffix(&self, line: usize, col: usize) -> String {
         match &self.filename {
@@ -26,158 +196,406 @@ impl ParseContext {
             None => String::new(),
         }
     }
+
+    /// Format a file suffix for error messages without a line and column.
+    pub fn file_suffix(&self) -> String {
+        match &self.filename {
+            Some(name) => format!(" of <{}>", name),
+            None => String::new(),
+        }
+    }
+}
+
+/// An array or object being parsed, from [`ParseContext::enter`].
+pub(crate) struct Nesting<'a>(&'a Cell<usize>);
+
+impl Drop for Nesting<'_> {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() - 1);
+    }
 }
 
 /// Error type for YAY parsing.
-#[derive(Error, Debug)]
+#[derive(Debug, Clone)]
+#[non_exhaustive]
 pub enum ParseError {
     /// Illegal BOM at start of file.
-    #[error("Illegal BOM{0}")]
     IllegalBom(String),
 
+    /// Input bytes that are not valid in their (detected) encoding.
+    InvalidEncoding(&'static str, usize, String),
+
     /// Illegal surrogate code point.
-    #[error("Illegal surrogate{0}")]
     IllegalSurrogate(String),
 
     /// Forbidden code point.
-    #[error("Forbidden code point U+{0:04X}{1}")]
     ForbiddenCodePoint(u32, String),
 
     /// Tab character found where spaces expected.
-    #[error("Tab not allowed (use spaces){0}")]
     TabNotAllowed(String),
 
     /// Trailing space on a line.
-    #[error("Unexpected trailing space{0}")]
     TrailingSpace(String),
 
     /// Unexpected leading space.
-    #[error("Unexpected leading space{0}")]
     LeadingSpace(String),
 
     /// Unexpected indent.
-    #[error("Unexpected indent{0}")]
     UnexpectedIndent(String),
 
     /// Unexpected character.
-    #[error("Unexpected character \"{0}\"{1}")]
     UnexpectedChar(char, String),
 
     /// Unterminated string.
-    #[error("Unterminated string{0}")]
     UnterminatedString(String),
 
     /// Bad character in string.
-    #[error("Bad character in string{0}")]
     BadCharInString(String),
 
     /// Bad escaped character.
-    #[error("Bad escaped character{0}")]
     BadEscapedChar(String),
 
     /// Bad Unicode escape.
-    #[error("Bad Unicode escape{0}")]
     BadUnicodeEscape(String),
 
     /// Unicode code point out of range.
-    #[error("Unicode code point out of range{0}")]
     UnicodeOutOfRange(String),
 
     /// Odd number of hex digits in byte literal.
-    #[error("Odd number of hex digits in byte literal{0}")]
     OddHexDigits(String),
 
     /// Invalid hex digit.
-    #[error("Invalid hex digit{0}")]
     InvalidHexDigit(String),
 
     /// Uppercase hex digit (must be lowercase).
-    #[error("Uppercase hex digit (use lowercase){0}")]
     UppercaseHex(String),
 
     /// Uppercase exponent (must be lowercase).
-    #[error("Uppercase exponent (use lowercase 'e'){0}")]
     UppercaseExponent(String),
 
     /// Unexpected newline in inline construct.
-    #[error("Unexpected newline in inline {0}{1}")]
     UnexpectedNewline(String, String),
 
     /// Unexpected extra content after value.
-    #[error("Unexpected extra content{0}")]
     ExtraContent(String),
 
     /// Invalid number format.
-    #[error("Invalid number{0}")]
     InvalidNumber(String),
 
     /// Expected colon after key.
-    #[error("Expected colon after key{0}")]
     ExpectedColon(String),
 
     /// Invalid key.
-    #[error("Invalid key{0}")]
     InvalidKey(String),
 
     /// Unmatched bracket.
-    #[error("Unmatched bracket{0}")]
     UnmatchedBracket(String),
 
     /// Unmatched brace.
-    #[error("Unmatched brace{0}")]
     UnmatchedBrace(String),
 
     /// Unmatched angle bracket.
-    #[error("Unmatched angle bracket{0}")]
     UnmatchedAngle(String),
 
     /// Unexpected space after character.
-    #[error("Unexpected space after \"{0}\"{1}")]
     UnexpectedSpaceAfter(String, String),
 
     /// Unexpected space before character.
-    #[error("Unexpected space before \"{0}\"{1}")]
     UnexpectedSpaceBefore(String, String),
 
     /// Expected space after character.
-    #[error("Expected space after \"{0}\"{1}")]
     ExpectedSpaceAfter(String, String),
 
     /// No value found in document.
-    #[error("No value found in document{0}")]
     NoValueFound(String),
 
     /// Unexpected space in number.
-    #[error("Unexpected space in number{0}")]
     UnexpectedSpaceInNumber(String),
 
     /// Invalid key character.
-    #[error("Invalid key character{0}")]
     InvalidKeyChar(String),
 
+    /// A `<@ "file">` byte literal that cannot be read.
+    IncludeBytes(String, String, String),
+
+    /// Quantity unit not among the allowed units.
+    UnknownUnit(String, String),
+
+    /// A key that appears more than once in one object, with the line and
+    /// column of each occurrence, 1-based, and the file suffix.
+    DuplicateKey(String, String, String, String),
+
+    /// A document larger than [`ParseOptions::max_bytes`].
+    TooLarge(usize, String),
+
+    /// Arrays and objects nested deeper than [`ParseOptions::max_depth`].
+    TooDeep(usize, String),
+
+    /// A document with more values than [`ParseOptions::max_nodes`].
+    TooManyNodes(usize, String),
+
+    /// A parse that a [`ParseObserver`] or a [`CancellationToken`] stopped.
+    Cancelled(String),
+
+    /// A first line beginning with `%` that is not a `%yay` directive.
+    InvalidDirective(String),
+
+    /// A `%yay` directive for a version of YAY this library does not read.
+    UnsupportedVersion(String, String),
+
     /// Expected newline after block leader in property.
-    #[error("Expected newline after block leader in property")]
     ExpectedNewlineAfterBlockLeader,
 
     /// Expected hex or comment in hex block.
-    #[error("Expected hex or comment in hex block")]
     ExpectedHexInBlock,
 
     /// Expected value after property.
-    #[error("Expected value after property{0}")]
     ExpectedValueAfterProperty(String),
 
+    /// The input could not be read.
+    Read(String, String),
+
     /// Generic parse error.
-    #[error("{0}")]
     Generic(String),
 }
 
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::IllegalBom(suffix) => write!(f, "Illegal BOM{}", suffix),
+            ParseError::InvalidEncoding(encoding, offset, suffix) => write!(
+                f,
+                "Invalid {} at byte offset {}{}",
+                encoding, offset, suffix
+            ),
+            ParseError::IllegalSurrogate(suffix) => write!(f, "Illegal surrogate{}", suffix),
+            ParseError::ForbiddenCodePoint(cp, suffix) => {
+                write!(f, "Forbidden code point U+{:04X}{}", cp, suffix)
+            }
+            ParseError::TabNotAllowed(suffix) => {
+                write!(f, "Tab not allowed (use spaces){}", suffix)
+            }
+            ParseError::TrailingSpace(suffix) => write!(f, "Unexpected trailing space{}", suffix),
+            ParseError::LeadingSpace(suffix) => write!(f, "Unexpected leading space{}", suffix),
+            ParseError::UnexpectedIndent(suffix) => write!(f, "Unexpected indent{}", suffix),
+            ParseError::UnexpectedChar(c, suffix) => {
+                write!(f, "Unexpected character \"{}\"{}", c, suffix)
+            }
+            ParseError::UnterminatedString(suffix) => write!(f, "Unterminated string{}", suffix),
+            ParseError::BadCharInString(suffix) => write!(f, "Bad character in string{}", suffix),
+            ParseError::BadEscapedChar(suffix) => write!(f, "Bad escaped character{}", suffix),
+            ParseError::BadUnicodeEscape(suffix) => write!(f, "Bad Unicode escape{}", suffix),
+            ParseError::UnicodeOutOfRange(suffix) => {
+                write!(f, "Unicode code point out of range{}", suffix)
+            }
+            ParseError::OddHexDigits(suffix) => {
+                write!(f, "Odd number of hex digits in byte literal{}", suffix)
+            }
+            ParseError::InvalidHexDigit(suffix) => write!(f, "Invalid hex digit{}", suffix),
+            ParseError::UppercaseHex(suffix) => {
+                write!(f, "Uppercase hex digit (use lowercase){}", suffix)
+            }
+            ParseError::UppercaseExponent(suffix) => {
+                write!(f, "Uppercase exponent (use lowercase 'e'){}", suffix)
+            }
+            ParseError::UnexpectedNewline(kind, suffix) => {
+                write!(f, "Unexpected newline in inline {}{}", kind, suffix)
+            }
+            ParseError::ExtraContent(suffix) => write!(f, "Unexpected extra content{}", suffix),
+            ParseError::InvalidNumber(suffix) => write!(f, "Invalid number{}", suffix),
+            ParseError::ExpectedColon(suffix) => write!(f, "Expected colon after key{}", suffix),
+            ParseError::InvalidKey(suffix) => write!(f, "Invalid key{}", suffix),
+            ParseError::UnmatchedBracket(suffix) => write!(f, "Unmatched bracket{}", suffix),
+            ParseError::UnmatchedBrace(suffix) => write!(f, "Unmatched brace{}", suffix),
+            ParseError::UnmatchedAngle(suffix) => write!(f, "Unmatched angle bracket{}", suffix),
+            ParseError::UnexpectedSpaceAfter(s, suffix) => {
+                write!(f, "Unexpected space after \"{}\"{}", s, suffix)
+            }
+            ParseError::UnexpectedSpaceBefore(s, suffix) => {
+                write!(f, "Unexpected space before \"{}\"{}", s, suffix)
+            }
+            ParseError::ExpectedSpaceAfter(s, suffix) => {
+                write!(f, "Expected space after \"{}\"{}", s, suffix)
+            }
+            ParseError::NoValueFound(suffix) => write!(f, "No value found in document{}", suffix),
+            ParseError::UnexpectedSpaceInNumber(suffix) => {
+                write!(f, "Unexpected space in number{}", suffix)
+            }
+            ParseError::InvalidKeyChar(suffix) => write!(f, "Invalid key character{}", suffix),
+            ParseError::IncludeBytes(path, reason, suffix) => {
+                write!(f, "Cannot include \"{}\": {}{}", path, reason, suffix)
+            }
+            ParseError::UnknownUnit(unit, suffix) => {
+                write!(f, "Unknown unit \"{}\"{}", unit, suffix)
+            }
+            ParseError::DuplicateKey(key, at, first, suffix) => write!(
+                f,
+                "Duplicate key \"{}\" at {}, first at {}{}",
+                key, at, first, suffix
+            ),
+            ParseError::TooLarge(max, suffix) => {
+                write!(f, "Document is larger than {} bytes{}", max, suffix)
+            }
+            ParseError::TooDeep(max, suffix) => {
+                write!(f, "Document nests deeper than {} levels{}", max, suffix)
+            }
+            ParseError::TooManyNodes(max, suffix) => {
+                write!(f, "Document has more than {} values{}", max, suffix)
+            }
+            ParseError::Cancelled(suffix) => write!(f, "Parse cancelled{}", suffix),
+            ParseError::InvalidDirective(suffix) => {
+                write!(f, "Invalid directive (expected \"%yay 1\"){}", suffix)
+            }
+            ParseError::UnsupportedVersion(version, suffix) => {
+                write!(f, "Unsupported YAY version {}{}", version, suffix)
+            }
+            ParseError::ExpectedNewlineAfterBlockLeader => {
+                f.write_str("Expected newline after block leader in property")
+            }
+            ParseError::ExpectedHexInBlock => f.write_str("Expected hex or comment in hex block"),
+            ParseError::ExpectedValueAfterProperty(suffix) => {
+                write!(f, "Expected value after property{}", suffix)
+            }
+            ParseError::Read(what, reason) => write!(f, "Error reading {}: {}", what, reason),
+            ParseError::Generic(message) => f.write_str(message),
+        }
+    }
+}
+
+impl core::error::Error for ParseError {}
+
 impl ParseError {
+    /// A short, stable name for the kind of error, like `tab_not_allowed`
+    /// for [`ParseError::TabNotAllowed`], for counting errors by kind
+    /// without their messages.
+    ///
+    /// ```
+    /// let error = libyay::parse("a:\t1").unwrap_err();
+    /// assert_eq!(error.code(), "tab_not_allowed");
+    /// ```
+    pub fn code(&self) -> &'static str {
+        match self {
+            ParseError::IllegalBom(..) => "illegal_bom",
+            ParseError::InvalidEncoding(..) => "invalid_encoding",
+            ParseError::IllegalSurrogate(..) => "illegal_surrogate",
+            ParseError::ForbiddenCodePoint(..) => "forbidden_code_point",
+            ParseError::TabNotAllowed(..) => "tab_not_allowed",
+            ParseError::TrailingSpace(..) => "trailing_space",
+            ParseError::LeadingSpace(..) => "leading_space",
+            ParseError::UnexpectedIndent(..) => "unexpected_indent",
+            ParseError::UnexpectedChar(..) => "unexpected_char",
+            ParseError::UnterminatedString(..) => "unterminated_string",
+            ParseError::BadCharInString(..) => "bad_char_in_string",
+            ParseError::BadEscapedChar(..) => "bad_escaped_char",
+            ParseError::BadUnicodeEscape(..) => "bad_unicode_escape",
+            ParseError::UnicodeOutOfRange(..) => "unicode_out_of_range",
+            ParseError::OddHexDigits(..) => "odd_hex_digits",
+            ParseError::InvalidHexDigit(..) => "invalid_hex_digit",
+            ParseError::UppercaseHex(..) => "uppercase_hex",
+            ParseError::UppercaseExponent(..) => "uppercase_exponent",
+            ParseError::UnexpectedNewline(..) => "unexpected_newline",
+            ParseError::ExtraContent(..) => "extra_content",
+            ParseError::InvalidNumber(..) => "invalid_number",
+            ParseError::ExpectedColon(..) => "expected_colon",
+            ParseError::InvalidKey(..) => "invalid_key",
+            ParseError::UnmatchedBracket(..) => "unmatched_bracket",
+            ParseError::UnmatchedBrace(..) => "unmatched_brace",
+            ParseError::UnmatchedAngle(..) => "unmatched_angle",
+            ParseError::UnexpectedSpaceAfter(..) => "unexpected_space_after",
+            ParseError::UnexpectedSpaceBefore(..) => "unexpected_space_before",
+            ParseError::ExpectedSpaceAfter(..) => "expected_space_after",
+            ParseError::NoValueFound(..) => "no_value_found",
+            ParseError::UnexpectedSpaceInNumber(..) => "unexpected_space_in_number",
+            ParseError::InvalidKeyChar(..) => "invalid_key_char",
+            ParseError::IncludeBytes(..) => "include_bytes",
+            ParseError::UnknownUnit(..) => "unknown_unit",
+            ParseError::DuplicateKey(..) => "duplicate_key",
+            ParseError::TooLarge(..) => "too_large",
+            ParseError::TooDeep(..) => "too_deep",
+            ParseError::TooManyNodes(..) => "too_many_nodes",
+            ParseError::Cancelled(..) => "cancelled",
+            ParseError::InvalidDirective(..) => "invalid_directive",
+            ParseError::UnsupportedVersion(..) => "unsupported_version",
+            ParseError::ExpectedNewlineAfterBlockLeader => "expected_newline_after_block_leader",
+            ParseError::ExpectedHexInBlock => "expected_hex_in_block",
+            ParseError::ExpectedValueAfterProperty(..) => "expected_value_after_property",
+            ParseError::Read(..) => "read",
+            ParseError::Generic(..) => "generic",
+        }
+    }
+
+    /// The one-based line and column the message gives, which it does when
+    /// [`ParseOptions::filename`] is set.
+    ///
+    /// ```
+    /// use libyay::{parse, parse_with_options, ParseOptions};
+    ///
+    /// let mut options = ParseOptions::default();
+    /// options.filename = Some("a.yay".into());
+    /// let error = parse_with_options("a: 1\n\tb: 2\n", &options).unwrap_err();
+    /// assert_eq!(error.location(), Some((2, 1)));
+    /// assert_eq!(parse("a: 1\n\tb: 2\n").unwrap_err().location(), None);
+    /// ```
+    pub fn location(&self) -> Option<(usize, usize)> {
+        let message = self.to_string();
+        message.match_indices(" at ").find_map(|(at, _)| {
+            let (position, _) = message[at + 4..].split_once(" of <")?;
+            let (line, col) = position.split_once(':')?;
+            Some((line.parse().ok()?, col.parse().ok()?))
+        })
+    }
+
+    /// Advice for an unexpected character that begins a bare word in
+    /// `source`, like `did you mean true?` for `ture`, from
+    /// [`suggest_bare_word`](crate::suggest_bare_word). The message itself
+    /// stays the one every YAY implementation gives, so this needs the
+    /// location that [`ParseOptions::filename`] adds.
+    ///
+    /// ```
+    /// use libyay::{parse_with_options, ParseOptions};
+    ///
+    /// let mut options = ParseOptions::default();
+    /// options.filename = Some("a.yay".into());
+    /// let source = "debug: fasle  # for now\n";
+    /// let error = parse_with_options(source, &options).unwrap_err();
+    /// assert_eq!(error.to_string(), "Unexpected character \"f\" at 1:8 of <a.yay>");
+    /// assert_eq!(error.suggestion(source).as_deref(), Some("did you mean false?"));
+    /// ```
+    pub fn suggestion(&self, source: &str) -> Option<String> {
+        let ParseError::UnexpectedChar(first, _) = self else {
+            return None;
+        };
+        let (line, col) = self.location()?;
+        let rest: String = source
+            .lines()
+            .nth(line - 1)?
+            .chars()
+            .skip(col - 1)
+            .collect();
+        // The column of an item, or of a property in one, is that of its
+        // bullet, so the word is the first that begins with the character.
+        let mut rest = rest.as_str();
+        while let Some(item) = rest.strip_prefix("- ") {
+            rest = item.trim_start();
+        }
+        let rest = &rest[rest.find(*first)?..];
+        let end = rest
+            .find([',', ']', '}'])
+            .into_iter()
+            .chain(rest.find(" #"))
+            .min()
+            .unwrap_or(rest.len());
+        crate::suggest_bare_word(rest[..end].trim_end())
+    }
+
     /// Create an error with location information.
-    pub fn with_location(self, ctx: &ParseContext, line: usize, col: usize) -> Self {
+    pub(crate) fn with_location(self, ctx: &ParseContext, line: usize, col: usize) -> Self {
         let suffix = ctx.loc_suffix(line, col);
         match self {
             ParseError::IllegalBom(_) => ParseError::IllegalBom(suffix),
+            ParseError::InvalidEncoding(name, offset, file) => {
+                ParseError::InvalidEncoding(name, offset, file)
+            }
             ParseError::IllegalSurrogate(_) => ParseError::IllegalSurrogate(suffix),
             ParseError::ForbiddenCodePoint(cp, _) => ParseError::ForbiddenCodePoint(cp, suffix),
             ParseError::TabNotAllowed(_) => ParseError::TabNotAllowed(suffix),
@@ -208,6 +626,21 @@ impl ParseError {
             ParseError::NoValueFound(_) => ParseError::NoValueFound(suffix),
             ParseError::UnexpectedSpaceInNumber(_) => ParseError::UnexpectedSpaceInNumber(suffix),
             ParseError::InvalidKeyChar(_) => ParseError::InvalidKeyChar(suffix),
+            ParseError::IncludeBytes(path, reason, _) => {
+                ParseError::IncludeBytes(path, reason, suffix)
+            }
+            ParseError::UnknownUnit(unit, _) => ParseError::UnknownUnit(unit, suffix),
+            ParseError::DuplicateKey(key, at, first, file) => {
+                ParseError::DuplicateKey(key, at, first, file)
+            }
+            ParseError::TooLarge(max, _) => ParseError::TooLarge(max, suffix),
+            ParseError::TooDeep(max, _) => ParseError::TooDeep(max, suffix),
+            ParseError::TooManyNodes(max, _) => ParseError::TooManyNodes(max, suffix),
+            ParseError::Cancelled(_) => ParseError::Cancelled(suffix),
+            ParseError::InvalidDirective(_) => ParseError::InvalidDirective(suffix),
+            ParseError::UnsupportedVersion(version, _) => {
+                ParseError::UnsupportedVersion(version, suffix)
+            }
             ParseError::ExpectedNewlineAfterBlockLeader => {
                 ParseError::ExpectedNewlineAfterBlockLeader
             }
@@ -215,6 +648,7 @@ impl ParseError {
             ParseError::ExpectedValueAfterProperty(_) => {
                 ParseError::ExpectedValueAfterProperty(suffix)
             }
+            ParseError::Read(what, reason) => ParseError::Read(what, reason),
             ParseError::Generic(msg) => ParseError::Generic(format!("{}{}", msg, suffix)),
         }
     }
@@ -0,0 +1,714 @@
+//! Computed values.
+//!
+//! A value derived from others in the same document can be written as an
+//! object whose only member is `$eval`, an expression:
+//!
+//! ```yay
+//! base_port: 8000
+//! workers:
+//! - index: 1
+//!   port:
+//!     "$eval": "base_port + index"
+//! - index: 2
+//!   port:
+//!     "$eval": "base_port + index"
+//! url:
+//!   "$eval": '"http://localhost:" + workers[0].port'
+//! ```
+//!
+//! [`evaluate`] replaces each such object with the expression's value, so
+//! that the derived values above are `8001`, `8002`, and
+//! `"http://localhost:8001"`. Nothing is evaluated unless it is asked for:
+//! to every other reader, `$eval` is an ordinary key.
+//!
+//! Expressions have numbers, strings in double or single quotes, names,
+//! parentheses, unary `-`, and the operators `+`, `-`, `*`, `/`, and `%`
+//! with their usual precedence. `+` joins strings, and a number joined to
+//! a string is written as YAY writes it. Integer arithmetic is exact, and
+//! `/` and `%` on integers truncate toward zero, as in Rust; an integer
+//! meeting a float becomes a float.
+//!
+//! A name is a path like `server.port` or `workers[0].port`. Its first key
+//! is looked up in the object that holds the expression, then in each
+//! enclosing object out to the root, so a name finds its nearest
+//! namesake. A name may refer to another computed value, but not, even
+//! indirectly, to itself. Expressions read only numbers and strings of
+//! their own document: there are no functions, and nothing else to reach.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use num_bigint::BigInt;
+use num_traits::{ToPrimitive, Zero};
+
+use crate::encode::encode_yay_inline;
+use crate::Value;
+
+/// The key of a computed value's object.
+const EVAL: &str = "$eval";
+
+/// How deeply expressions may nest, and how long a chain of computed
+/// values referring to computed values may be.
+const MAX_DEPTH: usize = 64;
+
+/// Replace every `$eval` object in `document` with the value of its
+/// expression.
+///
+/// If any expression fails, the document is left as it was and the error
+/// names the path of the expression, like `workers[1].port: no value
+/// named base`.
+///
+/// # Example
+///
+/// ```
+/// use libyay::eval::evaluate;
+/// use libyay::parse;
+///
+/// let mut document = parse("width: 3\nheight: 4\narea:\n  \"$eval\": \"width * height\"").unwrap();
+/// evaluate(&mut document).unwrap();
+/// assert_eq!(document, parse("width: 3\nheight: 4\narea: 12").unwrap());
+/// ```
+pub fn evaluate(document: &mut Value) -> Result<(), String> {
+    let mut expressions = Vec::new();
+    find_expressions(document, &mut Vec::new(), &mut expressions)?;
+    if expressions.is_empty() {
+        return Ok(());
+    }
+
+    let mut evaluator = Evaluator {
+        root: document,
+        done: HashMap::new(),
+        active: Vec::new(),
+    };
+    let mut results = Vec::with_capacity(expressions.len());
+    for path in expressions {
+        let value = evaluator.value_at(&path).map_err(Failure::into_message)?;
+        results.push((path, value));
+    }
+    for (path, value) in results {
+        *node_mut(document, &path) = value;
+    }
+    Ok(())
+}
+
+/// One step of a path: an object key or an array index.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+/// A path like `workers[0].port`, with `(root)` for the root.
+struct PathDisplay<'a>(&'a [Segment]);
+
+impl fmt::Display for PathDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_empty() {
+            return f.write_str("(root)");
+        }
+        for (i, segment) in self.0.iter().enumerate() {
+            match segment {
+                Segment::Key(key) if i == 0 => f.write_str(key)?,
+                Segment::Key(key) => write!(f, ".{}", key)?,
+                Segment::Index(index) => write!(f, "[{}]", index)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The expression of a `$eval` object, `None` for any other value.
+fn expression(value: &Value) -> Result<Option<&str>, String> {
+    let Value::Object(members) = value else {
+        return Ok(None);
+    };
+    match members.get(EVAL) {
+        None => Ok(None),
+        Some(_) if members.len() > 1 => Err(format!("{} must be the only member", EVAL)),
+        Some(Value::String(source)) => Ok(Some(source)),
+        Some(_) => Err(format!("{} must be a string", EVAL)),
+    }
+}
+
+fn find_expressions(
+    value: &Value,
+    path: &mut Vec<Segment>,
+    found: &mut Vec<Vec<Segment>>,
+) -> Result<(), String> {
+    if expression(value)
+        .map_err(|e| format!("{}: {}", PathDisplay(path), e))?
+        .is_some()
+    {
+        found.push(path.clone());
+        return Ok(());
+    }
+    match value {
+        Value::Object(members) => {
+            for (key, member) in members {
+                path.push(Segment::Key(key.clone()));
+                find_expressions(member, path, found)?;
+                path.pop();
+            }
+        }
+        Value::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                path.push(Segment::Index(index));
+                find_expressions(item, path, found)?;
+                path.pop();
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn node_mut<'a>(mut value: &'a mut Value, path: &[Segment]) -> &'a mut Value {
+    for segment in path {
+        value = match (value, segment) {
+            (Value::Object(members), Segment::Key(key)) => members.get_mut(key).unwrap(),
+            (Value::Array(items), Segment::Index(index)) => &mut items[*index],
+            _ => unreachable!("paths are found in the document"),
+        };
+    }
+    value
+}
+
+struct Evaluator<'a> {
+    root: &'a Value,
+    /// Computed values by the path of their expression.
+    done: HashMap<Vec<Segment>, Value>,
+    /// The paths of the expressions being computed, innermost last.
+    active: Vec<Vec<Segment>>,
+}
+
+impl Evaluator<'_> {
+    /// The value at `path`, computing any expression on the way.
+    fn value_at(&mut self, path: &[Segment]) -> Result<Value, Failure> {
+        let mut node = self.root;
+        for (depth, segment) in path.iter().enumerate() {
+            if let Some(source) = expression(node).ok().flatten() {
+                let value = self
+                    .compute(&path[..depth], source)
+                    .map_err(Failure::Elsewhere)?;
+                return Err(Failure::Here(format!(
+                    "{} is {}, which has no {}",
+                    PathDisplay(&path[..depth]),
+                    encode_yay_inline(&value),
+                    PathDisplay(&path[depth..depth + 1])
+                )));
+            }
+            node = match (node, segment) {
+                (Value::Object(members), Segment::Key(key)) => members.get(key),
+                (Value::Array(items), Segment::Index(index)) => items.get(*index),
+                _ => None,
+            }
+            .ok_or_else(|| {
+                Failure::Here(format!("{} does not exist", PathDisplay(&path[..=depth])))
+            })?;
+        }
+        match expression(node).ok().flatten() {
+            Some(source) => self.compute(path, source).map_err(Failure::Elsewhere),
+            None => Ok(node.clone()),
+        }
+    }
+
+    /// The value of the expression at `path`.
+    fn compute(&mut self, path: &[Segment], source: &str) -> Result<Value, String> {
+        if let Some(value) = self.done.get(path) {
+            return Ok(value.clone());
+        }
+        if self.active.iter().any(|active| active == path) {
+            return Err(format!("{} refers to itself", PathDisplay(path)));
+        }
+        if self.active.len() >= MAX_DEPTH {
+            return Err(format!(
+                "{}: more than {} computed values refer to each other in turn",
+                PathDisplay(path),
+                MAX_DEPTH
+            ));
+        }
+        self.active.push(path.to_vec());
+        let result = Parser::new(source)
+            .parse()
+            .and_then(|expr| self.eval(&expr, path));
+        self.active.pop();
+        // Report an error where it happened, not at each expression that
+        // refers to it.
+        let value = match result {
+            Ok(value) => value,
+            Err(Failure::Here(e)) => return Err(format!("{}: {}", PathDisplay(path), e)),
+            Err(Failure::Elsewhere(e)) => return Err(e),
+        };
+        self.done.insert(path.to_vec(), value.clone());
+        Ok(value)
+    }
+
+    fn eval(&mut self, expr: &Expr, path: &[Segment]) -> Result<Value, Failure> {
+        match expr {
+            Expr::Literal(value) => Ok(value.clone()),
+            Expr::Name(name) => {
+                let value = self.lookup(name, path)?;
+                match value {
+                    Value::Integer(_) | Value::Float(_) | Value::String(_) => Ok(value),
+                    other => Err(Failure::Here(format!(
+                        "{} is {}, not a number or string",
+                        PathDisplay(name),
+                        encode_yay_inline(&other)
+                    ))),
+                }
+            }
+            Expr::Negate(operand) => match self.eval(operand, path)? {
+                Value::Integer(n) => Ok(Value::Integer(-n)),
+                Value::Float(f) => Ok(Value::Float(-f)),
+                _ => Err(Failure::Here("cannot negate a string".to_string())),
+            },
+            Expr::Binary(op, left, right) => {
+                let left = self.eval(left, path)?;
+                let right = self.eval(right, path)?;
+                op.apply(left, right).map_err(Failure::Here)
+            }
+        }
+    }
+
+    /// The value `name` refers to from the expression at `path`.
+    fn lookup(&mut self, name: &[Segment], path: &[Segment]) -> Result<Value, Failure> {
+        let Segment::Key(first) = &name[0] else {
+            unreachable!("names start with a key");
+        };
+        for scope in (0..path.len()).rev() {
+            let mut node = self.root;
+            for segment in &path[..scope] {
+                node = match (node, segment) {
+                    (Value::Object(members), Segment::Key(key)) => &members[key.as_str()],
+                    (Value::Array(items), Segment::Index(index)) => &items[*index],
+                    _ => unreachable!("enclosing values are containers"),
+                };
+            }
+            if let Value::Object(members) = node {
+                if members.contains_key(first.as_str()) {
+                    let mut target = path[..scope].to_vec();
+                    target.extend_from_slice(name);
+                    return self.value_at(&target);
+                }
+            }
+        }
+        Err(Failure::Here(format!("no value named {}", first)))
+    }
+}
+
+/// Why an expression failed: for a reason of its own, or because a value
+/// it refers to failed, which has already said where.
+enum Failure {
+    Here(String),
+    Elsewhere(String),
+}
+
+impl Failure {
+    fn into_message(self) -> String {
+        match self {
+            Failure::Here(message) | Failure::Elsewhere(message) => message,
+        }
+    }
+}
+
+enum Expr {
+    Literal(Value),
+    Name(Vec<Segment>),
+    Negate(Box<Expr>),
+    Binary(Op, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Clone, Copy)]
+enum Op {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+}
+
+impl Op {
+    fn apply(self, left: Value, right: Value) -> Result<Value, String> {
+        match (self, left, right) {
+            (Op::Add, Value::String(mut a), b) => {
+                a.push_str(&text(&b));
+                Ok(Value::String(a))
+            }
+            (Op::Add, a, Value::String(b)) => Ok(Value::String(text(&a) + &b)),
+            (_, Value::String(_), _) | (_, _, Value::String(_)) => {
+                Err(format!("{} needs numbers, not strings", self.symbol()))
+            }
+            (Op::Divide | Op::Remainder, Value::Integer(_), Value::Integer(b)) if b.is_zero() => {
+                Err("division by zero".to_string())
+            }
+            (op, Value::Integer(a), Value::Integer(b)) => Ok(Value::Integer(match op {
+                Op::Add => a + b,
+                Op::Subtract => a - b,
+                Op::Multiply => a * b,
+                Op::Divide => a / b,
+                Op::Remainder => a % b,
+            })),
+            (op, a, b) => {
+                let (a, b) = (float(&a), float(&b));
+                Ok(Value::Float(match op {
+                    Op::Add => a + b,
+                    Op::Subtract => a - b,
+                    Op::Multiply => a * b,
+                    Op::Divide => a / b,
+                    Op::Remainder => a % b,
+                }))
+            }
+        }
+    }
+
+    fn symbol(self) -> &'static str {
+        match self {
+            Op::Add => "+",
+            Op::Subtract => "-",
+            Op::Multiply => "*",
+            Op::Divide => "/",
+            Op::Remainder => "%",
+        }
+    }
+}
+
+/// A string as it is, or a number as YAY writes it.
+fn text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => encode_yay_inline(other),
+    }
+}
+
+fn float(value: &Value) -> f64 {
+    match value {
+        Value::Integer(n) => n.to_f64().unwrap_or(f64::NAN),
+        Value::Float(f) => *f,
+        _ => unreachable!("operands are numbers or strings"),
+    }
+}
+
+/// A recursive descent parser for one expression.
+struct Parser<'a> {
+    source: &'a str,
+    pos: usize,
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(source: &'a str) -> Self {
+        Parser {
+            source,
+            pos: 0,
+            depth: 0,
+        }
+    }
+
+    fn parse(mut self) -> Result<Expr, Failure> {
+        let expr = self.sum().map_err(Failure::Here)?;
+        self.skip_spaces();
+        match self.peek() {
+            None => Ok(expr),
+            Some(c) => Err(Failure::Here(self.unexpected(c))),
+        }
+    }
+
+    fn sum(&mut self) -> Result<Expr, String> {
+        let mut expr = self.product()?;
+        loop {
+            self.skip_spaces();
+            let op = match self.peek() {
+                Some('+') => Op::Add,
+                Some('-') => Op::Subtract,
+                _ => return Ok(expr),
+            };
+            self.pos += 1;
+            expr = Expr::Binary(op, Box::new(expr), Box::new(self.product()?));
+        }
+    }
+
+    fn product(&mut self) -> Result<Expr, String> {
+        let mut expr = self.unary()?;
+        loop {
+            self.skip_spaces();
+            let op = match self.peek() {
+                Some('*') => Op::Multiply,
+                Some('/') => Op::Divide,
+                Some('%') => Op::Remainder,
+                _ => return Ok(expr),
+            };
+            self.pos += 1;
+            expr = Expr::Binary(op, Box::new(expr), Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(format!("expression nested more than {} deep", MAX_DEPTH));
+        }
+        self.skip_spaces();
+        let expr = match self.peek() {
+            Some('-') => {
+                self.pos += 1;
+                Expr::Negate(Box::new(self.unary()?))
+            }
+            Some('(') => {
+                self.pos += 1;
+                let expr = self.sum()?;
+                self.skip_spaces();
+                match self.peek() {
+                    Some(')') => self.pos += 1,
+                    Some(c) => return Err(self.unexpected(c)),
+                    None => return Err("missing )".to_string()),
+                }
+                expr
+            }
+            Some(quote @ ('"' | '\'')) => Expr::Literal(Value::String(self.string(quote)?)),
+            Some(c) if c.is_ascii_digit() || c == '.' => Expr::Literal(self.number()?),
+            Some(c) if c.is_ascii_alphabetic() || c == '_' => Expr::Name(self.name()?),
+            Some(c) => return Err(self.unexpected(c)),
+            None => return Err("expression ends where a value should be".to_string()),
+        };
+        self.depth -= 1;
+        Ok(expr)
+    }
+
+    fn number(&mut self) -> Result<Value, String> {
+        let start = self.pos;
+        self.take_while(|c| c.is_ascii_digit());
+        let mut float = false;
+        if self.peek() == Some('.') {
+            float = true;
+            self.pos += 1;
+            self.take_while(|c| c.is_ascii_digit());
+        }
+        if matches!(self.peek(), Some('e' | 'E')) {
+            float = true;
+            self.pos += 1;
+            if matches!(self.peek(), Some('+' | '-')) {
+                self.pos += 1;
+            }
+            self.take_while(|c| c.is_ascii_digit());
+        }
+        let text = &self.source[start..self.pos];
+        let value = if float {
+            text.parse::<f64>().ok().map(Value::Float)
+        } else {
+            text.parse::<BigInt>().ok().map(Value::Integer)
+        };
+        value.ok_or_else(|| format!("invalid number {}", text))
+    }
+
+    fn string(&mut self, quote: char) -> Result<String, String> {
+        self.pos += 1;
+        let mut out = String::new();
+        loop {
+            let Some(c) = self.peek() else {
+                return Err("unterminated string".to_string());
+            };
+            self.pos += c.len_utf8();
+            match c {
+                c if c == quote => return Ok(out),
+                '\\' => {
+                    let escaped = match self.peek() {
+                        Some('n') => '\n',
+                        Some('t') => '\t',
+                        Some(c @ ('\\' | '"' | '\'')) => c,
+                        Some(c) => return Err(format!("unknown escape \\{}", c)),
+                        None => return Err("unterminated string".to_string()),
+                    };
+                    self.pos += 1;
+                    out.push(escaped);
+                }
+                c => out.push(c),
+            }
+        }
+    }
+
+    fn name(&mut self) -> Result<Vec<Segment>, String> {
+        let mut name = vec![Segment::Key(self.key())];
+        loop {
+            match self.peek() {
+                Some('.') => {
+                    self.pos += 1;
+                    match self.peek() {
+                        Some(c) if c.is_ascii_alphabetic() || c == '_' => {}
+                        _ => return Err(format!("expected a key after {}.", PathDisplay(&name))),
+                    }
+                    name.push(Segment::Key(self.key()));
+                }
+                Some('[') => {
+                    self.pos += 1;
+                    let start = self.pos;
+                    self.take_while(|c| c.is_ascii_digit());
+                    let index = self.source[start..self.pos].parse::<usize>();
+                    match (index, self.peek()) {
+                        (Ok(index), Some(']')) => {
+                            self.pos += 1;
+                            name.push(Segment::Index(index));
+                        }
+                        _ => {
+                            return Err(format!("expected an index after {}[", PathDisplay(&name)))
+                        }
+                    }
+                }
+                _ => return Ok(name),
+            }
+        }
+    }
+
+    fn key(&mut self) -> String {
+        let start = self.pos;
+        self.take_while(|c| c.is_ascii_alphanumeric() || c == '_');
+        self.source[start..self.pos].to_string()
+    }
+
+    fn unexpected(&self, c: char) -> String {
+        format!("unexpected {} at column {}", c, self.pos + 1)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.source[self.pos..].chars().next()
+    }
+
+    fn skip_spaces(&mut self) {
+        self.take_while(char::is_whitespace);
+    }
+
+    fn take_while(&mut self, keep: impl Fn(char) -> bool) {
+        while let Some(c) = self.peek().filter(|&c| keep(c)) {
+            self.pos += c.len_utf8();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    fn evaluated(source: &str) -> Result<Value, String> {
+        let mut document = parse(source).unwrap();
+        evaluate(&mut document)?;
+        Ok(document)
+    }
+
+    fn computed(expression: &str) -> Result<Value, String> {
+        let mut document = parse("a: 7\nb: 2\nf: 0.5\ns: \"x\"\nlist: [1, 2]").unwrap();
+        if let Value::Object(members) = &mut document {
+            let mut eval = crate::Map::new();
+            eval.insert(EVAL.to_string(), Value::String(expression.to_string()));
+            members.insert("result".to_string(), Value::Object(eval));
+        }
+        evaluate(&mut document)?;
+        Ok(document.get("result").unwrap().clone())
+    }
+
+    #[test]
+    fn test_expressions() {
+        let int = |n: i64| Ok(Value::from(n));
+        assert_eq!(computed("a + b * 3"), int(13));
+        assert_eq!(computed("(a + b) * 3"), int(27));
+        assert_eq!(computed("-a / b"), int(-3));
+        assert_eq!(computed("-a % b"), int(-1));
+        assert_eq!(computed("a - -b"), int(9));
+        assert_eq!(computed("list[1] * 10"), int(20));
+        assert_eq!(computed("a * f"), Ok(Value::Float(3.5)));
+        assert_eq!(computed("1e3 + 1"), Ok(Value::Float(1001.0)));
+        assert_eq!(
+            computed("99999999999999999999 + 1"),
+            computed("100000000000000000000")
+        );
+        assert_eq!(
+            computed(r#"s + "-" + a + ':' + f"#),
+            Ok(Value::String("x-7:0.5".to_string()))
+        );
+        assert_eq!(
+            computed(r#""say \"hi\"""#),
+            Ok(Value::String("say \"hi\"".to_string()))
+        );
+
+        assert_eq!(
+            computed("a / 0"),
+            Err("result: division by zero".to_string())
+        );
+        assert_eq!(computed("1.0 / 0"), Ok(Value::Float(f64::INFINITY)));
+        assert_eq!(
+            computed("s * 2"),
+            Err("result: * needs numbers, not strings".to_string())
+        );
+        assert_eq!(
+            computed("-s"),
+            Err("result: cannot negate a string".to_string())
+        );
+        assert_eq!(
+            computed("list"),
+            Err("result: list is [1, 2], not a number or string".to_string())
+        );
+        assert_eq!(
+            computed("c + 1"),
+            Err("result: no value named c".to_string())
+        );
+        assert_eq!(
+            computed("list[2]"),
+            Err("result: list[2] does not exist".to_string())
+        );
+        assert_eq!(
+            computed("a +"),
+            Err("result: expression ends where a value should be".to_string())
+        );
+        assert_eq!(
+            computed("a b"),
+            Err("result: unexpected b at column 3".to_string())
+        );
+        assert_eq!(computed("(a"), Err("result: missing )".to_string()));
+        assert_eq!(
+            computed("'a"),
+            Err("result: unterminated string".to_string())
+        );
+        assert_eq!(
+            computed("a.b"),
+            Err("result: a.b does not exist".to_string())
+        );
+        assert!(computed(&"(".repeat(100))
+            .unwrap_err()
+            .contains("nested more than 64"));
+    }
+
+    #[test]
+    fn test_evaluate() {
+        // Names find their nearest namesake, and computed values can refer
+        // to computed values.
+        assert_eq!(
+            evaluated(
+                "base_port: 8000\nindex: 0\nworkers:\n- index: 1\n  port:\n    \"$eval\": \"base_port + index\"\n- index: 2\n  port:\n    \"$eval\": \"base_port + index\"\nurl:\n  \"$eval\": '\"http://localhost:\" + workers[1].port'\nroot_port:\n  \"$eval\": \"base_port + index\""
+            ),
+            Ok(parse(
+                "base_port: 8000\nindex: 0\nworkers:\n- index: 1\n  port: 8001\n- index: 2\n  port: 8002\nurl: \"http://localhost:8002\"\nroot_port: 8000"
+            )
+            .unwrap())
+        );
+        assert_eq!(
+            evaluated("a:\n  \"$eval\": \"b + 1\"\nb:\n  \"$eval\": \"a + 1\""),
+            Err("a refers to itself".to_string())
+        );
+        assert_eq!(
+            evaluated("a:\n  \"$eval\": \"b\"\nb:\n  \"$eval\": \"c\""),
+            Err("b: no value named c".to_string())
+        );
+        assert_eq!(
+            evaluated("a:\n  \"$eval\": 1"),
+            Err("a: $eval must be a string".to_string())
+        );
+        assert_eq!(
+            evaluated("a:\n  \"$eval\": \"1\"\n  b: 2"),
+            Err("a: $eval must be the only member".to_string())
+        );
+        // A failure leaves the document unchanged.
+        let mut document = parse("a:\n  \"$eval\": \"1\"\nb:\n  \"$eval\": \"c\"").unwrap();
+        let before = document.clone();
+        assert!(evaluate(&mut document).is_err());
+        assert_eq!(document, before);
+    }
+}
@@ -0,0 +1,152 @@
+//! Mechanical fixes for strict YAY errors.
+
+use crate::{parse_with_options, ParseError, ParseOptions};
+
+/// A change [`fix_yay`] made to a document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Fix {
+    /// The one-based line changed.
+    pub line: usize,
+    /// What changed, like `"lowercased hex digits"`.
+    pub message: &'static str,
+}
+
+/// Fix the errors in a strict YAY document that have only one possible
+/// repair: uppercase hex digits and exponents, extra spaces after a colon,
+/// and trailing spaces. Each is fixed in place on its own line, so comments
+/// and the rest of the layout are kept.
+///
+/// Fixing stops at the first error of another kind, which parsing the
+/// returned text reports. The fixes are listed in line order.
+///
+/// # Example
+///
+/// ```
+/// use libyay::fix_yay;
+///
+/// let (fixed, fixes) = fix_yay("# Limits\nrate:  1E3 \nkey: <C0FFEE>\n");
+/// assert_eq!(fixed, "# Limits\nrate: 1e3\nkey: <c0ffee>\n");
+/// assert_eq!(fixes.len(), 4);
+/// assert_eq!((fixes[0].line, fixes[0].message), (2, "removed trailing space"));
+/// ```
+pub fn fix_yay(source: &str) -> (String, Vec<Fix>) {
+    let options = ParseOptions {
+        filename: Some("fix".into()),
+        ..ParseOptions::default()
+    };
+    let mut lines: Vec<String> = source.split('\n').map(String::from).collect();
+    let mut fixes = Vec::new();
+    while let Err(error) = parse_with_options(&lines.join("\n"), &options) {
+        let Some((line, col)) = error.location() else {
+            break;
+        };
+        let Some(text) = lines.get(line - 1) else {
+            break;
+        };
+        let Some((fixed, message)) = fix_line(text, col - 1, &error) else {
+            break;
+        };
+        if fixed == *text {
+            break;
+        }
+        lines[line - 1] = fixed;
+        fixes.push(Fix { line, message });
+    }
+    fixes.sort_by_key(|fix| fix.line);
+    (lines.join("\n"), fixes)
+}
+
+/// `line` with the error at `col`, zero-based, fixed.
+fn fix_line(line: &str, col: usize, error: &ParseError) -> Option<(String, &'static str)> {
+    let mut chars: Vec<char> = line.chars().collect();
+    match error {
+        ParseError::TrailingSpace(_) => {
+            let fixed = line.trim_end_matches(' ').to_string();
+            Some((fixed, "removed trailing space"))
+        }
+        ParseError::UnexpectedSpaceAfter(after, _) if after == ":" => {
+            let colon = chars[..col.min(chars.len())]
+                .iter()
+                .rposition(|&c| c == ':')?;
+            let spaces = chars[colon + 1..].iter().take_while(|&&c| c == ' ').count();
+            chars.drain(colon + 2..colon + 1 + spaces);
+            Some((
+                chars.into_iter().collect(),
+                "removed extra space after colon",
+            ))
+        }
+        ParseError::UppercaseExponent(_) => {
+            // An item's column is that of its bullet, so look ahead.
+            let e = (col.max(1)..chars.len()).find(|&i| {
+                chars[i] == 'E' && (chars[i - 1].is_ascii_digit() || chars[i - 1] == '.')
+            })?;
+            chars[e] = 'e';
+            Some((chars.into_iter().collect(), "lowercased exponent"))
+        }
+        ParseError::UppercaseHex(_) => {
+            let comment = line
+                .find(" #")
+                .map_or(chars.len(), |i| line[..i].chars().count());
+            let range = match chars[..comment].iter().rposition(|&c| c == '<') {
+                // Inline bytes, `<...>`.
+                Some(open) if open <= col || chars[..col].iter().all(|&c| c == ' ' || c == '-') => {
+                    let close = open + chars[open..].iter().position(|&c| c == '>')?;
+                    open + 1..close
+                }
+                // A line of block bytes.
+                _ if chars[..comment]
+                    .iter()
+                    .all(|c| c.is_ascii_hexdigit() || *c == ' ') =>
+                {
+                    0..comment
+                }
+                _ => return None,
+            };
+            for c in &mut chars[range] {
+                *c = c.to_ascii_lowercase();
+            }
+            Some((chars.into_iter().collect(), "lowercased hex digits"))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fix_yay() {
+        let source = "# Keys  \nkeys:\n  - <ABCD>\n  - >\n    0A FF  # second\n\
+                      \x20   0b\nrates:\n  - 1.5E3\n  - x: 2E-1\nname:  \"A  B\"   \n";
+        let (fixed, fixes) = fix_yay(source);
+        assert_eq!(
+            fixed,
+            "# Keys\nkeys:\n  - <abcd>\n  - >\n    0a ff  # second\n\
+             \x20   0b\nrates:\n  - 1.5e3\n  - x: 2e-1\nname: \"A  B\"\n"
+        );
+        assert!(crate::parse(&fixed).is_ok());
+        assert_eq!(
+            fixes
+                .iter()
+                .map(|fix| (fix.line, fix.message))
+                .collect::<Vec<_>>(),
+            [
+                (1, "removed trailing space"),
+                (3, "lowercased hex digits"),
+                (5, "lowercased hex digits"),
+                (8, "lowercased exponent"),
+                (9, "lowercased exponent"),
+                (10, "removed trailing space"),
+                (10, "removed extra space after colon"),
+            ]
+        );
+
+        // Other errors stop fixing.
+        let (fixed, fixes) = fix_yay("a: 1E3\nb: hello\nc: 2E3\n");
+        assert_eq!(fixed, "a: 1e3\nb: hello\nc: 2E3\n");
+        assert_eq!(fixes.len(), 1);
+        assert_eq!(fix_yay("a: 1\n"), ("a: 1\n".to_string(), vec![]));
+    }
+}
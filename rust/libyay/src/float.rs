@@ -0,0 +1,329 @@
+//! Canonical YAY float formatting.
+//!
+//! Both the value encoder and the MEH formatter write floats through
+//! [`format_float`], so a float prints the same whether it came from a
+//! parsed [`Value`](crate::Value) or from source text being reformatted.
+//!
+//! Floats may also be spelled in hexadecimal, as in C: `0x1.8p3` is
+//! 1.5 × 2³ = 12.0. Hex literals spell every f64 exactly in few digits,
+//! but are not standard YAY, so the parser reads them only with
+//! [`ParseOptions::hex_floats`](crate::ParseOptions).
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use num_bigint::BigUint;
+use num_traits::{One, ToPrimitive, Zero};
+
+/// When [`format_float`] writes a float in exponential notation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum Notation {
+    /// Use whichever of positional and exponential notation is shorter,
+    /// preferring positional notation on a tie. `1e21` stays `1e21` and
+    /// `1234.5` stays `1234.5`.
+    #[default]
+    Shortest,
+    /// Use exponential notation when the decimal exponent (the power of ten
+    /// of the leading digit) is at least `max` or below `min`. JavaScript
+    /// uses `min: -6, max: 21`.
+    Threshold { min: i32, max: i32 },
+}
+
+/// Options for [`format_float`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FloatFormat {
+    pub notation: Notation,
+    /// Write a finite, non-zero float in hexadecimal when its shortest
+    /// decimal spelling needs more than this many significant digits.
+    /// `Some(15)` writes `0.1 + 0.2` as `0x1.3333333333334p-2` but keeps
+    /// `0.1`; `Some(0)` writes every such float in hexadecimal. Readers
+    /// need [`ParseOptions::hex_floats`](crate::ParseOptions) to read them.
+    pub hex_above_digits: Option<usize>,
+}
+
+/// Format a float as a YAY float literal.
+///
+/// The digits are the shortest that read back as the same `f64`. Whole
+/// numbers in positional notation get a `.0` suffix so they do not read
+/// back as integers, and the special values are `nan`, `infinity`,
+/// `-infinity`, and `-0.0`.
+pub fn format_float(f: f64, options: &FloatFormat) -> String {
+    if f.is_nan() {
+        return "nan".to_string();
+    }
+    if f.is_infinite() {
+        return if f > 0.0 { "infinity" } else { "-infinity" }.to_string();
+    }
+    if f == 0.0 {
+        return if f.is_sign_negative() { "-0.0" } else { "0.0" }.to_string();
+    }
+    let exponential = format!("{:e}", f);
+    if let Some(limit) = options.hex_above_digits {
+        if significant_digits(&exponential) > limit {
+            return format_hex_float(f);
+        }
+    }
+    let regular = format!("{}", f);
+    let use_exponential = match options.notation {
+        Notation::Shortest => exponential.len() < regular.len(),
+        Notation::Threshold { min, max } => {
+            let exponent = decimal_exponent(&exponential);
+            exponent >= max || exponent < min
+        }
+    };
+    if use_exponential {
+        exponential
+    } else if regular.contains('.') {
+        regular
+    } else {
+        format!("{}.0", regular)
+    }
+}
+
+/// Format a finite float in positional notation with at least one
+/// fractional digit, as the code generators write float literals.
+///
+/// Rust's `Display` for `f64` writes the shortest digits that read back as
+/// the same float, never uses exponents, and ignores the locale, so the
+/// literal round-trips in every target language.
+pub(crate) fn positional(f: f64) -> String {
+    let s = format!("{}", f);
+    if s.contains('.') {
+        s
+    } else {
+        format!("{}.0", s)
+    }
+}
+
+/// The exponent of a float formatted with `{:e}`.
+fn decimal_exponent(exponential: &str) -> i32 {
+    exponential
+        .rsplit_once('e')
+        .and_then(|(_, exponent)| exponent.parse().ok())
+        .unwrap_or(0)
+}
+
+/// The number of significant digits of a float formatted with `{:e}`.
+fn significant_digits(exponential: &str) -> usize {
+    exponential
+        .split('e')
+        .next()
+        .unwrap_or("")
+        .chars()
+        .filter(char::is_ascii_digit)
+        .count()
+}
+
+/// Format a finite float as a hexadecimal float literal, like `0x1.8p3`.
+///
+/// Normal numbers have a leading `1`, subnormal numbers a leading `0` with
+/// the exponent `-1022`, and trailing zero digits are dropped.
+pub(crate) fn format_hex_float(f: f64) -> String {
+    let bits = f.to_bits();
+    let sign = if f.is_sign_negative() { "-" } else { "" };
+    let biased = ((bits >> 52) & 0x7ff) as i32;
+    let fraction = bits & ((1 << 52) - 1);
+    if biased == 0 && fraction == 0 {
+        return format!("{}0x0p0", sign);
+    }
+    let (lead, exponent) = if biased == 0 {
+        (0, -1022)
+    } else {
+        (1, biased - 1023)
+    };
+    let digits = format!("{:013x}", fraction);
+    let digits = digits.trim_end_matches('0');
+    if digits.is_empty() {
+        format!("{}0x{}p{}", sign, lead, exponent)
+    } else {
+        format!("{}0x{}.{}p{}", sign, lead, digits, exponent)
+    }
+}
+
+/// Parse a hexadecimal float literal: an optional `-`, `0x`, hex digits
+/// with an optional fraction, and a required binary exponent `p` with an
+/// optional sign. Only lowercase letters are accepted, matching the rest of
+/// YAY. Values between floats round to nearest, ties to even; literals too
+/// large for f64 are rejected.
+pub(crate) fn parse_hex_float(s: &str) -> Option<f64> {
+    let (negative, body) = match s.strip_prefix('-') {
+        Some(body) => (true, body),
+        None => (false, s),
+    };
+    let body = body.strip_prefix("0x")?;
+    let (mantissa, exponent) = body.split_once('p')?;
+    let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let is_hex = |d: &str| d.chars().all(|c| matches!(c, '0'..='9' | 'a'..='f'));
+    if int.len() + frac.len() == 0 || !is_hex(int) || !is_hex(frac) {
+        return None;
+    }
+    let digits = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let exponent: i64 = exponent.parse().ok()?;
+    let m = BigUint::parse_bytes(format!("{}{}", int, frac).as_bytes(), 16)?;
+    let magnitude = if m.is_zero() {
+        0.0
+    } else {
+        scale(m, exponent - 4 * frac.len() as i64)?
+    };
+    Some(if negative { -magnitude } else { magnitude })
+}
+
+/// Round `m` × 2^`e` to the nearest f64, or `None` if it overflows.
+fn scale(m: BigUint, e: i64) -> Option<f64> {
+    let bits = m.bits() as i64;
+    // Choose k so the result is q × 2^k with q below 2^53 and k no smaller
+    // than the subnormal exponent.
+    let mut k = (e + bits - 53).max(-1074);
+    let shift = k - e;
+    let mut q = if shift <= 0 {
+        m << (-shift) as usize
+    } else {
+        let shift = shift as usize;
+        let q = &m >> shift;
+        let rest = &m - (&q << shift);
+        let half = BigUint::one() << (shift - 1);
+        if rest > half || (rest == half && q.bit(0)) {
+            q + 1u8
+        } else {
+            q
+        }
+    };
+    if q.bits() > 53 {
+        q >>= 1;
+        k += 1;
+    }
+    if k > 1023 - 52 {
+        return None;
+    }
+    let q = q.to_u64()? as f64;
+    let power = if k >= -1022 {
+        f64::from_bits(((k + 1023) as u64) << 52)
+    } else {
+        f64::from_bits(1 << (k + 1074))
+    };
+    Some(q * power)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shortest(f: f64) -> String {
+        format_float(f, &FloatFormat::default())
+    }
+
+    #[test]
+    fn test_shortest() {
+        assert_eq!(shortest(1e21), "1e21");
+        assert_eq!(shortest(1.5e10), "1.5e10");
+        assert_eq!(shortest(1234.5), "1234.5");
+        assert_eq!(shortest(100.0), "100.0");
+        assert_eq!(shortest(0.001), "1e-3");
+        assert_eq!(shortest(0.5), "0.5");
+        assert_eq!(shortest(-0.0), "-0.0");
+        assert_eq!(shortest(f64::NEG_INFINITY), "-infinity");
+    }
+
+    #[test]
+    fn test_encoder_and_formatter_agree() {
+        use crate::{encode, format_yay, parse, Format, Value};
+        let sources = [
+            "1000000000000000000000.0",
+            "1e21",
+            "0.001",
+            "1.5E10",
+            "6.022e23",
+            "12 345.678 9",
+            "100.",
+            ".5e2",
+            "-0.0",
+            "2.5e-300",
+            "infinity",
+        ];
+        for source in sources {
+            // MEH accepts sources that strict YAY rejects, like `1.5E10`.
+            let formatted = format_yay(source).unwrap();
+            let value = parse(&formatted).unwrap();
+            assert!(matches!(value, Value::Float(_)), "{}", source);
+            let encoded = encode(&value, Format::Yay);
+            assert_eq!(formatted.trim_end(), encoded.trim_end(), "{}", source);
+        }
+    }
+
+    #[test]
+    fn test_hex_floats() {
+        assert_eq!(parse_hex_float("0x1.8p3"), Some(12.0));
+        assert_eq!(parse_hex_float("-0x.8p0"), Some(-0.5));
+        assert_eq!(parse_hex_float("0x10p-4"), Some(1.0));
+        assert_eq!(parse_hex_float("0x1p-1074"), Some(f64::from_bits(1)));
+        assert_eq!(parse_hex_float("0x1.fffffffffffff8p0"), Some(2.0));
+        assert_eq!(parse_hex_float("0x1p1024"), None);
+        for bad in ["0x1.8", "0X1p3", "0x1P3", "0xp3", "0x1.gp3", "0x1p", "1p3"] {
+            assert_eq!(parse_hex_float(bad), None, "{}", bad);
+        }
+        for f in [12.0, 0.1 + 0.2, -f64::MAX, f64::MIN_POSITIVE, 5e-324, -0.0] {
+            let hex = format_hex_float(f);
+            assert_eq!(parse_hex_float(&hex).map(f64::to_bits), Some(f.to_bits()));
+        }
+        assert_eq!(format_hex_float(12.0), "0x1.8p3");
+        assert_eq!(format_hex_float(5e-324), "0x0.0000000000001p-1022");
+    }
+
+    #[test]
+    fn test_hex_float_documents() {
+        use crate::{encode_yay_with, format_yay, parse, parse_with_report, ParseOptions, Value};
+        let options = ParseOptions {
+            hex_floats: true,
+            ..ParseOptions::default()
+        };
+        let (value, report) =
+            parse_with_report("a: 0x1.8p3\nb: [-0x1p-2, 1.5]\n", &options).unwrap();
+        assert!(report.relaxations.hex_floats);
+        assert_eq!(value.get("a"), Some(&Value::Float(12.0)));
+        assert_eq!(
+            value.get("b"),
+            Some(&Value::Array(vec![Value::Float(-0.25), Value::Float(1.5)]))
+        );
+        assert!(parse_with_report("0x1.8", &options).is_err());
+        // Hexadecimal floats are an extension, off by default.
+        for source in ["0x1.8p3", "a: 0x1.8p3", "[0x1.8p3]"] {
+            assert!(parse(source).is_err(), "{}", source);
+        }
+        // MEH reads them, and writes standard YAY.
+        assert_eq!(format_yay("x: 0x18p-1\n").unwrap(), "x: 12.0\n");
+        let hex = FloatFormat {
+            hex_above_digits: Some(0),
+            ..FloatFormat::default()
+        };
+        let encoded = encode_yay_with(&value, &hex);
+        assert_eq!(encoded, "a: 0x1.8p3\nb: [-0x1p-2, 0x1.8p0]");
+        assert_eq!(parse_with_report(&encoded, &options).unwrap().0, value);
+    }
+
+    #[test]
+    fn test_hex_above_digits() {
+        let options = FloatFormat {
+            hex_above_digits: Some(15),
+            ..FloatFormat::default()
+        };
+        assert_eq!(format_float(0.1 + 0.2, &options), "0x1.3333333333334p-2");
+        assert_eq!(format_float(0.1, &options), "0.1");
+        assert_eq!(format_float(f64::INFINITY, &options), "infinity");
+    }
+
+    #[test]
+    fn test_threshold() {
+        let js = FloatFormat {
+            notation: Notation::Threshold { min: -6, max: 21 },
+            ..FloatFormat::default()
+        };
+        assert_eq!(format_float(1e21, &js), "1e21");
+        assert_eq!(format_float(1e20, &js), "100000000000000000000.0");
+        assert_eq!(format_float(1.5e-6, &js), "0.0000015");
+        assert_eq!(format_float(1.5e-7, &js), "1.5e-7");
+        assert_eq!(format_float(f64::NAN, &js), "nan");
+    }
+}
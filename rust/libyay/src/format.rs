@@ -0,0 +1,9 @@
+//! Rewriting YAY text while keeping its comments.
+//!
+//! [`format_yay`] turns loose or strict YAY into canonical YAY, and
+//! [`format_yay_range`] formats the items on some lines of a document.
+//! [`fix_yay`] repairs the mechanical errors in a strict document in
+//! place. [`crate::cst`] gives the syntax tree for other rewrites.
+
+pub use crate::fix::{fix_yay, Fix};
+pub use crate::meh::{format_yay, format_yay_range};
@@ -0,0 +1,176 @@
+//! Content hashes of values.
+//!
+//! [`Value::canonical_hash`] is the SHA-256 digest of a value's canonical
+//! encoding, a byte string that depends only on what the value means, not
+//! on how a document wrote it: indentation, comments, quoting, inline or
+//! block form, and the order of object members make no difference.
+
+use num_bigint::BigInt;
+
+use crate::checksum::sha256;
+use crate::Value;
+
+impl Value {
+    /// The SHA-256 digest of the value's canonical encoding, the same for
+    /// any two documents that parse to equal values, whatever their
+    /// formatting or member order. See [`Value::canonical_bytes`].
+    ///
+    /// An integer and a float are different values, so `1` and `1.0` hash
+    /// differently, as do `0.0` and `-0.0`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use libyay::parse;
+    ///
+    /// let block = parse("# Ports\nname: \"web\"\nports:\n- 80\n- 443").unwrap();
+    /// let inline = parse("ports: [80, 443]\nname: 'web'").unwrap();
+    /// assert_eq!(block.canonical_hash(), inline.canonical_hash());
+    /// ```
+    pub fn canonical_hash(&self) -> [u8; 32] {
+        sha256(&self.canonical_bytes())
+    }
+
+    /// The canonical encoding that [`Value::canonical_hash`] digests: a
+    /// tag byte for each value, followed by:
+    ///
+    /// | Tag | Value | Followed by |
+    /// |-----|-------|-------------|
+    /// | `n` | null | nothing |
+    /// | `t`, `f` | `true`, `false` | nothing |
+    /// | `i` | integer | its length, then its two's complement bytes, big-endian |
+    /// | `d` | float | its 8 IEEE 754 bytes, big-endian, with one NaN for every NaN |
+    /// | `m` | decimal | its mantissa as an integer, then its exponent as 8 bytes |
+    /// | `s` | string | its length, then its UTF-8 bytes |
+    /// | `b` | byte array | its length, then its bytes |
+    /// | `a` | array | its length, then each item |
+    /// | `o` | object | its length, then each key as a string and its value, by key |
+    /// | `z` | date and time | its RFC 3339 text as a string |
+    ///
+    /// Lengths are 8 bytes, big-endian, and object keys are in the order
+    /// of their UTF-8 bytes. The encoding will not change, so a hash
+    /// recorded by one version is the hash of the same value in every
+    /// later version.
+    pub fn canonical_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        write_value(self, &mut out);
+        out
+    }
+}
+
+fn write_value(value: &Value, out: &mut Vec<u8>) {
+    match value {
+        Value::Null => out.push(b'n'),
+        Value::Bool(true) => out.push(b't'),
+        Value::Bool(false) => out.push(b'f'),
+        Value::Integer(n) => {
+            out.push(b'i');
+            write_integer(n, out);
+        }
+        Value::Float(f) => {
+            out.push(b'd');
+            let f = if f.is_nan() { f64::NAN } else { *f };
+            out.extend_from_slice(&f.to_bits().to_be_bytes());
+        }
+        Value::Decimal(d) => {
+            out.push(b'm');
+            write_integer(d.mantissa(), out);
+            out.extend_from_slice(&d.exponent().to_be_bytes());
+        }
+        Value::String(s) => {
+            out.push(b's');
+            write_bytes(s.as_bytes(), out);
+        }
+        Value::Bytes(bytes) => {
+            out.push(b'b');
+            write_bytes(bytes, out);
+        }
+        Value::Array(items) => {
+            out.push(b'a');
+            write_len(items.len(), out);
+            for item in items {
+                write_value(item, out);
+            }
+        }
+        Value::Object(members) => {
+            out.push(b'o');
+            write_len(members.len(), out);
+            let mut members: Vec<_> = members.iter().collect();
+            members.sort_by(|(a, _), (b, _)| a.as_bytes().cmp(b.as_bytes()));
+            for (key, value) in members {
+                write_bytes(key.as_bytes(), out);
+                write_value(value, out);
+            }
+        }
+        Value::DateTime(dt) => {
+            out.push(b'z');
+            write_bytes(dt.as_str().as_bytes(), out);
+        }
+    }
+}
+
+fn write_integer(n: &BigInt, out: &mut Vec<u8>) {
+    write_bytes(&n.to_signed_bytes_be(), out);
+}
+
+fn write_bytes(bytes: &[u8], out: &mut Vec<u8>) {
+    write_len(bytes.len(), out);
+    out.extend_from_slice(bytes);
+}
+
+fn write_len(len: usize, out: &mut Vec<u8>) {
+    out.extend_from_slice(&(len as u64).to_be_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse, Value};
+
+    fn hash(source: &str) -> [u8; 32] {
+        parse(source).unwrap().canonical_hash()
+    }
+
+    #[test]
+    fn test_canonical_hash() {
+        assert_eq!(
+            hash("a: 1\nb:\n  c: [1, 2]\n  d: \"x\""),
+            hash("b: {d: 'x', c: [1, 2]}\na: 1 # one")
+        );
+        assert_eq!(
+            Value::Float(f64::NAN).canonical_hash(),
+            Value::Float(-f64::NAN).canonical_hash()
+        );
+
+        assert_ne!(hash("1"), hash("1.0"));
+        assert_ne!(hash("0.0"), hash("-0.0"));
+        assert_ne!(hash("[1, 2]"), hash("[2, 1]"));
+        assert_ne!(hash("\"1\""), hash("1"));
+        // Lengths keep neighbouring strings apart.
+        assert_ne!(hash("[\"ab\", \"c\"]"), hash("[\"a\", \"bc\"]"));
+        assert_ne!(hash("{a: \"b\"}"), hash("{ab: \"\"}"));
+    }
+
+    #[test]
+    fn test_canonical_bytes() {
+        assert_eq!(
+            parse("{b: -1, a: null}").unwrap().canonical_bytes(),
+            [
+                b"o\0\0\0\0\0\0\0\x02".as_slice(),
+                b"\0\0\0\0\0\0\0\x01an",
+                b"\0\0\0\0\0\0\0\x01bi\0\0\0\0\0\0\0\x01\xff",
+            ]
+            .concat()
+        );
+        // Recorded hashes stay valid.
+        let digest: String = parse("{b: -1, a: null}")
+            .unwrap()
+            .canonical_hash()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        assert_eq!(
+            digest,
+            "41d4334c6941595a8579c718be791697e28dbcb4bfb0928eb92626fe364016e9"
+        );
+    }
+}
@@ -0,0 +1,162 @@
+//! On-type formatting hints for editors.
+//!
+//! After the user types a newline or a `:`, an editor asks where the
+//! cursor's line belongs: how far to indent it and whether it continues a
+//! block array with a `- ` marker. The answer comes from the outline of the
+//! lines above the cursor, so every editor gets YAY's indentation rules
+//! from one place.
+
+use crate::error::ParseContext;
+use crate::lexer::{outline_lex, TokenType};
+use crate::scanner::scan;
+
+/// Where the cursor's line should start.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hint {
+    /// The number of spaces before the line's content or marker.
+    pub indent: usize,
+    /// Whether the line should begin with a `- ` marker after the indent.
+    pub marker: bool,
+}
+
+/// Suggest the indentation of line `line` (0-based) of `source`, where the
+/// cursor sits at `column` after typing a newline or a `:`.
+///
+/// The lines above need not be a complete or valid document: trailing
+/// spaces are ignored, and when the outline cannot be read the previous
+/// line's indentation is kept.
+///
+/// ```
+/// use libyay::hints::{on_type, Hint};
+///
+/// let source = "servers:\n  - host: \"a\"\n    port: 80\n  - \"b\"\n";
+/// assert_eq!(on_type(source, 1, 0), Hint { indent: 2, marker: false });
+/// assert_eq!(on_type(source, 3, 0), Hint { indent: 4, marker: false });
+/// assert_eq!(on_type(source, 4, 0), Hint { indent: 2, marker: true });
+/// ```
+pub fn on_type(source: &str, line: usize, column: usize) -> Hint {
+    let lines: Vec<&str> = source.split('\n').collect();
+    let above: Vec<&str> = lines.iter().take(line).map(|l| l.trim_end()).collect();
+
+    // With `:` just typed, a marker already on the line stays.
+    let current = lines.get(line).copied().unwrap_or("");
+    let before = current.get(..column).unwrap_or(current).trim();
+    let marked = before.ends_with(':') && before.starts_with("- ");
+
+    let mut hint = outline_hint(&above.join("\n")).unwrap_or_else(|| Hint {
+        indent: above
+            .iter()
+            .rev()
+            .find(|l| !l.is_empty())
+            .map_or(0, |l| l.len() - l.trim_start().len()),
+        marker: false,
+    });
+    hint.marker |= marked;
+    hint
+}
+
+/// The hint for the line after `above`, from its outline, or `None` if the
+/// lines do not scan.
+fn outline_hint(above: &str) -> Option<Hint> {
+    let scanned = scan(above, &ParseContext::new(None)).ok()?;
+    let tokens = outline_lex(&scanned.lines);
+
+    // The last line of content, and whether it opens a list item.
+    let mut last = None;
+    for (i, token) in tokens.iter().enumerate() {
+        if token.typ == TokenType::Text && !token.text.starts_with('#') {
+            let item = i > 0 && tokens[i - 1].typ == TokenType::Start;
+            last = Some((token, item));
+        }
+    }
+    let Some((token, item)) = last else {
+        return Some(Hint {
+            indent: 0,
+            marker: false,
+        });
+    };
+
+    // Peel off the markers of items nested on one line, like `- - 1`.
+    let (mut text, mut marker) = (token.text.as_str(), token.indent);
+    let mut content = marker + if item { 2 } else { 0 };
+    while let Some(rest) = text.strip_prefix("- ").filter(|_| item) {
+        (text, marker, content) = (rest, content, content + 2);
+    }
+
+    let opens_block = text.ends_with(':')
+        || text == "-"
+        || text == "`"
+        || text == ">"
+        || text.ends_with(": `")
+        || text.ends_with(": >")
+        || text.starts_with("` ")
+        || text.starts_with("> ");
+    Some(if opens_block {
+        Hint {
+            indent: content + 2,
+            marker: false,
+        }
+    } else if item && !is_property(text) {
+        Hint {
+            indent: marker,
+            marker: true,
+        }
+    } else {
+        Hint {
+            indent: content,
+            marker: false,
+        }
+    })
+}
+
+/// Whether a line's content is a `key: value` property.
+fn is_property(text: &str) -> bool {
+    let mut quote = None;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(_), '\\') => {
+                chars.next();
+            }
+            (Some(q), c) if c == q => quote = None,
+            (None, '"' | '\'') => quote = Some(c),
+            (None, ':') => return chars.peek() == Some(&' '),
+            _ => {}
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hint(indent: usize, marker: bool) -> Hint {
+        Hint { indent, marker }
+    }
+
+    #[test]
+    fn test_after_newline() {
+        assert_eq!(on_type("", 0, 0), hint(0, false));
+        assert_eq!(on_type("a: 1\n", 1, 0), hint(0, false));
+        assert_eq!(on_type("a:\n", 1, 0), hint(2, false));
+        assert_eq!(on_type("a:\n  b: 1\n\n", 3, 0), hint(2, false));
+        assert_eq!(on_type("- 1\n", 1, 0), hint(0, true));
+        assert_eq!(on_type("- a: 1\n", 1, 0), hint(2, false));
+        assert_eq!(on_type("- a:\n", 1, 0), hint(4, false));
+        assert_eq!(on_type("- - 1\n", 1, 0), hint(2, true));
+        assert_eq!(on_type("text: `\n", 1, 0), hint(2, false));
+        assert_eq!(on_type("a: \"x:y\"\n  # note\n", 2, 0), hint(0, false));
+    }
+
+    #[test]
+    fn test_after_colon() {
+        assert_eq!(on_type("items:\n- name:", 1, 7), hint(2, true));
+        assert_eq!(on_type("a:\nb:", 1, 2), hint(2, false));
+    }
+
+    #[test]
+    fn test_unreadable_lines_keep_indent() {
+        assert_eq!(on_type("a:\n  -x\n", 2, 0), hint(2, false));
+    }
+}
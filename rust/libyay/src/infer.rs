@@ -0,0 +1,247 @@
+//! Inferring the shape of data.
+//!
+//! An [`Inference`] gathers, for each path pattern in one or more
+//! documents, the types found there and how often, the range of its numbers
+//! and lengths, and a few example values. Array indices are collapsed, so
+//! `servers[0].port` and `servers[1].port` are both `servers[].port`. The
+//! report is a first draft of a schema, and a quick audit of unknown data.
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use num_traits::ToPrimitive;
+
+use crate::stats::type_name;
+use crate::{Map, Value};
+
+/// What was found at one path pattern.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PathShape {
+    /// How many values were found at the path.
+    pub count: usize,
+    /// How many values of each type (`null`, `integer`, `string`, ...).
+    pub types: BTreeMap<&'static str, usize>,
+    /// The least and greatest numbers (integers, floats, and decimals).
+    pub min: Option<Value>,
+    pub max: Option<Value>,
+    /// The fewest and most characters of a string, bytes of a byte array,
+    /// or items of an array.
+    pub shortest: Option<usize>,
+    pub longest: Option<usize>,
+    /// The first distinct values found that are not null, arrays, or
+    /// objects.
+    pub examples: Vec<Value>,
+}
+
+impl PathShape {
+    /// How many of the values were null.
+    pub fn nulls(&self) -> usize {
+        self.types.get("null").copied().unwrap_or(0)
+    }
+}
+
+/// The shapes of the paths in the documents added so far.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Inference {
+    examples: usize,
+    paths: BTreeMap<String, PathShape>,
+}
+
+impl Inference {
+    /// An empty inference that keeps up to `examples` example values per
+    /// path.
+    pub fn new(examples: usize) -> Inference {
+        Inference {
+            examples,
+            paths: BTreeMap::new(),
+        }
+    }
+
+    /// Record the values in a document. The root itself is not recorded,
+    /// only the values under it.
+    ///
+    /// ```
+    /// use libyay::infer::Inference;
+    /// use libyay::{parse, Value};
+    ///
+    /// let mut inference = Inference::new(2);
+    /// inference.add(&parse("servers:\n  - port: 80\n  - port: 443\n  - port: null\n").unwrap());
+    /// let port = &inference.paths()["servers[].port"];
+    /// assert_eq!((port.count, port.nulls()), (3, 1));
+    /// assert_eq!(port.max, Some(Value::from(443)));
+    /// ```
+    pub fn add(&mut self, value: &Value) {
+        let mut path = String::new();
+        self.visit_children(value, &mut path);
+    }
+
+    /// The shape of each path, by path.
+    pub fn paths(&self) -> &BTreeMap<String, PathShape> {
+        &self.paths
+    }
+
+    /// The report as a YAY object with a member for each path.
+    pub fn to_value(&self) -> Value {
+        let mut report = Map::new();
+        for (path, shape) in &self.paths {
+            let mut entry = Map::new();
+            entry.insert("count".into(), Value::from(shape.count));
+            let types = shape
+                .types
+                .iter()
+                .map(|(name, count)| (name.to_string(), Value::from(*count)))
+                .collect();
+            entry.insert("types".into(), Value::Object(types));
+            entry.insert("nulls".into(), Value::from(shape.nulls()));
+            let bounds = [
+                ("min", shape.min.clone()),
+                ("max", shape.max.clone()),
+                ("shortest", shape.shortest.map(Value::from)),
+                ("longest", shape.longest.map(Value::from)),
+            ];
+            for (name, bound) in bounds {
+                if let Some(bound) = bound {
+                    entry.insert(name.into(), bound);
+                }
+            }
+            if !shape.examples.is_empty() {
+                entry.insert("examples".into(), Value::Array(shape.examples.clone()));
+            }
+            report.insert(path.clone(), Value::Object(entry));
+        }
+        Value::Object(report)
+    }
+
+    fn visit_children(&mut self, value: &Value, path: &mut String) {
+        let len = path.len();
+        match value {
+            Value::Array(items) => {
+                path.push_str("[]");
+                for item in items {
+                    self.visit(item, path);
+                }
+            }
+            Value::Object(members) => {
+                for (key, item) in members {
+                    if len > 0 {
+                        path.push('.');
+                    }
+                    path.push_str(key);
+                    self.visit(item, path);
+                    path.truncate(len);
+                }
+            }
+            _ => {}
+        }
+        path.truncate(len);
+    }
+
+    fn visit(&mut self, value: &Value, path: &mut String) {
+        let examples = self.examples;
+        let shape = self.paths.entry(path.clone()).or_default();
+        shape.count += 1;
+        *shape.types.entry(type_name(value)).or_insert(0) += 1;
+        let length = match value {
+            Value::String(s) => Some(s.chars().count()),
+            Value::Bytes(b) => Some(b.len()),
+            Value::Array(a) => Some(a.len()),
+            _ => None,
+        };
+        if let Some(length) = length {
+            shape.shortest = Some(shape.shortest.map_or(length, |n| n.min(length)));
+            shape.longest = Some(shape.longest.map_or(length, |n| n.max(length)));
+        }
+        if is_number(value) {
+            if shape
+                .min
+                .as_ref()
+                .is_none_or(|min| compare_numbers(value, min) == Some(Ordering::Less))
+            {
+                shape.min = Some(value.clone());
+            }
+            if shape
+                .max
+                .as_ref()
+                .is_none_or(|max| compare_numbers(value, max) == Some(Ordering::Greater))
+            {
+                shape.max = Some(value.clone());
+            }
+        }
+        let scalar = !matches!(value, Value::Null | Value::Array(_) | Value::Object(_));
+        if scalar && shape.examples.len() < examples && !shape.examples.contains(value) {
+            shape.examples.push(value.clone());
+        }
+        self.visit_children(value, path);
+    }
+}
+
+fn is_number(value: &Value) -> bool {
+    match value {
+        Value::Integer(_) | Value::Decimal(_) => true,
+        Value::Float(f) => !f.is_nan(),
+        _ => false,
+    }
+}
+
+/// Compare two numbers exactly if both are integers, and as floats
+/// otherwise.
+fn compare_numbers(a: &Value, b: &Value) -> Option<Ordering> {
+    if let (Value::Integer(a), Value::Integer(b)) = (a, b) {
+        return Some(a.cmp(b));
+    }
+    let float = |v: &Value| match v {
+        Value::Integer(i) => i.to_f64(),
+        Value::Float(f) => Some(*f),
+        Value::Decimal(d) => Some(d.to_f64()),
+        _ => None,
+    };
+    float(a)?.partial_cmp(&float(b)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    #[test]
+    fn test_infer() {
+        let mut inference = Inference::new(2);
+        inference.add(
+            &parse(
+                "servers:\n  - host: \"a\"\n    port: 80\n  - host: \"bbb\"\n    port: 800.5\n    tags: [\"x\"]\n  - host: null\n    port: -1\n",
+            )
+            .unwrap(),
+        );
+        inference.add(&parse("servers: []\nname: \"b\"\n").unwrap());
+
+        let paths = inference.paths();
+        assert_eq!(
+            paths.keys().collect::<Vec<_>>(),
+            [
+                "name",
+                "servers",
+                "servers[]",
+                "servers[].host",
+                "servers[].port",
+                "servers[].tags",
+                "servers[].tags[]",
+            ]
+        );
+        let host = &paths["servers[].host"];
+        assert_eq!((host.count, host.nulls()), (3, 1));
+        assert_eq!((host.shortest, host.longest), (Some(1), Some(3)));
+        assert_eq!(host.examples, [Value::from("a"), Value::from("bbb")]);
+        let port = &paths["servers[].port"];
+        assert_eq!(port.types.get("integer"), Some(&2));
+        assert_eq!(port.min, Some(Value::from(-1)));
+        assert_eq!(port.max, Some(Value::Float(800.5)));
+        let servers = &paths["servers"];
+        assert_eq!((servers.shortest, servers.longest), (Some(0), Some(3)));
+        assert!(servers.examples.is_empty());
+
+        let report = inference.to_value();
+        assert_eq!(report["servers[].host"]["nulls"], Value::from(1));
+        assert_eq!(report["servers[].tags"]["types"]["array"], Value::from(1));
+        assert!(report["name"].get("min").is_none());
+    }
+}
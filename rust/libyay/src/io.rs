@@ -0,0 +1,44 @@
+//! The writer the encoders write through: [`std::io::Write`] with the
+//! `std` feature, and without it, a trait of the same shape that writes
+//! only to a `Vec<u8>`, which cannot fail.
+
+#[cfg(feature = "std")]
+pub(crate) use std::io::{Result, Write};
+
+#[cfg(not(feature = "std"))]
+pub(crate) use shim::{Result, Write};
+
+#[cfg(not(feature = "std"))]
+mod shim {
+    use alloc::vec::Vec;
+    use core::fmt;
+
+    /// An error writing, which a `Vec` never has.
+    #[derive(Debug)]
+    pub(crate) struct Error;
+
+    pub(crate) type Result<T> = core::result::Result<T, Error>;
+
+    pub(crate) trait Write {
+        fn write_all(&mut self, buf: &[u8]) -> Result<()>;
+
+        fn write_fmt(&mut self, args: fmt::Arguments<'_>) -> Result<()> {
+            struct Adapter<'a, W: ?Sized>(&'a mut W);
+
+            impl<W: Write + ?Sized> fmt::Write for Adapter<'_, W> {
+                fn write_str(&mut self, s: &str) -> fmt::Result {
+                    self.0.write_all(s.as_bytes()).map_err(|_| fmt::Error)
+                }
+            }
+
+            fmt::write(&mut Adapter(self), args).map_err(|_| Error)
+        }
+    }
+
+    impl Write for Vec<u8> {
+        fn write_all(&mut self, buf: &[u8]) -> Result<()> {
+            self.extend_from_slice(buf);
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,378 @@
+//! JSONC import.
+//!
+//! JSONC is JSON with `//` line comments, `/* */` block comments, and
+//! trailing commas. Going through [`Value`] would drop the comments, so
+//! [`jsonc_to_yay`] builds a MEH CST directly: each comment becomes a YAY
+//! comment line before the property or array item that follows it, and a
+//! `//` comment on the same line as a scalar member stays on that line.
+
+use crate::encode::{encode_yay, encode_yay_key};
+use crate::meh::{
+    format_document, transform_to_canonical, ArrayItem, ArrayItemValue, Block, Comment, CstArray,
+    CstObject, CstString, CstValue, Document, Item, Key, Property, PropertyValue,
+};
+use crate::yson::{parse_json_string, parse_number};
+use crate::{parse, Value};
+
+/// Convert JSONC to canonical YAY, keeping comments and member order.
+pub fn jsonc_to_yay(input: &str) -> Result<String, String> {
+    let doc = JsoncParser::new(input).parse()?;
+    Ok(format_document(&transform_to_canonical(&doc)))
+}
+
+/// Parse JSONC into a value, ignoring comments.
+pub fn parse_jsonc(input: &str) -> Result<Value, String> {
+    let yay = jsonc_to_yay(input)?;
+    parse(&yay).map_err(|e| e.to_string())
+}
+
+struct JsoncParser<'a> {
+    input: &'a str,
+    pos: usize,
+    /// Comments seen since the last property or array item.
+    pending: Vec<Comment>,
+    /// Line of the first pending comment.
+    pending_line: usize,
+    /// Whether the pending comments are a single `//` comment.
+    pending_single: bool,
+}
+
+impl<'a> JsoncParser<'a> {
+    fn new(input: &'a str) -> Self {
+        Self {
+            input,
+            pos: 0,
+            pending: Vec::new(),
+            pending_line: 0,
+            pending_single: false,
+        }
+    }
+
+    fn parse(&mut self) -> Result<Document, String> {
+        self.skip_trivia()?;
+        let mut items = match self.peek() {
+            None => return Err("Empty input".to_string()),
+            Some('{') => {
+                self.pos += 1;
+                self.parse_members()?
+            }
+            Some('[') => {
+                self.pos += 1;
+                self.parse_elements()?
+            }
+            Some(_) => {
+                let mut items = self.take_comments();
+                let value = self.parse_scalar()?;
+                items.push(Item::Value(value));
+                items
+            }
+        };
+        self.skip_trivia()?;
+        if self.pos < self.input.len() {
+            return Err(format!(
+                "Unexpected content after value at line {}",
+                self.line()
+            ));
+        }
+        items.extend(self.take_comments());
+        Ok(Document {
+            directive: None,
+            items,
+            trailing_comments: Vec::new(),
+        })
+    }
+
+    /// Parse object members after `{`, through the closing `}`.
+    fn parse_members(&mut self) -> Result<Vec<Item>, String> {
+        let mut items = Vec::new();
+        loop {
+            self.skip_trivia()?;
+            if self.eat('}') {
+                items.extend(self.take_comments());
+                return Ok(items);
+            }
+            if self.peek() != Some('"') {
+                return Err(format!("Expected member name at line {}", self.line()));
+            }
+            items.extend(self.take_comments());
+            let (name, rest) = parse_json_string(&self.input[self.pos..])?;
+            self.pos = self.input.len() - rest.len();
+            let key = match encode_yay_key(&name) {
+                k if k.starts_with('"') => Key::DoubleQuoted(k),
+                k => Key::Bare(k),
+            };
+            self.skip_trivia()?;
+            if !self.eat(':') {
+                return Err(format!("Expected ':' at line {}", self.line()));
+            }
+            let value = match self.parse_value()? {
+                Nested::Scalar(v) => PropertyValue::Inline(v),
+                Nested::Empty(v) => PropertyValue::Inline(v),
+                Nested::Block(block) => PropertyValue::Block(block),
+            };
+            let inline_comment = self.finish_entry('}')?;
+            items.push(Item::Property(Property {
+                key,
+                value: Some(value),
+                inline_comment,
+            }));
+        }
+    }
+
+    /// Parse array elements after `[`, through the closing `]`.
+    fn parse_elements(&mut self) -> Result<Vec<Item>, String> {
+        let mut items = Vec::new();
+        loop {
+            self.skip_trivia()?;
+            if self.eat(']') {
+                items.extend(self.take_comments());
+                return Ok(items);
+            }
+            items.extend(self.take_comments());
+            let value = match self.parse_value()? {
+                Nested::Scalar(v) | Nested::Empty(v) => ArrayItemValue::Inline(v),
+                Nested::Block(mut block) => {
+                    // A comment cannot share a line with `-`, so comments
+                    // that lead a nested block move above the item.
+                    let leading = block
+                        .items
+                        .iter()
+                        .take_while(|item| matches!(item, Item::Comment(_)))
+                        .count();
+                    items.extend(block.items.drain(..leading));
+                    ArrayItemValue::Block(block)
+                }
+            };
+            let inline_comment = self.finish_entry(']')?;
+            items.push(Item::ArrayItem(ArrayItem {
+                value: Some(value),
+                inline_comment,
+            }));
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<Nested, String> {
+        self.skip_trivia()?;
+        let (items, empty) = match self.peek() {
+            Some('{') => {
+                self.pos += 1;
+                (
+                    self.parse_members()?,
+                    CstValue::Object(CstObject { entries: vec![] }),
+                )
+            }
+            Some('[') => {
+                self.pos += 1;
+                (
+                    self.parse_elements()?,
+                    CstValue::Array(CstArray { items: vec![] }),
+                )
+            }
+            _ => return Ok(Nested::Scalar(self.parse_scalar()?)),
+        };
+        if items.is_empty() {
+            Ok(Nested::Empty(empty))
+        } else {
+            Ok(Nested::Block(Block { items }))
+        }
+    }
+
+    fn parse_scalar(&mut self) -> Result<CstValue, String> {
+        let rest = &self.input[self.pos..];
+        let (value, rest) = if rest.starts_with('"') {
+            parse_json_string(rest).map(|(s, rest)| (Value::String(s), rest))?
+        } else if let Some(rest) = rest.strip_prefix("null") {
+            (Value::Null, rest)
+        } else if let Some(rest) = rest.strip_prefix("true") {
+            (Value::Bool(true), rest)
+        } else if let Some(rest) = rest.strip_prefix("false") {
+            (Value::Bool(false), rest)
+        } else if rest.starts_with(|c: char| c == '-' || c.is_ascii_digit()) {
+            parse_number(rest)?
+        } else {
+            return Err(format!("Unexpected character at line {}", self.line()));
+        };
+        self.pos = self.input.len() - rest.len();
+        let text = encode_yay(&value, 0);
+        Ok(match value {
+            Value::Null => CstValue::Null,
+            Value::Bool(b) => CstValue::Bool(b),
+            Value::Integer(_) => CstValue::Integer(text),
+            Value::Float(_) => CstValue::Float(text),
+            _ => CstValue::String(CstString::DoubleQuoted(text)),
+        })
+    }
+
+    /// Consume the separator after an entry, returning a `//` comment that
+    /// ends the entry's line.
+    fn finish_entry(&mut self, close: char) -> Result<Option<Comment>, String> {
+        let line = self.line();
+        self.skip_trivia()?;
+        if !self.eat(',') && self.peek() != Some(close) {
+            return Err(format!(
+                "Expected ',' or '{}' at line {}",
+                close,
+                self.line()
+            ));
+        }
+        self.skip_trivia()?;
+        // Only a comment that started on the entry's own line is inline.
+        if self.pending.len() == 1 && self.pending_line == line && self.pending_single {
+            return Ok(self.pending.pop());
+        }
+        Ok(None)
+    }
+
+    /// Skip whitespace and comments, collecting the comments.
+    fn skip_trivia(&mut self) -> Result<(), String> {
+        loop {
+            let rest = &self.input[self.pos..];
+            let trimmed = rest.trim_start();
+            self.pos += rest.len() - trimmed.len();
+            if let Some(body) = trimmed.strip_prefix("//") {
+                let end = body.find('\n').unwrap_or(body.len());
+                self.push_comment(body[..end].trim_end(), true);
+                self.pos += 2 + end;
+            } else if let Some(body) = trimmed.strip_prefix("/*") {
+                let end = body
+                    .find("*/")
+                    .ok_or_else(|| format!("Unterminated comment at line {}", self.line()))?;
+                for text in block_comment_lines(&body[..end]) {
+                    self.push_comment(text, false);
+                }
+                self.pos += 2 + end + 2;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn push_comment(&mut self, text: &str, single: bool) {
+        if self.pending.is_empty() {
+            self.pending_line = self.line();
+            self.pending_single = single;
+        } else {
+            self.pending_single = false;
+        }
+        let text = if text.is_empty() || text.starts_with(' ') {
+            text.to_string()
+        } else {
+            format!(" {}", text)
+        };
+        self.pending.push(Comment {
+            text,
+            align_column: None,
+        });
+    }
+
+    fn take_comments(&mut self) -> Vec<Item> {
+        self.pending.drain(..).map(Item::Comment).collect()
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// One-based line of the current position.
+    fn line(&self) -> usize {
+        self.input[..self.pos].matches('\n').count() + 1
+    }
+}
+
+/// A parsed member or element value.
+enum Nested {
+    Scalar(CstValue),
+    /// `{}` or `[]`, which stay inline.
+    Empty(CstValue),
+    Block(Block),
+}
+
+/// Split a block comment body into lines, dropping the blank first and last
+/// lines and the ` * ` decoration of doc-style comments.
+fn block_comment_lines(body: &str) -> Vec<&str> {
+    let mut lines: Vec<&str> = body
+        .lines()
+        .map(|line| {
+            let line = line.trim();
+            line.strip_prefix('*').unwrap_or(line).trim()
+        })
+        .collect();
+    while lines.first() == Some(&"") {
+        lines.remove(0);
+    }
+    while lines.last() == Some(&"") {
+        lines.pop();
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_comments_attach_to_following_property() {
+        let input = r#"{
+  // The server name.
+  "name": "parrot",
+  /* Listening port.
+   * Must be free. */
+  "port": 8080, // default
+  "tags": ["a", "b"],
+}
+"#;
+        let yay = jsonc_to_yay(input).unwrap();
+        assert_eq!(
+            yay,
+            "# The server name.\nname: \"parrot\"\n# Listening port.\n# Must be free.\nport: 8080.0  # default\ntags:\n  - \"a\"\n  - \"b\"\n"
+        );
+        assert_eq!(parse_jsonc(input).unwrap(), parse(&yay).unwrap());
+    }
+
+    #[test]
+    fn test_nested_and_array_comments() {
+        let input = r#"{
+  "servers": [
+    // primary
+    {"host": "a", "port": 1},
+    {"host": "b", "port": 2}
+  ],
+  "empty": {}
+}"#;
+        let yay = jsonc_to_yay(input).unwrap();
+        let value = parse(&yay).expect(&yay);
+        let servers = value.as_object().unwrap()["servers"].as_array().unwrap();
+        assert_eq!(servers.len(), 2);
+        assert!(yay.contains("# primary\n  - host: \"a\"\n"), "{}", yay);
+        assert!(yay.contains("empty: {}\n"), "{}", yay);
+    }
+
+    #[test]
+    fn test_member_order_preserved() {
+        let value = parse_jsonc(r#"{"b": 1, "a": 2.5, "c": null}"#).unwrap();
+        let keys: Vec<&str> = value
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect();
+        assert_eq!(keys, vec!["b", "a", "c"]);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(jsonc_to_yay("").is_err());
+        assert!(jsonc_to_yay("{\"a\": 1 /* open").is_err());
+        assert!(jsonc_to_yay("{\"a\" 1}").is_err());
+        assert!(jsonc_to_yay("[1] 2").is_err());
+    }
+}
@@ -8,6 +8,9 @@
 //! - `Break`: Blank lines (coalesced)
 
 use crate::scanner::ScanLine;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
 
 /// Token type in the outline lexer output.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -14,53 +14,176 @@
 //! 2. **Outline Lexer**: Converts scan lines into a token stream with explicit
 //!    block start/stop markers based on indentation changes.
 //!
-//! 3. **Value Parser**: Recursively parses the token stream into Rust values.
+//! 3. **Value Parser**: Recursively parses the token stream into Rust values,
+//!    or with [`Parser`], into a sequence of [`stream::Event`]s.
+//!
+//! # Modules
+//!
+//! Everything most programs need is exported from the crate root, and
+//! grouped by purpose in [`parse`](mod@parse), [`encode`](mod@encode),
+//! [`format`](mod@format), [`value`], [`error`], and [`options`]. Enums that may grow,
+//! like [`ParseError`] and [`Format`], are `#[non_exhaustive]`.
+//!
+//! # `no_std`
+//!
+//! With the default `std` feature off, the crate is `no_std` and needs only
+//! `alloc`. It keeps [`Value`], the parsers of text and bytes, and the
+//! encoders; what reads files, readers, writers, or the environment, and
+//! the editing and checking tools built on them, need `std`.
 
-mod encode;
-mod error;
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+#[doc(hidden)]
+pub extern crate alloc as __alloc;
+
+#[cfg(feature = "arbitrary")]
+mod arbitrary;
+#[cfg(feature = "compress")]
+pub mod archive;
+#[cfg(feature = "tokio")]
+mod async_read;
+mod borrowed;
+mod cancel;
+#[cfg(feature = "checksum")]
+mod checksum;
+mod collate;
+mod compat;
+#[cfg(feature = "std")]
+pub mod completion;
+#[cfg(feature = "compress")]
+pub mod compress;
+#[cfg(feature = "std")]
+pub mod cst;
+mod datetime;
+#[cfg(feature = "serde")]
+pub mod de;
+mod decimal;
+#[cfg(feature = "std")]
+mod diff;
+pub mod encode;
+mod encoding;
+pub mod error;
+#[cfg(feature = "std")]
+pub mod eval;
+#[cfg(feature = "std")]
+mod fix;
+mod float;
+#[cfg(feature = "std")]
+pub mod format;
+#[cfg(feature = "checksum")]
+mod hash;
+#[cfg(feature = "std")]
+pub mod hints;
+#[cfg(feature = "std")]
+pub mod infer;
+mod io;
+#[cfg(feature = "std")]
+mod jsonc;
 mod lexer;
+#[cfg(feature = "std")]
+pub mod lint;
+mod macros;
+mod map;
+#[cfg(feature = "std")]
 mod meh;
+#[cfg(feature = "std")]
+mod merge;
+pub mod metrics;
+#[cfg(feature = "std")]
+pub mod migrate;
+#[cfg(feature = "std")]
+mod ndyay;
+pub mod options;
+#[cfg(feature = "std")]
+pub mod outline;
+pub mod parse;
 mod parser;
+#[cfg(feature = "std")]
+pub mod patch;
+mod push;
+mod quantity;
+#[cfg(feature = "std")]
+pub mod refactor;
+#[cfg(feature = "std")]
+pub mod references;
+mod report;
+#[cfg(feature = "std")]
+mod reveal;
 mod scanner;
+#[cfg(feature = "serde")]
+pub mod ser;
+#[cfg(feature = "std")]
 pub mod shon;
-mod value;
+#[cfg(feature = "std")]
+pub mod spans;
+pub mod stats;
+pub mod stream;
+#[cfg(feature = "std")]
+pub mod subset;
+mod suggest;
+#[cfg(feature = "testing")]
+pub mod testing;
+mod units;
+pub mod value;
 mod yson;
 
-pub use encode::{encode, Format};
+#[cfg(feature = "tokio")]
+pub use async_read::{parse_async, parse_async_with_options, AsyncDocuments};
+pub use borrowed::{BorrowedMap, BorrowedValue};
+pub use cancel::CancellationToken;
+#[cfg(feature = "checksum")]
+pub use checksum::{add_checksums, verify_checksums, ChecksumMismatch};
+pub use collate::{compare_collated, KeyOrder};
+pub use compat::{Incompatibility, JsonCompatOptions, NonFinite};
+pub use datetime::DateTime;
+#[cfg(feature = "serde")]
+pub use de::{from_str, from_value};
+pub use decimal::Decimal;
+#[cfg(feature = "std")]
+pub use diff::{diff, Change};
+pub use encode::{
+    encode, encode_decimals_as_strings, encode_ordered, encode_with_options, encode_yay_ordered,
+    encode_yay_with, ArrayEncoder, EncodeOptions, Format, Quotes,
+};
+#[cfg(feature = "std")]
+pub use encode::{encode_to, encode_to_cancellable};
+pub use encoding::{decode_bytes, detect_encoding, Encoding, EncodingDetection, Newlines};
 pub use error::{ParseError, Result};
-pub use meh::format_yay;
+pub use float::{format_float, FloatFormat, Notation};
+#[cfg(feature = "std")]
+pub use format::{fix_yay, format_yay, format_yay_range, Fix};
+#[cfg(feature = "std")]
+pub use jsonc::{jsonc_to_yay, parse_jsonc};
+#[doc(hidden)]
+pub use macros::hex_bytes as __hex_bytes;
+#[cfg(feature = "std")]
+pub use merge::{ArrayMerge, MergeStrategy};
+pub use options::{DuplicateKeys, ParseObserver, ParseOptions};
+pub use parse::{
+    parse, parse_borrowed, parse_bytes, parse_with_filename, parse_with_options, parse_with_report,
+    Parser, PushParser,
+};
+#[cfg(feature = "std")]
+pub use parse::{parse_file, parse_reader, parse_reader_with_options, parse_with_spans, Documents};
+pub use quantity::{Quantities, Quantity};
+pub use report::{DuplicateKey, ParseReport, Relaxations};
+#[cfg(feature = "std")]
+pub use reveal::{reveal_invisible, Invisible, Revealed};
+pub use scanner::YAY_VERSION;
+#[cfg(feature = "serde")]
+pub use ser::{to_string, to_value};
+#[cfg(feature = "std")]
 pub use shon::{
     parse_shon_bracket, parse_shon_file_bytes, parse_shon_file_string, parse_shon_hex, ShonError,
 };
-pub use value::Value;
-pub use yson::parse_yson;
-
-/// Parse a YAY document from a string.
-///
-/// # Example
-///
-/// ```
-/// use libyay::parse;
-///
-/// let value = parse("42").unwrap();
-/// ```
-pub fn parse(input: &str) -> Result<Value> {
-    parse_with_filename(input, None)
-}
-
-/// Parse a YAY document from a string with a filename for error messages.
-pub fn parse_with_filename(input: &str, filename: Option<&str>) -> Result<Value> {
-    let ctx = error::ParseContext::new(filename);
-
-    // Phase 1: Scan source into lines
-    let scan_result = scanner::scan(input, &ctx)?;
-
-    // Phase 2: Convert lines to token stream
-    let tokens = lexer::outline_lex(&scan_result.lines);
-
-    // Phase 3: Parse tokens into value
-    parser::parse_root(&tokens, &ctx, scan_result.had_comments)
-}
+pub use suggest::suggest_bare_word;
+#[cfg(feature = "std")]
+pub use value::Hashed;
+pub use value::{
+    Field, GenericValue, Indexed, Map, MapKind, ObjectMap, Sorted, TryFromValueError, Value,
+};
+pub use yson::{parse_yson, parse_yson_with_duplicates, DuplicateMember};
 
 // Unit tests removed - coverage should come from fixtures
 // #[cfg(test)]
@@ -0,0 +1,986 @@
+//! Lint subsystem for YAY documents.
+//!
+//! A [`Linter`] runs a set of named [`Rule`]s over a document and reports
+//! [`Diagnostic`]s. Each rule has a default [`Severity`] that a [`LintConfig`]
+//! may override, either globally or for files matching a path pattern.
+//!
+//! Rules can be suppressed from within a document with a comment:
+//!
+//! ```text
+//! # yay-lint: disable=line-length,key-style
+//! ```
+//!
+//! A comment on its own line disables the listed rules for the whole file.
+//! A trailing comment disables them for that line only.
+//!
+//! Configuration is itself a YAY document:
+//!
+//! ```text
+//! rules:
+//!   line-length:
+//!     severity: "error"
+//!     max: 100
+//!   key-style: {severity: "warning", style: "snake"}
+//! paths:
+//!   - pattern: "vendor/**"
+//!     disable: ["key-style"]
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::units::parse_byte_size;
+use crate::Value;
+
+/// Comment prefix that introduces a suppression directive.
+const SUPPRESS_PREFIX: &str = "yay-lint:";
+
+// =============================================================================
+// Diagnostics
+// =============================================================================
+
+/// How seriously a rule violation is reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// The rule does not run.
+    Off,
+    /// Violations are reported but do not fail the lint.
+    Warning,
+    /// Violations fail the lint.
+    Error,
+}
+
+impl Severity {
+    /// Parse a severity name (`off`, `warning`/`warn`, `error`).
+    pub fn from_name(s: &str) -> Option<Severity> {
+        match s {
+            "off" => Some(Severity::Off),
+            "warning" | "warn" => Some(Severity::Warning),
+            "error" => Some(Severity::Error),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Off => write!(f, "off"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A single rule violation produced by a rule, before severity is applied.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    /// Zero-based line number, when the rule can attribute one.
+    pub line: Option<usize>,
+    /// Value path (e.g. `servers[0].port`), when the rule works on values.
+    pub path: Option<String>,
+    /// Human-readable description of the violation.
+    pub message: String,
+}
+
+impl Finding {
+    /// A finding attributed to a source line.
+    pub fn at_line(line: usize, message: impl Into<String>) -> Self {
+        Finding {
+            line: Some(line),
+            path: None,
+            message: message.into(),
+        }
+    }
+
+    /// A finding attributed to a value path.
+    pub fn at_path(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Finding {
+            line: None,
+            path: Some(path.into()),
+            message: message.into(),
+        }
+    }
+}
+
+/// A reported rule violation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    /// Name of the rule that produced this diagnostic.
+    pub rule: String,
+    /// Effective severity after configuration.
+    pub severity: Severity,
+    /// Zero-based line number, if known.
+    pub line: Option<usize>,
+    /// Value path, if known.
+    pub path: Option<String>,
+    /// Human-readable description.
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "{}: ", line + 1)?;
+        }
+        write!(f, "{} [{}] ", self.severity, self.rule)?;
+        if let Some(path) = &self.path {
+            write!(f, "{}: ", path)?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+// =============================================================================
+// Rules
+// =============================================================================
+
+/// The document handed to each rule.
+pub struct LintDocument<'a> {
+    /// Source text.
+    pub source: &'a str,
+    /// Source split into lines.
+    pub lines: Vec<&'a str>,
+    /// The parsed value, if the document parsed.
+    pub value: Option<&'a Value>,
+    /// The file path being linted, if any.
+    pub path: Option<&'a str>,
+}
+
+/// A named lint rule.
+///
+/// `options` is the rule's configuration object (or `Value::Null` when the
+/// rule is not configured), from which rules read their own settings.
+pub trait Rule {
+    /// Stable rule name used in configuration and suppression comments.
+    fn name(&self) -> &'static str;
+
+    /// Severity used when the configuration does not mention the rule.
+    fn default_severity(&self) -> Severity;
+
+    /// Check the document and return any findings.
+    fn check(&self, doc: &LintDocument<'_>, options: &Value) -> Vec<Finding>;
+}
+
+/// Read an integer option from a rule's options object.
+pub fn option_usize(options: &Value, name: &str) -> Option<usize> {
+    use num_traits::ToPrimitive;
+    options
+        .as_object()
+        .and_then(|obj| obj.get(name))
+        .and_then(|v| v.as_integer())
+        .and_then(|n| n.to_usize())
+}
+
+/// Read a string option from a rule's options object.
+pub fn option_str<'a>(options: &'a Value, name: &str) -> Option<&'a str> {
+    options
+        .as_object()
+        .and_then(|obj| obj.get(name))
+        .and_then(|v| v.as_str())
+}
+
+/// Lines must not exceed a maximum width (`max`, default 80).
+pub struct LineLength;
+
+impl Rule for LineLength {
+    fn name(&self) -> &'static str {
+        "line-length"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, doc: &LintDocument<'_>, options: &Value) -> Vec<Finding> {
+        let max = option_usize(options, "max").unwrap_or(80);
+        doc.lines
+            .iter()
+            .enumerate()
+            .filter_map(|(i, line)| {
+                let width = line.chars().count();
+                (width > max).then(|| {
+                    Finding::at_line(i, format!("Line is {} columns, limit is {}", width, max))
+                })
+            })
+            .collect()
+    }
+}
+
+/// Documents must end with exactly one newline.
+pub struct FinalNewline;
+
+impl Rule for FinalNewline {
+    fn name(&self) -> &'static str {
+        "final-newline"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, doc: &LintDocument<'_>, _options: &Value) -> Vec<Finding> {
+        if doc.source.is_empty() {
+            return Vec::new();
+        }
+        let last = doc.lines.len().saturating_sub(1);
+        if !doc.source.ends_with('\n') {
+            vec![Finding::at_line(last, "Missing final newline")]
+        } else if doc.source.ends_with("\n\n") {
+            vec![Finding::at_line(last, "Extra blank lines at end of file")]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+/// Values must not nest deeper than `max` (default 16).
+pub struct MaxDepth;
+
+impl Rule for MaxDepth {
+    fn name(&self) -> &'static str {
+        "max-depth"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, doc: &LintDocument<'_>, options: &Value) -> Vec<Finding> {
+        let max = option_usize(options, "max").unwrap_or(16);
+        let mut findings = Vec::new();
+        if let Some(value) = doc.value {
+            walk(value, "", &mut |path, v, depth| {
+                if depth == max + 1 && matches!(v, Value::Array(_) | Value::Object(_)) {
+                    findings.push(Finding::at_path(
+                        display_path(path),
+                        format!("Nesting exceeds maximum depth of {}", max),
+                    ));
+                }
+            });
+        }
+        findings
+    }
+}
+
+/// Object keys must follow a naming style (`style`: `snake`, `kebab`, or
+/// `camel`). Off by default since YAY itself has no key convention.
+pub struct KeyStyle;
+
+impl KeyStyle {
+    fn matches(style: &str, key: &str) -> bool {
+        let mut chars = key.chars();
+        let first_ok = chars.next().is_some_and(|c| c.is_ascii_lowercase());
+        first_ok
+            && match style {
+                "snake" => key
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_'),
+                "kebab" => key
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-'),
+                "camel" => key.chars().all(|c| c.is_ascii_alphanumeric()),
+                _ => true,
+            }
+    }
+}
+
+impl Rule for KeyStyle {
+    fn name(&self) -> &'static str {
+        "key-style"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Off
+    }
+
+    fn check(&self, doc: &LintDocument<'_>, options: &Value) -> Vec<Finding> {
+        let style = option_str(options, "style").unwrap_or("snake");
+        let mut findings = Vec::new();
+        if let Some(value) = doc.value {
+            walk(value, "", &mut |path, v, _| {
+                if let Value::Object(obj) = v {
+                    let mut keys: Vec<&String> = obj.keys().collect();
+                    keys.sort();
+                    for key in keys {
+                        if !KeyStyle::matches(style, key) {
+                            findings.push(Finding::at_path(
+                                display_path(&child_path(path, key)),
+                                format!("Key \"{}\" is not {} case", key, style),
+                            ));
+                        }
+                    }
+                }
+            });
+        }
+        findings
+    }
+}
+
+/// Computes the encoded size of a value in some format, or `None` if the
+/// value cannot be encoded in that format.
+pub type Sizer = Box<dyn Fn(&Value) -> Option<usize>>;
+
+/// Encoded subtrees must fit size budgets, for documents shipped to
+/// memory-constrained targets.
+///
+/// Options:
+///
+/// - `format`: the encoding to measure (`yay`, `json`, `yson`, or any format
+///   registered with [`SizeBudget::with_sizer`]); defaults to `yay`.
+/// - `budgets`: an object mapping value paths (`firmware.blob`,
+///   `tables[0]`, or `(root)`) to limits such as `"256KiB"`, `"<= 1MB"`, or
+///   an integer byte count.
+///
+/// Paths that do not exist in the document are skipped.
+pub struct SizeBudget {
+    sizers: HashMap<String, Sizer>,
+}
+
+impl SizeBudget {
+    /// A size budget rule that can measure the formats `libyay::encode`
+    /// supports directly.
+    pub fn new() -> Self {
+        let mut sizers: HashMap<String, Sizer> = HashMap::new();
+        for (name, format) in [
+            ("yay", crate::Format::Yay),
+            ("json", crate::Format::Json),
+            ("yson", crate::Format::Yson),
+        ] {
+            sizers.insert(
+                name.to_string(),
+                Box::new(move |v: &Value| Some(crate::encode(v, format).len())),
+            );
+        }
+        SizeBudget { sizers }
+    }
+
+    /// Register (or replace) the sizer for a format name.
+    pub fn with_sizer(mut self, format: &str, sizer: Sizer) -> Self {
+        self.sizers.insert(format.to_string(), sizer);
+        self
+    }
+}
+
+impl Default for SizeBudget {
+    fn default() -> Self {
+        SizeBudget::new()
+    }
+}
+
+impl Rule for SizeBudget {
+    fn name(&self) -> &'static str {
+        "size-budget"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, doc: &LintDocument<'_>, options: &Value) -> Vec<Finding> {
+        let Some(value) = doc.value else {
+            return Vec::new();
+        };
+        let Some(budgets) = options.as_object().and_then(|o| o.get("budgets")) else {
+            return Vec::new();
+        };
+        let format = option_str(options, "format").unwrap_or("yay");
+        let Some(sizer) = self.sizers.get(format) else {
+            return vec![Finding::at_path(
+                "(root)",
+                format!("No sizer available for format {}", format),
+            )];
+        };
+        let Some(budgets) = budgets.as_object() else {
+            return vec![Finding::at_path(
+                "(root)",
+                "size-budget budgets must be an object",
+            )];
+        };
+
+        let mut paths: Vec<&String> = budgets.keys().collect();
+        paths.sort();
+        let mut findings = Vec::new();
+        for path in paths {
+            let limit = match parse_budget(&budgets[path]) {
+                Some(limit) => limit,
+                None => {
+                    findings.push(Finding::at_path(
+                        path.as_str(),
+                        format!("Invalid size budget {:?}", budgets[path]),
+                    ));
+                    continue;
+                }
+            };
+            let Some(subtree) = resolve_path(value, path) else {
+                continue;
+            };
+            match sizer(subtree) {
+                Some(size) if size > limit => findings.push(Finding::at_path(
+                    path.as_str(),
+                    format!(
+                        "Encoded size in {} is {} bytes, budget is {} bytes",
+                        format, size, limit
+                    ),
+                )),
+                Some(_) => {}
+                None => findings.push(Finding::at_path(
+                    path.as_str(),
+                    format!("Value cannot be encoded as {}", format),
+                )),
+            }
+        }
+        findings
+    }
+}
+
+/// Parse a budget: an integer byte count or a string like `"<= 256KiB"`.
+fn parse_budget(value: &Value) -> Option<usize> {
+    use num_traits::ToPrimitive;
+    match value {
+        Value::Integer(n) => n.to_usize(),
+        Value::String(s) => {
+            let s = s.trim();
+            let s = s.strip_prefix("<=").unwrap_or(s).trim();
+            parse_byte_size(s).and_then(|n| usize::try_from(n).ok())
+        }
+        _ => None,
+    }
+}
+
+/// Resolve a dotted value path such as `servers[0].port` against a value.
+/// The empty path and `(root)` name the value itself.
+pub fn resolve_path<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    if path.is_empty() || path == "(root)" {
+        return Some(value);
+    }
+    let mut current = value;
+    for segment in path.split('.') {
+        let (key, mut indices) = match segment.find('[') {
+            Some(i) => (&segment[..i], &segment[i..]),
+            None => (segment, ""),
+        };
+        if !key.is_empty() {
+            current = current.as_object()?.get(key)?;
+        }
+        while let Some(rest) = indices.strip_prefix('[') {
+            let close = rest.find(']')?;
+            let index: usize = rest[..close].parse().ok()?;
+            current = current.as_array()?.get(index)?;
+            indices = &rest[close + 1..];
+        }
+    }
+    Some(current)
+}
+
+/// The rules every [`Linter`] starts with.
+pub fn builtin_rules() -> Vec<Box<dyn Rule>> {
+    vec![
+        Box::new(LineLength),
+        Box::new(FinalNewline),
+        Box::new(MaxDepth),
+        Box::new(KeyStyle),
+        Box::new(SizeBudget::new()),
+    ]
+}
+
+/// Visit every value with its path and depth (the root has depth 0).
+/// Object keys are visited in sorted order so findings are deterministic.
+pub fn walk(value: &Value, path: &str, visit: &mut dyn FnMut(&str, &Value, usize)) {
+    walk_inner(value, path, 0, visit);
+}
+
+fn walk_inner(value: &Value, path: &str, depth: usize, visit: &mut dyn FnMut(&str, &Value, usize)) {
+    visit(path, value, depth);
+    match value {
+        Value::Array(arr) => {
+            for (i, item) in arr.iter().enumerate() {
+                walk_inner(item, &format!("{}[{}]", path, i), depth + 1, visit);
+            }
+        }
+        Value::Object(obj) => {
+            let mut keys: Vec<&String> = obj.keys().collect();
+            keys.sort();
+            for key in keys {
+                walk_inner(&obj[key], &child_path(path, key), depth + 1, visit);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn child_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+fn display_path(path: &str) -> String {
+    if path.is_empty() {
+        "(root)".to_string()
+    } else {
+        path.to_string()
+    }
+}
+
+// =============================================================================
+// Configuration
+// =============================================================================
+
+/// Configuration for a single rule.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleConfig {
+    /// Severity override, if configured.
+    pub severity: Option<Severity>,
+    /// Rule-specific options (an object, or `Value::Null`).
+    pub options: Value,
+}
+
+/// Rules disabled for files whose path matches a glob pattern.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathOverride {
+    /// Glob pattern (`*` matches within a path segment, `**` across segments).
+    pub pattern: String,
+    /// Names of rules to disable for matching files.
+    pub disable: Vec<String>,
+}
+
+/// Lint configuration, usually loaded from a YAY file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LintConfig {
+    /// Per-rule configuration by rule name.
+    pub rules: HashMap<String, RuleConfig>,
+    /// Path-scoped rule suppressions.
+    pub paths: Vec<PathOverride>,
+}
+
+impl LintConfig {
+    /// Parse a configuration from YAY source.
+    pub fn from_yay(source: &str) -> Result<LintConfig, String> {
+        let value = crate::parse(source).map_err(|e| e.to_string())?;
+        LintConfig::from_value(&value)
+    }
+
+    /// Build a configuration from a parsed value.
+    pub fn from_value(value: &Value) -> Result<LintConfig, String> {
+        let mut config = LintConfig::default();
+        let root = match value {
+            Value::Null => return Ok(config),
+            Value::Object(obj) => obj,
+            _ => return Err("Lint config must be an object".to_string()),
+        };
+
+        if let Some(rules) = root.get("rules") {
+            let rules = rules
+                .as_object()
+                .ok_or_else(|| "Lint config \"rules\" must be an object".to_string())?;
+            for (name, setting) in rules {
+                let rule_config = match setting {
+                    Value::String(s) => RuleConfig {
+                        severity: Some(parse_severity(name, s)?),
+                        options: Value::Null,
+                    },
+                    Value::Object(obj) => RuleConfig {
+                        severity: match obj.get("severity") {
+                            Some(Value::String(s)) => Some(parse_severity(name, s)?),
+                            Some(_) => {
+                                return Err(format!("Severity for rule {} must be a string", name))
+                            }
+                            None => None,
+                        },
+                        options: setting.clone(),
+                    },
+                    _ => {
+                        return Err(format!(
+                            "Rule {} must be configured with a severity or an object",
+                            name
+                        ))
+                    }
+                };
+                config.rules.insert(name.clone(), rule_config);
+            }
+        }
+
+        if let Some(paths) = root.get("paths") {
+            let paths = paths
+                .as_array()
+                .ok_or_else(|| "Lint config \"paths\" must be an array".to_string())?;
+            for entry in paths {
+                let pattern = entry
+                    .as_object()
+                    .and_then(|o| o.get("pattern"))
+                    .and_then(|p| p.as_str())
+                    .ok_or_else(|| "Each lint path override needs a \"pattern\"".to_string())?;
+                let disable = entry
+                    .as_object()
+                    .and_then(|o| o.get("disable"))
+                    .and_then(|d| d.as_array())
+                    .map(|arr| {
+                        arr.iter()
+                            .filter_map(|v| v.as_str().map(String::from))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                config.paths.push(PathOverride {
+                    pattern: pattern.to_string(),
+                    disable,
+                });
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+fn parse_severity(rule: &str, s: &str) -> Result<Severity, String> {
+    Severity::from_name(s).ok_or_else(|| format!("Unknown severity \"{}\" for rule {}", s, rule))
+}
+
+/// Match a path against a glob pattern where `*` matches any run of
+/// characters other than `/`, `**` matches anything, and `?` matches one
+/// character.
+pub fn glob_match(pattern: &str, path: &str) -> bool {
+    fn go(p: &[u8], s: &[u8]) -> bool {
+        match p.first() {
+            None => s.is_empty(),
+            Some(b'*') if p.get(1) == Some(&b'*') => {
+                let rest = p[2..].strip_prefix(b"/").unwrap_or(&p[2..]);
+                (0..=s.len()).any(|i| go(rest, &s[i..]))
+            }
+            Some(b'*') => {
+                let mut i = 0;
+                loop {
+                    if go(&p[1..], &s[i..]) {
+                        return true;
+                    }
+                    if i >= s.len() || s[i] == b'/' {
+                        return false;
+                    }
+                    i += 1;
+                }
+            }
+            Some(b'?') => !s.is_empty() && s[0] != b'/' && go(&p[1..], &s[1..]),
+            Some(&c) => s.first() == Some(&c) && go(&p[1..], &s[1..]),
+        }
+    }
+    go(pattern.as_bytes(), path.as_bytes())
+}
+
+// =============================================================================
+// Suppression comments
+// =============================================================================
+
+/// Rules suppressed by `# yay-lint: disable=...` comments in a document.
+#[derive(Debug, Default)]
+struct Suppressions {
+    file: HashSet<String>,
+    lines: HashMap<usize, HashSet<String>>,
+}
+
+impl Suppressions {
+    fn scan(lines: &[&str]) -> Self {
+        let mut result = Suppressions::default();
+        for (i, line) in lines.iter().enumerate() {
+            let Some(hash) = comment_start(line) else {
+                continue;
+            };
+            let comment = line[hash + 1..].trim();
+            let Some(directive) = comment.strip_prefix(SUPPRESS_PREFIX) else {
+                continue;
+            };
+            let Some(names) = directive.trim().strip_prefix("disable=") else {
+                continue;
+            };
+            let names = names.split(',').map(|n| n.trim().to_string());
+            if line[..hash].trim().is_empty() {
+                result.file.extend(names);
+            } else {
+                result.lines.entry(i).or_default().extend(names);
+            }
+        }
+        result
+    }
+
+    fn suppresses(&self, rule: &str, line: Option<usize>) -> bool {
+        if self.file.contains(rule) || self.file.contains("all") {
+            return true;
+        }
+        line.and_then(|l| self.lines.get(&l))
+            .is_some_and(|set| set.contains(rule) || set.contains("all"))
+    }
+}
+
+/// Find the byte index of a `#` that starts a comment, ignoring `#` inside
+/// quoted strings.
+fn comment_start(line: &str) -> Option<usize> {
+    let mut quote: Option<char> = None;
+    let mut escape = false;
+    for (i, c) in line.char_indices() {
+        if escape {
+            escape = false;
+            continue;
+        }
+        match quote {
+            Some(q) => {
+                if c == '\\' {
+                    escape = true;
+                } else if c == q {
+                    quote = None;
+                }
+            }
+            None => match c {
+                '"' | '\'' => quote = Some(c),
+                '#' => return Some(i),
+                _ => {}
+            },
+        }
+    }
+    None
+}
+
+// =============================================================================
+// Linter
+// =============================================================================
+
+/// Runs rules over documents according to a configuration.
+pub struct Linter {
+    rules: Vec<Box<dyn Rule>>,
+    config: LintConfig,
+}
+
+impl Linter {
+    /// Create a linter with the built-in rules and the given configuration.
+    pub fn new(config: LintConfig) -> Self {
+        Linter {
+            rules: builtin_rules(),
+            config,
+        }
+    }
+
+    /// Register an additional rule, replacing any rule with the same name.
+    pub fn add_rule(&mut self, rule: Box<dyn Rule>) {
+        self.rules.retain(|r| r.name() != rule.name());
+        self.rules.push(rule);
+    }
+
+    /// Names of all registered rules.
+    pub fn rule_names(&self) -> Vec<&'static str> {
+        self.rules.iter().map(|r| r.name()).collect()
+    }
+
+    /// Check that the configuration names only registered rules, so that a
+    /// misspelled rule is reported rather than silently never applied.
+    /// Call this after registering any additional rules.
+    pub fn validate_config(&self) -> Result<(), String> {
+        let known = self.rule_names();
+        let mut unknown: Vec<&str> = self
+            .config
+            .rules
+            .keys()
+            .map(String::as_str)
+            .chain(
+                self.config
+                    .paths
+                    .iter()
+                    .flat_map(|o| o.disable.iter().map(String::as_str))
+                    .filter(|name| *name != "all"),
+            )
+            .filter(|name| !known.contains(name))
+            .collect();
+        unknown.sort_unstable();
+        unknown.dedup();
+        match unknown.as_slice() {
+            [] => Ok(()),
+            [name] => Err(format!("Unknown lint rule \"{}\"", name)),
+            names => Err(format!("Unknown lint rules \"{}\"", names.join("\", \""))),
+        }
+    }
+
+    /// Lint a document. Parse failures are reported as a `parse` error
+    /// diagnostic and value-based rules are skipped.
+    pub fn lint(&self, source: &str, path: Option<&str>) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        let parsed = crate::parse_with_filename(source, path);
+        if let Err(e) = &parsed {
+            diagnostics.push(Diagnostic {
+                rule: "parse".to_string(),
+                severity: Severity::Error,
+                line: None,
+                path: None,
+                message: e.to_string(),
+            });
+        }
+
+        let lines: Vec<&str> = source.lines().collect();
+        let suppressions = Suppressions::scan(&lines);
+        let doc = LintDocument {
+            source,
+            lines,
+            value: parsed.as_ref().ok(),
+            path,
+        };
+
+        for rule in &self.rules {
+            let name = rule.name();
+            let rule_config = self.config.rules.get(name);
+            let severity = rule_config
+                .and_then(|c| c.severity)
+                .unwrap_or_else(|| rule.default_severity());
+            if severity == Severity::Off || self.disabled_for_path(name, path) {
+                continue;
+            }
+            let options = rule_config.map(|c| &c.options).unwrap_or(&Value::Null);
+            for finding in rule.check(&doc, options) {
+                if suppressions.suppresses(name, finding.line) {
+                    continue;
+                }
+                diagnostics.push(Diagnostic {
+                    rule: name.to_string(),
+                    severity,
+                    line: finding.line,
+                    path: finding.path,
+                    message: finding.message,
+                });
+            }
+        }
+
+        diagnostics
+    }
+
+    fn disabled_for_path(&self, rule: &str, path: Option<&str>) -> bool {
+        let Some(path) = path else {
+            return false;
+        };
+        self.config.paths.iter().any(|o| {
+            glob_match(&o.pattern, path) && o.disable.iter().any(|d| d == rule || d == "all")
+        })
+    }
+}
+
+impl Default for Linter {
+    fn default() -> Self {
+        Linter::new(LintConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules_fired(diagnostics: &[Diagnostic]) -> Vec<&str> {
+        diagnostics.iter().map(|d| d.rule.as_str()).collect()
+    }
+
+    #[test]
+    fn test_line_length_and_suppression() {
+        let long = format!("a: \"{}\"\n", "x".repeat(90));
+        let linter = Linter::default();
+        assert_eq!(rules_fired(&linter.lint(&long, None)), vec!["line-length"]);
+
+        let suppressed = format!("# yay-lint: disable=line-length\n{}", long);
+        assert!(linter.lint(&suppressed, None).is_empty());
+
+        let inline = format!(
+            "a: \"{}\"  # yay-lint: disable=line-length\n",
+            "x".repeat(90)
+        );
+        assert!(linter.lint(&inline, None).is_empty());
+    }
+
+    #[test]
+    fn test_config_severity_and_options() {
+        let config = LintConfig::from_yay(
+            "rules:\n  key-style: {severity: \"error\", style: \"kebab\"}\n  final-newline: \"off\"\n",
+        )
+        .unwrap();
+        let linter = Linter::new(config);
+        let diagnostics = linter.lint("my_key: 1", None);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].rule, "key-style");
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+        assert_eq!(diagnostics[0].path.as_deref(), Some("my_key"));
+    }
+
+    #[test]
+    fn test_unknown_rule_names() {
+        let config =
+            LintConfig::from_yay("rules:\n  line-lenght: \"off\"\n  final-newline: \"off\"\n")
+                .unwrap();
+        assert_eq!(
+            Linter::new(config).validate_config(),
+            Err("Unknown lint rule \"line-lenght\"".to_string())
+        );
+
+        let config = LintConfig::from_yay(
+            "paths:\n  - pattern: \"*\"\n    disable: [\"all\", \"size\", \"depth\"]\n",
+        )
+        .unwrap();
+        assert_eq!(
+            Linter::new(config).validate_config(),
+            Err("Unknown lint rules \"depth\", \"size\"".to_string())
+        );
+
+        let config = LintConfig::from_yay("rules:\n  size-budget: {max: 10}\n").unwrap();
+        let mut linter = Linter::new(config);
+        linter.add_rule(Box::new(SizeBudget::new()));
+        assert_eq!(linter.validate_config(), Ok(()));
+    }
+
+    #[test]
+    fn test_path_override() {
+        let config = LintConfig::from_yay(
+            "paths:\n  - pattern: \"vendor/**\"\n    disable: [\"final-newline\"]\n",
+        )
+        .unwrap();
+        let linter = Linter::new(config);
+        assert!(linter.lint("1", Some("vendor/a/b.yay")).is_empty());
+        assert_eq!(
+            rules_fired(&linter.lint("1", Some("src/b.yay"))),
+            vec!["final-newline"]
+        );
+    }
+
+    #[test]
+    fn test_max_depth() {
+        let config = LintConfig::from_yay("rules:\n  max-depth: {max: 1}\n").unwrap();
+        let linter = Linter::new(config);
+        let diagnostics = linter.lint("a:\n  b:\n    c: 1\n", None);
+        assert_eq!(rules_fired(&diagnostics), vec!["max-depth"]);
+        assert_eq!(diagnostics[0].path.as_deref(), Some("a.b"));
+    }
+
+    #[test]
+    fn test_parse_error_reported() {
+        let diagnostics = Linter::default().lint("a:  1\n", None);
+        assert_eq!(rules_fired(&diagnostics), vec!["parse"]);
+    }
+
+    #[test]
+    fn test_size_budget() {
+        let config = LintConfig::from_yay(
+            "rules:\n  size-budget:\n    format: \"json\"\n    budgets: {\"a.b[1]\": \"<= 3B\", c: 100}\n",
+        )
+        .unwrap();
+        let mut linter = Linter::new(config);
+        let source = "a:\n  b: [\"x\", \"long\"]\nc: \"short\"\n";
+        let diagnostics = linter.lint(source, None);
+        assert_eq!(rules_fired(&diagnostics), vec!["size-budget"]);
+        assert_eq!(diagnostics[0].path.as_deref(), Some("a.b[1]"));
+
+        // A registered sizer replaces the built-in measurement.
+        linter.add_rule(Box::new(
+            SizeBudget::new().with_sizer("json", Box::new(|_| Some(0))),
+        ));
+        assert!(linter.lint(source, None).is_empty());
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*.yay", "a.yay"));
+        assert!(!glob_match("*.yay", "dir/a.yay"));
+        assert!(glob_match("**/*.yay", "dir/sub/a.yay"));
+        assert!(glob_match("dir/?.yay", "dir/a.yay"));
+    }
+}
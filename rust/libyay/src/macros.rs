@@ -0,0 +1,231 @@
+//! The `yay!` macro for writing `Value` trees as literals.
+
+/// Build a [`Value`](crate::Value) from YAY-like syntax.
+///
+/// Objects are `{ key: value, ... }`, where a key is an identifier or a
+/// literal; arrays are `[value, ...]`; byte arrays are `<hex>`; and `null`,
+/// `true`, and `false` are themselves. Anything else is a Rust expression
+/// converted with `Value::from`, so numbers, strings, and variables work as
+/// they are. The braces of a top-level object may be left out.
+///
+/// ```
+/// use libyay::{parse, yay};
+///
+/// let port = 8080;
+/// let value = yay! {
+///     name: "web",
+///     ports: [80, port],
+///     key: <cafe babe>,
+///     "tls enabled": true,
+///     limits: { cpu: 1.5, memory: null },
+/// };
+/// assert_eq!(
+///     value,
+///     parse(
+///         "name: \"web\"\n\
+///          ports: [80, 8080]\n\
+///          key: <cafebabe>\n\
+///          \"tls enabled\": true\n\
+///          limits: {cpu: 1.5, memory: null}"
+///     )
+///     .unwrap()
+/// );
+/// ```
+///
+/// Rust must be able to read the hex digits of a byte array as tokens,
+/// which it cannot for a group like `1e`. Such digits may be quoted:
+/// `<"1e00">`.
+#[macro_export]
+macro_rules! yay {
+    // Arrays: elements so far, then the tokens left.
+    (@array [$($elems:expr,)*]) => {
+        $crate::__alloc::vec![$($elems,)*]
+    };
+    (@array [$($elems:expr,)*] @built ($value:expr) , $($rest:tt)*) => {
+        $crate::yay!(@array [$($elems,)* $value,] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] @built ($value:expr)) => {
+        $crate::yay!(@array [$($elems,)* $value,])
+    };
+    (@array [$($elems:expr,)*] null $($rest:tt)*) => {
+        $crate::yay!(@array [$($elems,)*] @built ($crate::Value::Null) $($rest)*)
+    };
+    (@array [$($elems:expr,)*] true $($rest:tt)*) => {
+        $crate::yay!(@array [$($elems,)*] @built ($crate::Value::Bool(true)) $($rest)*)
+    };
+    (@array [$($elems:expr,)*] false $($rest:tt)*) => {
+        $crate::yay!(@array [$($elems,)*] @built ($crate::Value::Bool(false)) $($rest)*)
+    };
+    (@array [$($elems:expr,)*] [$($array:tt)*] $($rest:tt)*) => {
+        $crate::yay!(@array [$($elems,)*] @built ($crate::yay!([$($array)*])) $($rest)*)
+    };
+    (@array [$($elems:expr,)*] {$($object:tt)*} $($rest:tt)*) => {
+        $crate::yay!(@array [$($elems,)*] @built ($crate::yay!({$($object)*})) $($rest)*)
+    };
+    (@array [$($elems:expr,)*] < $($rest:tt)*) => {
+        $crate::yay!(@bytes (@array [$($elems,)*]) () $($rest)*)
+    };
+    (@array [$($elems:expr,)*] $value:expr , $($rest:tt)*) => {
+        $crate::yay!(@array [$($elems,)* $crate::Value::from($value),] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] $value:expr) => {
+        $crate::yay!(@array [$($elems,)* $crate::Value::from($value),])
+    };
+
+    // Objects: the map to insert into, then the tokens left.
+    (@object $map:ident) => {};
+    (@object $map:ident $key:ident : $($rest:tt)*) => {
+        $crate::yay!(@member $map ($crate::__alloc::string::String::from(stringify!($key))) $($rest)*)
+    };
+    (@object $map:ident $key:literal : $($rest:tt)*) => {
+        $crate::yay!(@member $map ($crate::__alloc::string::ToString::to_string(&$key)) $($rest)*)
+    };
+    (@member $map:ident ($key:expr) @built ($value:expr) , $($rest:tt)*) => {
+        $map.insert($key, $value);
+        $crate::yay!(@object $map $($rest)*);
+    };
+    (@member $map:ident ($key:expr) @built ($value:expr)) => {
+        $map.insert($key, $value);
+    };
+    (@member $map:ident ($key:expr) null $($rest:tt)*) => {
+        $crate::yay!(@member $map ($key) @built ($crate::Value::Null) $($rest)*)
+    };
+    (@member $map:ident ($key:expr) true $($rest:tt)*) => {
+        $crate::yay!(@member $map ($key) @built ($crate::Value::Bool(true)) $($rest)*)
+    };
+    (@member $map:ident ($key:expr) false $($rest:tt)*) => {
+        $crate::yay!(@member $map ($key) @built ($crate::Value::Bool(false)) $($rest)*)
+    };
+    (@member $map:ident ($key:expr) [$($array:tt)*] $($rest:tt)*) => {
+        $crate::yay!(@member $map ($key) @built ($crate::yay!([$($array)*])) $($rest)*)
+    };
+    (@member $map:ident ($key:expr) {$($object:tt)*} $($rest:tt)*) => {
+        $crate::yay!(@member $map ($key) @built ($crate::yay!({$($object)*})) $($rest)*)
+    };
+    (@member $map:ident ($key:expr) < $($rest:tt)*) => {
+        $crate::yay!(@bytes (@member $map ($key)) () $($rest)*)
+    };
+    (@member $map:ident ($key:expr) $value:expr , $($rest:tt)*) => {
+        $crate::yay!(@member $map ($key) @built ($crate::Value::from($value)) , $($rest)*)
+    };
+    (@member $map:ident ($key:expr) $value:expr) => {
+        $crate::yay!(@member $map ($key) @built ($crate::Value::from($value)))
+    };
+
+    // Byte arrays: where to go next, the hex so far, then the tokens left.
+    (@bytes ($($next:tt)*) ($($hex:tt)*) > $($rest:tt)*) => {
+        $crate::yay!($($next)* @built ($crate::Value::Bytes(
+            $crate::__hex_bytes(stringify!($($hex)*))
+        )) $($rest)*)
+    };
+    (@bytes ($($next:tt)*) ($($hex:tt)*) $digits:tt $($rest:tt)*) => {
+        $crate::yay!(@bytes ($($next)*) ($($hex)* $digits) $($rest)*)
+    };
+    (@built ($value:expr)) => {
+        $value
+    };
+
+    // Values.
+    () => {
+        $crate::Value::Object($crate::Map::default())
+    };
+    (null) => {
+        $crate::Value::Null
+    };
+    (true) => {
+        $crate::Value::Bool(true)
+    };
+    (false) => {
+        $crate::Value::Bool(false)
+    };
+    ([$($array:tt)*]) => {
+        $crate::Value::Array($crate::yay!(@array [] $($array)*))
+    };
+    ({$($object:tt)*}) => {
+        $crate::Value::Object({
+            #[allow(unused_mut)]
+            let mut map = $crate::Map::default();
+            $crate::yay!(@object map $($object)*);
+            map
+        })
+    };
+    (< $($rest:tt)*) => {
+        $crate::yay!(@bytes () () $($rest)*)
+    };
+    ($key:ident : $($rest:tt)*) => {
+        $crate::yay!({$key : $($rest)*})
+    };
+    ($key:literal : $($rest:tt)*) => {
+        $crate::yay!({$key : $($rest)*})
+    };
+    ($value:expr) => {
+        $crate::Value::from($value)
+    };
+}
+
+use alloc::vec::Vec;
+
+/// The bytes of the hex digits in a `yay!` byte array.
+#[doc(hidden)]
+pub fn hex_bytes(hex: &str) -> Vec<u8> {
+    let digits: Vec<u8> = hex
+        .bytes()
+        .filter(|b| !b.is_ascii_whitespace() && *b != b'"')
+        .map(|b| match (b as char).to_digit(16) {
+            Some(digit) => digit as u8,
+            None => panic!("yay!: invalid hex digit {:?} in <{}>", b as char, hex),
+        })
+        .collect();
+    if !digits.len().is_multiple_of(2) {
+        panic!("yay!: odd number of hex digits in <{}>", hex);
+    }
+    digits
+        .chunks(2)
+        .map(|pair| pair[0] << 4 | pair[1])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse, Map, Value};
+    use num_bigint::BigInt;
+
+    #[test]
+    fn test_scalars() {
+        assert_eq!(yay!(null), Value::Null);
+        assert_eq!(yay!(true), Value::Bool(true));
+        assert_eq!(yay!(-42), Value::Integer(BigInt::from(-42)));
+        assert_eq!(yay!(0.5), Value::Float(0.5));
+        assert_eq!(yay!("hi"), Value::String("hi".to_string()));
+        assert_eq!(yay!(<>), Value::Bytes(vec![]));
+        assert_eq!(
+            yay!(<cafe 0f"1e">),
+            Value::Bytes(vec![0xca, 0xfe, 0x0f, 0x1e])
+        );
+        assert_eq!(yay!({}), Value::Object(Map::new()));
+    }
+
+    #[test]
+    fn test_nesting() {
+        let name = String::from("x");
+        let value = yay! {
+            a: [1, [], {}, <ff>, null, false, name.as_str()],
+            "b c": { d: [{ e: <00> }], f: 2 + 3 },
+            1: true
+        };
+        let expected = parse(
+            "a: [1, [], {}, <ff>, null, false, \"x\"]\n\
+             \"b c\": {d: [{e: <00>}], f: 5}\n\
+             \"1\": true",
+        )
+        .unwrap();
+        assert_eq!(value, expected);
+        assert_eq!(yay!([[1, 2,], { a: 1, },]), yay!([[1, 2], { a: 1 }]));
+    }
+
+    #[test]
+    #[should_panic(expected = "odd number of hex digits")]
+    fn test_odd_hex() {
+        yay!(<abc>);
+    }
+}
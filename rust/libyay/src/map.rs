@@ -0,0 +1,179 @@
+//! The maps that can hold an object's members.
+//!
+//! [`GenericValue`](crate::GenericValue) takes a [`MapKind`] that names the
+//! map of every object in it. [`Value`](crate::Value), what the parsers
+//! produce, is the [`Indexed`] kind. Because the kind is a type parameter
+//! rather than a feature, crates in one build can each choose their own.
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+
+use indexmap::IndexMap;
+
+use crate::value::MapState;
+
+/// A choice of map for the objects of a [`GenericValue`](crate::GenericValue).
+pub trait MapKind: Sized + 'static {
+    /// The map from keys to `V`.
+    type Map<V>: ObjectMap<V>;
+}
+
+/// What a value needs of the map behind its objects.
+pub trait ObjectMap<V>:
+    Default + FromIterator<(String, V)> + IntoIterator<Item = (String, V)>
+{
+    /// The member named `key`.
+    fn get(&self, key: &str) -> Option<&V>;
+
+    /// The member named `key`, to change.
+    fn get_mut(&mut self, key: &str) -> Option<&mut V>;
+
+    /// Set the member named `key`, returning what it replaced.
+    fn insert(&mut self, key: String, value: V) -> Option<V>;
+
+    /// The number of members.
+    fn len(&self) -> usize;
+
+    /// Whether there are no members.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The members, in the map's own order.
+    fn members<'a>(&'a self) -> impl Iterator<Item = (&'a String, &'a V)>
+    where
+        V: 'a;
+
+    /// A copy of the map.
+    fn duplicate(&self) -> Self
+    where
+        V: Clone;
+}
+
+/// Objects keep their keys in the order they were inserted, which for a
+/// parsed document is the order it wrote them. This is the kind of
+/// [`Value`](crate::Value).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Indexed {}
+
+/// Objects keep their keys sorted by their bytes, in a `BTreeMap`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sorted {}
+
+/// Objects keep their keys in no order, in a `HashMap`, which is quicker
+/// to look up in large objects.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hashed {}
+
+impl MapKind for Indexed {
+    type Map<V> = IndexMap<String, V, MapState>;
+}
+
+impl MapKind for Sorted {
+    type Map<V> = BTreeMap<String, V>;
+}
+
+#[cfg(feature = "std")]
+impl MapKind for Hashed {
+    type Map<V> = HashMap<String, V>;
+}
+
+impl<V> ObjectMap<V> for IndexMap<String, V, MapState> {
+    fn get(&self, key: &str) -> Option<&V> {
+        IndexMap::get(self, key)
+    }
+
+    fn get_mut(&mut self, key: &str) -> Option<&mut V> {
+        IndexMap::get_mut(self, key)
+    }
+
+    fn insert(&mut self, key: String, value: V) -> Option<V> {
+        IndexMap::insert(self, key, value)
+    }
+
+    fn len(&self) -> usize {
+        IndexMap::len(self)
+    }
+
+    fn members<'a>(&'a self) -> impl Iterator<Item = (&'a String, &'a V)>
+    where
+        V: 'a,
+    {
+        self.iter()
+    }
+
+    fn duplicate(&self) -> Self
+    where
+        V: Clone,
+    {
+        self.clone()
+    }
+}
+
+impl<V> ObjectMap<V> for BTreeMap<String, V> {
+    fn get(&self, key: &str) -> Option<&V> {
+        BTreeMap::get(self, key)
+    }
+
+    fn get_mut(&mut self, key: &str) -> Option<&mut V> {
+        BTreeMap::get_mut(self, key)
+    }
+
+    fn insert(&mut self, key: String, value: V) -> Option<V> {
+        BTreeMap::insert(self, key, value)
+    }
+
+    fn len(&self) -> usize {
+        BTreeMap::len(self)
+    }
+
+    fn members<'a>(&'a self) -> impl Iterator<Item = (&'a String, &'a V)>
+    where
+        V: 'a,
+    {
+        self.iter()
+    }
+
+    fn duplicate(&self) -> Self
+    where
+        V: Clone,
+    {
+        self.clone()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<V> ObjectMap<V> for HashMap<String, V> {
+    fn get(&self, key: &str) -> Option<&V> {
+        HashMap::get(self, key)
+    }
+
+    fn get_mut(&mut self, key: &str) -> Option<&mut V> {
+        HashMap::get_mut(self, key)
+    }
+
+    fn insert(&mut self, key: String, value: V) -> Option<V> {
+        HashMap::insert(self, key, value)
+    }
+
+    fn len(&self) -> usize {
+        HashMap::len(self)
+    }
+
+    fn members<'a>(&'a self) -> impl Iterator<Item = (&'a String, &'a V)>
+    where
+        V: 'a,
+    {
+        self.iter()
+    }
+
+    fn duplicate(&self) -> Self
+    where
+        V: Clone,
+    {
+        self.clone()
+    }
+}
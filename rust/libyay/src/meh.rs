@@ -5,23 +5,35 @@
 //! - Preserves comments and their line associations
 //! - Preserves key order (not sorted alphabetically)
 //! - Preserves blank lines between sections
+//! - Sorts the keys of a block after a `# yay-fmt: sort-keys` comment
+//! - Writes an array of objects one row per line after a `# yay-fmt: table`
+//!   comment
 //!
 //! The MEH pipeline consists of:
 //! 1. MEH Parser - Parses loose YAY into a CST
 //! 2. MEH-to-YAY Transform - Normalizes the CST to canonical form
 //! 3. MEH Formatter - Serializes the CST back to text
 
+use crate::encode::normalize_hex_spaces;
+use crate::float::{format_float, parse_hex_float, FloatFormat};
+use crate::outline::Kind;
 use std::env;
+use std::ops::Range;
 
 /// Default line wrap length
 const DEFAULT_WRAP: usize = 80;
 
+/// Comment prefix that introduces a formatting directive.
+const FORMAT_PREFIX: &str = "yay-fmt:";
+
 /// Get the line wrap length from YAY_WRAP env var or default
 fn get_wrap_length() -> usize {
-    env::var("YAY_WRAP")
-        .ok()
-        .and_then(|s| s.parse().ok())
-        .unwrap_or(DEFAULT_WRAP)
+    env_width("YAY_WRAP").unwrap_or(DEFAULT_WRAP)
+}
+
+/// A width set by an environment variable, if it is set to a number.
+fn env_width(name: &str) -> Option<usize> {
+    env::var(name).ok().and_then(|s| s.parse().ok())
 }
 
 // =============================================================================
@@ -29,14 +41,16 @@ fn get_wrap_length() -> usize {
 // =============================================================================
 
 /// A CST node representing a YAY document
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Document {
+    /// A `%yay` version directive on the first line, kept as written.
+    pub directive: Option<String>,
     pub items: Vec<Item>,
     pub trailing_comments: Vec<Comment>,
 }
 
 /// An item in a document or block
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Item {
     /// A blank line
     BlankLine,
@@ -51,14 +65,14 @@ pub enum Item {
 }
 
 /// A comment with its content
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Comment {
     pub text: String,                // Content after #
     pub align_column: Option<usize>, // Column to align to (for inline comments in blocks)
 }
 
 /// A key-value property
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Property {
     pub key: Key,
     pub value: Option<PropertyValue>,
@@ -66,7 +80,7 @@ pub struct Property {
 }
 
 /// A property key
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Key {
     Bare(String),
     SingleQuoted(String),
@@ -74,34 +88,34 @@ pub enum Key {
 }
 
 /// The value part of a property
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum PropertyValue {
     Inline(CstValue),
     Block(Block),
 }
 
 /// A block (indented content after a property)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Block {
     pub items: Vec<Item>,
 }
 
 /// An array item (- prefix)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ArrayItem {
     pub value: Option<ArrayItemValue>,
     pub inline_comment: Option<Comment>,
 }
 
 /// The value part of an array item
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum ArrayItemValue {
     Inline(CstValue),
     Block(Block),
 }
 
 /// A CST value node
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum CstValue {
     Null,
     Bool(bool),
@@ -114,7 +128,7 @@ pub enum CstValue {
 }
 
 /// A string value
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum CstString {
     SingleQuoted(String),
     DoubleQuoted(String),
@@ -122,39 +136,41 @@ pub enum CstString {
 }
 
 /// A block string (backtick)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct BlockString {
     pub first_line: Option<String>, // Content after ` on first line
     pub lines: Vec<BlockStringLine>,
 }
 
-#[derive(Debug, Clone)]
+/// A line of a block string, with the column its content starts at.
+#[derive(Debug, Clone, PartialEq)]
 pub struct BlockStringLine {
     pub indent: usize,
     pub content: String,
 }
 
 /// A bytes value
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum CstBytes {
     Inline(InlineBytes),
     Block(BlockBytes),
 }
 
 /// Inline bytes <hex>
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct InlineBytes {
     pub content: String, // Content between < and >, preserving spaces
 }
 
 /// Block bytes (> prefix)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct BlockBytes {
     pub first_line_comment: Option<Comment>,
     pub lines: Vec<BlockBytesLine>,
 }
 
-#[derive(Debug, Clone)]
+/// A line of block bytes, with its comment.
+#[derive(Debug, Clone, PartialEq)]
 pub struct BlockBytesLine {
     pub indent: usize,
     pub hex: String,
@@ -162,23 +178,25 @@ pub struct BlockBytesLine {
 }
 
 /// Inline array [...]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct CstArray {
     pub items: Vec<CstArrayItem>,
 }
 
-#[derive(Debug, Clone)]
+/// An item of an inline array
+#[derive(Debug, Clone, PartialEq)]
 pub struct CstArrayItem {
     pub value: CstValue,
 }
 
 /// Inline object {...}
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct CstObject {
     pub entries: Vec<CstObjectEntry>,
 }
 
-#[derive(Debug, Clone)]
+/// An entry of an inline object
+#[derive(Debug, Clone, PartialEq)]
 pub struct CstObjectEntry {
     pub key: Key,
     pub value: CstValue,
@@ -192,6 +210,113 @@ pub struct MehParser<'a> {
     lines: Vec<&'a str>,
     line_idx: usize,
     col: usize,
+    /// The lines of every non-blank item, parents before children.
+    spans: Vec<Span>,
+    /// The spans of the items being parsed, innermost last.
+    open: Vec<usize>,
+}
+
+/// The lines an item occupies, what it is, and the item it is nested in.
+#[derive(Debug, Clone)]
+pub(crate) struct Span {
+    pub lines: Range<usize>,
+    pub parent: Option<usize>,
+    pub item: SpanItem,
+}
+
+/// The kind of item a span holds, with the shape of its value.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum SpanItem {
+    Property(String, Shape),
+    ArrayItem(Shape),
+    Comment,
+    Value(Shape),
+}
+
+/// The kind of a value, and the number of items in an inline array.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Shape {
+    pub kind: Kind,
+    pub inline_items: Option<usize>,
+}
+
+impl Shape {
+    fn of(kind: Kind) -> Self {
+        Shape {
+            kind,
+            inline_items: None,
+        }
+    }
+}
+
+impl SpanItem {
+    fn of(item: &Item) -> Self {
+        match item {
+            Item::Property(p) => SpanItem::Property(p.key.name().to_string(), p.shape()),
+            Item::ArrayItem(a) => SpanItem::ArrayItem(a.shape()),
+            Item::Value(v) => SpanItem::Value(v.shape()),
+            Item::Comment(_) | Item::BlankLine => SpanItem::Comment,
+        }
+    }
+}
+
+impl Key {
+    pub(crate) fn name(&self) -> &str {
+        match self {
+            Key::Bare(s) | Key::SingleQuoted(s) | Key::DoubleQuoted(s) => s,
+        }
+    }
+}
+
+impl Property {
+    fn shape(&self) -> Shape {
+        match &self.value {
+            None => Shape::of(Kind::Null),
+            Some(PropertyValue::Inline(v)) => v.shape(),
+            Some(PropertyValue::Block(b)) => b.shape(),
+        }
+    }
+}
+
+impl ArrayItem {
+    fn shape(&self) -> Shape {
+        match &self.value {
+            None => Shape::of(Kind::Null),
+            Some(ArrayItemValue::Inline(v)) => v.shape(),
+            Some(ArrayItemValue::Block(b)) => b.shape(),
+        }
+    }
+}
+
+impl Block {
+    fn shape(&self) -> Shape {
+        self.items
+            .iter()
+            .find_map(|item| match item {
+                Item::BlankLine | Item::Comment(_) => None,
+                Item::ArrayItem(_) => Some(Shape::of(Kind::Array)),
+                Item::Property(_) => Some(Shape::of(Kind::Object)),
+                Item::Value(v) => Some(v.shape()),
+            })
+            .unwrap_or(Shape::of(Kind::Null))
+    }
+}
+
+impl CstValue {
+    fn shape(&self) -> Shape {
+        match self {
+            CstValue::Null => Shape::of(Kind::Null),
+            CstValue::Bool(_) => Shape::of(Kind::Bool),
+            CstValue::Integer(_) | CstValue::Float(_) => Shape::of(Kind::Number),
+            CstValue::String(_) => Shape::of(Kind::String),
+            CstValue::Bytes(_) => Shape::of(Kind::Bytes),
+            CstValue::Array(a) => Shape {
+                kind: Kind::Array,
+                inline_items: Some(a.items.len()),
+            },
+            CstValue::Object(_) => Shape::of(Kind::Object),
+        }
+    }
 }
 
 impl<'a> MehParser<'a> {
@@ -201,11 +326,20 @@ impl<'a> MehParser<'a> {
             lines,
             line_idx: 0,
             col: 0,
+            spans: Vec::new(),
+            open: Vec::new(),
         }
     }
 
     pub fn parse(&mut self) -> Result<Document, String> {
         let mut items = Vec::new();
+        let directive = match self.lines.first() {
+            Some(line) if line.starts_with('%') => {
+                self.line_idx = 1;
+                Some(line.to_string())
+            }
+            _ => None,
+        };
 
         while self.line_idx < self.lines.len() {
             if let Some(item) = self.parse_item(0)? {
@@ -214,6 +348,7 @@ impl<'a> MehParser<'a> {
         }
 
         Ok(Document {
+            directive,
             items,
             trailing_comments: Vec::new(),
         })
@@ -228,7 +363,44 @@ impl<'a> MehParser<'a> {
         self.col = 0;
     }
 
+    /// The item spans found by `parse`, parents before children.
+    pub(crate) fn spans(&self) -> &[Span] {
+        &self.spans
+    }
+
+    /// Start a span at line `start`, nested in the innermost open one.
+    fn open_span(&mut self, start: usize) -> usize {
+        let index = self.spans.len();
+        self.spans.push(Span {
+            lines: start..start,
+            parent: self.open.last().copied(),
+            item: SpanItem::Comment,
+        });
+        self.open.push(index);
+        index
+    }
+
+    /// End the innermost open span before the current line.
+    fn close_span(&mut self, index: usize, item: SpanItem) {
+        self.open.pop();
+        self.spans[index].lines.end = self.line_idx;
+        self.spans[index].item = item;
+    }
+
     fn parse_item(&mut self, min_indent: usize) -> Result<Option<Item>, String> {
+        let index = self.open_span(self.line_idx);
+        let item = self.parse_item_lines(min_indent)?;
+        match &item {
+            Some(Item::BlankLine) | None => {
+                self.open.pop();
+                self.spans.truncate(index);
+            }
+            Some(item) => self.close_span(index, SpanItem::of(item)),
+        }
+        Ok(item)
+    }
+
+    fn parse_item_lines(&mut self, min_indent: usize) -> Result<Option<Item>, String> {
         let line = match self.current_line() {
             Some(l) => l,
             None => return Ok(None),
@@ -280,49 +452,48 @@ impl<'a> MehParser<'a> {
 
         // Block bytes at root level
         if content == ">" || content.starts_with("> ") {
-            let (first_hex, first_comment) = if let Some(rest) = content.strip_prefix("> ") {
-                if rest.trim_start().starts_with('#') {
-                    // Just a comment on the first line
-                    (
-                        None,
-                        Some(Comment {
-                            text: rest.trim_start()[1..].to_string(),
-                            align_column: None,
-                        }),
-                    )
-                } else if !rest.trim().is_empty() {
-                    // Hex data on the first line (possibly with inline comment)
-                    let (hex_part, comment) = split_inline_comment(rest);
-                    (Some(hex_part.trim().to_string()), comment)
-                } else {
-                    (None, None)
-                }
-            } else {
-                (None, None)
-            };
             self.advance_line();
-            let mut block_bytes = self.parse_block_bytes(indent, first_comment.clone())?;
-            // If there was hex on the first line, prepend it
-            if let Some(hex) = first_hex {
-                let first_line = BlockBytesLine {
-                    indent,
-                    hex,
-                    comment: first_comment,
-                };
-                block_bytes.lines.insert(0, first_line);
-                block_bytes.first_line_comment = None;
-            }
+            let block_bytes = self.parse_block_bytes_after(content, indent)?;
             return Ok(Some(Item::Value(CstValue::Bytes(CstBytes::Block(
                 block_bytes,
             )))));
         }
 
         // Standalone value
-        let value = self.parse_inline_value(content)?;
         self.advance_line();
+        if let Some(empty) = self.parse_split_empty(content) {
+            return Ok(Some(Item::Value(empty)));
+        }
+        let value = self.parse_inline_value(content)?;
         Ok(Some(Item::Value(value)))
     }
 
+    /// An empty array or object whose brackets are on separate lines, like
+    /// `[` followed by a line with just `]`, written as `[]` or `{}`. The
+    /// opening bracket is `value`, the rest of a line already passed.
+    fn parse_split_empty(&mut self, value: &str) -> Option<CstValue> {
+        let (close, empty) = match value {
+            "[" => ("]", CstValue::Array(CstArray { items: Vec::new() })),
+            "{" => (
+                "}",
+                CstValue::Object(CstObject {
+                    entries: Vec::new(),
+                }),
+            ),
+            _ => return None,
+        };
+        let mut next = self.line_idx;
+        while self.lines.get(next)?.trim().is_empty() {
+            next += 1;
+        }
+        if self.lines[next].trim() != close {
+            return None;
+        }
+        self.line_idx = next;
+        self.advance_line();
+        Some(empty)
+    }
+
     fn parse_array_item(&mut self, indent: usize) -> Result<Option<Item>, String> {
         let line = self.current_line().unwrap();
         let content = &line[indent..];
@@ -331,12 +502,19 @@ impl<'a> MehParser<'a> {
         let after_dash = content.strip_prefix("- ").unwrap_or_default();
 
         // Check for inline comment
-        let (value_part, inline_comment) = split_inline_comment(after_dash);
+        let (value_part, mut inline_comment) = split_inline_comment(after_dash);
 
         self.advance_line();
 
         // Parse the value part
-        let value = if value_part.is_empty() {
+        let value = if after_dash == ">" || after_dash.starts_with("> ") {
+            // Block bytes, whose first line may have hex or a comment
+            inline_comment = None;
+            let block_bytes = self.parse_block_bytes_after(after_dash, indent)?;
+            Some(ArrayItemValue::Inline(CstValue::Bytes(CstBytes::Block(
+                block_bytes,
+            ))))
+        } else if value_part.is_empty() {
             // Check for block content
             let block = self.parse_block(indent)?;
             if block.items.is_empty() {
@@ -351,10 +529,14 @@ impl<'a> MehParser<'a> {
             Some(ArrayItemValue::Inline(CstValue::String(CstString::Block(
                 block_str,
             ))))
+        } else if let Some(empty) = self.parse_split_empty(value_part) {
+            Some(ArrayItemValue::Inline(empty))
         } else if value_part.starts_with("- ") || value_part == "-" {
             // Nested array item on same line (e.g., "- - a")
             // Parse the rest as a nested array item
+            let span = self.open_span(self.line_idx - 1);
             let nested_item = self.parse_inline_array_item(value_part, indent + 2)?;
+            self.close_span(span, SpanItem::ArrayItem(nested_item.shape()));
             // Then check for more items on subsequent lines
             let mut block_items = vec![Item::ArrayItem(nested_item)];
             let block = self.parse_block(indent)?;
@@ -362,6 +544,7 @@ impl<'a> MehParser<'a> {
             Some(ArrayItemValue::Block(Block { items: block_items }))
         } else if find_colon_outside_quotes(value_part).is_some() {
             // Nested property on same line (e.g., "- a: 1")
+            let span = self.open_span(self.line_idx - 1);
             let mut nested_item = self.parse_inline_property(value_part, indent + 2)?;
             // If the property has no inline value, parse its block content
             if nested_item.value.is_none() {
@@ -370,6 +553,10 @@ impl<'a> MehParser<'a> {
                     nested_item.value = Some(PropertyValue::Block(prop_block));
                 }
             }
+            self.close_span(
+                span,
+                SpanItem::Property(nested_item.key.name().to_string(), nested_item.shape()),
+            );
             let mut block_items = vec![Item::Property(nested_item)];
             // Parse sibling items at the same level as the property
             let block = self.parse_block(indent)?;
@@ -419,40 +606,12 @@ impl<'a> MehParser<'a> {
             ))))
         } else if value_str == ">" || value_str.starts_with("> ") {
             // Block bytes
-            let (first_hex, first_comment) = if let Some(rest) = value_str.strip_prefix("> ") {
-                if rest.trim_start().starts_with('#') {
-                    // Just a comment on the first line
-                    (
-                        None,
-                        Some(Comment {
-                            text: rest.trim_start()[1..].to_string(),
-                            align_column: None,
-                        }),
-                    )
-                } else if !rest.trim().is_empty() {
-                    // Hex data on the first line (possibly with inline comment)
-                    let (hex_part, comment) = split_inline_comment(rest);
-                    (Some(hex_part.trim().to_string()), comment)
-                } else {
-                    (None, None)
-                }
-            } else {
-                (None, None)
-            };
-            let mut block_bytes = self.parse_block_bytes(indent, first_comment.clone())?;
-            // If there was hex on the first line, prepend it
-            if let Some(hex) = first_hex {
-                let first_line = BlockBytesLine {
-                    indent,
-                    hex,
-                    comment: first_comment,
-                };
-                block_bytes.lines.insert(0, first_line);
-                block_bytes.first_line_comment = None;
-            }
+            let block_bytes = self.parse_block_bytes_after(value_str, indent)?;
             Some(PropertyValue::Inline(CstValue::Bytes(CstBytes::Block(
                 block_bytes,
             ))))
+        } else if let Some(empty) = self.parse_split_empty(value_str) {
+            Some(PropertyValue::Inline(empty))
         } else {
             Some(PropertyValue::Inline(self.parse_inline_value(value_str)?))
         };
@@ -505,7 +664,7 @@ impl<'a> MehParser<'a> {
     fn parse_inline_array_item(
         &mut self,
         content: &str,
-        _indent: usize,
+        indent: usize,
     ) -> Result<ArrayItem, String> {
         // content starts with "- " or is just "-"
         let after_dash = content.strip_prefix("- ").unwrap_or_default();
@@ -516,16 +675,30 @@ impl<'a> MehParser<'a> {
             None
         } else if value_part.starts_with("- ") || value_part == "-" {
             // Recursively nested array item
-            let nested = self.parse_inline_array_item(value_part, _indent + 2)?;
-            Some(ArrayItemValue::Block(Block {
-                items: vec![Item::ArrayItem(nested)],
-            }))
+            let span = self.open_span(self.line_idx - 1);
+            let nested = self.parse_inline_array_item(value_part, indent + 2)?;
+            self.close_span(span, SpanItem::ArrayItem(nested.shape()));
+            // Then its siblings on the lines below, deeper than this item
+            let mut block_items = vec![Item::ArrayItem(nested)];
+            block_items.extend(self.parse_block(indent)?.items);
+            Some(ArrayItemValue::Block(Block { items: block_items }))
         } else if find_colon_outside_quotes(value_part).is_some() {
             // Nested property
-            let nested = self.parse_inline_property(value_part, _indent + 2)?;
-            Some(ArrayItemValue::Block(Block {
-                items: vec![Item::Property(nested)],
-            }))
+            let span = self.open_span(self.line_idx - 1);
+            let mut nested = self.parse_inline_property(value_part, indent + 2)?;
+            if nested.value.is_none() {
+                let prop_block = self.parse_block(indent + 2)?;
+                if !prop_block.items.is_empty() {
+                    nested.value = Some(PropertyValue::Block(prop_block));
+                }
+            }
+            self.close_span(
+                span,
+                SpanItem::Property(nested.key.name().to_string(), nested.shape()),
+            );
+            let mut block_items = vec![Item::Property(nested)];
+            block_items.extend(self.parse_block(indent)?.items);
+            Some(ArrayItemValue::Block(Block { items: block_items }))
         } else {
             Some(ArrayItemValue::Inline(self.parse_inline_value(value_part)?))
         };
@@ -595,6 +768,47 @@ impl<'a> MehParser<'a> {
         Ok(BlockString { first_line, lines })
     }
 
+    /// Parse block bytes from their `>` leader, the rest of a line already
+    /// passed, which may hold the first line of hex or a comment.
+    fn parse_block_bytes_after(
+        &mut self,
+        leader: &str,
+        indent: usize,
+    ) -> Result<BlockBytes, String> {
+        let (first_hex, first_comment) = if let Some(rest) = leader.strip_prefix("> ") {
+            if rest.trim_start().starts_with('#') {
+                // Just a comment on the first line
+                (
+                    None,
+                    Some(Comment {
+                        text: rest.trim_start()[1..].to_string(),
+                        align_column: None,
+                    }),
+                )
+            } else if !rest.trim().is_empty() {
+                // Hex data on the first line (possibly with inline comment)
+                let (hex_part, comment) = split_inline_comment(rest);
+                (Some(hex_part.trim().to_string()), comment)
+            } else {
+                (None, None)
+            }
+        } else {
+            (None, None)
+        };
+        let mut block_bytes = self.parse_block_bytes(indent, first_comment.clone())?;
+        // If there was hex on the first line, prepend it
+        if let Some(hex) = first_hex {
+            let first_line = BlockBytesLine {
+                indent,
+                hex,
+                comment: first_comment,
+            };
+            block_bytes.lines.insert(0, first_line);
+            block_bytes.first_line_comment = None;
+        }
+        Ok(block_bytes)
+    }
+
     fn parse_block_bytes(
         &mut self,
         parent_indent: usize,
@@ -685,6 +899,11 @@ impl<'a> MehParser<'a> {
             return self.parse_inline_object(s);
         }
 
+        // Hexadecimal float
+        if parse_hex_float(s).is_some() {
+            return Ok(CstValue::Float(s.to_string()));
+        }
+
         // Number (integer or float)
         if looks_like_number(s) {
             if s.contains('.')
@@ -793,7 +1012,7 @@ fn find_colon_outside_quotes(s: &str) -> Option<usize> {
     let mut escape = false;
     let mut depth: i32 = 0;
 
-    for (i, c) in s.chars().enumerate() {
+    for (i, c) in s.char_indices() {
         if escape {
             escape = false;
             continue;
@@ -818,7 +1037,7 @@ fn find_colon_outside_quotes(s: &str) -> Option<usize> {
     None
 }
 
-fn parse_key(s: &str) -> Key {
+pub(crate) fn parse_key(s: &str) -> Key {
     if s.starts_with('"') && s.ends_with('"') {
         Key::DoubleQuoted(s.to_string())
     } else if s.starts_with('\'') && s.ends_with('\'') {
@@ -937,21 +1156,116 @@ fn looks_like_number(s: &str) -> bool {
 // MEH-to-YAY Transform
 // =============================================================================
 
+/// Normalize a document to canonical YAY, keeping its comments.
 pub fn transform_to_canonical(doc: &Document) -> Document {
-    let wrap = get_wrap_length();
-    let transformer = Transformer { wrap };
+    let transformer = Transformer::new(0);
+    transformer.transform_document(doc)
+}
+
+/// Whether a comment is the formatting directive `name`, such as
+/// `sort-keys`, which asks for the keys of the block after it to be sorted.
+fn is_directive(comment: &Comment, name: &str) -> bool {
+    comment
+        .text
+        .trim()
+        .strip_prefix(FORMAT_PREFIX)
+        .is_some_and(|directive| directive.trim() == name)
+}
+
+/// An item with the properties of its block, or the entries of its inline
+/// object, sorted by key.
+fn sort_keys(item: &Item) -> Item {
+    let mut item = item.clone();
+    match &mut item {
+        Item::Property(Property {
+            value: Some(PropertyValue::Block(block)),
+            ..
+        })
+        | Item::ArrayItem(ArrayItem {
+            value: Some(ArrayItemValue::Block(block)),
+            ..
+        }) => sort_block_keys(&mut block.items),
+        Item::Property(Property {
+            value: Some(PropertyValue::Inline(CstValue::Object(obj))),
+            ..
+        })
+        | Item::Value(CstValue::Object(obj)) => {
+            obj.entries.sort_by(|a, b| a.key.name().cmp(b.key.name()));
+        }
+        _ => {}
+    }
+    item
+}
+
+/// Sort the properties of a block by key, each with the comments above it.
+/// Blank lines divide the block into groups that are sorted on their own,
+/// and a group with anything but properties and comments is left as it is.
+fn sort_block_keys(items: &mut [Item]) {
+    for group in items.split_mut(|item| matches!(item, Item::BlankLine)) {
+        if !group
+            .iter()
+            .all(|item| matches!(item, Item::Property(_) | Item::Comment(_)))
+        {
+            continue;
+        }
+        let mut entries: Vec<Vec<Item>> = Vec::new();
+        let mut comments = Vec::new();
+        for item in group.iter() {
+            comments.push(item.clone());
+            if matches!(item, Item::Property(_)) {
+                entries.push(std::mem::take(&mut comments));
+            }
+        }
+        let key = |entry: &Vec<Item>| match entry.last() {
+            Some(Item::Property(p)) => p.key.name().to_string(),
+            _ => String::new(),
+        };
+        entries.sort_by_key(key);
+        for (slot, item) in group.iter_mut().zip(entries.into_iter().flatten()) {
+            *slot = item;
+        }
+    }
+}
+
+/// Transform to canonical form for text that will be indented by `indent`
+/// columns, leaving room for the indentation when wrapping.
+fn transform_to_canonical_at(doc: &Document, indent: usize) -> Document {
+    let transformer = Transformer::new(indent);
     transformer.transform_document(doc)
 }
 
 struct Transformer {
     wrap: usize,
+    /// The furthest column inline comments align to, from `YAY_ALIGN_WIDTH`
+    /// or else the wrap width. A comment after a line reaching past it
+    /// follows its line instead, so one long key does not push every
+    /// comment in its group to the right.
+    align_width: usize,
+    /// From `YAY_BLOCK_KEY_WIDTH`: an inline array or object value is written
+    /// in block form when its property's key, or one of its own keys, is
+    /// longer than this.
+    block_key_width: Option<usize>,
 }
 
 impl Transformer {
+    /// A transformer for text that will be indented by `indent` columns,
+    /// configured by the environment.
+    fn new(indent: usize) -> Self {
+        let wrap = get_wrap_length();
+        Transformer {
+            wrap: wrap.saturating_sub(indent),
+            align_width: env_width("YAY_ALIGN_WIDTH")
+                .unwrap_or(wrap)
+                .saturating_sub(indent),
+            block_key_width: env_width("YAY_BLOCK_KEY_WIDTH"),
+        }
+    }
+
     fn transform_document(&self, doc: &Document) -> Document {
         let items = self.transform_items_with_alignment(&doc.items, 0);
 
         Document {
+            directive: doc.directive.clone(),
             items,
             trailing_comments: doc.trailing_comments.clone(),
         }
@@ -959,8 +1273,11 @@ impl Transformer {
 
     /// Transform a list of items, handling blank line collapsing and comment alignment
     fn transform_items_with_alignment(&self, items: &[Item], base_indent: usize) -> Vec<Item> {
+        let items = &self.lay_out_tables(items, base_indent);
         let mut result = Vec::new();
         let mut prev_was_blank = false;
+        // Whether a sort-keys directive comes before the next item.
+        let mut sort_next = false;
 
         // First pass: transform all items (may produce multiple items from one)
         for item in items {
@@ -970,6 +1287,18 @@ impl Transformer {
                         result.push(Item::BlankLine);
                         prev_was_blank = true;
                     }
+                    sort_next = false;
+                }
+                Item::Comment(c) => {
+                    sort_next |= is_directive(c, "sort-keys");
+                    result.push(item.clone());
+                    prev_was_blank = false;
+                }
+                _ if sort_next => {
+                    let sorted = sort_keys(item);
+                    result.extend(self.transform_item_maybe_expand(&sorted, base_indent));
+                    prev_was_blank = false;
+                    sort_next = false;
                 }
                 _ => {
                     let transformed = self.transform_item_maybe_expand(item, base_indent);
@@ -990,6 +1319,118 @@ impl Transformer {
         result
     }
 
+    /// The items with the array after each `table` directive written one
+    /// object per line, if it can be. The array is the block of the next
+    /// property, or the array items that come next, before a blank line.
+    fn lay_out_tables(&self, items: &[Item], indent: usize) -> Vec<Item> {
+        let mut result = items.to_vec();
+        let mut i = 0;
+        while i < result.len() {
+            if !matches!(&result[i], Item::Comment(c) if is_directive(c, "table")) {
+                i += 1;
+                continue;
+            }
+            i += 1;
+            while matches!(result.get(i), Some(Item::Comment(_))) {
+                i += 1;
+            }
+            match result.get_mut(i) {
+                Some(Item::Property(Property {
+                    value: Some(PropertyValue::Block(block)),
+                    ..
+                })) => {
+                    if let Some(rows) = self.table_rows(&block.items, indent + 1) {
+                        block.items = rows;
+                    }
+                }
+                Some(Item::ArrayItem(_)) => {
+                    let end = i + result[i..]
+                        .iter()
+                        .take_while(|item| matches!(item, Item::ArrayItem(_)))
+                        .count();
+                    if let Some(rows) = self.table_rows(&result[i..end], indent) {
+                        result.splice(i..end, rows);
+                    }
+                }
+                _ => {}
+            }
+        }
+        result
+    }
+
+    /// Array items written as inline objects, one per line, with their keys
+    /// in the order of the first. Each item must be an object of scalars,
+    /// with the same keys as the others and no comments, that fits on its
+    /// line.
+    fn table_rows(&self, items: &[Item], indent: usize) -> Option<Vec<Item>> {
+        let mut rows = Vec::new();
+        for item in items {
+            match item {
+                Item::ArrayItem(item) if item.inline_comment.is_none() => {
+                    rows.push(self.table_row(item)?)
+                }
+                _ => return None,
+            }
+        }
+        let order: Vec<String> = rows
+            .first()?
+            .entries
+            .iter()
+            .map(|entry| entry.key.name().to_string())
+            .collect();
+        let mut result = Vec::new();
+        for mut row in rows {
+            if row.entries.len() != order.len() {
+                return None;
+            }
+            let mut entries = Vec::new();
+            for key in &order {
+                let at = row.entries.iter().position(|e| e.key.name() == key)?;
+                entries.push(row.entries.swap_remove(at));
+            }
+            row.entries = entries;
+            if indent * 2 + 2 + self.measure_object(&row) > self.wrap {
+                return None;
+            }
+            result.push(Item::ArrayItem(ArrayItem {
+                value: Some(ArrayItemValue::Inline(CstValue::Object(row))),
+                inline_comment: None,
+            }));
+        }
+        Some(result)
+    }
+
+    /// An array item as an inline object of scalars, if it is an object
+    /// whose members all have scalar values and no comments.
+    fn table_row(&self, item: &ArrayItem) -> Option<CstObject> {
+        let row = match &item.value {
+            Some(ArrayItemValue::Inline(CstValue::Object(obj))) => obj.clone(),
+            Some(ArrayItemValue::Block(block)) => {
+                let mut entries = Vec::new();
+                for item in &block.items {
+                    match item {
+                        Item::Property(Property {
+                            key,
+                            value: Some(PropertyValue::Inline(value)),
+                            inline_comment: None,
+                        }) => entries.push(CstObjectEntry {
+                            key: key.clone(),
+                            value: value.clone(),
+                        }),
+                        _ => return None,
+                    }
+                }
+                CstObject { entries }
+            }
+            _ => return None,
+        };
+        let scalars = row.entries.iter().all(|entry| {
+            !matches!(entry.value, CstValue::Array(_) | CstValue::Object(_))
+                && self.measure_value(&entry.value) != usize::MAX
+        });
+        scalars.then_some(row)
+    }
+
     /// Transform an item, potentially expanding it into multiple items
     /// (e.g., when converting inline array to block form)
     fn transform_item_maybe_expand(&self, item: &Item, indent: usize) -> Vec<Item> {
@@ -1007,11 +1448,13 @@ impl Transformer {
         if let Some(PropertyValue::Inline(value)) = &prop.value {
             let indent_width = indent * 2;
             let prefix_width = indent_width + key_width + 2; // "key: "
+            let long_key = self.long_key(&prop.key);
 
             match value {
                 CstValue::Array(arr) => {
                     let value_width = self.measure_array(arr);
-                    if prefix_width + value_width > self.wrap {
+                    let long_key = long_key && !arr.items.is_empty();
+                    if long_key || prefix_width + value_width > self.wrap {
                         // Convert to block form: property with no value, followed by array items
                         // Array items are siblings at the same indent level
                         let mut items = vec![Item::Property(Property {
@@ -1034,7 +1477,9 @@ impl Transformer {
                 }
                 CstValue::Object(obj) => {
                     let value_width = self.measure_object(obj);
-                    if prefix_width + value_width > self.wrap {
+                    let long_key = !obj.entries.is_empty()
+                        && (long_key || obj.entries.iter().any(|e| self.long_key(&e.key)));
+                    if long_key || prefix_width + value_width > self.wrap {
                         // Convert to block form: property with block value containing nested properties
                         // Object properties are children at indent + 1
                         let nested_items: Vec<Item> = obj
@@ -1099,11 +1544,15 @@ impl Transformer {
 
     /// Align comments within a contiguous group of items
     fn align_group(&self, items: &mut [Item], indent_width: usize) {
+        // Items too wide to align within align_width keep their comments
+        // two spaces after them.
+        let fits = |width: usize| indent_width + width + 2 <= self.align_width;
+
         // Calculate max data width for items with inline comments
         let mut max_data_width = 0usize;
         for item in items.iter() {
             if let Some(width) = self.item_data_width(item) {
-                if self.item_has_inline_comment(item) {
+                if self.item_has_inline_comment(item) && fits(width) {
                     max_data_width = max_data_width.max(width);
                 }
             }
@@ -1116,9 +1565,11 @@ impl Transformer {
         // The alignment column is where # starts: indent + max_data_width + 2 spaces
         let align_col = indent_width + max_data_width + 2;
 
-        // Set alignment on all inline comments in this group
+        // Set alignment on the inline comments in this group that fit
         for item in items.iter_mut() {
-            self.set_item_comment_alignment(item, align_col);
+            if self.item_data_width(item).is_none_or(fits) {
+                self.set_item_comment_alignment(item, align_col);
+            }
         }
     }
 
@@ -1190,6 +1641,12 @@ impl Transformer {
         }
     }
 
+    /// Whether a key is too long for an inline array or object to follow.
+    fn long_key(&self, key: &Key) -> bool {
+        self.block_key_width
+            .is_some_and(|max| self.key_width(key) > max)
+    }
+
     /// Measure the formatted width of a key
     fn key_width(&self, key: &Key) -> usize {
         match key {
@@ -1325,7 +1782,20 @@ impl Transformer {
                     ArrayItemValue::Inline(self.transform_value(val, indent))
                 }
                 ArrayItemValue::Block(b) => {
-                    ArrayItemValue::Block(self.transform_block(b, indent + 1))
+                    let mut block = self.transform_block(b, indent + 1);
+                    // A nested item on the dash's line ("- - <hex>") cannot
+                    // take block bytes, so it keeps them inline
+                    if let (
+                        Some(Item::ArrayItem(ArrayItem {
+                            value: Some(ArrayItemValue::Inline(val)),
+                            ..
+                        })),
+                        Some(Item::ArrayItem(first)),
+                    ) = (b.items.first(), block.items.first_mut())
+                    {
+                        first.value = Some(ArrayItemValue::Inline(self.transform_flow_value(val)));
+                    }
+                    ArrayItemValue::Block(block)
                 }
             }),
             inline_comment: item.inline_comment.clone(),
@@ -1340,8 +1810,8 @@ impl Transformer {
             CstValue::Float(s) => CstValue::Float(canonicalize_float(s)),
             CstValue::String(s) => CstValue::String(s.clone()),
             CstValue::Bytes(b) => self.transform_bytes(b, indent),
-            CstValue::Array(a) => self.transform_array(a, indent),
-            CstValue::Object(o) => self.transform_object(o, indent),
+            CstValue::Array(a) => self.transform_array(a),
+            CstValue::Object(o) => self.transform_object(o),
         }
     }
 
@@ -1600,25 +2070,38 @@ impl Transformer {
         result
     }
 
-    fn transform_array(&self, arr: &CstArray, indent: usize) -> CstValue {
+    /// Transform a value that stays on its line, as in an inline array or
+    /// object, where bytes cannot turn into a block however long.
+    fn transform_flow_value(&self, value: &CstValue) -> CstValue {
+        match value {
+            CstValue::Bytes(CstBytes::Inline(ib)) => {
+                CstValue::Bytes(CstBytes::Inline(InlineBytes {
+                    content: normalize_hex_spaces(&ib.content),
+                }))
+            }
+            _ => self.transform_value(value, 0),
+        }
+    }
+
+    fn transform_array(&self, arr: &CstArray) -> CstValue {
         let items: Vec<CstArrayItem> = arr
             .items
             .iter()
             .map(|item| CstArrayItem {
-                value: self.transform_value(&item.value, indent),
+                value: self.transform_flow_value(&item.value),
             })
             .collect();
 
         CstValue::Array(CstArray { items })
     }
 
-    fn transform_object(&self, obj: &CstObject, indent: usize) -> CstValue {
+    fn transform_object(&self, obj: &CstObject) -> CstValue {
         let entries: Vec<CstObjectEntry> = obj
             .entries
             .iter()
             .map(|entry| CstObjectEntry {
                 key: entry.key.clone(),
-                value: self.transform_value(&entry.value, indent),
+                value: self.transform_flow_value(&entry.value),
             })
             .collect();
 
@@ -1795,89 +2278,29 @@ fn normalize_number_spaces(s: &str) -> String {
 fn canonicalize_float(s: &str) -> String {
     // Handle special values
     match s {
-        "nan" => return "nan".to_string(),
-        "infinity" => return "infinity".to_string(),
-        "-infinity" => return "-infinity".to_string(),
+        "nan" | "infinity" | "-infinity" => return s.to_string(),
         _ => {}
     }
 
-    // Remove spaces (digit grouping)
-    let compact: String = s.chars().filter(|c| *c != ' ').collect();
-
-    // Parse to f64
-    let f: f64 = match compact.parse() {
-        Ok(v) => v,
-        Err(_) => return s.to_string(), // Preserve original if parse fails
-    };
-
-    // Handle special float values
-    if f.is_nan() {
-        return "nan".to_string();
-    }
-    if f.is_infinite() {
-        return if f.is_sign_positive() {
-            "infinity".to_string()
-        } else {
-            "-infinity".to_string()
-        };
-    }
-
-    // Format to shortest representation
-    // Try both regular and exponential notation, pick the shorter one
-    let regular = format!("{}", f);
-    let exponential = format!("{:e}", f);
-
-    // Choose the shorter representation
-    let formatted = if exponential.len() < regular.len() {
-        exponential
-    } else {
-        regular
-    };
-
-    // Ensure floats have a decimal point to distinguish from integers
-    if !formatted.contains('.') && !formatted.contains('e') && !formatted.contains('E') {
-        format!("{}.0", formatted)
-    } else {
-        formatted
-    }
-}
-
-fn normalize_hex_spaces(s: &str) -> String {
-    // Remove all spaces, lowercase, then re-add with proper grouping
-    let hex: String = s
-        .chars()
-        .filter(|c| !c.is_whitespace())
-        .flat_map(|c| c.to_lowercase())
-        .collect();
-
-    if hex.is_empty() {
-        return String::new();
+    // Hexadecimal floats are not standard YAY, so they become decimal
+    if let Some(f) = parse_hex_float(s) {
+        return format_float(f, &FloatFormat::default());
     }
 
-    // Group into bytes (2 chars each), then words (4 bytes = 8 chars)
-    let mut result = String::new();
-    let chars: Vec<char> = hex.chars().collect();
+    // Remove spaces (digit grouping)
+    let compact: String = s.chars().filter(|c| *c != ' ').collect();
 
-    for (i, chunk) in chars.chunks(2).enumerate() {
-        if i > 0 {
-            if i % 4 == 0 {
-                result.push_str("  "); // Double space between words
-            } else {
-                result.push(' '); // Single space between bytes
-            }
-        }
-        for &c in chunk {
-            result.push(c);
-        }
+    match compact.parse::<f64>() {
+        Ok(f) => format_float(f, &FloatFormat::default()),
+        Err(_) => s.to_string(), // Preserve original if parse fails
     }
-
-    result
 }
 
 // =============================================================================
 // MEH Formatter
 // =============================================================================
 
+/// Write a document as text.
 pub fn format_document(doc: &Document) -> String {
     let mut formatter = Formatter::new();
     formatter.format_document(doc)
@@ -1901,6 +2324,10 @@ impl Formatter {
     }
 
     fn format_document(&mut self, doc: &Document) -> String {
+        if let Some(directive) = &doc.directive {
+            self.output.push_str(directive);
+            self.output.push('\n');
+        }
         for item in &doc.items {
             self.format_item(item);
         }
@@ -1961,7 +2388,7 @@ impl Formatter {
                 self.newline();
             }
             Item::Comment(c) => {
-                self.write_indent();
+                // Comment lines are always at column 0 in canonical YAY
                 self.write_char('#');
                 self.write(&c.text);
                 self.newline();
@@ -2013,8 +2440,11 @@ impl Formatter {
                 }
             }
             Some(PropertyValue::Block(block)) => {
+                // Strict YAY allows one space after the colon of a key
+                // whose value is on the lines below.
                 if let Some(comment) = &prop.inline_comment {
-                    self.write_inline_comment(comment);
+                    self.write(" #");
+                    self.write(&comment.text);
                 }
                 self.newline();
                 self.indent += 1;
@@ -2057,9 +2487,12 @@ impl Formatter {
                 if let Some(first_item) = items_iter.next() {
                     match first_item {
                         Item::ArrayItem(nested) => {
-                            // Write "- " then the nested array item inline
+                            // Write "- " then the nested array item inline,
+                            // a level deeper
                             self.write("- ");
+                            self.indent += 1;
                             self.format_array_item_inline(nested);
+                            self.indent -= 1;
                         }
                         Item::Property(nested) => {
                             // Write "- " then the property inline
@@ -2107,7 +2540,8 @@ impl Formatter {
         }
     }
 
-    /// Format an array item inline (without leading indent, for nested items on same line)
+    /// Format an array item inline (without leading indent, for nested items on same line),
+    /// with the indent already at the item's own level
     fn format_array_item_inline(&mut self, item: &ArrayItem) {
         match &item.value {
             Some(ArrayItemValue::Inline(v)) => {
@@ -2125,7 +2559,9 @@ impl Formatter {
                     match first_item {
                         Item::ArrayItem(nested) => {
                             self.write("- ");
+                            self.indent += 1;
                             self.format_array_item_inline(nested);
+                            self.indent -= 1;
                         }
                         Item::Property(nested) => {
                             self.write("- ");
@@ -2383,6 +2819,90 @@ pub fn format_yay(input: &str) -> Result<String, String> {
     Ok(format_document(&canonical))
 }
 
+/// Format only the items on the given lines (0-based, end exclusive) of a
+/// loose YAY document, for an editor's "format selection".
+///
+/// The range grows to whole items: a selection that starts or ends partway
+/// through an item takes in all of it, and a selection that spans items at
+/// different depths takes in their enclosing items until the ends are
+/// siblings. Returns the lines to replace and their replacement, which
+/// keeps the indentation of the first of them.
+pub fn format_yay_range(
+    input: &str,
+    lines: Range<usize>,
+) -> Result<(Range<usize>, String), String> {
+    let mut parser = MehParser::new(input);
+    parser.parse()?;
+    let source = &parser.lines;
+    let spans = &parser.spans;
+
+    let mut start = lines.start.min(source.len());
+    let mut end = lines.end.clamp(start, source.len());
+    while start < end && source[start].trim().is_empty() {
+        start += 1;
+    }
+    while end > start && source[end - 1].trim().is_empty() {
+        end -= 1;
+    }
+    if start == end {
+        return Ok((start..start, String::new()));
+    }
+
+    // The innermost item on a line is the last one that contains it, less
+    // any that share their first line with their parent, like the `a: 1`
+    // of `- a: 1`.
+    let innermost = |line: usize| {
+        let mut index = spans
+            .iter()
+            .rposition(|span| span.lines.contains(&line))
+            .ok_or_else(|| format!("line {} is not part of an item", line + 1))?;
+        while let Some(parent) = spans[index].parent {
+            if spans[parent].lines.start != spans[index].lines.start {
+                break;
+            }
+            index = parent;
+        }
+        Ok::<_, String>(index)
+    };
+    let depth = |mut index: usize| {
+        let mut depth = 0;
+        while let Some(parent) = spans[index].parent {
+            index = parent;
+            depth += 1;
+        }
+        depth
+    };
+    let (mut first, mut last) = (innermost(start)?, innermost(end - 1)?);
+    while spans[first].parent != spans[last].parent {
+        if depth(first) >= depth(last) {
+            first = spans[first].parent.unwrap_or(first);
+        } else {
+            last = spans[last].parent.unwrap_or(last);
+        }
+    }
+    let lines = spans[first].lines.start..spans[last].lines.end;
+
+    let indent = count_indent(source[lines.start]);
+    let fragment: String = source[lines.clone()]
+        .iter()
+        .map(|line| format!("{}\n", &line[count_indent(line).min(indent)..]))
+        .collect();
+    let doc = MehParser::new(&fragment).parse()?;
+    let formatted = format_document(&transform_to_canonical_at(&doc, indent));
+    let pad = " ".repeat(indent);
+    let replacement = formatted
+        .lines()
+        .map(|line| {
+            if line.is_empty() {
+                "\n".to_string()
+            } else {
+                format!("{}{}\n", pad, line)
+            }
+        })
+        .collect();
+    Ok((lines, replacement))
+}
+
 // Most MEH functionality is tested via fixtures in test/meh/
 // These unit tests cover internal helper functions not directly exercised by fixtures
 #[cfg(test)]
@@ -2448,4 +2968,202 @@ mod tests {
         let result = wrap_comment_text("   ", 80);
         assert_eq!(result.len(), 1);
     }
+
+    #[test]
+    fn test_version_directive_kept() {
+        assert_eq!(format_yay("%yay 1\n\na:   1\n").unwrap(), "%yay 1\na: 1\n");
+        assert_eq!(format_yay("%yay 1\n").unwrap(), "%yay 1\n");
+    }
+
+    #[test]
+    fn test_sort_keys_directive() {
+        let input = "\
+# yay-fmt: sort-keys
+deps:
+  zeta: 1
+  # alpha note
+  alpha: {z: 1, a: 2}
+
+  mid: 3
+  beta: 4
+manual:
+  z: 1
+  a: 2
+# yay-fmt: sort-keys
+inline: {z: 1, a: 2}
+";
+        let output = format_yay(input).unwrap();
+        let keys: Vec<&str> = output
+            .lines()
+            .filter_map(|line| line.split(':').next())
+            .collect();
+        assert_eq!(
+            keys,
+            [
+                "# yay-fmt",
+                "deps",
+                "# alpha note",
+                "  alpha",
+                "  zeta",
+                "",
+                "  beta",
+                "  mid",
+                "manual",
+                "  z",
+                "  a",
+                "# yay-fmt",
+                "inline",
+            ]
+        );
+        assert!(output.contains("alpha: {z: 1, a: 2}"));
+        assert!(output.contains("inline: {a: 2, z: 1}"));
+    }
+
+    #[test]
+    fn test_empty_collections() {
+        let input = "a: [ ]\nb: {\n\n}\nc:\n  []\nd:\n  - [\n    ]\n  - { }\n";
+        assert_eq!(
+            format_yay(input).unwrap(),
+            "a: []\nb: {}\nc: []\nd:\n  - []\n  - {}\n"
+        );
+        assert_eq!(format_yay("{\n}\n").unwrap(), "{}\n");
+        // A bracket with content after it is not an empty collection.
+        assert_ne!(format_yay("a: [\n1\n]\n").unwrap(), "a: []\n");
+    }
+
+    #[test]
+    fn test_table_directive() {
+        let input = "\
+# yay-fmt: table
+users:
+  - name: \"bob\"
+    id: 1
+  - id: 22
+    name: \"alice\"
+  - {name: \"eve\", id: 3}
+mixed:
+  - name: \"bob\"
+    id: 1
+";
+        assert_eq!(
+            format_yay(input).unwrap(),
+            "\
+# yay-fmt: table
+users:
+  - {name: \"bob\", id: 1}
+  - {name: \"alice\", id: 22}
+  - {name: \"eve\", id: 3}
+mixed:
+  - name: \"bob\"
+    id: 1
+"
+        );
+
+        // Rows with different keys or nested values stay as they are.
+        for input in [
+            "# yay-fmt: table\n- a: 1\n- b: 2\n",
+            "# yay-fmt: table\n- a: 1\n  b: [2]\n",
+        ] {
+            assert_eq!(format_yay(input).unwrap(), input);
+        }
+        let input = "# yay-fmt: table\n- a: 1\n  b: 2\n- a: 3\n  b: 4\n";
+        assert_eq!(
+            format_yay(input).unwrap(),
+            "# yay-fmt: table\n- {a: 1, b: 2}\n- {a: 3, b: 4}\n"
+        );
+    }
+
+    #[test]
+    fn test_format_nested_items_on_one_line() {
+        // Long bytes stay inline where block bytes cannot go.
+        let hex = "00".repeat(40);
+        let spaced = normalize_hex_spaces(&hex);
+        for (input, expected) in [
+            (format!("- - <{}>\n", hex), format!("- - <{}>\n", spaced)),
+            (format!("- [<{}>]\n", hex), format!("- [<{}>]\n", spaced)),
+            (
+                format!("- {{a: <{}>}}\n", hex),
+                format!("- {{a: <{}>}}\n", spaced),
+            ),
+        ] {
+            let output = format_yay(&input).unwrap();
+            assert_eq!(output, expected);
+            assert!(crate::parse(&output).is_ok(), "{}", output);
+        }
+
+        // Items under a nested item keep their depth.
+        assert_eq!(
+            format_yay("- - - 1\n    - 2\n  - 3\n").unwrap(),
+            "- - - 1\n    - 2\n  - 3\n"
+        );
+    }
+
+    #[test]
+    fn test_format_range_snaps_to_items() {
+        let input = "a:   1\nb:\n  c:    [1,2]\n  d:\n    - [3,4]\n    - y: 2\ne:  3\n";
+        let lines: Vec<&str> = input.lines().collect();
+
+        // A line inside a nested block formats just that member.
+        let (range, text) = format_yay_range(input, 2..3).unwrap();
+        assert_eq!(range, 2..3);
+        assert_eq!(text, "  c: [1, 2]\n");
+
+        // A line partway into an item takes in the whole item.
+        let (range, text) = format_yay_range(input, 4..5).unwrap();
+        assert_eq!(range, 4..5);
+        assert_eq!(text, "    - [3, 4]\n");
+
+        // Ends at different depths grow to siblings.
+        let (range, text) = format_yay_range(input, 4..7).unwrap();
+        assert_eq!(range, 1..7);
+        assert!(text.starts_with("b:\n  c: [1, 2]\n"), "{}", text);
+        assert!(text.ends_with("e: 3\n"), "{}", text);
+
+        // Lines outside the range are left alone.
+        let (range, _) = format_yay_range(input, 0..1).unwrap();
+        assert_eq!(range, 0..1);
+        assert_eq!(lines[range.end], "b:");
+    }
+
+    #[test]
+    fn test_format_range_blank_and_past_end() {
+        let input = "a: 1\n\n\nb: 2\n";
+        let (range, text) = format_yay_range(input, 1..3).unwrap();
+        assert!(range.is_empty());
+        assert_eq!(text, "");
+        let (range, _) = format_yay_range(input, 0..100).unwrap();
+        assert_eq!(range, 0..4);
+        let (range, text) = format_yay_range(input, 10..20).unwrap();
+        assert_eq!((range, text.as_str()), (4..4, ""));
+    }
+
+    #[test]
+    fn test_non_ascii_keys() {
+        let formatted = format_yay("\u{c9}t\u{e9}:  1\nb:\n  \u{f1}and\u{fa}: x\n").unwrap();
+        assert_eq!(
+            formatted,
+            "\u{c9}t\u{e9}: 1\nb:\n  \u{f1}and\u{fa}: \"x\"\n"
+        );
+    }
+
+    #[test]
+    fn test_long_keys() {
+        let transformer = Transformer {
+            wrap: 80,
+            align_width: 20,
+            block_key_width: Some(12),
+        };
+        let format = |input: &str| {
+            let doc = MehParser::new(input).parse().unwrap();
+            format_document(&transformer.transform_document(&doc))
+        };
+        assert_eq!(
+            format("a: 1  # one\nbb: 2  # two\nd41d8cd98f00b204: 3  # three\n"),
+            "a: 1   # one\nbb: 2  # two\nd41d8cd98f00b204: 3  # three\n"
+        );
+        assert_eq!(
+            format("d41d8cd98f00b204: [1, 2]\nsums: {d41d8cd98f00b204: 1}\nshort: {a: []}\nd41d8cd98f00b204: []\n"),
+            "d41d8cd98f00b204:\n- 1\n- 2\nsums:\n  d41d8cd98f00b204: 1\nshort: {a: []}\nd41d8cd98f00b204: []\n"
+        );
+    }
 }
@@ -0,0 +1,179 @@
+//! Deep merging of values, for layered configuration.
+
+use crate::Value;
+
+/// How [`Value::merge`] combines two values.
+///
+/// Objects always merge member by member. The default replaces arrays and
+/// keeps null as a value, as everywhere else in YAY.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MergeStrategy {
+    /// What to do when both values are arrays.
+    pub arrays: ArrayMerge,
+    /// Remove an object member when the overriding value is null, as in a
+    /// JSON Merge Patch, instead of setting it to null.
+    pub null_deletes: bool,
+}
+
+/// How [`Value::merge`] combines two arrays.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ArrayMerge {
+    /// The overriding array replaces the base array.
+    #[default]
+    Replace,
+    /// The overriding array's items follow the base array's.
+    Concat,
+}
+
+impl Value {
+    /// Merges `other` over this value, for defaults overridden by an
+    /// environment's settings.
+    ///
+    /// When both are objects, each member of `other` is merged into the
+    /// member of the same name, or added after the existing members if
+    /// there is none. When both are arrays, `strategy.arrays` decides.
+    /// Otherwise `other` replaces this value.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use libyay::{parse, MergeStrategy};
+    ///
+    /// let mut config = parse("server:\n  host: \"localhost\"\n  port: 8080\ndebug: true").unwrap();
+    /// let production = parse("server:\n  host: \"example.com\"\ndebug: null").unwrap();
+    /// config.merge(&production, MergeStrategy { null_deletes: true, ..Default::default() });
+    /// assert_eq!(config, parse("server:\n  host: \"example.com\"\n  port: 8080").unwrap());
+    /// ```
+    pub fn merge(&mut self, other: &Value, strategy: MergeStrategy) {
+        match (self, other) {
+            (Value::Object(base), Value::Object(over)) => {
+                for (key, value) in over {
+                    if strategy.null_deletes && value.is_null() {
+                        base.shift_remove(key);
+                    } else if let Some(member) = base.get_mut(key) {
+                        member.merge(value, strategy);
+                    } else {
+                        base.insert(key.clone(), without_nulls(value, strategy));
+                    }
+                }
+            }
+            (Value::Array(base), Value::Array(over)) if strategy.arrays == ArrayMerge::Concat => {
+                base.extend(over.iter().cloned());
+            }
+            (base, over) => *base = without_nulls(over, strategy),
+        }
+    }
+
+    /// Applies a JSON Merge Patch (RFC 7386): the members of an object
+    /// patch merge into this value, a null member removes the member of
+    /// that name, and any other patch replaces this value, arrays included.
+    /// This is [`Value::merge`] with `null_deletes`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use libyay::parse;
+    ///
+    /// let mut deployment = parse("replicas: 1\nlabels: {app: \"web\", tier: \"dev\"}").unwrap();
+    /// deployment.merge_patch(&parse("replicas: 3\nlabels: {tier: null}").unwrap());
+    /// assert_eq!(deployment, parse("replicas: 3\nlabels: {app: \"web\"}").unwrap());
+    /// ```
+    pub fn merge_patch(&mut self, patch: &Value) {
+        let strategy = MergeStrategy {
+            null_deletes: true,
+            ..MergeStrategy::default()
+        };
+        self.merge(patch, strategy);
+    }
+}
+
+/// A copy of `value`, without its null object members when they delete.
+fn without_nulls(value: &Value, strategy: MergeStrategy) -> Value {
+    match value {
+        Value::Object(obj) if strategy.null_deletes => Value::Object(
+            obj.iter()
+                .filter(|(_, v)| !v.is_null())
+                .map(|(k, v)| (k.clone(), without_nulls(v, strategy)))
+                .collect(),
+        ),
+        _ => value.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    fn merged(base: &str, over: &str, strategy: MergeStrategy) -> Value {
+        let mut value = parse(base).unwrap();
+        value.merge(&parse(over).unwrap(), strategy);
+        value
+    }
+
+    #[test]
+    fn test_merge() {
+        let base = "a:\n  b: 1\n  c: [1, 2]\nd: \"x\"\ne: null";
+        let over = "a:\n  c: [3]\n  f: {g: null, h: 4}\nd: null\ne: {i: 5}";
+
+        let value = merged(base, over, MergeStrategy::default());
+        assert_eq!(
+            value,
+            parse("a:\n  b: 1\n  c: [3]\n  f: {g: null, h: 4}\nd: null\ne: {i: 5}").unwrap()
+        );
+        // Added members follow the existing ones.
+        let keys: Vec<&String> = value["a"].as_object().unwrap().keys().collect();
+        assert_eq!(keys, ["b", "c", "f"]);
+
+        let strategy = MergeStrategy {
+            arrays: ArrayMerge::Concat,
+            null_deletes: true,
+        };
+        assert_eq!(
+            merged(base, over, strategy),
+            parse("a:\n  b: 1\n  c: [1, 2, 3]\n  f: {h: 4}\ne: {i: 5}").unwrap()
+        );
+
+        // Values of different kinds replace, and nulls in arrays stay.
+        assert_eq!(merged("[1]", "{a: 1}", strategy), parse("{a: 1}").unwrap());
+        assert_eq!(
+            merged("{a: 1}", "[null]", strategy),
+            parse("[null]").unwrap()
+        );
+        assert_eq!(
+            merged("a: [1]", "a: [null]", strategy),
+            parse("a: [1, null]").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_merge_patch() {
+        // The examples of RFC 7386, appendix A.
+        for (target, patch, result) in [
+            ("{a: \"b\"}", "{a: \"c\"}", "{a: \"c\"}"),
+            ("{a: \"b\"}", "{b: \"c\"}", "{a: \"b\", b: \"c\"}"),
+            ("{a: \"b\"}", "{a: null}", "{}"),
+            ("{a: \"b\", b: \"c\"}", "{a: null}", "{b: \"c\"}"),
+            ("{a: [\"b\"]}", "{a: \"c\"}", "{a: \"c\"}"),
+            ("{a: \"c\"}", "{a: [\"b\"]}", "{a: [\"b\"]}"),
+            (
+                "a:\n  b: \"c\"",
+                "a:\n  b: \"d\"\n  c: null",
+                "a:\n  b: \"d\"",
+            ),
+            ("a: [{b: \"c\"}]", "a: [1]", "a: [1]"),
+            ("[\"a\", \"b\"]", "[\"c\", \"d\"]", "[\"c\", \"d\"]"),
+            ("{a: \"b\"}", "[\"c\"]", "[\"c\"]"),
+            ("{a: \"foo\"}", "null", "null"),
+            ("{a: \"foo\"}", "\"bar\"", "\"bar\""),
+            ("{e: null}", "{a: 1}", "{e: null, a: 1}"),
+            ("[1, 2]", "{a: \"b\", c: null}", "{a: \"b\"}"),
+            ("{}", "a:\n  bb:\n    ccc: null", "a:\n  bb: {}"),
+        ] {
+            let mut value = parse(target).unwrap();
+            value.merge_patch(&parse(patch).unwrap());
+            assert_eq!(value, parse(result).unwrap(), "{} with {}", target, patch);
+        }
+    }
+}
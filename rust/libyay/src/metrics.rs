@@ -0,0 +1,140 @@
+//! Counting what the library does, for programs that embed it.
+//!
+//! Once a [`MetricsSink`] is installed with [`set_sink`], every parse and
+//! encoding in the process reports a [`Metric`] to it: each document
+//! parsed and its size, the code of each error, and each format encoded.
+//! A service can total these across all its callers without wrapping
+//! every call. With no sink installed, reporting costs one atomic load.
+//!
+//! # Example
+//!
+//! ```
+//! use std::sync::atomic::{AtomicUsize, Ordering};
+//! use std::sync::Arc;
+//! use libyay::metrics::{self, Metric, MetricsSink};
+//!
+//! #[derive(Debug, Default)]
+//! struct Bytes(AtomicUsize);
+//!
+//! impl MetricsSink for Bytes {
+//!     fn record(&self, metric: Metric) {
+//!         if let Metric::Parsed { bytes } = metric {
+//!             self.0.fetch_add(bytes, Ordering::Relaxed);
+//!         }
+//!     }
+//! }
+//!
+//! let sink = Arc::new(Bytes::default());
+//! metrics::set_sink(Some(sink.clone()));
+//! libyay::parse("answer: 42").unwrap();
+//! metrics::set_sink(None);
+//! assert!(sink.0.load(Ordering::Relaxed) >= 10);
+//! ```
+
+use core::fmt;
+#[cfg(feature = "std")]
+use core::sync::atomic::{AtomicBool, Ordering};
+#[cfg(feature = "std")]
+use std::sync::{Arc, RwLock};
+
+use crate::{Format, ParseError};
+
+/// One thing the library did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Metric {
+    /// A document parsed, and its size in bytes.
+    Parsed { bytes: usize },
+    /// A parse that failed, with the [`ParseError::code`] of its error and
+    /// the bytes read before it stopped.
+    Failed { code: &'static str, bytes: usize },
+    /// A value encoded in a format.
+    Encoded { format: Format },
+}
+
+/// Where [`Metric`]s go. A sink is called from whatever thread parsed or
+/// encoded, so it should be quick, and count rather than log.
+pub trait MetricsSink: fmt::Debug + Send + Sync {
+    /// Count one metric.
+    fn record(&self, metric: Metric);
+}
+
+/// Whether a sink is installed, checked before taking the lock.
+#[cfg(feature = "std")]
+static ENABLED: AtomicBool = AtomicBool::new(false);
+#[cfg(feature = "std")]
+static SINK: RwLock<Option<Arc<dyn MetricsSink>>> = RwLock::new(None);
+
+/// Send every metric in the process to `sink` from now on, or with `None`,
+/// stop reporting them.
+#[cfg(feature = "std")]
+pub fn set_sink(sink: Option<Arc<dyn MetricsSink>>) {
+    let mut current = SINK.write().unwrap_or_else(|e| e.into_inner());
+    ENABLED.store(sink.is_some(), Ordering::Relaxed);
+    *current = sink;
+}
+
+/// Report `metric` to the installed sink, if there is one.
+#[cfg(feature = "std")]
+pub(crate) fn record(metric: Metric) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    let sink = SINK.read().unwrap_or_else(|e| e.into_inner()).clone();
+    if let Some(sink) = sink {
+        sink.record(metric);
+    }
+}
+
+/// Without `std` there is nowhere to install a sink, so nothing is reported.
+#[cfg(not(feature = "std"))]
+pub(crate) fn record(_metric: Metric) {}
+
+/// Report how a parse of `bytes` bytes ended, and pass its result on.
+pub(crate) fn parsed<T>(bytes: usize, result: Result<T, ParseError>) -> Result<T, ParseError> {
+    match &result {
+        Ok(_) => record(Metric::Parsed { bytes }),
+        Err(e) => record(Metric::Failed {
+            code: e.code(),
+            bytes,
+        }),
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{encode, parse, parse_bytes, ParseOptions, Value};
+    use std::sync::Mutex;
+
+    #[derive(Debug, Default)]
+    struct Recorder(Mutex<Vec<Metric>>);
+
+    impl MetricsSink for Recorder {
+        fn record(&self, metric: Metric) {
+            self.0.lock().unwrap().push(metric);
+        }
+    }
+
+    #[test]
+    fn test_metrics() {
+        let recorder = Arc::new(Recorder::default());
+        set_sink(Some(recorder.clone()));
+        // Other tests parse at the same time, so look for these sizes.
+        parse("metrics: \"a string of 31 bytes\"").unwrap();
+        parse_bytes(b"metrics:\n\t- 1\n", &ParseOptions::default()).unwrap_err();
+        encode(&Value::from(1), Format::Scheme);
+        set_sink(None);
+
+        let metrics = recorder.0.lock().unwrap();
+        assert!(metrics.contains(&Metric::Parsed { bytes: 31 }));
+        assert!(metrics.contains(&Metric::Failed {
+            code: "tab_not_allowed",
+            bytes: 14
+        }));
+        assert!(metrics.contains(&Metric::Encoded {
+            format: Format::Scheme
+        }));
+    }
+}
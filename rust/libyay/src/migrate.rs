@@ -0,0 +1,310 @@
+//! Versioned document upgrades.
+//!
+//! A document records its version in a top-level `version:` member. A
+//! migrations file lists, for each version, the steps that upgrade a
+//! document from the version before it:
+//!
+//! ```yay
+//! - version: 2
+//!   steps:
+//!     - rename: {path: "/server/host", to: "hostname"}
+//!     - move: {from: "/port", to: "/server/port"}
+//! - version: 3
+//!   steps:
+//!     - default: {path: "/server/tls", value: false}
+//!     - delete: "/legacy"
+//! ```
+//!
+//! Paths are JSON Pointers. A step whose path is missing does nothing, so
+//! a migration works on documents that never had an optional member.
+//! A document without a `version` is at version 0.
+
+use crate::value::split_pointer;
+use crate::{Map, Value};
+
+/// One change to a document.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Step {
+    /// Rename the member at `path` to `to`, keeping its place.
+    Rename { path: String, to: String },
+    /// Move the value at `from` to the path `to`.
+    Move { from: String, to: String },
+    /// Set the value at `path` if it is missing.
+    Default { path: String, value: Value },
+    /// Remove the value at `path`.
+    Delete { path: String },
+}
+
+/// The steps that bring a document up to `version`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Migration {
+    pub version: u64,
+    pub steps: Vec<Step>,
+}
+
+/// Migrations in increasing order of version.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Migrations {
+    pub migrations: Vec<Migration>,
+}
+
+impl Migrations {
+    /// Parse migrations from YAY source.
+    pub fn from_yay(source: &str) -> Result<Migrations, String> {
+        let value = crate::parse(source).map_err(|e| e.to_string())?;
+        Migrations::from_value(&value)
+    }
+
+    /// Build migrations from a parsed value.
+    pub fn from_value(value: &Value) -> Result<Migrations, String> {
+        let entries = value
+            .as_array()
+            .ok_or_else(|| "Migrations must be an array".to_string())?;
+        let mut migrations: Vec<Migration> = Vec::new();
+        for entry in entries {
+            let version = entry
+                .get("version")
+                .and_then(|v| u64::try_from(v).ok())
+                .ok_or_else(|| "Each migration needs a version".to_string())?;
+            if migrations.last().is_some_and(|m| m.version >= version) {
+                return Err(format!("Migration to version {} is out of order", version));
+            }
+            let steps = match entry.get("steps") {
+                Some(Value::Array(steps)) => steps
+                    .iter()
+                    .map(|step| parse_step(version, step))
+                    .collect::<Result<_, _>>()?,
+                None => Vec::new(),
+                Some(_) => {
+                    return Err(format!(
+                        "Steps of migration to version {} must be an array",
+                        version
+                    ))
+                }
+            };
+            migrations.push(Migration { version, steps });
+        }
+        Ok(Migrations { migrations })
+    }
+
+    /// The highest version these migrations reach, or 0 if there are none.
+    pub fn latest(&self) -> u64 {
+        self.migrations.last().map_or(0, |m| m.version)
+    }
+
+    /// Upgrade `document` to version `to`, applying every migration after
+    /// its current version in order, and set its `version`. Returns the
+    /// version it started at.
+    ///
+    /// ```
+    /// use libyay::migrate::Migrations;
+    /// use libyay::parse;
+    ///
+    /// let migrations = Migrations::from_yay(
+    ///     "- version: 2\n  steps:\n    - rename: {path: \"/host\", to: \"hostname\"}\n",
+    /// )
+    /// .unwrap();
+    /// let mut document = parse("version: 1\nhost: \"a\"\n").unwrap();
+    /// assert_eq!(migrations.apply(&mut document, 2), Ok(1));
+    /// assert_eq!(document, parse("version: 2\nhostname: \"a\"\n").unwrap());
+    /// ```
+    pub fn apply(&self, document: &mut Value, to: u64) -> Result<u64, String> {
+        let root = document
+            .as_object()
+            .ok_or_else(|| "Only an object can be migrated".to_string())?;
+        let from = match root.get("version") {
+            None => 0,
+            Some(v) => u64::try_from(v).map_err(|e| format!("Invalid version: {}", e))?,
+        };
+        if to < from {
+            return Err(format!(
+                "Document is at version {}, after version {}",
+                from, to
+            ));
+        }
+        if to > self.latest() && to > from {
+            return Err(format!("No migration reaches version {}", to));
+        }
+        for migration in &self.migrations {
+            if migration.version <= from || migration.version > to {
+                continue;
+            }
+            for step in &migration.steps {
+                apply_step(document, step)
+                    .map_err(|e| format!("Migration to version {}: {}", migration.version, e))?;
+            }
+        }
+        if let Value::Object(root) = document {
+            root.insert("version".to_string(), Value::from(to));
+        }
+        Ok(from)
+    }
+}
+
+fn parse_step(version: u64, step: &Value) -> Result<Step, String> {
+    let invalid = |what: &str| format!("Migration to version {}: {}", version, what);
+    let string = |value: &Value, name: &str| {
+        value
+            .get(name)
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .ok_or_else(|| invalid(&format!("step needs a string \"{}\"", name)))
+    };
+    let (op, args) = match step.as_object().map(|obj| obj.iter().collect::<Vec<_>>()) {
+        Some(members) if members.len() == 1 => members[0],
+        _ => return Err(invalid("each step must be an object with one operation")),
+    };
+    match op.as_str() {
+        "rename" => Ok(Step::Rename {
+            path: string(args, "path")?,
+            to: string(args, "to")?,
+        }),
+        "move" => Ok(Step::Move {
+            from: string(args, "from")?,
+            to: string(args, "to")?,
+        }),
+        "default" => Ok(Step::Default {
+            path: string(args, "path")?,
+            value: args
+                .get("value")
+                .cloned()
+                .ok_or_else(|| invalid("default needs a \"value\""))?,
+        }),
+        "delete" => match args {
+            Value::String(path) => Ok(Step::Delete { path: path.clone() }),
+            _ => Err(invalid("delete takes a path")),
+        },
+        _ => Err(invalid(&format!("unknown operation {:?}", op))),
+    }
+}
+
+fn apply_step(document: &mut Value, step: &Step) -> Result<(), String> {
+    match step {
+        Step::Rename { path, to } => {
+            let (parent, key) = split_pointer(path)?;
+            let Some(Value::Object(obj)) = document.pointer_mut(parent) else {
+                return Ok(());
+            };
+            if key == *to || !obj.contains_key(&key) {
+                return Ok(());
+            }
+            if obj.contains_key(to.as_str()) {
+                return Err(format!("cannot rename {} to {:?}: it exists", path, to));
+            }
+            if let Some((index, _, value)) = obj.shift_remove_full(&key) {
+                obj.shift_insert(index, to.clone(), value);
+            }
+        }
+        Step::Move { from, to } => {
+            if document.pointer(to).is_some() {
+                return Err(format!("cannot move {} to {}: it exists", from, to));
+            }
+            if let Some(value) = remove(document, from)? {
+                insert(document, to, value)?;
+            }
+        }
+        Step::Default { path, value } => {
+            if document.pointer(path).is_none() {
+                insert(document, path, value.clone())?;
+            }
+        }
+        Step::Delete { path } => {
+            remove(document, path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Remove and return the value at `pointer`, if there is one.
+fn remove(document: &mut Value, pointer: &str) -> Result<Option<Value>, String> {
+    let (parent, key) = split_pointer(pointer)?;
+    Ok(match document.pointer_mut(parent) {
+        Some(Value::Object(obj)) => obj.shift_remove(&key),
+        Some(Value::Array(arr)) => match key.parse::<usize>() {
+            Ok(index) if index < arr.len() => Some(arr.remove(index)),
+            _ => None,
+        },
+        _ => None,
+    })
+}
+
+/// Put `value` at `pointer`, making objects for missing parents.
+fn insert(document: &mut Value, pointer: &str, value: Value) -> Result<(), String> {
+    let (parent, key) = split_pointer(pointer)?;
+    if document.pointer(parent).is_none() {
+        insert(document, parent, Value::Object(Map::new()))?;
+    }
+    match document.pointer_mut(parent) {
+        Some(Value::Object(obj)) => {
+            obj.insert(key, value);
+            Ok(())
+        }
+        _ => Err(format!(
+            "cannot set {}: its parent is not an object",
+            pointer
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    fn migrations() -> Migrations {
+        Migrations::from_yay(
+            r#"
+- version: 2
+  steps:
+    - rename: {path: "/server/host", to: "hostname"}
+    - move: {from: "/port", to: "/server/port"}
+- version: 3
+  steps:
+    - default: {path: "/server/tls/enabled", value: false}
+    - delete: "/legacy"
+"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_upgrade() {
+        let mut document =
+            parse("server:\n  host: \"a\"\n  name: \"web\"\nport: 80\nlegacy: true\n").unwrap();
+        assert_eq!(migrations().apply(&mut document, 3), Ok(0));
+        let expected = parse(
+            "server:\n  hostname: \"a\"\n  name: \"web\"\n  port: 80\n  tls:\n    enabled: false\nversion: 3\n",
+        )
+        .unwrap();
+        assert_eq!(document, expected);
+        let keys: Vec<&String> = document["server"].as_object().unwrap().keys().collect();
+        assert_eq!(keys, ["hostname", "name", "port", "tls"]);
+    }
+
+    #[test]
+    fn test_partial_and_missing_paths() {
+        let mut document = parse("version: 1\nother: 1\n").unwrap();
+        assert_eq!(migrations().apply(&mut document, 2), Ok(1));
+        assert_eq!(document, parse("version: 2\nother: 1\n").unwrap());
+        assert_eq!(migrations().apply(&mut document, 2), Ok(2));
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut document = parse("version: 3\n").unwrap();
+        assert_eq!(
+            migrations().apply(&mut document, 2),
+            Err("Document is at version 3, after version 2".to_string())
+        );
+        let mut document = parse("{}").unwrap();
+        assert_eq!(
+            migrations().apply(&mut document, 4),
+            Err("No migration reaches version 4".to_string())
+        );
+        let mut document = parse("server: {host: 1, hostname: 2}").unwrap();
+        assert!(migrations().apply(&mut document, 2).is_err());
+
+        assert!(Migrations::from_yay("- version: 2\n- version: 2\n").is_err());
+        assert!(Migrations::from_yay("- version: 2\n  steps: [{explode: \"/\"}]\n").is_err());
+    }
+}
@@ -0,0 +1,330 @@
+//! Newline-delimited YAY: a stream of independent documents.
+
+use std::io::{self, BufRead};
+
+use crate::encoding::Newlines;
+use crate::error::{ParseContext, ParseError, Result};
+use crate::lexer::outline_lex;
+use crate::parser::parse_root;
+use crate::scanner::{scan_line, validate_line_code_points, validate_no_bom, ScanLine};
+use crate::{ParseOptions, Value};
+
+/// An iterator over the documents in a reader of newline-delimited YAY,
+/// parsing each one as it is read, for logs and pipelines where every
+/// record is a value of its own.
+///
+/// A record begins on each line that starts in column zero and continues
+/// through the indented lines after it, so a record may be a single inline
+/// value or a block of properties or items. Blank lines and comments
+/// between records are skipped.
+///
+/// A record that does not parse yields its error, with line numbers
+/// counted from the start of the reader, and iteration goes on with the
+/// next record. An error reading the input or decoding it as UTF-8 is
+/// yielded last.
+///
+/// # Example
+///
+/// ```
+/// use libyay::{Documents, ParseOptions, Value};
+///
+/// let input = b"{level: \"info\", n: 1}\n{level: \"warn\", n: 2}\nevent:\n  n: 3\n";
+/// let values: Vec<Value> = Documents::new(&input[..], &ParseOptions::default())
+///     .collect::<Result<_, _>>()
+///     .unwrap();
+/// assert_eq!(values.len(), 3);
+/// assert_eq!(values[2].get("event"), Some(&libyay::parse("n: 3").unwrap()));
+/// ```
+pub struct Documents<R> {
+    reader: R,
+    records: Records,
+}
+
+impl<R: BufRead> Documents<R> {
+    /// An iterator over the documents in `reader`, each parsed with
+    /// `options`.
+    pub fn new(reader: R, options: &ParseOptions) -> Self {
+        Documents {
+            reader,
+            records: Records::new(options),
+        }
+    }
+
+    /// The one-based line where the last document returned began.
+    pub fn line(&self) -> usize {
+        self.records.start + 1
+    }
+
+    /// Read the next record, or `None` at the end of the input.
+    fn read_record(&mut self) -> Result<Option<Record>> {
+        let mut record = self.records.next.take().unwrap_or_default();
+        loop {
+            let mut bytes = Vec::new();
+            let read = self.reader.read_until(b'\n', &mut bytes);
+            let line = self.records.decode(read, bytes);
+            if self.records.take(&mut record, line)? {
+                break;
+            }
+        }
+        Ok(record.start.is_some().then_some(record))
+    }
+}
+
+impl<R: BufRead> Iterator for Documents<R> {
+    type Item = Result<Value>;
+
+    fn next(&mut self) -> Option<Result<Value>> {
+        if self.records.done {
+            return None;
+        }
+        if let Some(e) = self.records.failed.take() {
+            self.records.done = true;
+            return Some(Err(e));
+        }
+        let record = self.read_record();
+        self.records.parse(record)
+    }
+}
+
+/// Splitting lines into records and parsing them, apart from reading the
+/// lines, which [`Documents`] does from a `BufRead` and
+/// [`AsyncDocuments`](crate::AsyncDocuments) from an `AsyncBufRead`.
+pub(crate) struct Records {
+    options: ParseOptions,
+    ctx: ParseContext,
+    /// Byte offset of the next line in the input.
+    offset: usize,
+    /// Zero-based number of the next line to read.
+    line_num: usize,
+    /// The next record, begun while reading the last.
+    pub(crate) next: Option<Record>,
+    /// Zero-based line where the last record returned began.
+    pub(crate) start: usize,
+    /// An error reading the input, to yield after the record before it.
+    pub(crate) failed: Option<ParseError>,
+    pub(crate) done: bool,
+}
+
+impl Records {
+    pub(crate) fn new(options: &ParseOptions) -> Self {
+        Records {
+            options: options.clone(),
+            ctx: ParseContext::from_options(options),
+            offset: 0,
+            line_num: 0,
+            next: None,
+            start: 0,
+            failed: None,
+            done: false,
+        }
+    }
+
+    /// The line read into `bytes` with `read_until`, or `None` at the end
+    /// of the input.
+    pub(crate) fn decode(
+        &mut self,
+        read: io::Result<usize>,
+        mut bytes: Vec<u8>,
+    ) -> Result<Option<String>> {
+        let len = read.map_err(|e| {
+            let what = self.options.filename.as_deref().unwrap_or("input");
+            ParseError::Read(what.to_string(), e.to_string())
+        })?;
+        if len == 0 {
+            return Ok(None);
+        }
+        if bytes.last() == Some(&b'\n') {
+            bytes.pop();
+            if self.options.newlines == Newlines::Normalize && bytes.last() == Some(&b'\r') {
+                bytes.pop();
+            }
+        }
+        let line = String::from_utf8(bytes).map_err(|e| {
+            ParseError::InvalidEncoding(
+                "UTF-8",
+                self.offset + e.utf8_error().valid_up_to(),
+                self.ctx.file_suffix(),
+            )
+        })?;
+        self.offset += len;
+        Ok(Some(line))
+    }
+
+    /// Scan a line, returning `None` for a comment.
+    fn scan(&self, line: &str, line_num: usize) -> Result<Option<ScanLine>> {
+        if line_num == 0 {
+            validate_no_bom(line, &self.ctx)?;
+        }
+        validate_line_code_points(line, line_num, &self.ctx)?;
+        scan_line(line, line_num, &self.ctx)
+    }
+
+    /// Take the next line into `record`, returning whether the record is
+    /// complete: at the end of the input, or when the line begins the next
+    /// record. A line that does not scan belongs to the record it is in,
+    /// which then yields that line's error once the rest of it is read.
+    pub(crate) fn take(
+        &mut self,
+        record: &mut Record,
+        line: Result<Option<String>>,
+    ) -> Result<bool> {
+        let text = match line {
+            Ok(Some(text)) => text,
+            Ok(None) => return Ok(true),
+            // Yield the record read so far before the error.
+            Err(e) if record.start.is_some() => {
+                self.failed = Some(e);
+                return Ok(true);
+            }
+            Err(e) => return Err(e),
+        };
+        let line_num = self.line_num;
+        self.line_num += 1;
+        // A version directive belongs to the stream, not its first record.
+        if line_num == 0 && text.starts_with('%') && self.ctx.version_directive {
+            self.scan(&text, line_num)?;
+            return Ok(false);
+        }
+        let starts = !text.is_empty() && !text.starts_with(' ') && !text.starts_with('#');
+        if starts && record.start.is_some() {
+            let mut next = Record::default();
+            self.begin(&mut next, &text, line_num);
+            self.next = Some(next);
+            return Ok(true);
+        }
+        if starts || record.start.is_some() {
+            self.begin(record, &text, line_num);
+        } else if text.trim_start().starts_with('#') {
+            record.had_comments = true;
+        }
+        Ok(false)
+    }
+
+    /// Add a line to a record, starting the record if it is empty.
+    fn begin(&self, record: &mut Record, text: &str, line_num: usize) {
+        record.start.get_or_insert(line_num);
+        match self.scan(text, line_num) {
+            Ok(Some(line)) => record.lines.push(line),
+            Ok(None) => record.had_comments = true,
+            Err(e) => {
+                record.error.get_or_insert(e);
+            }
+        }
+    }
+
+    /// The document a record read holds, or `None` after the last.
+    pub(crate) fn parse(&mut self, record: Result<Option<Record>>) -> Option<Result<Value>> {
+        match record {
+            Ok(Some(record)) => {
+                self.start = record.start.unwrap_or_default();
+                Some(match record.error {
+                    Some(e) => Err(e),
+                    None => parse_root(&outline_lex(&record.lines), &self.ctx),
+                })
+            }
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// The lines of one document.
+#[derive(Default)]
+pub(crate) struct Record {
+    /// Zero-based line where the record begins.
+    pub(crate) start: Option<usize>,
+    lines: Vec<ScanLine>,
+    had_comments: bool,
+    /// The first line that did not scan.
+    error: Option<ParseError>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    fn documents(input: &str, options: &ParseOptions) -> Vec<Result<Value>> {
+        Documents::new(input.as_bytes(), options).collect()
+    }
+
+    #[test]
+    fn test_records() {
+        let options = ParseOptions::default();
+        let values: Vec<Value> = documents(
+            "# log\n1\n\n\"two\"\na:\n  b: 3\n\n  c: [4]\n- 5\n- 6\n",
+            &options,
+        )
+        .into_iter()
+        .collect::<Result<_>>()
+        .unwrap();
+        assert_eq!(
+            values,
+            vec![
+                parse("1").unwrap(),
+                parse("\"two\"").unwrap(),
+                parse("a:\n  b: 3\n  c: [4]").unwrap(),
+                parse("[5]").unwrap(),
+                parse("[6]").unwrap(),
+            ]
+        );
+        assert!(documents("", &options).is_empty());
+        assert!(documents("# only a comment\n\n", &options).is_empty());
+        assert!(documents("%yay 1\n1\n", &options)[0].is_err());
+        let options = ParseOptions {
+            version_directive: true,
+            ..ParseOptions::default()
+        };
+        assert_eq!(documents("%yay 1\n1\n2\n", &options).len(), 2);
+        assert!(documents("%yay 9\n1\n", &options)[0].is_err());
+    }
+
+    #[test]
+    fn test_errors_and_lines() {
+        let options = ParseOptions {
+            filename: Some("log.ndyay".into()),
+            ..ParseOptions::default()
+        };
+        let mut docs = Documents::new(&b"1\n[2,3]\n{a: 4}\n"[..], &options);
+        assert_eq!(docs.next().unwrap().unwrap(), Value::Integer(1.into()));
+        assert_eq!(docs.line(), 1);
+        let error = docs.next().unwrap().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            parse_with_filename("\n[2,3]", "log.ndyay")
+        );
+        assert_eq!(docs.line(), 2);
+        assert_eq!(docs.next().unwrap().unwrap(), parse("a: 4").unwrap());
+        assert_eq!(docs.line(), 3);
+        assert!(docs.next().is_none());
+
+        // A line that does not scan spoils only its own record.
+        let results = documents("1\n\"\u{FFFE}\"\n3\n", &options);
+        assert_eq!(results.len(), 3);
+        assert!(results[1].is_err());
+        assert_eq!(results[2].as_ref().unwrap(), &Value::Integer(3.into()));
+
+        let results: Vec<_> = Documents::new(&b"1\n\"\xFF\"\n3\n"[..], &options).collect();
+        assert_eq!(results.len(), 2);
+        assert_eq!(
+            results[1].as_ref().unwrap_err().to_string(),
+            "Invalid UTF-8 at byte offset 3 of <log.ndyay>"
+        );
+    }
+
+    fn parse_with_filename(source: &str, filename: &str) -> String {
+        let options = ParseOptions {
+            filename: Some(filename.into()),
+            ..ParseOptions::default()
+        };
+        crate::parse_with_options(source, &options)
+            .unwrap_err()
+            .to_string()
+    }
+}
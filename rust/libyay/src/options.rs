@@ -0,0 +1,158 @@
+//! Options for parsing.
+
+use alloc::string::String;
+use alloc::sync::Arc;
+use core::fmt;
+use core::ops::ControlFlow;
+#[cfg(feature = "std")]
+use std::path::PathBuf;
+
+use crate::encoding::{EncodingDetection, Newlines};
+use crate::quantity::Quantities;
+use crate::{CancellationToken, ParseError, Value};
+
+/// Options for [`parse_with_options`](crate::parse_with_options) and
+/// [`parse_bytes`](crate::parse_bytes).
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct ParseOptions {
+    /// Filename for error messages.
+    pub filename: Option<String>,
+    /// How to determine the encoding of the input bytes.
+    pub encoding: EncodingDetection,
+    /// How to treat carriage returns.
+    pub newlines: Newlines,
+    /// Whether to accept unit-suffixed quantities like `10s`.
+    pub quantities: Quantities,
+    /// Whether to read floats written in decimal, like `12.30`, as exact
+    /// [`Decimal`](crate::Decimal)s instead of `f64`s. Hexadecimal floats,
+    /// `nan`, and the infinities stay floats.
+    pub decimals: bool,
+    /// Whether to accept hexadecimal floats like `0x1.8p3`, which are not
+    /// standard YAY.
+    pub hex_floats: bool,
+    /// Whether to read a `%yay 1` directive on the first line, declaring
+    /// the version of YAY the document is written in, which is not
+    /// standard YAY. The report gives the version.
+    pub version_directive: bool,
+    /// The directory that `<@ "file">` byte literals read from. A literal
+    /// may name any file inside it, but nothing outside it. `None`, the
+    /// default, leaves such literals invalid.
+    #[cfg(feature = "std")]
+    pub bytes_root: Option<PathBuf>,
+    /// What to do with a key that appears more than once in one object.
+    pub duplicate_keys: DuplicateKeys,
+    /// Whether a property with no value after its colon and nothing
+    /// nested under it, like `key:` in YAML, is null instead of an error.
+    pub empty_values: bool,
+    /// The most bytes the document may have. `None`, the default, allows
+    /// any size.
+    pub max_bytes: Option<usize>,
+    /// How deep arrays and objects may nest, counted as in
+    /// [`ParseReport::max_depth`](crate::ParseReport::max_depth). `None`,
+    /// the default, allows any depth.
+    pub max_depth: Option<usize>,
+    /// The most values the document may have, counting each array, object,
+    /// and scalar, but not keys. `None`, the default, allows any number.
+    pub max_nodes: Option<usize>,
+    /// What to tell of the parse as it goes. [`parse_with_options`],
+    /// [`parse_bytes`], [`parse_with_report`], and [`parse_with_spans`]
+    /// report to it; the other parsers do not.
+    ///
+    /// [`parse_with_options`]: crate::parse_with_options
+    /// [`parse_bytes`]: crate::parse_bytes
+    /// [`parse_with_report`]: crate::parse_with_report
+    /// [`parse_with_spans`]: crate::parse_with_spans
+    pub observer: Option<Arc<dyn ParseObserver>>,
+    /// A token that stops the parse once it is cancelled, with
+    /// [`ParseError::Cancelled`]. Every parser checks it.
+    pub cancel: Option<CancellationToken>,
+}
+
+/// Callbacks for a parse in progress, for progress bars, metrics, and
+/// giving up early. Register one as [`ParseOptions::observer`].
+///
+/// A callback that returns [`ControlFlow::Break`] stops the parse, which
+/// fails with [`ParseError::Cancelled`]. Every method does nothing by
+/// default.
+///
+/// # Example
+///
+/// ```
+/// use std::ops::ControlFlow;
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use std::sync::Arc;
+/// use libyay::{parse_with_options, ParseError, ParseObserver, ParseOptions, Value};
+///
+/// /// Gives up after the first two members of the root.
+/// #[derive(Debug, Default)]
+/// struct FirstTwo(AtomicUsize);
+///
+/// impl ParseObserver for FirstTwo {
+///     fn on_value(&self, _path: &str, _value: &Value) -> ControlFlow<()> {
+///         match self.0.fetch_add(1, Ordering::Relaxed) {
+///             0 | 1 => ControlFlow::Continue(()),
+///             _ => ControlFlow::Break(()),
+///         }
+///     }
+/// }
+///
+/// let mut options = ParseOptions::default();
+/// options.observer = Some(Arc::new(FirstTwo::default()));
+/// let result = parse_with_options("a: 1\nb: 2\nc: 3\n", &options);
+/// assert!(matches!(result, Err(ParseError::Cancelled(_))));
+/// ```
+pub trait ParseObserver: fmt::Debug + Send + Sync {
+    /// Called as each line is scanned, with its number, counting from 1,
+    /// and the number of lines in the document.
+    fn on_line(&self, line: usize, lines: usize) -> ControlFlow<()> {
+        let _ = (line, lines);
+        ControlFlow::Continue(())
+    }
+
+    /// Called with each value at the top of the document as it is parsed:
+    /// each member of a root object, with its key as the path, each item
+    /// of a root block array, with a path like `[0]`, or any other root
+    /// value, with an empty path.
+    fn on_value(&self, path: &str, value: &Value) -> ControlFlow<()> {
+        let _ = (path, value);
+        ControlFlow::Continue(())
+    }
+
+    /// Called with the error when the parse fails, cancellation included.
+    fn on_error(&self, error: &ParseError) {
+        let _ = error;
+    }
+}
+
+/// What to do with a key that appears more than once in one object.
+///
+/// The policy applies where a document is read into a [`Value`](crate::Value);
+/// a [`Parser`](crate::Parser) reports every member as it is written.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DuplicateKeys {
+    /// Fail, naming where the key appears both times.
+    Error,
+    /// Keep the first value.
+    FirstWins,
+    /// Keep the last value, at the position of the first.
+    #[default]
+    LastWins,
+    /// Keep the last value, and list the key in
+    /// [`ParseReport::duplicate_keys`](crate::ParseReport::duplicate_keys).
+    Warn,
+}
+
+impl DuplicateKeys {
+    /// Parse a policy name (`error`, `first`, `last`, or `warn`).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "error" => Some(DuplicateKeys::Error),
+            "first" => Some(DuplicateKeys::FirstWins),
+            "last" => Some(DuplicateKeys::LastWins),
+            "warn" => Some(DuplicateKeys::Warn),
+            _ => None,
+        }
+    }
+}
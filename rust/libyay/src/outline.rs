@@ -0,0 +1,192 @@
+//! Document outlines for editors.
+//!
+//! [`symbols`] lists the keys and array items of a document as a tree, each
+//! with the lines it covers, which is what an editor needs for an outline
+//! view, breadcrumbs, and folding. Documents are read the way
+//! [`format_yay`](crate::format_yay) reads them, so a document that is
+//! still being written has an outline too.
+
+use std::fmt;
+use std::ops::Range;
+
+use crate::meh::{MehParser, Shape, Span, SpanItem};
+
+/// The kind of value a symbol holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Null,
+    Bool,
+    Number,
+    String,
+    Bytes,
+    Array,
+    Object,
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Kind::Null => "null",
+            Kind::Bool => "bool",
+            Kind::Number => "number",
+            Kind::String => "string",
+            Kind::Bytes => "bytes",
+            Kind::Array => "array",
+            Kind::Object => "object",
+        })
+    }
+}
+
+/// An object member or array item in an outline.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Symbol {
+    /// The member's key, or the item's index.
+    pub name: String,
+    pub kind: Kind,
+    /// The lines the symbol covers, 0-based and end-exclusive.
+    pub lines: Range<usize>,
+    /// For an array, the number of items.
+    pub items: Option<usize>,
+    /// The members or items of a block object or array.
+    pub children: Vec<Symbol>,
+}
+
+/// The outline of a document: its top-level members or items, each with
+/// its own members or items.
+///
+/// ```
+/// use libyay::outline::{symbols, Kind};
+///
+/// let outline = symbols("name: \"web\"\nports:\n  - 80\n  - 443\n");
+/// assert_eq!(outline[1].name, "ports");
+/// assert_eq!(outline[1].kind, Kind::Array);
+/// assert_eq!(outline[1].lines, 1..4);
+/// assert_eq!(outline[1].items, Some(2));
+/// ```
+pub fn symbols(source: &str) -> Vec<Symbol> {
+    let mut parser = MehParser::new(source);
+    if parser.parse().is_err() {
+        return Vec::new();
+    }
+    let spans = parser.spans();
+
+    let mut tree = Tree {
+        spans,
+        children: vec![Vec::new(); spans.len()],
+    };
+    let mut roots = Vec::new();
+    for (index, span) in spans.iter().enumerate() {
+        match span.parent {
+            Some(parent) => tree.children[parent].push(index),
+            None => roots.push(index),
+        }
+    }
+    tree.symbols(&roots)
+}
+
+/// Spans and the indices of each one's children.
+struct Tree<'a> {
+    spans: &'a [Span],
+    children: Vec<Vec<usize>>,
+}
+
+impl Tree<'_> {
+    /// The symbols of the members and items among `indices`, numbering
+    /// the items from 0.
+    fn symbols(&self, indices: &[usize]) -> Vec<Symbol> {
+        let mut items = 0..;
+        indices
+            .iter()
+            .filter_map(|&index| match &self.spans[index].item {
+                SpanItem::Property(key, shape) => Some(self.symbol(index, key.clone(), *shape)),
+                SpanItem::ArrayItem(shape) => {
+                    let name = items.next().unwrap_or_default().to_string();
+                    Some(self.symbol(index, name, *shape))
+                }
+                SpanItem::Comment | SpanItem::Value(_) => None,
+            })
+            .collect()
+    }
+
+    fn symbol(&self, index: usize, name: String, shape: Shape) -> Symbol {
+        let children = self.symbols(&self.children[index]);
+        let items = match shape.kind {
+            Kind::Array => Some(shape.inline_items.unwrap_or(children.len())),
+            _ => None,
+        };
+        Symbol {
+            name,
+            kind: shape.kind,
+            lines: self.spans[index].lines.clone(),
+            items,
+            children,
+        }
+    }
+}
+
+/// The ranges an editor can fold: every symbol that covers more than one
+/// line, outermost first.
+pub fn folding_ranges(symbols: &[Symbol]) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    for symbol in symbols {
+        if symbol.lines.len() > 1 {
+            ranges.push(symbol.lines.clone());
+        }
+        ranges.extend(folding_ranges(&symbol.children));
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nested_symbols() {
+        let source = "\
+# servers
+servers:
+  - host: \"a\"
+    ports: [80, 443]
+  - host: \"b\"
+    tls:
+      cert: <cafe>
+
+limits: {cpu: 2}
+";
+        let outline = symbols(source);
+        assert_eq!(outline.len(), 2);
+        let servers = &outline[0];
+        assert_eq!(
+            (servers.name.as_str(), servers.kind),
+            ("servers", Kind::Array)
+        );
+        assert_eq!(servers.lines, 1..7);
+        assert_eq!(servers.items, Some(2));
+
+        let second = &servers.children[1];
+        assert_eq!((second.name.as_str(), second.kind), ("1", Kind::Object));
+        assert_eq!(second.lines, 4..7);
+        let names: Vec<&str> = second.children.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["host", "tls"]);
+        assert_eq!(second.children[1].children[0].kind, Kind::Bytes);
+
+        let ports = &servers.children[0].children[1];
+        assert_eq!((ports.kind, ports.items), (Kind::Array, Some(2)));
+        assert!(ports.children.is_empty());
+
+        assert_eq!(outline[1].kind, Kind::Object);
+        assert_eq!(outline[1].lines, 8..9);
+
+        assert_eq!(folding_ranges(&outline), [1..7, 2..4, 4..7, 5..7]);
+    }
+
+    #[test]
+    fn test_unfinished_and_scalar_documents() {
+        let outline = symbols("a:\n  b: [1,\nc");
+        assert_eq!(outline[0].name, "a");
+        assert_eq!(outline[0].children[0].name, "b");
+        assert!(symbols("42\n").is_empty());
+        assert!(symbols("").is_empty());
+    }
+}
@@ -0,0 +1,244 @@
+//! Reading YAY documents.
+//!
+//! The functions here parse a whole document into a [`Value`]: from a
+//! string, bytes, a reader, or a file. [`Parser`] reads a document as a
+//! sequence of events instead, [`PushParser`] takes its input in chunks as
+//! it arrives, and [`Documents`] reads the records of NDYAY.
+
+#[cfg(feature = "std")]
+use alloc::string::ToString;
+#[cfg(feature = "std")]
+use alloc::vec;
+#[cfg(feature = "std")]
+use std::fs::File;
+#[cfg(feature = "std")]
+use std::io::{self, Read};
+#[cfg(feature = "std")]
+use std::path::Path;
+
+#[cfg(feature = "std")]
+use crate::error::ParseError;
+use crate::error::{self, Result};
+use crate::{
+    decode_bytes, lexer, metrics, parser, report, scanner, ParseOptions, ParseReport, Value,
+};
+
+pub use crate::borrowed::parse_borrowed;
+#[cfg(feature = "std")]
+pub use crate::ndyay::Documents;
+pub use crate::parser::Parser;
+pub use crate::push::PushParser;
+
+/// Parse a YAY document from a string.
+///
+/// # Example
+///
+/// ```
+/// use libyay::parse;
+///
+/// let value = parse("42").unwrap();
+/// ```
+pub fn parse(input: &str) -> Result<Value> {
+    parse_with_filename(input, None)
+}
+
+/// Parse a YAY document from a string with a filename for error messages.
+pub fn parse_with_filename(input: &str, filename: Option<&str>) -> Result<Value> {
+    metrics::parsed(
+        input.len(),
+        parse_with_context(input, &error::ParseContext::new(filename)),
+    )
+}
+
+/// Parse a YAY document from a string with options.
+///
+/// # Example
+///
+/// ```
+/// use libyay::{parse_with_options, ParseOptions, Quantities, Quantity};
+///
+/// let mut options = ParseOptions::default();
+/// options.quantities = Quantities::Any;
+/// let value = parse_with_options("timeout: 30s", &options).unwrap();
+/// let timeout = Quantity::from_value(value.get("timeout").unwrap()).unwrap();
+/// assert_eq!(timeout.unit, "s");
+/// ```
+pub fn parse_with_options(input: &str, options: &ParseOptions) -> Result<Value> {
+    let ctx = error::ParseContext::observed(options);
+    metrics::parsed(
+        input.len(),
+        ctx.observe_result(parse_with_context(input, &ctx)),
+    )
+}
+
+/// Parse a YAY document with options, and report what the parse found
+/// besides the value: whether there were comments, how deeply the value
+/// nests, the size of the document, and which extensions it used.
+///
+/// # Example
+///
+/// ```
+/// use libyay::{parse_with_report, ParseOptions};
+///
+/// let mut options = ParseOptions::default();
+/// options.decimals = true;
+/// let (_, report) = parse_with_report("# prices\nitems:\n  - 12.30\n", &options).unwrap();
+/// assert!(report.had_comments);
+/// assert_eq!((report.max_depth, report.lines, report.bytes), (2, 3, 26));
+/// assert!(report.relaxations.decimals && !report.relaxations.quantities);
+/// ```
+pub fn parse_with_report(input: &str, options: &ParseOptions) -> Result<(Value, ParseReport)> {
+    let ctx = error::ParseContext::observed(options);
+    let (value, had_comments) = metrics::parsed(
+        input.len(),
+        ctx.observe_result(scanner::scan(input, &ctx).and_then(|scan_result| {
+            let tokens = lexer::outline_lex(&scan_result.lines);
+            let value = parser::parse_root(&tokens, &ctx)?;
+            Ok((value, scan_result.had_comments))
+        })),
+    )?;
+    let report = ParseReport {
+        had_comments,
+        max_depth: report::depth(&value),
+        lines: input.lines().count(),
+        bytes: input.len(),
+        relaxations: ctx.relaxations.get(),
+        duplicate_keys: ctx.duplicates.take(),
+        version: ctx.version.get(),
+    };
+    Ok((value, report))
+}
+
+#[cfg(feature = "std")]
+/// Parse a YAY document with options, and find the span of every value and
+/// key in it, for tools that report problems with a document's values at
+/// the text they came from. See [`spans`](crate::spans) for what the spans cover.
+///
+/// # Example
+///
+/// ```
+/// use libyay::{parse_with_spans, ParseOptions};
+///
+/// let source = "name: \"web\"\nports: [80, 443]\n";
+/// let spanned = parse_with_spans(source, &ParseOptions::default()).unwrap();
+/// let port = spanned.resolve("ports[1]").unwrap();
+/// assert_eq!((port.span.line, port.span.column), (1, 12));
+/// assert_eq!(&source[port.span.bytes.clone()], "443");
+/// ```
+pub fn parse_with_spans(input: &str, options: &ParseOptions) -> Result<crate::spans::Spanned> {
+    let value = parse_with_options(input, options)?;
+    Ok(crate::spans::locate(input, &value))
+}
+
+fn parse_with_context(input: &str, ctx: &error::ParseContext) -> Result<Value> {
+    // Phase 1: Scan source into lines
+    let scan_result = scanner::scan(input, ctx)?;
+
+    // Phase 2: Convert lines to token stream
+    let tokens = lexer::outline_lex(&scan_result.lines);
+
+    // Phase 3: Parse tokens into value
+    parser::parse_root(&tokens, ctx)
+}
+
+/// Parse a YAY document from bytes.
+///
+/// The encoding is detected and newlines normalized according to
+/// `options` (see [`decode_bytes`]) before parsing.
+///
+/// # Example
+///
+/// ```
+/// use libyay::{parse_bytes, ParseOptions};
+///
+/// let value = parse_bytes(b"\xEF\xBB\xBFanswer: 42\n", &ParseOptions::default()).unwrap();
+/// ```
+pub fn parse_bytes(input: &[u8], options: &ParseOptions) -> Result<Value> {
+    let ctx = error::ParseContext::observed(options);
+    metrics::parsed(
+        input.len(),
+        ctx.observe_result(
+            scanner::validate_size(input.len(), &ctx)
+                .and_then(|()| decode_bytes(input, options))
+                .and_then(|text| parse_with_context(&text, &ctx)),
+        ),
+    )
+}
+
+#[cfg(feature = "std")]
+/// Parse a YAY document from a reader, such as a file or socket.
+///
+/// The input is read in chunks and parsed as it arrives with a
+/// [`PushParser`], so it is never held whole as text, and an invalid UTF-8
+/// sequence is reported by its byte offset in the input.
+///
+/// # Example
+///
+/// ```
+/// use libyay::{parse_reader, Value};
+///
+/// let value = parse_reader(&b"answer: 42\n"[..]).unwrap();
+/// assert_eq!(value["answer"], Value::from(42));
+/// ```
+pub fn parse_reader(reader: impl Read) -> Result<Value> {
+    parse_reader_with_options(reader, &ParseOptions::default())
+}
+
+#[cfg(feature = "std")]
+/// Parse a YAY document from a reader with options.
+///
+/// Errors reading the input, like errors in the document, name
+/// `options.filename` if it is set.
+///
+/// # Example
+///
+/// ```
+/// use libyay::{parse_reader_with_options, ParseOptions};
+///
+/// let mut options = ParseOptions::default();
+/// options.filename = Some("config.yay".into());
+/// let error = parse_reader_with_options(&b"a: 1\nb: \"\xFF\"\n"[..], &options).unwrap_err();
+/// assert_eq!(error.to_string(), "Invalid UTF-8 at byte offset 9 of <config.yay>");
+/// ```
+pub fn parse_reader_with_options(mut reader: impl Read, options: &ParseOptions) -> Result<Value> {
+    let mut parser = PushParser::new(options);
+    let mut buffer = vec![0; 64 * 1024];
+    let mut bytes = 0;
+    let result = loop {
+        match reader.read(&mut buffer) {
+            Ok(0) => break parser.finish(),
+            Ok(n) => {
+                bytes += n;
+                if let Err(e) = parser.feed(&buffer[..n]) {
+                    break Err(e);
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => {
+                let what = options.filename.as_deref().unwrap_or("input");
+                break Err(ParseError::Read(what.to_string(), e.to_string()));
+            }
+        }
+    };
+    metrics::parsed(bytes, result)
+}
+
+#[cfg(feature = "std")]
+/// Parse the YAY file at `path`, naming it in errors.
+///
+/// # Example
+///
+/// ```
+/// let error = libyay::parse_file("no/such/file.yay").unwrap_err();
+/// assert!(error.to_string().starts_with("Error reading no/such/file.yay: "));
+/// ```
+pub fn parse_file(path: impl AsRef<Path>) -> Result<Value> {
+    let path = path.as_ref();
+    let options = ParseOptions {
+        filename: Some(path.display().to_string()),
+        ..ParseOptions::default()
+    };
+    let file = File::open(path)
+        .map_err(|e| ParseError::Read(path.display().to_string(), e.to_string()))?;
+    parse_reader_with_options(file, &options)
+}
@@ -8,25 +8,36 @@
 //! - Block strings: multiline string literals
 
 use crate::error::{ParseContext, ParseError, Result};
+use crate::float::parse_hex_float;
 use crate::lexer::{Token, TokenType};
-use crate::value::Value;
+use crate::options::{DuplicateKeys, ParseOptions};
+use crate::quantity::{is_unit, split_quantity, Quantity};
+use crate::report::{count_values, depth, DuplicateKey};
+use crate::stream::Event;
+use crate::value::{Map, Value};
+use crate::Decimal;
+use alloc::collections::VecDeque;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
 use num_bigint::BigInt;
-use std::collections::HashMap;
+#[cfg(feature = "std")]
+use std::fs;
+#[cfg(feature = "std")]
+use std::path::Path;
 
 /// Parse the root of a YAY document.
-pub fn parse_root(tokens: &[Token], ctx: &ParseContext, had_comments: bool) -> Result<Value> {
+pub fn parse_root(tokens: &[Token], ctx: &ParseContext) -> Result<Value> {
     let i = skip_breaks_and_stops(tokens, 0);
 
+    // A document without a value, even one of only comments, is invalid
     if i >= tokens.len() {
-        // If there were comments but no actual content, error
-        if had_comments {
-            let suffix = match &ctx.filename {
-                Some(name) => format!(" <{}>", name),
-                None => String::new(),
-            };
-            return Err(ParseError::NoValueFound(suffix));
-        }
-        return Ok(Value::Null);
+        let suffix = match &ctx.filename {
+            Some(name) => format!(" <{}>", name),
+            None => String::new(),
+        };
+        return Err(ParseError::NoValueFound(suffix));
     }
 
     let t = &tokens[i];
@@ -37,16 +48,39 @@ pub fn parse_root(tokens: &[Token], ctx: &ParseContext, had_comments: bool) -> R
     }
 
     // Detect root object (key: value at indent 0)
-    // But not inline objects starting with {
-    if t.typ == TokenType::Text && t.text.contains(':') && t.indent == 0 && !t.text.starts_with('{')
-    {
-        let (value, next) = parse_root_object(tokens, i, ctx)?;
-        return ensure_at_end(value, tokens, next, ctx);
+    if t.typ == TokenType::Text && t.indent == 0 && starts_root_object(&t.text) {
+        let (members, next) = parse_root_object(tokens, i, ctx)?;
+        let value = ensure_at_end(Value::Object(members.into_map()), tokens, next, ctx)?;
+        check_limits(&value, ctx)?;
+        return Ok(value);
     }
 
-    // Parse as single value
-    let (value, next) = parse_value(tokens, i, ctx)?;
-    ensure_at_end(value, tokens, next, ctx)
+    // Parse a root block array an item at a time, and any other value whole
+    let (value, next) = if t.typ == TokenType::Start && t.text == "- " {
+        parse_block_array(tokens, i, ctx, true)?
+    } else {
+        let (value, next) = parse_value(tokens, i, ctx)?;
+        ctx.observe_value("", &value)?;
+        (value, next)
+    };
+    let value = ensure_at_end(value, tokens, next, ctx)?;
+    check_limits(&value, ctx)?;
+    Ok(value)
+}
+
+/// Check a parsed value against the depth and value count limits of `ctx`.
+pub(crate) fn check_limits(value: &Value, ctx: &ParseContext) -> Result<()> {
+    if let Some(max) = ctx.max_depth {
+        if depth(value) > max {
+            return Err(ParseError::TooDeep(max, ctx.file_suffix()));
+        }
+    }
+    if let Some(max) = ctx.max_nodes {
+        if count_values(value) > max {
+            return Err(ParseError::TooManyNodes(max, ctx.file_suffix()));
+        }
+    }
+    Ok(())
 }
 
 /// Verify no content remains after parsing.
@@ -170,9 +204,17 @@ fn parse_text_value(tokens: &[Token], i: usize, ctx: &ParseContext) -> Result<(V
     }
 
     // Try numbers
-    if let Some(num) = parse_number(s) {
+    if let Some(num) = hex_float(s, ctx) {
         return Ok((num, i + 1));
     }
+    if let Some(num) = parse_number(s) {
+        return Ok((exact(num, s, ctx), i + 1));
+    }
+
+    // Try quantities
+    if let Some(quantity) = parse_quantity(s, ctx, t.line_num, t.col)? {
+        return Ok((quantity, i + 1));
+    }
 
     // Try block string
     if is_block_string_start(s) {
@@ -180,8 +222,8 @@ fn parse_text_value(tokens: &[Token], i: usize, ctx: &ParseContext) -> Result<(V
         return parse_block_string(tokens, i, first_line);
     }
 
-    // Try quoted string
-    if is_quoted_string(s) {
+    // Try quoted string, unless it is the key of a key:value pair
+    if is_quoted_string(s) && find_colon_outside_quotes(s).is_none() {
         let str_val = parse_quoted_string(s, ctx, t.line_num, t.col)?;
         return Ok((Value::String(str_val), i + 1));
     }
@@ -260,7 +302,7 @@ fn strip_inline_comment(s: &str) -> &str {
 // ============================================================================
 
 /// Check if s is a YAY keyword and return its value.
-fn parse_keyword(s: &str) -> Option<Value> {
+pub(crate) fn parse_keyword(s: &str) -> Option<Value> {
     match s {
         "null" => Some(Value::Null),
         "true" => Some(Value::Bool(true)),
@@ -278,7 +320,7 @@ fn parse_keyword(s: &str) -> Option<Value> {
 
 /// Attempt to parse s as a number.
 /// Returns None if the string is not a valid number or uses uppercase E.
-fn parse_number(s: &str) -> Option<Value> {
+pub(crate) fn parse_number(s: &str) -> Option<Value> {
     // Reject uppercase E in exponent
     if s.contains('E') {
         return None;
@@ -304,6 +346,73 @@ fn parse_number(s: &str) -> Option<Value> {
     None
 }
 
+/// With [`ParseOptions::hex_floats`](crate::ParseOptions) on, read a
+/// hexadecimal float like `0x1.8p3`.
+fn hex_float(s: &str, ctx: &ParseContext) -> Option<Value> {
+    if !ctx.hex_floats {
+        return None;
+    }
+    let f = parse_hex_float(s)?;
+    ctx.relaxed(|r| r.hex_floats = true);
+    Some(Value::Float(f))
+}
+
+/// With [`ParseOptions::decimals`](crate::ParseOptions) on, read a float
+/// written in decimal as an exact [`Decimal`] instead.
+fn exact(value: Value, text: &str, ctx: &ParseContext) -> Value {
+    match value {
+        Value::Float(_) if ctx.decimals => match Decimal::parse(text) {
+            Some(decimal) => {
+                ctx.relaxed(|r| r.decimals = true);
+                Value::Decimal(decimal)
+            }
+            None => value,
+        },
+        _ => value,
+    }
+}
+
+/// Attempt to parse s as a unit-suffixed quantity such as `10s`, when
+/// quantities are enabled.
+fn parse_quantity(
+    s: &str,
+    ctx: &ParseContext,
+    line_num: usize,
+    col: usize,
+) -> Result<Option<Value>> {
+    if !ctx.quantities.enabled() {
+        return Ok(None);
+    }
+    let Some((number, unit)) = split_quantity(s) else {
+        return Ok(None);
+    };
+    let Some(magnitude) = parse_number(number) else {
+        return Ok(None);
+    };
+    let magnitude = exact(magnitude, number, ctx);
+    quantity_value(magnitude, unit, ctx, line_num, col + number.len()).map(Some)
+}
+
+/// Build a quantity, checking its unit against the allowed units.
+fn quantity_value(
+    magnitude: Value,
+    unit: &str,
+    ctx: &ParseContext,
+    line_num: usize,
+    col: usize,
+) -> Result<Value> {
+    if !is_unit(unit) || !ctx.quantities.allows(unit) {
+        return Err(ParseError::UnknownUnit(unit.to_string(), String::new())
+            .with_location(ctx, line_num, col));
+    }
+    ctx.relaxed(|r| r.quantities = true);
+    Ok(Quantity {
+        magnitude,
+        unit: unit.to_string(),
+    }
+    .into())
+}
+
 /// Validate spaces in a potential number string.
 /// Spaces are only allowed between two digits.
 /// Returns the column of an invalid space if found.
@@ -468,7 +577,7 @@ fn parse_single_quoted_content(s: &str) -> String {
 }
 
 /// Parse a JSON-style double-quoted string.
-fn parse_double_quoted_string(
+pub(crate) fn parse_double_quoted_string(
     s: &str,
     ctx: &ParseContext,
     line_num: usize,
@@ -777,6 +886,7 @@ fn validate_inline_syntax(
     let chars: Vec<char> = s.chars().collect();
     let mut in_single = false;
     let mut in_double = false;
+    let mut in_angle = false;
     let mut escape = false;
     let mut depth = 0;
 
@@ -820,6 +930,39 @@ fn validate_inline_syntax(
             in_double = true;
             continue;
         }
+        // Byte arrays take spaces between their hex pairs.
+        if ch == '<' {
+            in_angle = true;
+            continue;
+        }
+        if ch == '>' {
+            in_angle = false;
+            continue;
+        }
+        if in_angle {
+            continue;
+        }
+        if ch == ' ' && i > 0 && chars[i - 1].is_ascii_digit() {
+            if chars.get(i + 1).is_some_and(|c| c.is_ascii_digit()) {
+                return Err(
+                    ParseError::UnexpectedSpaceInNumber(String::new()).with_location(
+                        ctx,
+                        line_num,
+                        col + i,
+                    ),
+                );
+            }
+            continue;
+        }
+        if ch == ':' && chars.get(i + 1) != Some(&' ') {
+            return Err(
+                ParseError::ExpectedSpaceAfter(":".to_string(), String::new()).with_location(
+                    ctx,
+                    line_num,
+                    col + i,
+                ),
+            );
+        }
         if ch == open_char {
             depth += 1;
             if i + 1 < chars.len() && chars[i + 1] == ' ' {
@@ -934,6 +1077,7 @@ fn parse_inline_array(
     line_num: usize,
     col: usize,
 ) -> Result<Vec<Value>> {
+    let _nesting = ctx.enter(line_num, col)?;
     let s = s.trim();
     if !s.starts_with('[') || !s.ends_with(']') {
         return Err(ParseError::UnmatchedBracket(String::new()).with_location(ctx, line_num, col));
@@ -954,7 +1098,9 @@ fn parse_inline_array(
     while !remaining.is_empty() {
         remaining = remaining.trim_start();
 
+        ctx.check_cancelled(line_num)?;
         let (value, consumed) = parse_inline_value(remaining, ctx, line_num, col)?;
+        ctx.count_value(line_num, col)?;
         result.push(value);
         remaining = &remaining[consumed..];
         remaining = remaining.trim_start();
@@ -986,12 +1132,8 @@ fn parse_inline_object_value(
 }
 
 /// Parse an inline object in brace notation.
-fn parse_inline_object(
-    s: &str,
-    ctx: &ParseContext,
-    line_num: usize,
-    col: usize,
-) -> Result<HashMap<String, Value>> {
+fn parse_inline_object(s: &str, ctx: &ParseContext, line_num: usize, col: usize) -> Result<Map> {
+    let _nesting = ctx.enter(line_num, col)?;
     let s = s.trim();
     if !s.starts_with('{') || !s.ends_with('}') {
         return Err(ParseError::UnmatchedBrace(String::new()).with_location(ctx, line_num, col));
@@ -1003,14 +1145,15 @@ fn parse_inline_object(
     let inner = s[1..s.len() - 1].trim();
 
     if inner.is_empty() {
-        return Ok(HashMap::new());
+        return Ok(Map::default());
     }
 
-    let mut result = HashMap::new();
+    let mut result = Members::default();
     let mut remaining = inner;
 
     while !remaining.is_empty() {
         remaining = remaining.trim_start();
+        let key_col = col + 1 + inner.len() - remaining.len();
 
         // Parse key
         let (key, key_len) = parse_inline_key(remaining, ctx, line_num, col)?;
@@ -1026,7 +1169,7 @@ fn parse_inline_object(
 
         // Parse value
         let (value, consumed) = parse_inline_value(remaining, ctx, line_num, col)?;
-        result.insert(key, value);
+        result.insert(key, value, line_num, key_col, ctx)?;
         remaining = &remaining[consumed..];
         remaining = remaining.trim_start();
 
@@ -1036,7 +1179,7 @@ fn parse_inline_object(
         }
     }
 
-    Ok(result)
+    Ok(result.into_map())
 }
 
 /// Parse an object key (unquoted identifier or quoted string).
@@ -1057,9 +1200,8 @@ fn parse_inline_key(
 
     // Unquoted key: alphanumeric characters, underscores, and hyphens
     let i = s
-        .chars()
-        .take_while(|c| c.is_alphanumeric() || *c == '_' || *c == '-')
-        .count();
+        .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-'))
+        .unwrap_or(s.len());
     if i == 0 {
         return Err(ParseError::InvalidKey(String::new()).with_location(ctx, line_num, col));
     }
@@ -1089,6 +1231,16 @@ fn parse_inline_value(
         return Ok((Value::Object(obj), end + 1));
     }
 
+    #[cfg(feature = "std")]
+    if ctx.bytes_root.is_some() && s.starts_with("<@ \"") {
+        let (path, consumed) = parse_inline_string(&s[3..], ctx, line_num, col + 3)?;
+        if s[3 + consumed..].starts_with('>') {
+            let bytes = include_bytes(&path, ctx, line_num, col)?;
+            return Ok((Value::Bytes(bytes), 3 + consumed + 1));
+        }
+        return Err(ParseError::UnmatchedAngle(String::new()).with_location(ctx, line_num, col));
+    }
+
     if s.starts_with('<') {
         let end = s.find('>').ok_or_else(|| {
             ParseError::UnmatchedAngle(String::new()).with_location(ctx, line_num, col)
@@ -1131,11 +1283,41 @@ fn parse_inline_value(
         return Ok((Value::Float(f64::NEG_INFINITY), 9));
     }
 
-    parse_inline_number(s)
+    if ctx.hex_floats && s.strip_prefix('-').unwrap_or(s).starts_with("0x") {
+        ctx.relaxed(|r| r.hex_floats = true);
+        return parse_inline_hex_float(s);
+    }
+
+    let (num, consumed) = parse_inline_number(s)?;
+    let num = exact(num, &s[..consumed], ctx);
+    if ctx.quantities.enabled() {
+        let unit_len = s[consumed..]
+            .find(|c: char| !(c.is_ascii_alphabetic() || c == '/'))
+            .unwrap_or(s.len() - consumed);
+        if unit_len > 0 {
+            let unit = &s[consumed..consumed + unit_len];
+            let quantity = quantity_value(num, unit, ctx, line_num, col + consumed)?;
+            return Ok((quantity, consumed + unit_len));
+        }
+    }
+    Ok((num, consumed))
 }
 
 /// Parse hex content from inside angle brackets.
 fn parse_inline_byte_array(s: &str) -> Result<Vec<u8>> {
+    if s.starts_with(' ') {
+        return Err(ParseError::UnexpectedSpaceAfter(
+            "<".to_string(),
+            String::new(),
+        ));
+    }
+    if s.ends_with(' ') {
+        return Err(ParseError::UnexpectedSpaceBefore(
+            ">".to_string(),
+            String::new(),
+        ));
+    }
+
     // Check for uppercase hex digits before filtering whitespace
     if s.chars()
         .any(|c| c.is_ascii_uppercase() && c.is_ascii_hexdigit())
@@ -1163,7 +1345,7 @@ fn find_matching_bracket(s: &str) -> Option<usize> {
     let mut string_char = '\0';
     let mut escape = false;
 
-    for (i, c) in s.chars().enumerate() {
+    for (i, c) in s.char_indices() {
         if escape {
             escape = false;
             continue;
@@ -1204,7 +1386,7 @@ fn find_matching_brace(s: &str) -> Option<usize> {
     let mut string_char = '\0';
     let mut escape = false;
 
-    for (i, c) in s.chars().enumerate() {
+    for (i, c) in s.char_indices() {
         if escape {
             escape = false;
             continue;
@@ -1247,7 +1429,7 @@ fn parse_inline_single_quoted_string(s: &str) -> Result<(String, usize)> {
     let mut out = String::new();
     let mut escape = false;
 
-    for (i, c) in s.chars().enumerate().skip(1) {
+    for (i, c) in s.char_indices().skip(1) {
         if escape {
             match c {
                 '\'' | '\\' => out.push(c),
@@ -1384,7 +1566,8 @@ fn parse_inline_string(
         }
 
         if c == '"' {
-            return Ok((out, i + 1));
+            let consumed = chars[..=i].iter().map(|c| c.len_utf8()).sum();
+            return Ok((out, consumed));
         }
 
         out.push(c);
@@ -1457,10 +1640,54 @@ fn parse_inline_number(s: &str) -> Result<(Value, usize)> {
     Ok((Value::Float(f), i))
 }
 
+/// Parse a hexadecimal float in inline notation.
+fn parse_inline_hex_float(s: &str) -> Result<(Value, usize)> {
+    let mut end = 0;
+    let mut prev = '\0';
+    for c in s.chars() {
+        let sign_ok = (c == '-' && end == 0) || ((c == '+' || c == '-') && prev == 'p');
+        if !(c.is_ascii_alphanumeric() || c == '.' || sign_ok) {
+            break;
+        }
+        prev = c;
+        end += c.len_utf8();
+    }
+    let f = parse_hex_float(&s[..end]).ok_or(ParseError::InvalidNumber(String::new()))?;
+    Ok((Value::Float(f), end))
+}
+
 // ============================================================================
 // Byte Array Parsing
 // ============================================================================
 
+/// Read the bytes of `path`, relative to the context's bytes root, for a
+/// `<@ "file">` literal. The path must stay inside the root, even through
+/// `..` components and symbolic links.
+#[cfg(feature = "std")]
+fn include_bytes(path: &str, ctx: &ParseContext, line_num: usize, col: usize) -> Result<Vec<u8>> {
+    let fail = |reason: String| {
+        ParseError::IncludeBytes(path.to_string(), reason, String::new())
+            .with_location(ctx, line_num, col)
+    };
+    let Some(root) = &ctx.bytes_root else {
+        return Err(fail("file bytes are not enabled".to_string()));
+    };
+    if Path::new(path).is_absolute() {
+        return Err(fail("path must be relative".to_string()));
+    }
+    let root = root.canonicalize().map_err(|e| fail(e.to_string()))?;
+    let full = root
+        .join(path)
+        .canonicalize()
+        .map_err(|e| fail(e.to_string()))?;
+    if !full.starts_with(&root) {
+        return Err(fail("path is outside the bytes root".to_string()));
+    }
+    let bytes = fs::read(&full).map_err(|e| fail(e.to_string()))?;
+    ctx.relaxed(|r| r.file_bytes = true);
+    Ok(bytes)
+}
+
 /// Parse an inline byte array: <hexdigits>
 fn parse_angle_bytes(s: &str, ctx: &ParseContext, line_num: usize, col: usize) -> Result<Vec<u8>> {
     // Check for unclosed angle bracket
@@ -1472,7 +1699,25 @@ fn parse_angle_bytes(s: &str, ctx: &ParseContext, line_num: usize, col: usize) -
         return Ok(Vec::new());
     }
 
-    // Check for space before closing >
+    // File bytes: <@ "blob.bin">
+    #[cfg(feature = "std")]
+    if ctx.bytes_root.is_some() {
+        if let Some(quoted) = s.strip_prefix("<@ ").and_then(|r| r.strip_suffix('>')) {
+            let path = parse_double_quoted_string(quoted, ctx, line_num, col + 3)?;
+            return include_bytes(&path, ctx, line_num, col);
+        }
+    }
+
+    // Check for space after opening < and before closing >
+    if s.starts_with("< ") {
+        return Err(
+            ParseError::UnexpectedSpaceAfter("<".to_string(), String::new()).with_location(
+                ctx,
+                line_num,
+                col + 1,
+            ),
+        );
+    }
     let chars: Vec<char> = s.chars().collect();
     if chars.len() >= 2 && chars[chars.len() - 2] == ' ' && chars[chars.len() - 1] == '>' {
         return Err(
@@ -1703,6 +1948,13 @@ fn parse_block_string_from_property(
     Ok((Value::String(result), i))
 }
 
+/// Whether a line is a quoted string on its own, a part of a concatenated
+/// string, and not a property with a quoted key.
+fn is_string_line(s: &str) -> bool {
+    ((s.starts_with('"') && s.ends_with('"')) || (s.starts_with('\'') && s.ends_with('\'')))
+        && find_colon_outside_quotes(s).is_none()
+}
+
 /// Parse concatenated quoted strings (multiple quoted strings on consecutive lines).
 /// Each line must be a complete quoted string that gets concatenated together.
 fn parse_concatenated_strings(
@@ -1771,11 +2023,20 @@ fn parse_concatenated_strings(
 // ============================================================================
 
 /// Parse a multiline array (list items with - prefix).
-fn parse_multiline_array(
+fn parse_multiline_array(tokens: &[Token], i: usize, ctx: &ParseContext) -> Result<(Value, usize)> {
+    parse_block_array(tokens, i, ctx, false)
+}
+
+/// Parse a block array, reporting each item to the observer if it is the
+/// root.
+fn parse_block_array(
     tokens: &[Token],
     mut i: usize,
     ctx: &ParseContext,
+    root: bool,
 ) -> Result<(Value, usize)> {
+    let (line, col) = tokens.get(i).map_or((0, 0), |t| (t.line_num, t.col));
+    let _nesting = ctx.enter(line, col)?;
     let mut arr = Vec::new();
 
     // Track the indent of the first list item - all items must be at this indent
@@ -1800,7 +2061,12 @@ fn parse_multiline_array(
         }
 
         // Parse the array item
+        ctx.check_cancelled(tokens[i].line_num)?;
         let (value, next_i) = parse_array_item(tokens, i, list_indent, ctx)?;
+        if root {
+            ctx.observe_value(&format!("[{}]", arr.len()), &value)?;
+        }
+        ctx.count_value(tokens[i].line_num, tokens[i].col)?;
         arr.push(value);
         i = next_i;
 
@@ -1857,23 +2123,33 @@ fn is_inline_list_item(text: &str) -> bool {
     text.starts_with("- ") || text == "-"
 }
 
-/// Recursively parse nested inline bullets like "- - - 'hello'".
-/// Returns the parsed value (could be a nested array or a scalar).
+/// Parse the value after a bullet on the line of token `i`, where the
+/// bullet's list is indented past `list_indent`. The value may be more
+/// bullets, like "- - 'hello'", each of whose lists takes the items lined
+/// up under its bullet on the lines that follow.
 fn parse_nested_inline_bullet(
+    tokens: &[Token],
+    i: usize,
     text: &str,
-    ctx: &ParseContext,
-    line_num: usize,
+    list_indent: usize,
     col: usize,
-) -> Result<Value> {
+    ctx: &ParseContext,
+) -> Result<(Value, usize)> {
     // Check if the text itself is another inline bullet
-    if is_inline_list_item(text) {
-        let inner_text = text.strip_prefix("- ").unwrap_or(text).trim();
-        let inner_value = parse_nested_inline_bullet(inner_text, ctx, line_num, col + 2)?;
-        return Ok(Value::Array(vec![inner_value]));
+    if let Some(inner_text) = text.strip_prefix("- ") {
+        let _nesting = ctx.enter(tokens[i].line_num, col)?;
+        let inner_text = inner_text.trim();
+        let (first, next) =
+            parse_nested_inline_bullet(tokens, i, inner_text, list_indent + 2, col + 2, ctx)?;
+        ctx.count_value(tokens[i].line_num, col + 2)?;
+        let mut group = vec![first];
+        let next = collect_nested_items(tokens, next, list_indent, &mut group, ctx)?;
+        return Ok((Value::Array(group), next));
     }
     // Otherwise, parse as a scalar (strip inline comments first)
     let text_no_comment = strip_inline_comment(text);
-    parse_scalar(text_no_comment, ctx, line_num, col)
+    let scalar = parse_scalar(text_no_comment, ctx, tokens[i].line_num, col)?;
+    Ok((scalar, i + 1))
 }
 
 /// Parse inline nested list items like "- a" as text.
@@ -1883,6 +2159,7 @@ fn parse_inline_nested_list(
     list_indent: usize,
     ctx: &ParseContext,
 ) -> Result<(Value, usize)> {
+    let _nesting = ctx.enter(tokens[i].line_num, tokens[i].col)?;
     let mut group = Vec::new();
 
     // Collect inline items
@@ -1905,30 +2182,46 @@ fn parse_inline_nested_list(
 
         let val_str = t.text.strip_prefix("- ").unwrap_or(&t.text).trim();
         // Use parse_nested_inline_bullet to handle nested "- - value" patterns
-        let value = parse_nested_inline_bullet(val_str, ctx, t.line_num, t.col + 2)?;
+        let (value, next) =
+            parse_nested_inline_bullet(tokens, i, val_str, list_indent + 2, t.col + 2, ctx)?;
+        ctx.count_value(t.line_num, t.col + 2)?;
         group.push(value);
-        i += 1;
+        i = next;
     }
 
-    // Continue with nested start tokens at deeper indent
+    let i = collect_nested_items(tokens, i, list_indent, &mut group, ctx)?;
+    Ok((Value::Array(group), i))
+}
+
+/// Collect the items of a nested list after its first, the start tokens
+/// indented past `list_indent`.
+fn collect_nested_items(
+    tokens: &[Token],
+    mut i: usize,
+    list_indent: usize,
+    group: &mut Vec<Value>,
+    ctx: &ParseContext,
+) -> Result<usize> {
     while i < tokens.len()
         && tokens[i].typ == TokenType::Start
         && tokens[i].text == "- "
         && tokens[i].indent > list_indent
     {
+        let item_indent = tokens[i].indent;
         i += 1;
         i = skip_breaks(tokens, i);
         if i >= tokens.len() {
             break;
         }
 
-        let (sub_val, next_i) = parse_value(tokens, i, ctx)?;
+        // The item may itself be a nested list, as the first one may.
+        let (sub_val, next_i) = parse_array_item(tokens, i, item_indent, ctx)?;
+        ctx.count_value(tokens[i].line_num, tokens[i].col)?;
         group.push(sub_val);
         i = next_i;
         i = skip_stops(tokens, i);
     }
-
-    Ok((Value::Array(group), i))
+    Ok(i)
 }
 
 /// Parse a regular array item value.
@@ -1941,8 +2234,19 @@ fn parse_array_item_value(
     let (mut value, mut j) = parse_value(tokens, i, ctx)?;
 
     // If value is an object, check for additional properties at the same level
-    if let Value::Object(ref mut obj) = value {
-        j = merge_additional_object_properties(tokens, j, list_indent, obj, ctx)?;
+    if let Value::Object(obj) = value {
+        // The first key follows the item's "- " on the same line.
+        let t = &tokens[i];
+        let col = match i.checked_sub(1).map(|p| &tokens[p]) {
+            Some(start) if start.typ == TokenType::Start => t.col + start.text.len(),
+            _ => t.col,
+        };
+        let mut members = Members::default();
+        for (k, v) in obj {
+            members.insert(k, v, t.line_num, col, ctx)?;
+        }
+        j = merge_additional_object_properties(tokens, j, list_indent, &mut members, ctx)?;
+        value = Value::Object(members.into_map());
     }
 
     // Check for nested list items after this value
@@ -1965,7 +2269,7 @@ fn merge_additional_object_properties(
     tokens: &[Token],
     mut j: usize,
     list_indent: usize,
-    obj: &mut HashMap<String, Value>,
+    obj: &mut Members,
     ctx: &ParseContext,
 ) -> Result<usize> {
     loop {
@@ -1982,7 +2286,7 @@ fn merge_additional_object_properties(
             let (prop_val, next_j) = parse_value(tokens, j, ctx)?;
             if let Value::Object(prop_obj) = prop_val {
                 for (k, v) in prop_obj {
-                    obj.insert(k, v);
+                    obj.insert(k, v, t.line_num, t.col, ctx)?;
                 }
             }
             j = next_j;
@@ -2001,6 +2305,8 @@ fn collect_nested_list_group(
     first_value: Value,
     ctx: &ParseContext,
 ) -> Result<(Value, usize)> {
+    let _nesting = ctx.enter(tokens[i].line_num, tokens[i].col)?;
+    ctx.count_value(tokens[i].line_num, tokens[i].col)?;
     let mut group = vec![first_value];
 
     while i < tokens.len()
@@ -2015,6 +2321,7 @@ fn collect_nested_list_group(
         }
 
         let (sub_val, next_i) = parse_value(tokens, i, ctx)?;
+        ctx.count_value(tokens[i].line_num, tokens[i].col)?;
         group.push(sub_val);
         i = next_i;
         i = skip_stops(tokens, i);
@@ -2027,6 +2334,83 @@ fn collect_nested_list_group(
 // Object Parsing
 // ============================================================================
 
+/// The members of an object being parsed, with the line and column of each
+/// key, so that a key that appears again can be handled as the context's
+/// [`DuplicateKeys`] policy asks.
+#[derive(Default)]
+pub(crate) struct Members {
+    map: Map,
+    positions: Vec<(usize, usize)>,
+}
+
+impl Members {
+    /// Add a member whose key is at `line` and `col`.
+    pub(crate) fn insert(
+        &mut self,
+        key: String,
+        value: Value,
+        line: usize,
+        col: usize,
+        ctx: &ParseContext,
+    ) -> Result<()> {
+        ctx.count_value(line, col)?;
+        self.add(key, value, line, col, ctx)
+    }
+
+    /// Add a member already counted against the parse's limits.
+    fn add(
+        &mut self,
+        key: String,
+        value: Value,
+        line: usize,
+        col: usize,
+        ctx: &ParseContext,
+    ) -> Result<()> {
+        ctx.check_cancelled(line)?;
+        let Some(index) = self.map.get_index_of(&key) else {
+            self.map.insert(key, value);
+            self.positions.push((line, col));
+            return Ok(());
+        };
+        let (first_line, first_col) = self.positions[index];
+        match ctx.duplicate_keys {
+            DuplicateKeys::Error => {
+                return Err(ParseError::DuplicateKey(
+                    key,
+                    format!("{}:{}", line + 1, col + 1),
+                    format!("{}:{}", first_line + 1, first_col + 1),
+                    ctx.file_suffix(),
+                ));
+            }
+            DuplicateKeys::FirstWins => {}
+            DuplicateKeys::LastWins => self.map[index] = value,
+            DuplicateKeys::Warn => {
+                ctx.duplicates.borrow_mut().push(DuplicateKey {
+                    key,
+                    line,
+                    column: col,
+                    first_line,
+                    first_column: first_col,
+                });
+                self.map[index] = value;
+            }
+        }
+        Ok(())
+    }
+
+    /// Add the members of `more`, parsed from a later part of the object.
+    pub(crate) fn extend(&mut self, more: Members, ctx: &ParseContext) -> Result<()> {
+        for ((key, value), (line, col)) in more.map.into_iter().zip(more.positions) {
+            self.add(key, value, line, col, ctx)?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn into_map(self) -> Map {
+        self.map
+    }
+}
+
 /// Parse a key:value pair from a text token.
 fn parse_key_value_pair(
     tokens: &[Token],
@@ -2036,9 +2420,10 @@ fn parse_key_value_pair(
 ) -> Result<(Value, usize)> {
     let t = &tokens[i];
     let s = &t.text;
+    let _nesting = ctx.enter(t.line_num, t.col)?;
 
     let key_raw = s[..colon_idx].trim();
-    let key = parse_key_name(key_raw);
+    let key = parse_key_name(key_raw, ctx, t.line_num, t.col)?;
 
     // Calculate the column where the value part starts
     let after_colon = &s[colon_idx + 1..];
@@ -2046,23 +2431,29 @@ fn parse_key_value_pair(
     let value_col = t.col + colon_idx + 1 + leading_spaces;
     let value_part = after_colon.trim();
 
+    // A key on an item's `- ` line is indented past the bullet.
+    let key_indent = match i.checked_sub(1).map(|p| &tokens[p]) {
+        Some(start) if start.typ == TokenType::Start => t.indent + start.text.len(),
+        _ => t.indent,
+    };
+
     // Empty value part means nested content follows
-    if value_part.is_empty() && !key.is_empty() {
+    if value_part.is_empty() && !key_raw.is_empty() {
         return parse_object_or_named_array(tokens, i, &key, ctx);
     }
 
     // Block bytes: "key: >" followed by indented hex lines
-    if value_part == ">" && !key.is_empty() {
-        let (bytes, next) = parse_block_bytes_from_property(tokens, i, t.indent, ctx)?;
-        let mut obj = HashMap::new();
+    if value_part == ">" && !key_raw.is_empty() {
+        let (bytes, next) = parse_block_bytes_from_property(tokens, i, key_indent, ctx)?;
+        let mut obj = Map::default();
         obj.insert(key, bytes);
         return Ok((Value::Object(obj), next));
     }
 
     // Block string: "key: `" followed by indented content
-    if value_part == "`" && !key.is_empty() {
-        let (body, next) = parse_block_string_from_property(tokens, i, t.indent)?;
-        let mut obj = HashMap::new();
+    if value_part == "`" && !key_raw.is_empty() {
+        let (body, next) = parse_block_string_from_property(tokens, i, key_indent)?;
+        let mut obj = Map::default();
         obj.insert(key, body);
         return Ok((Value::Object(obj), next));
     }
@@ -2071,13 +2462,13 @@ fn parse_key_value_pair(
 
     // Inline value (strip inline comments first)
     let value_part_no_comment = strip_inline_comment(value_part);
-    if !key.is_empty() {
+    if !key_raw.is_empty() {
         let value = if !value_part_no_comment.is_empty() {
             parse_scalar(value_part_no_comment, ctx, t.line_num, value_col)?
         } else {
             Value::Null
         };
-        let mut obj = HashMap::new();
+        let mut obj = Map::default();
         obj.insert(key, value);
         return Ok((Value::Object(obj), i + 1));
     }
@@ -2085,13 +2476,25 @@ fn parse_key_value_pair(
     Ok((Value::Null, i + 1))
 }
 
+/// Whether a line at the root begins a root object: it has a colon outside
+/// quotes, and is not an inline object or array, whose colons are their
+/// own.
+pub(crate) fn starts_root_object(line: &str) -> bool {
+    !line.starts_with('{') && !line.starts_with('[') && find_colon_outside_quotes(line).is_some()
+}
+
 /// Find the first colon not inside quotes.
 fn find_colon_outside_quotes(s: &str) -> Option<usize> {
     let mut in_double = false;
     let mut in_single = false;
+    let mut escaped = false;
 
-    for (i, c) in s.chars().enumerate() {
-        if c == '"' && !in_single {
+    for (i, c) in s.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' && in_double {
+            escaped = true;
+        } else if c == '"' && !in_single {
             in_double = !in_double;
         } else if c == '\'' && !in_double {
             in_single = !in_single;
@@ -2102,22 +2505,33 @@ fn find_colon_outside_quotes(s: &str) -> Option<usize> {
     None
 }
 
-/// Extract the key name, handling quoted keys.
-fn parse_key_name(s: &str) -> String {
+/// Extract the key name, handling quoted keys. A double-quoted key has the
+/// escapes of a double-quoted string.
+fn parse_key_name(s: &str, ctx: &ParseContext, line_num: usize, col: usize) -> Result<String> {
     let s = s.trim();
 
     // Double-quoted key
     if s.starts_with('"') && s.ends_with('"') && s.len() >= 2 {
-        let inner = &s[1..s.len() - 1];
-        return inner.replace("\\\"", "\"").replace("\\\\", "\\");
+        return parse_double_quoted_string(s, ctx, line_num, col);
     }
 
     // Single-quoted key
     if s.starts_with('\'') && s.ends_with('\'') && s.len() >= 2 {
-        return s[1..s.len() - 1].to_string();
+        return Ok(s[1..s.len() - 1].to_string());
     }
 
-    s.to_string()
+    // Bare key: letters, digits, underscores, and hyphens
+    if let Some(j) = s
+        .chars()
+        .position(|c| !(c.is_alphanumeric() || c == '_' || c == '-'))
+    {
+        return Err(ParseError::InvalidKeyChar(String::new()).with_location(
+            ctx,
+            line_num,
+            col + j,
+        ));
+    }
+    Ok(s.to_string())
 }
 
 /// Parse content after "key:" (no inline value).
@@ -2128,6 +2542,12 @@ fn parse_object_or_named_array(
     ctx: &ParseContext,
 ) -> Result<(Value, usize)> {
     i += 1;
+    let after = i;
+    let empty = || {
+        let mut obj = Map::default();
+        obj.insert(key.to_string(), empty_value(ctx, 0, key.len() + 1)?);
+        Ok((Value::Object(obj), after))
+    };
 
     // Skip to next content
     i = skip_breaks_and_stops(tokens, i);
@@ -2139,14 +2559,7 @@ fn parse_object_or_named_array(
     };
 
     if i >= tokens.len() {
-        // Empty property with no nested content is invalid
-        return Err(
-            ParseError::ExpectedValueAfterProperty(String::new()).with_location(
-                ctx,
-                0,
-                key.len() + 1,
-            ),
-        );
+        return empty();
     }
 
     let first = &tokens[i];
@@ -2154,7 +2567,7 @@ fn parse_object_or_named_array(
     // Named array
     if first.typ == TokenType::Start && first.text == "- " {
         let (arr, next) = parse_multiline_array(tokens, i, ctx)?;
-        let mut obj = HashMap::new();
+        let mut obj = Map::default();
         obj.insert(key.to_string(), arr);
         return Ok((Value::Object(obj), next));
     }
@@ -2164,7 +2577,7 @@ fn parse_object_or_named_array(
     // Block string
     if first.typ == TokenType::Text && first.text.trim() == "`" {
         let (body, next) = parse_block_string(tokens, i, "")?;
-        let mut obj = HashMap::new();
+        let mut obj = Map::default();
         obj.insert(key.to_string(), body);
         return Ok((Value::Object(obj), next));
     }
@@ -2172,19 +2585,11 @@ fn parse_object_or_named_array(
     // Nested object
     let (nested_obj, next) = parse_nested_object_content(tokens, i, base_indent, ctx)?;
 
-    let mut obj = HashMap::new();
-    if !nested_obj.is_empty() {
-        obj.insert(key.to_string(), Value::Object(nested_obj));
-    } else {
-        // Empty property with no nested content is invalid
-        return Err(
-            ParseError::ExpectedValueAfterProperty(String::new()).with_location(
-                ctx,
-                0,
-                key.len() + 1,
-            ),
-        );
+    if nested_obj.is_empty() {
+        return empty();
     }
+    let mut obj = Map::default();
+    obj.insert(key.to_string(), Value::Object(nested_obj));
     Ok((Value::Object(obj), next))
 }
 
@@ -2194,8 +2599,10 @@ fn parse_nested_object_content(
     mut i: usize,
     base_indent: usize,
     ctx: &ParseContext,
-) -> Result<(HashMap<String, Value>, usize)> {
-    let mut obj = HashMap::new();
+) -> Result<(Map, usize)> {
+    let (line, col) = tokens.get(i).map_or((0, 0), |t| (t.line_num, t.col));
+    let _nesting = ctx.enter(line, col)?;
+    let mut obj = Members::default();
 
     while i < tokens.len() {
         let t = &tokens[i];
@@ -2223,23 +2630,26 @@ fn parse_nested_object_content(
             }
 
             let k_raw = t.text[..colon_idx].trim();
-            let k = parse_key_name(k_raw);
+            let k = parse_key_name(k_raw, ctx, t.line_num, t.col)?;
             let v_part = t.text[colon_idx + 1..].trim();
 
-            if k.is_empty() {
+            if k_raw.is_empty() {
                 i += 1;
                 continue;
             }
 
             let (value, next_i) = parse_object_property_value(tokens, i, t, v_part, ctx)?;
-            obj.insert(k, value);
+            obj.insert(k, value, t.line_num, t.col, ctx)?;
             i = next_i;
+        } else if t.typ == TokenType::Start && t.indent < base_indent {
+            // The next item of an enclosing array.
+            break;
         } else {
             i += 1;
         }
     }
 
-    Ok((obj, i))
+    Ok((obj.into_map(), i))
 }
 
 /// Parse the value of an object property.
@@ -2252,7 +2662,7 @@ fn parse_object_property_value(
 ) -> Result<(Value, usize)> {
     // Empty object
     if v_part == "{}" {
-        return Ok((Value::Object(HashMap::new()), i + 1));
+        return Ok((Value::Object(Map::default()), i + 1));
     }
 
     // Block bytes - either just ">" or "> # comment"
@@ -2270,7 +2680,10 @@ fn parse_object_property_value(
     // Inline value (strip inline comments first)
     let v_part_no_comment = strip_inline_comment(v_part);
     if !v_part_no_comment.is_empty() {
-        let scalar = parse_scalar(v_part_no_comment, ctx, t.line_num, t.col)?;
+        let colon_idx = find_colon_outside_quotes(&t.text).unwrap_or(0);
+        let after_colon = &t.text[colon_idx + 1..];
+        let value_col = t.col + t.text.len() - after_colon.trim_start().len();
+        let scalar = parse_scalar(v_part_no_comment, ctx, t.line_num, value_col)?;
         return Ok((scalar, i + 1));
     }
 
@@ -2278,8 +2691,14 @@ fn parse_object_property_value(
     let mut j = i + 1;
     j = skip_breaks_and_stops(tokens, j);
 
+    // A property with no value and nothing nested under it
+    let no_value = || {
+        let colon = find_colon_outside_quotes(&t.text).unwrap_or(0);
+        Ok((empty_value(ctx, t.line_num, t.col + colon + 1)?, i + 1))
+    };
+
     if j >= tokens.len() {
-        return Ok((Value::Null, i + 1));
+        return no_value();
     }
 
     let next_t = &tokens[j];
@@ -2297,14 +2716,12 @@ fn parse_object_property_value(
     }
 
     // Concatenated quoted strings (multiple quoted strings on consecutive lines)
-    if next_t.typ == TokenType::Text && next_t.indent > t.indent {
-        let trimmed = next_t.text.trim();
-        if (trimmed.starts_with('"') && trimmed.ends_with('"'))
-            || (trimmed.starts_with('\'') && trimmed.ends_with('\''))
-        {
-            let (concat_str, next) = parse_concatenated_strings(tokens, j, next_t.indent, ctx)?;
-            return Ok((concat_str, next));
-        }
+    if next_t.typ == TokenType::Text
+        && next_t.indent > t.indent
+        && is_string_line(next_t.text.trim())
+    {
+        let (concat_str, next) = parse_concatenated_strings(tokens, j, next_t.indent, ctx)?;
+        return Ok((concat_str, next));
     }
 
     // Nested object
@@ -2313,7 +2730,7 @@ fn parse_object_property_value(
         return Ok((Value::Object(nested_obj), next));
     }
 
-    Ok((Value::Null, j))
+    no_value()
 }
 
 // ============================================================================
@@ -2321,8 +2738,13 @@ fn parse_object_property_value(
 // ============================================================================
 
 /// Parse an object at the document root level.
-fn parse_root_object(tokens: &[Token], mut i: usize, ctx: &ParseContext) -> Result<(Value, usize)> {
-    let mut obj = HashMap::new();
+pub fn parse_root_object(
+    tokens: &[Token],
+    mut i: usize,
+    ctx: &ParseContext,
+) -> Result<(Members, usize)> {
+    let _nesting = ctx.enter(tokens.get(i).map_or(0, |t| t.line_num), 0)?;
+    let mut obj = Members::default();
 
     while i < tokens.len() {
         let t = &tokens[i];
@@ -2344,78 +2766,74 @@ fn parse_root_object(tokens: &[Token], mut i: usize, ctx: &ParseContext) -> Resu
             );
         }
 
-        let colon_idx = match find_colon_outside_quotes(&t.text) {
-            Some(idx) => idx,
+        let (k, v_part, value_col) = match root_member(t, ctx)? {
+            Some(member) => member,
             None => {
                 i += 1;
                 continue;
             }
         };
 
-        let k_raw = &t.text[..colon_idx];
+        let (value, next_i) = parse_root_object_property(tokens, i, t, v_part, value_col, ctx)?;
+        ctx.observe_value(&k, &value)?;
+        obj.insert(k, value, t.line_num, 0, ctx)?;
+        i = next_i;
+    }
 
-        // Validate: no space before colon (for unquoted keys)
-        if !k_raw.is_empty() && k_raw.ends_with(' ') {
+    Ok((obj, i))
+}
+
+/// Validate a member line of the root object and split it into its key,
+/// the text after the colon, and the column where that text starts, or
+/// `None` if the line has no colon.
+fn root_member<'t>(t: &'t Token, ctx: &ParseContext) -> Result<Option<(String, &'t str, usize)>> {
+    let colon_idx = match find_colon_outside_quotes(&t.text) {
+        Some(idx) => idx,
+        None => return Ok(None),
+    };
+
+    let k_raw = &t.text[..colon_idx];
+
+    // Validate: no space before colon (for unquoted keys)
+    if !k_raw.is_empty() && k_raw.ends_with(' ') {
+        return Err(
+            ParseError::UnexpectedSpaceBefore(":".to_string(), String::new()).with_location(
+                ctx,
+                t.line_num,
+                t.col + colon_idx - 1,
+            ),
+        );
+    }
+
+    let k = parse_key_name(k_raw, ctx, t.line_num, t.col)?;
+    let after_colon = &t.text[colon_idx + 1..];
+
+    // Validate: must have exactly one space after colon (if there's a value)
+    if !after_colon.is_empty() {
+        if !after_colon.starts_with(' ') {
             return Err(
-                ParseError::UnexpectedSpaceBefore(":".to_string(), String::new()).with_location(
+                ParseError::ExpectedSpaceAfter(":".to_string(), String::new()).with_location(
                     ctx,
                     t.line_num,
-                    t.col + colon_idx - 1,
+                    t.col + colon_idx,
                 ),
             );
         }
-
-        // Validate key characters for unquoted keys
-        let k_trimmed = k_raw.trim();
-        if !k_trimmed.is_empty() && !k_trimmed.starts_with('"') && !k_trimmed.starts_with('\'') {
-            // Check for invalid characters in unquoted key
-            // Valid characters: alphanumeric, underscore, hyphen
-            for (j, ch) in k_trimmed.chars().enumerate() {
-                if ch == ' ' {
-                    return Err(ParseError::InvalidKeyChar(String::new()).with_location(
-                        ctx,
-                        t.line_num,
-                        t.col + j,
-                    ));
-                }
-            }
-        }
-
-        let k = parse_key_name(k_trimmed);
-        let after_colon = &t.text[colon_idx + 1..];
-
-        // Validate: must have exactly one space after colon (if there's a value)
-        if !after_colon.is_empty() {
-            if !after_colon.starts_with(' ') {
-                return Err(
-                    ParseError::ExpectedSpaceAfter(":".to_string(), String::new()).with_location(
-                        ctx,
-                        t.line_num,
-                        t.col + colon_idx,
-                    ),
-                );
-            }
-            if after_colon.len() > 1 && after_colon.starts_with("  ") {
-                return Err(
-                    ParseError::UnexpectedSpaceAfter(":".to_string(), String::new()).with_location(
-                        ctx,
-                        t.line_num,
-                        t.col + colon_idx + 2,
-                    ),
-                );
-            }
+        if after_colon.len() > 1 && after_colon.starts_with("  ") {
+            return Err(
+                ParseError::UnexpectedSpaceAfter(":".to_string(), String::new()).with_location(
+                    ctx,
+                    t.line_num,
+                    t.col + colon_idx + 2,
+                ),
+            );
         }
-
-        let leading_spaces = after_colon.len() - after_colon.trim_start().len();
-        let value_col = t.col + colon_idx + 1 + leading_spaces;
-        let v_part = after_colon.trim();
-
-        let (value, next_i) = parse_root_object_property(tokens, i, t, v_part, value_col, ctx)?;
-        obj.insert(k, value);
-        i = next_i;
     }
 
-    Ok((Value::Object(obj), i))
+    let leading_spaces = after_colon.len() - after_colon.trim_start().len();
+    let value_col = t.col + colon_idx + 1 + leading_spaces;
+    let v_part = after_colon.trim();
+    Ok(Some((k, v_part, value_col)))
 }
 
 /// Parse a single property in a root object.
@@ -2448,7 +2866,7 @@ fn parse_root_object_property(
 
     // Empty object
     if v_part == "{}" {
-        return Ok((Value::Object(HashMap::new()), i + 1));
+        return Ok((Value::Object(Map::default()), i + 1));
     }
 
     // Block string
@@ -2543,14 +2961,7 @@ fn parse_root_nested_content(
     j = skip_breaks_and_stops(tokens, j);
 
     if j >= tokens.len() {
-        // Empty property with no nested content is invalid
-        return Err(
-            ParseError::ExpectedValueAfterProperty(String::new()).with_location(
-                ctx,
-                t.line_num,
-                t.col + colon_idx + 1,
-            ),
-        );
+        return Ok((empty_value(ctx, t.line_num, t.col + colon_idx + 1)?, i + 1));
     }
 
     let next_t = &tokens[j];
@@ -2562,14 +2973,9 @@ fn parse_root_nested_content(
     }
 
     // Concatenated quoted strings (multiple quoted strings on consecutive lines)
-    if next_t.typ == TokenType::Text && next_t.indent > 0 {
-        let trimmed = next_t.text.trim();
-        if (trimmed.starts_with('"') && trimmed.ends_with('"'))
-            || (trimmed.starts_with('\'') && trimmed.ends_with('\''))
-        {
-            let (concat_str, next) = parse_concatenated_strings(tokens, j, next_t.indent, ctx)?;
-            return Ok((concat_str, next));
-        }
+    if next_t.typ == TokenType::Text && next_t.indent > 0 && is_string_line(next_t.text.trim()) {
+        let (concat_str, next) = parse_concatenated_strings(tokens, j, next_t.indent, ctx)?;
+        return Ok((concat_str, next));
     }
 
     // Nested object
@@ -2578,14 +2984,20 @@ fn parse_root_nested_content(
         return Ok((Value::Object(nested_obj), next));
     }
 
-    // Empty property with no nested content is invalid
-    Err(
-        ParseError::ExpectedValueAfterProperty(String::new()).with_location(
-            ctx,
-            t.line_num,
-            t.col + colon_idx + 1,
-        ),
-    )
+    Ok((empty_value(ctx, t.line_num, t.col + colon_idx + 1)?, i + 1))
+}
+
+/// The value of a property with nothing after its colon or nested under
+/// it: null with [`ParseOptions::empty_values`](crate::ParseOptions), and
+/// otherwise an error at `line` and `col`.
+fn empty_value(ctx: &ParseContext, line: usize, col: usize) -> Result<Value> {
+    if !ctx.empty_values {
+        return Err(
+            ParseError::ExpectedValueAfterProperty(String::new()).with_location(ctx, line, col)
+        );
+    }
+    ctx.relaxed(|r| r.empty_values = true);
+    Ok(Value::Null)
 }
 
 // ============================================================================
@@ -2617,22 +3029,35 @@ fn parse_scalar(s: &str, ctx: &ParseContext, line_num: usize, col: usize) -> Res
     }
 
     // Numbers
-    if let Some(num) = parse_number(s) {
+    if let Some(num) = hex_float(s, ctx) {
         return Ok(num);
     }
+    if let Some(num) = parse_number(s) {
+        return Ok(exact(num, s, ctx));
+    }
+
+    // Quantities
+    if let Some(quantity) = parse_quantity(s, ctx, line_num, col)? {
+        return Ok(quantity);
+    }
 
     // Double-quoted string
-    if s.starts_with('"') && s.ends_with('"') {
+    if s.starts_with('"') && s.ends_with('"') && s.len() >= 2 {
         return Ok(Value::String(parse_quoted_string(s, ctx, line_num, col)?));
     }
 
     // Single-quoted string
-    if s.starts_with('\'') && s.ends_with('\'') {
+    if s.starts_with('\'') && s.ends_with('\'') && s.len() >= 2 {
         return Ok(Value::String(parse_single_quoted_content(
             &s[1..s.len() - 1],
         )));
     }
 
+    // A lone quote opens a string that never closes.
+    if s == "\"" || s == "'" {
+        return Err(ParseError::UnterminatedString(String::new()).with_location(ctx, line_num, col));
+    }
+
     // Inline array
     if s.starts_with('[') {
         return Ok(Value::Array(parse_inline_array(s, ctx, line_num, col)?));
@@ -2653,8 +3078,322 @@ fn parse_scalar(s: &str, ctx: &ParseContext, line_num: usize, col: usize) -> Res
     Err(ParseError::UnexpectedChar(first_char, String::new()).with_location(ctx, line_num, col))
 }
 
+// ============================================================================
+// Event Parsing
+// ============================================================================
+
+/// A pull parser that reads a YAY document as [`Event`]s, in document order,
+/// instead of building the whole [`Value`].
+///
+/// The root object and the block arrays and nested objects under it are
+/// read a member or an item at a time, so only one member or array item is
+/// held as a [`Value`] at once: an array of records yields each record as
+/// it is read. The source is still scanned into lines up front, so it must
+/// fit in memory, but its tree need not.
+///
+/// The events are those of the value [`parse`](fn@crate::parse) returns,
+/// except that a key written twice in one object appears twice. An error
+/// ends the events; the events before it come from the part of the document
+/// that parsed.
+///
+/// # Example
+///
+/// ```
+/// use libyay::stream::Event;
+/// use libyay::{Parser, Value};
+///
+/// let source = "log:\n  - level: \"warn\"\n    code: 7\n  - level: \"info\"\n    code: 9\n";
+/// let mut codes = Vec::new();
+/// let mut key = String::new();
+/// for event in Parser::new(source) {
+///     match event.unwrap() {
+///         Event::Key(k) => key = k,
+///         Event::Scalar(Value::Integer(code)) if key == "code" => codes.push(code),
+///         _ => {}
+///     }
+/// }
+/// assert_eq!(codes, [7.into(), 9.into()]);
+/// ```
+pub struct Parser {
+    tokens: Vec<Token>,
+    ctx: ParseContext,
+    /// The next token to read.
+    i: usize,
+    /// The objects and arrays open at `i`, innermost last.
+    frames: Vec<Frame>,
+    /// Events read but not yet returned.
+    queue: VecDeque<Event>,
+    /// An error scanning the source, returned as the first event.
+    error: Option<ParseError>,
+    started: bool,
+    done: bool,
+}
+
+/// A block object or array the [`Parser`] is reading a member at a time.
+#[derive(Clone, Copy)]
+enum Frame {
+    /// The root object.
+    Root,
+    /// A nested object whose keys are at this indent.
+    Object(usize),
+    /// A block array whose `- ` markers are at this indent.
+    Array(usize),
+}
+
+impl Parser {
+    /// A parser for the YAY document `input`.
+    pub fn new(input: &str) -> Parser {
+        Parser::with_context(input, ParseContext::new(None))
+    }
+
+    /// A parser for the YAY document `input`, parsed with `options`.
+    pub fn with_options(input: &str, options: &ParseOptions) -> Parser {
+        Parser::with_context(input, ParseContext::from_options(options))
+    }
+
+    fn with_context(input: &str, ctx: ParseContext) -> Parser {
+        let (tokens, error) = match crate::scanner::scan(input, &ctx) {
+            Ok(scanned) => (crate::lexer::outline_lex(&scanned.lines), None),
+            Err(e) => (Vec::new(), Some(e)),
+        };
+        Parser {
+            tokens,
+            ctx,
+            i: 0,
+            frames: Vec::new(),
+            queue: VecDeque::new(),
+            error,
+            started: false,
+            done: false,
+        }
+    }
+
+    /// Read the next member, item, or end of the innermost open object or
+    /// array, queueing its events.
+    fn step(&mut self) -> Result<()> {
+        if let Some(e) = self.error.take() {
+            return Err(e);
+        }
+        match self.frames.last().copied() {
+            None if !self.started => self.start(),
+            None => {
+                ensure_at_end(Value::Null, &self.tokens, self.i, &self.ctx)?;
+                self.done = true;
+                Ok(())
+            }
+            Some(Frame::Root) => self.root_member(),
+            Some(Frame::Object(indent)) => self.object_member(indent),
+            Some(Frame::Array(indent)) => self.array_item(indent),
+        }
+    }
+
+    /// Open the root object or array, or read any other root value whole,
+    /// deciding as [`parse_root`] does.
+    fn start(&mut self) -> Result<()> {
+        self.started = true;
+        let i = skip_breaks_and_stops(&self.tokens, 0);
+        match self.tokens.get(i) {
+            Some(t) if t.typ == TokenType::Text && t.indent == 0 && starts_root_object(&t.text) => {
+                self.open(Frame::Root, i);
+            }
+            Some(t) if t.typ == TokenType::Start && t.text == "- " => {
+                self.open(Frame::Array(t.indent), i);
+            }
+            _ => {
+                let value = parse_root(&self.tokens, &self.ctx)?;
+                push_events(value, &mut self.queue);
+                self.i = self.tokens.len();
+            }
+        }
+        Ok(())
+    }
+
+    fn open(&mut self, frame: Frame, i: usize) {
+        self.queue.push_back(match frame {
+            Frame::Array(_) => Event::StartArray,
+            Frame::Root | Frame::Object(_) => Event::StartObject,
+        });
+        self.frames.push(frame);
+        self.i = i;
+    }
+
+    fn close(&mut self, i: usize) {
+        self.queue.push_back(Event::End);
+        self.frames.pop();
+        self.i = i;
+    }
+
+    /// Read a member of the root object, as [`parse_root_object`] does.
+    fn root_member(&mut self) -> Result<()> {
+        let mut i = self.i;
+        while i < self.tokens.len() {
+            let t = &self.tokens[i];
+            if t.typ != TokenType::Text {
+                i += 1;
+                continue;
+            }
+            if t.indent != 0 {
+                return Err(ParseError::UnexpectedIndent(String::new())
+                    .with_location(&self.ctx, t.line_num, 0));
+            }
+            let (k, v_part, value_col) = match root_member(t, &self.ctx)? {
+                Some(member) => member,
+                None => {
+                    i += 1;
+                    continue;
+                }
+            };
+            self.queue.push_back(Event::Key(k));
+            match open_block(&self.tokens, i, v_part) {
+                Some((frame, j)) => self.open(frame, j),
+                None => {
+                    let (value, next) = parse_root_object_property(
+                        &self.tokens,
+                        i,
+                        t,
+                        v_part,
+                        value_col,
+                        &self.ctx,
+                    )?;
+                    push_events(value, &mut self.queue);
+                    self.i = next;
+                }
+            }
+            return Ok(());
+        }
+        self.close(i);
+        Ok(())
+    }
+
+    /// Read a member of a nested object, as [`parse_nested_object_content`]
+    /// does.
+    fn object_member(&mut self, base_indent: usize) -> Result<()> {
+        let mut i = self.i;
+        while i < self.tokens.len() {
+            let t = &self.tokens[i];
+            if t.typ != TokenType::Text {
+                i += 1;
+                continue;
+            }
+            if matches!(t.text.chars().next(), Some('{') | Some('[') | Some('<')) {
+                return Err(ParseError::UnexpectedIndent(String::new())
+                    .with_location(&self.ctx, t.line_num, 0));
+            }
+            let colon_idx = match find_colon_outside_quotes(&t.text) {
+                Some(idx) if t.indent >= base_indent => idx,
+                _ => break,
+            };
+            let k_raw = t.text[..colon_idx].trim();
+            let k = parse_key_name(k_raw, &self.ctx, t.line_num, t.col)?;
+            let v_part = t.text[colon_idx + 1..].trim();
+            if k_raw.is_empty() {
+                i += 1;
+                continue;
+            }
+            self.queue.push_back(Event::Key(k));
+            match open_block(&self.tokens, i, v_part) {
+                Some((frame, j)) => self.open(frame, j),
+                None => {
+                    let (value, next) =
+                        parse_object_property_value(&self.tokens, i, t, v_part, &self.ctx)?;
+                    push_events(value, &mut self.queue);
+                    self.i = next;
+                }
+            }
+            return Ok(());
+        }
+        self.close(i);
+        Ok(())
+    }
+
+    /// Read an item of a block array, as [`parse_multiline_array`] does.
+    fn array_item(&mut self, base_indent: usize) -> Result<()> {
+        let mut i = self.i;
+        if let Some(t) = self.tokens.get(i) {
+            if t.typ == TokenType::Start && t.text == "- " && t.indent == base_indent {
+                i = skip_breaks(&self.tokens, i + 1);
+                if i < self.tokens.len() {
+                    let (value, next) = parse_array_item(&self.tokens, i, base_indent, &self.ctx)?;
+                    push_events(value, &mut self.queue);
+                    self.i = skip_breaks_and_stops(&self.tokens, next);
+                    return Ok(());
+                }
+            }
+        }
+        self.close(i);
+        Ok(())
+    }
+}
+
+impl Iterator for Parser {
+    type Item = Result<Event>;
+
+    fn next(&mut self) -> Option<Result<Event>> {
+        loop {
+            if let Some(event) = self.queue.pop_front() {
+                return Some(Ok(event));
+            }
+            if self.done {
+                return None;
+            }
+            if let Err(e) = self.step() {
+                self.done = true;
+                return Some(Err(e));
+            }
+        }
+    }
+}
+
+/// If the member at `i`, with `v_part` after its colon, opens a block array
+/// or a nested object on the lines below, the frame to read it with and the
+/// index of its first token. Anything else under the key (a block string,
+/// concatenated strings, or nothing) is left to be parsed whole.
+fn open_block(tokens: &[Token], i: usize, v_part: &str) -> Option<(Frame, usize)> {
+    if !strip_inline_comment(v_part).is_empty() {
+        return None;
+    }
+    let j = skip_breaks_and_stops(tokens, i + 1);
+    let next = tokens.get(j)?;
+    if next.typ == TokenType::Start && next.text == "- " {
+        return Some((Frame::Array(next.indent), j));
+    }
+    let trimmed = next.text.trim();
+    if next.typ == TokenType::Text
+        && next.indent > tokens[i].indent
+        && trimmed != "`"
+        && !is_string_line(trimmed)
+    {
+        return Some((Frame::Object(next.indent), j));
+    }
+    None
+}
+
+/// Queue the events of `value`.
+fn push_events(value: Value, queue: &mut VecDeque<Event>) {
+    match value {
+        Value::Array(items) => {
+            queue.push_back(Event::StartArray);
+            for item in items {
+                push_events(item, queue);
+            }
+            queue.push_back(Event::End);
+        }
+        Value::Object(members) => {
+            queue.push_back(Event::StartObject);
+            for (key, item) in members {
+                queue.push_back(Event::Key(key));
+                push_events(item, queue);
+            }
+            queue.push_back(Event::End);
+        }
+        scalar => queue.push_back(Event::Scalar(scalar)),
+    }
+}
+
 // Add hex crate functionality inline since we can't add it as a dependency easily
 mod hex {
+    use alloc::vec::Vec;
+
     pub fn decode(s: &str) -> Result<Vec<u8>, ()> {
         if !s.len().is_multiple_of(2) {
             return Err(());
@@ -2720,4 +3459,442 @@ mod tests {
         assert_eq!(find_colon_outside_quotes("\"a:b\": 1"), Some(5));
         assert_eq!(find_colon_outside_quotes("'a:b': 1"), Some(5));
     }
+
+    #[test]
+    fn test_duplicate_keys() {
+        use crate::{parse_with_options, parse_with_report, DuplicateKeys, ParseOptions};
+        let policy = |duplicate_keys| ParseOptions {
+            filename: Some("dup.yay".into()),
+            duplicate_keys,
+            ..ParseOptions::default()
+        };
+        let source = "a: 1\nb:\n  - x: 1\n    x: 2\na: {c: 1, c: 2}\n";
+
+        let value = parse_with_options(source, &policy(DuplicateKeys::LastWins)).unwrap();
+        assert_eq!(value.get("a"), Some(&crate::yay!({c: 2})));
+        let value = parse_with_options(source, &policy(DuplicateKeys::FirstWins)).unwrap();
+        assert_eq!(value.get("a"), Some(&Value::from(1)));
+        assert_eq!(value["b"][0]["x"], Value::from(1));
+
+        let err = parse_with_options(source, &policy(DuplicateKeys::Error)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Duplicate key \"x\" at 4:5, first at 3:5 of <dup.yay>"
+        );
+
+        let (value, report) = parse_with_report(source, &policy(DuplicateKeys::Warn)).unwrap();
+        assert_eq!(value["b"][0]["x"], Value::from(2));
+        let found: Vec<_> = report
+            .duplicate_keys
+            .iter()
+            .map(|d| (d.key.as_str(), d.line, d.first_line))
+            .collect();
+        assert_eq!(found, [("x", 3, 2), ("c", 4, 4), ("a", 4, 0)]);
+    }
+
+    #[test]
+    fn test_property_without_value() {
+        for (source, location) in [
+            ("a:\n", "1:3"),
+            ("a:\n  b:\n", "2:5"),
+            ("a:\n  b:\nc: []\n", "2:5"),
+            ("a:\n  b: # note\n  c: {}\n", "2:5"),
+        ] {
+            let err = crate::parse_with_filename(source, Some("empty.yay")).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                format!(
+                    "Expected value after property at {} of <empty.yay>",
+                    location
+                ),
+                "{:?}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn test_non_ascii_keys() {
+        let value = crate::parse("\u{c9}t\u{e9}: 1\nb:\n  \u{f1}and\u{fa}: {\u{f8}: 2}\n").unwrap();
+        assert_eq!(value["\u{c9}t\u{e9}"], Value::Integer(1.into()));
+        assert_eq!(
+            value["b"]["\u{f1}and\u{fa}"]["\u{f8}"],
+            Value::Integer(2.into())
+        );
+        let value = crate::parse("a: [\"\u{e9}\", '\u{fc}']\nb: {\u{f1}: \"\u{f8}\"}").unwrap();
+        assert_eq!(
+            value,
+            crate::parse("a: [\"\\u{e9}\", \"\\u{fc}\"]\nb: {\"\u{f1}\": \"\\u{f8}\"}").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_array_item_after_nested_object() {
+        // The second item is not a member of the root.
+        let value = crate::parse("w:\n- i: 1\n  p:\n    e: 1\n- i: 2\n  p: 3\n").unwrap();
+        assert_eq!(
+            value,
+            crate::parse("w: [{i: 1, p: {e: 1}}, {i: 2, p: 3}]").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_empty_values() {
+        use crate::{parse_with_options, parse_with_report, ParseOptions};
+        let options = ParseOptions {
+            empty_values: true,
+            ..ParseOptions::default()
+        };
+        for (source, expected) in [
+            (
+                "a:
+", "a: null",
+            ),
+            (
+                "a:
+b: 1
+",
+                "a: null
+b: 1",
+            ),
+            (
+                "a:
+  b:
+  c: 1
+",
+                "a:
+  b: null
+  c: 1",
+            ),
+            (
+                "a:
+  b:
+    c:
+d:
+",
+                "a:
+  b:
+    c: null
+d: null",
+            ),
+            (
+                "- a: 1
+  b:
+",
+                "- a: 1
+  b: null",
+            ),
+            (
+                "a: # none
+
+b: []
+",
+                "a: null
+b: []",
+            ),
+        ] {
+            let value = parse_with_options(source, &options).unwrap();
+            assert_eq!(value, crate::parse(expected).unwrap(), "{:?}", source);
+        }
+        let (_, report) = parse_with_report(
+            "a:
+", &options,
+        )
+        .unwrap();
+        assert!(report.relaxations.empty_values);
+        let (_, report) = parse_with_report(
+            "a: 1
+", &options,
+        )
+        .unwrap();
+        assert!(!report.relaxations.any());
+    }
+
+    #[test]
+    fn test_limits() {
+        use crate::{parse_bytes, parse_with_options, ParseOptions, PushParser};
+        let limits = |max_bytes, max_depth, max_nodes| ParseOptions {
+            filename: Some("big.yay".into()),
+            max_bytes,
+            max_depth,
+            max_nodes,
+            ..ParseOptions::default()
+        };
+        let source = "a:\n  b: [1, {c: 2}]\nd: 3\n";
+        // Depth 4 and 7 values: the root, b's object, its array, the inline
+        // object, and three scalars.
+        assert!(parse_with_options(source, &limits(Some(25), Some(4), Some(7))).is_ok());
+
+        let err = parse_with_options(source, &limits(Some(24), None, None)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Document is larger than 24 bytes of <big.yay>"
+        );
+        let err = parse_bytes(source.as_bytes(), &limits(Some(10), None, None)).unwrap_err();
+        assert!(matches!(err, ParseError::TooLarge(10, _)));
+        let err = parse_with_options(source, &limits(None, Some(3), None)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Document nests deeper than 3 levels at 2:6 of <big.yay>"
+        );
+        let err = parse_with_options(source, &limits(None, None, Some(6))).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Document has more than 6 values at 3:1 of <big.yay>"
+        );
+
+        let err = parse_with_options("a:\n  b:\n    c: 1\n", &limits(None, Some(2), None));
+        assert!(matches!(err, Err(ParseError::TooDeep(2, _))));
+
+        // Deep nesting fails as soon as it crosses the limit, before it
+        // can exhaust the stack.
+        let mut indented = String::new();
+        for level in 0..5_000 {
+            indented += &format!("{}a:\n", "  ".repeat(level));
+        }
+        indented += &format!("{}b: 1\n", "  ".repeat(5_000));
+        for deep in [
+            "[".repeat(100_000) + &"]".repeat(100_000),
+            "{a: ".repeat(100_000) + "1" + &"}".repeat(100_000),
+            "- ".repeat(100_000) + "1\n",
+            indented,
+        ] {
+            let err = parse_with_options(&deep, &limits(None, Some(10), Some(100))).unwrap_err();
+            assert!(matches!(err, ParseError::TooDeep(10, _)), "{}", err);
+        }
+        let err = parse_with_options(&"- 1\n".repeat(100_000), &limits(None, None, Some(100)))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Document has more than 100 values at 100:1 of <big.yay>"
+        );
+
+        let mut parser = PushParser::new(&limits(Some(8), None, None));
+        parser.feed(b"a: 1\n").unwrap();
+        assert!(matches!(
+            parser.feed(b"b: 2\n"),
+            Err(ParseError::TooLarge(8, _))
+        ));
+        let mut parser = PushParser::new(&limits(None, None, Some(2)));
+        parser.feed(b"a: 1\nb: 2\n").unwrap();
+        assert!(matches!(
+            parser.finish(),
+            Err(ParseError::TooManyNodes(2, _))
+        ));
+    }
+
+    #[test]
+    fn test_observer() {
+        use crate::{parse_bytes, parse_with_options, ParseObserver, ParseOptions};
+        use std::ops::ControlFlow;
+        use std::sync::{Arc, Mutex};
+
+        /// Records each callback, and stops at the line or path named.
+        #[derive(Debug, Default)]
+        struct Record {
+            stop: &'static str,
+            seen: Mutex<Vec<String>>,
+        }
+        impl Record {
+            fn note(&self, what: String) -> ControlFlow<()> {
+                let stop = what == self.stop;
+                self.seen.lock().unwrap().push(what);
+                if stop {
+                    ControlFlow::Break(())
+                } else {
+                    ControlFlow::Continue(())
+                }
+            }
+        }
+        impl ParseObserver for Record {
+            fn on_line(&self, line: usize, lines: usize) -> ControlFlow<()> {
+                self.note(format!("line {} of {}", line, lines))
+            }
+            fn on_value(&self, path: &str, value: &Value) -> ControlFlow<()> {
+                self.note(format!("{} = {}", path, value))
+            }
+            fn on_error(&self, error: &ParseError) {
+                self.seen.lock().unwrap().push(error.to_string());
+            }
+        }
+        let observe = |source: &str, stop: &'static str| {
+            let record = Arc::new(Record {
+                stop,
+                ..Record::default()
+            });
+            let options = ParseOptions {
+                filename: Some("obs.yay".into()),
+                observer: Some(record.clone()),
+                ..ParseOptions::default()
+            };
+            let result = parse_with_options(source, &options);
+            let seen = record.seen.lock().unwrap().clone();
+            (result, seen)
+        };
+
+        let (result, seen) = observe("a: 1\nb:\n  c: [2]\n", "");
+        assert!(result.is_ok());
+        assert_eq!(
+            seen,
+            [
+                "line 1 of 4",
+                "line 2 of 4",
+                "line 3 of 4",
+                "line 4 of 4",
+                "a = 1",
+                "b = c: [2]"
+            ]
+        );
+        let (_, seen) = observe("- 1\n- [2]", "");
+        assert_eq!(seen[2..], ["[0] = 1", "[1] = [2]"]);
+        let (_, seen) = observe("\"x\"", "");
+        assert_eq!(seen[1..], [" = \"x\""]);
+
+        // Breaking stops the parse where it is, and the error is reported.
+        let (result, seen) = observe("a: 1\nb: 2\nc: 3\n", "line 2 of 4");
+        assert!(matches!(result, Err(ParseError::Cancelled(_))));
+        assert_eq!(
+            seen,
+            [
+                "line 1 of 4",
+                "line 2 of 4",
+                "Parse cancelled at 2:1 of <obs.yay>"
+            ]
+        );
+        let (result, seen) = observe("- 1\n- 2\n- 3\n", "[1] = 2");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Parse cancelled of <obs.yay>"
+        );
+        assert_eq!(
+            seen[seen.len() - 2..],
+            ["[1] = 2", "Parse cancelled of <obs.yay>"]
+        );
+        let (_, seen) = observe("a: 1\na b: 2\n", "");
+        assert_eq!(
+            seen.last().unwrap(),
+            "Invalid key character at 2:2 of <obs.yay>"
+        );
+
+        // Decoding errors from bytes are reported too.
+        let record = Arc::new(Record::default());
+        let options = ParseOptions {
+            observer: Some(record.clone()),
+            ..ParseOptions::default()
+        };
+        assert!(parse_bytes(b"\xff", &options).is_err());
+        assert_eq!(record.seen.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_cancel() {
+        use crate::{
+            parse_with_options, CancellationToken, ParseObserver, ParseOptions, PushParser,
+        };
+        use std::ops::ControlFlow;
+        use std::sync::Arc;
+
+        /// Cancels the token when the scanner reaches a line.
+        #[derive(Debug)]
+        struct CancelAt(usize, CancellationToken);
+        impl ParseObserver for CancelAt {
+            fn on_line(&self, line: usize, _lines: usize) -> ControlFlow<()> {
+                if line == self.0 {
+                    self.1.cancel();
+                }
+                ControlFlow::Continue(())
+            }
+        }
+
+        let token = CancellationToken::new();
+        let options = ParseOptions {
+            filename: Some("big.yay".into()),
+            cancel: Some(token.clone()),
+            observer: Some(Arc::new(CancelAt(3, token.clone()))),
+            ..ParseOptions::default()
+        };
+        let err = parse_with_options("- 1\n- 2\n- 3\n- 4\n", &options).unwrap_err();
+        assert_eq!(err.to_string(), "Parse cancelled at 3:1 of <big.yay>");
+        assert!(token.is_cancelled());
+
+        // The parser checks between values, after the lines are scanned.
+        let token = CancellationToken::new();
+        let options = ParseOptions {
+            cancel: Some(token.clone()),
+            ..ParseOptions::default()
+        };
+        let mut parser = Parser::with_options("a:\n  b: [1, 2]\n", &options);
+        assert!(matches!(parser.next(), Some(Ok(Event::StartObject))));
+        token.cancel();
+        assert!(parser.any(|event| matches!(event, Err(ParseError::Cancelled(_)))));
+
+        let mut parser = PushParser::new(&options);
+        assert!(matches!(
+            parser.feed(b"a: 1\n"),
+            Err(ParseError::Cancelled(_))
+        ));
+    }
+
+    #[test]
+    fn test_file_bytes() {
+        use crate::{parse_with_options, ParseOptions};
+        let dir = std::env::temp_dir().join(format!("yay-bytes-{}", std::process::id()));
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("sub/blob.bin"), [0xca, 0xfe]).unwrap();
+        let options = ParseOptions {
+            bytes_root: Some(dir.join("sub")),
+            ..ParseOptions::default()
+        };
+        let source = "a: <@ \"blob.bin\">\nb: [<@ \"blob.bin\">, <00>]\n";
+        let value = parse_with_options(source, &options).unwrap();
+        assert_eq!(value.get("a"), Some(&Value::Bytes(vec![0xca, 0xfe])));
+        assert_eq!(
+            value.get("b").and_then(|b| b.get_index(0)),
+            Some(&Value::Bytes(vec![0xca, 0xfe]))
+        );
+        // Off by default, and sandboxed when on.
+        assert!(crate::parse(source).is_err());
+        fs::write(dir.join("secret.bin"), [1]).unwrap();
+        let err = parse_with_options("<@ \"../secret.bin\">", &options).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Cannot include \"../secret.bin\": path is outside the bytes root"
+        );
+        assert!(parse_with_options("<@ \"missing.bin\">", &options).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_parser_reads_a_member_at_a_time() {
+        let source = "name: \"x\"\nlog:\n  - 1\n  - {a: 2}\n  - [bad\n";
+        let mut parser = Parser::new(source);
+        let mut events = Vec::new();
+        let error = loop {
+            match parser.next() {
+                Some(Ok(event)) => events.push(event),
+                Some(Err(e)) => break e,
+                None => panic!("expected an error"),
+            }
+        };
+        assert_eq!(
+            events,
+            [
+                Event::StartObject,
+                Event::Key("name".into()),
+                Event::Scalar(Value::String("x".into())),
+                Event::Key("log".into()),
+                Event::StartArray,
+                Event::Scalar(Value::Integer(1.into())),
+                Event::StartObject,
+                Event::Key("a".into()),
+                Event::Scalar(Value::Integer(2.into())),
+                Event::End,
+            ]
+        );
+        assert_eq!(
+            error.to_string(),
+            crate::parse(source).unwrap_err().to_string()
+        );
+        assert!(parser.next().is_none());
+    }
 }
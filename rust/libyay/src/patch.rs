@@ -0,0 +1,356 @@
+//! JSON Patch (RFC 6902).
+//!
+//! A patch is an array of operations, each an object with an `op` and the
+//! JSON Pointers it works on, written in YAY or JSON:
+//!
+//! ```yay
+//! - op: "test"
+//!   path: "/server/port"
+//!   value: 8080
+//! - op: "replace"
+//!   path: "/server/port"
+//!   value: 8443
+//! - op: "add"
+//!   path: "/server/hosts/-"
+//!   value: "c.example.com"
+//! - op: "remove"
+//!   path: "/debug"
+//! ```
+//!
+//! Applying a patch either makes every change or, if any operation fails
+//! (a `test` included), none of them.
+
+use num_bigint::BigInt;
+use num_traits::FromPrimitive;
+
+use crate::encode::encode_yay_inline;
+use crate::value::{pointer_index, split_pointer};
+use crate::Value;
+
+/// One operation of a patch.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operation {
+    /// Add a member or insert an array item at `path`, where `-` stands for
+    /// the end of an array. An existing member is replaced.
+    Add { path: String, value: Value },
+    /// Remove the value at `path`, which must exist.
+    Remove { path: String },
+    /// Replace the value at `path`, which must exist.
+    Replace { path: String, value: Value },
+    /// Remove the value at `from` and add it at `path`.
+    Move { from: String, path: String },
+    /// Add a copy of the value at `from` at `path`.
+    Copy { from: String, path: String },
+    /// Fail unless the value at `path` equals `value`.
+    Test { path: String, value: Value },
+}
+
+impl Operation {
+    /// The operation's `op` name, like `"add"`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Operation::Add { .. } => "add",
+            Operation::Remove { .. } => "remove",
+            Operation::Replace { .. } => "replace",
+            Operation::Move { .. } => "move",
+            Operation::Copy { .. } => "copy",
+            Operation::Test { .. } => "test",
+        }
+    }
+}
+
+/// The operations of a patch, in order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Patch {
+    pub operations: Vec<Operation>,
+}
+
+impl Patch {
+    /// Parse a patch from YAY source.
+    pub fn from_yay(source: &str) -> Result<Patch, String> {
+        let value = crate::parse(source).map_err(|e| e.to_string())?;
+        Patch::from_value(&value)
+    }
+
+    /// Build a patch from a parsed value. Members besides those of the
+    /// operation are ignored, as the RFC asks.
+    pub fn from_value(value: &Value) -> Result<Patch, String> {
+        let entries = value
+            .as_array()
+            .ok_or_else(|| "A patch must be an array of operations".to_string())?;
+        let operations = entries
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| {
+                parse_operation(entry).map_err(|e| format!("Operation {}: {}", i + 1, e))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Patch { operations })
+    }
+
+    /// Apply the operations to `document` in order. If one fails, the
+    /// document is left as it was and the error names the operation.
+    ///
+    /// ```
+    /// use libyay::patch::Patch;
+    /// use libyay::parse;
+    ///
+    /// let patch = Patch::from_yay(
+    ///     "- {op: \"test\", path: \"/port\", value: 8080}\n\
+    ///      - {op: \"replace\", path: \"/port\", value: 8443}\n\
+    ///      - {op: \"add\", path: \"/hosts/0\", value: \"a\"}\n",
+    /// )
+    /// .unwrap();
+    /// let mut document = parse("port: 8080\nhosts: [\"b\"]").unwrap();
+    /// patch.apply(&mut document).unwrap();
+    /// assert_eq!(document, parse("port: 8443\nhosts: [\"a\", \"b\"]").unwrap());
+    ///
+    /// let error = patch.apply(&mut document).unwrap_err();
+    /// assert_eq!(error, "Operation 1 (test): /port is 8443, not 8080");
+    /// ```
+    pub fn apply(&self, document: &mut Value) -> Result<(), String> {
+        let mut patched = document.clone();
+        for (i, operation) in self.operations.iter().enumerate() {
+            apply_operation(&mut patched, operation)
+                .map_err(|e| format!("Operation {} ({}): {}", i + 1, operation.name(), e))?;
+        }
+        *document = patched;
+        Ok(())
+    }
+}
+
+fn parse_operation(entry: &Value) -> Result<Operation, String> {
+    if entry.as_object().is_none() {
+        return Err("each operation must be an object".to_string());
+    }
+    let string = |name: &str| {
+        entry
+            .get(name)
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .ok_or_else(|| format!("needs a string \"{}\"", name))
+    };
+    let value = || {
+        entry
+            .get("value")
+            .cloned()
+            .ok_or_else(|| "needs a \"value\"".to_string())
+    };
+    match string("op")?.as_str() {
+        "add" => Ok(Operation::Add {
+            path: string("path")?,
+            value: value()?,
+        }),
+        "remove" => Ok(Operation::Remove {
+            path: string("path")?,
+        }),
+        "replace" => Ok(Operation::Replace {
+            path: string("path")?,
+            value: value()?,
+        }),
+        "move" => Ok(Operation::Move {
+            from: string("from")?,
+            path: string("path")?,
+        }),
+        "copy" => Ok(Operation::Copy {
+            from: string("from")?,
+            path: string("path")?,
+        }),
+        "test" => Ok(Operation::Test {
+            path: string("path")?,
+            value: value()?,
+        }),
+        op => Err(format!("unknown op {:?}", op)),
+    }
+}
+
+fn apply_operation(document: &mut Value, operation: &Operation) -> Result<(), String> {
+    match operation {
+        Operation::Add { path, value } => add(document, path, value.clone()),
+        Operation::Remove { path } => remove(document, path).map(drop),
+        Operation::Replace { path, value } => {
+            let slot = document
+                .pointer_mut(path)
+                .ok_or_else(|| format!("{} does not exist", path))?;
+            *slot = value.clone();
+            Ok(())
+        }
+        Operation::Move { from, path } => {
+            if path.starts_with(from.as_str()) && path[from.len()..].starts_with('/') {
+                return Err(format!("cannot move {} into itself", from));
+            }
+            if from == path {
+                return get(document, from).map(drop);
+            }
+            let value = remove(document, from)?;
+            add(document, path, value)
+        }
+        Operation::Copy { from, path } => {
+            let value = get(document, from)?.clone();
+            add(document, path, value)
+        }
+        Operation::Test { path, value } => {
+            let actual = get(document, path)?;
+            if equal(actual, value) {
+                Ok(())
+            } else {
+                Err(format!(
+                    "{} is {}, not {}",
+                    path,
+                    encode_yay_inline(actual),
+                    encode_yay_inline(value)
+                ))
+            }
+        }
+    }
+}
+
+fn get<'a>(document: &'a Value, path: &str) -> Result<&'a Value, String> {
+    document
+        .pointer(path)
+        .ok_or_else(|| format!("{} does not exist", path))
+}
+
+fn add(document: &mut Value, path: &str, value: Value) -> Result<(), String> {
+    if path.is_empty() {
+        *document = value;
+        return Ok(());
+    }
+    let (parent, key) = split_pointer(path)?;
+    match document.pointer_mut(parent) {
+        Some(Value::Object(obj)) => {
+            obj.insert(key, value);
+            Ok(())
+        }
+        Some(Value::Array(arr)) => {
+            let index = match key.as_str() {
+                "-" => arr.len(),
+                key => pointer_index(key)
+                    .filter(|&index| index <= arr.len())
+                    .ok_or_else(|| format!("{} is past the end of its array", path))?,
+            };
+            arr.insert(index, value);
+            Ok(())
+        }
+        Some(_) => Err(format!("the parent of {} is not an object or array", path)),
+        None => Err(format!("the parent of {} does not exist", path)),
+    }
+}
+
+fn remove(document: &mut Value, path: &str) -> Result<Value, String> {
+    if path.is_empty() {
+        return Err("cannot remove the whole document".to_string());
+    }
+    let (parent, key) = split_pointer(path)?;
+    let removed = match document.pointer_mut(parent) {
+        Some(Value::Object(obj)) => obj.shift_remove(&key),
+        Some(Value::Array(arr)) => pointer_index(&key)
+            .filter(|&index| index < arr.len())
+            .map(|index| arr.remove(index)),
+        _ => None,
+    };
+    removed.ok_or_else(|| format!("{} does not exist", path))
+}
+
+/// Equality as the RFC defines it for `test`: numbers are equal when their
+/// values are, whether integers or floats, and objects whatever the order
+/// of their members.
+fn equal(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Integer(i), Value::Float(f)) | (Value::Float(f), Value::Integer(i)) => {
+            f.fract() == 0.0 && BigInt::from_f64(*f).as_ref() == Some(i)
+        }
+        (Value::Array(a), Value::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| equal(a, b))
+        }
+        (Value::Object(a), Value::Object(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .all(|(key, a)| b.get(key).is_some_and(|b| equal(a, b)))
+        }
+        _ => a == b,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    fn patched(document: &str, patch: &str) -> Result<Value, String> {
+        let mut document = parse(document).unwrap();
+        Patch::from_yay(patch)?.apply(&mut document)?;
+        Ok(document)
+    }
+
+    #[test]
+    fn test_patch() {
+        let document = "a:\n  b: 1\n  c: [1, 2]\nd: \"x\"";
+        assert_eq!(
+            patched(
+                document,
+                "- {op: \"add\", path: \"/a/c/1\", value: 5}\n\
+                 - {op: \"add\", path: \"/a/c/-\", value: 6}\n\
+                 - {op: \"remove\", path: \"/a/b\"}\n\
+                 - {op: \"move\", from: \"/d\", path: \"/a/d\"}\n\
+                 - {op: \"copy\", from: \"/a/c\", path: \"/e\"}\n\
+                 - {op: \"replace\", path: \"/e/0\", value: null}\n\
+                 - {op: \"test\", path: \"/a\", value: {d: \"x\", c: [1.0, 5, 2, 6]}}\n"
+            ),
+            Ok(parse("a:\n  c: [1, 5, 2, 6]\n  d: \"x\"\ne: [null, 5, 2, 6]").unwrap())
+        );
+        assert_eq!(
+            patched(document, "- {op: \"add\", path: \"\", value: [1]}\n"),
+            Ok(parse("[1]").unwrap())
+        );
+
+        for (patch, error) in [
+            (
+                "- {op: \"remove\", path: \"/a/x\"}\n",
+                "Operation 1 (remove): /a/x does not exist",
+            ),
+            (
+                "- {op: \"add\", path: \"/a/c/3\", value: 1}\n",
+                "Operation 1 (add): /a/c/3 is past the end of its array",
+            ),
+            (
+                "- {op: \"add\", path: \"/x/y\", value: 1}\n",
+                "Operation 1 (add): the parent of /x/y does not exist",
+            ),
+            (
+                "- {op: \"replace\", path: \"/a/c/01\", value: 1}\n",
+                "Operation 1 (replace): /a/c/01 does not exist",
+            ),
+            (
+                "- {op: \"move\", from: \"/a\", path: \"/a/b/c\"}\n",
+                "Operation 1 (move): cannot move /a into itself",
+            ),
+            (
+                "- {op: \"test\", path: \"/a/b\", value: 1.5}\n",
+                "Operation 1 (test): /a/b is 1, not 1.5",
+            ),
+            ("- {op: \"frob\"}\n", "Operation 1: unknown op \"frob\""),
+            (
+                "- {op: \"add\", path: \"/a\"}\n",
+                "Operation 1: needs a \"value\"",
+            ),
+            ("{op: \"add\"}\n", "A patch must be an array of operations"),
+        ] {
+            assert_eq!(
+                patched(document, patch),
+                Err(error.to_string()),
+                "{}",
+                patch
+            );
+        }
+
+        // A failed operation leaves the document as it was.
+        let mut value = parse(document).unwrap();
+        let patch = Patch::from_yay(
+            "- {op: \"remove\", path: \"/d\"}\n- {op: \"test\", path: \"/a/b\", value: 2}\n",
+        )
+        .unwrap();
+        assert!(patch.apply(&mut value).is_err());
+        assert_eq!(value, parse(document).unwrap());
+    }
+}
@@ -0,0 +1,292 @@
+//! Parsing a document as its bytes arrive.
+
+use crate::encoding::{detect_encoding, Encoding, EncodingDetection, Newlines};
+use crate::error::{ParseContext, ParseError, Result};
+use crate::lexer::outline_lex;
+use crate::parser::{check_limits, parse_root, parse_root_object, starts_root_object, Members};
+use crate::scanner::{
+    scan_line, validate_line_code_points, validate_no_bom, validate_size, ScanLine,
+};
+use crate::{ParseOptions, Value};
+use alloc::string::ToString;
+use alloc::vec::Vec;
+
+/// A parser fed a YAY document in chunks, such as the body of a network
+/// request, that parses each part of it as soon as the part is complete.
+///
+/// Each member of a root object and each item of a root block array is
+/// parsed when the line that begins the next one arrives, and its text is
+/// dropped, so memory holds the value parsed so far rather than the source
+/// text as well. A document of any other shape is held until
+/// [`finish`](PushParser::finish).
+///
+/// The result is the value [`parse_bytes`](crate::parse_bytes) returns for
+/// the whole input. Errors are reported by the `feed` that completes the
+/// line or member they are in, so a document with several errors may
+/// report a different one first. Once `feed` fails, every later call fails
+/// with the same error. Input detected as UTF-16 or UTF-32 is held whole
+/// and decoded at the end.
+///
+/// # Example
+///
+/// ```
+/// use libyay::{parse, ParseOptions, PushParser};
+///
+/// let mut parser = PushParser::new(&ParseOptions::default());
+/// parser.feed(b"name: \"ser").unwrap();
+/// parser.feed(b"ver\"\nports:\n  - 80\n  - 4").unwrap();
+/// parser.feed(b"43\n").unwrap();
+/// let value = parser.finish().unwrap();
+/// assert_eq!(value, parse("name: \"server\"\nports: [80, 443]").unwrap());
+/// ```
+pub struct PushParser {
+    options: ParseOptions,
+    ctx: ParseContext,
+    /// Input not yet read as lines: the start of an unfinished line, or all
+    /// of the input if it is not UTF-8.
+    pending: Vec<u8>,
+    /// Byte offset of `pending` in the input.
+    offset: usize,
+    /// The encoding, once enough input has arrived to detect it.
+    encoding: Option<Encoding>,
+    line_num: usize,
+    had_comments: bool,
+    root: Root,
+    /// The lines of the member or item being read, or of the whole
+    /// document if it is not a root object or block array.
+    lines: Vec<ScanLine>,
+    error: Option<ParseError>,
+}
+
+/// What the [`PushParser`] knows of the root value.
+enum Root {
+    /// Nothing but blank lines and comments so far.
+    Unknown,
+    /// A root object, with the members read so far.
+    Object(Members),
+    /// A root block array, with the items read so far.
+    Array(Vec<Value>),
+    /// Any other value, parsed at the end.
+    Other,
+}
+
+impl PushParser {
+    /// A parser for a document to be parsed with `options`.
+    pub fn new(options: &ParseOptions) -> PushParser {
+        PushParser {
+            options: options.clone(),
+            ctx: ParseContext::from_options(options),
+            pending: Vec::new(),
+            offset: 0,
+            encoding: None,
+            line_num: 0,
+            had_comments: false,
+            root: Root::Unknown,
+            lines: Vec::new(),
+            error: None,
+        }
+    }
+
+    /// Parse the next chunk of the document, which may end anywhere, even
+    /// inside a line or a UTF-8 sequence.
+    pub fn feed(&mut self, bytes: &[u8]) -> Result<()> {
+        if let Some(e) = &self.error {
+            return Err(e.clone());
+        }
+        self.pending.extend_from_slice(bytes);
+        let result = validate_size(self.offset + self.pending.len(), &self.ctx)
+            .and_then(|()| self.read_lines(false));
+        if let Err(e) = &result {
+            self.error = Some(e.clone());
+        }
+        result
+    }
+
+    /// Parse the rest of the document and return its value.
+    pub fn finish(mut self) -> Result<Value> {
+        if let Some(e) = self.error.take() {
+            return Err(e);
+        }
+        self.read_lines(true)?;
+        if self
+            .encoding
+            .is_some_and(|encoding| encoding != Encoding::Utf8)
+        {
+            return crate::parse_bytes(&self.pending, &self.options);
+        }
+        self.end_member()?;
+        let value = match self.root {
+            Root::Object(members) => Value::Object(members.into_map()),
+            Root::Array(items) => Value::Array(items),
+            Root::Unknown | Root::Other => return parse_root(&outline_lex(&self.lines), &self.ctx),
+        };
+        check_limits(&value, &self.ctx)?;
+        Ok(value)
+    }
+
+    /// Read the complete lines in `pending`, and at the end of the input,
+    /// the final line too.
+    fn read_lines(&mut self, at_end: bool) -> Result<()> {
+        if self.encoding.is_none() {
+            if self.pending.len() < 4 && !at_end {
+                return Ok(());
+            }
+            let (encoding, bom) = match self.options.encoding {
+                EncodingDetection::Detect => detect_encoding(&self.pending),
+                EncodingDetection::Utf8Only => (Encoding::Utf8, 0),
+            };
+            self.encoding = Some(encoding);
+            if encoding == Encoding::Utf8 {
+                self.pending.drain(..bom);
+                self.offset = bom;
+            }
+        }
+        if self.encoding != Some(Encoding::Utf8) {
+            return Ok(());
+        }
+
+        let normalize = self.options.newlines == Newlines::Normalize;
+        let mut start = 0;
+        loop {
+            let rest = &self.pending[start..];
+            let end = rest
+                .iter()
+                .position(|&b| b == b'\n' || (normalize && b == b'\r'));
+            let (len, next) = match end {
+                Some(end) if rest[end] == b'\r' => match rest.get(end + 1) {
+                    Some(b'\n') => (end, end + 2),
+                    Some(_) => (end, end + 1),
+                    None if at_end => (end, end + 1),
+                    None => break,
+                },
+                Some(end) => (end, end + 1),
+                None if at_end => (rest.len(), rest.len()),
+                None => break,
+            };
+            let line = core::str::from_utf8(&rest[..len]).map_err(|e| {
+                ParseError::InvalidEncoding(
+                    "UTF-8",
+                    self.offset + start + e.valid_up_to(),
+                    self.ctx.file_suffix(),
+                )
+            })?;
+            let line = line.to_string();
+            self.read_line(&line)?;
+            start += next;
+            if end.is_none() {
+                break;
+            }
+        }
+        self.pending.drain(..start);
+        self.offset += start;
+        Ok(())
+    }
+
+    /// Scan one line and add it to the member or item it belongs to,
+    /// parsing the previous member or item if this line begins a new one.
+    fn read_line(&mut self, line: &str) -> Result<()> {
+        let line_num = self.line_num;
+        self.line_num += 1;
+        if line_num == 0 {
+            validate_no_bom(line, &self.ctx)?;
+        }
+        validate_line_code_points(line, line_num, &self.ctx)?;
+        let line = match scan_line(line, line_num, &self.ctx)? {
+            Some(line) => line,
+            None => {
+                self.had_comments = true;
+                return Ok(());
+            }
+        };
+
+        let at_root = line.indent == 0 && !line.line.is_empty();
+        match self.root {
+            Root::Unknown if line.line.is_empty() && line.leader.is_empty() => {}
+            Root::Unknown => {
+                // Decide as the parser does from the first token.
+                self.root = if at_root && line.leader.is_empty() && starts_root_object(&line.line) {
+                    Root::Object(Members::default())
+                } else if line.indent == 0 && !line.leader.is_empty() {
+                    Root::Array(Vec::new())
+                } else {
+                    Root::Other
+                };
+            }
+            Root::Object(_) if at_root && line.leader.is_empty() => self.end_member()?,
+            Root::Array(_) if line.indent == 0 && !line.leader.is_empty() => self.end_member()?,
+            _ => {}
+        }
+        self.lines.push(line);
+        Ok(())
+    }
+
+    /// Parse the lines of the member or item just read into the root.
+    fn end_member(&mut self) -> Result<()> {
+        match &mut self.root {
+            Root::Object(members) => {
+                let (more, _) = parse_root_object(&outline_lex(&self.lines), 0, &self.ctx)?;
+                members.extend(more, &self.ctx)?;
+            }
+            Root::Array(items) => {
+                if let Value::Array(more) = parse_root(&outline_lex(&self.lines), &self.ctx)? {
+                    items.extend(more);
+                }
+            }
+            Root::Unknown | Root::Other => return Ok(()),
+        }
+        self.lines.clear();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    fn push(chunks: &[&[u8]], options: &ParseOptions) -> Result<Value> {
+        let mut parser = PushParser::new(options);
+        for chunk in chunks {
+            parser.feed(chunk)?;
+        }
+        parser.finish()
+    }
+
+    #[test]
+    fn test_encodings_and_newlines() {
+        let normalize = ParseOptions {
+            newlines: Newlines::Normalize,
+            ..ParseOptions::default()
+        };
+        let expected = parse("a: 1\nb: [2]\n").unwrap();
+        for chunks in [
+            &[b"\xEF\xBB".as_slice(), b"\xBFa: 1\r", b"\nb: [2]\r"][..],
+            &[b"a: 1\rb: [2]"],
+            &[
+                b"\xFF\xFEa\x00:\x00 \x001\x00\n\x00b\x00:",
+                b"\x00 \x00[\x002\x00]\x00",
+            ],
+        ] {
+            assert_eq!(push(chunks, &normalize).unwrap(), expected);
+        }
+        assert!(push(&[b"a: 1\r\n"], &ParseOptions::default()).is_err());
+        let error = push(&[b"a: 1\nb: \"\xC3", b"\x28\"\n"], &normalize).unwrap_err();
+        assert_eq!(error.to_string(), "Invalid UTF-8 at byte offset 9");
+    }
+
+    #[test]
+    fn test_errors_as_members_end() {
+        let mut parser = PushParser::new(&ParseOptions::default());
+        parser.feed(b"- 1\n- [2,3]\n").unwrap();
+        let error = parser.feed(b"- 4\n").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            parse("- 1\n- [2,3]\n").unwrap_err().to_string()
+        );
+        assert_eq!(
+            parser.feed(b"- 5\n").unwrap_err().to_string(),
+            error.to_string()
+        );
+        assert_eq!(parser.finish().unwrap_err().to_string(), error.to_string());
+    }
+}
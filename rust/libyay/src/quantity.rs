@@ -0,0 +1,164 @@
+//! Unit-suffixed quantity literals.
+//!
+//! With [`ParseOptions::quantities`](crate::ParseOptions) turned on, a
+//! number immediately followed by a unit, like `10s`, `512KiB`, or
+//! `1.5GHz`, parses as a quantity instead of being rejected. A quantity is
+//! an object with a `magnitude` (integer or float) and a `unit` (string),
+//! so every encoder carries it without change; [`Quantity`] reads it back.
+
+use crate::{Map, Value};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// Which quantity literals the parser accepts.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum Quantities {
+    /// Quantity literals are not part of the grammar.
+    #[default]
+    Off,
+    /// Accept a quantity with any unit.
+    Any,
+    /// Accept only these units; any other unit is a parse error.
+    Units(Vec<String>),
+}
+
+impl Quantities {
+    /// Whether quantity literals are accepted at all.
+    pub fn enabled(&self) -> bool {
+        !matches!(self, Quantities::Off)
+    }
+
+    /// Whether `unit` is accepted.
+    pub fn allows(&self, unit: &str) -> bool {
+        match self {
+            Quantities::Off => false,
+            Quantities::Any => true,
+            Quantities::Units(units) => units.iter().any(|u| u == unit),
+        }
+    }
+}
+
+/// A number with a unit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Quantity {
+    /// The number, a `Value::Integer` or `Value::Float`.
+    pub magnitude: Value,
+    /// The unit, like `s` or `KiB`.
+    pub unit: String,
+}
+
+impl Quantity {
+    /// Read a quantity from its object form, `{magnitude: 10, unit: "s"}`.
+    pub fn from_value(value: &Value) -> Option<Quantity> {
+        let obj = value.as_object()?;
+        if obj.len() != 2 {
+            return None;
+        }
+        let magnitude = obj.get("magnitude")?;
+        if !matches!(magnitude, Value::Integer(_) | Value::Float(_)) {
+            return None;
+        }
+        Some(Quantity {
+            magnitude: magnitude.clone(),
+            unit: obj.get("unit")?.as_str()?.to_string(),
+        })
+    }
+}
+
+impl From<Quantity> for Value {
+    fn from(quantity: Quantity) -> Self {
+        let mut obj = Map::default();
+        obj.insert("magnitude".to_string(), quantity.magnitude);
+        obj.insert("unit".to_string(), Value::String(quantity.unit));
+        Value::Object(obj)
+    }
+}
+
+/// Whether `s` is a unit: ASCII letters, optionally followed by `/` and
+/// more letters, as in `km/h`.
+pub(crate) fn is_unit(s: &str) -> bool {
+    let mut parts = s.splitn(2, '/');
+    let letters = |p: &str| !p.is_empty() && p.chars().all(|c| c.is_ascii_alphabetic());
+    parts.next().is_some_and(letters) && parts.next().is_none_or(letters)
+}
+
+/// Split a quantity literal into its number and unit, trying each place
+/// the unit could start so that `1e3s` is `1e3` seconds.
+pub(crate) fn split_quantity(s: &str) -> Option<(&str, &str)> {
+    s.char_indices()
+        .filter(|(i, c)| *i > 0 && c.is_ascii_alphabetic())
+        .map(|(i, _)| s.split_at(i))
+        .find(|(number, unit)| {
+            is_unit(unit) && number.ends_with(|c: char| c.is_ascii_digit() || c == '.')
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse, parse_with_options, ParseOptions};
+
+    fn options(quantities: Quantities) -> ParseOptions {
+        ParseOptions {
+            quantities,
+            ..ParseOptions::default()
+        }
+    }
+
+    fn quantity(value: &Value) -> (Value, String) {
+        let q = Quantity::from_value(value).unwrap();
+        (q.magnitude, q.unit)
+    }
+
+    #[test]
+    fn test_split_quantity() {
+        assert_eq!(split_quantity("10s"), Some(("10", "s")));
+        assert_eq!(split_quantity("1.5GHz"), Some(("1.5", "GHz")));
+        assert_eq!(split_quantity("1e3s"), Some(("1e3", "s")));
+        assert_eq!(split_quantity("90km/h"), Some(("90", "km/h")));
+        assert_eq!(split_quantity("10"), None);
+        assert_eq!(split_quantity("s"), None);
+        assert_eq!(split_quantity("0x1p3"), None);
+    }
+
+    #[test]
+    fn test_quantities_are_opt_in() {
+        assert!(parse("timeout: 10s").is_err());
+        let value = parse_with_options(
+            "timeout: 10s\nsizes: [512KiB, -1.5GHz]\n",
+            &options(Quantities::Any),
+        )
+        .unwrap();
+        assert_eq!(
+            quantity(value.get("timeout").unwrap()),
+            (Value::from(10i64), "s".to_string())
+        );
+        let sizes = value.get("sizes").unwrap();
+        assert_eq!(
+            quantity(sizes.get_index(1).unwrap()),
+            (Value::Float(-1.5), "GHz".to_string())
+        );
+    }
+
+    #[test]
+    fn test_allowed_units() {
+        let units = options(Quantities::Units(vec!["s".to_string(), "ms".to_string()]));
+        assert!(parse_with_options("- 10s\n- 5ms\n", &units).is_ok());
+        let err = parse_with_options("a: 3parsecs", &units).unwrap_err();
+        assert_eq!(err.to_string(), "Unknown unit \"parsecs\"");
+        let err = parse_with_options("[1s, 2h]", &units).unwrap_err();
+        assert_eq!(err.to_string(), "Unknown unit \"h\"");
+    }
+
+    #[test]
+    fn test_value_round_trip() {
+        let q = Quantity {
+            magnitude: Value::from(512i64),
+            unit: "KiB".to_string(),
+        };
+        let value = Value::from(q.clone());
+        assert_eq!(Quantity::from_value(&value), Some(q));
+        assert_eq!(Quantity::from_value(&Value::from("10s")), None);
+    }
+}
@@ -0,0 +1,324 @@
+//! Edits to documents that leave everything else as it was.
+//!
+//! [`rename_key`] renames a key where it appears in the source text, so
+//! comments, blank lines, and formatting around it are untouched.
+//! Documents are read the way [`format_yay`](crate::format_yay) reads them.
+
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+use crate::encode::encode_yay_key;
+use crate::meh::{MehParser, SpanItem};
+
+/// Rename the key at `path` to `to` everywhere it appears in `source`,
+/// returning the new text and the number of keys renamed.
+///
+/// Each element of `path` names an object member. Arrays are passed
+/// through, so `["servers", "host"]` renames `host` in every item of
+/// `servers`. Keys in block and inline objects are both renamed, and `to`
+/// is quoted if it must be.
+///
+/// Fails if the document cannot be read, or if an object holding the key
+/// already has a member named `to`.
+///
+/// ```
+/// use libyay::refactor::rename_key;
+///
+/// let source = "servers:\n  - host: \"a\" # primary\n  - {host: \"b\"}\n";
+/// let (text, count) = rename_key(source, &["servers", "host"], "hostname").unwrap();
+/// assert_eq!(count, 2);
+/// assert_eq!(
+///     text,
+///     "servers:\n  - hostname: \"a\" # primary\n  - {hostname: \"b\"}\n"
+/// );
+/// ```
+pub fn rename_key(source: &str, path: &[&str], to: &str) -> Result<(String, usize), String> {
+    let mut parser = MehParser::new(source);
+    parser.parse()?;
+    let spans = parser.spans();
+
+    let mut starts = vec![0];
+    starts.extend(source.match_indices('\n').map(|(i, _)| i + 1));
+
+    // Each span's keys from the root, with array items passed through.
+    let mut keys: Vec<Vec<String>> = Vec::with_capacity(spans.len());
+    for span in spans {
+        let mut path = span.parent.map_or_else(Vec::new, |p| keys[p].clone());
+        if let SpanItem::Property(key, _) = &span.item {
+            path.push(unquote(key));
+        }
+        keys.push(path);
+    }
+
+    let mut edits = Edits::default();
+    for (index, span) in spans.iter().enumerate() {
+        let line = span.lines.start;
+        let start = starts[line];
+        let text = source[start..].split('\n').next().unwrap_or("");
+        let text = text.trim_end_matches('\r');
+        let content = content_start(text);
+        let rest = match &span.item {
+            SpanItem::Property(..) => {
+                let Some(key) = key_len(&text[content..]) else {
+                    continue;
+                };
+                let found = &keys[index];
+                if found.len() == path.len() && found.iter().zip(path).all(|(a, b)| a == b) {
+                    let taken = spans.iter().enumerate().any(|(other, s)| {
+                        s.parent == span.parent
+                            && matches!(&s.item, SpanItem::Property(..))
+                            && keys[other].last().is_some_and(|k| k == to)
+                    });
+                    if taken {
+                        return Err(format!("line {}: key {:?} already exists", line + 1, to));
+                    }
+                    edits.rename(start + content..start + content + key, to);
+                    continue;
+                }
+                content + key + 1
+            }
+            SpanItem::ArrayItem(_) | SpanItem::Value(_) => content,
+            SpanItem::Comment => continue,
+        };
+        let found = &keys[index];
+        if found.len() < path.len() && found.iter().zip(path).all(|(a, b)| a == b) {
+            let mut inline = Inline {
+                source,
+                pos: start + rest,
+                to,
+                edits: &mut edits,
+            };
+            inline.value(&path[found.len()..]).map_err(|pos| {
+                let line = starts.partition_point(|&s| s <= pos);
+                format!("line {}: key {:?} already exists", line, to)
+            })?;
+        }
+    }
+
+    let count = edits.0.len();
+    let mut text = source.to_string();
+    for (range, key) in edits.0.into_values().rev() {
+        text.replace_range(range, &key);
+    }
+    Ok((text, count))
+}
+
+/// Replacements by their place in the source, each made once.
+#[derive(Default)]
+struct Edits(BTreeMap<usize, (Range<usize>, String)>);
+
+impl Edits {
+    fn rename(&mut self, range: Range<usize>, to: &str) {
+        self.0.insert(range.start, (range, encode_yay_key(to)));
+    }
+}
+
+/// The key a `MehParser` property holds, without quotes.
+pub(crate) fn unquote(key: &str) -> String {
+    match crate::parse(key) {
+        Ok(crate::Value::String(s)) if key.starts_with(['"', '\'']) => s,
+        _ => key.to_string(),
+    }
+}
+
+/// The offset of a line's content after its indent and `- ` markers.
+fn content_start(line: &str) -> usize {
+    let mut rest = line.trim_start_matches(' ');
+    while let Some(after) = rest.strip_prefix("- ") {
+        rest = after.trim_start_matches(' ');
+    }
+    line.len() - rest.len()
+}
+
+/// The length of the key at the start of `text`, if a colon follows it.
+fn key_len(text: &str) -> Option<usize> {
+    let len = match text.chars().next()? {
+        quote @ ('"' | '\'') => quoted_len(text, quote)?,
+        _ => text.find(|c: char| c == ':' || c.is_whitespace())?,
+    };
+    text[len..].trim_start().starts_with(':').then_some(len)
+}
+
+/// The length of the quoted string at the start of `text`.
+fn quoted_len(text: &str, quote: char) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in text.char_indices().skip(1) {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            c if c == quote => return Some(i + 1),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// A walk over an inline value in the source, renaming keys on the way.
+struct Inline<'a, 'e> {
+    source: &'a str,
+    pos: usize,
+    to: &'a str,
+    edits: &'e mut Edits,
+}
+
+impl Inline<'_, '_> {
+    fn peek(&self) -> Option<char> {
+        self.source[self.pos..].chars().next()
+    }
+
+    fn skip_spaces(&mut self) {
+        let rest = &self.source[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    /// Rename the keys at `path` within the value here, and move past it.
+    /// Fails with the offset of an object that already has the new key.
+    fn value(&mut self, path: &[&str]) -> Result<(), usize> {
+        self.skip_spaces();
+        match self.peek() {
+            Some('{') => self.object(path),
+            Some('[') => {
+                self.pos += 1;
+                loop {
+                    self.skip_spaces();
+                    match self.peek() {
+                        Some(']') => {
+                            self.pos += 1;
+                            return Ok(());
+                        }
+                        Some(',') => self.pos += 1,
+                        None => return Ok(()),
+                        Some(_) => self.value(path)?,
+                    }
+                }
+            }
+            _ => {
+                self.skip_scalar();
+                Ok(())
+            }
+        }
+    }
+
+    fn object(&mut self, path: &[&str]) -> Result<(), usize> {
+        let open = self.pos;
+        self.pos += 1;
+        let mut found = None;
+        let mut taken = false;
+        loop {
+            self.skip_spaces();
+            match self.peek() {
+                Some('}') => {
+                    self.pos += 1;
+                    break;
+                }
+                Some(',') => self.pos += 1,
+                None => break,
+                Some(_) => {
+                    let start = self.pos;
+                    let Some(len) = key_len(&self.source[start..]) else {
+                        self.skip_scalar();
+                        continue;
+                    };
+                    let key = unquote(&self.source[start..start + len]);
+                    self.pos = start + len;
+                    self.skip_spaces();
+                    self.pos += 1;
+                    taken |= key == self.to;
+                    match path.split_first() {
+                        Some((first, [])) if *first == key => {
+                            found = Some(start..start + len);
+                            self.value(&[])?;
+                        }
+                        Some((first, rest)) if *first == key => self.value(rest)?,
+                        _ => self.value(&[])?,
+                    }
+                }
+            }
+        }
+        if let Some(range) = found {
+            if taken {
+                return Err(open);
+            }
+            self.edits.rename(range, self.to);
+        }
+        Ok(())
+    }
+
+    /// Move past a scalar, or past anything when there is no path left.
+    fn skip_scalar(&mut self) {
+        let start = self.pos;
+        let mut depth = 0usize;
+        while let Some(c) = self.peek() {
+            match c {
+                '"' | '\'' => match quoted_len(&self.source[self.pos..], c) {
+                    Some(len) => {
+                        self.pos += len;
+                        continue;
+                    }
+                    None => break,
+                },
+                '[' | '{' | '<' => depth += 1,
+                ']' | '}' | '>' | ',' | '\n' if depth == 0 && self.pos > start => return,
+                ']' | '}' | '>' if depth > 0 => depth -= 1,
+                _ => {}
+            }
+            self.pos += c.len_utf8();
+        }
+        self.pos = self.source.len();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_keys() {
+        let source = "\
+# config
+name: \"web\"   # the name
+server:
+  name: \"a\"
+  \"old name\": 1
+list:
+  - name: 1
+  - - name: 2
+";
+        let (text, count) = rename_key(source, &["name"], "title").unwrap();
+        assert_eq!(count, 1);
+        assert_eq!(text, source.replacen("name: \"web\"", "title: \"web\"", 1));
+
+        let (text, count) = rename_key(source, &["server", "old name"], "new name").unwrap();
+        assert_eq!(count, 1);
+        assert!(text.contains("  \"new name\": 1\n"));
+
+        let (text, count) = rename_key(source, &["list", "name"], "id").unwrap();
+        assert_eq!(count, 2);
+        assert!(text.contains("  - id: 1\n  - - id: 2\n"));
+
+        let (text, count) = rename_key(source, &["missing"], "x").unwrap();
+        assert_eq!((text.as_str(), count), (source, 0));
+    }
+
+    #[test]
+    fn test_inline_keys() {
+        let source = "a: {b: {c: 1, d: [{c: 2}]}, c: \"{c: 3}\"}\n- {c: 4}\n";
+        let (text, count) = rename_key(source, &["a", "b", "c"], "e").unwrap();
+        assert_eq!(count, 1);
+        assert_eq!(
+            text,
+            "a: {b: {e: 1, d: [{c: 2}]}, c: \"{c: 3}\"}\n- {c: 4}\n"
+        );
+        let (text, _) = rename_key(source, &["a", "b", "d", "c"], "x y").unwrap();
+        assert!(text.contains("d: [{\"x y\": 2}]"));
+    }
+
+    #[test]
+    fn test_existing_key() {
+        let err = rename_key("a: 1\nb: 2\n", &["a"], "b").unwrap_err();
+        assert_eq!(err, "line 1: key \"b\" already exists");
+        let err = rename_key("x:\n  y: {a: 1, b: 2}\n", &["x", "y", "a"], "b").unwrap_err();
+        assert_eq!(err, "line 2: key \"b\" already exists");
+        assert!(rename_key("a: 1\nc: {b: 2}\n", &["a"], "b").is_ok());
+    }
+}
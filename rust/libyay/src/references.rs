@@ -0,0 +1,381 @@
+//! Checking references between values, like foreign keys.
+//!
+//! A [`ReferenceRules`] file lists, for each kind of reference, the path
+//! pattern of the values that refer and the path pattern of the values (or
+//! object keys) they must match. The targets may be in the same document
+//! or in any of the others checked with it:
+//!
+//! ```text
+//! references:
+//!   - from: "services.*.depends_on[]"
+//!     to: "services.*.name"
+//!   - from: "routes[].backend"
+//!     to-keys: "backends"
+//!     in: "backends/*.yay"
+//! ```
+//!
+//! A pattern names object members separated by dots, with `*` for every
+//! member of an object and `[]` for every item of an array. `in` limits
+//! the documents that targets are gathered from to those whose names match
+//! a glob (see [`glob_match`]).
+
+use std::collections::HashSet;
+
+use crate::lint::glob_match;
+use crate::outline::{symbols, Symbol};
+use crate::{encode, Format, Value};
+
+/// One step of a path pattern.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Step {
+    Key(String),
+    AnyMember,
+    AnyItem,
+}
+
+/// A path pattern like `services.*.depends_on[]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern {
+    text: String,
+    steps: Vec<Step>,
+}
+
+impl Pattern {
+    /// Read a pattern, or explain why it is not one.
+    pub fn parse(text: &str) -> Result<Pattern, String> {
+        let mut steps = Vec::new();
+        for segment in text.split('.') {
+            let (key, mut rest) = match segment.find('[') {
+                Some(i) => segment.split_at(i),
+                None => (segment, ""),
+            };
+            match key {
+                "" if steps.is_empty() && !rest.is_empty() => {}
+                "" => return Err(format!("Empty key in pattern \"{}\"", text)),
+                "*" => steps.push(Step::AnyMember),
+                key => steps.push(Step::Key(key.to_string())),
+            }
+            while let Some(after) = rest.strip_prefix("[]") {
+                steps.push(Step::AnyItem);
+                rest = after;
+            }
+            if !rest.is_empty() {
+                return Err(format!(
+                    "Expected \"[]\" after \"{}\" in pattern \"{}\"",
+                    key, text
+                ));
+            }
+        }
+        Ok(Pattern {
+            text: text.to_string(),
+            steps,
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// Visit each value the pattern matches, with its path.
+    fn visit<'v>(&self, value: &'v Value, visit: &mut dyn FnMut(&str, &'v Value)) {
+        matches(value, &self.steps, &mut String::new(), visit);
+    }
+}
+
+fn matches<'v>(
+    value: &'v Value,
+    steps: &[Step],
+    path: &mut String,
+    visit: &mut dyn FnMut(&str, &'v Value),
+) {
+    let Some((step, rest)) = steps.split_first() else {
+        visit(path, value);
+        return;
+    };
+    let len = path.len();
+    match (step, value) {
+        (Step::Key(k), Value::Object(members)) => {
+            if let Some(item) = members.get(k) {
+                push_key(path, k);
+                matches(item, rest, path, visit);
+            }
+        }
+        (Step::AnyMember, Value::Object(members)) => {
+            for (k, item) in members {
+                push_key(path, k);
+                matches(item, rest, path, visit);
+                path.truncate(len);
+            }
+        }
+        (Step::AnyItem, Value::Array(items)) => {
+            for (i, item) in items.iter().enumerate() {
+                path.push_str(&format!("[{}]", i));
+                matches(item, rest, path, visit);
+                path.truncate(len);
+            }
+        }
+        _ => {}
+    }
+    path.truncate(len);
+}
+
+fn push_key(path: &mut String, key: &str) {
+    if !path.is_empty() {
+        path.push('.');
+    }
+    path.push_str(key);
+}
+
+/// What a reference must match.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Target {
+    /// A value at the pattern.
+    Values(Pattern),
+    /// A key of an object at the pattern.
+    Keys(Pattern),
+}
+
+/// One kind of reference.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reference {
+    /// The values that refer.
+    pub from: Pattern,
+    /// What they refer to.
+    pub to: Target,
+    /// A glob that the names of the documents holding the targets must
+    /// match, or `None` for every document.
+    pub within: Option<String>,
+}
+
+/// A document to check, by name.
+pub struct Document<'a> {
+    /// The name used in reports and matched against `in` globs, usually
+    /// the file's path.
+    pub name: &'a str,
+    pub value: &'a Value,
+    /// The YAY source, if any, to find the lines of broken references in.
+    pub source: Option<&'a str>,
+}
+
+/// A value that refers to nothing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BrokenReference {
+    /// The index of the document in those checked.
+    pub document: usize,
+    /// The path of the value, like `services.web.depends_on[1]`.
+    pub path: String,
+    /// The zero-based line of the value, if the document has a source.
+    pub line: Option<usize>,
+    pub value: Value,
+    /// What the value should have matched, like `a services.*.name`.
+    pub expected: String,
+}
+
+/// The kinds of reference to check.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReferenceRules {
+    pub references: Vec<Reference>,
+}
+
+impl ReferenceRules {
+    /// Read rules from YAY source.
+    pub fn from_yay(source: &str) -> Result<ReferenceRules, String> {
+        let value = crate::parse(source).map_err(|e| e.to_string())?;
+        ReferenceRules::from_value(&value)
+    }
+
+    /// Read rules from a parsed value.
+    pub fn from_value(value: &Value) -> Result<ReferenceRules, String> {
+        let entries = value
+            .get("references")
+            .and_then(Value::as_array)
+            .ok_or_else(|| "Reference rules need a \"references\" array".to_string())?;
+        let mut references = Vec::new();
+        for entry in entries {
+            let text = |name: &str| entry.get(name).and_then(Value::as_str);
+            let from = text("from")
+                .ok_or_else(|| "Each reference needs a \"from\" pattern".to_string())?;
+            let to = match (text("to"), text("to-keys")) {
+                (Some(to), None) => Target::Values(Pattern::parse(to)?),
+                (None, Some(to)) => Target::Keys(Pattern::parse(to)?),
+                _ => {
+                    return Err(format!(
+                        "Reference from \"{}\" needs one of \"to\" and \"to-keys\"",
+                        from
+                    ))
+                }
+            };
+            references.push(Reference {
+                from: Pattern::parse(from)?,
+                to,
+                within: text("in").map(String::from),
+            });
+        }
+        Ok(ReferenceRules { references })
+    }
+
+    /// Find every value that matches no target, in document order for each
+    /// kind of reference.
+    ///
+    /// ```
+    /// use libyay::parse;
+    /// use libyay::references::{Document, ReferenceRules};
+    ///
+    /// let rules = ReferenceRules::from_yay(
+    ///     "references:\n  - from: \"services.*.depends_on[]\"\n    to-keys: \"services\"\n",
+    /// )
+    /// .unwrap();
+    /// let source = "services:\n  api:\n    depends_on: [\"db\"]\n  web:\n    depends_on: [\"api\"]\n";
+    /// let value = parse(source).unwrap();
+    /// let broken = rules.check(&[Document { name: "deploy.yay", value: &value, source: Some(source) }]);
+    /// assert_eq!(broken[0].path, "services.api.depends_on[0]");
+    /// assert_eq!(broken[0].line, Some(2));
+    /// assert_eq!(broken.len(), 1);
+    /// ```
+    pub fn check(&self, documents: &[Document<'_>]) -> Vec<BrokenReference> {
+        let outlines: Vec<Option<Vec<Symbol>>> = documents
+            .iter()
+            .map(|document| document.source.map(symbols))
+            .collect();
+        let mut broken = Vec::new();
+        for reference in &self.references {
+            let mut targets = HashSet::new();
+            for document in documents {
+                if let Some(glob) = &reference.within {
+                    if !glob_match(glob, document.name) {
+                        continue;
+                    }
+                }
+                match &reference.to {
+                    Target::Values(pattern) => pattern.visit(document.value, &mut |_, value| {
+                        targets.insert(identity(value));
+                    }),
+                    Target::Keys(pattern) => pattern.visit(document.value, &mut |_, value| {
+                        if let Value::Object(members) = value {
+                            targets.extend(members.keys().map(|k| identity(&Value::from(k))));
+                        }
+                    }),
+                }
+            }
+
+            let expected = match &reference.to {
+                Target::Values(pattern) => format!("a {}", pattern.as_str()),
+                Target::Keys(pattern) => format!("a key of {}", pattern.as_str()),
+            };
+            for (index, document) in documents.iter().enumerate() {
+                reference.from.visit(document.value, &mut |path, value| {
+                    if !targets.contains(&identity(value)) {
+                        broken.push(BrokenReference {
+                            document: index,
+                            path: path.to_string(),
+                            line: outlines[index]
+                                .as_deref()
+                                .and_then(|outline| line_of(outline, path)),
+                            value: value.clone(),
+                            expected: expected.clone(),
+                        });
+                    }
+                });
+            }
+        }
+        broken
+    }
+}
+
+/// The text that identifies a value among the targets.
+fn identity(value: &Value) -> String {
+    encode(value, Format::Yay)
+}
+
+/// The first line of the innermost symbol on `path`, which is the value
+/// itself unless it is inside an inline array or object.
+fn line_of(outline: &[Symbol], path: &str) -> Option<usize> {
+    let mut names = Vec::new();
+    for segment in path.split('.') {
+        let (key, indices) = segment.split_at(segment.find('[').unwrap_or(segment.len()));
+        if !key.is_empty() {
+            names.push(key);
+        }
+        names.extend(indices.split(['[', ']']).filter(|index| !index.is_empty()));
+    }
+    let mut symbols = outline;
+    let mut line = None;
+    for name in names {
+        match symbols.iter().find(|symbol| symbol.name == name) {
+            Some(symbol) => {
+                line = Some(symbol.lines.start);
+                symbols = &symbol.children;
+            }
+            None => break,
+        }
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    #[test]
+    fn test_patterns() {
+        let value = parse("a:\n  x: {b: [1, 2]}\n  y: {b: [3]}\n").unwrap();
+        let mut found = Vec::new();
+        Pattern::parse("a.*.b[]")
+            .unwrap()
+            .visit(&value, &mut |path, value| {
+                found.push((path.to_string(), value.clone()))
+            });
+        assert_eq!(
+            found,
+            [
+                ("a.x.b[0]".to_string(), Value::from(1)),
+                ("a.x.b[1]".to_string(), Value::from(2)),
+                ("a.y.b[0]".to_string(), Value::from(3)),
+            ]
+        );
+        assert!(Pattern::parse("[]").is_ok());
+        assert!(Pattern::parse("a..b").is_err());
+        assert!(Pattern::parse("a[0]").is_err());
+    }
+
+    #[test]
+    fn test_references_across_documents() {
+        let rules = ReferenceRules::from_yay(
+            "references:\n  - from: \"routes[].backend\"\n    to: \"backends[].name\"\n    in: \"backends/*\"\n",
+        )
+        .unwrap();
+        let routes_source = "routes:\n  - path: \"/\"\n    backend: \"web\"\n  - path: \"/api\"\n    backend: \"api\"\n";
+        let routes = parse(routes_source).unwrap();
+        let backends = parse("backends:\n  - name: \"web\"\n").unwrap();
+        let elsewhere = parse("backends:\n  - name: \"api\"\n").unwrap();
+        let broken = rules.check(&[
+            Document {
+                name: "routes.yay",
+                value: &routes,
+                source: Some(routes_source),
+            },
+            Document {
+                name: "backends/a.yay",
+                value: &backends,
+                source: None,
+            },
+            Document {
+                name: "old/b.yay",
+                value: &elsewhere,
+                source: None,
+            },
+        ]);
+        assert_eq!(
+            broken,
+            [BrokenReference {
+                document: 0,
+                path: "routes[1].backend".into(),
+                line: Some(4),
+                value: Value::from("api"),
+                expected: "a backends[].name".into(),
+            }]
+        );
+        assert!(ReferenceRules::from_yay("references:\n  - from: \"a\"\n").is_err());
+    }
+}
@@ -0,0 +1,154 @@
+//! What parsing a document found besides its value.
+
+use crate::Value;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Facts about a document from [`parse_with_report`](crate::parse_with_report),
+/// for tools that need to know what a [`Value`] leaves out, such as whether
+/// rewriting the file from its value would drop comments.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ParseReport {
+    /// Whether the document has any comments.
+    pub had_comments: bool,
+    /// The deepest nesting of arrays and objects: 0 for a scalar root, 1
+    /// for a root array or object of scalars, and so on.
+    pub max_depth: usize,
+    /// The number of lines, counting a last line without a newline.
+    pub lines: usize,
+    /// The size of the document in bytes.
+    pub bytes: usize,
+    /// The extensions to standard YAY that the document used, of those its
+    /// options allowed.
+    pub relaxations: Relaxations,
+    /// The keys that appeared more than once in one object, when parsed
+    /// with [`DuplicateKeys::Warn`](crate::DuplicateKeys::Warn).
+    pub duplicate_keys: Vec<DuplicateKey>,
+    /// The version of YAY a `%yay` directive on the first line declared,
+    /// or `None` for a document without one.
+    pub version: Option<u32>,
+}
+
+/// A key that appears more than once in one object.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DuplicateKey {
+    pub key: String,
+    /// Zero-based line of this (later) occurrence.
+    pub line: usize,
+    pub column: usize,
+    /// Zero-based line of the first occurrence.
+    pub first_line: usize,
+    pub first_column: usize,
+}
+
+/// Extensions to standard YAY turned on by
+/// [`ParseOptions`](crate::ParseOptions).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Relaxations {
+    /// A unit-suffixed quantity, like `10s`.
+    pub quantities: bool,
+    /// A float read as an exact [`Decimal`](crate::Decimal).
+    pub decimals: bool,
+    /// A `<@ "file">` byte literal.
+    pub file_bytes: bool,
+    /// A property with no value, read as null.
+    pub empty_values: bool,
+    /// A hexadecimal float, like `0x1.8p3`.
+    pub hex_floats: bool,
+    /// A `%yay` version directive.
+    pub version_directive: bool,
+}
+
+impl Relaxations {
+    /// Whether any extension was used, so the document is not standard YAY.
+    pub fn any(&self) -> bool {
+        self.quantities
+            || self.decimals
+            || self.file_bytes
+            || self.empty_values
+            || self.hex_floats
+            || self.version_directive
+    }
+}
+
+/// The nesting depth of arrays and objects in `value`.
+pub(crate) fn depth(value: &Value) -> usize {
+    match value {
+        Value::Array(items) => 1 + items.iter().map(depth).max().unwrap_or(0),
+        Value::Object(members) => 1 + members.values().map(depth).max().unwrap_or(0),
+        _ => 0,
+    }
+}
+
+/// The number of values in `value`, counting each array, object, and
+/// scalar.
+pub(crate) fn count_values(value: &Value) -> usize {
+    match value {
+        Value::Array(items) => 1 + items.iter().map(count_values).sum::<usize>(),
+        Value::Object(members) => 1 + members.values().map(count_values).sum::<usize>(),
+        _ => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        parse, parse_reader_with_options, parse_with_options, parse_with_report, ParseOptions,
+        Quantities,
+    };
+
+    #[test]
+    fn test_report() {
+        let options = ParseOptions {
+            quantities: Quantities::Any,
+            ..ParseOptions::default()
+        };
+        let (_, report) = parse_with_report("a: [[1], {b: 10s}]", &options).unwrap();
+        // A quantity is an object of its magnitude and unit.
+        assert_eq!(report.max_depth, 4);
+        assert_eq!((report.lines, report.bytes), (1, 18));
+        assert!(!report.had_comments);
+        assert!(report.relaxations.quantities && report.relaxations.any());
+
+        let (_, report) = parse_with_report("42\n", &options).unwrap();
+        assert_eq!(report.max_depth, 0);
+        assert!(!report.relaxations.any());
+        assert!(report.duplicate_keys.is_empty());
+        assert_eq!(report.version, None);
+    }
+
+    #[test]
+    fn test_version_directive() {
+        // The directive is an extension, off by default.
+        assert!(parse("%yay 1\na: 1").is_err());
+
+        let options = ParseOptions {
+            version_directive: true,
+            ..ParseOptions::default()
+        };
+        let (value, report) = parse_with_report("%yay 1\na: 1\n", &options).unwrap();
+        assert_eq!(value, parse("a: 1").unwrap());
+        assert_eq!((report.version, report.lines), (Some(1), 2));
+        assert!(report.relaxations.version_directive && !report.had_comments);
+
+        let error = parse_with_options("%yay 2\na: 1", &options).unwrap_err();
+        assert_eq!(error.code(), "unsupported_version");
+        assert_eq!(error.to_string(), "Unsupported YAY version 2");
+        for source in ["%yaml 1.2\n", "%yay\n", "%yay 1.0\n", "%YAY 1\n"] {
+            assert!(
+                parse_with_options(source, &options).is_err(),
+                "{:?}",
+                source
+            );
+        }
+        let error = parse_with_options("%yay 1\n", &options).unwrap_err();
+        assert_eq!(error.code(), "no_value_found");
+        assert_eq!(
+            parse_reader_with_options("%yay 1\n- 1\n".as_bytes(), &options).unwrap(),
+            parse("- 1").unwrap()
+        );
+    }
+}
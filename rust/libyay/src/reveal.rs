@@ -0,0 +1,108 @@
+//! Making invisible characters visible in error output.
+
+/// A line of a document with its invisible characters replaced by marks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Revealed {
+    /// The line, with one mark in place of each invisible character, so
+    /// columns are unchanged.
+    pub text: String,
+    /// Each kind of invisible character in the line, in the order they
+    /// first appear.
+    pub found: Vec<Invisible>,
+}
+
+/// A kind of invisible character and the mark that stands for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Invisible {
+    pub ch: char,
+    pub mark: char,
+    /// What the character is, like `"tab"` or `"no-break space"`.
+    pub name: &'static str,
+}
+
+impl Invisible {
+    /// The character as a legend entry, like `→ is a tab (U+0009)`.
+    pub fn describe(&self) -> String {
+        format!("{} is {} (U+{:04X})", self.mark, self.name, self.ch as u32)
+    }
+}
+
+/// Show the tabs, no-break spaces, control characters, and other characters
+/// that print as nothing or as a plain space in `line`, or `None` if it has
+/// none.
+///
+/// # Example
+///
+/// ```
+/// let revealed = libyay::reveal_invisible("\tname:\u{A0}1").unwrap();
+/// assert_eq!(revealed.text, "→name:·1");
+/// assert_eq!(revealed.found[0].describe(), "→ is a tab (U+0009)");
+/// assert!(libyay::reveal_invisible("name: 1").is_none());
+/// ```
+pub fn reveal_invisible(line: &str) -> Option<Revealed> {
+    let mut found: Vec<Invisible> = Vec::new();
+    let text = line
+        .chars()
+        .map(|ch| match invisible(ch) {
+            Some(invisible) => {
+                if !found.iter().any(|f| f.ch == ch) {
+                    found.push(invisible);
+                }
+                invisible.mark
+            }
+            None => ch,
+        })
+        .collect();
+    (!found.is_empty()).then_some(Revealed { text, found })
+}
+
+/// The mark and name for an invisible character.
+fn invisible(ch: char) -> Option<Invisible> {
+    let (mark, name) = match ch {
+        '\t' => ('→', "a tab"),
+        '\r' => ('␍', "a carriage return"),
+        '\u{A0}' => ('·', "a no-break space"),
+        '\u{7F}' => ('␡', "a delete character"),
+        // Control pictures stand for the C0 controls.
+        '\0'..='\u{1F}' => (
+            char::from_u32(0x2400 + ch as u32).unwrap_or('◌'),
+            "a control character",
+        ),
+        '\u{80}'..='\u{9F}' => ('◌', "a control character"),
+        '\u{2000}'..='\u{200A}' | '\u{202F}' | '\u{205F}' | '\u{3000}' => ('·', "a wide space"),
+        '\u{200B}'..='\u{200D}' | '\u{2060}' | '\u{FEFF}' => ('◌', "a zero-width character"),
+        '\u{2028}' | '\u{2029}' => ('◌', "a line or paragraph separator"),
+        '\u{AD}' | '\u{200E}' | '\u{200F}' | '\u{202A}'..='\u{202E}' | '\u{2066}'..='\u{2069}' => {
+            ('◌', "a formatting character")
+        }
+        _ => return None,
+    };
+    Some(Invisible { ch, mark, name })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reveal_invisible() {
+        let revealed = reveal_invisible("a:\u{2003}\"x\u{200B}\u{1}\"\t\t").unwrap();
+        assert_eq!(revealed.text, "a:·\"x◌␁\"→→");
+        assert_eq!(
+            revealed
+                .found
+                .iter()
+                .map(Invisible::describe)
+                .collect::<Vec<_>>(),
+            [
+                "· is a wide space (U+2003)",
+                "◌ is a zero-width character (U+200B)",
+                "␁ is a control character (U+0001)",
+                "→ is a tab (U+0009)",
+            ]
+        );
+        assert_eq!(reveal_invisible("- \"é 日本\""), None);
+    }
+}
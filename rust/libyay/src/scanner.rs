@@ -6,8 +6,15 @@
 //! - Indentation counting
 //! - List marker extraction (the "-" prefix)
 //! - Comment filtering
+//! - Reading the `%yay` version directive
 
 use crate::error::{ParseContext, ParseError, Result};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// The version of YAY this library reads and writes, as a `%yay 1`
+/// directive on the first line of a document declares it.
+pub const YAY_VERSION: u32 = 1;
 
 /// A single line after the scanning phase.
 #[derive(Debug, Clone)]
@@ -30,6 +37,8 @@ pub struct ScanResult {
 
 /// Scan source text into scan lines with validation.
 pub fn scan(source: &str, ctx: &ParseContext) -> Result<ScanResult> {
+    validate_size(source.len(), ctx)?;
+
     // Validate: No BOM allowed
     validate_no_bom(source, ctx)?;
 
@@ -40,8 +49,16 @@ pub fn scan(source: &str, ctx: &ParseContext) -> Result<ScanResult> {
     scan_lines(source, ctx)
 }
 
+/// Check that a document of `len` bytes is within the size limit.
+pub fn validate_size(len: usize, ctx: &ParseContext) -> Result<()> {
+    match ctx.max_bytes {
+        Some(max) if len > max => Err(ParseError::TooLarge(max, ctx.file_suffix())),
+        _ => Ok(()),
+    }
+}
+
 /// Check that the source doesn't start with a UTF-8 BOM.
-fn validate_no_bom(source: &str, ctx: &ParseContext) -> Result<()> {
+pub fn validate_no_bom(source: &str, ctx: &ParseContext) -> Result<()> {
     if source.starts_with('\u{FEFF}') {
         return Err(ParseError::IllegalBom(String::new()).with_location(ctx, 0, 0));
     }
@@ -60,31 +77,33 @@ fn is_allowed_code_point(cp: u32) -> bool {
 
 /// Validate that the source contains no forbidden code points.
 fn validate_code_points(source: &str, ctx: &ParseContext) -> Result<()> {
-    let mut line = 0;
-    let mut col = 0;
-    for ch in source.chars() {
+    for (line_num, line) in source.split('\n').enumerate() {
+        validate_line_code_points(line, line_num, ctx)?;
+    }
+    Ok(())
+}
+
+/// Validate that one line contains no forbidden code points.
+pub fn validate_line_code_points(line: &str, line_num: usize, ctx: &ParseContext) -> Result<()> {
+    for (col, ch) in line.chars().enumerate() {
         let cp = ch as u32;
         if !is_allowed_code_point(cp) {
             // Tabs get their own specific error message.
             if cp == 0x0009 {
-                return Err(ParseError::TabNotAllowed(String::new()).with_location(ctx, line, col));
+                return Err(
+                    ParseError::TabNotAllowed(String::new()).with_location(ctx, line_num, col)
+                );
             }
             // Surrogates get their own specific error message.
             if (0xD800..=0xDFFF).contains(&cp) {
                 return Err(
-                    ParseError::IllegalSurrogate(String::new()).with_location(ctx, line, col)
+                    ParseError::IllegalSurrogate(String::new()).with_location(ctx, line_num, col)
                 );
             }
             return Err(
-                ParseError::ForbiddenCodePoint(cp, String::new()).with_location(ctx, line, col)
+                ParseError::ForbiddenCodePoint(cp, String::new()).with_location(ctx, line_num, col)
             );
         }
-        if ch == '\n' {
-            line += 1;
-            col = 0;
-        } else {
-            col += 1;
-        }
     }
     Ok(())
 }
@@ -94,42 +113,87 @@ fn scan_lines(source: &str, ctx: &ParseContext) -> Result<ScanResult> {
     let mut lines = Vec::new();
     let mut had_comments = false;
 
+    let count = match ctx.observer {
+        Some(_) => source.bytes().filter(|&b| b == b'\n').count() + 1,
+        None => 0,
+    };
     for (line_num, line_str) in source.split('\n').enumerate() {
-        // Validate: No trailing spaces
-        if !line_str.is_empty() && line_str.ends_with(' ') {
-            return Err(ParseError::TrailingSpace(String::new()).with_location(
-                ctx,
-                line_num,
-                line_str.len() - 1,
-            ));
+        ctx.observe_line(line_num, count)?;
+        match scan_line(line_str, line_num, ctx)? {
+            Some(line) => lines.push(line),
+            None => had_comments = true,
         }
+    }
 
-        // Count leading spaces (indent)
-        let indent = count_indent(line_str);
+    Ok(ScanResult {
+        lines,
+        had_comments,
+    })
+}
 
-        let rest = &line_str[indent..];
+/// Scan one line of source, or return `None` for a top-level comment.
+pub fn scan_line(line_str: &str, line_num: usize, ctx: &ParseContext) -> Result<Option<ScanLine>> {
+    ctx.check_cancelled(line_num)?;
 
-        // Skip top-level comments but track that we saw them
-        if rest.starts_with('#') && indent == 0 {
-            had_comments = true;
-            continue;
-        }
-
-        // Extract leader (list marker) and content
-        let (leader, content) = extract_leader(rest, line_num, indent, ctx)?;
+    // Validate: No trailing spaces
+    if !line_str.is_empty() && line_str.ends_with(' ') {
+        return Err(ParseError::TrailingSpace(String::new()).with_location(
+            ctx,
+            line_num,
+            line_str.len() - 1,
+        ));
+    }
 
-        lines.push(ScanLine {
-            line: content.to_string(),
-            indent,
-            leader: leader.to_string(),
+    // A version directive reads as a blank line
+    if line_num == 0 && line_str.starts_with('%') && ctx.version_directive {
+        directive(line_str, ctx)?;
+        return Ok(Some(ScanLine {
+            line: String::new(),
+            indent: 0,
+            leader: String::new(),
             line_num,
-        });
+        }));
     }
 
-    Ok(ScanResult {
-        lines,
-        had_comments,
-    })
+    // Count leading spaces (indent)
+    let indent = count_indent(line_str);
+
+    let rest = &line_str[indent..];
+
+    // Skip top-level comments but track that we saw them
+    if rest.starts_with('#') && indent == 0 {
+        return Ok(None);
+    }
+
+    // Extract leader (list marker) and content
+    let (leader, content) = extract_leader(rest, line_num, indent, ctx)?;
+
+    Ok(Some(ScanLine {
+        line: content.to_string(),
+        indent,
+        leader: leader.to_string(),
+        line_num,
+    }))
+}
+
+/// Read a `%yay <version>` directive, failing for any other directive or
+/// for a version this library does not read.
+fn directive(line_str: &str, ctx: &ParseContext) -> Result<()> {
+    let version = line_str
+        .strip_prefix("%yay ")
+        .filter(|v| !v.is_empty() && v.bytes().all(|b| b.is_ascii_digit()));
+    let Some(version) = version else {
+        return Err(ParseError::InvalidDirective(String::new()).with_location(ctx, 0, 0));
+    };
+    if version.parse() != Ok(YAY_VERSION) {
+        return Err(
+            ParseError::UnsupportedVersion(version.to_string(), String::new())
+                .with_location(ctx, 0, 5),
+        );
+    }
+    ctx.version.set(Some(YAY_VERSION));
+    ctx.relaxed(|r| r.version_directive = true);
+    Ok(())
 }
 
 /// Count the number of leading spaces in a line.
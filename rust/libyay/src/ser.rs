@@ -0,0 +1,470 @@
+//! Serializing Rust types to YAY documents with serde.
+//!
+//! A type is serialized into a [`Value`] first, which is then encoded as
+//! canonical YAY, so object keys come out sorted whatever the field order.
+//!
+//! Every Rust integer type becomes a YAY integer, `u128` and `i128`
+//! included. Bytes serialized with `serde_bytes` become a byte array; a
+//! plain `Vec<u8>` is a sequence and becomes an array of integers. `None`
+//! and `()` are null. Enums follow serde's externally tagged form: a unit
+//! variant is a string, and any other variant is an object with the
+//! variant name as its only key. Map keys must be strings, numbers, or
+//! unit variants, since YAY keys are strings.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+
+use num_bigint::BigInt;
+use serde::ser::{self, Serialize};
+
+use crate::{encode, Format, Map, Value};
+
+/// An error serializing a Rust value to YAY.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    message: String,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl core::error::Error for Error {}
+
+impl ser::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error {
+            message: msg.to_string(),
+        }
+    }
+}
+
+/// Serialize `value` as a canonical YAY document.
+///
+/// # Example
+///
+/// ```
+/// #[derive(serde::Serialize)]
+/// struct Server {
+///     port: u16,
+///     host: String,
+/// }
+///
+/// let server = Server { port: 8080, host: "example.com".to_string() };
+/// let text = libyay::to_string(&server).unwrap();
+/// assert_eq!(text, "{host: \"example.com\", port: 8080}");
+/// ```
+pub fn to_string<T: ?Sized + Serialize>(value: &T) -> Result<String, Error> {
+    Ok(encode(&to_value(value)?, Format::Yay))
+}
+
+/// Serialize `value` into a YAY [`Value`].
+pub fn to_value<T: ?Sized + Serialize>(value: &T) -> Result<Value, Error> {
+    value.serialize(Serializer)
+}
+
+/// A serde `Serializer` that produces a YAY [`Value`].
+pub struct Serializer;
+
+fn tagged(variant: &str, value: Value) -> Value {
+    let mut map = Map::default();
+    map.insert(variant.to_string(), value);
+    Value::Object(map)
+}
+
+impl ser::Serializer for Serializer {
+    type Ok = Value;
+    type Error = Error;
+    type SerializeSeq = SerializeArray;
+    type SerializeTuple = SerializeArray;
+    type SerializeTupleStruct = SerializeArray;
+    type SerializeTupleVariant = SerializeArray;
+    type SerializeMap = SerializeObject;
+    type SerializeStruct = SerializeObject;
+    type SerializeStructVariant = SerializeObject;
+
+    fn serialize_bool(self, v: bool) -> Result<Value, Error> {
+        Ok(Value::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Value, Error> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Value, Error> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Value, Error> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Value, Error> {
+        Ok(Value::Integer(BigInt::from(v)))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Value, Error> {
+        Ok(Value::Integer(BigInt::from(v)))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Value, Error> {
+        self.serialize_u64(v.into())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Value, Error> {
+        self.serialize_u64(v.into())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Value, Error> {
+        self.serialize_u64(v.into())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Value, Error> {
+        Ok(Value::Integer(BigInt::from(v)))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Value, Error> {
+        Ok(Value::Integer(BigInt::from(v)))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Value, Error> {
+        self.serialize_f64(v.into())
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Value, Error> {
+        Ok(Value::Float(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Value, Error> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Value, Error> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Value, Error> {
+        Ok(Value::Bytes(v.to_vec()))
+    }
+
+    fn serialize_none(self) -> Result<Value, Error> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Value, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Value, Error> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Value, Error> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<Value, Error> {
+        Ok(Value::String(variant.to_string()))
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Value, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Value, Error> {
+        Ok(tagged(variant, value.serialize(self)?))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeArray, Error> {
+        Ok(SerializeArray {
+            items: Vec::with_capacity(len.unwrap_or(0)),
+            variant: None,
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeArray, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SerializeArray, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeArray, Error> {
+        Ok(SerializeArray {
+            items: Vec::with_capacity(len),
+            variant: Some(variant),
+        })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<SerializeObject, Error> {
+        Ok(SerializeObject {
+            map: Map::default(),
+            key: None,
+            variant: None,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<SerializeObject, Error> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<SerializeObject, Error> {
+        Ok(SerializeObject {
+            map: Map::default(),
+            key: None,
+            variant: Some(variant),
+        })
+    }
+}
+
+/// Collects the items of a sequence, tuple, or tuple variant.
+pub struct SerializeArray {
+    items: Vec<Value>,
+    variant: Option<&'static str>,
+}
+
+impl SerializeArray {
+    fn push<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        self.items.push(value.serialize(Serializer)?);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Value, Error> {
+        let array = Value::Array(self.items);
+        Ok(match self.variant {
+            Some(variant) => tagged(variant, array),
+            None => array,
+        })
+    }
+}
+
+impl ser::SerializeSeq for SerializeArray {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for SerializeArray {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeArray {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for SerializeArray {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
+
+/// Collects the members of a map, struct, or struct variant.
+pub struct SerializeObject {
+    map: Map,
+    key: Option<String>,
+    variant: Option<&'static str>,
+}
+
+impl SerializeObject {
+    fn finish(self) -> Result<Value, Error> {
+        let object = Value::Object(self.map);
+        Ok(match self.variant {
+            Some(variant) => tagged(variant, object),
+            None => object,
+        })
+    }
+}
+
+impl ser::SerializeMap for SerializeObject {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), Error> {
+        let key = match key.serialize(Serializer)? {
+            Value::String(s) => s,
+            Value::Integer(n) => n.to_string(),
+            Value::Bool(b) => b.to_string(),
+            _ => return Err(ser::Error::custom("map key must be a string or a number")),
+        };
+        self.key = Some(key);
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        let key = self
+            .key
+            .take()
+            .expect("serialize_value called before serialize_key");
+        self.map.insert(key, value.serialize(Serializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStruct for SerializeObject {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.map
+            .insert(key.to_string(), value.serialize(Serializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeObject {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.map
+            .insert(key.to_string(), value.serialize(Serializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::from_str;
+    use serde::{Deserialize, Serialize};
+    use std::collections::BTreeMap;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Config {
+        name: String,
+        port: u16,
+        ratio: f64,
+        tags: Vec<String>,
+        comment: Option<String>,
+        #[serde(with = "serde_bytes")]
+        key: Vec<u8>,
+        modes: Vec<Mode>,
+        limits: BTreeMap<u32, i128>,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    enum Mode {
+        Fast,
+        Limited { rate: u32 },
+        Pair(i8, bool),
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let config = Config {
+            name: "demo".to_string(),
+            port: 8080,
+            ratio: 0.5,
+            tags: vec!["a".to_string(), "b".to_string()],
+            comment: None,
+            key: vec![0xca, 0xfe],
+            modes: vec![Mode::Fast, Mode::Limited { rate: 5 }, Mode::Pair(-1, true)],
+            limits: BTreeMap::from([(1, i128::MIN), (20, 7)]),
+        };
+        let text = to_string(&config).unwrap();
+        assert!(text.starts_with("comment: null\nkey: <cafe>\n"), "{}", text);
+        assert!(text.contains("limits: {1: -170141183460469231731687303715884105728, 20: 7}\n"));
+        assert!(text.contains("Pair: [-1, true]"));
+        assert_eq!(from_str::<Config>(&text).unwrap(), config);
+    }
+
+    #[test]
+    fn test_values_and_errors() {
+        assert_eq!(
+            to_value(&u128::MAX).unwrap(),
+            Value::Integer(u128::MAX.into())
+        );
+        assert_eq!(to_value(&()).unwrap(), Value::Null);
+        assert_eq!(to_string(&vec![1u8, 2]).unwrap(), "[1, 2]");
+
+        let err = to_string(&BTreeMap::from([((1, 2), 3)])).unwrap_err();
+        assert_eq!(err.to_string(), "map key must be a string or a number");
+    }
+}
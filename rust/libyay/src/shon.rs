@@ -7,11 +7,10 @@
 //! See `SHON.md` for the full specification.
 
 use num_bigint::BigInt;
-use std::collections::HashMap;
 use std::fs;
 use std::str::FromStr;
 
-use crate::Value;
+use crate::{Map, Value};
 
 /// Error type for SHON parsing.
 #[derive(Debug, Clone, PartialEq)]
@@ -34,7 +33,7 @@ pub fn parse_shon_bracket(args: &[String]) -> Result<(Value, usize), ShonError>
     }
     match args[0].as_str() {
         "[]" => Ok((Value::Array(Vec::new()), 1)),
-        "[--]" => Ok((Value::Object(HashMap::new()), 1)),
+        "[--]" => Ok((Value::Object(Map::new()), 1)),
         "[" => {
             let mut pos = 1; // skip opening [
             let (value, consumed) = parse_bracket_contents(args, &mut pos)?;
@@ -96,7 +95,7 @@ fn parse_bracket_contents(args: &[String], pos: &mut usize) -> Result<(Value, us
     // Check for empty object `[--]`
     if *pos < args.len() && args[*pos] == "--]" {
         *pos += 1;
-        return Ok((Value::Object(HashMap::new()), *pos));
+        return Ok((Value::Object(Map::new()), *pos));
     }
 
     // Check for `[]` (empty array as single token)
@@ -132,7 +131,7 @@ fn is_object_key(token: &str) -> bool {
 
 /// Parse object contents: `--key value --key value ... ]`
 fn parse_object_contents(args: &[String], pos: &mut usize) -> Result<(Value, usize), ShonError> {
-    let mut map = HashMap::new();
+    let mut map = Map::new();
 
     loop {
         if *pos >= args.len() {
@@ -206,7 +205,7 @@ fn parse_value(args: &[String], pos: &mut usize) -> Result<Value, ShonError> {
         // `[--]` as single token
         "[--]" => {
             *pos += 1;
-            Ok(Value::Object(HashMap::new()))
+            Ok(Value::Object(Map::new()))
         }
         // String escape
         "--" => {
@@ -678,7 +677,7 @@ mod tests {
     fn test_empty_object() {
         let a = args(&["[--]"]);
         let (val, _) = parse_shon_bracket(&a).unwrap();
-        assert_eq!(val, Value::Object(HashMap::new()));
+        assert_eq!(val, Value::Object(Map::new()));
     }
 
     #[test]
@@ -786,6 +785,7 @@ mod tests {
     // ---- Mixed content ----
 
     #[test]
+    #[allow(clippy::approx_constant)]
     fn test_array_with_mixed_types() {
         let a = args(&["[", "hello", "42", "3.14", "-t", "-n", "-x", "ff", "]"]);
         let (val, _) = parse_shon_bracket(&a).unwrap();
@@ -0,0 +1,580 @@
+//! Values annotated with where they appear in the source.
+//!
+//! [`parse_with_spans`](crate::parse_with_spans) returns a [`Spanned`]
+//! tree: the document's [`Value`], with the byte range, line, and column of
+//! every array item, object member, and key, so that a tool checking a
+//! document can point at the exact text of a value it rejects.
+//!
+//! The document is parsed first, so errors and values are the same as
+//! [`parse_with_options`](crate::parse_with_options) gives. The spans are
+//! then found by reading the source again alongside the value.
+//! The parts of a value that have no text of their own, such as the
+//! magnitude and unit of a quantity, share the span of the text they came
+//! from.
+
+use std::ops::Range;
+
+use indexmap::IndexMap;
+
+use crate::Value;
+
+/// Where a value or key appears in the source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Span {
+    /// The byte range of the text, from its first character to its last,
+    /// not counting a comment after it.
+    pub bytes: Range<usize>,
+    /// The 0-based line of the first character.
+    pub line: usize,
+    /// The 0-based column of the first character, counted in characters.
+    pub column: usize,
+}
+
+/// A value with its span, and the spans of its items or members.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spanned {
+    pub span: Span,
+    pub node: Node,
+}
+
+/// The contents of a [`Spanned`] value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Node {
+    /// Any value other than an array or object, or one written as a
+    /// scalar, like a quantity.
+    Scalar(Value),
+    Array(Vec<Spanned>),
+    /// The members, in the order of the source.
+    Object(IndexMap<String, Member>),
+}
+
+/// An object member: the span of its key, and its value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Member {
+    pub key: Span,
+    pub value: Spanned,
+}
+
+impl Spanned {
+    /// The member named `key` if this is an object.
+    pub fn get(&self, key: &str) -> Option<&Spanned> {
+        match &self.node {
+            Node::Object(members) => members.get(key).map(|member| &member.value),
+            _ => None,
+        }
+    }
+
+    /// The span of the key of the member named `key` if this is an object.
+    pub fn key_span(&self, key: &str) -> Option<&Span> {
+        match &self.node {
+            Node::Object(members) => members.get(key).map(|member| &member.key),
+            _ => None,
+        }
+    }
+
+    /// The item at `index` if this is an array.
+    pub fn item(&self, index: usize) -> Option<&Spanned> {
+        match &self.node {
+            Node::Array(items) => items.get(index),
+            _ => None,
+        }
+    }
+
+    /// Resolve a dotted value path such as `servers[0].port`, as
+    /// [`lint::resolve_path`](crate::lint::resolve_path) does for a value.
+    ///
+    /// ```
+    /// use libyay::{parse_with_spans, ParseOptions};
+    ///
+    /// let source = "servers:\n  - host: \"a\"\n    port: 80\n";
+    /// let spanned = parse_with_spans(source, &ParseOptions::default()).unwrap();
+    /// let port = spanned.resolve("servers[0].port").unwrap();
+    /// assert_eq!((port.span.line, port.span.column), (2, 10));
+    /// assert_eq!(&source[port.span.bytes.clone()], "80");
+    /// ```
+    pub fn resolve(&self, path: &str) -> Option<&Spanned> {
+        if path.is_empty() || path == "(root)" {
+            return Some(self);
+        }
+        let mut current = self;
+        for segment in path.split('.') {
+            let (key, mut indices) = match segment.find('[') {
+                Some(i) => (&segment[..i], &segment[i..]),
+                None => (segment, ""),
+            };
+            if !key.is_empty() {
+                current = current.get(key)?;
+            }
+            while let Some(rest) = indices.strip_prefix('[') {
+                let close = rest.find(']')?;
+                let index: usize = rest[..close].parse().ok()?;
+                current = current.item(index)?;
+                indices = &rest[close + 1..];
+            }
+        }
+        Some(current)
+    }
+
+    /// The value without its spans.
+    pub fn to_value(&self) -> Value {
+        match &self.node {
+            Node::Scalar(value) => value.clone(),
+            Node::Array(items) => Value::Array(items.iter().map(Spanned::to_value).collect()),
+            Node::Object(members) => Value::Object(
+                members
+                    .iter()
+                    .map(|(key, member)| (key.clone(), member.value.to_value()))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// `value` with `span` for itself and everything in it.
+    fn whole(value: &Value, span: &Span) -> Spanned {
+        let node = match value {
+            Value::Array(items) => Node::Array(
+                items
+                    .iter()
+                    .map(|item| Spanned::whole(item, span))
+                    .collect(),
+            ),
+            Value::Object(members) => Node::Object(
+                members
+                    .iter()
+                    .map(|(key, value)| {
+                        let member = Member {
+                            key: span.clone(),
+                            value: Spanned::whole(value, span),
+                        };
+                        (key.clone(), member)
+                    })
+                    .collect(),
+            ),
+            _ => Node::Scalar(value.clone()),
+        };
+        Spanned {
+            span: span.clone(),
+            node,
+        }
+    }
+}
+
+/// Find the spans of `value`, which was parsed from `source`.
+pub(crate) fn locate(source: &str, value: &Value) -> Spanned {
+    let locator = Locator::new(source);
+    match locator.next_content(0) {
+        Some(l) => locator.value(value, l, locator.lines[l].indent, None).0,
+        None => Spanned::whole(value, &locator.span(0, 0)),
+    }
+}
+
+/// A line of the source, by byte offsets.
+struct Line {
+    start: usize,
+    end: usize,
+    indent: usize,
+}
+
+struct Locator<'s> {
+    source: &'s str,
+    lines: Vec<Line>,
+}
+
+impl<'s> Locator<'s> {
+    fn new(source: &'s str) -> Self {
+        let mut lines = Vec::new();
+        let mut start = 0;
+        for text in source.split('\n') {
+            let indent = text.len() - text.trim_start_matches(' ').len();
+            lines.push(Line {
+                start,
+                end: start + text.trim_end().len(),
+                indent,
+            });
+            start += text.len() + 1;
+        }
+        Locator { source, lines }
+    }
+
+    /// The span of the bytes `start..end`.
+    fn span(&self, start: usize, end: usize) -> Span {
+        let line = self
+            .lines
+            .partition_point(|l| l.start <= start)
+            .saturating_sub(1);
+        let line_start = self.lines.get(line).map_or(0, |l| l.start);
+        Span {
+            bytes: start..end,
+            line,
+            column: self.source[line_start..start].chars().count(),
+        }
+    }
+
+    /// The text of line `l` from byte `col` of the line.
+    fn text(&self, l: usize, col: usize) -> &'s str {
+        let line = &self.lines[l];
+        self.source.get(line.start + col..line.end).unwrap_or("")
+    }
+
+    /// The first line from `l` on that is neither blank nor a comment.
+    fn next_content(&self, l: usize) -> Option<usize> {
+        (l..self.lines.len()).find(|&i| {
+            let text = self.text(i, self.lines[i].indent);
+            !text.is_empty() && !text.starts_with('#')
+        })
+    }
+
+    /// The last line of a block that continues from line `l` with lines
+    /// indented past `owner`, or `l - 1` if none does.
+    fn extent(&self, l: usize, owner: Option<usize>) -> usize {
+        let mut last = l.saturating_sub(1);
+        for i in l..self.lines.len() {
+            let line = &self.lines[i];
+            if line.start == line.end || line.indent == line.end - line.start {
+                continue;
+            }
+            if owner.is_some_and(|o| line.indent <= o) {
+                break;
+            }
+            last = i;
+        }
+        last
+    }
+
+    /// Whether line `l` is an array item at column `col`.
+    fn is_item(&self, l: usize, col: usize) -> bool {
+        self.lines[l].indent == col && self.text(l, col).starts_with("- ")
+    }
+
+    /// Locate `value`, whose text starts at byte `col` of line `l`, in a
+    /// block whose lines are indented past `owner`. Returns the spanned
+    /// value and the line after it.
+    fn value(&self, value: &Value, l: usize, col: usize, owner: Option<usize>) -> (Spanned, usize) {
+        let start = self.lines[l].start + col;
+        let text = self.text(l, col);
+        if text.is_empty() || text.starts_with('#') {
+            // The value is on the lines that follow, like a block array or
+            // object, or concatenated strings.
+            let next = self.next_content(l + 1).filter(|&n| {
+                let indent = self.lines[n].indent;
+                owner.is_none_or(|o| indent > o || (indent == o && self.is_item(n, indent)))
+            });
+            let Some(n) = next else {
+                return (Spanned::whole(value, &self.span(start, start)), l + 1);
+            };
+            let indent = self.lines[n].indent;
+            return match value {
+                Value::Array(items) if self.is_item(n, indent) => {
+                    self.block_array(value, items, n, indent)
+                }
+                Value::Object(_) => self.block_object(value, n, indent, owner),
+                _ => {
+                    let last = self.extent(n, owner);
+                    let span = self.span(self.lines[n].start + indent, self.lines[last].end);
+                    (Spanned::whole(value, &span), last + 1)
+                }
+            };
+        }
+        match value {
+            Value::Array(items) if text.starts_with("- ") => {
+                return self.block_array(value, items, l, col);
+            }
+            Value::Object(_) if !text.starts_with('{') && colon(text).is_some() => {
+                return self.block_object(value, l, col, owner);
+            }
+            _ => {}
+        }
+        if text.starts_with('`') || text.starts_with('>') {
+            let last = self.extent(l + 1, owner).max(l);
+            let span = self.span(start, self.lines[last].end);
+            return (Spanned::whole(value, &span), last + 1);
+        }
+        let end = self.lines[l].end;
+        let spanned = self.inline(value, start, end, true).unwrap_or_else(|| {
+            let text = strip_comment(text);
+            Spanned::whole(value, &self.span(start, start + text.len()))
+        });
+        (spanned, l + 1)
+    }
+
+    /// Locate a block array whose first item is at byte `col` of line `l`.
+    fn block_array(
+        &self,
+        value: &Value,
+        items: &[Value],
+        l: usize,
+        col: usize,
+    ) -> (Spanned, usize) {
+        let start = self.lines[l].start + col;
+        let mut spanned = Vec::new();
+        let mut end = start;
+        let mut i = l;
+        let mut next = l + 1;
+        while let Some(item) = items.get(spanned.len()) {
+            let (item, after) = self.value(item, i, col + 2, Some(col));
+            end = end.max(item.span.bytes.end);
+            spanned.push(item);
+            next = after;
+            match self.next_content(after) {
+                Some(n) if self.is_item(n, col) => i = n,
+                _ => break,
+            }
+        }
+        let span = self.span(start, end);
+        if spanned.len() < items.len() {
+            return (Spanned::whole(value, &span), next);
+        }
+        let node = Node::Array(spanned);
+        (Spanned { span, node }, next)
+    }
+
+    /// Locate a block object whose first key is at byte `col` of line `l`.
+    fn block_object(
+        &self,
+        value: &Value,
+        l: usize,
+        col: usize,
+        owner: Option<usize>,
+    ) -> (Spanned, usize) {
+        let start = self.lines[l].start + col;
+        let mut found = IndexMap::new();
+        let mut end = start;
+        let mut i = l;
+        let mut next = l + 1;
+        loop {
+            let text = self.text(i, col);
+            let Some(colon) = colon(text) else { break };
+            let key = text[..colon].trim_end();
+            let key_start = self.lines[i].start + col;
+            let name = key_name(key);
+            let Some(member) = value.get(&name) else {
+                break;
+            };
+            let rest = &text[colon + 1..];
+            let value_col = col + colon + 1 + (rest.len() - rest.trim_start().len());
+            let (member, after) = self.value(member, i, value_col, Some(col));
+            end = end.max(member.span.bytes.end);
+            let key = self.span(key_start, key_start + key.len());
+            found.insert(name, Member { key, value: member });
+            next = after;
+            match self.next_content(after) {
+                Some(n) if self.lines[n].indent == col && !self.is_item(n, col) => i = n,
+                _ => break,
+            }
+        }
+        if found.is_empty() {
+            let last = self.extent(l + 1, owner).max(l);
+            let span = self.span(start, self.lines[last].end);
+            return (Spanned::whole(value, &span), last + 1);
+        }
+        (self.object(value, found, self.span(start, end)), next)
+    }
+
+    /// The spanned object of `value`, given the members found in the
+    /// source; any others take the span of the whole object.
+    fn object(&self, value: &Value, mut found: IndexMap<String, Member>, span: Span) -> Spanned {
+        let Value::Object(members) = value else {
+            return Spanned::whole(value, &span);
+        };
+        let node = Node::Object(
+            members
+                .iter()
+                .map(|(key, value)| {
+                    let member = found.swap_remove(key).unwrap_or_else(|| Member {
+                        key: span.clone(),
+                        value: Spanned::whole(value, &span),
+                    });
+                    (key.clone(), member)
+                })
+                .collect(),
+        );
+        Spanned { span, node }
+    }
+
+    /// Locate an inline value starting at byte `start` and ending by byte
+    /// `limit`. A value at the `top` of a line may be followed by a comment
+    /// instead of a delimiter.
+    fn inline(&self, value: &Value, start: usize, limit: usize, top: bool) -> Option<Spanned> {
+        let text = &self.source[start..limit];
+        match (text.chars().next()?, value) {
+            ('[', Value::Array(items)) => {
+                let mut spanned = Vec::new();
+                let mut pos = start + 1;
+                loop {
+                    pos = self.skip_spaces(pos, limit);
+                    if self.source[pos..limit].starts_with(']') {
+                        break;
+                    }
+                    let item = self.inline(items.get(spanned.len())?, pos, limit, false)?;
+                    pos = self.skip_spaces(item.span.bytes.end, limit);
+                    spanned.push(item);
+                    if self.source[pos..limit].starts_with(',') {
+                        pos += 1;
+                    }
+                }
+                if spanned.len() != items.len() {
+                    return None;
+                }
+                let span = self.span(start, pos + 1);
+                let node = Node::Array(spanned);
+                Some(Spanned { span, node })
+            }
+            ('{', Value::Object(_)) => {
+                let mut found = IndexMap::new();
+                let mut pos = start + 1;
+                loop {
+                    pos = self.skip_spaces(pos, limit);
+                    let rest = &self.source[pos..limit];
+                    if rest.starts_with('}') {
+                        break;
+                    }
+                    let colon = colon(rest)?;
+                    let key = rest[..colon].trim_end();
+                    let name = key_name(key);
+                    let member = value.get(&name)?;
+                    let value_start = self.skip_spaces(pos + colon + 1, limit);
+                    let member = self.inline(member, value_start, limit, false)?;
+                    let key = self.span(pos, pos + key.len());
+                    pos = self.skip_spaces(member.span.bytes.end, limit);
+                    found.insert(name, Member { key, value: member });
+                    if self.source[pos..limit].starts_with(',') {
+                        pos += 1;
+                    }
+                }
+                Some(self.object(value, found, self.span(start, pos + 1)))
+            }
+            (quote @ ('"' | '\''), _) => {
+                let mut escaped = false;
+                let close = text[1..].char_indices().find(|&(_, c)| {
+                    let found = c == quote && !escaped;
+                    escaped = quote == '"' && c == '\\' && !escaped;
+                    found
+                })?;
+                Some(Spanned::whole(
+                    value,
+                    &self.span(start, start + close.0 + 2),
+                ))
+            }
+            ('<', _) => {
+                let close = text.find('>')?;
+                Some(Spanned::whole(value, &self.span(start, start + close + 1)))
+            }
+            _ => {
+                let len = if top {
+                    strip_comment(text).len()
+                } else {
+                    text.find([',', ']', '}']).unwrap_or(text.len())
+                };
+                let len = text[..len].trim_end().len();
+                Some(Spanned::whole(value, &self.span(start, start + len)))
+            }
+        }
+    }
+
+    fn skip_spaces(&self, pos: usize, limit: usize) -> usize {
+        let rest = &self.source[pos..limit];
+        pos + (rest.len() - rest.trim_start_matches(' ').len())
+    }
+}
+
+/// The byte offset of the first colon outside quotes in `s`.
+fn colon(s: &str) -> Option<usize> {
+    let mut in_double = false;
+    let mut in_single = false;
+    for (i, c) in s.char_indices() {
+        match c {
+            '"' if !in_single => in_double = !in_double,
+            '\'' if !in_double => in_single = !in_single,
+            ':' if !in_double && !in_single => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The name of a bare or quoted key.
+fn key_name(key: &str) -> String {
+    if key.len() >= 2 && key.starts_with('"') && key.ends_with('"') {
+        return key[1..key.len() - 1]
+            .replace("\\\"", "\"")
+            .replace("\\\\", "\\");
+    }
+    if key.len() >= 2 && key.starts_with('\'') && key.ends_with('\'') {
+        return key[1..key.len() - 1].to_string();
+    }
+    key.to_string()
+}
+
+/// `text` up to a comment, without the spaces before it.
+fn strip_comment(text: &str) -> &str {
+    let mut in_double = false;
+    let mut in_single = false;
+    let mut previous = ' ';
+    for (i, c) in text.char_indices() {
+        match c {
+            '"' if !in_single => in_double = !in_double,
+            '\'' if !in_double => in_single = !in_single,
+            '#' if !in_double && !in_single && previous == ' ' => return text[..i].trim_end(),
+            _ => {}
+        }
+        previous = c;
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse_with_spans, ParseOptions, Quantities};
+
+    #[test]
+    fn test_block_and_inline_spans() {
+        let source = "\
+# servers
+servers:
+  - host: \"a\"  # the first
+    ports: [80, 443]
+  - - 1
+    - 2
+limits: {cpu: 2, name: 'x'}
+notes: `
+  one
+  two
+";
+        let spanned = parse_with_spans(source, &ParseOptions::default()).unwrap();
+        let text = |path: &str| {
+            let span = &spanned.resolve(path).unwrap().span;
+            (&source[span.bytes.clone()], span.line, span.column)
+        };
+        assert_eq!(text("servers[0].host"), ("\"a\"", 2, 10));
+        assert_eq!(text("servers[0].ports[1]"), ("443", 3, 16));
+        assert_eq!(text("servers[1][1]"), ("2", 5, 6));
+        assert_eq!(text("limits.name"), ("'x'", 6, 23));
+        assert_eq!(text("notes"), ("`\n  one\n  two", 7, 7));
+        assert_eq!(
+            text("servers[0]"),
+            ("host: \"a\"  # the first\n    ports: [80, 443]", 2, 4)
+        );
+
+        let key = spanned
+            .resolve("servers[0]")
+            .unwrap()
+            .key_span("ports")
+            .unwrap();
+        assert_eq!(&source[key.bytes.clone()], "ports");
+        assert_eq!(spanned.to_value(), crate::parse(source).unwrap());
+    }
+
+    #[test]
+    fn test_values_without_text_share_a_span() {
+        let options = ParseOptions {
+            quantities: Quantities::Any,
+            ..ParseOptions::default()
+        };
+        let source = "timeout: 30s\n";
+        let spanned = parse_with_spans(source, &options).unwrap();
+        let timeout = spanned.get("timeout").unwrap();
+        assert_eq!(&source[timeout.span.bytes.clone()], "30s");
+        let unit = timeout.get("unit").unwrap();
+        assert_eq!(unit.span, timeout.span);
+    }
+}
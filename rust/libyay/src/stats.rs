@@ -0,0 +1,182 @@
+//! Document statistics.
+//!
+//! [`stats`] summarizes a value: node counts by type, nesting depth, and the
+//! encoded YAY size and node count of every subtree down to a path depth.
+//! [`diff`] compares two summaries path by path, which answers questions
+//! like "what made this config grow by 3MB".
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::{encode, Format, Value};
+
+/// Size and node count of one subtree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PathStats {
+    /// Bytes of canonical YAY encoding.
+    pub size: usize,
+    /// Number of values in the subtree, including the subtree root.
+    pub count: usize,
+}
+
+/// Statistics for a whole document.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Stats {
+    /// Total number of values.
+    pub nodes: usize,
+    /// Deepest nesting level (a scalar root has depth 0).
+    pub max_depth: usize,
+    /// Number of values of each type (`null`, `bool`, `integer`, ...).
+    pub by_type: BTreeMap<&'static str, usize>,
+    /// Per-path subtree statistics, keyed by paths like `servers[0].port`.
+    /// The root is keyed by the empty string.
+    pub paths: BTreeMap<String, PathStats>,
+}
+
+/// The name of a value's type as used in statistics.
+pub fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "bool",
+        Value::Integer(_) => "integer",
+        Value::Float(_) => "float",
+        Value::Decimal(_) => "decimal",
+        Value::String(_) => "string",
+        Value::Bytes(_) => "bytes",
+        Value::DateTime(_) => "datetime",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// Compute statistics for a value, recording per-path entries for paths no
+/// deeper than `max_path_depth` (0 records only the root).
+pub fn stats(value: &Value, max_path_depth: usize) -> Stats {
+    let mut result = Stats::default();
+    collect(value, String::new(), 0, max_path_depth, &mut result);
+    result
+}
+
+fn collect(
+    value: &Value,
+    path: String,
+    depth: usize,
+    max_path_depth: usize,
+    out: &mut Stats,
+) -> usize {
+    out.max_depth = out.max_depth.max(depth);
+    *out.by_type.entry(type_name(value)).or_insert(0) += 1;
+
+    let mut count = 1;
+    match value {
+        Value::Array(arr) => {
+            for (i, item) in arr.iter().enumerate() {
+                count += collect(
+                    item,
+                    format!("{}[{}]", path, i),
+                    depth + 1,
+                    max_path_depth,
+                    out,
+                );
+            }
+        }
+        Value::Object(obj) => {
+            for (key, item) in obj {
+                let child = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                count += collect(item, child, depth + 1, max_path_depth, out);
+            }
+        }
+        _ => {}
+    }
+
+    out.nodes += 1;
+    if depth <= max_path_depth {
+        let size = encode(value, Format::Yay).len();
+        out.paths.insert(path, PathStats { size, count });
+    }
+    count
+}
+
+/// A change in one path's statistics between two documents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathDelta {
+    /// The path (empty for the root).
+    pub path: String,
+    /// Statistics in the old document, if the path existed.
+    pub old: Option<PathStats>,
+    /// Statistics in the new document, if the path exists.
+    pub new: Option<PathStats>,
+}
+
+impl PathDelta {
+    /// Change in encoded size, in bytes.
+    pub fn size_delta(&self) -> i64 {
+        self.new.unwrap_or_default().size as i64 - self.old.unwrap_or_default().size as i64
+    }
+
+    /// Change in node count.
+    pub fn count_delta(&self) -> i64 {
+        self.new.unwrap_or_default().count as i64 - self.old.unwrap_or_default().count as i64
+    }
+}
+
+/// Compare two sets of statistics, returning paths whose size or count
+/// changed, largest absolute size change first.
+pub fn diff(old: &Stats, new: &Stats) -> Vec<PathDelta> {
+    let mut deltas: Vec<PathDelta> = old
+        .paths
+        .keys()
+        .chain(new.paths.keys())
+        .collect::<alloc::collections::BTreeSet<_>>()
+        .into_iter()
+        .map(|path| PathDelta {
+            path: path.clone(),
+            old: old.paths.get(path).copied(),
+            new: new.paths.get(path).copied(),
+        })
+        .filter(|d| d.old != d.new)
+        .collect();
+    deltas.sort_by(|a, b| {
+        b.size_delta()
+            .abs()
+            .cmp(&a.size_delta().abs())
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    deltas
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    #[test]
+    fn test_stats_counts() {
+        let value = parse("a: [1, 2]\nb:\n  c: null\n").unwrap();
+        let s = stats(&value, 1);
+        assert_eq!(s.nodes, 6);
+        assert_eq!(s.max_depth, 2);
+        assert_eq!(s.by_type["integer"], 2);
+        assert_eq!(s.paths[""].count, 6);
+        assert_eq!(s.paths["a"].count, 3);
+        assert!(!s.paths.contains_key("a[0]"));
+    }
+
+    #[test]
+    fn test_diff_orders_by_size() {
+        let old = stats(&parse("a: 1\nb: \"x\"\n").unwrap(), 1);
+        let new = stats(&parse("a: 1\nb: \"xxxxxxxxxx\"\nc: true\n").unwrap(), 1);
+        let deltas = diff(&old, &new);
+        let paths: Vec<&str> = deltas.iter().map(|d| d.path.as_str()).collect();
+        assert_eq!(paths, vec!["", "b", "c"]);
+        assert_eq!(deltas[1].size_delta(), 9);
+        assert_eq!(deltas[2].old, None);
+        assert_eq!(deltas[0].count_delta(), 1);
+    }
+}
@@ -0,0 +1,577 @@
+//! [`encode_events`] and the buffers it spills to temporary files.
+
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use super::Event;
+use crate::encode::{
+    encode_json, encode_json_string, encode_yay_array_item, encode_yay_block_member,
+    encode_yay_inline_bytes, encode_yay_key, encode_yay_member_value, encode_yay_value,
+    encode_yson, is_block_value,
+};
+use crate::{FloatFormat, Format, KeyOrder, Map, Value};
+
+/// Bookkeeping charged against the budget for each event, beyond the bytes
+/// of its strings.
+const EVENT_OVERHEAD: usize = 32;
+
+impl Event {
+    fn size(&self) -> usize {
+        EVENT_OVERHEAD
+            + match self {
+                Event::Scalar(Value::String(s)) | Event::Key(s) => s.len(),
+                Event::Scalar(Value::Bytes(b)) => b.len(),
+                _ => 0,
+            }
+    }
+}
+
+/// Write the document described by `events` to `out` in `format`, holding
+/// roughly `budget` bytes of it in memory at a time.
+///
+/// `format` may be [`Format::Yay`], [`Format::Json`], or [`Format::Yson`].
+/// The output is the same as [`encode`](fn@crate::encode) writes for the whole
+/// document. A value JSON cannot represent stops the conversion with an
+/// `InvalidData` error naming its path, as do malformed events.
+///
+/// # Example
+///
+/// ```
+/// use libyay::stream::{encode_events, Event};
+/// use libyay::{Format, Value};
+///
+/// let events = vec![
+///     Event::StartObject,
+///     Event::Key("b".to_string()),
+///     Event::Scalar(Value::Bool(true)),
+///     Event::Key("a".to_string()),
+///     Event::Scalar(Value::Null),
+///     Event::End,
+/// ];
+/// let mut out = Vec::new();
+/// encode_events(events.into_iter().map(Ok), &mut out, Format::Yay, 1 << 20).unwrap();
+/// assert_eq!(out, b"{a: null, b: true}");
+/// ```
+pub fn encode_events<I, W>(events: I, out: &mut W, format: Format, budget: usize) -> io::Result<()>
+where
+    I: Iterator<Item = io::Result<Event>>,
+    W: Write,
+{
+    if !matches!(format, Format::Yay | Format::Json | Format::Yson) {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("cannot stream {:?} output", format),
+        ));
+    }
+    let mut encoder = Encoder {
+        events: Lookahead {
+            events,
+            buffer: VecDeque::new(),
+        },
+        format,
+        budget,
+        floats: FloatFormat::default(),
+        path: String::new(),
+    };
+    encoder.value(Context::Value(0), out)?;
+    if encoder.events.peek(0)?.is_some() {
+        return Err(malformed("events after the end of the document"));
+    }
+    Ok(())
+}
+
+fn malformed(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+/// An event source that can be read ahead of.
+struct Lookahead<I> {
+    events: I,
+    buffer: VecDeque<Event>,
+}
+
+impl<I: Iterator<Item = io::Result<Event>>> Lookahead<I> {
+    fn peek(&mut self, i: usize) -> io::Result<Option<&Event>> {
+        while self.buffer.len() <= i {
+            match self.events.next().transpose()? {
+                Some(event) => self.buffer.push_back(event),
+                None => return Ok(None),
+            }
+        }
+        Ok(self.buffer.get(i))
+    }
+
+    fn next(&mut self) -> io::Result<Option<Event>> {
+        match self.buffer.pop_front() {
+            Some(event) => Ok(Some(event)),
+            None => self.events.next().transpose(),
+        }
+    }
+
+    fn expect(&mut self) -> io::Result<Event> {
+        self.next()?
+            .ok_or_else(|| malformed("events end in the middle of the document"))
+    }
+}
+
+/// Where a value is written, which decides its layout.
+#[derive(Debug, Clone, Copy)]
+enum Context {
+    /// At the top level or as an object member, at an indent level.
+    Value(usize),
+    /// As an item of a block array at an indent level, after its `- `.
+    Item(usize),
+    /// As the value of an object member whose key is at an indent level.
+    Member(usize),
+}
+
+struct Encoder<I> {
+    events: Lookahead<I>,
+    format: Format,
+    budget: usize,
+    floats: FloatFormat,
+    /// Path to the current value, as in [`Value::compatibility`].
+    path: String,
+}
+
+impl<I: Iterator<Item = io::Result<Event>>> Encoder<I> {
+    /// Write the next value, returning whether YAY puts it on the lines
+    /// after its key.
+    fn value(&mut self, context: Context, out: &mut dyn Write) -> io::Result<bool> {
+        if self.fits()? {
+            let value = self.build()?;
+            self.check(&value)?;
+            out.write_all(self.encode(&value, context).as_bytes())?;
+            return Ok(is_block_value(&value));
+        }
+        match self.events.expect()? {
+            Event::StartArray => self.array(context, out)?,
+            Event::StartObject => self.object(context, out)?,
+            _ => unreachable!("only containers outgrow the budget"),
+        }
+        Ok(true)
+    }
+
+    /// Whether the next value can be held in memory: it fits the budget,
+    /// or it is an array or object that YAY writes on one line, which can
+    /// only hold a few scalars.
+    fn fits(&mut self) -> io::Result<bool> {
+        let (mut depth, mut bytes, mut children, mut nested) = (0usize, 0, 0, false);
+        let mut inline_limit = 0;
+        for i in 0.. {
+            // Malformed events are reported by `build`.
+            let Some(event) = self.events.peek(i)? else {
+                return Ok(true);
+            };
+            bytes += event.size();
+            match event {
+                Event::StartArray | Event::StartObject => {
+                    if depth == 0 {
+                        inline_limit = if *event == Event::StartArray { 5 } else { 3 };
+                    } else {
+                        nested = true;
+                    }
+                    if depth == 1 {
+                        children += 1;
+                    }
+                    depth += 1;
+                }
+                Event::End if depth <= 1 => return Ok(true),
+                Event::End => depth -= 1,
+                Event::Scalar(_) if depth == 0 => return Ok(true),
+                Event::Scalar(_) if depth == 1 => children += 1,
+                _ => {}
+            }
+            if bytes > self.budget && (nested || children > inline_limit) {
+                return Ok(false);
+            }
+        }
+        unreachable!()
+    }
+
+    /// Read the next value whole.
+    fn build(&mut self) -> io::Result<Value> {
+        match self.events.expect()? {
+            Event::Scalar(value) => Ok(value),
+            Event::StartArray => {
+                let mut items = Vec::new();
+                while self.events.peek(0)? != Some(&Event::End) {
+                    items.push(self.build()?);
+                }
+                self.events.next()?;
+                Ok(Value::Array(items))
+            }
+            Event::StartObject => {
+                let mut map = Map::new();
+                loop {
+                    match self.events.expect()? {
+                        Event::Key(key) => {
+                            let value = self.build()?;
+                            map.insert(key, value);
+                        }
+                        Event::End => return Ok(Value::Object(map)),
+                        _ => return Err(malformed("expected an object key")),
+                    }
+                }
+            }
+            Event::Key(_) => Err(malformed("object key outside an object")),
+            Event::End => Err(malformed("end of a container that was not started")),
+        }
+    }
+
+    fn encode(&self, value: &Value, context: Context) -> String {
+        match (self.format, context) {
+            (Format::Yay, Context::Value(indent)) => encode_yay_value(value, indent, &self.floats),
+            (Format::Yay, Context::Item(indent)) => {
+                encode_yay_array_item(value, indent, &self.floats)
+            }
+            (Format::Yay, Context::Member(indent)) if is_block_value(value) => {
+                encode_yay_block_member(value, indent, &self.floats)
+            }
+            (Format::Yay, Context::Member(indent)) => {
+                encode_yay_member_value(value, indent + 1, &self.floats)
+            }
+            (Format::Json, Context::Value(indent)) => encode_json(value, indent, KeyOrder::Bytes),
+            (_, Context::Value(indent)) => encode_yson(value, indent, KeyOrder::Bytes),
+            (_, _) => unreachable!("only YAY has block arrays and members"),
+        }
+    }
+
+    /// Fail on the first value that JSON cannot represent.
+    fn check(&self, value: &Value) -> io::Result<()> {
+        if self.format != Format::Json {
+            return Ok(());
+        }
+        let Some(found) = value.compatibility(Format::Json).into_iter().next() else {
+            return Ok(());
+        };
+        let path = match (self.path.is_empty(), found.path.is_empty()) {
+            (true, true) => "(root)".to_string(),
+            (false, true) => self.path.clone(),
+            (true, false) => found.path,
+            (false, false) if found.path.starts_with('[') => {
+                format!("{}{}", self.path, found.path)
+            }
+            (false, false) => format!("{}.{}", self.path, found.path),
+        };
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("cannot convert to JSON: {}: {}", path, found.reason),
+        ))
+    }
+
+    fn array(&mut self, context: Context, out: &mut dyn Write) -> io::Result<()> {
+        let len = self.path.len();
+        let (item, pad) = match (self.format, context) {
+            (Format::Yay, Context::Value(indent)) => (Context::Item(indent), indent),
+            (Format::Yay, Context::Item(indent)) => (Context::Item(indent + 1), indent + 1),
+            (Format::Yay, Context::Member(indent)) => {
+                out.write_all("  ".repeat(indent + 1).as_bytes())?;
+                (Context::Item(indent + 1), indent + 1)
+            }
+            (_, Context::Value(indent) | Context::Item(indent) | Context::Member(indent)) => {
+                out.write_all(b"[\n")?;
+                (Context::Value(indent + 1), indent)
+            }
+        };
+        let mut index = 0;
+        while self.events.peek(0)? != Some(&Event::End) {
+            self.path.push_str(&format!("[{}]", index));
+            if self.format == Format::Yay {
+                if index > 0 {
+                    write!(out, "\n{}", "  ".repeat(pad))?;
+                }
+                out.write_all(b"- ")?;
+                // A `>` block cannot follow a second bullet on a line.
+                if let (0, Context::Item(_), Some(Event::Scalar(Value::Bytes(b)))) =
+                    (index, context, self.events.peek(0)?)
+                {
+                    out.write_all(encode_yay_inline_bytes(b).as_bytes())?;
+                    self.events.next()?;
+                    self.path.truncate(len);
+                    index += 1;
+                    continue;
+                }
+            } else {
+                if index > 0 {
+                    out.write_all(b",\n")?;
+                }
+                out.write_all("  ".repeat(pad + 1).as_bytes())?;
+            }
+            self.value(item, out)?;
+            self.path.truncate(len);
+            index += 1;
+        }
+        self.events.next()?;
+        if self.format != Format::Yay {
+            write!(out, "\n{}]", "  ".repeat(pad))?;
+        }
+        Ok(())
+    }
+
+    fn object(&mut self, context: Context, out: &mut dyn Write) -> io::Result<()> {
+        let len = self.path.len();
+        let (member, first_pad, pad) = match (self.format, context) {
+            (Format::Yay, Context::Value(indent)) => (Context::Member(indent), indent, indent),
+            (Format::Yay, Context::Item(indent)) => (Context::Member(indent + 1), 0, indent + 1),
+            (Format::Yay, Context::Member(indent)) => {
+                (Context::Member(indent + 1), indent + 1, indent + 1)
+            }
+            (_, Context::Value(indent) | Context::Item(indent) | Context::Member(indent)) => {
+                (Context::Value(indent + 1), indent + 1, indent)
+            }
+        };
+
+        // Encode the members in document order, then sort them.
+        let mut spill = Spill::new(self.budget);
+        let mut members = Vec::new();
+        loop {
+            let key = match self.events.expect()? {
+                Event::Key(key) => key,
+                Event::End => break,
+                _ => return Err(malformed("expected an object key")),
+            };
+            if !self.path.is_empty() {
+                self.path.push('.');
+            }
+            self.path.push_str(&key);
+            let start = spill.len;
+            let block = self.value(member, &mut spill)?;
+            members.push(Member {
+                key,
+                block,
+                start,
+                len: spill.len - start,
+            });
+            self.path.truncate(len);
+        }
+        // The last of repeated keys wins, as when building a `Map`.
+        members.reverse();
+        members.sort_by(|a, b| a.key.cmp(&b.key));
+        members.dedup_by(|later, earlier| later.key == earlier.key);
+
+        if self.format != Format::Yay {
+            out.write_all(b"{\n")?;
+        }
+        for (i, m) in members.iter().enumerate() {
+            if self.format == Format::Yay {
+                if i > 0 {
+                    write!(out, "\n{}", "  ".repeat(pad))?;
+                } else {
+                    out.write_all("  ".repeat(first_pad).as_bytes())?;
+                }
+                let separator = if m.block { ":\n" } else { ": " };
+                write!(out, "{}{}", encode_yay_key(&m.key), separator)?;
+            } else {
+                if i > 0 {
+                    out.write_all(b",\n")?;
+                }
+                write!(
+                    out,
+                    "{}{}: ",
+                    "  ".repeat(first_pad),
+                    encode_json_string(&m.key)
+                )?;
+            }
+            spill.copy_to(m.start, m.len, out)?;
+        }
+        if self.format != Format::Yay {
+            write!(out, "\n{}}}", "  ".repeat(pad))?;
+        }
+        Ok(())
+    }
+}
+
+/// The encoded value of an object member, held in a [`Spill`].
+struct Member {
+    key: String,
+    block: bool,
+    start: u64,
+    len: u64,
+}
+
+/// Distinguishes the spill files of one process.
+static SPILLS: AtomicUsize = AtomicUsize::new(0);
+
+/// A buffer that moves to a temporary file when it outgrows its budget.
+/// The file is removed when the buffer is dropped.
+struct Spill {
+    memory: Vec<u8>,
+    file: Option<(File, PathBuf)>,
+    len: u64,
+    budget: usize,
+}
+
+impl Spill {
+    fn new(budget: usize) -> Self {
+        Spill {
+            memory: Vec::new(),
+            file: None,
+            len: 0,
+            budget,
+        }
+    }
+
+    fn copy_to(&mut self, start: u64, len: u64, out: &mut dyn Write) -> io::Result<()> {
+        match &mut self.file {
+            None => out.write_all(&self.memory[start as usize..(start + len) as usize]),
+            Some((file, _)) => {
+                file.seek(SeekFrom::Start(start))?;
+                let copied = io::copy(&mut Read::by_ref(file).take(len), out)?;
+                if copied < len {
+                    return Err(io::ErrorKind::UnexpectedEof.into());
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Write for Spill {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.file.is_none() && self.memory.len() + buf.len() > self.budget {
+            let path = std::env::temp_dir().join(format!(
+                "yay-spill-{}-{}",
+                std::process::id(),
+                SPILLS.fetch_add(1, Ordering::Relaxed)
+            ));
+            let mut file = File::options()
+                .read(true)
+                .write(true)
+                .create_new(true)
+                .open(&path)?;
+            file.write_all(&self.memory)?;
+            self.memory = Vec::new();
+            self.file = Some((file, path));
+        }
+        match &mut self.file {
+            None => self.memory.extend_from_slice(buf),
+            Some((file, _)) => file.write_all(buf)?,
+        }
+        self.len += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for Spill {
+    fn drop(&mut self) {
+        if let Some((_, path)) = &self.file {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{encode, parse};
+
+    /// The events of a parsed value, keys in document order.
+    fn events(value: &Value, out: &mut Vec<Event>) {
+        match value {
+            Value::Array(items) => {
+                out.push(Event::StartArray);
+                for item in items {
+                    events(item, out);
+                }
+                out.push(Event::End);
+            }
+            Value::Object(map) => {
+                out.push(Event::StartObject);
+                for (key, value) in map {
+                    out.push(Event::Key(key.clone()));
+                    events(value, out);
+                }
+                out.push(Event::End);
+            }
+            scalar => out.push(Event::Scalar(scalar.clone())),
+        }
+    }
+
+    fn stream(value: &Value, format: Format, budget: usize) -> io::Result<String> {
+        let mut list = Vec::new();
+        events(value, &mut list);
+        let mut out = Vec::new();
+        encode_events(list.into_iter().map(Ok), &mut out, format, budget)?;
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    const DOCUMENT: &str = "\
+z: [1, 2, 3, 4, 5, 6]
+b:
+  - - 1
+    - [2, 3]
+    - {x: 1}
+  - c: <cafe>
+    d: [<000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f>]
+    a: [[1], 2]
+    \"quoted key\": \"v\"
+    e: {p: 1, q: 2, r: 3, s: 4}
+  - []
+  - {}
+  - [1.5, 'x']
+  - - <000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f>
+    - <000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f>
+a: {m: null, n: true}
+y: <000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f>
+";
+
+    #[test]
+    fn test_matches_encode_at_every_budget() {
+        let value = parse(DOCUMENT).unwrap();
+        for format in [Format::Yay, Format::Yson] {
+            let expected = encode(&value, format);
+            for budget in [0, 64, 200, 1000, 1 << 20] {
+                assert_eq!(stream(&value, format, budget).unwrap(), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_spills_large_objects() {
+        let mut map = Map::new();
+        for i in (0..2000).rev() {
+            map.insert(format!("key{:05}", i), Value::Array(vec![Value::Null; 6]));
+        }
+        let value = Value::Array(vec![Value::Object(map)]);
+        for format in [Format::Yay, Format::Json] {
+            assert_eq!(
+                stream(&value, format, 4096).unwrap(),
+                encode(&value, format)
+            );
+        }
+    }
+
+    #[test]
+    fn test_repeated_keys_and_errors() {
+        let list = vec![
+            Event::StartObject,
+            Event::Key("k".to_string()),
+            Event::Scalar(Value::Integer(1.into())),
+            Event::Key("k".to_string()),
+            Event::Scalar(Value::Integer(2.into())),
+            Event::End,
+        ];
+        let mut out = Vec::new();
+        encode_events(list.into_iter().map(Ok), &mut out, Format::Yay, 0).unwrap();
+        assert_eq!(out, b"{k: 2}");
+
+        let value = parse("list: [1.5, 2.5, 3.5, 4.5, 5.5, [<00>]]").unwrap();
+        let err = stream(&value, Format::Json, 0).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "cannot convert to JSON: list[5][0]: byte array"
+        );
+
+        let truncated = vec![Event::StartArray, Event::Scalar(Value::Null)];
+        let err = encode_events(truncated.into_iter().map(Ok), &mut out, Format::Yay, 0);
+        assert_eq!(err.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,38 @@
+//! Encoding documents too large to hold in memory.
+//!
+//! [`encode_events`] takes a document as a sequence of [`Event`]s and writes
+//! the same text [`encode`](fn@crate::encode) would, without building the whole
+//! [`Value`]. Each value is held in memory only if it fits the memory
+//! budget; a larger array or object is written a member at a time instead.
+//!
+//! Canonical output sorts object keys, so the members of a large object
+//! are encoded into a buffer that moves to a temporary file once it
+//! outgrows the budget, and are copied out in key order when the object
+//! ends. Each large object that is open at once has its own buffer, and
+//! its keys stay in memory. Strings and byte arrays are always held whole.
+
+use alloc::string::String;
+
+use crate::Value;
+
+#[cfg(feature = "std")]
+mod encoder;
+
+#[cfg(feature = "std")]
+pub use encoder::encode_events;
+
+/// One step through a document, in document order.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    /// A value that is not an array or object.
+    Scalar(Value),
+    /// The start of an array, followed by its items and [`Event::End`].
+    StartArray,
+    /// The start of an object, followed by a [`Event::Key`] and a value for
+    /// each member, and [`Event::End`].
+    StartObject,
+    /// The key of the next object member.
+    Key(String),
+    /// The end of the innermost array or object.
+    End,
+}
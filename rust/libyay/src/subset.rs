@@ -0,0 +1,143 @@
+//! Keeping a few items of long arrays.
+//!
+//! A [`Subset`] cuts every array longer than its limit down to that many
+//! items, so the shape of a document with millions of records can be seen
+//! at a glance. [`Subset::apply`] reports each array it cut and how long it
+//! was.
+
+use crate::Value;
+
+/// Which items of a long array to keep.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Subset {
+    /// The first `n` items.
+    Head(usize),
+    /// The last `n` items.
+    Tail(usize),
+    /// `n` items spread evenly from first to last. The same array always
+    /// gives the same sample.
+    Sample(usize),
+}
+
+/// An array that [`Subset::apply`] cut short.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Truncation {
+    /// Path to the array, like `servers[0].ports` (empty for the root).
+    /// Indices are those of the original arrays.
+    pub path: String,
+    /// How many items the array had.
+    pub length: usize,
+}
+
+impl Subset {
+    /// The number of items kept from a long array.
+    pub fn limit(&self) -> usize {
+        match *self {
+            Subset::Head(n) | Subset::Tail(n) | Subset::Sample(n) => n,
+        }
+    }
+
+    /// Cut every array in `value` longer than the limit, including arrays
+    /// inside the items kept, and return the arrays cut in document order.
+    ///
+    /// ```
+    /// use libyay::subset::Subset;
+    /// use libyay::parse;
+    ///
+    /// let mut value = parse("[1, 2, 3, 4, 5]").unwrap();
+    /// let cut = Subset::Sample(3).apply(&mut value);
+    /// assert_eq!(value, parse("[1, 3, 5]").unwrap());
+    /// assert_eq!(cut[0].length, 5);
+    /// ```
+    pub fn apply(&self, value: &mut Value) -> Vec<Truncation> {
+        let mut cut = Vec::new();
+        self.visit(value, &mut String::new(), &mut cut);
+        cut
+    }
+
+    fn visit(&self, value: &mut Value, path: &mut String, cut: &mut Vec<Truncation>) {
+        let len = path.len();
+        match value {
+            Value::Array(arr) => {
+                let indices = self.indices(arr.len());
+                if indices.len() < arr.len() {
+                    cut.push(Truncation {
+                        path: path.clone(),
+                        length: arr.len(),
+                    });
+                }
+                let mut items: Vec<Option<Value>> = arr.drain(..).map(Some).collect();
+                for index in indices {
+                    let mut item = items[index].take().unwrap_or(Value::Null);
+                    path.push_str(&format!("[{}]", index));
+                    self.visit(&mut item, path, cut);
+                    path.truncate(len);
+                    arr.push(item);
+                }
+            }
+            Value::Object(obj) => {
+                for (key, item) in obj.iter_mut() {
+                    if !path.is_empty() {
+                        path.push('.');
+                    }
+                    path.push_str(key);
+                    self.visit(item, path, cut);
+                    path.truncate(len);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// The indices of the items kept from an array of `len` items.
+    fn indices(&self, len: usize) -> Vec<usize> {
+        let n = self.limit();
+        if len <= n {
+            return (0..len).collect();
+        }
+        match *self {
+            Subset::Head(_) => (0..n).collect(),
+            Subset::Tail(_) => (len - n..len).collect(),
+            Subset::Sample(_) if n == 1 => vec![0],
+            Subset::Sample(_) => (0..n).map(|i| i * (len - 1) / (n - 1)).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    #[test]
+    fn test_head_tail_sample() {
+        let source = "[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]";
+        for (subset, expected) in [
+            (Subset::Head(3), "[0, 1, 2]"),
+            (Subset::Tail(3), "[7, 8, 9]"),
+            (Subset::Sample(4), "[0, 3, 6, 9]"),
+            (Subset::Sample(1), "[0]"),
+            (Subset::Head(0), "[]"),
+            (Subset::Head(10), source),
+        ] {
+            let mut value = parse(source).unwrap();
+            subset.apply(&mut value);
+            assert_eq!(value, parse(expected).unwrap(), "{:?}", subset);
+        }
+    }
+
+    #[test]
+    fn test_nested_paths() {
+        let mut value = parse(
+            "runs:\n  - points: [1, 2, 3]\n  - points: [4]\n  - points: [5, 6, 7, 8]\nname: \"x\"\n",
+        )
+        .unwrap();
+        let cut = Subset::Tail(2).apply(&mut value);
+        assert_eq!(
+            value,
+            parse("runs:\n  - points: [4]\n  - points: [7, 8]\nname: \"x\"\n").unwrap()
+        );
+        let paths: Vec<(&str, usize)> = cut.iter().map(|t| (t.path.as_str(), t.length)).collect();
+        assert_eq!(paths, [("runs", 3), ("runs[2].points", 4)]);
+    }
+}
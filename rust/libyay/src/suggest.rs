@@ -0,0 +1,160 @@
+//! Suggestions for bare words in error output.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Spellings from other languages and formats for the keywords.
+const ALIASES: &[(&str, &str)] = &[
+    ("nil", "null"),
+    ("none", "null"),
+    ("undefined", "null"),
+    ("~", "null"),
+    ("inf", "infinity"),
+    ("+inf", "infinity"),
+    ("+infinity", "infinity"),
+    ("-inf", "-infinity"),
+    (".inf", "infinity"),
+    ("-.inf", "-infinity"),
+    (".nan", "nan"),
+];
+
+const KEYWORDS: &[&str] = &["null", "true", "false", "nan", "infinity", "-infinity"];
+
+/// Advice for a bare word where a value should be, like `ture` or `hello`:
+/// the keyword it is most likely a misspelling of, or else the word as a
+/// quoted string. `None` if `word` does not begin like a word.
+///
+/// # Example
+///
+/// ```
+/// use libyay::suggest_bare_word;
+///
+/// assert_eq!(suggest_bare_word("ture").as_deref(), Some("did you mean true?"));
+/// assert_eq!(suggest_bare_word("Infinity").as_deref(), Some("did you mean infinity?"));
+/// assert_eq!(
+///     suggest_bare_word("hello world").as_deref(),
+///     Some("strings must be quoted, as in \"hello world\""),
+/// );
+/// assert_eq!(suggest_bare_word("*"), None);
+/// ```
+pub fn suggest_bare_word(word: &str) -> Option<String> {
+    let first = word.chars().next()?;
+    if !(first.is_alphabetic() || first == '_' || "-+.~".contains(first)) {
+        return None;
+    }
+    if let Some(keyword) = nearest_keyword(word) {
+        return Some(format!("did you mean {}?", keyword));
+    }
+    if !first.is_alphabetic() && first != '_' {
+        return None;
+    }
+    if word.contains(['"', '\\']) {
+        return Some("strings must be quoted".to_string());
+    }
+    Some(format!("strings must be quoted, as in \"{}\"", word))
+}
+
+/// The keyword `word` is a case, alias, or close misspelling of.
+fn nearest_keyword(word: &str) -> Option<&'static str> {
+    let lower = word.to_lowercase();
+    if let Some(&(_, keyword)) = ALIASES.iter().find(|(alias, _)| *alias == lower) {
+        return Some(keyword);
+    }
+    // One edit in a short word and two in a longer one, so that `nul` and
+    // `fasle` are near misses but `no` and `file` are words.
+    let allowed = if lower.chars().count() <= 4 { 1 } else { 2 };
+    KEYWORDS
+        .iter()
+        .map(|keyword| (distance(&lower, keyword), *keyword))
+        .filter(|&(d, _)| d <= allowed)
+        .min_by_key(|&(d, _)| d)
+        .map(|(_, keyword)| keyword)
+}
+
+/// The number of insertions, deletions, substitutions, and swaps of
+/// neighboring characters that turn `a` into `b`.
+fn distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![(0..=b.len()).collect::<Vec<_>>()];
+    for i in 1..=a.len() {
+        let mut row = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            row[j] = (rows[i - 1][j] + 1)
+                .min(row[j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                row[j] = row[j].min(rows[i - 2][j - 2] + 1);
+            }
+        }
+        rows.push(row);
+    }
+    rows[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suggest_bare_word() {
+        let suggest = |word| suggest_bare_word(word).unwrap_or_default();
+        for (word, keyword) in [
+            ("ture", "true"),
+            ("True", "true"),
+            ("flase", "false"),
+            ("fasle", "false"),
+            ("fals", "false"),
+            ("nul", "null"),
+            ("NULL", "null"),
+            ("None", "null"),
+            ("NaN", "nan"),
+            ("-Infinity", "-infinity"),
+            ("infinty", "infinity"),
+            ("-inf", "-infinity"),
+        ] {
+            assert_eq!(
+                suggest(word),
+                format!("did you mean {}?", keyword),
+                "{}",
+                word
+            );
+        }
+        assert_eq!(suggest("no"), "strings must be quoted, as in \"no\"");
+        assert_eq!(suggest("file"), "strings must be quoted, as in \"file\"");
+        assert_eq!(suggest("say \"hi\""), "strings must be quoted");
+        assert_eq!(suggest_bare_word("-x"), None);
+        assert_eq!(suggest_bare_word(""), None);
+    }
+
+    #[test]
+    fn test_error_suggestion() {
+        let options = crate::ParseOptions {
+            filename: Some("a.yay".into()),
+            ..crate::ParseOptions::default()
+        };
+        let suggest = |source| {
+            crate::parse_with_options(source, &options)
+                .unwrap_err()
+                .suggestion(source)
+        };
+        assert_eq!(suggest("ture").as_deref(), Some("did you mean true?"));
+        assert_eq!(
+            suggest("- 1\n- - nul\n").as_deref(),
+            Some("did you mean null?")
+        );
+        assert_eq!(
+            suggest("a:\n  b: hello world  # greeting\n").as_deref(),
+            Some("strings must be quoted, as in \"hello world\"")
+        );
+        assert_eq!(
+            suggest("a:\n  - x: -Infinity\n").as_deref(),
+            Some("did you mean -infinity?")
+        );
+        assert_eq!(suggest("*"), None);
+        assert_eq!(crate::parse("ture").unwrap_err().suggestion("ture"), None);
+    }
+}
@@ -0,0 +1,356 @@
+//! Generated values and documents for property tests.
+//!
+//! [`Gen`] makes [`Value`]s from a seed, along with the YAY documents that
+//! encode them, as written or with one small edit that may make them
+//! invalid. A seed makes the same value on every platform, so a failing case
+//! is reproduced from its seed alone. [`check`] runs a property over a range
+//! of seeds, and [`round_trips`] and [`formats_idempotently`] are the
+//! properties the fixture suite asserts:
+//!
+//! ```
+//! use libyay::testing::{check, formats_idempotently, round_trips};
+//!
+//! check(100, |gen| round_trips(&gen.value()));
+//! check(100, |gen| formats_idempotently(&gen.document()));
+//! ```
+//!
+//! For proptest, [`values`], [`documents`] and [`near_valid_documents`] are
+//! strategies for the same cases, which shrink toward smaller values rather
+//! than other seeds:
+//!
+//! ```
+//! use libyay::testing::{round_trips, values};
+//! use proptest::prelude::*;
+//!
+//! proptest!(|(value in values())| {
+//!     prop_assert_eq!(round_trips(&value), Ok(()));
+//! });
+//! ```
+//!
+//! Requires the `testing` feature.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use num_bigint::BigInt;
+use proptest::collection::vec;
+use proptest::prelude::{any, prop_oneof, Just, Strategy};
+use proptest::sample::select;
+
+use crate::{encode, format_yay, parse, parse_yson, Format, Map, Value};
+
+/// Characters that the encoders quote, escape, or break lines at.
+const CHARS: &[char] = &[
+    'a', 'z', 'A', '0', '9', ' ', ' ', '_', '-', '.', ':', '#', '"', '\'', '\\', '`', '/', '{',
+    '}', '[', ']', '<', '>', '|', '\n', '\t', '\r', '\u{7f}', '\u{a0}', 'é', '中', '😀',
+];
+
+/// Characters that [`Gen::near_valid_document`] inserts: the ones YAY's
+/// syntax turns on.
+const EDITS: &[char] = &[
+    ' ', ' ', '\t', '\n', ':', '-', '#', '"', '\'', '`', '<', '>', '[', ']', '{', '}', ',', '*',
+];
+
+/// A seeded source of values and documents.
+#[derive(Debug, Clone)]
+pub struct Gen {
+    state: u64,
+    depth: u32,
+}
+
+impl Gen {
+    /// A generator for `seed`, making values up to three levels deep.
+    pub fn new(seed: u64) -> Self {
+        // Spread small seeds out; xorshift needs a nonzero state.
+        let state = seed.wrapping_add(1).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+        Gen {
+            state: state.max(1),
+            depth: 3,
+        }
+    }
+
+    /// Make values up to `depth` levels of arrays and objects deep.
+    pub fn with_depth(self, depth: u32) -> Self {
+        Gen { depth, ..self }
+    }
+
+    fn next(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+
+    fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len() as u64) as usize]
+    }
+
+    /// A string of up to a dozen characters, favoring ones that need quoting
+    /// or escaping.
+    pub fn string(&mut self) -> String {
+        (0..self.below(12)).map(|_| *self.pick(CHARS)).collect()
+    }
+
+    /// A value of any type that every text encoder can hold: no decimals
+    /// or dates, which YAY writes as numbers and strings.
+    pub fn value(&mut self) -> Value {
+        self.value_within(self.depth)
+    }
+
+    fn value_within(&mut self, depth: u32) -> Value {
+        let kinds = if depth == 0 { 7 } else { 9 };
+        match self.below(kinds) {
+            0 => Value::Null,
+            1 => Value::Bool(self.below(2) == 1),
+            2 => Value::Integer(BigInt::from(self.next() as i64 >> self.below(64))),
+            3 => Value::Integer(BigInt::from(self.next()) * BigInt::from(self.next()) * -1),
+            4 => {
+                let f = f64::from_bits(self.next());
+                let f = if f.is_nan() {
+                    *self.pick(&[f64::NAN, f64::INFINITY, f64::NEG_INFINITY, -0.0, 0.5])
+                } else {
+                    f
+                };
+                Value::Float(f)
+            }
+            5 => Value::String(self.string()),
+            6 => Value::Bytes((0..self.below(40)).map(|_| self.next() as u8).collect()),
+            7 => Value::Array(
+                (0..self.below(5))
+                    .map(|_| self.value_within(depth - 1))
+                    .collect(),
+            ),
+            _ => {
+                let mut map = Map::new();
+                for _ in 0..self.below(5) {
+                    map.insert(self.string(), self.value_within(depth - 1));
+                }
+                Value::Object(map)
+            }
+        }
+    }
+
+    /// A valid YAY document: a [`value`](Gen::value) as the encoder writes
+    /// it.
+    pub fn document(&mut self) -> String {
+        encode(&self.value(), Format::Yay)
+    }
+
+    /// A [`document`](Gen::document) with one character deleted, doubled,
+    /// or inserted, or one line indented differently. Most no longer parse;
+    /// the rest parse to something else, or the same.
+    ///
+    /// One time in sixteen the document is a single such character alone,
+    /// as an edit of the empty document would be.
+    pub fn near_valid_document(&mut self) -> String {
+        if self.below(16) == 0 {
+            return String::from(*self.pick(EDITS));
+        }
+        let document = self.document();
+        self.edit(&document)
+    }
+
+    fn edit(&mut self, document: &str) -> String {
+        let mut chars: Vec<char> = document.chars().collect();
+        let at = self.below(chars.len() as u64 + 1) as usize;
+        match self.below(4) {
+            0 if at < chars.len() => {
+                chars.remove(at);
+            }
+            1 if at < chars.len() => chars.insert(at, chars[at]),
+            2 => {
+                let line_start = chars[..at]
+                    .iter()
+                    .rposition(|c| *c == '\n')
+                    .map_or(0, |i| i + 1);
+                if chars.get(line_start) == Some(&' ') && self.below(2) == 0 {
+                    chars.remove(line_start);
+                } else {
+                    chars.insert(line_start, ' ');
+                }
+            }
+            _ => chars.insert(at, *self.pick(EDITS)),
+        }
+        chars.into_iter().collect()
+    }
+}
+
+/// A strategy for the values [`Gen::value`] makes, shrinking toward
+/// scalars and shorter arrays, objects and strings.
+pub fn values() -> impl Strategy<Value = Value> {
+    let integer = any::<i64>().prop_map(|i| Value::Integer(BigInt::from(i)));
+    let big = (any::<u64>(), any::<u64>())
+        .prop_map(|(a, b)| Value::Integer(BigInt::from(a) * BigInt::from(b) * -1));
+    let float = any::<f64>().prop_map(|f| Value::Float(if f.is_nan() { f64::NAN } else { f }));
+    let leaf = prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::Bool),
+        integer,
+        big,
+        float,
+        strings().prop_map(Value::String),
+        vec(any::<u8>(), 0..40).prop_map(Value::Bytes),
+    ];
+    leaf.prop_recursive(3, 64, 4, |inner| {
+        prop_oneof![
+            vec(inner.clone(), 0..5).prop_map(Value::Array),
+            vec((strings(), inner), 0..5).prop_map(|entries| {
+                let mut map = Map::default();
+                map.extend(entries);
+                Value::Object(map)
+            }),
+        ]
+    })
+}
+
+fn strings() -> impl Strategy<Value = String> {
+    vec(select(CHARS), 0..12).prop_map(|chars| chars.into_iter().collect())
+}
+
+/// A strategy for valid YAY documents: [`values`] as the encoder writes
+/// them.
+pub fn documents() -> impl Strategy<Value = String> {
+    values().prop_map(|value| encode(&value, Format::Yay))
+}
+
+/// A strategy for the documents [`Gen::near_valid_document`] makes: a
+/// [`documents`] case with one small edit, or a single character of YAY's
+/// syntax alone.
+pub fn near_valid_documents() -> impl Strategy<Value = String> {
+    prop_oneof![
+        1 => select(EDITS).prop_map(String::from),
+        15 => (documents(), any::<u64>()).prop_map(|(document, seed)| Gen::new(seed).edit(&document)),
+    ]
+}
+
+/// Check that `value` encodes as YAY and as YSON to documents that parse
+/// back to it.
+pub fn round_trips(value: &Value) -> Result<(), String> {
+    for format in [Format::Yay, Format::Yson] {
+        let encoded = encode(value, format);
+        let parsed = match format {
+            Format::Yay => parse(&encoded).map_err(|e| e.to_string()),
+            _ => parse_yson(&encoded),
+        };
+        match parsed {
+            Ok(parsed) if parsed == *value => {}
+            Ok(parsed) => {
+                return Err(format!(
+                    "{:?} parses back as {:?} from:\n{}",
+                    value, parsed, encoded
+                ))
+            }
+            Err(e) => {
+                return Err(format!(
+                    "{:?} encodes as unparseable:\n{}\n{}",
+                    value, encoded, e
+                ))
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Check that formatting `text` as MEH gives text that formatting again
+/// leaves unchanged. Text that does not format passes.
+pub fn formats_idempotently(text: &str) -> Result<(), String> {
+    let Ok(once) = format_yay(text) else {
+        return Ok(());
+    };
+    let twice = format_yay(&once).map_err(|e| format!("{} in:\n{}", e, once))?;
+    if once == twice {
+        Ok(())
+    } else {
+        Err(format!(
+            "formatting changes its own output:\n{}\ninto:\n{}",
+            once, twice
+        ))
+    }
+}
+
+/// Run `property` on generators for seeds 1 through `cases`, panicking with
+/// the seed of the first failure.
+pub fn check(cases: u64, mut property: impl FnMut(&mut Gen) -> Result<(), String>) {
+    for seed in 1..=cases {
+        if let Err(e) = property(&mut Gen::new(seed)) {
+            panic!("property fails for Gen::new({}): {}", seed, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::{prop_assert_eq, proptest};
+
+    #[test]
+    fn test_seeds_repeat() {
+        assert_eq!(Gen::new(7).document(), Gen::new(7).document());
+        assert_ne!(Gen::new(7).document(), Gen::new(8).document());
+        assert_eq!(
+            Gen::new(0).with_depth(0).value(),
+            Gen::new(0).with_depth(0).value()
+        );
+    }
+
+    #[test]
+    fn test_values_round_trip() {
+        check(500, |gen| round_trips(&gen.value()));
+    }
+
+    #[test]
+    fn test_documents_format_idempotently() {
+        check(500, |gen| formats_idempotently(&gen.document()));
+    }
+
+    #[test]
+    fn test_near_valid_documents() {
+        let mut failures = 0;
+        check(500, |gen| {
+            if parse(&gen.near_valid_document()).is_err() {
+                failures += 1;
+            }
+            Ok(())
+        });
+        assert!(failures > 0 && failures < 500, "{} of 500 fail", failures);
+    }
+
+    #[test]
+    fn test_near_valid_documents_include_lone_characters() {
+        for edit in ["'", "\"", "`"] {
+            assert!(
+                (1..=2000).any(|seed| Gen::new(seed).near_valid_document() == edit),
+                "no seed makes {:?}",
+                edit
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_strategy_values_round_trip(value in values()) {
+            prop_assert_eq!(round_trips(&value), Ok(()));
+        }
+
+        #[test]
+        fn test_strategy_documents_format_idempotently(document in documents()) {
+            prop_assert_eq!(formats_idempotently(&document), Ok(()));
+        }
+
+        #[test]
+        fn test_strategy_near_valid_documents_do_not_panic(document in near_valid_documents()) {
+            let _ = parse(&document);
+            let _ = format_yay(&document);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "property fails for Gen::new(1)")]
+    fn test_check_names_seed() {
+        check(3, |_| Err("no".into()));
+    }
+}
@@ -0,0 +1,196 @@
+//! Durations and byte sizes spelled as strings.
+//!
+//! Configuration files say `"30s"` and `"10MiB"` far more often than they
+//! say `30000` and `10485760`. [`Value::as_duration`] and
+//! [`Value::as_byte_size`] give every consumer the same reading of those
+//! spellings, and also accept the quantity objects that
+//! [`Quantities`](crate::Quantities) produces.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use core::time::Duration;
+
+use num_bigint::BigUint;
+use num_traits::ToPrimitive;
+
+use crate::{Quantity, Value};
+
+impl Value {
+    /// Returns the duration this value spells, if any.
+    ///
+    /// A duration is a string of one or more non-negative numbers, each
+    /// followed by a unit: `ns`, `us` (or `µs`), `ms`, `s`, `m`, `h`, or
+    /// `d` (24 hours). Parts add up, so `"1h30m"` is 90 minutes; fractions
+    /// are allowed, as in `"1.5s"`, and round down to whole nanoseconds.
+    /// A bare number has no unit and is not a duration. A quantity object
+    /// such as `{magnitude: 30, unit: "s"}` reads the same way.
+    pub fn as_duration(&self) -> Option<Duration> {
+        match self {
+            Value::String(s) => parse_duration(s),
+            Value::Object(_) => {
+                let quantity = Quantity::from_value(self)?;
+                parse_duration(&format!("{}{}", magnitude(&quantity)?, quantity.unit))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the number of bytes this value spells, if any.
+    ///
+    /// A non-negative integer is a count of bytes. A string is a number
+    /// followed by an optional space and unit: `B`, the decimal units `KB`
+    /// (or `kB`), `MB`, `GB`, and `TB` (powers of 1000), or the binary
+    /// units `KiB`, `MiB`, `GiB`, and `TiB` (powers of 1024). Without a
+    /// unit the number is bytes. Fractions are allowed, as in `"1.5 MiB"`,
+    /// and round down to whole bytes. A quantity object such as
+    /// `{magnitude: 10, unit: "MiB"}` reads the same way.
+    pub fn as_byte_size(&self) -> Option<u64> {
+        match self {
+            Value::Integer(n) => n.to_u64(),
+            Value::String(s) => parse_byte_size(s),
+            Value::Object(_) => {
+                let quantity = Quantity::from_value(self)?;
+                parse_byte_size(&format!("{}{}", magnitude(&quantity)?, quantity.unit))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// The magnitude of a quantity as a decimal number.
+fn magnitude(quantity: &Quantity) -> Option<String> {
+    match &quantity.magnitude {
+        Value::Integer(n) => Some(n.to_string()),
+        Value::Float(f) if f.is_finite() => Some(format!("{}", f)),
+        _ => None,
+    }
+}
+
+/// An unsigned decimal number, held exactly as its digits and the number of
+/// them after the point.
+struct Number {
+    digits: BigUint,
+    decimals: u32,
+}
+
+impl Number {
+    /// The number times `scale`, rounded down.
+    fn scaled(&self, scale: u64) -> BigUint {
+        &self.digits * scale / BigUint::from(10u32).pow(self.decimals)
+    }
+}
+
+/// Split a leading unsigned decimal number (`12`, `1.5`, `.5`) off `s`.
+fn split_number(s: &str) -> Option<(Number, &str)> {
+    let end = s
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(s.len());
+    let (number, rest) = s.split_at(end);
+    let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+    if fraction.contains('.') || whole.len() + fraction.len() == 0 {
+        return None;
+    }
+    let digits = BigUint::parse_bytes(format!("0{}{}", whole, fraction).as_bytes(), 10)?;
+    let decimals = u32::try_from(fraction.len()).ok()?;
+    Some((Number { digits, decimals }, rest))
+}
+
+/// Parse a duration such as `30s`, `5m`, or `1h30m`.
+pub(crate) fn parse_duration(s: &str) -> Option<Duration> {
+    let mut rest = s.trim();
+    if rest.is_empty() {
+        return None;
+    }
+    let mut nanos = BigUint::default();
+    while !rest.is_empty() {
+        let (number, after) = split_number(rest)?;
+        let end = after
+            .find(|c: char| c.is_ascii_digit() || c == '.')
+            .unwrap_or(after.len());
+        let scale = match &after[..end] {
+            "ns" => 1,
+            "us" | "µs" => 1_000,
+            "ms" => 1_000_000,
+            "s" => 1_000_000_000,
+            "m" => 60_000_000_000,
+            "h" => 3_600_000_000_000,
+            "d" => 86_400_000_000_000,
+            _ => return None,
+        };
+        // Each part rounds down on its own, as it would written in nanoseconds.
+        nanos += number.scaled(scale);
+        rest = &after[end..];
+    }
+    Some(Duration::from_nanos(nanos.to_u64()?))
+}
+
+/// Parse a byte size such as `512`, `64KB`, `256KiB`, or `1.5 MiB`.
+pub(crate) fn parse_byte_size(s: &str) -> Option<u64> {
+    let (number, unit) = split_number(s.trim())?;
+    let scale: u64 = match unit.trim_start() {
+        "" | "B" => 1,
+        "KB" | "kB" => 1_000,
+        "MB" => 1_000_000,
+        "GB" => 1_000_000_000,
+        "TB" => 1_000_000_000_000,
+        "KiB" => 1 << 10,
+        "MiB" => 1 << 20,
+        "GiB" => 1 << 30,
+        "TiB" => 1 << 40,
+        _ => return None,
+    };
+    number.scaled(scale).to_u64()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Map;
+
+    #[test]
+    fn test_as_duration() {
+        let duration = |s: &str| Value::from(s).as_duration();
+        assert_eq!(duration("30s"), Some(Duration::from_secs(30)));
+        assert_eq!(duration("5m"), Some(Duration::from_secs(300)));
+        assert_eq!(duration("1h30m"), Some(Duration::from_secs(5400)));
+        assert_eq!(duration("1.5s"), Some(Duration::from_millis(1500)));
+        assert_eq!(duration("2.01s"), Some(Duration::from_millis(2010)));
+        assert_eq!(duration(".1h0.7s"), Some(Duration::from_millis(360_700)));
+        assert_eq!(
+            duration("1.0000000009s"),
+            Some(Duration::from_nanos(1_000_000_000))
+        );
+        assert_eq!(duration("250ms"), Some(Duration::from_millis(250)));
+        assert_eq!(duration("2d"), Some(Duration::from_secs(172800)));
+        for bad in ["30", "", "s", ".s", "1.2.3s", "-5s", "5 parsecs", "1h 30m"] {
+            assert_eq!(duration(bad), None, "{}", bad);
+        }
+        assert_eq!(Value::from(30i64).as_duration(), None);
+    }
+
+    #[test]
+    fn test_as_byte_size() {
+        let size = |s: &str| Value::from(s).as_byte_size();
+        assert_eq!(size("512"), Some(512));
+        assert_eq!(size("256KiB"), Some(256 * 1024));
+        assert_eq!(size("1.5 MB"), Some(1_500_000));
+        assert_eq!(size("4.35 KB"), Some(4350));
+        assert_eq!(size("0.5B"), Some(0));
+        assert_eq!(size("16 EiB"), None);
+        assert_eq!(size("20000000 TiB"), None);
+        assert_eq!(size("10MiB"), Some(10 * 1024 * 1024));
+        assert_eq!(size("3 parsecs"), None);
+        assert_eq!(Value::from(4096i64).as_byte_size(), Some(4096));
+        assert_eq!(Value::from(-1i64).as_byte_size(), None);
+    }
+
+    #[test]
+    fn test_quantity_objects() {
+        let mut obj = Map::new();
+        obj.insert("magnitude".to_string(), Value::Float(1.5));
+        obj.insert("unit".to_string(), Value::from("h"));
+        let value = Value::Object(obj);
+        assert_eq!(value.as_duration(), Some(Duration::from_secs(5400)));
+        assert_eq!(value.as_byte_size(), None);
+    }
+}
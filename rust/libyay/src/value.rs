@@ -1,12 +1,106 @@
 //! YAY value representation.
 
+#[cfg(feature = "std")]
+pub use crate::map::Hashed;
+pub use crate::map::{Indexed, MapKind, ObjectMap, Sorted};
+use crate::{DateTime, Decimal, ParseError};
+use alloc::borrow::Cow;
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::ops::{Index, IndexMut};
+use core::str::FromStr;
+use indexmap::IndexMap;
 use num_bigint::BigInt;
+use num_traits::ToPrimitive;
+#[cfg(feature = "std")]
 use std::collections::HashMap;
-use std::fmt;
+
+/// The map type backing `Value::Object`.
+///
+/// Keys keep the order in which they were inserted, which for parsed
+/// documents is the order they appear in the source. Equality ignores order.
+///
+/// An object converts to and from a `BTreeMap`, kept in key order, or a
+/// `HashMap`, with `From` and `TryFrom`. To hold every object of a value in
+/// another map, use a [`GenericValue`] of another [`MapKind`].
+pub type Map = IndexMap<String, Value, MapState>;
+
+/// How [`Map`] hashes its keys: with the `std` feature, with the standard
+/// library's randomly seeded hasher, and without it, with [`FnvHasher`].
+#[cfg(feature = "std")]
+pub type MapState = std::collections::hash_map::RandomState;
+/// How [`Map`] hashes its keys: with the `std` feature, with the standard
+/// library's randomly seeded hasher, and without it, with [`FnvHasher`].
+#[cfg(not(feature = "std"))]
+pub type MapState = core::hash::BuildHasherDefault<FnvHasher>;
+
+/// The FNV-1a hash, for [`Map`] where there is no source of randomness to
+/// seed the standard hasher. Like any unseeded hash, it lets whoever
+/// chooses the keys of a document make lookups slow.
+#[derive(Debug, Clone, Copy)]
+pub struct FnvHasher(u64);
+
+impl Default for FnvHasher {
+    fn default() -> Self {
+        FnvHasher(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for FnvHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ u64::from(byte)).wrapping_mul(0x100_0000_01b3);
+        }
+    }
+}
 
 /// A YAY value.
-#[derive(Clone, PartialEq)]
-pub enum Value {
+///
+/// Values are [`Eq`], [`Ord`], and [`Hash`], so they can be the keys of a
+/// `BTreeMap` or `HashSet`. Equality is by type and then by value: `1` and
+/// `1.0` differ, every NaN equals every other, `0.0` and `-0.0` differ,
+/// and objects with the same members are equal in any order.
+///
+/// Values of different types order by type: null, booleans, integers,
+/// floats, decimals, strings, byte arrays, dates and times, arrays, then
+/// objects. Within a type, `false` is before `true`; numbers order by
+/// value, with `-0.0` before `0.0` and NaN after infinity; strings and byte
+/// arrays by their bytes; dates and times by their RFC 3339 text; arrays
+/// item by item; and objects as if each were an array of its members
+/// sorted by key.
+///
+/// The objects of a `Value` are [`Map`]s. [`GenericValue`] is the same
+/// value with the map of each object chosen by a [`MapKind`], and
+/// [`GenericValue::into_maps`] converts between kinds:
+///
+/// ```
+/// use libyay::{GenericValue, Sorted, Value};
+///
+/// let value: Value = "{b: 1, a: {d: 2, c: 3}}".parse().unwrap();
+/// let sorted: GenericValue<Sorted> = value.clone().into_maps();
+/// let keys: Vec<_> = sorted.as_object().unwrap().keys().collect();
+/// assert_eq!(keys, ["a", "b"]);
+/// assert_eq!(sorted["a"]["c"], GenericValue::Integer(3.into()));
+/// assert_eq!(sorted.into_maps::<libyay::Indexed>(), value);
+/// ```
+pub type Value = GenericValue<Indexed>;
+
+/// A YAY value whose objects are the maps of `K`.
+///
+/// The parsers produce [`Value`], which is `GenericValue<Indexed>`, and
+/// most of the library works with it. Other kinds have the accessors,
+/// indexing, equality, ordering, and hashing of `Value`, and convert to and
+/// from it with [`GenericValue::into_maps`].
+#[non_exhaustive]
+pub enum GenericValue<K: MapKind = Indexed> {
     /// Null value.
     Null,
     /// Boolean value.
@@ -15,26 +109,30 @@ pub enum Value {
     Integer(BigInt),
     /// 64-bit floating-point number.
     Float(f64),
+    /// Exact decimal number, from CBOR or when parsing with decimals.
+    Decimal(Decimal),
     /// UTF-8 string.
     String(String),
     /// Array of values.
-    Array(Vec<Value>),
-    /// Object (key-value map).
-    Object(HashMap<String, Value>),
+    Array(Vec<GenericValue<K>>),
+    /// Object: for [`Value`], a key-value map in insertion order.
+    Object(K::Map<GenericValue<K>>),
     /// Byte array.
     Bytes(Vec<u8>),
+    /// Date and time, from TOML or CBOR; written in YAY as a string.
+    DateTime(DateTime),
 }
 
-impl Value {
+impl<K: MapKind> GenericValue<K> {
     /// Returns `true` if this value is null.
     pub fn is_null(&self) -> bool {
-        matches!(self, Value::Null)
+        matches!(self, GenericValue::Null)
     }
 
     /// Returns the boolean value if this is a `Bool`.
     pub fn as_bool(&self) -> Option<bool> {
         match self {
-            Value::Bool(b) => Some(*b),
+            GenericValue::Bool(b) => Some(*b),
             _ => None,
         }
     }
@@ -42,7 +140,7 @@ impl Value {
     /// Returns a reference to the integer if this is an `Integer`.
     pub fn as_integer(&self) -> Option<&BigInt> {
         match self {
-            Value::Integer(n) => Some(n),
+            GenericValue::Integer(n) => Some(n),
             _ => None,
         }
     }
@@ -50,7 +148,7 @@ impl Value {
     /// Returns the float value if this is a `Float`.
     pub fn as_float(&self) -> Option<f64> {
         match self {
-            Value::Float(f) => Some(*f),
+            GenericValue::Float(f) => Some(*f),
             _ => None,
         }
     }
@@ -58,23 +156,23 @@ impl Value {
     /// Returns a reference to the string if this is a `String`.
     pub fn as_str(&self) -> Option<&str> {
         match self {
-            Value::String(s) => Some(s),
+            GenericValue::String(s) => Some(s),
             _ => None,
         }
     }
 
     /// Returns a reference to the array if this is an `Array`.
-    pub fn as_array(&self) -> Option<&Vec<Value>> {
+    pub fn as_array(&self) -> Option<&Vec<GenericValue<K>>> {
         match self {
-            Value::Array(arr) => Some(arr),
+            GenericValue::Array(arr) => Some(arr),
             _ => None,
         }
     }
 
     /// Returns a reference to the object if this is an `Object`.
-    pub fn as_object(&self) -> Option<&HashMap<String, Value>> {
+    pub fn as_object(&self) -> Option<&K::Map<GenericValue<K>>> {
         match self {
-            Value::Object(obj) => Some(obj),
+            GenericValue::Object(obj) => Some(obj),
             _ => None,
         }
     }
@@ -82,11 +180,64 @@ impl Value {
     /// Returns a reference to the bytes if this is a `Bytes`.
     pub fn as_bytes(&self) -> Option<&Vec<u8>> {
         match self {
-            Value::Bytes(b) => Some(b),
+            GenericValue::Bytes(b) => Some(b),
             _ => None,
         }
     }
 
+    /// Returns the member named `key` if this is an `Object` that has it.
+    ///
+    /// `None` means the key is missing; a key that is present with a null
+    /// value gives `Some(&GenericValue::Null)`. Use [`Value::field`] to match on
+    /// the three cases directly.
+    pub fn get(&self, key: &str) -> Option<&GenericValue<K>> {
+        self.as_object().and_then(|obj| obj.get(key))
+    }
+
+    /// Returns a mutable reference to the member named `key` if this is an
+    /// `Object` that has it.
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut GenericValue<K>> {
+        match self {
+            GenericValue::Object(obj) => obj.get_mut(key),
+            _ => None,
+        }
+    }
+
+    /// Returns the element at `index` if this is an `Array` that long.
+    pub fn get_index(&self, index: usize) -> Option<&GenericValue<K>> {
+        self.as_array().and_then(|arr| arr.get(index))
+    }
+
+    /// Returns `true` if this is an `Object` with a member named `key`,
+    /// even if the member is null.
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Looks up a value by JSON Pointer (RFC 6901), like
+    /// `"/servers/0/port"`.
+    ///
+    /// The empty pointer is this value. Each `/`-separated token names an
+    /// object member, with `~1` for `/` and `~0` for `~`, or an array
+    /// index in decimal without leading zeros. Returns `None` if any step
+    /// is missing or the pointer is malformed.
+    pub fn pointer(&self, pointer: &str) -> Option<&GenericValue<K>> {
+        pointer_tokens(pointer)?.try_fold(self, |value, token| match value {
+            GenericValue::Object(obj) => obj.get(token.as_ref()),
+            GenericValue::Array(arr) => arr.get(pointer_index(&token)?),
+            _ => None,
+        })
+    }
+
+    /// Like [`GenericValue::pointer`], but returns a mutable reference.
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut GenericValue<K>> {
+        pointer_tokens(pointer)?.try_fold(self, |value, token| match value {
+            GenericValue::Object(obj) => obj.get_mut(token.as_ref()),
+            GenericValue::Array(arr) => arr.get_mut(pointer_index(&token)?),
+            _ => None,
+        })
+    }
+
     /// Returns a description of why this value cannot be represented in JSON,
     /// or `None` if it can be represented.
     ///
@@ -95,10 +246,10 @@ impl Value {
     /// - Big integers (numbers larger than JavaScript's safe integer range)
     pub fn json_incompatibility(&self) -> Option<&'static str> {
         match self {
-            Value::Bytes(_) => Some("byte arrays"),
+            GenericValue::Bytes(_) => Some("byte arrays"),
             // YAY integers are always BigInts, which JSON cannot represent
-            Value::Integer(_) => Some("integers (YAY integers are BigInts)"),
-            Value::Array(arr) => {
+            GenericValue::Integer(_) => Some("integers (YAY integers are BigInts)"),
+            GenericValue::Array(arr) => {
                 for v in arr {
                     if let Some(reason) = v.json_incompatibility() {
                         return Some(reason);
@@ -106,8 +257,8 @@ impl Value {
                 }
                 None
             }
-            Value::Object(obj) => {
-                for v in obj.values() {
+            GenericValue::Object(obj) => {
+                for (_, v) in obj.members() {
                     if let Some(reason) = v.json_incompatibility() {
                         return Some(reason);
                     }
@@ -117,15 +268,108 @@ impl Value {
             _ => None,
         }
     }
+
+    /// This value with every object rebuilt as a map of kind `L`.
+    ///
+    /// Members keep their order where `L` keeps the order it is given, as
+    /// [`Indexed`] does.
+    pub fn into_maps<L: MapKind>(self) -> GenericValue<L> {
+        match self {
+            GenericValue::Null => GenericValue::Null,
+            GenericValue::Bool(b) => GenericValue::Bool(b),
+            GenericValue::Integer(n) => GenericValue::Integer(n),
+            GenericValue::Float(f) => GenericValue::Float(f),
+            GenericValue::Decimal(d) => GenericValue::Decimal(d),
+            GenericValue::String(s) => GenericValue::String(s),
+            GenericValue::Array(arr) => {
+                GenericValue::Array(arr.into_iter().map(GenericValue::into_maps).collect())
+            }
+            GenericValue::Object(obj) => GenericValue::Object(
+                obj.into_iter()
+                    .map(|(key, value)| (key, value.into_maps()))
+                    .collect(),
+            ),
+            GenericValue::Bytes(b) => GenericValue::Bytes(b),
+            GenericValue::DateTime(dt) => GenericValue::DateTime(dt),
+        }
+    }
+}
+
+impl Value {
+    /// Looks up the member named `key`, distinguishing a missing key from
+    /// one set to null.
+    pub fn field(&self, key: &str) -> Field<'_> {
+        match self.get(key) {
+            None => Field::Missing,
+            Some(Value::Null) => Field::Null,
+            Some(value) => Field::Present(value),
+        }
+    }
+}
+
+/// The result of [`Value::field`].
+///
+/// YAY keeps "set to null" and "not set" apart: `{a: null}` and `{}` are
+/// different documents. [`Value::merge`] follows the same rule by default:
+/// a member that is `Missing` from the override leaves the base alone,
+/// while a `Null` member sets it to null. With
+/// [`MergeStrategy::null_deletes`](crate::MergeStrategy), as `yay merge
+/// --null-deletes` and [`Value::merge_patch`] use it, a `Null` member
+/// removes the key instead, as JSON Merge Patch does.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Field<'a> {
+    /// The key is absent, or the value is not an object.
+    Missing,
+    /// The key is present with a null value.
+    Null,
+    /// The key is present with a non-null value.
+    Present(&'a Value),
+}
+
+impl<'a> Field<'a> {
+    /// Returns `true` if the key is absent.
+    pub fn is_missing(&self) -> bool {
+        matches!(self, Field::Missing)
+    }
+
+    /// Returns `true` if the key is present with a null value.
+    pub fn is_null(&self) -> bool {
+        matches!(self, Field::Null)
+    }
+
+    /// Returns the non-null value, if any.
+    pub fn value(&self) -> Option<&'a Value> {
+        match self {
+            Field::Present(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+impl<K: MapKind> Clone for GenericValue<K> {
+    fn clone(&self) -> Self {
+        match self {
+            GenericValue::Null => GenericValue::Null,
+            GenericValue::Bool(b) => GenericValue::Bool(*b),
+            GenericValue::Integer(n) => GenericValue::Integer(n.clone()),
+            GenericValue::Float(f) => GenericValue::Float(*f),
+            GenericValue::Decimal(d) => GenericValue::Decimal(d.clone()),
+            GenericValue::String(s) => GenericValue::String(s.clone()),
+            GenericValue::Array(arr) => GenericValue::Array(arr.clone()),
+            GenericValue::Object(obj) => GenericValue::Object(obj.duplicate()),
+            GenericValue::Bytes(b) => GenericValue::Bytes(b.clone()),
+            GenericValue::DateTime(dt) => GenericValue::DateTime(dt.clone()),
+        }
+    }
 }
 
-impl fmt::Debug for Value {
+impl<K: MapKind> fmt::Debug for GenericValue<K> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Value::Null => write!(f, "null"),
-            Value::Bool(b) => write!(f, "{}", b),
-            Value::Integer(n) => write!(f, "{}n", n),
-            Value::Float(n) => {
+            GenericValue::Null => write!(f, "null"),
+            GenericValue::Bool(b) => write!(f, "{}", b),
+            GenericValue::Integer(n) => write!(f, "{}n", n),
+            GenericValue::Float(n) => {
                 if n.is_nan() {
                     write!(f, "NaN")
                 } else if n.is_infinite() {
@@ -138,10 +382,12 @@ impl fmt::Debug for Value {
                     write!(f, "{}", n)
                 }
             }
-            Value::String(s) => write!(f, "{:?}", s),
-            Value::Array(arr) => f.debug_list().entries(arr).finish(),
-            Value::Object(obj) => f.debug_map().entries(obj).finish(),
-            Value::Bytes(b) => {
+            GenericValue::String(s) => write!(f, "{:?}", s),
+            GenericValue::Decimal(d) => write!(f, "{}m", d),
+            GenericValue::DateTime(dt) => write!(f, "{}", dt),
+            GenericValue::Array(arr) => f.debug_list().entries(arr).finish(),
+            GenericValue::Object(obj) => f.debug_map().entries(obj.members()).finish(),
+            GenericValue::Bytes(b) => {
                 write!(f, "<")?;
                 for byte in b {
                     write!(f, "{:02x}", byte)?;
@@ -152,6 +398,234 @@ impl fmt::Debug for Value {
     }
 }
 
+/// Canonical YAY, as [`encode`](fn@crate::encode) writes it with
+/// [`Format::Yay`](crate::Format::Yay), so that `value.to_string()` parses
+/// back to `value`. Use [`encode_yay_inline`](crate::encode_yay_inline) for
+/// one line.
+///
+/// ```
+/// use libyay::Value;
+///
+/// let value: Value = "{port: 8080, hosts: ['a']}".parse().unwrap();
+/// assert_eq!(value.to_string(), "hosts: [\"a\"]\nport: 8080");
+/// assert_eq!(value.to_string().parse::<Value>().unwrap(), value);
+/// ```
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&crate::encode(self, crate::Format::Yay))
+    }
+}
+
+/// Parses strict YAY, as [`parse`](fn@crate::parse) does.
+impl FromStr for Value {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        crate::parse(s)
+    }
+}
+
+impl<K: MapKind> GenericValue<K> {
+    /// The position of this value's type in the order of types.
+    fn rank(&self) -> u8 {
+        match self {
+            GenericValue::Null => 0,
+            GenericValue::Bool(_) => 1,
+            GenericValue::Integer(_) => 2,
+            GenericValue::Float(_) => 3,
+            GenericValue::Decimal(_) => 4,
+            GenericValue::String(_) => 5,
+            GenericValue::Bytes(_) => 6,
+            GenericValue::DateTime(_) => 7,
+            GenericValue::Array(_) => 8,
+            GenericValue::Object(_) => 9,
+        }
+    }
+}
+
+/// The float's bits, with one NaN for every NaN.
+fn float_bits(f: f64) -> u64 {
+    if f.is_nan() {
+        f64::NAN.to_bits()
+    } else {
+        f.to_bits()
+    }
+}
+
+fn sorted_members<K: MapKind>(map: &K::Map<GenericValue<K>>) -> Vec<(&String, &GenericValue<K>)> {
+    let mut members: Vec<_> = map.members().collect();
+    members.sort_unstable_by_key(|(key, _)| *key);
+    members
+}
+
+impl<K: MapKind> PartialEq for GenericValue<K> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (GenericValue::Null, GenericValue::Null) => true,
+            (GenericValue::Bool(a), GenericValue::Bool(b)) => a == b,
+            (GenericValue::Integer(a), GenericValue::Integer(b)) => a == b,
+            (GenericValue::Float(a), GenericValue::Float(b)) => float_bits(*a) == float_bits(*b),
+            (GenericValue::Decimal(a), GenericValue::Decimal(b)) => a == b,
+            (GenericValue::String(a), GenericValue::String(b)) => a == b,
+            (GenericValue::Bytes(a), GenericValue::Bytes(b)) => a == b,
+            (GenericValue::DateTime(a), GenericValue::DateTime(b)) => a == b,
+            (GenericValue::Array(a), GenericValue::Array(b)) => a == b,
+            (GenericValue::Object(a), GenericValue::Object(b)) => {
+                a.len() == b.len() && a.members().all(|(key, value)| b.get(key) == Some(value))
+            }
+            _ => false,
+        }
+    }
+}
+
+impl<K: MapKind> Eq for GenericValue<K> {}
+
+impl<K: MapKind> Ord for GenericValue<K> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (GenericValue::Bool(a), GenericValue::Bool(b)) => a.cmp(b),
+            (GenericValue::Integer(a), GenericValue::Integer(b)) => a.cmp(b),
+            (GenericValue::Float(a), GenericValue::Float(b)) => {
+                f64::from_bits(float_bits(*a)).total_cmp(&f64::from_bits(float_bits(*b)))
+            }
+            (GenericValue::Decimal(a), GenericValue::Decimal(b)) => a.cmp(b),
+            (GenericValue::String(a), GenericValue::String(b)) => a.cmp(b),
+            (GenericValue::Bytes(a), GenericValue::Bytes(b)) => a.cmp(b),
+            (GenericValue::DateTime(a), GenericValue::DateTime(b)) => a.cmp(b),
+            (GenericValue::Array(a), GenericValue::Array(b)) => a.cmp(b),
+            (GenericValue::Object(a), GenericValue::Object(b)) => {
+                sorted_members::<K>(a).cmp(&sorted_members::<K>(b))
+            }
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+}
+
+impl<K: MapKind> PartialOrd for GenericValue<K> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K: MapKind> Hash for GenericValue<K> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.rank().hash(state);
+        match self {
+            GenericValue::Null => {}
+            GenericValue::Bool(b) => b.hash(state),
+            GenericValue::Integer(n) => n.hash(state),
+            GenericValue::Float(f) => float_bits(*f).hash(state),
+            GenericValue::Decimal(d) => d.hash(state),
+            GenericValue::String(s) => s.hash(state),
+            GenericValue::Bytes(b) => b.hash(state),
+            GenericValue::DateTime(dt) => dt.hash(state),
+            GenericValue::Array(items) => items.hash(state),
+            GenericValue::Object(map) => sorted_members::<K>(map).hash(state),
+        }
+    }
+}
+
+/// `value["key"]` is the member named `key`, or null if this is not an
+/// object or has no such member, so lookups chain through missing paths.
+impl<K: MapKind> Index<&str> for GenericValue<K> {
+    type Output = GenericValue<K>;
+
+    fn index(&self, key: &str) -> &GenericValue<K> {
+        self.get(key).unwrap_or(&GenericValue::Null)
+    }
+}
+
+/// `value[i]` is the element at `i`, or null if this is not an array or
+/// is too short.
+impl<K: MapKind> Index<usize> for GenericValue<K> {
+    type Output = GenericValue<K>;
+
+    fn index(&self, index: usize) -> &GenericValue<K> {
+        self.get_index(index).unwrap_or(&GenericValue::Null)
+    }
+}
+
+/// `value["key"] = ...` sets a member, adding it if it is missing and
+/// turning a null into an empty object first.
+///
+/// # Panics
+///
+/// Panics if this is neither an object nor null.
+impl IndexMut<&str> for Value {
+    fn index_mut(&mut self, key: &str) -> &mut Value {
+        if self.is_null() {
+            *self = Value::Object(Map::default());
+        }
+        match self {
+            Value::Object(obj) => obj.entry(key.to_string()).or_insert(Value::Null),
+            _ => panic!(
+                "cannot index {} with key {:?}",
+                crate::stats::type_name(self),
+                key
+            ),
+        }
+    }
+}
+
+/// `value[i] = ...` sets an element.
+///
+/// # Panics
+///
+/// Panics if this is not an array or `i` is out of bounds.
+impl IndexMut<usize> for Value {
+    fn index_mut(&mut self, index: usize) -> &mut Value {
+        match self {
+            Value::Array(arr) => {
+                let len = arr.len();
+                arr.get_mut(index).unwrap_or_else(|| {
+                    panic!("index {} out of bounds for array of {} items", index, len)
+                })
+            }
+            _ => panic!(
+                "cannot index {} with {}",
+                crate::stats::type_name(self),
+                index
+            ),
+        }
+    }
+}
+
+/// The unescaped reference tokens of a JSON Pointer, or `None` if it is
+/// neither empty nor starts with `/`.
+fn pointer_tokens(pointer: &str) -> Option<impl Iterator<Item = Cow<'_, str>>> {
+    if !pointer.is_empty() && !pointer.starts_with('/') {
+        return None;
+    }
+    Some(pointer.split('/').skip(1).map(|token| {
+        if token.contains('~') {
+            Cow::Owned(token.replace("~1", "/").replace("~0", "~"))
+        } else {
+            Cow::Borrowed(token)
+        }
+    }))
+}
+
+/// A pointer's parent pointer and its last token, unescaped.
+#[cfg(feature = "std")]
+pub(crate) fn split_pointer(pointer: &str) -> Result<(&str, String), String> {
+    let (parent, last) = pointer
+        .rsplit_once('/')
+        .filter(|(parent, _)| parent.is_empty() || parent.starts_with('/'))
+        .ok_or_else(|| format!("invalid path {:?}", pointer))?;
+    Ok((parent, last.replace("~1", "/").replace("~0", "~")))
+}
+
+/// An array index token: `0` or digits without a leading zero.
+pub(crate) fn pointer_index(token: &str) -> Option<usize> {
+    if token.is_empty()
+        || !token.bytes().all(|b| b.is_ascii_digit())
+        || (token.len() > 1 && token.starts_with('0'))
+    {
+        return None;
+    }
+    token.parse().ok()
+}
+
 impl From<bool> for Value {
     fn from(b: bool) -> Self {
         Value::Bool(b)
@@ -164,18 +638,30 @@ impl From<BigInt> for Value {
     }
 }
 
-impl From<i64> for Value {
-    fn from(n: i64) -> Self {
-        Value::Integer(BigInt::from(n))
-    }
+macro_rules! from_integer {
+    ($($t:ty),*) => {$(
+        impl From<$t> for Value {
+            fn from(n: $t) -> Self {
+                Value::Integer(BigInt::from(n))
+            }
+        }
+    )*};
 }
 
+from_integer!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
 impl From<f64> for Value {
     fn from(f: f64) -> Self {
         Value::Float(f)
     }
 }
 
+impl From<f32> for Value {
+    fn from(f: f32) -> Self {
+        Value::Float(f64::from(f))
+    }
+}
+
 impl From<String> for Value {
     fn from(s: String) -> Self {
         Value::String(s)
@@ -188,15 +674,48 @@ impl From<&str> for Value {
     }
 }
 
+impl From<&String> for Value {
+    fn from(s: &String) -> Self {
+        Value::String(s.clone())
+    }
+}
+
+impl From<Cow<'_, str>> for Value {
+    fn from(s: Cow<'_, str>) -> Self {
+        Value::String(s.into_owned())
+    }
+}
+
+impl From<char> for Value {
+    fn from(c: char) -> Self {
+        Value::String(c.to_string())
+    }
+}
+
 impl From<Vec<Value>> for Value {
     fn from(arr: Vec<Value>) -> Self {
         Value::Array(arr)
     }
 }
 
+impl From<Map> for Value {
+    fn from(obj: Map) -> Self {
+        Value::Object(obj)
+    }
+}
+
+#[cfg(feature = "std")]
 impl From<HashMap<String, Value>> for Value {
     fn from(obj: HashMap<String, Value>) -> Self {
-        Value::Object(obj)
+        let mut entries: Vec<(String, Value)> = obj.into_iter().collect();
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        Value::Object(entries.into_iter().collect())
+    }
+}
+
+impl From<BTreeMap<String, Value>> for Value {
+    fn from(obj: BTreeMap<String, Value>) -> Self {
+        Value::Object(obj.into_iter().collect())
     }
 }
 
@@ -205,3 +724,494 @@ impl From<Vec<u8>> for Value {
         Value::Bytes(b)
     }
 }
+
+impl From<&[u8]> for Value {
+    fn from(b: &[u8]) -> Self {
+        Value::Bytes(b.to_vec())
+    }
+}
+
+/// `None` is null.
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(option: Option<T>) -> Self {
+        option.map_or(Value::Null, Into::into)
+    }
+}
+
+impl FromIterator<Value> for Value {
+    fn from_iter<I: IntoIterator<Item = Value>>(iter: I) -> Self {
+        Value::Array(iter.into_iter().collect())
+    }
+}
+
+impl<K: Into<String>> FromIterator<(K, Value)> for Value {
+    fn from_iter<I: IntoIterator<Item = (K, Value)>>(iter: I) -> Self {
+        Value::Object(iter.into_iter().map(|(k, v)| (k.into(), v)).collect())
+    }
+}
+
+/// Why a `Value` could not be converted with `TryFrom`.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum TryFromValueError {
+    /// The value is of another type.
+    WrongType {
+        expected: &'static str,
+        found: &'static str,
+    },
+    /// The integer does not fit in the target type.
+    OutOfRange { target: &'static str, value: BigInt },
+}
+
+impl fmt::Display for TryFromValueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryFromValueError::WrongType { expected, found } => {
+                write!(f, "expected {}, found {}", expected, found)
+            }
+            TryFromValueError::OutOfRange { target, value } => {
+                write!(f, "integer {} is out of range for {}", value, target)
+            }
+        }
+    }
+}
+
+impl core::error::Error for TryFromValueError {}
+
+fn wrong_type(expected: &'static str, value: &Value) -> TryFromValueError {
+    TryFromValueError::WrongType {
+        expected,
+        found: crate::stats::type_name(value),
+    }
+}
+
+macro_rules! try_from_integer {
+    ($($t:ty),*) => {$(
+        impl TryFrom<&Value> for $t {
+            type Error = TryFromValueError;
+
+            fn try_from(value: &Value) -> Result<Self, Self::Error> {
+                match value {
+                    Value::Integer(n) => {
+                        <$t>::try_from(n).map_err(|_| TryFromValueError::OutOfRange {
+                            target: stringify!($t),
+                            value: n.clone(),
+                        })
+                    }
+                    _ => Err(wrong_type("integer", value)),
+                }
+            }
+        }
+
+        impl TryFrom<Value> for $t {
+            type Error = TryFromValueError;
+
+            fn try_from(value: Value) -> Result<Self, Self::Error> {
+                <$t>::try_from(&value)
+            }
+        }
+    )*};
+}
+
+try_from_integer!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+/// Integers and decimals convert too, as when deserializing.
+impl TryFrom<&Value> for f64 {
+    type Error = TryFromValueError;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Float(f) => Ok(*f),
+            Value::Integer(n) => Ok(n.to_f64().unwrap_or(f64::NAN)),
+            Value::Decimal(d) => Ok(d.to_f64()),
+            _ => Err(wrong_type("float", value)),
+        }
+    }
+}
+
+impl TryFrom<Value> for f64 {
+    type Error = TryFromValueError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        f64::try_from(&value)
+    }
+}
+
+impl TryFrom<&Value> for bool {
+    type Error = TryFromValueError;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        value.as_bool().ok_or_else(|| wrong_type("bool", value))
+    }
+}
+
+impl TryFrom<Value> for bool {
+    type Error = TryFromValueError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        bool::try_from(&value)
+    }
+}
+
+impl<'a> TryFrom<&'a Value> for &'a str {
+    type Error = TryFromValueError;
+
+    fn try_from(value: &'a Value) -> Result<Self, Self::Error> {
+        value.as_str().ok_or_else(|| wrong_type("string", value))
+    }
+}
+
+impl TryFrom<&Value> for String {
+    type Error = TryFromValueError;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        <&str>::try_from(value).map(str::to_string)
+    }
+}
+
+impl TryFrom<Value> for String {
+    type Error = TryFromValueError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::String(s) => Ok(s),
+            _ => Err(wrong_type("string", &value)),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a Value> for &'a [u8] {
+    type Error = TryFromValueError;
+
+    fn try_from(value: &'a Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Bytes(b) => Ok(b),
+            _ => Err(wrong_type("bytes", value)),
+        }
+    }
+}
+
+impl TryFrom<&Value> for Vec<u8> {
+    type Error = TryFromValueError;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        <&[u8]>::try_from(value).map(<[u8]>::to_vec)
+    }
+}
+
+impl TryFrom<Value> for Vec<u8> {
+    type Error = TryFromValueError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Bytes(b) => Ok(b),
+            _ => Err(wrong_type("bytes", &value)),
+        }
+    }
+}
+
+/// An object's members, in key order. Nested objects stay [`Map`]s.
+impl TryFrom<&Value> for BTreeMap<String, Value> {
+    type Error = TryFromValueError;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Object(obj) => Ok(obj.clone().into_iter().collect()),
+            _ => Err(wrong_type("object", value)),
+        }
+    }
+}
+
+impl TryFrom<Value> for BTreeMap<String, Value> {
+    type Error = TryFromValueError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Object(obj) => Ok(obj.into_iter().collect()),
+            _ => Err(wrong_type("object", &value)),
+        }
+    }
+}
+
+/// An object's members, in no order. Nested objects stay [`Map`]s.
+#[cfg(feature = "std")]
+impl TryFrom<&Value> for HashMap<String, Value> {
+    type Error = TryFromValueError;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Object(obj) => Ok(obj.clone().into_iter().collect()),
+            _ => Err(wrong_type("object", value)),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl TryFrom<Value> for HashMap<String, Value> {
+    type Error = TryFromValueError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Object(obj) => Ok(obj.into_iter().collect()),
+            _ => Err(wrong_type("object", &value)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{encode, parse, Format};
+
+    #[test]
+    fn test_try_from() {
+        let value = parse("{n: 300, f: 0.5, s: \"x\", b: <ff>, t: true}").unwrap();
+        assert_eq!(i64::try_from(&value["n"]), Ok(300));
+        assert_eq!(u64::try_from(value["n"].clone()), Ok(300));
+        assert_eq!(f64::try_from(&value["n"]), Ok(300.0));
+        assert_eq!(f64::try_from(&value["f"]), Ok(0.5));
+        assert_eq!(<&str>::try_from(&value["s"]), Ok("x"));
+        assert_eq!(String::try_from(value["s"].clone()), Ok("x".to_string()));
+        assert_eq!(Vec::<u8>::try_from(&value["b"]), Ok(vec![0xff]));
+        assert_eq!(bool::try_from(&value["t"]), Ok(true));
+
+        let err = u8::try_from(&value["n"]).unwrap_err();
+        assert_eq!(err.to_string(), "integer 300 is out of range for u8");
+        let err = i64::try_from(&value["s"]).unwrap_err();
+        assert_eq!(err.to_string(), "expected integer, found string");
+        let err = String::try_from(Value::Null).unwrap_err();
+        assert_eq!(
+            err,
+            TryFromValueError::WrongType {
+                expected: "string",
+                found: "null"
+            }
+        );
+    }
+
+    #[test]
+    fn test_from() {
+        assert_eq!(Value::from(7u8), Value::Integer(BigInt::from(7)));
+        assert_eq!(Value::from(-7i32), Value::Integer(BigInt::from(-7)));
+        assert_eq!(
+            Value::from(u128::MAX),
+            Value::Integer(BigInt::from(u128::MAX))
+        );
+        assert_eq!(Value::from(1.5f32), Value::Float(1.5));
+        assert_eq!(Value::from('x'), Value::from("x"));
+        assert_eq!(Value::from(&String::from("x")), Value::from("x"));
+        assert_eq!(Value::from(Cow::Borrowed("x")), Value::from("x"));
+        assert_eq!(Value::from(&b"\x01"[..]), Value::Bytes(vec![1]));
+        assert_eq!(Value::from(Some(true)), Value::Bool(true));
+        assert_eq!(Value::from(None::<i64>), Value::Null);
+
+        let array: Value = (1..=2).map(Value::from).collect();
+        assert_eq!(array, parse("[1, 2]").unwrap());
+        let object: Value = [("a", Value::from(1)), ("b", Value::Null)]
+            .into_iter()
+            .collect();
+        assert_eq!(object, parse("{a: 1, b: null}").unwrap());
+    }
+
+    #[test]
+    fn test_map_conversions() {
+        let value = parse("{b: {d: 1, c: 2}, a: null}").unwrap();
+        let sorted = BTreeMap::try_from(&value).unwrap();
+        assert_eq!(sorted.keys().collect::<Vec<_>>(), ["a", "b"]);
+        assert_eq!(Value::from(sorted), value);
+        let hashed = HashMap::try_from(value.clone()).unwrap();
+        assert_eq!(hashed["b"], parse("{c: 2, d: 1}").unwrap());
+        assert_eq!(Value::from(hashed), value);
+        assert_eq!(
+            BTreeMap::try_from(Value::Null),
+            Err(TryFromValueError::WrongType {
+                expected: "object",
+                found: "null"
+            })
+        );
+    }
+
+    #[test]
+    fn test_map_kinds() {
+        let value = parse("{b: {d: 1, c: [2, {e: 3}]}, a: null}").unwrap();
+        let hashed: GenericValue<Hashed> = value.clone().into_maps();
+        assert_eq!(hashed["b"]["c"][1]["e"], GenericValue::Integer(3.into()));
+        assert_eq!(
+            hashed.pointer("/b/d"),
+            Some(&GenericValue::Integer(1.into()))
+        );
+        assert_eq!(hashed.clone(), hashed);
+        let sorted: GenericValue<Sorted> = hashed.into_maps();
+        assert_eq!(
+            format!("{:?}", sorted),
+            "{\"a\": null, \"b\": {\"c\": [2n, {\"e\": 3n}], \"d\": 1n}}"
+        );
+        assert_eq!(
+            sorted.cmp(&parse("{a: 1}").unwrap().into_maps()),
+            value.cmp(&parse("{a: 1}").unwrap())
+        );
+        assert_eq!(sorted.into_maps::<Indexed>(), value);
+    }
+
+    #[test]
+    fn test_index() {
+        let mut value = parse("servers:\n  - host: \"a\"\n").unwrap();
+        assert_eq!(value["servers"][0]["host"], Value::from("a"));
+        assert_eq!(value["servers"][1]["host"], Value::Null);
+        assert_eq!(value["missing"]["deeper"][3], Value::Null);
+        assert_eq!(value["servers"]["host"], Value::Null);
+
+        value["servers"][0]["port"] = Value::from(80);
+        value["limits"]["cpu"] = Value::from(2);
+        assert_eq!(value.pointer("/servers/0/port"), Some(&Value::from(80)));
+        assert_eq!(value.pointer("/limits/cpu"), Some(&Value::from(2)));
+    }
+
+    #[test]
+    #[should_panic(expected = "index 1 out of bounds")]
+    fn test_index_mut_out_of_bounds() {
+        let mut value = Value::Array(vec![Value::Null]);
+        value[1] = Value::Null;
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot index string")]
+    fn test_index_mut_wrong_type() {
+        let mut value = Value::from("x");
+        value["key"] = Value::Null;
+    }
+
+    #[test]
+    fn test_pointer() {
+        let mut value =
+            parse("servers:\n  - port: 80\n  - port: 443\n\"a/b\": 1\n\"m~n\": 2\n\"\": 3\n")
+                .unwrap();
+        assert_eq!(value.pointer(""), Some(&value));
+        assert_eq!(value.pointer("/servers/1/port"), Some(&Value::from(443)));
+        assert_eq!(value.pointer("/a~1b"), Some(&Value::from(1)));
+        assert_eq!(value.pointer("/m~0n"), Some(&Value::from(2)));
+        assert_eq!(value.pointer("/"), Some(&Value::from(3)));
+        assert_eq!(value.pointer("/servers/2/port"), None);
+        assert_eq!(value.pointer("/servers/01/port"), None);
+        assert_eq!(value.pointer("/servers/-"), None);
+        assert_eq!(value.pointer("/servers/0/port/x"), None);
+        assert_eq!(value.pointer("servers"), None);
+
+        *value.pointer_mut("/servers/0/port").unwrap() = Value::from(8080);
+        assert_eq!(value.pointer("/servers/0/port"), Some(&Value::from(8080)));
+    }
+
+    #[test]
+    fn test_null_versus_missing() {
+        let value = parse("a: null\nb: 1\n").unwrap();
+        assert_eq!(value.get("a"), Some(&Value::Null));
+        assert_eq!(value.get("c"), None);
+        assert!(value.contains_key("a"));
+        assert!(value.field("a").is_null());
+        assert!(value.field("c").is_missing());
+        assert_eq!(value.field("b").value(), Some(&Value::from(1i64)));
+        assert!(Value::Null.field("a").is_missing());
+    }
+
+    #[test]
+    fn test_get_index_and_get_mut() {
+        let mut value = parse("[1, 2]").unwrap();
+        assert_eq!(value.get_index(1), Some(&Value::from(2i64)));
+        assert_eq!(value.get_index(2), None);
+        assert_eq!(value.get("a"), None);
+        let mut obj = parse("a: 1").unwrap();
+        *obj.get_mut("a").unwrap() = Value::Null;
+        assert!(obj.field("a").is_null());
+        assert!(value.get_mut("a").is_none());
+    }
+
+    #[test]
+    fn test_ord_and_hash() {
+        use std::collections::{BTreeSet, HashSet};
+
+        let sorted: Vec<Value> = [
+            "{a: 1}",
+            "[1, 2]",
+            "[1]",
+            "<00>",
+            "\"b\"",
+            "\"a\"",
+            "nan",
+            "infinity",
+            "0.0",
+            "-0.0",
+            "-infinity",
+            "2",
+            "-1",
+            "true",
+            "false",
+            "null",
+        ]
+        .iter()
+        .map(|s| parse(s).unwrap())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+        let text: Vec<String> = sorted.iter().map(|v| format!("{:?}", v)).collect();
+        assert_eq!(
+            text,
+            [
+                "null",
+                "false",
+                "true",
+                "-1n",
+                "2n",
+                "-Infinity",
+                "-0",
+                "0",
+                "Infinity",
+                "NaN",
+                "\"a\"",
+                "\"b\"",
+                "<00>",
+                "[1n]",
+                "[1n, 2n]",
+                "{\"a\": 1n}",
+            ]
+        );
+
+        // Equal values are the same key, whatever their member order or NaN.
+        let mut set = HashSet::new();
+        assert!(set.insert(parse("{a: 1, b: [nan]}").unwrap()));
+        let mut reordered = parse("{b: [], a: 1}").unwrap();
+        reordered["b"] = Value::Array(vec![Value::Float(-f64::NAN)]);
+        assert!(!set.insert(reordered));
+        assert!(set.insert(parse("{a: 1.0, b: [nan]}").unwrap()));
+        assert_eq!(
+            parse("{a: 1, b: 2}")
+                .unwrap()
+                .cmp(&parse("{b: 2, a: 1}").unwrap()),
+            Ordering::Equal
+        );
+        assert!(parse("{a: 1, b: 2}").unwrap() < parse("{a: 1, c: 0}").unwrap());
+        assert_ne!(Value::Float(0.0), Value::Float(-0.0));
+    }
+
+    #[test]
+    fn test_display_and_from_str() {
+        for source in [
+            "null",
+            "-0.0",
+            "nan",
+            "\"a\\nb\"",
+            "<cafe>",
+            "k: [1, [2, {a: \"x\"}]]",
+            "a:\n  b: [1.5, true]\nc: {}",
+        ] {
+            let value: Value = source.parse().unwrap();
+            assert_eq!(
+                value.to_string().parse::<Value>().unwrap(),
+                value,
+                "{}",
+                source
+            );
+            assert_eq!(format!("{}", value), encode(&value, Format::Yay));
+        }
+        assert!("a: ".parse::<Value>().is_err());
+    }
+}
@@ -13,27 +13,80 @@
 //! - Escaped strings: `"!*hello"` (exclamation prefix for strings starting with reserved chars)
 //!
 //! Reserved prefixes (ASCII `!` through `/`) are escaped with `!`.
-
-use crate::Value;
+//!
+//! Plain numbers are float64. An integer literal that float64 cannot hold
+//! exactly is an error rather than a silently rounded float.
+
+use crate::{Map, Value};
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
 use num_bigint::BigInt;
-use std::collections::HashMap;
+
+/// A JSON object member name that appears more than once in one object.
+///
+/// The parsed object keeps the member at the position of its first
+/// occurrence with the value of its last occurrence.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DuplicateMember {
+    /// Path of the object containing the member (empty for the root).
+    pub path: String,
+    /// The repeated member name.
+    pub key: String,
+    /// Zero-based line of the first occurrence.
+    pub first_line: usize,
+    /// Zero-based line of this (later) occurrence.
+    pub line: usize,
+}
+
+/// Tracks source positions while parsing, to report duplicate members.
+struct Members<'a> {
+    source: &'a str,
+    duplicates: Vec<DuplicateMember>,
+}
+
+impl Members<'_> {
+    /// Zero-based line number of a suffix of the source.
+    fn line_of(&self, rest: &str) -> usize {
+        let offset = self.source.len() - rest.len();
+        self.source[..offset].matches('\n').count()
+    }
+}
 
 /// Parse a YSON string into a YAY Value.
+///
+/// Object members keep their source order. When a member name repeats, the
+/// last value wins; use [`parse_yson_with_duplicates`] to find out where.
 pub fn parse_yson(input: &str) -> Result<Value, String> {
+    parse_yson_with_duplicates(input).map(|(value, _)| value)
+}
+
+/// Parse a YSON string, also returning every repeated object member name.
+pub fn parse_yson_with_duplicates(input: &str) -> Result<(Value, Vec<DuplicateMember>), String> {
+    let mut members = Members {
+        source: input,
+        duplicates: Vec::new(),
+    };
     let input = input.trim();
     if input.is_empty() {
         return Err("Empty input".to_string());
     }
 
-    let (value, rest) = parse_value(input)?;
+    let (value, rest) = parse_value(input, "", &mut members)?;
     let rest = rest.trim();
     if !rest.is_empty() {
         return Err(format!("Unexpected content after value: {}", rest));
     }
-    Ok(value)
+    Ok((value, members.duplicates))
 }
 
-fn parse_value(input: &str) -> Result<(Value, &str), String> {
+fn parse_value<'a>(
+    input: &'a str,
+    path: &str,
+    members: &mut Members<'_>,
+) -> Result<(Value, &'a str), String> {
     let input = input.trim_start();
 
     if input.is_empty() {
@@ -45,8 +98,8 @@ fn parse_value(input: &str) -> Result<(Value, &str), String> {
         't' => parse_true(input),
         'f' => parse_false(input),
         '"' => parse_string(input),
-        '[' => parse_array(input),
-        '{' => parse_object(input),
+        '[' => parse_array(input, path, members),
+        '{' => parse_object(input, path, members),
         '-' | '0'..='9' => parse_number(input),
         c => Err(format!("Unexpected character: {}", c)),
     }
@@ -116,7 +169,7 @@ fn parse_string(input: &str) -> Result<(Value, &str), String> {
     Ok((Value::String(s), rest))
 }
 
-fn parse_json_string(input: &str) -> Result<(String, &str), String> {
+pub(crate) fn parse_json_string(input: &str) -> Result<(String, &str), String> {
     if !input.starts_with('"') {
         return Err("Expected '\"'".to_string());
     }
@@ -218,7 +271,7 @@ fn parse_hex(hex: &str) -> Result<Vec<u8>, String> {
     Ok(bytes)
 }
 
-fn parse_number(input: &str) -> Result<(Value, &str), String> {
+pub(crate) fn parse_number(input: &str) -> Result<(Value, &str), String> {
     let mut end = 0;
     let chars: Vec<char> = input.chars().collect();
 
@@ -261,10 +314,37 @@ fn parse_number(input: &str) -> Result<(Value, &str), String> {
     // YSON is a JSON dialect - all JSON numbers are float64.
     // Big integers are represented as strings with a # prefix.
     let f: f64 = num_str.parse().map_err(|_| "Invalid number")?;
+    // An integer literal that float64 cannot hold exactly would silently
+    // lose digits, so require the big integer spelling instead.
+    if !num_str.contains(['.', 'e', 'E']) && !is_exact(&num_str, f) {
+        return Err(format!(
+            "Integer {} cannot be represented exactly as a float64; write it as \"#{}\"",
+            num_str, num_str
+        ));
+    }
     Ok((Value::Float(f), rest))
 }
 
-fn parse_array(input: &str) -> Result<(Value, &str), String> {
+/// Whether the integer literal `digits` reads back from `f` as written.
+///
+/// Large floats print as their shortest round-tripping digits followed by
+/// zeros (`602200000000000000000000`), and those literals are fine.
+fn is_exact(digits: &str, f: f64) -> bool {
+    if f.abs() < 9007199254740992.0 {
+        // Every integer below 2^53 is a float64.
+        return true;
+    }
+    if !f.is_finite() {
+        return false;
+    }
+    digits.parse::<BigInt>().ok() == format!("{}", f).parse::<BigInt>().ok()
+}
+
+fn parse_array<'a>(
+    input: &'a str,
+    path: &str,
+    members: &mut Members<'_>,
+) -> Result<(Value, &'a str), String> {
     if !input.starts_with('[') {
         return Err("Expected '['".to_string());
     }
@@ -277,7 +357,8 @@ fn parse_array(input: &str) -> Result<(Value, &str), String> {
     }
 
     loop {
-        let (value, new_rest) = parse_value(rest)?;
+        let item_path = format!("{}[{}]", path, items.len());
+        let (value, new_rest) = parse_value(rest, &item_path, members)?;
         items.push(value);
         rest = new_rest.trim_start();
 
@@ -291,13 +372,18 @@ fn parse_array(input: &str) -> Result<(Value, &str), String> {
     }
 }
 
-fn parse_object(input: &str) -> Result<(Value, &str), String> {
+fn parse_object<'a>(
+    input: &'a str,
+    path: &str,
+    members: &mut Members<'_>,
+) -> Result<(Value, &'a str), String> {
     if !input.starts_with('{') {
         return Err("Expected '{'".to_string());
     }
 
     let mut rest = input[1..].trim_start();
-    let mut obj = HashMap::new();
+    let mut obj = Map::default();
+    let mut key_lines: BTreeMap<String, usize> = BTreeMap::new();
 
     if let Some(stripped) = rest.strip_prefix('}') {
         return Ok((Value::Object(obj), stripped));
@@ -308,8 +394,20 @@ fn parse_object(input: &str) -> Result<(Value, &str), String> {
         if !rest.starts_with('"') {
             return Err("Expected string key".to_string());
         }
+        let key_line = members.line_of(rest);
         let (key, new_rest) = parse_json_string(rest)?;
         rest = new_rest.trim_start();
+        match key_lines.get(&key) {
+            Some(&first_line) => members.duplicates.push(DuplicateMember {
+                path: path.to_string(),
+                key: key.clone(),
+                first_line,
+                line: key_line,
+            }),
+            None => {
+                key_lines.insert(key.clone(), key_line);
+            }
+        }
 
         // Expect colon
         if !rest.starts_with(':') {
@@ -318,7 +416,12 @@ fn parse_object(input: &str) -> Result<(Value, &str), String> {
         rest = rest[1..].trim_start();
 
         // Parse value
-        let (value, new_rest) = parse_value(rest)?;
+        let member_path = if path.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", path, key)
+        };
+        let (value, new_rest) = parse_value(rest, &member_path, members)?;
         obj.insert(key, value);
         rest = new_rest.trim_start();
 
@@ -348,6 +451,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::approx_constant)]
     fn test_parse_number() {
         // YSON is a JSON dialect - all JSON numbers are float64
         assert_eq!(parse_yson("42").unwrap(), Value::Float(42.0));
@@ -425,7 +529,7 @@ mod tests {
     fn test_roundtrip() {
         use crate::encode::{encode, Format};
 
-        let original = Value::Object(HashMap::from([
+        let original = Value::Object(Map::from([
             ("int".to_string(), Value::Integer(42.into())),
             (
                 "bigint".to_string(),
@@ -480,6 +584,49 @@ mod tests {
         assert!(parse_yson("fals").is_err());
     }
 
+    #[test]
+    fn test_parse_inexact_integer() {
+        // 2^53 + 1 is the first integer float64 cannot hold.
+        let err = parse_yson("9007199254740993").unwrap_err();
+        assert_eq!(
+            err,
+            "Integer 9007199254740993 cannot be represented exactly as a float64; write it as \"#9007199254740993\""
+        );
+        assert_eq!(
+            parse_yson("9007199254740994").unwrap(),
+            Value::Float(9007199254740994.0)
+        );
+        assert!(parse_yson(&"9".repeat(400)).is_err());
+        assert_eq!(
+            parse_yson("602200000000000000000000").unwrap(),
+            Value::Float(6.022e23)
+        );
+        // Fractions and exponents are float syntax and round as usual.
+        assert!(parse_yson("9007199254740993.0").is_ok());
+    }
+
+    #[test]
+    fn test_hundred_digit_integers_round_trip() {
+        use crate::shon::parse_shon_bracket;
+        use crate::{encode, parse, Format};
+        let digits = "1234567890".repeat(10);
+        let args: Vec<String> = ["[", &digits, &format!("-{}", digits), "]"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let (value, _) = parse_shon_bracket(&args).unwrap();
+        let expected = Value::Array(vec![
+            Value::Integer(digits.parse().unwrap()),
+            Value::Integer(format!("-{}", digits).parse().unwrap()),
+        ]);
+        assert_eq!(value, expected);
+        let yay = parse(&encode(&value, Format::Yay)).unwrap();
+        assert_eq!(yay, expected);
+        let yson = encode(&yay, Format::Yson);
+        assert!(yson.contains(&format!("\"#{}\"", digits)), "{}", yson);
+        assert_eq!(parse_yson(&yson).unwrap(), expected);
+    }
+
     #[test]
     fn test_parse_invalid_bigint() {
         assert!(parse_yson("\"#notanumber\"").is_err());
@@ -574,6 +721,40 @@ mod tests {
         assert_eq!(result.as_str().unwrap(), "A");
     }
 
+    #[test]
+    fn test_parse_object_preserves_order() {
+        let result = parse_yson("{\"b\": 1, \"a\": 2, \"c\": 3}").unwrap();
+        let keys: Vec<&String> = result.as_object().unwrap().keys().collect();
+        assert_eq!(keys, vec!["b", "a", "c"]);
+    }
+
+    #[test]
+    fn test_parse_duplicate_members() {
+        let input = "{\n  \"a\": 1,\n  \"b\": {\"x\": 1, \"x\": 2},\n  \"a\": 3\n}";
+        let (value, duplicates) = parse_yson_with_duplicates(input).unwrap();
+        let obj = value.as_object().unwrap();
+        let keys: Vec<&String> = obj.keys().collect();
+        assert_eq!(keys, vec!["a", "b"]);
+        assert_eq!(obj["a"], Value::Float(3.0));
+        assert_eq!(
+            duplicates,
+            vec![
+                DuplicateMember {
+                    path: "b".into(),
+                    key: "x".into(),
+                    first_line: 2,
+                    line: 2,
+                },
+                DuplicateMember {
+                    path: String::new(),
+                    key: "a".into(),
+                    first_line: 1,
+                    line: 3,
+                },
+            ]
+        );
+    }
+
     #[test]
     fn test_parse_nested_structures() {
         let result = parse_yson("[[1, 2], {\"a\": [3]}]").unwrap();
@@ -0,0 +1,196 @@
+//! Cross-platform determinism of parsing and encoding.
+//!
+//! Hashes everything the library writes for the fixture corpus, and for a
+//! large generated document, and compares each hash against
+//! `tests/digests.txt`. CI runs this on Linux, macOS, and Windows, so a
+//! difference in float formatting, map iteration order, or newline handling
+//! between platforms fails on the platform that differs.
+//!
+//! After an intended change to the output, rewrite the digests with:
+//!
+//! ```text
+//! UPDATE_DIGESTS=1 cargo test -p libyay --test determinism
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use libyay::{encode, format_yay, parse, parse_bytes, Format, Map, Newlines, ParseOptions, Value};
+
+/// The text formats `encode` writes.
+const FORMATS: &[Format] = &[
+    Format::Yay,
+    Format::Json,
+    Format::Yson,
+    Format::Ndyay,
+    Format::JavaScript,
+    Format::Go,
+    Format::Python,
+    Format::Rust,
+    Format::C,
+    Format::Java,
+    Format::Scheme,
+];
+
+fn test_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("../../test")
+}
+
+fn digests_path() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/digests.txt")
+}
+
+/// 64-bit FNV-1a, which is the same everywhere and needs no dependency.
+#[derive(Clone, Copy)]
+struct Fnv(u64);
+
+impl Fnv {
+    fn new() -> Self {
+        Fnv(0xcbf29ce484222325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+        // Separate the pieces, so that moving text between them shows.
+        self.0 ^= 0xff;
+        self.0 = self.0.wrapping_mul(0x100000001b3);
+    }
+
+    fn hex(self) -> String {
+        format!("{:016x}", self.0)
+    }
+}
+
+/// Sorted files with extension `ext` in `test/<subdir>`.
+fn fixtures(subdir: &str, ext: &str) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(test_root().join(subdir))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|e| e == ext))
+        .collect();
+    files.sort();
+    files
+}
+
+/// The hash of a value encoded in every text format.
+fn hash_encodings(value: &Value) -> String {
+    let mut hash = Fnv::new();
+    for &format in FORMATS {
+        hash.write(format.extension().as_bytes());
+        hash.write(encode(value, format).as_bytes());
+    }
+    hash.hex()
+}
+
+/// A document of many members with keys and numbers drawn from a fixed
+/// sequence, so that it is the same on every platform.
+fn generated_document() -> Value {
+    let mut state: u64 = 0x2545f4914f6cdd1d;
+    let mut next = move || {
+        // xorshift64
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+    let mut root = Map::new();
+    for i in 0..2000 {
+        let bits = next();
+        let key = format!("k{:x}", bits >> 40);
+        let value = match i % 5 {
+            0 => Value::Float(f64::from_bits(bits)),
+            1 => Value::Float((bits % 1_000_000) as f64 / 1000.0),
+            2 => Value::from(bits as i64),
+            3 => Value::String(format!("{} {}", key, bits % 97)),
+            _ => Value::Array(vec![Value::Bytes(bits.to_be_bytes().to_vec()), Value::Null]),
+        };
+        root.insert(key, value);
+    }
+    Value::Object(root)
+}
+
+/// The digest of everything written for each fixture, by name.
+fn digests() -> BTreeMap<String, String> {
+    let mut digests = BTreeMap::new();
+    for path in fixtures("yay", "yay") {
+        let name = path.file_name().unwrap().to_string_lossy().to_string();
+        let source = fs::read_to_string(&path).unwrap();
+        let value = parse(&source).unwrap();
+
+        // The same document with CRLF newlines, as a Windows editor might
+        // save it, reads the same once they are normalized.
+        let crlf = source.replace('\n', "\r\n");
+        let mut options = ParseOptions::default();
+        options.newlines = Newlines::Normalize;
+        let crlf_value = parse_bytes(crlf.as_bytes(), &options).unwrap();
+        assert_eq!(
+            hash_encodings(&crlf_value),
+            hash_encodings(&value),
+            "{} with CRLF newlines",
+            name
+        );
+
+        digests.insert(format!("yay/{}", name), hash_encodings(&value));
+    }
+    for path in fixtures("meh", "meh") {
+        let name = path.file_name().unwrap().to_string_lossy().to_string();
+        let source = fs::read_to_string(&path).unwrap();
+        let mut hash = Fnv::new();
+        hash.write(format_yay(&source).unwrap().as_bytes());
+        digests.insert(format!("meh/{}", name), hash.hex());
+    }
+    let generated = generated_document();
+    let reparsed = parse(&encode(&generated, Format::Yay)).unwrap();
+    assert_eq!(
+        hash_encodings(&reparsed),
+        hash_encodings(&generated),
+        "generated document after a round trip"
+    );
+    digests.insert("generated".to_string(), hash_encodings(&generated));
+    digests
+}
+
+#[test]
+fn test_output_digests() {
+    let actual = digests();
+    let text: String = actual
+        .iter()
+        .map(|(name, digest)| format!("{}  {}\n", digest, name))
+        .collect();
+    if std::env::var_os("UPDATE_DIGESTS").is_some() {
+        fs::write(digests_path(), text).unwrap();
+        return;
+    }
+
+    let recorded = fs::read_to_string(digests_path()).unwrap();
+    let expected: BTreeMap<&str, &str> = recorded
+        .lines()
+        .filter_map(|line| line.split_once("  "))
+        .map(|(digest, name)| (name, digest))
+        .collect();
+    let mut differences = Vec::new();
+    for (name, digest) in &actual {
+        match expected.get(name.as_str()) {
+            Some(recorded) if recorded == digest => {}
+            Some(recorded) => {
+                differences.push(format!("{}: {} != recorded {}", name, digest, recorded))
+            }
+            None => differences.push(format!("{}: not recorded", name)),
+        }
+    }
+    for name in expected.keys() {
+        if !actual.contains_key(*name) {
+            differences.push(format!("{}: recorded but not found", name));
+        }
+    }
+    assert!(
+        differences.is_empty(),
+        "Output differs from tests/digests.txt (UPDATE_DIGESTS=1 to record \
+         an intended change):\n{}",
+        differences.join("\n")
+    );
+}
@@ -6,9 +6,15 @@
 //! they produce the expected error messages from corresponding .error files.
 
 use std::fs;
+use std::iter::Peekable;
 use std::path::Path;
 
-use libyay::{encode, parse, parse_with_filename, Format, Value};
+use libyay::spans::{Node, Spanned};
+use libyay::stream::Event;
+use libyay::{
+    encode, parse, parse_borrowed, parse_bytes, parse_with_filename, parse_with_spans, Format, Map,
+    ParseOptions, Parser, PushParser, Value,
+};
 
 /// Compare two Values, treating NaN as equal to NaN
 fn values_equal(a: &Value, b: &Value) -> bool {
@@ -32,7 +38,7 @@ fn values_equal(a: &Value, b: &Value) -> bool {
         (Value::Object(a), Value::Object(b)) => {
             a.len() == b.len()
                 && a.iter()
-                    .all(|(k, v)| b.get(k).map_or(false, |bv| values_equal(v, bv)))
+                    .all(|(k, v)| b.get(k).is_some_and(|bv| values_equal(v, bv)))
         }
         _ => false,
     }
@@ -384,7 +390,7 @@ fn test_all_encoder_coverage() {
     let mut errors: Vec<String> = Vec::new();
 
     for file in &files {
-        let content = match fs::read_to_string(&file) {
+        let content = match fs::read_to_string(file) {
             Ok(c) => c,
             Err(e) => {
                 errors.push(format!("Failed to read {}: {}", file, e));
@@ -414,6 +420,16 @@ fn test_all_encoder_coverage() {
     assert!(errors.is_empty(), "{} files had errors", errors.len());
 }
 
+/// The Rust fixtures show what `-t rust` writes, so they must compile
+/// against this crate's `Value`.
+#[test]
+#[allow(clippy::approx_constant)]
+fn test_rust_fixture_compiles() {
+    let expected: Value = include!("../../../test/rs/at-a-glance.rs");
+    let content = fs::read_to_string(test_root().join("yay/at-a-glance.yay")).unwrap();
+    assert!(values_equal(&parse(&content).unwrap(), &expected));
+}
+
 /// Exercise Value accessor methods for coverage
 fn exercise_value_accessors(value: &Value) {
     // Exercise all accessor methods
@@ -444,6 +460,7 @@ fn exercise_value_accessors(value: &Value) {
 }
 
 #[test]
+#[allow(clippy::approx_constant)]
 fn test_value_accessor_coverage() {
     let files = get_yay_files();
 
@@ -458,7 +475,7 @@ fn test_value_accessor_coverage() {
     );
 
     for file in &files {
-        let content = match fs::read_to_string(&file) {
+        let content = match fs::read_to_string(file) {
             Ok(c) => c,
             Err(_) => continue,
         };
@@ -635,6 +652,29 @@ fn test_meh_roundtrip_all_yay_fixtures() {
     );
 }
 
+/// The properties of `libyay::testing`, on every fixture: its value
+/// round-trips through YAY and YSON, and formatting it is idempotent.
+#[cfg(feature = "testing")]
+#[test]
+fn test_testing_properties_hold_for_fixtures() {
+    use libyay::testing::{formats_idempotently, round_trips};
+
+    let mut errors = Vec::new();
+    for file in get_yay_files() {
+        let source = fs::read_to_string(&file).unwrap();
+        let Ok(value) = parse(&source) else {
+            continue;
+        };
+        let filename = Path::new(&file).file_name().unwrap().to_string_lossy();
+        for result in [round_trips(&value), formats_idempotently(&source)] {
+            if let Err(e) = result {
+                errors.push(format!("{}: {}", filename, e));
+            }
+        }
+    }
+    assert!(errors.is_empty(), "{}", errors.join("\n"));
+}
+
 // Individual test cases for specific fixtures
 
 #[test]
@@ -1001,3 +1041,221 @@ fn test_meh_reformat_fixtures() {
 
     assert!(failed == 0, "{} MEH reformat tests failed", failed);
 }
+
+/// Rebuild the value described by `events`, as `parse` would build it.
+fn value_from_events(events: &mut Peekable<impl Iterator<Item = Event>>) -> Value {
+    match events.next() {
+        Some(Event::StartArray) => {
+            let mut items = Vec::new();
+            while events.next_if_eq(&Event::End).is_none() {
+                items.push(value_from_events(events));
+            }
+            Value::Array(items)
+        }
+        Some(Event::StartObject) => {
+            let mut members = Map::new();
+            while let Some(Event::Key(key)) = events.next() {
+                members.insert(key, value_from_events(events));
+            }
+            Value::Object(members)
+        }
+        Some(Event::Scalar(value)) => value,
+        event => panic!("unexpected event {:?}", event),
+    }
+}
+
+/// Read a fixture with `Parser` and compare with `parse`: the same value
+/// for a .yay file, and the same error for a .nay file.
+fn run_parser_events_test(path: &str) -> Result<(), String> {
+    let content =
+        fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let filename = Path::new(path)
+        .file_name()
+        .unwrap()
+        .to_string_lossy()
+        .to_string();
+    let mut options = ParseOptions::default();
+    options.filename = Some(filename.clone());
+
+    let events: Result<Vec<Event>, _> = Parser::with_options(&content, &options).collect();
+    match (events, parse_with_filename(&content, Some(&filename))) {
+        (Ok(events), Ok(expected)) => {
+            let mut events = events.into_iter().peekable();
+            let actual = value_from_events(&mut events);
+            if events.next().is_some() || !values_equal(&actual, &expected) {
+                return Err(format!("{}: events differ from parse", filename));
+            }
+            Ok(())
+        }
+        (Err(actual), Err(expected)) if actual.to_string() == expected.to_string() => Ok(()),
+        (actual, expected) => Err(format!(
+            "{}: Parser gave {:?}, parse gave {:?}",
+            filename,
+            actual.map(|_| ()),
+            expected.map(|_| ())
+        )),
+    }
+}
+
+#[test]
+fn test_parser_events_match_parse() {
+    let mut files = get_yay_files();
+    files.extend(get_nay_files());
+
+    let errors: Vec<String> = files
+        .iter()
+        .filter_map(|file| run_parser_events_test(file).err())
+        .collect();
+    for error in &errors {
+        println!("  - {}", error);
+    }
+    assert!(errors.is_empty(), "{} Parser tests failed", errors.len());
+}
+
+/// Feed a fixture to `PushParser` a few bytes at a time and compare with
+/// `parse`: the same value for a .yay file, and the same error for a .nay
+/// file.
+fn run_push_parser_test(path: &str, chunk: usize) -> Result<(), String> {
+    let content = fs::read(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let filename = Path::new(path)
+        .file_name()
+        .unwrap()
+        .to_string_lossy()
+        .to_string();
+    let mut options = ParseOptions::default();
+    options.filename = Some(filename.clone());
+
+    let mut parser = PushParser::new(&options);
+    let actual = content
+        .chunks(chunk)
+        .try_for_each(|bytes| parser.feed(bytes))
+        .and_then(|()| parser.finish());
+    match (actual, parse_bytes(&content, &options)) {
+        (Ok(actual), Ok(expected)) if values_equal(&actual, &expected) => Ok(()),
+        (Err(actual), Err(expected)) if actual.to_string() == expected.to_string() => Ok(()),
+        (actual, expected) => Err(format!(
+            "{} in chunks of {}: PushParser gave {:?}, parse gave {:?}",
+            filename, chunk, actual, expected
+        )),
+    }
+}
+
+#[test]
+fn test_push_parser_matches_parse() {
+    let mut files = get_yay_files();
+    files.extend(get_nay_files());
+
+    let errors: Vec<String> = files
+        .iter()
+        .flat_map(|file| [1, 5, 4096].map(|chunk| run_push_parser_test(file, chunk)))
+        .filter_map(Result::err)
+        .collect();
+    for error in &errors {
+        println!("  - {}", error);
+    }
+    assert!(
+        errors.is_empty(),
+        "{} PushParser tests failed",
+        errors.len()
+    );
+}
+
+/// Check that `parse_borrowed` agrees with `parse` on a fixture, and on its
+/// canonical encoding, which it reads without the full parser.
+fn run_parse_borrowed_test(path: &str) -> Result<(), String> {
+    let content =
+        fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let mut sources = vec![content.clone()];
+    if let Ok(value) = parse(&content) {
+        sources.push(encode(&value, Format::Yay) + "\n");
+    }
+    for source in &sources {
+        let actual = parse_borrowed(source).map(|value| value.into_owned());
+        match (actual, parse(source)) {
+            (Ok(actual), Ok(expected)) if values_equal(&actual, &expected) => {}
+            (Err(actual), Err(expected)) if actual.to_string() == expected.to_string() => {}
+            (actual, expected) => {
+                return Err(format!(
+                    "{}: parse_borrowed gave {:?}, parse gave {:?} for {:?}",
+                    path, actual, expected, source
+                ))
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_parse_borrowed_matches_parse() {
+    let mut files = get_yay_files();
+    files.extend(get_nay_files());
+
+    let errors: Vec<String> = files
+        .iter()
+        .map(|file| run_parse_borrowed_test(file))
+        .filter_map(Result::err)
+        .collect();
+    for error in &errors {
+        println!("  - {}", error);
+    }
+    assert!(
+        errors.is_empty(),
+        "{} parse_borrowed tests failed",
+        errors.len()
+    );
+}
+
+/// Check that the spans `parse_with_spans` finds in a fixture cover text
+/// that parses, on its own, as the value it is the span of.
+fn run_parse_with_spans_test(path: &str) -> Result<(), String> {
+    let source = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let spanned = parse_with_spans(&source, &ParseOptions::default())
+        .map_err(|e| format!("{}: {}", path, e))?;
+    if !values_equal(&spanned.to_value(), &parse(&source).unwrap()) {
+        return Err(format!("{}: spanned value differs from parse", path));
+    }
+    check_spans(path, &source, &spanned)
+}
+
+fn check_spans(path: &str, source: &str, spanned: &Spanned) -> Result<(), String> {
+    let text = source
+        .get(spanned.span.bytes.clone())
+        .ok_or_else(|| format!("{}: span {:?} is not in the source", path, spanned.span))?;
+    match &spanned.node {
+        Node::Scalar(value) if !text.contains('\n') => match parse(text) {
+            Ok(parsed) if values_equal(&parsed, value) => Ok(()),
+            _ => Err(format!(
+                "{}: span {:?} of {:?} is {:?}",
+                path, spanned.span, value, text
+            )),
+        },
+        Node::Scalar(_) => Ok(()),
+        Node::Array(items) => items
+            .iter()
+            .try_for_each(|item| check_spans(path, source, item)),
+        Node::Object(members) => members.iter().try_for_each(|(key, member)| {
+            let key_text = &source[member.key.bytes.clone()];
+            if !key_text.contains(key.as_str()) {
+                return Err(format!("{}: key span of {:?} is {:?}", path, key, key_text));
+            }
+            check_spans(path, source, &member.value)
+        }),
+    }
+}
+
+#[test]
+fn test_parse_with_spans() {
+    let errors: Vec<String> = get_yay_files()
+        .iter()
+        .map(|file| run_parse_with_spans_test(file))
+        .filter_map(Result::err)
+        .collect();
+    for error in &errors {
+        println!("  - {}", error);
+    }
+    assert!(
+        errors.is_empty(),
+        "{} parse_with_spans tests failed",
+        errors.len()
+    );
+}
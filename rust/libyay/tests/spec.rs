@@ -0,0 +1,273 @@
+//! The executable specification of YAY.
+//!
+//! Each row of the table below is a document and what it means: the
+//! [`Value`] it parses to, or the `ParseError::code` of the error it must
+//! fail with. The sections follow GRAMMAR.md, so a change to what the
+//! parser accepts or produces fails a named test instead of passing
+//! unnoticed. Each row becomes its own `#[test]`, named `section::row`.
+//!
+//! Only the default [`parse`] is covered. Extensions a caller opts into
+//! through `ParseOptions`, such as hexadecimal floats and the `%yay`
+//! directive, are not part of GRAMMAR.md and are tested where they are
+//! implemented. The parser also accepts non-ASCII letters in bare keys,
+//! which GRAMMAR.md does not yet describe.
+//!
+//! Expected values are written with the `yay!` macro rather than parsed, so
+//! the table does not lean on the parser it checks.
+
+use libyay::{parse, yay, Value};
+
+/// What a row's document must parse to.
+enum Expect {
+    Value(Value),
+    Error(&'static str),
+}
+
+fn value(value: Value) -> Expect {
+    Expect::Value(value)
+}
+
+fn error(code: &'static str) -> Expect {
+    Expect::Error(code)
+}
+
+fn float(f: f64) -> Value {
+    Value::Float(f)
+}
+
+fn check(input: &str, expect: Expect) {
+    match (parse(input), expect) {
+        (Ok(actual), Expect::Value(expected)) => {
+            assert_eq!(actual, expected, "wrong value for {:?}", input)
+        }
+        (Err(e), Expect::Error(code)) => {
+            assert_eq!(e.code(), code, "wrong error for {:?}: {}", input, e)
+        }
+        (Ok(actual), Expect::Error(code)) => {
+            panic!("{:?} parsed to {:?}, expected {}", input, actual, code)
+        }
+        (Err(e), Expect::Value(expected)) => panic!(
+            "{:?} failed with {} ({}), expected {:?}",
+            input,
+            e,
+            e.code(),
+            expected
+        ),
+    }
+}
+
+/// Turn sections of `name: input => expectation,` rows into a module of
+/// tests per section.
+macro_rules! spec {
+    ($($section:ident { $($(#[$attr:meta])* $name:ident: $input:expr => $expect:expr,)* })*) => {
+        $(
+            mod $section {
+                use super::*;
+
+                $(
+                    #[test]
+                    $(#[$attr])*
+                    fn $name() {
+                        check($input, $expect);
+                    }
+                )*
+            }
+        )*
+    };
+}
+
+spec! {
+    source_encoding {
+        utf8: "\"é✓😀\"" => value(yay!("é✓😀")),
+        bom: "\u{FEFF}1" => error("illegal_bom"),
+        tab: "a:\t1" => error("tab_not_allowed"),
+        carriage_return: "1\r\n" => error("forbidden_code_point"),
+        control: "\"\u{1}\"" => error("forbidden_code_point"),
+        noncharacter: "\"\u{FDD0}\"" => error("forbidden_code_point"),
+        plane_noncharacter: "\"\u{1FFFE}\"" => error("forbidden_code_point"),
+    }
+
+    lines {
+        trailing_newline: "1\n" => value(yay!(1)),
+        trailing_blank_lines: "1\n\n\n" => value(yay!(1)),
+        trailing_space: "1 " => error("trailing_space"),
+        trailing_space_in_block: "a:\n  b: 1 \n" => error("trailing_space"),
+    }
+
+    document {
+        comments_before_root: "# one\n# two\n1" => value(yay!(1)),
+        only_comments: "# nothing\n" => error("no_value_found"),
+        empty: "" => error("no_value_found"),
+        root_object: "a: 1\nb: 2" => value(yay!({ a: 1, b: 2 })),
+        root_inline_object: "{a: 1}" => value(yay!({ a: 1 })),
+        content_after_root: "1\n2" => error("extra_content"),
+        leading_space: " 1" => error("unexpected_indent"),
+    }
+
+    null {
+        keyword: "null" => value(yay!(null)),
+        capitalized: "Null" => error("unexpected_char"),
+    }
+
+    booleans {
+        true_keyword: "true" => value(yay!(true)),
+        false_keyword: "false" => value(yay!(false)),
+        capitalized: "True" => error("unexpected_char"),
+    }
+
+    integers {
+        zero: "0" => value(yay!(0)),
+        negative: "-42" => value(yay!(-42)),
+        big: "123456789012345678901234567890" => value(Value::Integer(
+            "123456789012345678901234567890".parse().unwrap()
+        )),
+        grouped: "1 000 000" => value(yay!(1_000_000)),
+        space_after_minus: "- 1" => value(yay!([1])),
+        double_space: "1  000" => error("unexpected_space_in_number"),
+    }
+
+    floats {
+        point: "1.5" => value(float(1.5)),
+        leading_point: ".5" => value(float(0.5)),
+        trailing_point: "1." => value(float(1.0)),
+        negative: "-2.25" => value(float(-2.25)),
+        negative_zero: "-0.0" => value(float(-0.0)),
+        exponent: "1e3" => value(float(1000.0)),
+        signed_exponent: "2.5e-1" => value(float(0.25)),
+        grouped: "1 000.000 5" => value(float(1000.0005)),
+        uppercase_exponent: "1E3" => error("uppercase_exponent"),
+        nan: "nan" => value(float(f64::NAN)),
+        infinity: "infinity" => value(float(f64::INFINITY)),
+        negative_infinity: "-infinity" => value(float(f64::NEG_INFINITY)),
+        lone_point: "." => error("unexpected_char"),
+        lone_negative_point: "-." => error("unexpected_char"),
+    }
+
+    double_quoted {
+        plain: "\"hello\"" => value(yay!("hello")),
+        empty: "\"\"" => value(yay!("")),
+        escapes: r#""\"\\\/\b\f\n\r\t""# => value(yay!("\"\\/\u{8}\u{c}\n\r\t")),
+        unicode_escape: r#""\u{41}\u{1F600}""# => value(yay!("A😀")),
+        short_unicode_escape: r#""\u0041""# => error("bad_escaped_char"),
+        surrogate_escape: r#""\u{D800}""# => error("illegal_surrogate"),
+        escape_out_of_range: r#""\u{110000}""# => error("unicode_out_of_range"),
+        unknown_escape: r#""\q""# => error("bad_escaped_char"),
+        unterminated: "\"open" => error("unterminated_string"),
+        lone_quote: "\"" => error("unterminated_string"),
+        colon: "\"a: b\"" => value(yay!("a: b")),
+    }
+
+    single_quoted {
+        plain: "'hello'" => value(yay!("hello")),
+        escaped_quote: r"'it\'s'" => value(yay!("it's")),
+        escaped_backslash: r"'a\\b'" => value(yay!("a\\b")),
+        literal_backslash: r"'a\nb'" => value(yay!("a\\nb")),
+        unterminated: "'open" => error("unterminated_string"),
+        lone_quote: "'" => error("unterminated_string"),
+        lone_quote_property: "a: '" => error("unterminated_string"),
+    }
+
+    block_strings {
+        root_same_line: "` first\n  second\n" => value(yay!("first\nsecond\n")),
+        root_alone: "`\n  line\n" => value(yay!("\nline\n")),
+        property: "a: `\n  one\n    two\n" => value(yay!({ a: "one\n  two\n" })),
+        inner_blank_line: "a: `\n  one\n\n  two\n" => value(yay!({ a: "one\n\ntwo\n" })),
+        trailing_blank_lines: "a: `\n  one\n\n\n" => value(yay!({ a: "one\n" })),
+        literal_content: "a: `\n  \\n # not a comment\n" => value(yay!({ a: "\\n # not a comment\n" })),
+        property_same_line: "a: ` text" => error("expected_newline_after_block_leader"),
+        item: "- `\n  text\n" => value(yay!(["\ntext\n"])),
+    }
+
+    concatenated_strings {
+        two: "a:\n  \"one \"\n  'two'\n" => value(yay!({ a: "one two" })),
+        three: "a:\n  \"x\"\n  \"y\"\n  \"z\"\n" => value(yay!({ a: "xyz" })),
+    }
+
+    inline_bytes {
+        empty: "<>" => value(yay!(<>)),
+        pairs: "<cafe babe>" => value(yay!(<cafebabe>)),
+        uppercase: "<CAFE>" => error("uppercase_hex"),
+        odd: "<abc>" => error("odd_hex_digits"),
+        not_hex: "<zz>" => error("invalid_hex_digit"),
+        space_after_open: "< ab>" => error("unexpected_space_after"),
+        unclosed: "<ab" => error("unmatched_angle"),
+    }
+
+    block_bytes {
+        root: "> cafe\n  babe\n" => value(yay!(<cafebabe>)),
+        root_comment: "> # bytes\n  cafe\n" => value(yay!(<cafe>)),
+        root_bare: ">\n  cafe\n" => error("expected_hex_in_block"),
+        property: "a: >\n  cafe # two\n  babe\n" => value(yay!({ a: <cafebabe> })),
+        property_comment: "a: > # bytes\n  cafe\n" => value(yay!({ a: <cafe> })),
+        property_same_line: "a: > cafe\n" => error("expected_newline_after_block_leader"),
+    }
+
+    inline_arrays {
+        empty: "[]" => value(yay!([])),
+        items: "[1, \"two\", null]" => value(yay!([1, "two", null])),
+        nested: "a: [[1], {b: <ff>}]" => value(yay!({ a: [[1], { b: <ff> }] })),
+        root_object_item: "[{a: 1}]" => value(yay!([{ a: 1 }])),
+        space_after_open: "[ 1]" => error("unexpected_space_after"),
+        space_before_close: "[1 ]" => error("unexpected_space_before"),
+        no_space_after_comma: "[1,2]" => error("expected_space_after"),
+        two_spaces_after_comma: "[1,  2]" => error("unexpected_space_after"),
+        space_before_comma: "[1 , 2]" => error("unexpected_space_before"),
+        unclosed: "[1, 2" => error("unexpected_newline"),
+        grouped_number: "[1 000]" => error("unexpected_space_in_number"),
+    }
+
+    block_arrays {
+        items: "- 1\n- 2\n" => value(yay!([1, 2])),
+        nested_bullets: "- - - \"deep\"\n" => value(yay!([[["deep"]]])),
+        nested_block: "- - 1\n  - 2\n" => value(yay!([[1, 2]])),
+        nested_in_nested: "- - - 1\n    - 2\n  - 3\n" => value(yay!([[[1, 2], 3]])),
+        nested_later_item: "- - 1\n  - - 2\n" => value(yay!([[1, [2]]])),
+        nested_item_object: "- - 1\n  - a: 1\n    b: 2\n" => value(yay!([[1, { a: 1, b: 2 }]])),
+        nested_empty_item: "- -\n" => error("unexpected_char"),
+        item_object: "- a: 1\n  b: 2\n" => value(yay!([{ a: 1, b: 2 }])),
+        item_object_block_bytes: "- a: >\n    cafe\n  b: 1\n" => value(yay!([{ a: <cafe>, b: 1 }])),
+        item_quoted_key: "- \"a\": 1\n  \"b\": 2\n" => value(yay!([{ a: 1, b: 2 }])),
+        item_string_with_colon: "- \"a: b\"\n" => value(yay!(["a: b"])),
+        property: "a:\n  - 1\n" => value(yay!({ a: [1] })),
+        empty_item: "-\n" => error("unexpected_char"),
+        two_spaces_after_dash: "-  1\n" => error("leading_space"),
+        no_space_after_dash: "-1\n-2\n" => error("extra_content"),
+    }
+
+    inline_objects {
+        empty: "{}" => value(yay!({})),
+        entries: "{a: 1, 'b c': [2]}" => value(yay!({ a: 1, "b c": [2] })),
+        space_after_open: "{ a: 1}" => error("unexpected_space_after"),
+        space_before_close: "{a: 1 }" => error("unexpected_space_before"),
+        no_space_after_colon: "{a:1}" => error("expected_space_after"),
+        unclosed: "{a: 1" => error("unexpected_newline"),
+    }
+
+    block_objects {
+        nested: "a:\n  b:\n    c: 1\n" => value(yay!({ a: { b: { c: 1 } } })),
+        empty_property: "a: {}\n" => value(yay!({ a: {} })),
+        comment_after_value: "a: 1 # one\n" => value(yay!({ a: 1 })),
+        missing_value: "a:\n" => error("expected_value_after_property"),
+        no_space_after_colon: "a:1\n" => error("expected_space_after"),
+        duplicate_key: "a: 1\na: 2\n" => value(yay!({ a: 2 })),
+    }
+
+    keys {
+        bare: "a_b-C9: 1" => value(yay!({ "a_b-C9": 1 })),
+        double_quoted: "\"a b\": 1" => value(yay!({ "a b": 1 })),
+        single_quoted: "'a b': 1" => value(yay!({ "a b": 1 })),
+        escaped_quote: r#""a\"b": 1"# => value(yay!({ "a\"b": 1 })),
+        unknown_escape: r#""a\qb": 1"# => error("bad_escaped_char"),
+        empty_quoted: "a:\n  \"\": 1\n  b: 2\n" => value(yay!({ a: { "": 1, b: 2 } })),
+        space_before_colon: "a : 1" => error("unexpected_space_before"),
+        invalid_char: "a.b: 1" => error("invalid_key_char"),
+        dollar: "$a: 1" => error("invalid_key_char"),
+        non_ascii: "\u{e9}t\u{e9}: 1" => value(yay!({ "\u{e9}t\u{e9}": 1 })),
+    }
+
+    comments {
+        top_level: "# comment\na: 1" => value(yay!({ a: 1 })),
+        after_item: "- 1 # one\n" => value(yay!([1])),
+        in_string: "\"# not\"" => value(yay!("# not")),
+    }
+}
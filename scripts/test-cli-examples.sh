@@ -87,10 +87,30 @@ EOF
 {"a": 1, "b": 2}
 EOF
 
+  # events.ndyay - newline-delimited YAY records
+  cat >"$TEMP_DIR/events.ndyay" <<'EOF'
+{event: "start", at: "09:00"}
+{event: "stop", at: "17:00"}
+EOF
+
+  # app-config.yay and config.go.tmpl - input and template for --template
+  echo 'port: 8080' >"$TEMP_DIR/app-config.yay"
+  cat >"$TEMP_DIR/config.go.tmpl" <<'EOF'
+package config
+
+var {{symbol}} = {{value}}
+EOF
+
+  # config.yay.zst - compressed copy of config.yay
+  "$YAY" --compress zstd -o "$TEMP_DIR/config.yay.zst" "$TEMP_DIR/config.yay"
+
   # Create a configs directory with some .yay files
   mkdir -p "$TEMP_DIR/configs"
   echo 'a: 1' >"$TEMP_DIR/configs/one.yay"
   echo 'b: 2' >"$TEMP_DIR/configs/two.yay"
+
+  # fixtures.tar.gz - a bundle of the configs directory
+  tar -czf "$TEMP_DIR/fixtures.tar.gz" -C "$TEMP_DIR" configs
 }
 
 # Parse a bash code block and extract command and expected output
@@ -169,8 +189,11 @@ transform_command() {
   # Replace file paths with temp dir FIRST (before yay replacement)
   # Order matters - replace more specific patterns first
   cmd="${cmd//\.\/configs\//$TEMP_DIR/configs/}"
-  cmd="${cmd//config.yay/$TEMP_DIR/config.yay}"
+  cmd="${cmd//app-config.yay/$TEMP_DIR/app-config.yay}"
+  cmd=$(echo "$cmd" | sed "s#\(^\| \)config\.yay#\1$TEMP_DIR/config.yay#g")
+  cmd="${cmd//fixtures.tar.gz/$TEMP_DIR/fixtures.tar.gz}"
   cmd="${cmd//config.go/$TEMP_DIR/config.go}"
+  cmd="${cmd//events.ndyay/$TEMP_DIR/events.ndyay}"
   cmd="${cmd//data.yay/$TEMP_DIR/data.yay}"
   cmd="${cmd//data.yson/$TEMP_DIR/data.yson}"
   cmd="${cmd//data.json/$TEMP_DIR/data.json}"
@@ -182,8 +205,9 @@ transform_command() {
   cmd=$(echo "$cmd" | sed "s| \\.| $TEMP_DIR|g")
 
   # Replace 'yay' command with full path (only at word boundaries)
-  # Use sed to be more precise - only match 'yay' as a command, not in paths
-  cmd=$(echo "$cmd" | sed "s|^yay |$YAY |")
+  # Use sed to be more precise - only match 'yay' as a command, not in paths:
+  # at the start, after a pipe, or after an environment assignment
+  cmd=$(echo "$cmd" | sed "s#\(^\|| \|^[A-Z_]*=[^ ]* \)yay #\1$YAY #g")
 
   # Remove output redirections for testing (we capture stdout)
   cmd=$(echo "$cmd" | sed 's/ *> *[^ ]*$//')
@@ -199,7 +223,7 @@ run_example() {
   local line_num="$4"
   local expects_error="$5"
 
-  ((tests_run++))
+  tests_run=$((tests_run + 1))
 
   local cmd
   cmd=$(transform_command "$original_cmd")
@@ -212,7 +236,7 @@ run_example() {
   # For --check commands, we just verify exit code
   if [[ "$original_cmd" =~ --check ]]; then
     if [[ $status -eq 0 ]]; then
-      ((tests_passed++))
+      tests_passed=$((tests_passed + 1))
       echo -e "${GREEN}PASS${NC}: line $line_num: $original_cmd"
       return 0
     else
@@ -230,7 +254,7 @@ run_example() {
       # Command failed as expected, check output if specified
       if [[ "$has_expected" == true ]]; then
         if [[ "$output" == "$expected" ]]; then
-          ((tests_passed++))
+          tests_passed=$((tests_passed + 1))
           echo -e "${GREEN}PASS${NC}: line $line_num: $original_cmd (expected error)"
           return 0
         else
@@ -244,7 +268,7 @@ run_example() {
         fi
       else
         # Just expected to fail, no specific output required
-        ((tests_passed++))
+        tests_passed=$((tests_passed + 1))
         echo -e "${GREEN}PASS${NC}: line $line_num: $original_cmd (expected error)"
         return 0
       fi
@@ -260,7 +284,7 @@ run_example() {
   # For commands with expected output, verify it
   if [[ "$has_expected" == true ]]; then
     if [[ "$output" == "$expected" ]]; then
-      ((tests_passed++))
+      tests_passed=$((tests_passed + 1))
       echo -e "${GREEN}PASS${NC}: line $line_num: $original_cmd"
       return 0
     else
@@ -276,7 +300,7 @@ run_example() {
 
   # For commands without expected output, just verify they succeed
   if [[ $status -eq 0 ]]; then
-    ((tests_passed++))
+    tests_passed=$((tests_passed + 1))
     echo -e "${GREEN}PASS${NC}: line $line_num: $original_cmd"
     return 0
   else
@@ -299,7 +323,7 @@ main() {
   local line_num=0
 
   while IFS= read -r line; do
-    ((line_num++))
+    line_num=$((line_num + 1))
 
     if [[ "$in_code_block" == true ]]; then
       if [[ "$line" == '```' ]]; then
@@ -9,6 +9,10 @@
 #   - Error: Invalid .nay files are rejected
 #   - Transcode: YAY -> YAML/TOML/CBOR against golden fixtures
 #   - Ingest: YAML/TOML/CBOR -> YAY against golden fixtures
+#   - Limits: --timeout and --max-memory exit with code 3
+#   - Merge: mixed input formats, trailing -f, and -t json refusing BigInts
+#   - Rewrite: -w refusing to drop comments without --force
+#   - Subset: --head, --tail, and --sample recording the lengths they cut
 #
 # Exit codes: 0=pass, 1=fail, 2=skip
 
@@ -226,6 +230,14 @@ run_output_format_tests() {
     done
   done
 
+  # --help names the --template placeholders as they are written.
+  "$YAY" --help >"$tmp"
+  if grep -qF '{{value}}' "$tmp" && grep -qF '{{symbol}}' "$tmp" && grep -qF '{{source_path}}' "$tmp"; then
+    pass "output/help-template-placeholders"
+  else
+    fail "output/help-template-placeholders"
+  fi
+
   echo ""
 }
 
@@ -467,6 +479,218 @@ run_ingest_tests() {
   echo ""
 }
 
+# =============================================================================
+# 8. Limit Tests
+# =============================================================================
+
+run_limit_tests() {
+  echo "=== Limit Tests (--timeout, --max-memory) ==="
+  echo ""
+
+  local tmp status
+  tmp=$(mktemp -d)
+  trap 'rm -rf "$tmp"' RETURN
+
+  # Long enough that converting it takes far more than the timeout.
+  seq 1 300000 | sed 's/^/- "item /; s/$/"/' >"$tmp/big.yay"
+  echo "old" >"$tmp/out.json"
+
+  status=0
+  "$YAY" --timeout 0.01 -t json -o "$tmp/out.json" "$tmp/big.yay" 2>/dev/null || status=$?
+  if [[ $status -ne 3 ]]; then
+    fail "limits/timeout (exit $status, expected 3)"
+  elif [[ "$(cat "$tmp/out.json")" != "old" ]]; then
+    fail "limits/timeout (output file changed)"
+  else
+    pass "limits/timeout"
+  fi
+
+  status=0
+  "$YAY" --max-memory 1MiB -t json "$tmp/big.yay" >/dev/null 2>&1 || status=$?
+  if [[ $status -eq 3 ]]; then
+    pass "limits/max-memory"
+  else
+    fail "limits/max-memory (exit $status, expected 3)"
+  fi
+
+  status=0
+  "$YAY" --timeout 60 --max-memory 1GiB -t json -o "$tmp/out.json" "$tmp/big.yay" 2>/dev/null || status=$?
+  if [[ $status -eq 0 ]] && "$YAY" -f json --check "$tmp/out.json" >/dev/null 2>&1; then
+    pass "limits/within"
+  else
+    fail "limits/within (exit $status)"
+  fi
+
+  echo ""
+}
+
+# =============================================================================
+# 9. Merge Tests
+# =============================================================================
+
+run_merge_tests() {
+  echo "=== Merge Tests (yay merge) ==="
+  echo ""
+
+  local tmp status actual
+  tmp=$(mktemp -d)
+  trap 'rm -rf "$tmp"' RETURN
+
+  printf 'a: 1\nb: "x"\n' >"$tmp/base.yay"
+  printf '{"b": "y", "c": [true]}' >"$tmp/over.json"
+  printf 'd = "z"\n' >"$tmp/over.toml"
+
+  # Each -f applies to the input that follows it.
+  actual=$("$YAY" merge "$tmp/base.yay" -f json "$tmp/over.json" -f toml "$tmp/over.toml" 2>&1)
+  if [[ "$actual" == $'a: 1\nb: "y"\nc: [true]\nd: "z"' ]]; then
+    pass "merge/mixed-formats"
+  else
+    fail "merge/mixed-formats"
+  fi
+
+  status=0
+  "$YAY" merge "$tmp/base.yay" -f >/dev/null 2>&1 || status=$?
+  if [[ $status -ne 0 ]]; then
+    pass "merge/trailing-format"
+  else
+    fail "merge/trailing-format (expected an error)"
+  fi
+
+  status=0
+  actual=$("$YAY" merge -t json "$tmp/base.yay" -f json "$tmp/over.json" 2>&1) || status=$?
+  if [[ $status -ne 0 && "$actual" == *"YAY integers are BigInts"* ]]; then
+    pass "merge/json-refuses-bigint"
+  else
+    fail "merge/json-refuses-bigint (exit $status)"
+  fi
+
+  echo ""
+}
+
+# =============================================================================
+# 10. Rewrite Tests
+# =============================================================================
+
+run_rewrite_tests() {
+  echo "=== Rewrite Tests (-w and --force) ==="
+  echo ""
+
+  local tmp status
+  tmp=$(mktemp -d)
+  trap 'rm -rf "$tmp"' RETURN
+
+  printf '# important\na: 1 # note\n' >"$tmp/commented.yay"
+  printf 'a: 1\n' >"$tmp/plain.yay"
+  printf -- '- {op: "add", path: "/b", value: 2}\n' >"$tmp/patch.yay"
+
+  # Patching rewrites through a value, so comments would be lost.
+  cp "$tmp/commented.yay" "$tmp/doc.yay"
+  status=0
+  "$YAY" patch -w "$tmp/patch.yay" "$tmp/doc.yay" 2>/dev/null || status=$?
+  if [[ $status -ne 0 ]] && cmp -s "$tmp/doc.yay" "$tmp/commented.yay"; then
+    pass "rewrite/patch-keeps-commented"
+  else
+    fail "rewrite/patch-keeps-commented (exit $status)"
+  fi
+
+  status=0
+  "$YAY" patch -w --force "$tmp/patch.yay" "$tmp/doc.yay" 2>/dev/null || status=$?
+  if [[ $status -eq 0 && "$(cat "$tmp/doc.yay")" == '{a: 1, b: 2}' ]]; then
+    pass "rewrite/patch-force"
+  else
+    fail "rewrite/patch-force (exit $status)"
+  fi
+
+  cp "$tmp/plain.yay" "$tmp/doc.yay"
+  status=0
+  "$YAY" patch -w "$tmp/patch.yay" "$tmp/doc.yay" 2>/dev/null || status=$?
+  if [[ $status -eq 0 && "$(cat "$tmp/doc.yay")" == '{a: 1, b: 2}' ]]; then
+    pass "rewrite/patch-uncommented"
+  else
+    fail "rewrite/patch-uncommented (exit $status)"
+  fi
+
+  # A merge patch goes through a value too.
+  printf 'b: 2\n' >"$tmp/merge.yay"
+  cp "$tmp/commented.yay" "$tmp/doc.yay"
+  status=0
+  "$YAY" patch --merge -w "$tmp/merge.yay" "$tmp/doc.yay" 2>/dev/null || status=$?
+  if [[ $status -ne 0 ]] && cmp -s "$tmp/doc.yay" "$tmp/commented.yay"; then
+    pass "rewrite/merge-patch-keeps-commented"
+  else
+    fail "rewrite/merge-patch-keeps-commented (exit $status)"
+  fi
+
+  status=0
+  "$YAY" patch --merge -w --force "$tmp/merge.yay" "$tmp/doc.yay" 2>/dev/null || status=$?
+  if [[ $status -eq 0 && "$(cat "$tmp/doc.yay")" == '{a: 1, b: 2}' ]]; then
+    pass "rewrite/merge-patch-force"
+  else
+    fail "rewrite/merge-patch-force (exit $status)"
+  fi
+
+  printf -- '- version: 2\n  steps:\n    - rename: {path: "/a", to: "c"}\n' >"$tmp/migrations.yay"
+  cp "$tmp/commented.yay" "$tmp/doc.yay"
+  status=0
+  "$YAY" migrate -w "$tmp/doc.yay" 2>/dev/null || status=$?
+  if [[ $status -ne 0 ]] && cmp -s "$tmp/doc.yay" "$tmp/commented.yay"; then
+    pass "rewrite/migrate-keeps-commented"
+  else
+    fail "rewrite/migrate-keeps-commented (exit $status)"
+  fi
+
+  status=0
+  "$YAY" migrate -w --force "$tmp/doc.yay" 2>/dev/null || status=$?
+  if [[ $status -eq 0 && "$(cat "$tmp/doc.yay")" == '{c: 1, version: 2}' ]]; then
+    pass "rewrite/migrate-force"
+  else
+    fail "rewrite/migrate-force (exit $status)"
+  fi
+
+  echo ""
+}
+
+# =============================================================================
+# 11. Subset Tests
+# =============================================================================
+
+run_subset_tests() {
+  echo "=== Subset Tests (--head, --tail, --sample) ==="
+  echo ""
+
+  local tmp actual errors
+  tmp=$(mktemp -d)
+  trap 'rm -rf "$tmp"' RETURN
+
+  printf 'runs:\n  - points: [1, 2, 3]\n  - points: [4]\n  - points: [5, 6, 7, 8]\n' >"$tmp/runs.yay"
+
+  # Formats with comments record each array cut at the top of the output.
+  actual=$("$YAY" --tail 2 "$tmp/runs.yay" 2>&1)
+  if [[ "$actual" == $'# runs: kept 2 of 3 items\n# runs[2].points: kept 2 of 4 items\nruns:\n  - points: [4]\n  - points: [7, 8]' ]]; then
+    pass "subset/yay-comments"
+  else
+    fail "subset/yay-comments"
+  fi
+
+  actual=$("$YAY" --head 1 -t js "$tmp/runs.yay" 2>/dev/null | head -2)
+  if [[ "$actual" == $'// runs: kept 1 of 3 items\n// runs[0].points: kept 1 of 3 items' ]]; then
+    pass "subset/js-comments"
+  else
+    fail "subset/js-comments"
+  fi
+
+  # Formats without comments report them on stderr.
+  actual=$("$YAY" --sample 2 -t yson "$tmp/runs.yay" 2>/dev/null)
+  errors=$("$YAY" --sample 2 -t yson "$tmp/runs.yay" 2>&1 >/dev/null)
+  if [[ "$actual" != *"kept"* && "$errors" == *"runs: kept 2 of 3 items"* && "$errors" == *"runs[2].points: kept 2 of 4 items"* ]]; then
+    pass "subset/yson-stderr"
+  else
+    fail "subset/yson-stderr"
+  fi
+
+  echo ""
+}
+
 # =============================================================================
 # CLI Examples Tests (verify CLI.md examples work as documented)
 # =============================================================================
@@ -535,15 +759,27 @@ while [[ $# -gt 0 ]]; do
   ingest | ing)
     TESTS_TO_RUN="$TESTS_TO_RUN ingest"
     ;;
+  limits | lim)
+    TESTS_TO_RUN="$TESTS_TO_RUN limits"
+    ;;
+  merge | mg)
+    TESTS_TO_RUN="$TESTS_TO_RUN merge"
+    ;;
+  rewrite | rw)
+    TESTS_TO_RUN="$TESTS_TO_RUN rewrite"
+    ;;
+  subset | sub)
+    TESTS_TO_RUN="$TESTS_TO_RUN subset"
+    ;;
   examples | ex)
     TESTS_TO_RUN="$TESTS_TO_RUN examples"
     ;;
   all | "")
-    TESTS_TO_RUN="idempotence reformat roundtrip output error transcode ingest examples"
+    TESTS_TO_RUN="idempotence reformat roundtrip output error transcode ingest limits merge rewrite subset examples"
     ;;
   *)
     echo "Unknown test category: $1"
-    echo "Usage: $0 [idempotence|reformat|roundtrip|output|error|transcode|ingest|examples|all]"
+    echo "Usage: $0 [idempotence|reformat|roundtrip|output|error|transcode|ingest|limits|merge|rewrite|subset|examples|all]"
     exit 1
     ;;
   esac
@@ -552,7 +788,7 @@ done
 
 # Default to all tests
 if [[ -z "$TESTS_TO_RUN" ]]; then
-  TESTS_TO_RUN="idempotence reformat roundtrip output error transcode ingest examples"
+  TESTS_TO_RUN="idempotence reformat roundtrip output error transcode ingest limits merge rewrite subset examples"
 fi
 
 # Run selected tests
@@ -565,6 +801,10 @@ for test in $TESTS_TO_RUN; do
   error) run_error_tests ;;
   transcode) run_transcode_tests ;;
   ingest) run_ingest_tests ;;
+  limits) run_limit_tests ;;
+  merge) run_merge_tests ;;
+  rewrite) run_rewrite_tests ;;
+  subset) run_subset_tests ;;
   examples) run_cli_examples_tests ;;
   esac
 done
@@ -1,4 +1,4 @@
-Value::Object(HashMap::from([
+Value::Object(Map::from_iter([
     ("complaints".into(), Value::Array(vec![
         Value::String("I didn't vote for you.".into()),
         Value::String("Help, help, I'm being repressed!".into()),
@@ -1,8 +1,8 @@
-Value::Object(HashMap::from([(
+Value::Object(Map::from_iter([(
     "level1".into(),
-    Value::Object(HashMap::from([(
+    Value::Object(Map::from_iter([(
         "level2".into(),
-        Value::Object(HashMap::from([(
+        Value::Object(Map::from_iter([(
             "data".into(),
             Value::Bytes(vec![0xb0, 0xb5, 0xc0, 0xff]),
         )])),
@@ -1,4 +1,4 @@
-Value::Object(HashMap::from([(
+Value::Object(Map::from_iter([(
     "data".into(),
     Value::Bytes(vec![0xb0, 0xb5, 0xc0, 0xff]),
 )]))
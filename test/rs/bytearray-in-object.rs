@@ -1,4 +1,4 @@
-Value::Object(HashMap::from([
+Value::Object(Map::from_iter([
     ("a".into(), Value::Bytes(vec![0xb0, 0xb5])),
     ("b".into(), Value::Bytes(vec![0xca, 0xfe])),
 ]))
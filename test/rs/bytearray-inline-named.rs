@@ -1,3 +1,3 @@
-Value::Object(HashMap::from([
+Value::Object(Map::from_iter([
     ("data".into(), Value::Bytes(vec![0xb0, 0xb5, 0xc0, 0xff, 0xee, 0xfa, 0xca, 0xde])),
 ]))
@@ -1,13 +1,13 @@
-Value::Object(HashMap::from([(
+Value::Object(Map::from_iter([(
     "root".into(),
-    Value::Object(HashMap::from([
+    Value::Object(Map::from_iter([
         (
             "a".into(),
             Value::Array(vec![Value::Integer(1.into()), Value::Integer(2.into())]),
         ),
         (
             "b".into(),
-            Value::Object(HashMap::from([
+            Value::Object(Map::from_iter([
                 ("c".into(), Value::Array(vec![Value::Integer(3.into())])),
                 ("d".into(), Value::Integer(4.into())),
             ])),
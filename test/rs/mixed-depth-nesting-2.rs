@@ -1,8 +1,8 @@
 Value::Array(vec![
-    Value::Object(HashMap::from([
+    Value::Object(Map::from_iter([
         (
             "a".into(),
-            Value::Object(HashMap::from([(
+            Value::Object(Map::from_iter([(
                 "b".into(),
                 Value::Array(vec![Value::Integer(1.into()), Value::Integer(2.into())]),
             )])),
@@ -11,9 +11,9 @@ Value::Array(vec![
     ])),
     Value::Array(vec![
         Value::String("d".into()),
-        Value::Object(HashMap::from([(
+        Value::Object(Map::from_iter([(
             "e".into(),
-            Value::Object(HashMap::from([("f".into(), Value::Integer(4.into()))])),
+            Value::Object(Map::from_iter([("f".into(), Value::Integer(4.into()))])),
         )])),
     ]),
 ])
@@ -1,16 +1,16 @@
-Value::Object(HashMap::from([(
+Value::Object(Map::from_iter([(
     "top".into(),
-    Value::Object(HashMap::from([
+    Value::Object(Map::from_iter([
         (
             "list".into(),
             Value::Array(vec![
-                Value::Object(HashMap::from([
+                Value::Object(Map::from_iter([
                     ("bytes".into(), Value::Bytes(vec![0xb0, 0xb5])),
                     ("tag".into(), Value::String("x".into())),
                 ])),
-                Value::Object(HashMap::from([(
+                Value::Object(Map::from_iter([(
                     "more".into(),
-                    Value::Object(HashMap::from([(
+                    Value::Object(Map::from_iter([(
                         "inner".into(),
                         Value::Bytes(vec![0x0f, 0x0f]),
                     )])),
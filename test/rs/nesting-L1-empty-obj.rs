@@ -1 +1 @@
-Value::Object(HashMap::new())
+Value::Object(Map::new())
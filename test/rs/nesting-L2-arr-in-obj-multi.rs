@@ -1,4 +1,4 @@
-Value::Object(HashMap::from([(
+Value::Object(Map::from_iter([(
     "items".into(),
     Value::Array(vec![
         Value::Integer(42.into()),
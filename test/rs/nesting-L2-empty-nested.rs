@@ -1,4 +1,4 @@
-Value::Object(HashMap::from([
-    ("a".into(), Value::Object(HashMap::new())),
+Value::Object(Map::from_iter([
+    ("a".into(), Value::Object(Map::new())),
     ("b".into(), Value::Array(vec![])),
 ]))
@@ -1,7 +1,7 @@
 Value::Array(vec![
-    Value::Object(HashMap::from([
+    Value::Object(Map::from_iter([
         ("a".into(), Value::Integer(42.into())),
         ("b".into(), Value::String("hello".into())),
     ])),
-    Value::Object(HashMap::from([("c".into(), Value::Integer(42.into()))])),
+    Value::Object(Map::from_iter([("c".into(), Value::Integer(42.into()))])),
 ])
@@ -1,10 +1,10 @@
-Value::Object(HashMap::from([
+Value::Object(Map::from_iter([
     (
         "a".into(),
-        Value::Object(HashMap::from([("x".into(), Value::Integer(42.into()))])),
+        Value::Object(Map::from_iter([("x".into(), Value::Integer(42.into()))])),
     ),
     (
         "b".into(),
-        Value::Object(HashMap::from([("y".into(), Value::String("hello".into()))])),
+        Value::Object(Map::from_iter([("y".into(), Value::String("hello".into()))])),
     ),
 ]))
@@ -1,13 +1,13 @@
-Value::Object(HashMap::from([
+Value::Object(Map::from_iter([
     (
         "a".into(),
-        Value::Object(HashMap::from([
+        Value::Object(Map::from_iter([
             ("x".into(), Value::Integer(42.into())),
             ("y".into(), Value::String("hello".into())),
         ])),
     ),
     (
         "b".into(),
-        Value::Object(HashMap::from([("z".into(), Value::Integer(42.into()))])),
+        Value::Object(Map::from_iter([("z".into(), Value::Integer(42.into()))])),
     ),
 ]))
@@ -1,4 +1,4 @@
-Value::Array(vec![Value::Array(vec![Value::Object(HashMap::from([
+Value::Array(vec![Value::Array(vec![Value::Object(Map::from_iter([
     ("a".into(), Value::Integer(42.into())),
     ("b".into(), Value::String("hello".into())),
 ]))])])
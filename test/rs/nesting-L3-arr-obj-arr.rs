@@ -1,9 +1,9 @@
 Value::Array(vec![
-    Value::Object(HashMap::from([(
+    Value::Object(Map::from_iter([(
         "data".into(),
         Value::Array(vec![Value::Integer(42.into()), Value::Integer(42.into())]),
     )])),
-    Value::Object(HashMap::from([(
+    Value::Object(Map::from_iter([(
         "data".into(),
         Value::Array(vec![
             Value::String("hello".into()),
@@ -1,4 +1,4 @@
-Value::Array(vec![Value::Object(HashMap::from([(
+Value::Array(vec![Value::Object(Map::from_iter([(
     "nested".into(),
-    Value::Object(HashMap::from([("deep".into(), Value::Integer(42.into()))])),
+    Value::Object(Map::from_iter([("deep".into(), Value::Integer(42.into()))])),
 )]))])
@@ -1,8 +1,8 @@
-Value::Object(HashMap::from([(
+Value::Object(Map::from_iter([(
     "outer".into(),
-    Value::Object(HashMap::from([(
+    Value::Object(Map::from_iter([(
         "inner".into(),
-        Value::Object(HashMap::from([(
+        Value::Object(Map::from_iter([(
             "text".into(),
             Value::String("Deep\n".into()),
         )])),
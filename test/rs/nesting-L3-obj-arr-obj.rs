@@ -1,11 +1,11 @@
-Value::Object(HashMap::from([(
+Value::Object(Map::from_iter([(
     "items".into(),
     Value::Array(vec![
-        Value::Object(HashMap::from([
+        Value::Object(Map::from_iter([
             ("name".into(), Value::String("hello".into())),
             ("value".into(), Value::Integer(42.into())),
         ])),
-        Value::Object(HashMap::from([
+        Value::Object(Map::from_iter([
             ("name".into(), Value::String("hello".into())),
             ("value".into(), Value::Integer(42.into())),
         ])),
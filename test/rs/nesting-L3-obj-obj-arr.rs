@@ -1,6 +1,6 @@
-Value::Object(HashMap::from([(
+Value::Object(Map::from_iter([(
     "outer".into(),
-    Value::Object(HashMap::from([(
+    Value::Object(Map::from_iter([(
         "inner".into(),
         Value::Array(vec![
             Value::Integer(42.into()),
@@ -1,8 +1,8 @@
-Value::Object(HashMap::from([(
+Value::Object(Map::from_iter([(
     "a".into(),
-    Value::Object(HashMap::from([(
+    Value::Object(Map::from_iter([(
         "b".into(),
-        Value::Object(HashMap::from([
+        Value::Object(Map::from_iter([
             ("c".into(), Value::Integer(42.into())),
             ("d".into(), Value::String("hello".into())),
         ])),
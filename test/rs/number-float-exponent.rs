@@ -1,4 +1,4 @@
-Value::Object(HashMap::from([
+Value::Object(Map::from_iter([
     ("leading-dot".into(), Value::Float(50.0)),
     ("negative-exp".into(), Value::Float(0.0000314)),
     ("no-decimal".into(), Value::Float(600000000000000000000000.0)),
@@ -1,10 +1,10 @@
-Value::Object(HashMap::from([(
+Value::Object(Map::from_iter([(
     "level1".into(),
-    Value::Object(HashMap::from([(
+    Value::Object(Map::from_iter([(
         "level2".into(),
-        Value::Object(HashMap::from([(
+        Value::Object(Map::from_iter([(
             "level3".into(),
-            Value::Object(HashMap::new()),
+            Value::Object(Map::new()),
         )])),
     )])),
 )]))
@@ -1 +1 @@
-Value::Object(HashMap::from([("*".into(), Value::Float(1.0))]))
+Value::Object(Map::from_iter([("*".into(), Value::Float(1.0))]))
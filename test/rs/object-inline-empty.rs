@@ -1,3 +1,3 @@
-Value::Object(HashMap::from([
-    ("empty".into(), Value::Object(HashMap::new())),
+Value::Object(Map::from_iter([
+    ("empty".into(), Value::Object(Map::new())),
 ]))
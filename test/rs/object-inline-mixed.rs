@@ -1,4 +1,4 @@
-Value::Object(HashMap::from([
+Value::Object(Map::from_iter([
     ("mood".into(), Value::String("depressed".into())),
     ("name".into(), Value::String("Marvin".into())),
 ]))
@@ -1,9 +1,9 @@
-Value::Object(HashMap::from([
+Value::Object(Map::from_iter([
     ("air".into(), Value::Array(vec![
         Value::String("canned".into()),
         Value::String("Perri-Air".into()),
     ])),
-    ("luggage".into(), Value::Object(HashMap::from([
+    ("luggage".into(), Value::Object(Map::from_iter([
         ("combination".into(), Value::Integer(12345.into())),
     ]))),
 ]))
@@ -1,4 +1,4 @@
-Value::Object(HashMap::from([(
+Value::Object(Map::from_iter([(
     "key name".into(),
     Value::Integer(1.into()),
 )]))
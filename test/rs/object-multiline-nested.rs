@@ -1,5 +1,5 @@
-Value::Object(HashMap::from([
-    ("parrot".into(), Value::Object(HashMap::from([
+Value::Object(Map::from_iter([
+    ("parrot".into(), Value::Object(Map::from_iter([
         ("plumage".into(), Value::String("beautiful".into())),
         ("status".into(), Value::String("pining for the fjords".into())),
     ]))),
@@ -1,4 +1,4 @@
-Value::Object(HashMap::from([
+Value::Object(Map::from_iter([
     ("answer".into(), Value::Integer(42.into())),
     ("error".into(), Value::Integer(404.into())),
 ]))
@@ -1,7 +1,7 @@
-Value::Object(HashMap::from([(
+Value::Object(Map::from_iter([(
     "outer".into(),
-    Value::Object(HashMap::from([(
+    Value::Object(Map::from_iter([(
         "inner".into(),
-        Value::Object(HashMap::new()),
+        Value::Object(Map::new()),
     )])),
 )]))
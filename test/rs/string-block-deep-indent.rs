@@ -1,4 +1,4 @@
-Value::Object(HashMap::from([(
+Value::Object(Map::from_iter([(
     "message".into(),
     Value::String("Line 1\n  Indented\nLine 2\n".into()),
 )]))
@@ -1,8 +1,8 @@
-Value::Object(HashMap::from([(
+Value::Object(Map::from_iter([(
     "level1".into(),
-    Value::Object(HashMap::from([(
+    Value::Object(Map::from_iter([(
         "level2".into(),
-        Value::Object(HashMap::from([(
+        Value::Object(Map::from_iter([(
             "text".into(),
             Value::String("Hello\nWorld\n".into()),
         )])),
@@ -1,5 +1,5 @@
-Value::Object(HashMap::from([
-    ("parrot".into(), Value::Object(HashMap::from([
+Value::Object(Map::from_iter([
+    ("parrot".into(), Value::Object(Map::from_iter([
         ("condition".into(), Value::String("No, no, it's just resting!\n".into())),
         ("remarks".into(), Value::Array(vec![
             Value::String("Remarkable bird, the Norwegian Blue.\nBeautiful plumage, innit?\n".into()),
@@ -1,3 +1,3 @@
-Value::Object(HashMap::from([
+Value::Object(Map::from_iter([
     ("message".into(), Value::String("It's not pining!\n\nIt's passed on! This parrot is no more!\n".into())),
 ]))
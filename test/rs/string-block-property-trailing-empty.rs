@@ -1,4 +1,4 @@
-Value::Object(HashMap::from([
+Value::Object(Map::from_iter([
     ("message".into(), Value::String("By Grabthar's hammer... what a savings.\n".into())),
     ("next".into(), Value::Integer(1.into())),
 ]))
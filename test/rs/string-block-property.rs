@@ -1,3 +1,3 @@
-Value::Object(HashMap::from([
+Value::Object(Map::from_iter([
     ("message".into(), Value::String("By Grabthar's hammer, we live to tell the tale.\n".into())),
 ]))
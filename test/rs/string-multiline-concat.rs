@@ -1,3 +1,3 @@
-Value::Object(HashMap::from([
+Value::Object(Map::from_iter([
     ("confession".into(), Value::String("I'm not dead yet. I feel happy!".into())),
 ]))